@@ -1,25 +1,52 @@
 from __future__ import annotations
 
-from typing import List
+import asyncio
+import json
+from typing import AsyncGenerator, List
 from uuid import UUID
 
-from fastapi import APIRouter, Depends, HTTPException, Query, Response, status
+from fastapi import APIRouter, Depends, HTTPException, Query, Request, Response, status
+from fastapi.responses import StreamingResponse
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.core.database import get_db
 from app.deps.auth import get_current_user
 from app.schemas.auth import MessageResponse
 from app.schemas.mcp_market import (
+    McpMarketToolCreateRequest,
     McpMarketToolDetail,
     McpMarketToolSummary,
     McpSubscriptionCreateRequest,
     McpSubscriptionItem,
     McpToolCategory,
+    McpToolHealthReportBatch,
+    McpToolHealthReportResponse,
 )
 from app.services.mcp.market_service import McpMarketService
+from app.services.mcp.subscription_notifier import subscription_notifier
 
 router = APIRouter(prefix="/mcp", tags=["MCP"])
 
+_KEEPALIVE_INTERVAL_SECONDS = 30.0
+
+
+def _format_sse_event(event: str, data: dict) -> bytes:
+    payload = json.dumps(data, ensure_ascii=False, default=str)
+    return f"event: {event}\ndata: {payload}\n\n".encode("utf-8")
+
+
+def _subscription_item(sub, tool) -> McpSubscriptionItem:
+    return McpSubscriptionItem(
+        id=sub.id,
+        created_at=sub.created_at,
+        updated_at=sub.updated_at,
+        user_id=sub.user_id,
+        market_tool_id=sub.market_tool_id,
+        alias=sub.alias,
+        config_hash_snapshot=sub.config_hash_snapshot,
+        tool=tool,
+    )
+
 
 @router.get("/market-tools", response_model=List[McpMarketToolSummary])
 async def list_market_tools(
@@ -32,6 +59,18 @@ async def list_market_tools(
     return await svc.list_market_tools(category=category, search=q)
 
 
+@router.post("/market-tools", response_model=McpMarketToolDetail, status_code=status.HTTP_201_CREATED)
+async def publish_market_tool(
+    payload: McpMarketToolCreateRequest,
+    db: AsyncSession = Depends(get_db),
+    user=Depends(get_current_user),
+):
+    svc = McpMarketService(db)
+    tool = await svc.publish_market_tool(user=user, payload=payload)
+    await db.commit()
+    return tool
+
+
 @router.get("/market-tools/{tool_id}", response_model=McpMarketToolDetail)
 async def get_market_tool(
     tool_id: UUID,
@@ -45,6 +84,28 @@ async def get_market_tool(
     return tool
 
 
+@router.post(
+    "/telemetry/health",
+    response_model=McpToolHealthReportResponse,
+    status_code=status.HTTP_202_ACCEPTED,
+)
+async def report_tool_health(
+    payload: McpToolHealthReportBatch,
+    req: Request,
+    db: AsyncSession = Depends(get_db),
+):
+    """桌面端在用户开启健康上报后匿名调用，不需要登录；按来源 IP 限流。"""
+    client_ip = (
+        req.headers.get("x-forwarded-for", "").split(",")[0].strip()
+        or (req.client.host if req.client else None)
+    )
+    svc = McpMarketService(db)
+    await svc.check_health_report_rate_limit(client_ip)
+    accepted = await svc.record_health_reports(payload)
+    await db.commit()
+    return McpToolHealthReportResponse(accepted=accepted)
+
+
 @router.get("/subscriptions", response_model=List[McpSubscriptionItem])
 async def list_subscriptions(
     db: AsyncSession = Depends(get_db),
@@ -52,19 +113,33 @@ async def list_subscriptions(
 ):
     svc = McpMarketService(db)
     items = await svc.list_subscriptions(user_id=user.id)
-    return [
-        McpSubscriptionItem(
-            id=sub.id,
-            created_at=sub.created_at,
-            updated_at=sub.updated_at,
-            user_id=sub.user_id,
-            market_tool_id=sub.market_tool_id,
-            alias=sub.alias,
-            config_hash_snapshot=sub.config_hash_snapshot,
-            tool=tool,
-        )
-        for sub, tool in items
-    ]
+    return [_subscription_item(sub, tool) for sub, tool in items]
+
+
+@router.get("/subscriptions/events")
+async def stream_subscription_events(
+    request: Request,
+    user=Depends(get_current_user),
+):
+    """
+    推送订阅变更（SSE）：订阅工具的安装清单发生变化时发送 subscription_updated 事件。
+    客户端连接后应先全量同步一次，本流只推送此后的增量变化。
+    变更检测由进程内共享的 subscription_notifier 完成，连接本身不查询数据库。
+    """
+    user_id = user.id
+
+    async def gen() -> AsyncGenerator[bytes, None]:
+        async with subscription_notifier.listen(user_id) as changes:
+            while not await request.is_disconnected():
+                try:
+                    sub, tool = await asyncio.wait_for(changes.get(), timeout=_KEEPALIVE_INTERVAL_SECONDS)
+                except asyncio.TimeoutError:
+                    yield b": keep-alive\n\n"
+                    continue
+                item = _subscription_item(sub, tool)
+                yield _format_sse_event("subscription_updated", item.model_dump(mode="json"))
+
+    return StreamingResponse(gen(), media_type="text/event-stream")
 
 
 @router.post("/subscriptions", response_model=McpSubscriptionItem, status_code=status.HTTP_201_CREATED)
@@ -85,16 +160,7 @@ async def create_subscription(
         await db.commit()
     else:
         response.status_code = status.HTTP_200_OK
-    return McpSubscriptionItem(
-        id=subscription.id,
-        created_at=subscription.created_at,
-        updated_at=subscription.updated_at,
-        user_id=subscription.user_id,
-        market_tool_id=subscription.market_tool_id,
-        alias=subscription.alias,
-        config_hash_snapshot=subscription.config_hash_snapshot,
-        tool=tool,
-    )
+    return _subscription_item(subscription, tool)
 
 
 @router.delete("/subscriptions/{tool_id}", response_model=MessageResponse)
@@ -174,6 +174,10 @@ class CacheKeys:
     def rate_limit_global(cls, route: str) -> str:
         return f"{cls.prefix}:rl:global:{route}"
 
+    @classmethod
+    def mcp_health_report_ip(cls, ip: str) -> str:
+        return f"{cls.prefix}:rl:mcp_health:{ip}"
+
     # ===== Circuit Breaker =====
     @classmethod
     def circuit_breaker(cls, upstream_host: str) -> str:
@@ -217,6 +217,8 @@ class Settings(BaseSettings):
     PASSWORD_MIN_LENGTH: int = 8
     LOGIN_RATE_LIMIT_ATTEMPTS: int = 5
     LOGIN_RATE_LIMIT_WINDOW: int = 600  # 秒
+    MCP_HEALTH_REPORT_RATE_LIMIT: int = 12  # 每个 IP 在窗口内可上报的批次数
+    MCP_HEALTH_REPORT_RATE_WINDOW: int = 3600  # 秒
     VERIFICATION_CODE_TTL_SECONDS: int = 600
     VERIFICATION_CODE_MAX_ATTEMPTS: int = 3
 
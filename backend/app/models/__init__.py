@@ -67,7 +67,7 @@ from .identity import Identity
 from .registration_window import RegistrationWindow, RegistrationWindowStatus
 from .invite_code import InviteCode, InviteCodeStatus
 from .user import Permission, Role, RolePermission, User, UserRole
-from .mcp_market import McpMarketTool, McpToolCategory, UserMcpSubscription
+from .mcp_market import McpMarketTool, McpToolCategory, McpToolHealthReport, UserMcpSubscription
 from .user_mcp_server import UserMcpServer
 from .user_mcp_source import UserMcpSource
 from .spec_agent import SpecPlan, SpecExecutionLog, SpecWorkerSession
@@ -146,6 +146,7 @@ __all__ = [
     "BridgeAgentToken",
     "McpMarketTool",
     "McpToolCategory",
+    "McpToolHealthReport",
     "UserMcpSubscription",
     "UserMcpServer",
     "UserMcpSource",
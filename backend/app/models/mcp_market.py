@@ -136,3 +136,44 @@ class UserMcpSubscription(Base, UUIDPrimaryKeyMixin, TimestampMixin):
 
     def __repr__(self) -> str:
         return f"<UserMcpSubscription(user={self.user_id}, tool={self.market_tool_id})>"
+
+
+class McpToolHealthReport(Base, UUIDPrimaryKeyMixin, TimestampMixin):
+    """桌面端匿名上报的订阅工具健康数据，不关联用户。"""
+
+    __tablename__ = "mcp_tool_health_report"
+    __table_args__ = (
+        Index("ix_mcp_tool_health_report_tool", "market_tool_id"),
+    )
+
+    market_tool_id: Mapped[uuid.UUID] = mapped_column(
+        SA_UUID(as_uuid=True),
+        ForeignKey("mcp_market_tool.id", ondelete="CASCADE"),
+        nullable=False,
+        comment="市场工具 ID",
+    )
+    status: Mapped[str] = mapped_column(
+        String(20),
+        nullable=False,
+        comment="上报时的本地运行状态",
+    )
+    crash_count: Mapped[int] = mapped_column(
+        Integer,
+        nullable=False,
+        default=0,
+        server_default="0",
+        comment="距上次上报的崩溃次数",
+    )
+    ping_ms: Mapped[int | None] = mapped_column(
+        Integer,
+        nullable=True,
+        comment="最近一次探活耗时 (ms)",
+    )
+    client_version: Mapped[str] = mapped_column(
+        String(40),
+        nullable=False,
+        comment="桌面端版本",
+    )
+
+    def __repr__(self) -> str:
+        return f"<McpToolHealthReport(tool={self.market_tool_id}, status={self.status})>"
@@ -5,7 +5,7 @@ from uuid import UUID
 from sqlalchemy import delete, select
 from sqlalchemy.ext.asyncio import AsyncSession
 
-from app.models.mcp_market import McpMarketTool, UserMcpSubscription, McpToolCategory
+from app.models.mcp_market import McpMarketTool, McpToolHealthReport, UserMcpSubscription, McpToolCategory
 
 
 class McpMarketRepository:
@@ -40,6 +40,46 @@ class McpMarketRepository:
         result = await self.session.execute(stmt)
         return result.scalar_one_or_none()
 
+    async def create_market_tool(
+        self,
+        *,
+        identifier: str,
+        name: str,
+        description: str,
+        avatar_url: str | None,
+        category: McpToolCategory,
+        tags: list[str],
+        author: str,
+        install_manifest: dict,
+    ) -> McpMarketTool:
+        tool = McpMarketTool(
+            identifier=identifier,
+            name=name,
+            description=description,
+            avatar_url=avatar_url,
+            category=category,
+            tags=tags,
+            author=author,
+            is_official=False,
+            download_count=0,
+            install_manifest=install_manifest,
+        )
+        self.session.add(tool)
+        await self.session.flush()
+        await self.session.refresh(tool)
+        return tool
+
+    async def existing_market_tool_ids(self, tool_ids: set[UUID]) -> set[UUID]:
+        if not tool_ids:
+            return set()
+        stmt = select(McpMarketTool.id).where(McpMarketTool.id.in_(tool_ids))
+        result = await self.session.execute(stmt)
+        return set(result.scalars().all())
+
+    async def create_health_reports(self, reports: list[McpToolHealthReport]) -> None:
+        self.session.add_all(reports)
+        await self.session.flush()
+
     async def list_subscriptions(self, user_id: UUID) -> list[tuple[UserMcpSubscription, McpMarketTool]]:
         stmt = (
             select(UserMcpSubscription, McpMarketTool)
@@ -50,6 +90,17 @@ class McpMarketRepository:
         result = await self.session.execute(stmt)
         return list(result.all())
 
+    async def list_subscriptions_for_users(
+        self, user_ids: list[UUID]
+    ) -> list[tuple[UserMcpSubscription, McpMarketTool]]:
+        stmt = (
+            select(UserMcpSubscription, McpMarketTool)
+            .join(McpMarketTool, McpMarketTool.id == UserMcpSubscription.market_tool_id)
+            .where(UserMcpSubscription.user_id.in_(user_ids))
+        )
+        result = await self.session.execute(stmt)
+        return list(result.all())
+
     async def get_subscription(
         self,
         *,
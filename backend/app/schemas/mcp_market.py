@@ -55,6 +55,44 @@ class McpMarketToolDetail(McpMarketToolSummary):
     install_manifest: McpInstallManifest
 
 
+class McpMarketToolCreateRequest(BaseSchema):
+    identifier: str = Field(..., min_length=1, max_length=120)
+    name: str = Field(..., min_length=1, max_length=200)
+    description: str = Field("", max_length=2000)
+    category: McpToolCategory = McpToolCategory.OTHER
+    tags: list[str] = Field(default_factory=list)
+    avatar_url: str | None = Field(None, max_length=512)
+    install_manifest: McpInstallManifest
+
+
+class McpToolHealthStatus(str, Enum):
+    PENDING = "pending"
+    STOPPED = "stopped"
+    STARTING = "starting"
+    HEALTHY = "healthy"
+    DEGRADED = "degraded"
+    CRASHED = "crashed"
+    UPDATING = "updating"
+    ERROR = "error"
+    ORPHANED = "orphaned"
+
+
+class McpToolHealthReportItem(BaseSchema):
+    market_tool_id: UUID
+    status: McpToolHealthStatus
+    crash_count: int = Field(0, ge=0, le=1000)
+    ping_ms: int | None = Field(None, ge=0, le=600_000)
+
+
+class McpToolHealthReportBatch(BaseSchema):
+    client_version: str = Field(..., min_length=1, max_length=40)
+    reports: list[McpToolHealthReportItem] = Field(..., min_length=1, max_length=100)
+
+
+class McpToolHealthReportResponse(BaseSchema):
+    accepted: int
+
+
 class McpSubscriptionCreateRequest(BaseSchema):
     tool_id: UUID
     alias: str | None = Field(None, max_length=100)
@@ -65,4 +103,4 @@ class McpSubscriptionItem(IDSchema, TimestampSchema):
     market_tool_id: UUID
     alias: str | None = None
     config_hash_snapshot: str | None = None
-    tool: McpMarketToolSummary
+    tool: McpMarketToolDetail
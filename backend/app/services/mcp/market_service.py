@@ -7,8 +7,13 @@ from uuid import UUID
 from fastapi import HTTPException, status
 from sqlalchemy.ext.asyncio import AsyncSession
 
-from app.models.mcp_market import McpMarketTool, UserMcpSubscription, McpToolCategory
+from app.core.cache import cache
+from app.core.cache_keys import CacheKeys
+from app.core.config import settings
+from app.models.mcp_market import McpMarketTool, McpToolHealthReport, UserMcpSubscription, McpToolCategory
+from app.models.user import User
 from app.repositories.mcp_market_repository import McpMarketRepository
+from app.schemas.mcp_market import McpMarketToolCreateRequest, McpToolHealthReportBatch
 
 
 class McpMarketService:
@@ -27,9 +32,71 @@ class McpMarketService:
     async def get_market_tool(self, tool_id: UUID) -> McpMarketTool | None:
         return await self.repo.get_market_tool(tool_id)
 
+    async def publish_market_tool(
+        self,
+        *,
+        user: User,
+        payload: McpMarketToolCreateRequest,
+    ) -> McpMarketTool:
+        """用户发布的工具一律为非官方，作者取发布者的展示名（缺省为邮箱）。"""
+        identifier = payload.identifier.strip()
+        if await self.repo.get_market_tool_by_identifier(identifier):
+            raise HTTPException(status_code=status.HTTP_409_CONFLICT, detail="identifier already exists")
+        # 不在 Service 层直接 commit，由上层管理事务
+        return await self.repo.create_market_tool(
+            identifier=identifier,
+            name=payload.name,
+            description=payload.description,
+            avatar_url=payload.avatar_url,
+            category=payload.category,
+            tags=payload.tags,
+            author=user.username or user.email,
+            install_manifest=payload.install_manifest.model_dump(mode="json"),
+        )
+
+    async def check_health_report_rate_limit(self, client_ip: str | None) -> None:
+        """匿名健康上报按 IP 限流，避免刷量影响市场健康统计。"""
+        if not client_ip:
+            return
+        count = await cache.incr(
+            CacheKeys.mcp_health_report_ip(client_ip),
+            ttl=settings.MCP_HEALTH_REPORT_RATE_WINDOW,
+        )
+        if count > settings.MCP_HEALTH_REPORT_RATE_LIMIT:
+            raise HTTPException(
+                status_code=status.HTTP_429_TOO_MANY_REQUESTS,
+                detail="Too many health reports, please try again later",
+            )
+
+    async def record_health_reports(self, payload: McpToolHealthReportBatch) -> int:
+        """匿名健康上报：同一批次内每个工具只计一条（取最后一条），忽略市场中不存在的工具，返回实际写入条数。"""
+        latest = {report.market_tool_id: report for report in payload.reports}
+        known = await self.repo.existing_market_tool_ids(set(latest))
+        reports = [
+            McpToolHealthReport(
+                market_tool_id=report.market_tool_id,
+                status=report.status.value,
+                crash_count=report.crash_count,
+                ping_ms=report.ping_ms,
+                client_version=payload.client_version,
+            )
+            for report in latest.values()
+            if report.market_tool_id in known
+        ]
+        await self.repo.create_health_reports(reports)
+        return len(reports)
+
     async def list_subscriptions(self, user_id: UUID) -> list[tuple[UserMcpSubscription, McpMarketTool]]:
         return await self.repo.list_subscriptions(user_id)
 
+    async def list_subscriptions_for_users(
+        self, user_ids: list[UUID]
+    ) -> list[tuple[UserMcpSubscription, McpMarketTool]]:
+        """一次查询多个用户的订阅，供订阅变更推送共享轮询使用。"""
+        if not user_ids:
+            return []
+        return await self.repo.list_subscriptions_for_users(user_ids)
+
     async def subscribe(
         self,
         *,
@@ -45,7 +112,7 @@ class McpMarketService:
         if existing:
             return existing, tool, False
 
-        manifest_hash = self._hash_manifest(tool.install_manifest or {})
+        manifest_hash = self.hash_manifest(tool.install_manifest or {})
         subscription = await self.repo.create_subscription(
             user_id=user_id,
             market_tool_id=tool_id,
@@ -62,6 +129,7 @@ class McpMarketService:
         return deleted
 
     @staticmethod
-    def _hash_manifest(manifest: dict) -> str:
+    def hash_manifest(manifest: dict) -> str:
+        """安装清单的稳定哈希，订阅时记入 config_hash_snapshot，也用于检测清单变更。"""
         payload = json.dumps(manifest, sort_keys=True, separators=(",", ":"), ensure_ascii=False)
         return hashlib.sha256(payload.encode("utf-8")).hexdigest()
@@ -0,0 +1,106 @@
+"""
+订阅变更通知
+
+同一进程内所有 /mcp/subscriptions/events 连接共享一个轮询任务：每轮用一次查询取出
+所有在线用户的订阅，比较安装清单哈希，把变化分发给对应用户的连接。
+清单没有变化时轮询间隔逐步加倍（上限 max_interval），有变化或新用户连接后恢复为 min_interval。
+没有连接时轮询任务自动退出。
+"""
+
+from __future__ import annotations
+
+import asyncio
+from collections.abc import AsyncIterator, Callable
+from contextlib import asynccontextmanager
+from uuid import UUID
+
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.core.database import AsyncSessionLocal
+from app.core.logging import logger
+from app.models.mcp_market import McpMarketTool, UserMcpSubscription
+from app.services.mcp.market_service import McpMarketService
+
+SubscriptionChange = tuple[UserMcpSubscription, McpMarketTool]
+
+
+class SubscriptionChangeNotifier:
+    def __init__(
+        self,
+        session_factory: Callable[[], AsyncSession] = AsyncSessionLocal,
+        *,
+        min_interval: float = 5.0,
+        max_interval: float = 60.0,
+    ):
+        self._session_factory = session_factory
+        self._min_interval = min_interval
+        self._max_interval = max_interval
+        self._listeners: dict[UUID, set[asyncio.Queue[SubscriptionChange]]] = {}
+        # user_id -> {market_tool_id: manifest_hash}
+        self._known: dict[UUID, dict[UUID, str]] = {}
+        self._interval = min_interval
+        self._task: asyncio.Task | None = None
+
+    @asynccontextmanager
+    async def listen(self, user_id: UUID) -> AsyncIterator[asyncio.Queue[SubscriptionChange]]:
+        """在退出前持续接收该用户订阅工具的清单变化；进入时的清单作为基线，不推送。"""
+        queue: asyncio.Queue[SubscriptionChange] = asyncio.Queue()
+        if user_id not in self._listeners:
+            rows = await self._fetch([user_id])
+            self._known[user_id] = {sub.market_tool_id: digest for sub, _, digest in rows}
+            self._interval = self._min_interval
+        self._listeners.setdefault(user_id, set()).add(queue)
+        if self._task is None or self._task.done():
+            self._task = asyncio.create_task(self._run())
+        try:
+            yield queue
+        finally:
+            queues = self._listeners.get(user_id)
+            if queues is not None:
+                queues.discard(queue)
+                if not queues:
+                    del self._listeners[user_id]
+                    self._known.pop(user_id, None)
+
+    async def _run(self) -> None:
+        while self._listeners:
+            await asyncio.sleep(self._interval)
+            try:
+                changed = await self.poll()
+            except Exception:
+                logger.exception("mcp_subscription_poll_failed")
+                changed = False
+            self._interval = self._min_interval if changed else min(self._interval * 2, self._max_interval)
+
+    async def poll(self) -> bool:
+        """检查一轮，返回是否有变化被推送。"""
+        user_ids = list(self._listeners)
+        if not user_ids:
+            return False
+        current: dict[UUID, dict[UUID, str]] = {user_id: {} for user_id in user_ids}
+        changed = False
+        for sub, tool, digest in await self._fetch(user_ids):
+            if sub.user_id not in current:
+                continue
+            current[sub.user_id][sub.market_tool_id] = digest
+            known = self._known.get(sub.user_id)
+            if known is not None and known.get(sub.market_tool_id) != digest:
+                for queue in self._listeners.get(sub.user_id, ()):
+                    queue.put_nowait((sub, tool))
+                changed = True
+        for user_id, hashes in current.items():
+            # 轮询期间断开的用户不再记录
+            if user_id in self._listeners:
+                self._known[user_id] = hashes
+        return changed
+
+    async def _fetch(self, user_ids: list[UUID]) -> list[tuple[UserMcpSubscription, McpMarketTool, str]]:
+        async with self._session_factory() as session:
+            rows = await McpMarketService(session).list_subscriptions_for_users(user_ids)
+        return [
+            (sub, tool, McpMarketService.hash_manifest(tool.install_manifest or {}))
+            for sub, tool in rows
+        ]
+
+
+subscription_notifier = SubscriptionChangeNotifier()
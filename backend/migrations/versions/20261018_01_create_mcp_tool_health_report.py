@@ -0,0 +1,60 @@
+"""create mcp tool health report table
+
+Revision ID: 20261018_01_create_mcp_tool_health_report
+Revises: 6c16a8399765
+Create Date: 2026-10-18
+"""
+
+from typing import Sequence, Union
+
+from alembic import op
+import sqlalchemy as sa
+from sqlalchemy.dialects import postgresql
+
+
+revision: str = "20261018_01_create_mcp_tool_health_report"
+down_revision: Union[str, None] = "6c16a8399765"
+branch_labels: Union[str, Sequence[str], None] = None
+depends_on: Union[str, Sequence[str], None] = None
+
+
+def upgrade() -> None:
+    op.create_table(
+        "mcp_tool_health_report",
+        sa.Column("id", postgresql.UUID(as_uuid=True), primary_key=True, nullable=False),
+        sa.Column(
+            "market_tool_id",
+            postgresql.UUID(as_uuid=True),
+            sa.ForeignKey("mcp_market_tool.id", ondelete="CASCADE"),
+            nullable=False,
+            comment="市场工具 ID",
+        ),
+        sa.Column("status", sa.String(length=20), nullable=False, comment="上报时的本地运行状态"),
+        sa.Column(
+            "crash_count",
+            sa.Integer(),
+            nullable=False,
+            server_default="0",
+            comment="距上次上报的崩溃次数",
+        ),
+        sa.Column("ping_ms", sa.Integer(), nullable=True, comment="最近一次探活耗时 (ms)"),
+        sa.Column("client_version", sa.String(length=40), nullable=False, comment="桌面端版本"),
+        sa.Column(
+            "created_at",
+            sa.DateTime(timezone=True),
+            nullable=False,
+            server_default=sa.text("CURRENT_TIMESTAMP"),
+        ),
+        sa.Column(
+            "updated_at",
+            sa.DateTime(timezone=True),
+            nullable=False,
+            server_default=sa.text("CURRENT_TIMESTAMP"),
+        ),
+    )
+    op.create_index("ix_mcp_tool_health_report_tool", "mcp_tool_health_report", ["market_tool_id"])
+
+
+def downgrade() -> None:
+    op.drop_index("ix_mcp_tool_health_report_tool", table_name="mcp_tool_health_report")
+    op.drop_table("mcp_tool_health_report")
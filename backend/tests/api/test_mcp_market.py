@@ -1,11 +1,13 @@
 from __future__ import annotations
 
-from uuid import uuid4
+import asyncio
+from uuid import UUID, uuid4
 
 import pytest
 from httpx import AsyncClient
 
 from app.models.mcp_market import McpMarketTool, McpToolCategory
+from app.services.mcp.subscription_notifier import SubscriptionChangeNotifier
 
 
 async def _seed_market_tool(session) -> McpMarketTool:
@@ -73,6 +75,7 @@ async def test_subscribe_and_unsubscribe(client: AsyncClient, auth_tokens: dict,
     body = resp.json()
     assert body["market_tool_id"] == str(tool.id)
     assert body["config_hash_snapshot"]
+    assert body["tool"]["install_manifest"]["command"]
 
     resp_repeat = await client.post(
         "/api/v1/mcp/subscriptions",
@@ -101,3 +104,113 @@ async def test_subscribe_and_unsubscribe(client: AsyncClient, auth_tokens: dict,
     )
     assert list_resp.status_code == 200
     assert list_resp.json() == []
+
+
+@pytest.mark.asyncio
+async def test_notifier_pushes_manifest_changes(client: AsyncClient, auth_tokens: dict, AsyncSessionLocal) -> None:
+    async with AsyncSessionLocal() as session:
+        tool = await _seed_market_tool(session)
+    resp = await client.post(
+        "/api/v1/mcp/subscriptions",
+        json={"tool_id": str(tool.id)},
+        headers={"Authorization": f"Bearer {auth_tokens['access_token']}"},
+    )
+    assert resp.status_code == 201
+    user_id = UUID(resp.json()["user_id"])
+
+    notifier = SubscriptionChangeNotifier(AsyncSessionLocal, min_interval=0.01, max_interval=0.05)
+    async with notifier.listen(user_id) as changes:
+        async with AsyncSessionLocal() as session:
+            stored = await session.get(McpMarketTool, tool.id)
+            stored.install_manifest = {**stored.install_manifest, "args": ["-y", "brave-search@2"]}
+            await session.commit()
+
+        sub, changed_tool = await asyncio.wait_for(changes.get(), timeout=5)
+        assert sub.user_id == user_id
+        assert changed_tool.install_manifest["args"] == ["-y", "brave-search@2"]
+        assert changes.empty()
+
+    # 没有连接后共享轮询任务自行结束
+    await asyncio.wait_for(notifier._task, timeout=5)
+
+
+@pytest.mark.asyncio
+async def test_publish_market_tool(client: AsyncClient, auth_tokens: dict) -> None:
+    headers = {"Authorization": f"Bearer {auth_tokens['access_token']}"}
+    payload = {
+        "identifier": f"community/fs-{uuid4().hex[:8]}",
+        "name": "Filesystem",
+        "description": "Local filesystem access",
+        "category": "developer",
+        "tags": ["files"],
+        "install_manifest": {
+            "runtime": "node",
+            "command": "npx",
+            "args": ["-y", "@modelcontextprotocol/server-filesystem"],
+            "env_config": [{"key": "ROOT", "label": "ROOT", "required": True, "secret": False, "default": "/tmp"}],
+        },
+    }
+    resp = await client.post("/api/v1/mcp/market-tools", json=payload, headers=headers)
+    assert resp.status_code == 201
+    body = resp.json()
+    assert body["id"]
+    assert body["is_official"] is False
+    assert body["install_manifest"]["env_config"][0]["default"] == "/tmp"
+
+    detail = await client.get(f"/api/v1/mcp/market-tools/{body['id']}", headers=headers)
+    assert detail.status_code == 200
+    assert detail.json()["identifier"] == payload["identifier"]
+
+    duplicate = await client.post("/api/v1/mcp/market-tools", json=payload, headers=headers)
+    assert duplicate.status_code == 409
+
+    invalid = dict(payload, identifier=f"community/x-{uuid4().hex[:8]}", install_manifest={"runtime": "node"})
+    resp = await client.post("/api/v1/mcp/market-tools", json=invalid, headers=headers)
+    assert resp.status_code == 422
+
+
+@pytest.mark.asyncio
+async def test_report_tool_health_without_login(client: AsyncClient, AsyncSessionLocal) -> None:
+    async with AsyncSessionLocal() as session:
+        tool = await _seed_market_tool(session)
+
+    payload = {
+        "client_version": "0.1.0",
+        "reports": [
+            {"market_tool_id": str(tool.id), "status": "healthy", "crash_count": 2, "ping_ms": 40},
+            {"market_tool_id": str(uuid4()), "status": "crashed", "crash_count": 1, "ping_ms": None},
+        ],
+    }
+    resp = await client.post("/api/v1/mcp/telemetry/health", json=payload)
+    assert resp.status_code == 202
+    assert resp.json() == {"accepted": 1}
+
+    invalid = dict(payload, reports=[{"market_tool_id": str(tool.id), "status": "sleeping"}])
+    resp = await client.post("/api/v1/mcp/telemetry/health", json=invalid)
+    assert resp.status_code == 422
+
+
+@pytest.mark.asyncio
+async def test_report_tool_health_is_rate_limited(client: AsyncClient, AsyncSessionLocal, monkeypatch) -> None:
+    from app.core.config import settings
+
+    monkeypatch.setattr(settings, "MCP_HEALTH_REPORT_RATE_LIMIT", 2)
+    async with AsyncSessionLocal() as session:
+        tool = await _seed_market_tool(session)
+
+    report = {"market_tool_id": str(tool.id), "status": "crashed", "crash_count": 1}
+    payload = {"client_version": "0.1.0", "reports": [report, report, report]}
+    headers = {"x-forwarded-for": "203.0.113.9"}
+    for _ in range(2):
+        resp = await client.post("/api/v1/mcp/telemetry/health", json=payload, headers=headers)
+        assert resp.status_code == 202
+        # 同一批次内重复的工具只计一条
+        assert resp.json() == {"accepted": 1}
+
+    resp = await client.post("/api/v1/mcp/telemetry/health", json=payload, headers=headers)
+    assert resp.status_code == 429
+
+    other = await client.post(
+        "/api/v1/mcp/telemetry/health", json=payload, headers={"x-forwarded-for": "203.0.113.10"}
+    )
+    assert other.status_code == 202
@@ -0,0 +1,84 @@
+"use client"
+
+import { Download, ShieldAlert } from "lucide-react"
+import { useTranslations } from "next-intl"
+import { Button } from "@/components/ui/button"
+import { Badge } from "@/components/ui/badge"
+import {
+  Sheet,
+  SheetContent,
+  SheetDescription,
+  SheetHeader,
+  SheetTitle,
+  SheetFooter,
+} from "@/components/ui/sheet"
+import { Alert, AlertDescription } from "@/components/ui/alert"
+import { McpInstallRequest } from "@/types/mcp"
+import { ToolRunPreview } from "./tool-run-preview"
+
+interface InstallRequestDialogProps {
+  request: McpInstallRequest | null
+  onConfirm: (request: McpInstallRequest) => void
+  onDismiss: (request: McpInstallRequest) => void
+}
+
+export function InstallRequestDialog({ request, onConfirm, onDismiss }: InstallRequestDialogProps) {
+  const t = useTranslations("mcp")
+  if (!request) return null
+
+  return (
+    <Sheet open onOpenChange={(open) => !open && onDismiss(request)}>
+      <SheetContent side="right" className="w-full sm:max-w-xl">
+        <SheetHeader>
+          <SheetTitle className="flex items-center gap-2">
+            <Download size={20} /> {t("install.title")}
+          </SheetTitle>
+          <SheetDescription>
+            {t("install.description", { source: request.source_name })}
+          </SheetDescription>
+        </SheetHeader>
+
+        <div className="space-y-4 my-4">
+          <Badge variant="outline">{t("install.trust", { level: request.trust_level })}</Badge>
+
+          {request.payload.kind === "market" ? (
+            <div className="text-sm text-gray-700">
+              {t("install.market", { id: request.payload.market_tool_id })}
+            </div>
+          ) : (
+            <div className="bg-gray-50 p-4 rounded-lg border border-gray-200">
+              <div className="text-sm font-medium text-gray-700 mb-3">{t("install.servers")}</div>
+              <div className="font-mono text-xs text-gray-600 bg-white p-3 rounded border border-gray-100 overflow-x-auto space-y-1">
+                {request.servers.map((server) => (
+                  <div key={server.name}>
+                    <div className="text-gray-400 select-none">{server.name}</div>
+                    <ToolRunPreview preview={server} />
+                  </div>
+                ))}
+              </div>
+            </div>
+          )}
+
+          {request.requires_approval && (
+            <Alert className="bg-orange-50 text-orange-800 border-orange-200">
+              <ShieldAlert className="h-4 w-4 text-orange-600" />
+              <AlertDescription>{t("install.approvalRequired")}</AlertDescription>
+            </Alert>
+          )}
+        </div>
+
+        <SheetFooter className="gap-2 sm:gap-0">
+          <Button variant="outline" onClick={() => onDismiss(request)}>
+            {t("install.cancel")}
+          </Button>
+          <Button
+            className="bg-black text-white hover:bg-gray-800"
+            onClick={() => onConfirm(request)}
+          >
+            {t("install.confirm")}
+          </Button>
+        </SheetFooter>
+      </SheetContent>
+    </Sheet>
+  )
+}
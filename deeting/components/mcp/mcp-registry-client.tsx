@@ -8,7 +8,7 @@ import { listen, UnlistenFn } from "@tauri-apps/api/event"
 import { RegistryHeader } from "./registry-header"
 import { SupplyChainSection } from "./supply-chain-section"
 import { RuntimeGridSection } from "./runtime-grid-section"
-import { MCPLogEntry, MCPSource, MCPTool, MCPToolConflict, MCPEnvConfigItem, MCPToolStatus, McpSourceRecord, McpToolRecord } from "@/types/mcp"
+import { MCPLogEntry, MCPSource, MCPTool, MCPToolConflict, MCPEnvConfigItem, MCPToolStatus, McpSourceRecord, McpToolRecord, McpToolStatusEvent, McpInstallRequest, McpToolApprovalRequest, McpSyncSchedulerStatus, McpLogExportFormat, McpSyncChanges, McpSyncStatusEvent } from "@/types/mcp"
 import { useMcpServers } from "@/lib/swr/use-mcp-servers"
 import { useMcpSources } from "@/lib/swr/use-mcp-sources"
 import { useMcpTools, type McpServerToolRecord } from "@/lib/swr/use-mcp-tools"
@@ -16,9 +16,12 @@ import { useMcpToolToggle } from "@/lib/swr/use-mcp-tool-toggle"
 import { type McpServer, type McpServerUpdateRequest, type McpSource } from "@/lib/api/mcp"
 import { useAuthStore } from "@/store/auth-store"
 import { useNotifications } from "@/components/contexts/notification-context"
+import { formatCommandError, isCommandError } from "@/lib/platform/command-error"
 
 const ServerLogsSheet = dynamic(() => import("./server-logs-sheet").then(mod => mod.ServerLogsSheet), { ssr: false })
 const ConflictResolutionDialog = dynamic(() => import("./conflict-resolution-dialog").then(mod => mod.ConflictResolutionDialog), { ssr: false })
+const InstallRequestDialog = dynamic(() => import("./install-request-dialog").then(mod => mod.InstallRequestDialog), { ssr: false })
+const ToolApprovalDialog = dynamic(() => import("./tool-approval-dialog").then(mod => mod.ToolApprovalDialog), { ssr: false })
 const EditServerSheet = dynamic(() => import("./edit-server-sheet").then(mod => mod.EditServerSheet), { ssr: false })
 
 interface MCPRegistryClientProps {
@@ -27,6 +30,8 @@ interface MCPRegistryClientProps {
 }
 
 const MAX_LOG_LINES = 1000
+/** 打开日志面板时先加载的行数，更早的历史按需向前翻页 */
+const LOG_PAGE_SIZE = 100
 
 const parseEnvConfig = (configJson: string): MCPEnvConfigItem[] => {
   try {
@@ -83,6 +88,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
   const [tools, setTools] = useState<MCPTool[]>(initialTools)
   const [sources, setSources] = useState<MCPSource[]>(initialSources)
   const [logsByTool, setLogsByTool] = useState<Record<string, MCPLogEntry[]>>({})
+  const [hasOlderLogs, setHasOlderLogs] = useState<Record<string, boolean>>({})
   const [selectedTool, setSelectedTool] = useState<MCPTool | null>(null)
   const [logsOpen, setLogsOpen] = useState(false)
   const [conflictTool, setConflictTool] = useState<MCPTool | null>(null)
@@ -92,8 +98,12 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
   const [syncingServerIds, setSyncingServerIds] = useState<Record<string, boolean>>({})
   const [editServer, setEditServer] = useState<McpServer | null>(null)
   const [editServerOpen, setEditServerOpen] = useState(false)
+  const [installRequests, setInstallRequests] = useState<McpInstallRequest[]>([])
+  const [approvalRequests, setApprovalRequests] = useState<McpToolApprovalRequest[]>([])
+  const [schedulerStatus, setSchedulerStatus] = useState<McpSyncSchedulerStatus | null>(null)
 
   const logListeners = useRef<Record<string, UnlistenFn>>({})
+  const toolsRef = useRef(tools)
   const autoSyncRef = useRef(false)
   const initialRefreshRef = useRef(false)
 
@@ -269,12 +279,32 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       addNotification({
         type: "error",
         title: t("toast.loadFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
     }
   }, [addNotification, isTauri, mapSource, mapTool, refreshServers, refreshSources, refreshTools, t])
 
+  // Patches the changed tools in place instead of reloading every tool;
+  // sources are re-read for their sync status.
+  const applySyncChanges = useCallback(async (changes: McpSyncChanges) => {
+    const changed = new Map(changes.tools.map((tool) => [tool.id, mapTool(tool)]))
+    setTools((prev) => {
+      const known = new Set(prev.map((item) => item.id))
+      const next = prev.map((item) => changed.get(item.id) ?? item)
+      changed.forEach((tool, id) => {
+        if (!known.has(id)) next.push(tool)
+      })
+      return next
+    })
+    try {
+      const sourceRecords = await invoke<McpSourceRecord[]>("list_mcp_sources")
+      setSources(sourceRecords.map(mapSource))
+    } catch {
+      // The next full refresh picks the sources up.
+    }
+  }, [mapSource, mapTool])
+
   useEffect(() => {
     if (initialRefreshRef.current) return
     initialRefreshRef.current = true
@@ -337,15 +367,15 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
     autoSyncRef.current = true
     const run = async () => {
       try {
-        const localSources = sources.filter((source) => source.type === "local")
-        await Promise.all(
-          localSources.map((source) =>
+        // Outcomes arrive as mcp-sync-status events.
+        sources
+          .filter((source) => source.type === "local")
+          .forEach((source) => {
             invoke("sync_mcp_source", {
               source_id: source.id,
               payload: { auth_token: null },
-            })
-          )
-        )
+            }).catch(() => undefined)
+          })
         if (accessToken) {
           await invoke("sync_cloud_subscriptions", { access_token: accessToken })
         }
@@ -354,7 +384,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
         addNotification({
           type: "error",
           title: t("toast.syncFailed"),
-          description: String(err),
+          description: formatCommandError(t, err),
           timestamp: Date.now(),
         })
       }
@@ -389,9 +419,13 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
 
     const setup = async () => {
       try {
-        const entries = await invoke<MCPLogEntry[]>("get_mcp_logs", { tool_id: toolId })
+        const entries = await invoke<MCPLogEntry[]>("get_mcp_logs", {
+          tool_id: toolId,
+          limit: LOG_PAGE_SIZE,
+        })
         if (active) {
           setLogsByTool((prev) => ({ ...prev, [toolId]: entries }))
+          setHasOlderLogs((prev) => ({ ...prev, [toolId]: entries.length === LOG_PAGE_SIZE }))
         }
         if (!logListeners.current[toolId]) {
           const unlisten = await listen<MCPLogEntry>(`mcp-log://${toolId}`, (event) => {
@@ -403,7 +437,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
         addNotification({
           type: "error",
           title: t("toast.loadFailed"),
-          description: String(err),
+          description: formatCommandError(t, err),
           timestamp: Date.now(),
         })
       }
@@ -421,6 +455,260 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
     }
   }, [addNotification, isTauri, logsOpen, pushLog, selectedTool, t])
 
+  useEffect(() => {
+    toolsRef.current = tools
+  }, [tools])
+
+  const sourceIdsKey = useMemo(() => sources.map((source) => source.id).sort().join(","), [sources])
+
+  useEffect(() => {
+    if (!isTauri || !sourceIdsKey) {
+      return
+    }
+    let active = true
+    const unlisteners: UnlistenFn[] = []
+
+    for (const sourceId of sourceIdsKey.split(",")) {
+      listen<McpSyncStatusEvent>(`mcp-sync-status://${sourceId}`, (event) => {
+        const payload = event.payload
+        if (payload.phase === "started" || payload.phase === "progress") {
+          setSources((prev) =>
+            prev.map((item) => (item.id === sourceId ? { ...item, status: "syncing" } : item))
+          )
+          return
+        }
+        if (payload.phase === "failed") {
+          addNotification({
+            type: "error",
+            title: t("toast.syncFailed"),
+            description: formatCommandError(t, payload),
+            timestamp: Date.now(),
+          })
+        } else {
+          const { added, updated, pending } = payload.counts
+          // Quiet for no-op syncs such as the one at startup.
+          if (added + updated + pending > 0) {
+            addNotification({
+              type: "success",
+              title: t("toast.syncSuccess"),
+              description: t("toast.syncCounts", { added, updated, pending }),
+              timestamp: Date.now(),
+            })
+          }
+        }
+        refreshAll()
+      }).then((unlisten) => {
+        if (active) {
+          unlisteners.push(unlisten)
+        } else {
+          unlisten()
+        }
+      })
+    }
+
+    return () => {
+      active = false
+      unlisteners.forEach((unlisten) => unlisten())
+    }
+  }, [addNotification, isTauri, refreshAll, sourceIdsKey, t])
+
+  const toolIdsKey = useMemo(() => tools.map((tool) => tool.id).sort().join(","), [tools])
+
+  useEffect(() => {
+    if (!isTauri || !toolIdsKey) {
+      return
+    }
+    let active = true
+    const unlisteners: UnlistenFn[] = []
+
+    for (const toolId of toolIdsKey.split(",")) {
+      listen<McpToolStatusEvent>(`mcp-status://${toolId}`, (event) => {
+        const { status, conflict_status, error } = event.payload
+        const current = toolsRef.current.find((item) => item.id === toolId)
+        if (current && current.conflictStatus !== conflict_status) {
+          // The event does not carry the pending config needed for the diff.
+          refreshAll()
+          return
+        }
+        setTools((prev) =>
+          prev.map((item) =>
+            item.id === toolId ? { ...item, status, error: error ?? undefined } : item
+          )
+        )
+      }).then((unlisten) => {
+        if (active) {
+          unlisteners.push(unlisten)
+        } else {
+          unlisten()
+        }
+      })
+    }
+
+    return () => {
+      active = false
+      unlisteners.forEach((unlisten) => unlisten())
+    }
+  }, [isTauri, refreshAll, toolIdsKey])
+
+  useEffect(() => {
+    if (!isTauri) {
+      return
+    }
+    let active = true
+    let unlisten: UnlistenFn | undefined
+
+    // Links opened before this page mounted are still pending in the backend.
+    invoke<McpInstallRequest[]>("list_install_requests")
+      .then((pending) => {
+        if (active) setInstallRequests(pending)
+      })
+      .catch(() => undefined)
+    listen<McpInstallRequest>("mcp-install-request", (event) => {
+      setInstallRequests((prev) => [...prev.filter((item) => item.id !== event.payload.id), event.payload])
+    }).then((fn) => {
+      if (active) {
+        unlisten = fn
+      } else {
+        fn()
+      }
+    })
+
+    return () => {
+      active = false
+      unlisten?.()
+    }
+  }, [isTauri])
+
+  useEffect(() => {
+    if (!isTauri) {
+      return
+    }
+    let active = true
+    let unlisten: UnlistenFn | undefined
+
+    // Starts from the tray may be waiting on an approval from before this page mounted.
+    invoke<McpToolApprovalRequest[]>("list_tool_approvals")
+      .then((pending) => {
+        if (active) setApprovalRequests(pending)
+      })
+      .catch(() => undefined)
+    listen<McpToolApprovalRequest>("mcp-tool-approval", (event) => {
+      // A newer request for the same tool replaces the earlier one in the backend.
+      setApprovalRequests((prev) => [
+        ...prev.filter((item) => item.tool_id !== event.payload.tool_id),
+        event.payload,
+      ])
+    }).then((fn) => {
+      if (active) {
+        unlisten = fn
+      } else {
+        fn()
+      }
+    })
+
+    return () => {
+      active = false
+      unlisten?.()
+    }
+  }, [isTauri])
+
+  const handleApprovalAnswer = useCallback((request: McpToolApprovalRequest, approved: boolean) => {
+    setApprovalRequests((prev) => prev.filter((item) => item.id !== request.id))
+    // The start waiting on this answer reports its own outcome.
+    invoke("answer_tool_approval", { request_id: request.id, approved }).catch(() => undefined)
+  }, [])
+
+  useEffect(() => {
+    if (!isTauri) {
+      return
+    }
+    let active = true
+    let unlisten: UnlistenFn | undefined
+
+    invoke<McpSyncSchedulerStatus>("get_sync_scheduler_status")
+      .then((status) => {
+        if (active) setSchedulerStatus(status)
+      })
+      .catch(() => undefined)
+    listen<McpSyncSchedulerStatus>("cloud-sync-status", (event) => {
+      setSchedulerStatus(event.payload)
+      if (!event.payload.running) refreshAll()
+    }).then((fn) => {
+      if (active) {
+        unlisten = fn
+      } else {
+        fn()
+      }
+    })
+
+    return () => {
+      active = false
+      unlisten?.()
+    }
+  }, [isTauri, refreshAll])
+
+  const handleToggleScheduler = useCallback(async (enabled: boolean) => {
+    try {
+      setSchedulerStatus(await invoke<McpSyncSchedulerStatus>("set_sync_scheduler_enabled", { enabled }))
+    } catch (err) {
+      addNotification({
+        type: "error",
+        title: t("toast.saveFailed"),
+        description: formatCommandError(t, err),
+        timestamp: Date.now(),
+      })
+    }
+  }, [addNotification, t])
+
+  const handleTriggerScheduler = useCallback(() => {
+    invoke("trigger_sync_scheduler").catch((err) => {
+      addNotification({
+        type: "error",
+        title: t("toast.syncFailed"),
+        description: formatCommandError(t, err),
+        timestamp: Date.now(),
+      })
+    })
+  }, [addNotification, t])
+
+  const handleInstallRequest = useCallback(async (request: McpInstallRequest, confirmed: boolean) => {
+    setInstallRequests((prev) => prev.filter((item) => item.id !== request.id))
+    if (!confirmed) {
+      await invoke("dismiss_install_request", { request_id: request.id }).catch(() => undefined)
+      return
+    }
+    try {
+      const installed = await invoke<McpToolRecord[]>("confirm_install_request", { request_id: request.id })
+      addNotification({
+        type: "success",
+        title: t("install.success", { count: installed.length }),
+        timestamp: Date.now(),
+      })
+      refreshAll()
+    } catch (err) {
+      addNotification({
+        type: "error",
+        title: t("install.failed"),
+        description: formatCommandError(t, err),
+        timestamp: Date.now(),
+      })
+    }
+  }, [addNotification, refreshAll, t])
+
+  const handlePasteConfig = useCallback(async () => {
+    try {
+      const request = await invoke<McpInstallRequest>("import_config_from_clipboard")
+      setInstallRequests((prev) => [...prev, request])
+    } catch (err) {
+      addNotification({
+        type: "error",
+        title: t("install.clipboardFailed"),
+        description: formatCommandError(t, err),
+        timestamp: Date.now(),
+      })
+    }
+  }, [addNotification, t])
+
   const handleToggleTool = useCallback(async (tool: MCPTool, enabled: boolean) => {
     if (!isTauri) {
       if (!tool.sourceId) {
@@ -452,7 +740,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
         addNotification({
           type: "error",
           title: enabled ? t("toast.startFailed") : t("toast.stopFailed"),
-          description: String(err),
+          description: formatCommandError(t, err),
           timestamp: Date.now(),
         })
         refreshAll()
@@ -467,16 +755,22 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       )
     )
     try {
+      // Untrusted tools open the approval dialog on their first start.
       const updated = enabled
-        ? await invoke<McpToolRecord>("start_mcp_tool", { tool_id: tool.id })
+        ? await invoke<McpToolRecord>("request_tool_approval", { tool_id: tool.id })
         : await invoke<McpToolRecord>("stop_mcp_tool", { tool_id: tool.id })
       const mapped = mapTool(updated)
       setTools((prev) => prev.map((item) => (item.id === mapped.id ? mapped : item)))
     } catch (err) {
+      if (isCommandError(err) && err.code === "APPROVAL_REQUIRED") {
+        // Declined in the approval dialog; nothing to report.
+        refreshAll()
+        return
+      }
       addNotification({
         type: "error",
         title: enabled ? t("toast.startFailed") : t("toast.stopFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
       refreshAll()
@@ -505,7 +799,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
         addNotification({
           type: "error",
           title: t("toast.syncFailed"),
-          description: String(err),
+          description: formatCommandError(t, err),
           timestamp: Date.now(),
         })
         refreshAll()
@@ -516,29 +810,29 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       prev.map((item) => (item.id === source.id ? { ...item, status: "syncing" } : item))
     )
     try {
-      if (source.type === "cloud") {
-        if (!accessToken) {
-          throw new Error(t("toast.missingToken"))
-        }
-        await invoke("sync_cloud_subscriptions", { access_token: accessToken })
-      } else {
-        const token = sourceTokens[source.id]
-        await invoke("sync_mcp_source", {
+      if (source.type !== "cloud") {
+        // Non-blocking: progress and the outcome arrive as mcp-sync-status events.
+        invoke("sync_mcp_source", {
           source_id: source.id,
-          payload: { auth_token: token || null },
-        })
+          payload: { auth_token: sourceTokens[source.id] || null },
+        }).catch(() => undefined)
+        return
       }
-      await refreshAll()
+      if (!accessToken) {
+        throw new Error(t("toast.missingToken"))
+      }
+      const changes = await invoke<McpSyncChanges>("sync_cloud_subscriptions", { access_token: accessToken })
+      await applySyncChanges(changes)
     } catch (err) {
       addNotification({
         type: "error",
         title: t("toast.syncFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
       refreshAll()
     }
-  }, [accessToken, addNotification, isTauri, refreshAll, sourceTokens, syncSource, t])
+  }, [accessToken, addNotification, applySyncChanges, isTauri, refreshAll, sourceTokens, syncSource, t])
 
   const handleSyncServers = useCallback(async () => {
     if (isTauri) {
@@ -575,7 +869,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       addNotification({
         type: "error",
         title: t("toast.syncFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
       refreshAll()
@@ -618,7 +912,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       addNotification({
         type: "error",
         title: t("toast.syncFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
       refreshAll()
@@ -649,7 +943,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
         addNotification({
           type: "error",
           title: t("toast.syncFailed"),
-          description: String(err),
+          description: formatCommandError(t, err),
           timestamp: Date.now(),
         })
         refreshAll()
@@ -670,21 +964,21 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       setSources((prev) => [...prev, mapped])
       if (payload.authToken) {
         setSourceTokens((prev) => ({ ...prev, [created.id]: payload.authToken || "" }))
-        await invoke("sync_mcp_source", {
+        const changes = await invoke<McpSyncChanges>("sync_mcp_source", {
           source_id: created.id,
           payload: { auth_token: payload.authToken },
         })
-        await refreshAll()
+        await applySyncChanges(changes)
       }
     } catch (err) {
       addNotification({
         type: "error",
         title: t("toast.syncFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
     }
-  }, [addNotification, createSource, isTauri, refreshAll, syncSource, t])
+  }, [addNotification, applySyncChanges, createSource, isTauri, refreshAll, syncSource, t])
 
   const handleImportConfig = useCallback(async (payload: {
     config: Record<string, unknown>
@@ -797,7 +1091,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
           addNotification({
             type: "warning",
             title: t("toast.syncFailed"),
-            description: String(err),
+            description: formatCommandError(t, err),
             timestamp: Date.now(),
           })
         }
@@ -812,7 +1106,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       addNotification({
         type: "error",
         title: t("toast.saveFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
     }
@@ -840,7 +1134,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       addNotification({
         type: "error",
         title: t("toast.saveFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
     }
@@ -850,8 +1144,57 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
     if (!isTauri) return
     await invoke("clear_mcp_logs", { tool_id: tool.id })
     setLogsByTool((prev) => ({ ...prev, [tool.id]: [] }))
+    setHasOlderLogs((prev) => ({ ...prev, [tool.id]: false }))
   }, [isTauri])
 
+  const handleLoadOlderLogs = useCallback(async () => {
+    if (!isTauri || !selectedTool) return
+    const toolId = selectedTool.id
+    const before = logsByTool[toolId]?.find((entry) => entry.seq)?.seq
+    if (!before) return
+    try {
+      const older = await invoke<MCPLogEntry[]>("get_mcp_logs", {
+        tool_id: toolId,
+        limit: LOG_PAGE_SIZE,
+        before,
+      })
+      setLogsByTool((prev) => ({ ...prev, [toolId]: [...older, ...(prev[toolId] || [])] }))
+      setHasOlderLogs((prev) => ({ ...prev, [toolId]: older.length === LOG_PAGE_SIZE }))
+    } catch (err) {
+      addNotification({
+        type: "error",
+        title: t("toast.loadFailed"),
+        description: formatCommandError(t, err),
+        timestamp: Date.now(),
+      })
+    }
+  }, [addNotification, isTauri, logsByTool, selectedTool, t])
+
+  const handleExportLogs = useCallback(async (format: McpLogExportFormat) => {
+    if (!isTauri || !selectedTool) return
+    try {
+      const path = await invoke<string | null>("export_tool_logs", {
+        tool_id: selectedTool.id,
+        format,
+        open: true,
+      })
+      if (!path) return
+      addNotification({
+        type: "success",
+        title: t("logs.downloaded"),
+        description: path,
+        timestamp: Date.now(),
+      })
+    } catch (err) {
+      addNotification({
+        type: "error",
+        title: t("logs.exportFailed"),
+        description: formatCommandError(t, err),
+        timestamp: Date.now(),
+      })
+    }
+  }, [addNotification, isTauri, selectedTool, t])
+
   const handleShowLogs = useCallback((tool: MCPTool) => {
     if (!isTauri) return
     setSelectedTool(tool)
@@ -908,7 +1251,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       addNotification({
         type: "error",
         title: t("toast.updateFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
     }
@@ -927,7 +1270,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       addNotification({
         type: "error",
         title: t("toast.updateFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
       refreshTools()
@@ -963,7 +1306,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       addNotification({
         type: "error",
         title: t("toast.updateFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
     }
@@ -993,7 +1336,7 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
       addNotification({
         type: "error",
         title: t("toast.deleteFailed"),
-        description: String(err),
+        description: formatCommandError(t, err),
         timestamp: Date.now(),
       })
     }
@@ -1019,7 +1362,10 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
 
       <div className="relative mx-auto max-w-7xl space-y-16">
         <div className="animate-glass-card-in stagger-1">
-          <RegistryHeader onCreateManual={handleImportConfig} />
+          <RegistryHeader
+            onCreateManual={handleImportConfig}
+            onPasteConfig={isTauri ? handlePasteConfig : undefined}
+          />
         </div>
 
         <div className="animate-glass-card-in stagger-2">
@@ -1027,6 +1373,9 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
             sources={sources}
             onSync={handleSyncSource}
             onCreateSource={handleCreateSource}
+            schedulerStatus={schedulerStatus}
+            onToggleScheduler={handleToggleScheduler}
+            onTriggerScheduler={handleTriggerScheduler}
           />
         </div>
 
@@ -1060,6 +1409,9 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
             handleClearLogs(selectedTool)
           }
         }}
+        onExport={isTauri ? handleExportLogs : undefined}
+        onLoadOlder={isTauri ? handleLoadOlderLogs : undefined}
+        hasOlder={selectedTool ? hasOlderLogs[selectedTool.id] : false}
       />
 
       <ConflictResolutionDialog
@@ -1073,6 +1425,17 @@ export function MCPRegistryClient({ initialTools, initialSources }: MCPRegistryC
         }}
       />
 
+      <InstallRequestDialog
+        request={installRequests[0] ?? null}
+        onConfirm={(request) => handleInstallRequest(request, true)}
+        onDismiss={(request) => handleInstallRequest(request, false)}
+      />
+
+      <ToolApprovalDialog
+        request={approvalRequests[0] ?? null}
+        onAnswer={handleApprovalAnswer}
+      />
+
       <EditServerSheet
         server={editServer}
         tools={editServerTools}
@@ -1,6 +1,6 @@
 "use client"
 
-import { Plus, Terminal } from "lucide-react"
+import { ClipboardPaste, Plus, Terminal } from "lucide-react"
 import { GlassButton } from "@/components/ui/glass-button"
 import dynamic from "next/dynamic"
 import { useTranslations } from "next-intl"
@@ -9,9 +9,10 @@ const AddServerSheet = dynamic(() => import("./add-server-sheet").then(mod => mo
 
 interface RegistryHeaderProps {
   onCreateManual: (payload: { config: Record<string, unknown> }) => void
+  onPasteConfig?: () => void
 }
 
-export function RegistryHeader({ onCreateManual }: RegistryHeaderProps) {
+export function RegistryHeader({ onCreateManual, onPasteConfig }: RegistryHeaderProps) {
   const t = useTranslations("mcp")
 
   return (
@@ -28,12 +29,20 @@ export function RegistryHeader({ onCreateManual }: RegistryHeaderProps) {
         </p>
       </div>
 
-      <AddServerSheet onCreate={onCreateManual}>
-        <GlassButton variant="default" size="lg" className="px-6">
-          <Plus size={18} className="mr-2" />
-          {t("header.addManual")}
-        </GlassButton>
-      </AddServerSheet>
+      <div className="flex items-center gap-3">
+        {onPasteConfig && (
+          <GlassButton variant="secondary" size="lg" className="px-6" onClick={onPasteConfig}>
+            <ClipboardPaste size={18} className="mr-2" />
+            {t("header.pasteConfig")}
+          </GlassButton>
+        )}
+        <AddServerSheet onCreate={onCreateManual}>
+          <GlassButton variant="default" size="lg" className="px-6">
+            <Plus size={18} className="mr-2" />
+            {t("header.addManual")}
+          </GlassButton>
+        </AddServerSheet>
+      </div>
     </div>
   )
 }
@@ -1,7 +1,7 @@
 "use client"
 
 import { useMemo, useRef, useState, useEffect } from "react"
-import { Terminal, Copy, Download, Trash2, PauseCircle, PlayCircle, Activity } from "lucide-react"
+import { Terminal, Copy, Download, Trash2, PauseCircle, PlayCircle, Activity, History } from "lucide-react"
 import { useTranslations } from "next-intl"
 import {
   Sheet,
@@ -13,7 +13,8 @@ import { Tabs, TabsList, TabsTrigger, TabsContent } from "@/components/ui/tabs"
 import { Button } from "@/components/ui/button"
 import { ScrollArea } from "@/components/ui/scroll-area"
 import { Badge } from "@/components/ui/badge"
-import { MCPLogEntry, MCPTool } from "@/types/mcp"
+import { GlassDropdownMenu, GlassDropdownMenuContent, GlassDropdownMenuItem, GlassDropdownMenuTrigger } from "@/components/ui/glass-dropdown"
+import { MCPLogEntry, MCPTool, McpLogExportFormat } from "@/types/mcp"
 import { cn } from "@/lib/utils"
 import { useNotifications } from "@/components/contexts/notification-context"
 
@@ -23,6 +24,11 @@ interface ServerLogsSheetProps {
     open: boolean
     onOpenChange: (open: boolean) => void
     onClear: () => void
+    /** 桌面端：由后端写入文件，替代浏览器下载 */
+    onExport?: (format: McpLogExportFormat) => void
+    /** 桌面端：加载更早的历史日志 */
+    onLoadOlder?: () => void
+    hasOlder?: boolean
 }
 
 const formatTimestamp = (value: string) => {
@@ -31,7 +37,33 @@ const formatTimestamp = (value: string) => {
   return parsed.toLocaleTimeString()
 }
 
-export function ServerLogsSheet({ tool, logs, open, onOpenChange, onClear }: ServerLogsSheetProps) {
+const LEVEL_STYLES: Record<string, string> = {
+  trace: "text-gray-500",
+  debug: "text-gray-400",
+  info: "text-sky-400",
+  warn: "text-yellow-400",
+  warning: "text-yellow-400",
+  error: "text-red-400",
+  fatal: "text-red-500 font-bold",
+}
+
+/** JSON 日志（pino/winston）显示级别与 msg，其余原样显示 */
+function LogMessage({ entry }: { entry: MCPLogEntry }) {
+  const structured = entry.structured
+  if (!structured || (!structured.level && !structured.msg)) return <>{entry.message}</>
+  return (
+    <span title={entry.message}>
+      {structured.level && (
+        <span className={cn("uppercase select-none", LEVEL_STYLES[structured.level] ?? "text-gray-400")}>
+          {structured.level}{" "}
+        </span>
+      )}
+      {structured.msg ?? entry.message}
+    </span>
+  )
+}
+
+export function ServerLogsSheet({ tool, logs, open, onOpenChange, onClear, onExport, onLoadOlder, hasOlder }: ServerLogsSheetProps) {
     const t = useTranslations("mcp")
     const { addNotification } = useNotifications()
     const [autoScroll, setAutoScroll] = useState(true)
@@ -114,13 +146,42 @@ export function ServerLogsSheet({ tool, logs, open, onOpenChange, onClear }: Ser
                              {autoScroll ? <PlayCircle size={12} /> : <PauseCircle size={12} />}
                              {t("logs.autoScroll")}
                          </Button>
+                         {onLoadOlder && hasOlder && (
+                           <Button
+                              variant="ghost"
+                              size="sm"
+                              className="h-7 px-2 text-xs gap-1.5 text-gray-500"
+                              onClick={onLoadOlder}
+                           >
+                               <History size={12} />
+                               {t("logs.loadOlder")}
+                           </Button>
+                         )}
                          <div className="h-4 w-px bg-gray-200 mx-1" />
                          <Button variant="ghost" size="icon" className="h-7 w-7 text-gray-400 hover:text-gray-900" onClick={handleCopy}>
                              <Copy size={14} />
                          </Button>
-                         <Button variant="ghost" size="icon" className="h-7 w-7 text-gray-400 hover:text-gray-900" onClick={handleDownload}>
-                             <Download size={14} />
-                         </Button>
+                         {onExport ? (
+                           <GlassDropdownMenu>
+                             <GlassDropdownMenuTrigger asChild>
+                               <Button variant="ghost" size="icon" className="h-7 w-7 text-gray-400 hover:text-gray-900">
+                                 <Download size={14} />
+                               </Button>
+                             </GlassDropdownMenuTrigger>
+                             <GlassDropdownMenuContent align="end" className="w-[160px]">
+                               <GlassDropdownMenuItem onClick={() => onExport("text")}>
+                                 {t("logs.exportText")}
+                               </GlassDropdownMenuItem>
+                               <GlassDropdownMenuItem onClick={() => onExport("jsonl")}>
+                                 {t("logs.exportJsonl")}
+                               </GlassDropdownMenuItem>
+                             </GlassDropdownMenuContent>
+                           </GlassDropdownMenu>
+                         ) : (
+                           <Button variant="ghost" size="icon" className="h-7 w-7 text-gray-400 hover:text-gray-900" onClick={handleDownload}>
+                               <Download size={14} />
+                           </Button>
+                         )}
                          <Button variant="ghost" size="icon" className="h-7 w-7 text-gray-400 hover:text-red-600" onClick={() => {
                            onClear()
                            addNotification({
@@ -167,9 +228,12 @@ export function ServerLogsSheet({ tool, logs, open, onOpenChange, onClear }: Ser
                                 ) : (
                                   <div className="whitespace-pre-wrap">
                                     {stdoutLogs.map((entry, index) => (
-                                      <div key={`${entry.timestamp}-${index}`}>
+                                      <div
+                                        key={`${entry.timestamp}-${index}`}
+                                        className={cn(entry.level === "error" && "text-red-400", entry.level === "warn" && "text-yellow-400")}
+                                      >
                                         <span className="text-gray-500 select-none">{formatTimestamp(entry.timestamp)}</span>{" "}
-                                        {entry.message}
+                                        <LogMessage entry={entry} />
                                       </div>
                                     ))}
                                     <div ref={bottomRef} />
@@ -184,7 +248,7 @@ export function ServerLogsSheet({ tool, logs, open, onOpenChange, onClear }: Ser
                                         {stderrLogs.map((entry, index) => (
                                           <div key={`${entry.timestamp}-${index}`}>
                                             <span className="text-gray-500 select-none">{formatTimestamp(entry.timestamp)}</span>{" "}
-                                            {entry.message}
+                                            <LogMessage entry={entry} />
                                           </div>
                                         ))}
                                         <div ref={bottomRef} />
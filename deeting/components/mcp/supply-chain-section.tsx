@@ -2,7 +2,8 @@
 
 import { Button } from "@/components/ui/button"
 import { SyncSourceCard } from "./sync-source-card"
-import { MCPSource } from "@/types/mcp"
+import { SyncSchedulerBar } from "./sync-scheduler-bar"
+import { MCPSource, McpSyncSchedulerStatus } from "@/types/mcp"
 import dynamic from "next/dynamic"
 import { useTranslations } from "next-intl"
 
@@ -18,9 +19,19 @@ interface SupplyChainSectionProps {
     trustLevel: MCPSource["trustLevel"]
     authToken?: string
   }) => void
+  schedulerStatus?: McpSyncSchedulerStatus | null
+  onToggleScheduler?: (enabled: boolean) => void
+  onTriggerScheduler?: () => void
 }
 
-export function SupplyChainSection({ sources, onSync, onCreateSource }: SupplyChainSectionProps) {
+export function SupplyChainSection({
+  sources,
+  onSync,
+  onCreateSource,
+  schedulerStatus,
+  onToggleScheduler,
+  onTriggerScheduler,
+}: SupplyChainSectionProps) {
   const t = useTranslations("mcp")
 
   return (
@@ -28,6 +39,13 @@ export function SupplyChainSection({ sources, onSync, onCreateSource }: SupplyCh
        <div className="flex items-center gap-2 mb-2">
            <h2 className="text-sm font-bold text-gray-900 uppercase tracking-wider">{t("supplyChain.title")}</h2>
            <div className="h-px bg-gray-100 flex-1" />
+           {schedulerStatus && onToggleScheduler && onTriggerScheduler && (
+              <SyncSchedulerBar
+                  status={schedulerStatus}
+                  onToggle={onToggleScheduler}
+                  onTrigger={onTriggerScheduler}
+              />
+           )}
            <AddSourceDialog onCreate={onCreateSource}>
               <Button variant="ghost" size="sm" className="text-xs text-gray-500 hover:text-gray-900">
                  + {t("supplyChain.addSource")}
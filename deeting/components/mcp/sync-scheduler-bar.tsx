@@ -0,0 +1,61 @@
+"use client"
+
+import { useEffect, useState } from "react"
+import { RefreshCw } from "lucide-react"
+import { useFormatter, useTranslations } from "next-intl"
+import { Switch } from "@/components/ui/switch"
+import { Button } from "@/components/ui/button"
+import { cn } from "@/lib/utils"
+import { McpSyncSchedulerStatus } from "@/types/mcp"
+
+interface SyncSchedulerBarProps {
+  status: McpSyncSchedulerStatus
+  onToggle: (enabled: boolean) => void
+  onTrigger: () => void
+}
+
+export function SyncSchedulerBar({ status, onToggle, onTrigger }: SyncSchedulerBarProps) {
+  const t = useTranslations("mcp")
+  const format = useFormatter()
+  const [now, setNow] = useState(() => new Date())
+
+  useEffect(() => {
+    const timer = setInterval(() => setNow(new Date()), 30_000)
+    return () => clearInterval(timer)
+  }, [])
+
+  const failed = status.sources.filter(source => !source.success)
+  const lastSynced = status.last_run_at
+    ? t("scheduler.lastSynced", { time: format.relativeTime(new Date(status.last_run_at), now) })
+    : t("scheduler.neverSynced")
+  const next = status.running
+    ? t("scheduler.running")
+    : status.enabled && status.next_run_at
+      ? t("scheduler.nextRun", { time: format.relativeTime(new Date(status.next_run_at), now) })
+      : t("scheduler.paused")
+
+  return (
+    <div className="flex items-center gap-3 text-xs text-gray-500">
+      <span title={failed.map(source => `${source.source_name}: ${source.error ?? ""}`).join("\n") || undefined}>
+        {lastSynced} · {next}
+        {failed.length > 0 && (
+          <span className="ml-1 text-red-500">{t("scheduler.failed", { count: failed.length })}</span>
+        )}
+      </span>
+      <Button
+        variant="ghost"
+        size="sm"
+        className="h-7 px-2 text-xs"
+        disabled={status.running}
+        onClick={onTrigger}
+      >
+        <RefreshCw size={12} className={cn("mr-1", status.running && "animate-spin")} />
+        {t("scheduler.syncNow")}
+      </Button>
+      <label className="flex items-center gap-1.5">
+        <Switch checked={status.enabled} onCheckedChange={onToggle} />
+        {t("scheduler.autoSync")}
+      </label>
+    </div>
+  )
+}
@@ -0,0 +1,70 @@
+"use client"
+
+import { ShieldAlert } from "lucide-react"
+import { useTranslations } from "next-intl"
+import { Button } from "@/components/ui/button"
+import { Badge } from "@/components/ui/badge"
+import {
+  Sheet,
+  SheetContent,
+  SheetDescription,
+  SheetHeader,
+  SheetTitle,
+  SheetFooter,
+} from "@/components/ui/sheet"
+import { Alert, AlertDescription } from "@/components/ui/alert"
+import { McpToolApprovalRequest } from "@/types/mcp"
+import { ToolRunPreview } from "./tool-run-preview"
+
+interface ToolApprovalDialogProps {
+  request: McpToolApprovalRequest | null
+  onAnswer: (request: McpToolApprovalRequest, approved: boolean) => void
+}
+
+export function ToolApprovalDialog({ request, onAnswer }: ToolApprovalDialogProps) {
+  const t = useTranslations("mcp")
+  if (!request) return null
+
+  return (
+    <Sheet open onOpenChange={(open) => !open && onAnswer(request, false)}>
+      <SheetContent side="right" className="w-full sm:max-w-xl">
+        <SheetHeader>
+          <SheetTitle className="flex items-center gap-2">
+            <ShieldAlert size={20} /> {t("approval.title")}
+          </SheetTitle>
+          <SheetDescription>
+            {t("approval.description", { tool: request.tool_name, source: request.source_name })}
+          </SheetDescription>
+        </SheetHeader>
+
+        <div className="space-y-4 my-4">
+          <Badge variant="outline">{t("install.trust", { level: request.trust_level })}</Badge>
+
+          <div className="bg-gray-50 p-4 rounded-lg border border-gray-200">
+            <div className="text-sm font-medium text-gray-700 mb-3">{t("approval.command")}</div>
+            <div className="font-mono text-xs text-gray-600 bg-white p-3 rounded border border-gray-100 overflow-x-auto">
+              <ToolRunPreview preview={request} />
+            </div>
+          </div>
+
+          <Alert className="bg-orange-50 text-orange-800 border-orange-200">
+            <ShieldAlert className="h-4 w-4 text-orange-600" />
+            <AlertDescription>{t("approval.warning")}</AlertDescription>
+          </Alert>
+        </div>
+
+        <SheetFooter className="gap-2 sm:gap-0">
+          <Button variant="outline" onClick={() => onAnswer(request, false)}>
+            {t("approval.deny")}
+          </Button>
+          <Button
+            className="bg-black text-white hover:bg-gray-800"
+            onClick={() => onAnswer(request, true)}
+          >
+            {t("approval.approve")}
+          </Button>
+        </SheetFooter>
+      </SheetContent>
+    </Sheet>
+  )
+}
@@ -0,0 +1,31 @@
+"use client"
+
+import { useTranslations } from "next-intl"
+import { McpToolRunPreview } from "@/types/mcp"
+
+interface ToolRunPreviewProps {
+  preview: McpToolRunPreview
+}
+
+/** The command line of a tool followed by its env and the hook and health check commands it would run. */
+export function ToolRunPreview({ preview }: ToolRunPreviewProps) {
+  const t = useTranslations("mcp")
+  const extra = [
+    ...Object.entries(preview.env ?? {}).map(([key, value]) => [t("runPreview.env"), `${key}=${value}`]),
+    ...(preview.hooks.preStart ?? []).map((line) => [t("runPreview.preStart"), line]),
+    ...(preview.hooks.postStop ?? []).map((line) => [t("runPreview.postStop"), line]),
+    ...(preview.healthcheck_command ? [[t("runPreview.healthcheck"), preview.healthcheck_command]] : []),
+  ]
+
+  return (
+    <div className="space-y-1">
+      <div className="whitespace-pre-wrap">{[preview.command ?? "-", ...preview.args].join(" ")}</div>
+      {extra.map(([label, line], index) => (
+        <div key={index} className="whitespace-pre-wrap">
+          <span className="text-gray-400 select-none">{label}: </span>
+          {line}
+        </div>
+      ))}
+    </div>
+  )
+}
@@ -0,0 +1,41 @@
+/**
+ * 桌面端命令失败时 reject 的结构（对应 Rust 侧 `CommandError`）。
+ * `code` 为稳定标识，`message` 为英文说明，仅作兜底展示。
+ */
+export interface CommandError {
+  code: string
+  message: string
+  details?: Record<string, unknown>
+}
+
+type ErrorTranslator = {
+  (key: string, values?: Record<string, string>): string
+  has: (key: string) => boolean
+}
+
+export function isCommandError(err: unknown): err is CommandError {
+  return (
+    typeof err === "object" &&
+    err !== null &&
+    typeof (err as CommandError).code === "string" &&
+    typeof (err as CommandError).message === "string"
+  )
+}
+
+/**
+ * 将命令错误转换为本地化文案；未知 code 或非结构化错误时回退到原始信息。
+ * `t` 需为 `useTranslations("mcp")`。
+ */
+export function formatCommandError(t: ErrorTranslator, err: unknown): string {
+  if (!isCommandError(err)) {
+    return err instanceof Error ? err.message : String(err)
+  }
+  const key = `errors.${err.code}`
+  if (!t.has(key)) return err.message
+  const missing = err.details?.missing
+  return t(key, {
+    // 去掉 "validation error: " 这类与 code 重复的前缀
+    message: err.message.replace(/^[a-z ]+: /, ""),
+    missing: Array.isArray(missing) ? missing.join(", ") : "",
+  })
+}
@@ -0,0 +1,69 @@
+use log::warn;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_autostart::{MacosLauncher, ManagerExt};
+
+use crate::mcp::commands::start_tool_inner;
+use crate::mcp::error::McpError;
+use crate::mcp::types::LaunchSettings;
+use crate::mcp::McpRuntimeState;
+use crate::tray::MAIN_WINDOW;
+
+pub const LAUNCH_SETTINGS_KEY: &str = "launch";
+
+/// Passed by the OS login entry so a login launch can be told apart from one
+/// started by the user.
+const AUTOSTART_ARG: &str = "--autostart";
+
+pub fn plugin<R: tauri::Runtime>() -> tauri::plugin::TauriPlugin<R> {
+    tauri_plugin_autostart::init(MacosLauncher::LaunchAgent, Some(vec![AUTOSTART_ARG]))
+}
+
+pub fn launched_at_login() -> bool {
+    std::env::args().any(|arg| arg == AUTOSTART_ARG)
+}
+
+/// Registers or removes the OS login entry to match `launch_at_login`.
+pub fn apply(app: &AppHandle, settings: &LaunchSettings) -> Result<(), McpError> {
+    let autolaunch = app.autolaunch();
+    let result = if settings.launch_at_login {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|err| McpError::Process(format!("failed to update login item: {err}")))
+}
+
+/// Runs once the runtime state is managed: hides the window for a login
+/// launch when asked to, then starts every tool flagged `auto_start`.
+pub fn on_launch(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<McpRuntimeState>();
+        let settings = state
+            .store
+            .get_setting::<LaunchSettings>(LAUNCH_SETTINGS_KEY)
+            .await
+            .unwrap_or_default();
+        if launched_at_login() && settings.start_hidden {
+            if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+                let _ = window.hide();
+            }
+        }
+        start_autostart_tools(&state).await;
+    });
+}
+
+async fn start_autostart_tools(state: &McpRuntimeState) {
+    let tools = match state.store.list_tools().await {
+        Ok(tools) => tools,
+        Err(err) => {
+            warn!("failed to list autostart tools: {}", err);
+            return;
+        }
+    };
+    for tool in tools.into_iter().filter(|tool| tool.auto_start) {
+        if let Err(err) = start_tool_inner(state, &tool.id).await {
+            warn!("autostart of {} failed: {}", tool.name, err);
+        }
+    }
+}
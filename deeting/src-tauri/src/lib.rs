@@ -1,79 +1,82 @@
+mod autostart;
 mod mcp;
+mod paths;
+mod tray;
 
 use std::sync::Arc;
 
-use log::warn;
+use log::{error, warn};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_log::{Target, TargetKind};
 use crate::mcp::error::McpError;
 use crate::mcp::process::ProcessManager;
-use crate::mcp::store::{expand_path, McpStore};
-use crate::mcp::types::McpSourceStatus;
+use crate::mcp::startup::Startup;
+use crate::mcp::store::McpStore;
 use crate::mcp::McpRuntimeState;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
   tauri::Builder::default()
+    // Must be registered first. A second launch exits here after handing its
+    // arguments over; the `deep-link` feature replays its URLs through
+    // `on_open_url` in this instance.
+    .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+      crate::tray::show_main_window(app);
+    }))
     .plugin(tauri_plugin_http::init())
+    .plugin(tauri_plugin_dialog::init())
+    .plugin(tauri_plugin_clipboard_manager::init())
+    .plugin(tauri_plugin_notification::init())
+    .plugin(tauri_plugin_opener::init())
+    .plugin(crate::autostart::plugin())
+    .plugin(tauri_plugin_deep_link::init())
     .setup(|app| {
       if cfg!(debug_assertions) {
-        app.handle().plugin(
-          tauri_plugin_log::Builder::default()
-            .level(log::LevelFilter::Info)
-            .build(),
-        )?;
+        let mut log = tauri_plugin_log::Builder::default().level(log::LevelFilter::Info);
+        // Portable installs keep their log beside the binary as well.
+        if let Some(dir) = crate::paths::portable_dir() {
+          log = log.targets([
+            Target::new(TargetKind::Stdout),
+            Target::new(TargetKind::Folder {
+              path: dir.join("logs"),
+              file_name: None,
+            }),
+          ]);
+        }
+        app.handle().plugin(log.build())?;
       }
+      // The window opens straight away; commands reject with `INITIALIZING`
+      // until the store is ready.
+      app.manage(Startup::default());
+      crate::tray::init(app.handle())?;
       let handle = app.handle().clone();
-      let cloud_base_url = resolve_cloud_base_url();
-      let state = tauri::async_runtime::block_on(async {
-        let database_url = resolve_database_url()?;
-        let store = Arc::new(McpStore::new(&database_url).await?);
-        store.init().await?;
-        store.ensure_local_source().await?;
-        store.ensure_cloud_source(&cloud_base_url).await?;
-        let process_manager = ProcessManager::new(store.clone(), handle);
-        Ok::<_, McpError>(McpRuntimeState::new(
-          store,
-          process_manager,
-          cloud_base_url,
-        ))
-      })
-      .map_err(|err| Box::<dyn std::error::Error>::from(err))?;
-      let sync_state = state.clone();
-      app.manage(state);
       tauri::async_runtime::spawn(async move {
-        let source = match sync_state.store.ensure_local_source().await {
-          Ok(source) => source,
-          Err(err) => {
-            warn!("mcp auto sync skipped: {}", err);
-            return;
-          }
-        };
-        let _ = sync_state
-          .store
-          .update_source_status(&source.id, McpSourceStatus::Syncing, None)
-          .await;
-        match crate::mcp::commands::sync_source_inner(&sync_state, source.clone(), None).await {
-          Ok(_) => {
-            let _ = sync_state
-              .store
-              .update_source_status(&source.id, McpSourceStatus::Active, Some(now_rfc3339()))
-              .await;
-          }
+        match init_state(&handle).await {
+          Ok(state) => start(&handle, state),
           Err(err) => {
-            let _ = sync_state
-              .store
-              .update_source_status(&source.id, McpSourceStatus::Error, None)
-              .await;
-            warn!("mcp auto sync failed: {}", err);
+            error!("mcp startup failed: {}", err);
+            crate::mcp::startup::mark_failed(&handle, &err);
           }
         }
       });
       Ok(())
     })
+    .on_window_event(|window, event| {
+      // Closing the main window hides it; the tray keeps the manager running.
+      if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+        if window.label() == crate::tray::MAIN_WINDOW {
+          let _ = window.hide();
+          api.prevent_close();
+        }
+      }
+    })
     .invoke_handler(tauri::generate_handler![
       crate::mcp::commands::set_cloud_base_url,
       crate::mcp::commands::list_mcp_sources,
       crate::mcp::commands::create_mcp_source,
       crate::mcp::commands::sync_mcp_source,
+      crate::mcp::commands::test_mcp_source,
       crate::mcp::commands::list_mcp_tools,
       crate::mcp::commands::list_local_assistants,
       crate::mcp::commands::create_local_assistant,
@@ -83,49 +86,163 @@ pub fn run() {
       crate::mcp::commands::append_assistant_message,
       crate::mcp::commands::delete_assistant_messages,
       crate::mcp::commands::import_mcp_config,
+      crate::mcp::commands::import_config_from_file,
+      crate::mcp::commands::import_config_from_clipboard,
       crate::mcp::commands::start_mcp_tool,
+      crate::mcp::commands::approve_mcp_tool,
+      crate::mcp::commands::request_tool_approval,
+      crate::mcp::commands::answer_tool_approval,
+      crate::mcp::commands::list_tool_approvals,
       crate::mcp::commands::stop_mcp_tool,
+      crate::mcp::commands::set_mcp_tool_auto_start,
+      crate::mcp::commands::set_mcp_tool_log_to_file,
+      crate::mcp::commands::set_mcp_tool_log_buffer_size,
+      crate::mcp::commands::get_tool_env_requirements,
       crate::mcp::commands::update_mcp_tool_env,
       crate::mcp::commands::apply_pending_config,
       crate::mcp::commands::resolve_mcp_conflict,
       crate::mcp::commands::get_mcp_logs,
+      crate::mcp::commands::search_mcp_logs,
+      crate::mcp::commands::subscribe_all_mcp_logs,
+      crate::mcp::commands::unsubscribe_all_mcp_logs,
+      crate::mcp::commands::export_tool_logs,
       crate::mcp::commands::clear_mcp_logs,
-      crate::mcp::commands::sync_cloud_subscriptions
+      crate::mcp::commands::publish_mcp_tool,
+      crate::mcp::commands::sync_cloud_subscriptions,
+      crate::mcp::commands::sync_all_sources,
+      crate::mcp::commands::cloud_login,
+      crate::mcp::commands::cloud_set_session,
+      crate::mcp::commands::cloud_logout,
+      crate::mcp::commands::get_cloud_session_status,
+      crate::mcp::commands::list_cloud_accounts,
+      crate::mcp::commands::add_cloud_account,
+      crate::mcp::commands::remove_cloud_account,
+      crate::mcp::commands::sync_cloud_assistants,
+      crate::mcp::commands::resolve_assistant_conflict,
+      crate::mcp::commands::get_cloud_sync_settings,
+      crate::mcp::commands::update_cloud_sync_settings,
+      crate::mcp::commands::get_sync_scheduler_status,
+      crate::mcp::commands::set_sync_scheduler_enabled,
+      crate::mcp::commands::trigger_sync_scheduler,
+      crate::mcp::commands::export_diagnostics,
+      crate::mcp::commands::export_app_state,
+      crate::mcp::commands::import_app_state,
+      crate::mcp::commands::get_startup_status,
+      crate::mcp::commands::get_app_settings,
+      crate::mcp::commands::update_app_settings,
+      crate::mcp::commands::get_launch_settings,
+      crate::mcp::commands::update_launch_settings,
+      crate::mcp::commands::get_notification_settings,
+      crate::mcp::commands::update_notification_settings,
+      crate::mcp::commands::get_telemetry_settings,
+      crate::mcp::commands::update_telemetry_settings,
+      crate::mcp::commands::list_cloud_market_tools,
+      crate::mcp::commands::subscribe_cloud_tool,
+      crate::mcp::commands::list_install_requests,
+      crate::mcp::commands::confirm_install_request,
+      crate::mcp::commands::dismiss_install_request
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
 }
 
+/// Opens the database, ensures the built-in sources and restores settings.
+async fn init_state(app: &AppHandle) -> Result<McpRuntimeState, McpError> {
+  let cloud_base_url = resolve_cloud_base_url();
+  let database_url = resolve_database_url()?;
+  let store = Arc::new(McpStore::new(&database_url).await?);
+  store.init().await?;
+  store.ensure_local_source().await?;
+  let cloud_source = store.ensure_cloud_source(&cloud_base_url).await?;
+  let process_manager = ProcessManager::new(store.clone(), app.clone());
+  process_manager.resume_log_seq().await?;
+  let state = McpRuntimeState::new(store, process_manager, cloud_base_url, cloud_source.id);
+  let settings = crate::mcp::settings::load(&state).await?;
+  crate::mcp::settings::apply(&state, &settings).await;
+  state.process_manager.reload_attention().await?;
+  Ok(state)
+}
+
+/// Publishes the state to commands, then starts the background work that
+/// needs it.
+fn start(app: &AppHandle, state: McpRuntimeState) {
+  let sync_state = state.clone();
+  crate::mcp::event_bus::spawn_event_emitter(
+    app.clone(),
+    state.store.clone(),
+    state.process_manager.events().subscribe(),
+  );
+  crate::mcp::cloud_events::spawn_subscription_listeners(app.clone(), state.clone());
+  crate::mcp::scheduler::spawn_cloud_sync_scheduler(app.clone(), state.clone());
+  crate::mcp::telemetry::spawn_health_reporter(state.clone());
+  crate::mcp::startup::mark_ready(app, state);
+  crate::tray::refresh(app);
+  crate::autostart::on_launch(app);
+  if let Err(err) = register_deep_links(app) {
+    warn!("failed to register deep links: {}", err);
+  }
+  tauri::async_runtime::spawn(async move {
+    if let Err(err) = crate::mcp::commands::sync_local_source(&sync_state).await {
+      warn!("mcp auto sync failed: {}", err);
+    }
+  });
+}
+
+/// Routes `deeting://` links to the installer, including the one the app
+/// was launched with.
+fn register_deep_links(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+  // Linux and Windows dev builds are not installed, so the scheme has to be
+  // registered at runtime.
+  #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+  app.deep_link().register_all()?;
+
+  let handle = app.clone();
+  app.deep_link().on_open_url(move |event| {
+    for url in event.urls() {
+      let handle = handle.clone();
+      tauri::async_runtime::spawn(async move {
+        crate::mcp::deep_link::handle_url(&handle, &url).await;
+      });
+    }
+  });
+  if let Some(urls) = app.deep_link().get_current()? {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+      for url in urls {
+        crate::mcp::deep_link::handle_url(&handle, &url).await;
+      }
+    });
+  }
+  Ok(())
+}
+
 fn resolve_database_url() -> Result<String, McpError> {
-  let db_path = std::env::var("DESKTOP_DB_PATH").unwrap_or_else(|_| default_db_path());
+  let db_path = match std::env::var("DESKTOP_DB_PATH") {
+    Ok(db_path) => db_path,
+    Err(_) => {
+      let default = crate::paths::default_db_path();
+      if let Err(err) = crate::paths::migrate_legacy_db(&default) {
+        warn!("failed to move the database from its legacy location: {}", err);
+      }
+      default.to_string_lossy().into_owned()
+    }
+  };
   if db_path == ":memory:" {
     return Ok("sqlite::memory:".to_string());
   }
   if db_path.starts_with("sqlite:") {
     return Ok(db_path);
   }
-  let expanded = expand_path(&db_path);
+  let expanded = crate::paths::expand_path(&db_path);
   if let Some(parent) = expanded.parent() {
     std::fs::create_dir_all(parent)
       .map_err(|err| McpError::Storage(err.to_string()))?;
   }
-  Ok(format!("sqlite://{}", expanded.to_string_lossy()))
-}
-
-fn default_db_path() -> String {
-  if let Ok(home) = std::env::var("HOME") {
-    return format!("{home}/.config/deeting/deeting.db");
-  }
-  "deeting.db".to_string()
+  // `mode=rwc` creates the file, e.g. in a fresh portable data directory.
+  Ok(format!("sqlite://{}?mode=rwc", expanded.to_string_lossy()))
 }
 
 fn resolve_cloud_base_url() -> String {
   std::env::var("NEXT_PUBLIC_API_BASE_URL")
     .unwrap_or_else(|_| "http://127.0.0.1:8000".to_string())
 }
-
-fn now_rfc3339() -> String {
-  time::OffsetDateTime::now_utc()
-    .format(&time::format_description::well_known::Rfc3339)
-    .unwrap_or_else(|_| "".to_string())
-}
@@ -0,0 +1,322 @@
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use mcp_core::env::is_secret_env_key;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+use zip::ZipArchive;
+
+use crate::autostart::LAUNCH_SETTINGS_KEY;
+use crate::mcp::diagnostics::{to_json, write_zip};
+use crate::mcp::error::McpError;
+use crate::mcp::types::{
+    AppStateImportSummary, LaunchSettings, McpSourceType, McpTool, McpToolStatus, McpTrustLevel,
+};
+use crate::mcp::McpRuntimeState;
+
+/// Raised when the layout changes in a way older versions cannot read;
+/// added or removed columns do not need it.
+const ARCHIVE_VERSION: u32 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+const SOURCES: (&str, &str) = ("mcp_sources", "sources.json");
+const TOOLS: (&str, &str) = ("mcp_tools", "tools.json");
+const ASSISTANTS: (&str, &str) = ("assistants", "assistants.json");
+const MESSAGES: (&str, &str) = ("assistant_messages", "conversations.json");
+const SETTINGS: (&str, &str) = ("app_settings", "settings.json");
+/// Restore order: sources before the tools and assistants before the
+/// messages that reference them.
+const TABLES: [(&str, &str); 5] = [SOURCES, TOOLS, ASSISTANTS, MESSAGES, SETTINGS];
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    archive_version: u32,
+    exported_at: String,
+    app_version: String,
+    /// `PRAGMA user_version` of the exporting database.
+    schema_version: i64,
+    secrets_included: bool,
+    /// The exporting machine's built-in sources. Every machine has its own,
+    /// so their tools move to this machine's built-in sources on import.
+    local_source_id: Option<String>,
+    cloud_source_id: Option<String>,
+}
+
+/// Writes sources, tools, assistants with their conversations and settings
+/// to a zip, one JSON file per table, for moving to another machine. Secret
+/// env values are blanked unless `include_secrets`, and their tools marked
+/// new so they ask to be configured again. Returns the path written.
+pub async fn export(
+    app: &AppHandle,
+    state: &McpRuntimeState,
+    path: &Path,
+    include_secrets: bool,
+) -> Result<PathBuf, McpError> {
+    let store = &state.store;
+    let manifest = Manifest {
+        archive_version: ARCHIVE_VERSION,
+        exported_at: now_rfc3339(),
+        app_version: app.package_info().version.to_string(),
+        schema_version: store.schema_info().await?.user_version,
+        secrets_included: include_secrets,
+        local_source_id: store
+            .find_source_by_type(McpSourceType::Local)
+            .await?
+            .map(|source| source.id),
+        cloud_source_id: store
+            .find_source_by_type(McpSourceType::Cloud)
+            .await?
+            .map(|source| source.id),
+    };
+    let mut files = vec![(MANIFEST_FILE.to_string(), to_json(&manifest)?)];
+    for (table, file) in TABLES {
+        let mut rows = store.dump_table(table).await?;
+        if table == TOOLS.0 && !include_secrets {
+            for row in &mut rows {
+                if strip_secrets(row) {
+                    row["is_new"] = Value::from(1);
+                }
+            }
+        }
+        files.push((file.to_string(), to_json(&rows)?));
+    }
+
+    let path = path.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || write_zip(&path, files).map(|_| path))
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?
+}
+
+/// Restores an archive written by [`export`], updating rows with the same
+/// id and leaving everything else in place. Tools come back stopped; all of
+/// them must be stopped here first.
+///
+/// An archive may come from anywhere, so nothing in it is trusted more than
+/// a fresh sync would be: official sources come back as community, every
+/// tool needs approval again before its first start, and tools of the
+/// exported cloud source are left for the next cloud sync, since this
+/// machine's cloud source starts tools without approval. A tool whose name
+/// or identifier is already taken by another tool of its source is skipped
+/// rather than replacing that tool.
+pub async fn import(
+    app: &AppHandle,
+    state: &McpRuntimeState,
+    path: &Path,
+) -> Result<AppStateImportSummary, McpError> {
+    if state.process_manager.running_count().await > 0 {
+        return Err(McpError::validation(
+            "stop all tools before importing app state",
+        ));
+    }
+    let archive = path.to_path_buf();
+    let mut files = tauri::async_runtime::spawn_blocking(move || read_zip(&archive))
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))??;
+    let manifest: Manifest = parse(&mut files, MANIFEST_FILE)?;
+    if manifest.archive_version > ARCHIVE_VERSION {
+        return Err(McpError::validation(
+            "the archive was written by a newer version of Deeting",
+        ));
+    }
+
+    let store = &state.store;
+    let local_source = store.ensure_local_source().await?;
+    let existing = store.list_tools().await?;
+    // Every machine has its own built-in sources; the exported ones are not
+    // restored.
+    let mut local_ids: HashSet<String> = manifest.local_source_id.into_iter().collect();
+    let mut cloud_ids: HashSet<String> = manifest.cloud_source_id.into_iter().collect();
+    let mut skipped_tools = Vec::new();
+
+    let mut tables = Vec::new();
+    for (table, file) in TABLES {
+        let mut rows: Vec<Value> = parse(&mut files, file)?;
+        if table == SOURCES.0 {
+            rows.retain(|row| {
+                let id = row["id"].as_str().unwrap_or_default().to_string();
+                match row["source_type"].as_str() {
+                    Some(kind) if kind == McpSourceType::Local.as_str() => {
+                        local_ids.insert(id);
+                        false
+                    }
+                    Some(kind) if kind == McpSourceType::Cloud.as_str() => {
+                        cloud_ids.insert(id);
+                        false
+                    }
+                    _ => !local_ids.contains(&id) && !cloud_ids.contains(&id),
+                }
+            });
+            for row in &mut rows {
+                if row["trust_level"].as_str() == Some(McpTrustLevel::Official.as_str()) {
+                    row["trust_level"] = Value::from(McpTrustLevel::Community.as_str());
+                }
+            }
+        }
+        if table == TOOLS.0 {
+            rows.retain_mut(|row| {
+                let source_id = row["source_id"].as_str().unwrap_or_default().to_string();
+                if local_ids.contains(&source_id) {
+                    row["source_id"] = Value::String(local_source.id.clone());
+                }
+                let name = row["name"].as_str().unwrap_or_default().to_string();
+                if cloud_ids.contains(&source_id) || clashes_with_existing(row, &existing) {
+                    skipped_tools.push(name);
+                    return false;
+                }
+                row["status"] = Value::from(McpToolStatus::Stopped.as_str());
+                row["ping_ms"] = Value::Null;
+                row["approved_at"] = Value::Null;
+                true
+            });
+        }
+        tables.push((table, rows));
+    }
+    let count = |name: &str| {
+        tables
+            .iter()
+            .find(|(table, _)| *table == name)
+            .map_or(0, |(_, rows)| rows.len())
+    };
+    let summary = AppStateImportSummary {
+        sources: count(SOURCES.0),
+        tools: count(TOOLS.0),
+        assistants: count(ASSISTANTS.0),
+        messages: count(MESSAGES.0),
+        settings: count(SETTINGS.0),
+        secrets_included: manifest.secrets_included,
+        skipped_tools,
+    };
+    store.restore_tables(&tables).await?;
+
+    let settings = crate::mcp::settings::load(state).await?;
+    crate::mcp::settings::apply(state, &settings).await;
+    let launch: LaunchSettings = store.get_setting(LAUNCH_SETTINGS_KEY).await?;
+    if let Err(err) = crate::autostart::apply(app, &launch) {
+        warn!("failed to apply imported launch settings: {}", err);
+    }
+    for tool in store.list_tools().await? {
+        state.process_manager.apply_log_to_file(&tool);
+        state.process_manager.publish_status(&tool.id).await;
+    }
+    state.process_manager.reload_attention().await?;
+    crate::tray::refresh(app);
+    Ok(summary)
+}
+
+/// Whether another tool of the row's source already has its name or
+/// identifier; restoring the row would replace that tool.
+fn clashes_with_existing(row: &Value, existing: &[McpTool]) -> bool {
+    let id = row["id"].as_str();
+    let source_id = row["source_id"].as_str();
+    let name = row["name"].as_str();
+    let identifier = row["identifier"].as_str();
+    existing.iter().any(|tool| {
+        Some(tool.id.as_str()) != id
+            && tool.source_id.as_deref() == source_id
+            && (Some(tool.name.as_str()) == name
+                || (identifier.is_some() && tool.identifier.as_deref() == identifier))
+    })
+}
+
+/// Blanks secret env values in a `mcp_tools` row: its `env` column and the
+/// `env` of both configs. Returns whether any value was blanked.
+fn strip_secrets(row: &mut Value) -> bool {
+    let Some(row) = row.as_object_mut() else {
+        return false;
+    };
+    let mut stripped = false;
+    for column in ["env", "config_json", "pending_config_json"] {
+        let Some(raw) = row.get(column).and_then(Value::as_str) else {
+            continue;
+        };
+        let Ok(mut parsed) = serde_json::from_str::<Value>(raw) else {
+            continue;
+        };
+        let env = if column == "env" {
+            Some(&mut parsed)
+        } else {
+            parsed.get_mut("env")
+        };
+        let Some(Value::Object(env)) = env else {
+            continue;
+        };
+        let mut changed = false;
+        for (key, value) in env.iter_mut() {
+            if is_secret_env_key(key) && value.as_str().is_some_and(|text| !text.is_empty()) {
+                *value = Value::String(String::new());
+                changed = true;
+            }
+        }
+        if changed {
+            row.insert(column.to_string(), Value::String(parsed.to_string()));
+            stripped = true;
+        }
+    }
+    stripped
+}
+
+fn read_zip(path: &Path) -> Result<HashMap<String, String>, McpError> {
+    let invalid = |err: &dyn std::fmt::Display| {
+        McpError::validation(format!(
+            "{}: not an app state archive: {err}",
+            path.display()
+        ))
+    };
+    let file = std::fs::File::open(path)
+        .map_err(|err| McpError::Storage(format!("{}: {err}", path.display())))?;
+    let mut zip = ZipArchive::new(file).map_err(|err| invalid(&err))?;
+    let mut files = HashMap::new();
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(|err| invalid(&err))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut text = String::new();
+        entry
+            .read_to_string(&mut text)
+            .map_err(|err| invalid(&err))?;
+        files.insert(name, text);
+    }
+    Ok(files)
+}
+
+fn parse<T: serde::de::DeserializeOwned>(
+    files: &mut HashMap<String, String>,
+    name: &str,
+) -> Result<T, McpError> {
+    let text = files
+        .remove(name)
+        .ok_or_else(|| McpError::validation(format!("the archive has no {name}")))?;
+    serde_json::from_str(&text).map_err(|err| McpError::validation(format!("{name}: {err}")))
+}
+
+fn now_rfc3339() -> String {
+    time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn blanks_secret_env_values_in_columns_and_configs() {
+        let mut row = json!({
+            "id": "t1",
+            "env": r#"{"GITHUB_TOKEN":"ghp_x","REGION":"eu"}"#,
+            "config_json": r#"{"command":"npx","env":{"GITHUB_TOKEN":"ghp_x"}}"#,
+            "pending_config_json": null,
+        });
+        assert!(strip_secrets(&mut row));
+        let env: Value = serde_json::from_str(row["env"].as_str().unwrap()).unwrap();
+        assert_eq!(env, json!({"GITHUB_TOKEN": "", "REGION": "eu"}));
+        let config: Value = serde_json::from_str(row["config_json"].as_str().unwrap()).unwrap();
+        assert_eq!(config["env"], json!({"GITHUB_TOKEN": ""}));
+        assert!(!strip_secrets(&mut row));
+    }
+}
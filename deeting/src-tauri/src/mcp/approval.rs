@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{oneshot, Mutex};
+use uuid::Uuid;
+
+use crate::mcp::process::{config_healthcheck, config_hooks};
+use crate::mcp::types::{McpSource, McpTool, McpTrustLevel, ToolHooks};
+
+pub const TOOL_APPROVAL_EVENT: &str = "mcp-tool-approval";
+
+/// A start waiting for the user to approve an untrusted tool on its first run.
+/// It lists every command line the approval lets the tool run; changing any
+/// of them on a later sync asks again (see `store::update_tool`).
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolApprovalRequest {
+    pub id: String,
+    pub tool_id: String,
+    pub tool_name: String,
+    pub source_name: String,
+    pub trust_level: McpTrustLevel,
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    pub hooks: ToolHooks,
+    pub healthcheck_command: Option<String>,
+}
+
+impl ToolApprovalRequest {
+    fn new(tool: &McpTool, source: &McpSource) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            tool_id: tool.id.clone(),
+            tool_name: tool.name.clone(),
+            source_name: source.name.clone(),
+            trust_level: source.trust_level.clone(),
+            command: tool.command.clone(),
+            args: tool.args.clone().unwrap_or_default(),
+            hooks: config_hooks(&tool.config_json),
+            healthcheck_command: config_healthcheck(&tool.config_json)
+                .and_then(|check| check.command),
+        }
+    }
+}
+
+struct PendingApproval {
+    request: ToolApprovalRequest,
+    answer: oneshot::Sender<bool>,
+}
+
+/// Starts parked until the user answers their approval dialog.
+#[derive(Clone, Default)]
+pub struct PendingApprovals(Arc<Mutex<HashMap<String, PendingApproval>>>);
+
+impl PendingApprovals {
+    pub async fn list(&self) -> Vec<ToolApprovalRequest> {
+        self.0
+            .lock()
+            .await
+            .values()
+            .map(|pending| pending.request.clone())
+            .collect()
+    }
+
+    /// Hands the user's answer to the waiting start. Returns `false` when the
+    /// request is no longer pending.
+    pub async fn answer(&self, id: &str, approved: bool) -> bool {
+        match self.0.lock().await.remove(id) {
+            Some(pending) => pending.answer.send(approved).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Records a request, replacing (and so cancelling) an earlier one for the
+    /// same tool.
+    async fn insert(&self, request: ToolApprovalRequest) -> oneshot::Receiver<bool> {
+        let (answer, receiver) = oneshot::channel();
+        let mut pending = self.0.lock().await;
+        pending.retain(|_, item| item.request.tool_id != request.tool_id);
+        pending.insert(request.id.clone(), PendingApproval { request, answer });
+        receiver
+    }
+}
+
+/// Asks the UI to approve `tool` and waits for the answer. A request that is
+/// replaced or dropped before it is answered counts as declined.
+pub async fn request(
+    app: &AppHandle,
+    approvals: &PendingApprovals,
+    tool: &McpTool,
+    source: &McpSource,
+) -> bool {
+    let request = ToolApprovalRequest::new(tool, source);
+    let receiver = approvals.insert(request.clone()).await;
+    crate::tray::show_main_window(app);
+    let _ = app.emit(TOOL_APPROVAL_EVENT, request);
+    receiver.await.unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(id: &str, tool_id: &str) -> ToolApprovalRequest {
+        ToolApprovalRequest {
+            id: id.to_string(),
+            tool_id: tool_id.to_string(),
+            tool_name: tool_id.to_string(),
+            source_name: "GitHub".to_string(),
+            trust_level: McpTrustLevel::Community,
+            command: Some("npx".to_string()),
+            args: Vec::new(),
+            hooks: ToolHooks::default(),
+            healthcheck_command: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_answers_and_cancels_replaced_requests() {
+        let approvals = PendingApprovals::default();
+        let first = approvals.insert(request("a", "tool")).await;
+        let second = approvals.insert(request("b", "tool")).await;
+        assert!(first.await.is_err());
+        assert_eq!(approvals.list().await.len(), 1);
+
+        assert!(approvals.answer("b", true).await);
+        assert!(second.await.unwrap());
+        assert!(!approvals.answer("b", true).await);
+    }
+}
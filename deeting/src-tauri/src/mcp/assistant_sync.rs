@@ -0,0 +1,449 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::mcp::error::McpError;
+use crate::mcp::store::CloudAssistantFields;
+use crate::mcp::types::{
+    AssistantConflictStrategy, AssistantSyncConflict, AssistantSyncResult, LocalAssistant,
+};
+use crate::mcp::{CloudAccount, McpRuntimeState};
+
+const OWNED_PAGE_SIZE: usize = 50;
+const SUMMARY_MAX_CHARS: usize = 200;
+const INITIAL_VERSION: &str = "0.1.0";
+
+#[derive(Debug, Clone, Deserialize)]
+struct CloudAssistant {
+    id: String,
+    visibility: String,
+    summary: Option<String>,
+    icon_id: Option<String>,
+    current_version_id: Option<String>,
+    #[serde(default)]
+    versions: Vec<CloudAssistantVersion>,
+    updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct CloudAssistantVersion {
+    id: String,
+    version: String,
+    name: String,
+    description: Option<String>,
+    system_prompt: String,
+    #[serde(default)]
+    model_config: Option<Value>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudAssistantPage {
+    items: Vec<CloudAssistant>,
+    next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CloudAssistantVersionBody {
+    version: String,
+    name: String,
+    description: Option<String>,
+    system_prompt: String,
+    model_config: Value,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct CloudAssistantBody {
+    visibility: String,
+    summary: Option<String>,
+    icon_id: Option<String>,
+    version: CloudAssistantVersionBody,
+}
+
+/// Which side wins when resolving a conflict reported by a `prompt` sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictResolution {
+    KeepLocal,
+    KeepCloud,
+}
+
+impl ConflictResolution {
+    pub fn parse(value: &str) -> Result<Self, McpError> {
+        match value {
+            "local" => Ok(Self::KeepLocal),
+            "cloud" => Ok(Self::KeepCloud),
+            other => Err(McpError::Validation(format!(
+                "unknown conflict resolution: {other}"
+            ))),
+        }
+    }
+}
+
+/// Pulls owned assistants from the cloud, pushes local changes back and
+/// reconciles deletions on both sides.
+///
+/// An assistant counts as changed locally when it was edited after its last
+/// sync, and as changed remotely when the cloud `updated_at` differs from the
+/// one recorded at that sync. When both changed, `strategy` decides whether
+/// the newer side wins or the pair is reported back as a conflict untouched.
+///
+/// Assistants are kept with the primary cloud account.
+pub async fn sync_assistants(
+    state: &McpRuntimeState,
+    strategy: AssistantConflictStrategy,
+) -> Result<AssistantSyncResult, McpError> {
+    let account = state.cloud_account(None).await?;
+    let account = &account;
+    let remote = fetch_owned_assistants(state, account).await?;
+    let remote_by_id: HashMap<&str, &CloudAssistant> =
+        remote.iter().map(|item| (item.id.as_str(), item)).collect();
+    let linked = state.store.list_cloud_linked_assistants().await?;
+    let linked_ids: HashSet<String> = linked
+        .iter()
+        .filter_map(|assistant| assistant.cloud_id.clone())
+        .collect();
+
+    let mut result = AssistantSyncResult::default();
+
+    for local in &linked {
+        let Some(cloud_id) = local.cloud_id.as_deref() else {
+            continue;
+        };
+        let local_changed = is_locally_changed(local);
+
+        if local.is_deleted {
+            if remote_by_id.contains_key(cloud_id) {
+                delete_remote(state, account, cloud_id).await?;
+                result.deleted += 1;
+            }
+            state
+                .store
+                .mark_assistant_synced(&local.id, None, None)
+                .await?;
+            continue;
+        }
+
+        let Some(cloud) = remote_by_id.get(cloud_id) else {
+            // Removed in the cloud. Keep local edits made since the last sync by
+            // re-publishing them; otherwise follow the deletion.
+            if local_changed {
+                let created = create_remote(state, account, local).await?;
+                state
+                    .store
+                    .mark_assistant_synced(&local.id, Some(&created.id), Some(&created.updated_at))
+                    .await?;
+                result.pushed += 1;
+            } else {
+                state.store.delete_local_assistant(&local.id).await?;
+                state
+                    .store
+                    .mark_assistant_synced(&local.id, None, None)
+                    .await?;
+                result.deleted += 1;
+            }
+            continue;
+        };
+
+        let remote_changed = local.cloud_updated_at.as_deref() != Some(cloud.updated_at.as_str());
+        match (local_changed, remote_changed) {
+            (false, false) => {}
+            (false, true) => {
+                pull(state, Some(&local.id), cloud).await?;
+                result.pulled += 1;
+            }
+            (true, false) => {
+                push_update(state, account, local, cloud).await?;
+                result.pushed += 1;
+            }
+            (true, true) => match strategy {
+                AssistantConflictStrategy::LastWriteWins => {
+                    if is_local_newer(local, cloud) {
+                        push_update(state, account, local, cloud).await?;
+                        result.pushed += 1;
+                    } else {
+                        pull(state, Some(&local.id), cloud).await?;
+                        result.pulled += 1;
+                    }
+                }
+                AssistantConflictStrategy::Prompt => {
+                    result.conflicts.push(AssistantSyncConflict {
+                        assistant_id: local.id.clone(),
+                        cloud_id: cloud.id.clone(),
+                        name: local.name.clone(),
+                        local_updated_at: local.updated_at.clone(),
+                        cloud_updated_at: cloud.updated_at.clone(),
+                    });
+                }
+            },
+        }
+    }
+
+    for cloud in remote.iter().filter(|item| !linked_ids.contains(&item.id)) {
+        pull(state, None, cloud).await?;
+        result.pulled += 1;
+    }
+
+    for local in state.store.list_local_assistants().await? {
+        if local.cloud_id.is_some() {
+            continue;
+        }
+        let created = create_remote(state, account, &local).await?;
+        state
+            .store
+            .mark_assistant_synced(&local.id, Some(&created.id), Some(&created.updated_at))
+            .await?;
+        result.pushed += 1;
+    }
+
+    Ok(result)
+}
+
+/// Settles a conflict reported by a `prompt` sync by forcing one side over
+/// the other.
+pub async fn resolve_assistant_conflict(
+    state: &McpRuntimeState,
+    assistant_id: &str,
+    resolution: ConflictResolution,
+) -> Result<LocalAssistant, McpError> {
+    let local = state
+        .store
+        .get_local_assistant(assistant_id)
+        .await?
+        .ok_or_else(|| McpError::NotFound(format!("assistant {assistant_id} not found")))?;
+    let cloud_id = local
+        .cloud_id
+        .clone()
+        .ok_or_else(|| McpError::validation("assistant is not linked to the cloud"))?;
+    let account = state.cloud_account(None).await?;
+    let cloud = fetch_owned_assistants(state, &account)
+        .await?
+        .into_iter()
+        .find(|item| item.id == cloud_id)
+        .ok_or_else(|| McpError::NotFound(format!("cloud assistant {cloud_id} not found")))?;
+
+    match resolution {
+        ConflictResolution::KeepLocal => push_update(state, &account, &local, &cloud).await?,
+        ConflictResolution::KeepCloud => {
+            pull(state, Some(&local.id), &cloud).await?;
+        }
+    }
+
+    state
+        .store
+        .get_local_assistant(assistant_id)
+        .await?
+        .ok_or_else(|| McpError::NotFound("assistant missing after sync".to_string()))
+}
+
+fn is_locally_changed(local: &LocalAssistant) -> bool {
+    let Some(synced_at) = local.cloud_synced_at.as_deref() else {
+        return true;
+    };
+    match (
+        parse_timestamp(&local.updated_at),
+        parse_timestamp(synced_at),
+    ) {
+        (Some(updated_at), Some(synced_at)) => updated_at > synced_at,
+        _ => local.updated_at != synced_at,
+    }
+}
+
+fn is_local_newer(local: &LocalAssistant, cloud: &CloudAssistant) -> bool {
+    match (
+        parse_timestamp(&local.updated_at),
+        parse_timestamp(&cloud.updated_at),
+    ) {
+        (Some(local_at), Some(cloud_at)) => local_at > cloud_at,
+        // The cloud copy is authoritative when timestamps cannot be compared.
+        _ => false,
+    }
+}
+
+fn parse_timestamp(value: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(value, &Rfc3339)
+        .or_else(|_| OffsetDateTime::parse(&format!("{value}Z"), &Rfc3339))
+        .ok()
+}
+
+fn current_version(cloud: &CloudAssistant) -> Option<&CloudAssistantVersion> {
+    cloud
+        .current_version_id
+        .as_deref()
+        .and_then(|id| cloud.versions.iter().find(|version| version.id == id))
+        .or_else(|| cloud.versions.last())
+}
+
+fn next_version(current: &str) -> String {
+    let mut parts: Vec<u64> = current
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    parts.resize(3, 0);
+    parts[2] += 1;
+    format!("{}.{}.{}", parts[0], parts[1], parts[2])
+}
+
+async fn pull(
+    state: &McpRuntimeState,
+    local_id: Option<&str>,
+    cloud: &CloudAssistant,
+) -> Result<String, McpError> {
+    let version = current_version(cloud).ok_or_else(|| {
+        McpError::Validation(format!("cloud assistant {} has no versions", cloud.id))
+    })?;
+    let fields = CloudAssistantFields {
+        name: version.name.clone(),
+        description: version
+            .description
+            .clone()
+            .or_else(|| cloud.summary.clone()),
+        avatar: cloud.icon_id.clone(),
+        system_prompt: version.system_prompt.clone(),
+        model_config: version.model_config.clone(),
+        tags: version.tags.clone(),
+        visibility: cloud.visibility.clone(),
+        cloud_id: cloud.id.clone(),
+        cloud_updated_at: cloud.updated_at.clone(),
+    };
+    state.store.save_cloud_assistant(local_id, fields).await
+}
+
+async fn push_update(
+    state: &McpRuntimeState,
+    account: &CloudAccount,
+    local: &LocalAssistant,
+    cloud: &CloudAssistant,
+) -> Result<(), McpError> {
+    let version = current_version(cloud)
+        .map(|version| next_version(&version.version))
+        .unwrap_or_else(|| INITIAL_VERSION.to_string());
+    let body = build_body(local, version);
+    let url = account.url(&format!("/api/v1/assistants/{}", cloud.id));
+    let response = account
+        .send(&state.client, |token| {
+            state.client.patch(&url).bearer_auth(token).json(&body)
+        })
+        .await?;
+    let updated: CloudAssistant = read_json(response, "cloud assistant update").await?;
+    state
+        .store
+        .mark_assistant_synced(&local.id, Some(&updated.id), Some(&updated.updated_at))
+        .await
+}
+
+async fn create_remote(
+    state: &McpRuntimeState,
+    account: &CloudAccount,
+    local: &LocalAssistant,
+) -> Result<CloudAssistant, McpError> {
+    let body = build_body(local, INITIAL_VERSION.to_string());
+    let url = account.url("/api/v1/assistants");
+    let response = account
+        .send(&state.client, |token| {
+            state.client.post(&url).bearer_auth(token).json(&body)
+        })
+        .await?;
+    read_json(response, "cloud assistant create").await
+}
+
+async fn delete_remote(
+    state: &McpRuntimeState,
+    account: &CloudAccount,
+    cloud_id: &str,
+) -> Result<(), McpError> {
+    let url = account.url(&format!("/api/v1/assistants/{cloud_id}"));
+    let response = account
+        .send(&state.client, |token| {
+            state.client.delete(&url).bearer_auth(token)
+        })
+        .await?;
+    if !response.status().is_success() && response.status() != reqwest::StatusCode::NOT_FOUND {
+        return Err(McpError::Network(format!(
+            "cloud assistant delete failed: {}",
+            response.status()
+        )));
+    }
+    Ok(())
+}
+
+async fn fetch_owned_assistants(
+    state: &McpRuntimeState,
+    account: &CloudAccount,
+) -> Result<Vec<CloudAssistant>, McpError> {
+    let url = account.url("/api/v1/assistants/owned");
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let mut query = vec![("size", OWNED_PAGE_SIZE.to_string())];
+        if let Some(cursor) = cursor.as_ref() {
+            query.push(("cursor", cursor.clone()));
+        }
+        let response = account
+            .send(&state.client, |token| {
+                state.client.get(&url).query(&query).bearer_auth(token)
+            })
+            .await?;
+        let page: CloudAssistantPage = read_json(response, "cloud assistant list").await?;
+        items.extend(page.items);
+        match page.next_cursor {
+            Some(next) if !next.is_empty() => cursor = Some(next),
+            _ => break,
+        }
+    }
+    Ok(items)
+}
+
+fn build_body(local: &LocalAssistant, version: String) -> CloudAssistantBody {
+    CloudAssistantBody {
+        visibility: local.visibility.clone(),
+        summary: local
+            .description
+            .as_ref()
+            .map(|description| description.chars().take(SUMMARY_MAX_CHARS).collect()),
+        icon_id: local.avatar.clone(),
+        version: CloudAssistantVersionBody {
+            version,
+            name: local.name.clone(),
+            description: local.description.clone(),
+            system_prompt: local.system_prompt.clone(),
+            model_config: local
+                .model_config
+                .clone()
+                .unwrap_or_else(|| Value::Object(Default::default())),
+            tags: local.tags.clone(),
+        },
+    }
+}
+
+async fn read_json<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    action: &str,
+) -> Result<T, McpError> {
+    if !response.status().is_success() {
+        return Err(McpError::Network(format!(
+            "{action} failed: {}",
+            response.status()
+        )));
+    }
+    response
+        .json()
+        .await
+        .map_err(|err| McpError::Network(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_version;
+
+    #[test]
+    fn next_version_bumps_patch() {
+        assert_eq!(next_version("0.1.0"), "0.1.1");
+        assert_eq!(next_version("2.3"), "2.3.1");
+        assert_eq!(next_version("beta"), "0.0.1");
+    }
+}
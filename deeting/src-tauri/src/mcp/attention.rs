@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use crate::mcp::types::{McpConflictStatus, McpTool, McpToolStatus};
+
+/// Whether a tool is waiting on the user: it crashed, or sync staged an
+/// update or a name conflict for it.
+pub fn needs_attention(tool: &McpTool) -> bool {
+    tool.status == McpToolStatus::Crashed
+        || matches!(
+            tool.conflict_status,
+            McpConflictStatus::Conflict | McpConflictStatus::UpdateAvailable
+        )
+}
+
+/// Ids of the tools that need attention, kept current by
+/// `ProcessManager::publish_status` so the tray tooltip and dock badge can
+/// read a count without going back to the store.
+#[derive(Clone, Default)]
+pub struct AttentionAggregate {
+    tools: Arc<Mutex<HashSet<String>>>,
+}
+
+impl AttentionAggregate {
+    /// Replaces the aggregate with the given tools, e.g. at startup or after
+    /// a source and its tools were deleted.
+    pub fn reset(&self, tools: &[McpTool]) {
+        let mut ids = self.lock();
+        ids.clear();
+        ids.extend(
+            tools
+                .iter()
+                .filter(|tool| needs_attention(tool))
+                .map(|tool| tool.id.clone()),
+        );
+    }
+
+    /// Records a tool's latest state, or its removal when `tool` is `None`,
+    /// and returns the new count.
+    pub fn update(&self, tool_id: &str, tool: Option<&McpTool>) -> usize {
+        let mut ids = self.lock();
+        if tool.is_some_and(needs_attention) {
+            ids.insert(tool_id.to_string());
+        } else {
+            ids.remove(tool_id);
+        }
+        ids.len()
+    }
+
+    pub fn count(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<String>> {
+        self.tools.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::types::McpSourceType;
+
+    fn tool(id: &str, status: McpToolStatus, conflict_status: McpConflictStatus) -> McpTool {
+        McpTool {
+            id: id.to_string(),
+            identifier: None,
+            name: id.to_string(),
+            source_type: McpSourceType::Local,
+            source_id: None,
+            status,
+            ping_ms: None,
+            capabilities: Vec::new(),
+            description: String::new(),
+            error: None,
+            command: None,
+            args: None,
+            env: None,
+            config_json: "{}".to_string(),
+            pending_config_json: None,
+            config_hash: String::new(),
+            pending_config_hash: None,
+            conflict_status,
+            is_read_only: false,
+            is_new: false,
+            approved_at: None,
+            market_tool_id: None,
+            auto_start: false,
+            log_to_file: false,
+            log_buffer_size: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn counts_crashed_and_conflicting_tools() {
+        let aggregate = AttentionAggregate::default();
+        aggregate.reset(&[
+            tool("a", McpToolStatus::Crashed, McpConflictStatus::None),
+            tool("b", McpToolStatus::Healthy, McpConflictStatus::UpdateAvailable),
+            tool("c", McpToolStatus::Healthy, McpConflictStatus::None),
+        ]);
+        assert_eq!(aggregate.count(), 2);
+
+        let conflict = tool("c", McpToolStatus::Stopped, McpConflictStatus::Conflict);
+        assert_eq!(aggregate.update("c", Some(&conflict)), 3);
+        let recovered = tool("a", McpToolStatus::Healthy, McpConflictStatus::None);
+        assert_eq!(aggregate.update("a", Some(&recovered)), 2);
+        assert_eq!(aggregate.update("b", None), 1);
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use log::warn;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Mutex, RwLock};
+
+use crate::mcp::error::McpError;
+
+const KEYRING_SERVICE: &str = "com.deeting.app.cloud";
+pub const DEFAULT_CLOUD_ACCOUNT: &str = "default";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize)]
+struct LoginBody<'a> {
+    email: &'a str,
+    code: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct RefreshBody<'a> {
+    refresh_token: &'a str,
+}
+
+/// Holds the cloud access/refresh token pair for one account. Tokens are kept
+/// in the OS keychain so background sync survives restarts, and the access
+/// token is refreshed transparently when the cloud answers 401.
+#[derive(Clone)]
+pub struct CloudAuth {
+    account: String,
+    tokens: Arc<RwLock<Option<CloudTokens>>>,
+    refresh_lock: Arc<Mutex<()>>,
+}
+
+impl CloudAuth {
+    pub fn load(account: &str) -> Self {
+        let tokens = match read_keyring(account) {
+            Ok(tokens) => tokens,
+            Err(err) => {
+                warn!("failed to load cloud session for {}: {}", account, err);
+                None
+            }
+        };
+        Self {
+            account: account.to_string(),
+            tokens: Arc::new(RwLock::new(tokens)),
+            refresh_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub async fn has_session(&self) -> bool {
+        self.tokens.read().await.is_some()
+    }
+
+    pub async fn set_tokens(&self, tokens: CloudTokens) -> Result<(), McpError> {
+        write_keyring(&self.account, Some(&tokens))?;
+        *self.tokens.write().await = Some(tokens);
+        Ok(())
+    }
+
+    pub async fn clear(&self) -> Result<(), McpError> {
+        write_keyring(&self.account, None)?;
+        *self.tokens.write().await = None;
+        Ok(())
+    }
+
+    pub async fn login(
+        &self,
+        client: &Client,
+        base_url: &str,
+        email: &str,
+        code: &str,
+    ) -> Result<(), McpError> {
+        let url = format!("{}/api/v1/auth/login", base_url.trim_end_matches('/'));
+        let response = client
+            .post(&url)
+            .json(&LoginBody { email, code })
+            .send()
+            .await
+            .map_err(|err| McpError::Network(err.to_string()))?;
+        if !response.status().is_success() {
+            return Err(McpError::Network(format!(
+                "cloud login failed: {}",
+                response.status()
+            )));
+        }
+        let tokens: CloudTokens = response
+            .json()
+            .await
+            .map_err(|err| McpError::Network(err.to_string()))?;
+        self.set_tokens(tokens).await
+    }
+
+    pub async fn logout(&self, client: &Client, base_url: &str) -> Result<(), McpError> {
+        if let Some(tokens) = self.tokens.read().await.clone() {
+            let url = format!("{}/api/v1/auth/logout", base_url.trim_end_matches('/'));
+            let result = client
+                .post(&url)
+                .bearer_auth(&tokens.access_token)
+                .header("X-Refresh-Token", &tokens.refresh_token)
+                .send()
+                .await;
+            if let Err(err) = result {
+                warn!("cloud logout request failed: {}", err);
+            }
+        }
+        self.clear().await
+    }
+
+    /// Sends a request built by `build` with the current access token,
+    /// refreshing once and retrying when the cloud rejects it with 401.
+    pub async fn send<F>(&self, client: &Client, base_url: &str, build: F) -> Result<Response, McpError>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        let access_token = self
+            .tokens
+            .read()
+            .await
+            .as_ref()
+            .map(|tokens| tokens.access_token.clone())
+            .ok_or_else(|| McpError::Validation("cloud session not found".to_string()))?;
+
+        let response = build(&access_token)
+            .send()
+            .await
+            .map_err(|err| McpError::Network(err.to_string()))?;
+        if response.status() != StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        let refreshed = self.refresh(client, base_url, &access_token).await?;
+        build(&refreshed.access_token)
+            .send()
+            .await
+            .map_err(|err| McpError::Network(err.to_string()))
+    }
+
+    async fn refresh(
+        &self,
+        client: &Client,
+        base_url: &str,
+        stale_access_token: &str,
+    ) -> Result<CloudTokens, McpError> {
+        let _guard = self.refresh_lock.lock().await;
+        let current = self
+            .tokens
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| McpError::Validation("cloud session not found".to_string()))?;
+        // Another caller refreshed while we waited for the lock.
+        if current.access_token != stale_access_token {
+            return Ok(current);
+        }
+
+        let url = format!("{}/api/v1/auth/refresh", base_url.trim_end_matches('/'));
+        let response = client
+            .post(&url)
+            .json(&RefreshBody {
+                refresh_token: &current.refresh_token,
+            })
+            .send()
+            .await
+            .map_err(|err| McpError::Network(err.to_string()))?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            self.clear().await?;
+            return Err(McpError::Network("cloud session expired".to_string()));
+        }
+        if !response.status().is_success() {
+            return Err(McpError::Network(format!(
+                "cloud token refresh failed: {}",
+                response.status()
+            )));
+        }
+        let tokens: CloudTokens = response
+            .json()
+            .await
+            .map_err(|err| McpError::Network(err.to_string()))?;
+        self.set_tokens(tokens.clone()).await?;
+        Ok(tokens)
+    }
+}
+
+/// Sessions for every Cloud-type source. The primary cloud source keeps the
+/// `default` keychain entry it always used; additional accounts are keyed by
+/// their source id and loaded on first use.
+#[derive(Clone)]
+pub struct CloudAccounts {
+    primary_source_id: String,
+    primary: CloudAuth,
+    others: Arc<RwLock<HashMap<String, CloudAuth>>>,
+}
+
+impl CloudAccounts {
+    pub fn new(primary_source_id: String, primary: CloudAuth) -> Self {
+        Self {
+            primary_source_id,
+            primary,
+            others: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn primary_source_id(&self) -> &str {
+        &self.primary_source_id
+    }
+
+    pub async fn auth_for(&self, source_id: &str) -> CloudAuth {
+        if source_id == self.primary_source_id {
+            return self.primary.clone();
+        }
+        if let Some(auth) = self.others.read().await.get(source_id) {
+            return auth.clone();
+        }
+        self.others
+            .write()
+            .await
+            .entry(source_id.to_string())
+            .or_insert_with(|| CloudAuth::load(source_id))
+            .clone()
+    }
+
+    /// Drops a removed account and deletes its tokens from the keychain.
+    pub async fn forget(&self, source_id: &str) -> Result<(), McpError> {
+        let auth = self.others.write().await.remove(source_id);
+        match auth {
+            Some(auth) => auth.clear().await,
+            None => write_keyring(source_id, None),
+        }
+    }
+}
+
+fn read_keyring(account: &str) -> Result<Option<CloudTokens>, McpError> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, account)
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+    match entry.get_password() {
+        Ok(raw) => serde_json::from_str(&raw)
+            .map(Some)
+            .map_err(|err| McpError::Storage(err.to_string())),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(err) => Err(McpError::Storage(err.to_string())),
+    }
+}
+
+fn write_keyring(account: &str, tokens: Option<&CloudTokens>) -> Result<(), McpError> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, account)
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+    match tokens {
+        Some(tokens) => {
+            let raw = serde_json::to_string(tokens).map_err(|err| McpError::Storage(err.to_string()))?;
+            entry
+                .set_password(&raw)
+                .map_err(|err| McpError::Storage(err.to_string()))
+        }
+        None => match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(McpError::Storage(err.to_string())),
+        },
+    }
+}
@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+use log::warn;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::mcp::commands::{
+    apply_cloud_subscription, sync_cloud_subscriptions_inner, CloudSubscriptionItem,
+};
+use crate::mcp::error::McpError;
+use crate::mcp::types::McpSourceType;
+use crate::mcp::{CloudAccount, McpRuntimeState};
+
+const SUBSCRIPTION_UPDATED_EVENT: &str = "subscription_updated";
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+const SESSION_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+struct SubscriptionUpdatedPayload {
+    market_tool_id: String,
+    identifier: String,
+}
+
+/// Starts a [`spawn_subscription_listener`] for every cloud account.
+pub fn spawn_subscription_listeners(app: AppHandle, state: McpRuntimeState) {
+    tauri::async_runtime::spawn(async move {
+        let sources = match state.store.list_sources_by_type(McpSourceType::Cloud).await {
+            Ok(sources) => sources,
+            Err(err) => {
+                warn!("cloud subscription listeners not started: {}", err);
+                return;
+            }
+        };
+        for source in sources {
+            spawn_subscription_listener(app.clone(), state.clone(), source.id);
+        }
+    });
+}
+
+/// Keeps a server-sent event stream open against the cloud account of
+/// `source_id` and applies subscription manifest changes as soon as they are
+/// announced.
+///
+/// The listener idles while the account has no session, reconnects with
+/// exponential backoff when the stream drops, and stops once the account is
+/// removed. Every (re)connect runs a full subscription sync first so changes
+/// made while offline are not missed.
+pub fn spawn_subscription_listener(app: AppHandle, state: McpRuntimeState, source_id: String) {
+    tauri::async_runtime::spawn(async move {
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            let account = match state.cloud_account(Some(&source_id)).await {
+                Ok(account) => account,
+                Err(McpError::SourceNotFound(_)) => return,
+                Err(err) => {
+                    warn!("cloud listener for {} idle: {}", source_id, err);
+                    tokio::time::sleep(SESSION_POLL_INTERVAL).await;
+                    continue;
+                }
+            };
+            if !account.auth.has_session().await {
+                tokio::time::sleep(SESSION_POLL_INTERVAL).await;
+                continue;
+            }
+            if let Err(err) = listen(&app, &state, &account, &mut backoff).await {
+                warn!("cloud subscription stream disconnected: {}", err);
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    });
+}
+
+async fn listen(
+    app: &AppHandle,
+    state: &McpRuntimeState,
+    account: &CloudAccount,
+    backoff: &mut Duration,
+) -> Result<(), McpError> {
+    let url = account.url("/api/v1/mcp/subscriptions/events");
+    let response = account
+        .send(&state.client, |token| {
+            state
+                .client
+                .get(&url)
+                .header(reqwest::header::ACCEPT, "text/event-stream")
+                .bearer_auth(token)
+        })
+        .await?;
+    if !response.status().is_success() {
+        return Err(McpError::Network(format!(
+            "cloud event stream failed: {}",
+            response.status()
+        )));
+    }
+    *backoff = INITIAL_BACKOFF;
+
+    let source_id = account.source.id.as_str();
+    sync_cloud_subscriptions_inner(app, state, Some(source_id), None).await?;
+
+    let mut stream = response.bytes_stream();
+    let mut buffer = String::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| McpError::Network(err.to_string()))?;
+        buffer.push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n"));
+        while let Some(end) = buffer.find("\n\n") {
+            let frame: String = buffer.drain(..end + 2).collect();
+            if let Some((event, data)) = parse_frame(&frame) {
+                if event == SUBSCRIPTION_UPDATED_EVENT {
+                    handle_subscription_updated(app, state, source_id, &data).await;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_subscription_updated(
+    app: &AppHandle,
+    state: &McpRuntimeState,
+    source_id: &str,
+    data: &str,
+) {
+    let sub: CloudSubscriptionItem = match serde_json::from_str(data) {
+        Ok(sub) => sub,
+        Err(err) => {
+            warn!("invalid subscription event payload: {}", err);
+            return;
+        }
+    };
+    // Re-resolved so an update arriving after the account was removed is
+    // dropped instead of recreating its tools.
+    let result = match state.cloud_account(Some(source_id)).await {
+        Ok(account) => apply_cloud_subscription(state, &account.source, &sub).await,
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(_) => {
+            let _ = app.emit(
+                "mcp-subscription-updated",
+                SubscriptionUpdatedPayload {
+                    market_tool_id: sub.market_tool_id.clone(),
+                    identifier: sub.tool.identifier.clone(),
+                },
+            );
+        }
+        Err(err) => warn!("failed to apply subscription update: {}", err),
+    }
+}
+
+/// Splits one SSE frame into its event name and joined data lines. Frames
+/// without data (keep-alive comments) yield `None`.
+fn parse_frame(frame: &str) -> Option<(String, String)> {
+    let mut event = "message".to_string();
+    let mut data: Vec<&str> = Vec::new();
+    for line in frame.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            event = value.trim().to_string();
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data.push(value.strip_prefix(' ').unwrap_or(value));
+        }
+    }
+    if data.is_empty() {
+        return None;
+    }
+    Some((event, data.join("\n")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_frame;
+
+    #[test]
+    fn parse_frame_reads_event_and_data() {
+        let frame = "event: subscription_updated\ndata: {\"id\":1}\n\n";
+        assert_eq!(
+            parse_frame(frame),
+            Some(("subscription_updated".to_string(), "{\"id\":1}".to_string()))
+        );
+        assert_eq!(parse_frame(": keep-alive\n\n"), None);
+    }
+}
@@ -2,27 +2,58 @@ use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use serde::Deserialize;
+use mcp_core::env::{env_requirements, is_secret_env_key, missing_required, validate_env};
+use mcp_core::merge::merge_config;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use tauri::{AppHandle, State};
-
-use crate::mcp::error::McpError;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_opener::OpenerExt;
+
+use crate::autostart::LAUNCH_SETTINGS_KEY;
+use crate::mcp::approval::ToolApprovalRequest;
+use crate::mcp::assistant_sync::{self, ConflictResolution};
+use crate::mcp::cloud_auth::CloudTokens;
+use crate::mcp::cloud_events;
+use crate::mcp::deep_link::{stage_install, InstallPayload, InstallRequest};
+use crate::mcp::diagnostics::redact_args;
+use crate::mcp::error::{CommandError, McpError};
+use crate::mcp::import::{parse_config, parse_config_lenient};
+use crate::mcp::log_export;
+use crate::mcp::log_filter::{self, LogFilter};
+use crate::mcp::notifications::NOTIFICATION_SETTINGS_KEY;
 use crate::mcp::process::ProcessManager;
-use crate::mcp::store::{expand_path, ExtractedToolFields, McpStore, NewSource, ToolUpsert};
+use crate::mcp::scheduler::{self, CLOUD_SYNC_SETTINGS_KEY};
+use crate::mcp::settings::{self, APP_SETTINGS_KEY};
+use crate::mcp::startup::{Ready, Startup, StartupStatus};
+use crate::mcp::store::{ExtractedToolFields, McpStore, NewSource, SyncWrite, ToolUpsert};
+use crate::mcp::sync_events::SyncReporter;
+use crate::mcp::telemetry::TELEMETRY_SETTINGS_KEY;
 use crate::mcp::types::{
-    CreateAssistantMessageRequest, CreateLocalAssistantRequest, CreateSourceRequest,
-    ImportConfigRequest, LocalAssistant, LocalAssistantMessage, LocalChatInputMessage,
-    LocalChatRequest, LocalChatResponse, McpConfigPayload, McpConflictStatus, McpLogEntry,
-    McpSource, McpSourceStatus, McpSourceType, McpTool, McpToolConfigPayload, McpToolStatus,
-    McpTrustLevel, ResolveConflictRequest, SyncSourceRequest, UpdateLocalAssistantRequest,
-    UpdateToolConfigRequest,
+    AppSettings, AppStateImportSummary, AssistantConflictStrategy, AssistantSyncResult, CloudAccountStatus,
+    CloudMarketTool, CloudMarketToolPage, CloudSyncSettings, CreateAssistantMessageRequest,
+    CreateLocalAssistantRequest, CreateSourceRequest, EnvRequirement, ImportConfigRequest,
+    LaunchSettings, LocalAssistant, LocalAssistantMessage, LocalChatInputMessage, LocalChatRequest,
+    LocalChatResponse, LogExportFormat, LogFilterQuery, LogSearchMatch, McpConfigPayload,
+    McpConflictStatus, McpLogEntry, McpLogLevel, McpSource, McpSourceStatus, McpSourceType, McpTool, McpToolConfigPayload,
+    McpToolStatus, McpTrustLevel, NotificationSettings, PublishToolRequest, ResolveConflictRequest,
+    SourceSyncResult, SourceTestResult, SyncChanges, SyncCounts, SyncSchedulerStatus, SyncSourceRequest, TelemetrySettings,
+    TestSourceRequest, ToolChange, UpdateLocalAssistantRequest, UpdateToolConfigRequest,
 };
-use crate::mcp::McpRuntimeState;
+use crate::mcp::{CloudAccount, McpRuntimeState};
+use crate::paths::expand_path;
+
+const MARKET_PAGE_SIZE: usize = 20;
+const LOG_SEARCH_LIMIT: usize = 100;
+const LOG_SEARCH_CONTEXT: usize = 2;
 
 #[derive(Debug, Deserialize)]
-struct CloudToolSummary {
+pub(crate) struct CloudToolSummary {
     id: String,
-    identifier: String,
+    pub(crate) identifier: String,
     name: String,
     description: String,
     avatar_url: Option<String>,
@@ -42,33 +73,54 @@ struct CloudInstallManifest {
 }
 
 #[derive(Debug, Deserialize)]
-struct CloudSubscriptionItem {
+pub(crate) struct CloudSubscriptionItem {
     id: String,
-    market_tool_id: String,
+    pub(crate) market_tool_id: String,
     config_hash_snapshot: Option<String>,
-    tool: CloudToolSummary,
+    pub(crate) tool: CloudToolSummary,
+}
+
+#[derive(Debug, Serialize)]
+struct CloudPublishRequest {
+    identifier: String,
+    name: String,
+    description: String,
+    category: String,
+    tags: Vec<String>,
+    avatar_url: Option<String>,
+    install_manifest: CloudPublishManifest,
+}
+
+#[derive(Debug, Serialize)]
+struct CloudPublishManifest {
+    runtime: String,
+    command: String,
+    args: Vec<String>,
+    env_config: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CloudPublishResponse {
+    id: String,
 }
 
 #[tauri::command]
-pub async fn set_cloud_base_url(
-    state: State<'_, McpRuntimeState>,
-    url: String,
-) -> Result<(), String> {
+pub async fn set_cloud_base_url(state: Ready<'_>, url: String) -> Result<(), CommandError> {
     let mut base = state.cloud_base_url.write().await;
     *base = url;
     Ok(())
 }
 
 #[tauri::command]
-pub async fn list_mcp_sources(state: State<'_, McpRuntimeState>) -> Result<Vec<McpSource>, String> {
-    state.store.list_sources().await.map_err(to_string)
+pub async fn list_mcp_sources(state: Ready<'_>) -> Result<Vec<McpSource>, CommandError> {
+    state.store.list_sources().await.map_err(command_error)
 }
 
 #[tauri::command]
 pub async fn create_mcp_source(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     payload: CreateSourceRequest,
-) -> Result<McpSource, String> {
+) -> Result<McpSource, CommandError> {
     let source = state
         .store
         .insert_source(NewSource {
@@ -81,139 +133,180 @@ pub async fn create_mcp_source(
             is_read_only: payload.is_read_only.unwrap_or(false),
         })
         .await
-        .map_err(to_string)?;
+        .map_err(command_error)?;
     Ok(source)
 }
 
 #[tauri::command]
 pub async fn sync_mcp_source(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     source_id: String,
     payload: SyncSourceRequest,
-) -> Result<Vec<McpTool>, String> {
-    let source = state
-        .store
-        .get_source(&source_id)
-        .await
-        .map_err(to_string)?
-        .ok_or_else(|| to_string(McpError::NotFound(format!("source {source_id} not found"))))?;
-
-    state
-        .store
-        .update_source_status(&source_id, McpSourceStatus::Syncing, None)
+) -> Result<SyncChanges, CommandError> {
+    let source = match state.store.get_source(&source_id).await {
+        Ok(Some(source)) => source,
+        Ok(None) => {
+            let err = McpError::SourceNotFound(source_id.to_string());
+            SyncReporter::new(state.process_manager.events(), &source_id).failed(&err);
+            return Err(command_error(err));
+        }
+        Err(err) => {
+            SyncReporter::new(state.process_manager.events(), &source_id).failed(&err);
+            return Err(command_error(err));
+        }
+    };
+    run_source_sync(&state, source, payload.auth_token)
         .await
-        .map_err(to_string)?;
+        .map_err(command_error)
+}
 
-    let result = sync_source_inner(&state, source, payload.auth_token).await;
-    match result {
-        Ok(tools) => {
-            state
+#[tauri::command]
+pub async fn test_mcp_source(
+    state: Ready<'_>,
+    payload: TestSourceRequest,
+) -> Result<SourceTestResult, CommandError> {
+    let (source_type, path_or_url) = match (payload.source_id, payload.source) {
+        (Some(source_id), _) => {
+            let source = state
                 .store
-                .update_source_status(&source_id, McpSourceStatus::Active, Some(now_rfc3339()))
+                .get_source(&source_id)
                 .await
-                .map_err(to_string)?;
-            Ok(tools)
+                .map_err(command_error)?
+                .ok_or_else(|| command_error(McpError::SourceNotFound(source_id.to_string())))?;
+            (source.source_type, source.path_or_url)
         }
-        Err(err) => {
-            state
-                .store
-                .update_source_status(&source_id, McpSourceStatus::Error, None)
-                .await
-                .map_err(to_string)?;
-            Err(to_string(err))
+        (None, Some(source)) => (source.source_type, source.path_or_url),
+        (None, None) => {
+            return Err(command_error(McpError::validation("source_id or source is required")));
+        }
+    };
+    if path_or_url.trim().is_empty() {
+        return Err(command_error(McpError::validation("path_or_url is required")));
+    }
+
+    let fetched =
+        fetch_source_payload(&state.client, &source_type, &path_or_url, payload.auth_token).await;
+    Ok(source_test_result(fetched))
+}
+
+/// A failed fetch is a test result, not a command error, so the UI can show
+/// why the source is unreachable.
+fn source_test_result(fetched: Result<McpConfigPayload, McpError>) -> SourceTestResult {
+    match fetched {
+        Ok(config) => {
+            let mut server_names: Vec<String> = config.mcp_servers.into_keys().collect();
+            server_names.sort();
+            SourceTestResult {
+                ok: true,
+                server_count: server_names.len(),
+                server_names,
+                error: None,
+            }
         }
+        Err(err) => SourceTestResult {
+            ok: false,
+            server_count: 0,
+            server_names: Vec::new(),
+            error: Some(err.to_string()),
+        },
     }
 }
 
 #[tauri::command]
-pub async fn list_mcp_tools(state: State<'_, McpRuntimeState>) -> Result<Vec<McpTool>, String> {
-    state.store.list_tools().await.map_err(to_string)
+pub async fn list_mcp_tools(state: Ready<'_>) -> Result<Vec<McpTool>, CommandError> {
+    state.store.list_tools().await.map_err(command_error)
 }
 
 #[tauri::command]
-pub async fn list_local_assistants(
-    state: State<'_, McpRuntimeState>,
-) -> Result<Vec<LocalAssistant>, String> {
-    state.store.list_local_assistants().await.map_err(to_string)
+pub async fn list_local_assistants(state: Ready<'_>) -> Result<Vec<LocalAssistant>, CommandError> {
+    state.store.list_local_assistants().await.map_err(command_error)
 }
 
 #[tauri::command]
 pub async fn create_local_assistant(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     payload: CreateLocalAssistantRequest,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     state
         .store
         .create_local_assistant(payload)
         .await
-        .map_err(to_string)
+        .map_err(command_error)
 }
 
 #[tauri::command]
 pub async fn update_local_assistant(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     id: String,
     payload: UpdateLocalAssistantRequest,
-) -> Result<LocalAssistant, String> {
+) -> Result<LocalAssistant, CommandError> {
     state
         .store
         .update_local_assistant(&id, payload)
         .await
-        .map_err(to_string)
+        .map_err(command_error)
 }
 
 #[tauri::command]
-pub async fn delete_local_assistant(
-    state: State<'_, McpRuntimeState>,
-    id: String,
-) -> Result<(), String> {
+pub async fn delete_local_assistant(state: Ready<'_>, id: String) -> Result<(), CommandError> {
     state
         .store
         .delete_local_assistant(&id)
         .await
-        .map_err(to_string)
+        .map_err(command_error)
 }
 
 #[tauri::command]
 pub async fn list_assistant_messages(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     assistant_id: String,
-) -> Result<Vec<LocalAssistantMessage>, String> {
+) -> Result<Vec<LocalAssistantMessage>, CommandError> {
     state
         .store
         .list_assistant_messages(&assistant_id)
         .await
-        .map_err(to_string)
+        .map_err(command_error)
 }
 
 #[tauri::command]
 pub async fn append_assistant_message(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     payload: CreateAssistantMessageRequest,
-) -> Result<LocalAssistantMessage, String> {
+) -> Result<LocalAssistantMessage, CommandError> {
     state
         .store
         .append_assistant_message(payload)
         .await
-        .map_err(to_string)
+        .map_err(command_error)
 }
 
 #[tauri::command]
 pub async fn local_chat_complete(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     payload: LocalChatRequest,
-) -> Result<LocalChatResponse, String> {
-    let model = payload.model.trim().to_string();
+) -> Result<LocalChatResponse, CommandError> {
+    let defaults = settings::load(&state)
+        .await
+        .map_err(command_error)?
+        .default_provider
+        .unwrap_or_default();
+    let model = match payload.model.trim() {
+        "" => defaults.model.trim().to_string(),
+        model => model.to_string(),
+    };
     if model.is_empty() {
-        return Err(to_string(McpError::validation("model is required")));
+        return Err(command_error(McpError::validation("model is required")));
     }
     if payload.messages.is_empty() {
-        return Err(to_string(McpError::validation("messages is required")));
+        return Err(command_error(McpError::validation("messages is required")));
     }
 
-    let base_url = payload.base_url.unwrap_or_default();
+    let base_url = payload
+        .base_url
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or(defaults.base_url);
     if base_url.trim().is_empty() {
-        return Err(to_string(McpError::validation("base_url is required")));
+        return Err(command_error(McpError::validation("base_url is required")));
     }
 
     let mut messages = payload.messages;
@@ -222,9 +315,9 @@ pub async fn local_chat_complete(
             .store
             .get_local_assistant(assistant_id)
             .await
-            .map_err(to_string)?
+            .map_err(command_error)?
             .ok_or_else(|| {
-                to_string(McpError::NotFound(format!(
+                command_error(McpError::NotFound(format!(
                     "assistant {assistant_id} not found"
                 )))
             })?;
@@ -263,331 +356,1515 @@ pub async fn local_chat_complete(
         .send()
         .await
         .map_err(|err| McpError::Network(err.to_string()))
-        .map_err(to_string)?;
+        .map_err(command_error)?;
     let status = response.status();
     let response_json: Value = response
         .json()
         .await
         .map_err(|err| McpError::Network(err.to_string()))
-        .map_err(to_string)?;
+        .map_err(command_error)?;
 
     if !status.is_success() {
         let message = extract_error_message(&response_json)
             .unwrap_or_else(|| format!("upstream error: {}", status));
-        return Err(message);
+        return Err(command_error(McpError::Network(message)));
     }
 
     let content = extract_chat_content(&response_json)
-        .ok_or_else(|| to_string(McpError::Process("empty response content".to_string())))?;
+        .ok_or_else(|| command_error(McpError::Process("empty response content".to_string())))?;
 
     Ok(LocalChatResponse { content })
 }
 
 #[tauri::command]
 pub async fn delete_assistant_messages(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     assistant_id: String,
-) -> Result<(), String> {
+) -> Result<(), CommandError> {
     state
         .store
         .delete_assistant_messages(&assistant_id)
         .await
-        .map_err(to_string)
+        .map_err(command_error)
 }
 
 #[tauri::command]
 pub async fn import_mcp_config(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     payload: ImportConfigRequest,
-) -> Result<Vec<McpTool>, String> {
-    let source = if let Some(source_id) = payload.source_id {
-        state
-            .store
-            .get_source(&source_id)
-            .await
-            .map_err(to_string)?
-            .ok_or_else(|| to_string(McpError::NotFound(format!("source {source_id} not found"))))?
-    } else {
-        state.store.ensure_local_source().await.map_err(to_string)?
-    };
-
+) -> Result<Vec<McpTool>, CommandError> {
+    let source = import_target(&state, payload.source_id).await.map_err(command_error)?;
     apply_config_payload(&state, &source, payload.config)
         .await
-        .map_err(to_string)
+        .map_err(command_error)
 }
 
+/// Picks a config file with the native dialog and imports it into
+/// `source_id` (the local source by default). The file is read here so its
+/// contents never cross IPC; a cancelled dialog imports nothing.
 #[tauri::command]
-pub async fn start_mcp_tool(
+pub async fn import_config_from_file(
     app: AppHandle,
-    state: State<'_, McpRuntimeState>,
-    tool_id: String,
-) -> Result<McpTool, String> {
+    state: Ready<'_>,
+    source_id: Option<String>,
+) -> Result<Vec<McpTool>, CommandError> {
+    let source = import_target(&state, source_id).await.map_err(command_error)?;
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .set_title("Import MCP config")
+        .add_filter("MCP config", &["json"])
+        .pick_file(move |path| {
+            let _ = tx.send(path);
+        });
+    let Some(path) = rx.await.ok().flatten() else {
+        return Ok(Vec::new());
+    };
+    let path = path
+        .into_path()
+        .map_err(|err| command_error(McpError::validation(err.to_string())))?;
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|err| command_error(McpError::Storage(format!("{}: {err}", path.display()))))?;
+    let config = parse_config(&bytes).map_err(command_error)?;
+    apply_config_payload(&state, &source, config)
+        .await
+        .map_err(command_error)
+}
+
+/// Parses the clipboard as an MCP config, tolerating README-style comments
+/// and trailing commas, and stages it as an install request. Nothing is
+/// imported until `confirm_install_request`.
+#[tauri::command]
+pub async fn import_config_from_clipboard(
+    app: AppHandle,
+    state: Ready<'_>,
+) -> Result<InstallRequest, CommandError> {
+    let text = app.clipboard().read_text().map_err(|err| {
+        command_error(McpError::validation(format!("clipboard is not readable: {err}")))
+    })?;
+    let config = parse_config_lenient(&text).map_err(command_error)?;
+    stage_install(&state, InstallPayload::Config { config })
+        .await
+        .map_err(command_error)
+}
+
+async fn import_target(
+    state: &McpRuntimeState,
+    source_id: Option<String>,
+) -> Result<McpSource, McpError> {
+    match source_id {
+        Some(source_id) => state
+            .store
+            .get_source(&source_id)
+            .await?
+            .ok_or_else(|| McpError::SourceNotFound(source_id.to_string())),
+        None => state.store.ensure_local_source().await,
+    }
+}
+
+#[tauri::command]
+pub async fn start_mcp_tool(state: Ready<'_>, tool_id: String) -> Result<McpTool, CommandError> {
+    start_tool_inner(&state, &tool_id)
+        .await
+        .map_err(command_error)
+}
+
+/// Starts a tool after the approval policy and required-env checks; shared by
+/// the `start_mcp_tool` command and the tray menu.
+pub(crate) async fn start_tool_inner(
+    state: &McpRuntimeState,
+    tool_id: &str,
+) -> Result<McpTool, McpError> {
     let tool = state
         .store
-        .get_tool(&tool_id)
-        .await
-        .map_err(to_string)?
-        .ok_or_else(|| to_string(McpError::NotFound(format!("tool {tool_id} not found"))))?;
+        .get_tool(tool_id)
+        .await?
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+
+    crate::mcp::policy::ensure_start_allowed(&state.store, &tool).await?;
 
-    let missing = missing_required_env(&tool).unwrap_or_default();
+    let missing = missing_required(&env_requirements(&tool.config_json, tool.env.as_ref()));
     if !missing.is_empty() {
         let message = format!("missing required env: {}", missing.join(", "));
         state
             .store
-            .set_tool_status(&tool_id, McpToolStatus::Pending, None, Some(message.clone()))
-            .await
-            .map_err(to_string)?;
-        app.emit_all(&format!("mcp-log://{}", tool_id), McpLogEntry {
+            .set_tool_status(tool_id, McpToolStatus::Pending, None, Some(message.clone()))
+            .await?;
+        state.process_manager.publish_status(tool_id).await;
+        state.process_manager.broadcast_log(tool_id, McpLogEntry {
+            seq: 0,
             timestamp: now_rfc3339(),
             stream: crate::mcp::types::McpLogStream::Event,
-            message,
-        }).ok();
-        return Err("missing required env".to_string());
+            message: message.clone(),
+            level: crate::mcp::types::McpLogLevel::Warn,
+            structured: None,
+        });
+        return Err(McpError::EnvMissing(missing));
     }
 
+    state.process_manager.start_tool(tool, true).await?;
     state
-        .process_manager
-        .start_tool(tool.clone(), true)
-        .await
-        .map_err(to_string)?;
-    let updated = state
         .store
-        .get_tool(&tool_id)
+        .get_tool(tool_id)
+        .await?
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))
+}
+
+#[tauri::command]
+pub async fn approve_mcp_tool(
+    state: Ready<'_>,
+    tool_id: String,
+    approved: bool,
+) -> Result<McpTool, CommandError> {
+    let approved_at = if approved { Some(now_rfc3339()) } else { None };
+    state
+        .store
+        .set_tool_approval(&tool_id, approved_at)
         .await
-        .map_err(to_string)?
-        .ok_or_else(|| to_string(McpError::NotFound(format!("tool {tool_id} not found"))))?;
-    Ok(updated)
+        .map_err(command_error)
 }
 
+/// Starts a tool, first asking the user to approve it when its source is
+/// untrusted and it was never approved. The call stays pending until they
+/// answer the `mcp-tool-approval` dialog through `answer_tool_approval`.
 #[tauri::command]
-pub async fn stop_mcp_tool(
-    state: State<'_, McpRuntimeState>,
+pub async fn request_tool_approval(
+    app: AppHandle,
+    state: Ready<'_>,
     tool_id: String,
-) -> Result<McpTool, String> {
+) -> Result<McpTool, CommandError> {
+    start_tool_with_approval(&app, &state, &tool_id)
+        .await
+        .map_err(command_error)
+}
+
+/// `start_tool_inner` behind the first-run approval dialog; shared by
+/// `request_tool_approval` and the tray menu. The answer is persisted, so an
+/// approved tool is not asked about again.
+pub(crate) async fn start_tool_with_approval(
+    app: &AppHandle,
+    state: &McpRuntimeState,
+    tool_id: &str,
+) -> Result<McpTool, McpError> {
+    let tool = state
+        .store
+        .get_tool(tool_id)
+        .await?
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+    if let Some(source) = crate::mcp::policy::pending_approval(&state.store, &tool).await? {
+        let approved =
+            crate::mcp::approval::request(app, &state.pending_approvals, &tool, &source).await;
+        let approved_at = if approved { Some(now_rfc3339()) } else { None };
+        state.store.set_tool_approval(tool_id, approved_at).await?;
+        if !approved {
+            return Err(McpError::ApprovalRequired(format!(
+                "start of tool {} was declined",
+                tool.name
+            )));
+        }
+    }
+    start_tool_inner(state, tool_id).await
+}
+
+#[tauri::command]
+pub async fn answer_tool_approval(
+    state: Ready<'_>,
+    request_id: String,
+    approved: bool,
+) -> Result<(), CommandError> {
+    if state.pending_approvals.answer(&request_id, approved).await {
+        Ok(())
+    } else {
+        Err(command_error(McpError::NotFound(format!(
+            "approval request {request_id} not found"
+        ))))
+    }
+}
+
+#[tauri::command]
+pub async fn list_tool_approvals(
+    state: Ready<'_>,
+) -> Result<Vec<ToolApprovalRequest>, CommandError> {
+    Ok(state.pending_approvals.list().await)
+}
+
+#[tauri::command]
+pub async fn stop_mcp_tool(state: Ready<'_>, tool_id: String) -> Result<McpTool, CommandError> {
     state
         .process_manager
         .stop_tool(&tool_id)
         .await
-        .map_err(to_string)?;
+        .map_err(command_error)?;
     let updated = state
         .store
         .get_tool(&tool_id)
         .await
-        .map_err(to_string)?
-        .ok_or_else(|| to_string(McpError::NotFound(format!("tool {tool_id} not found"))))?;
+        .map_err(command_error)?
+        .ok_or_else(|| command_error(McpError::ToolNotFound(tool_id.to_string())))?;
     Ok(updated)
 }
 
+#[tauri::command]
+pub async fn get_tool_env_requirements(
+    state: Ready<'_>,
+    tool_id: String,
+) -> Result<Vec<EnvRequirement>, CommandError> {
+    let tool = state
+        .store
+        .get_tool(&tool_id)
+        .await
+        .map_err(command_error)?
+        .ok_or_else(|| command_error(McpError::ToolNotFound(tool_id.to_string())))?;
+    Ok(env_requirements(&tool.config_json, tool.env.as_ref()))
+}
+
 #[tauri::command]
 pub async fn update_mcp_tool_env(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     tool_id: String,
     env: Option<HashMap<String, String>>,
-) -> Result<McpTool, String> {
-    state.store.update_tool_env(&tool_id, env).await.map_err(to_string)
+) -> Result<McpTool, CommandError> {
+    if let Some(env) = env.as_ref() {
+        let tool = state
+            .store
+            .get_tool(&tool_id)
+            .await
+            .map_err(command_error)?
+            .ok_or_else(|| command_error(McpError::ToolNotFound(tool_id.to_string())))?;
+        validate_env(&env_requirements(&tool.config_json, Some(env)), env)
+            .map_err(|err| command_error(McpError::from(err)))?;
+    }
+    state.store.update_tool_env(&tool_id, env).await.map_err(command_error)
 }
 
 #[tauri::command]
 pub async fn apply_pending_config(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     tool_id: String,
     payload: UpdateToolConfigRequest,
-) -> Result<McpTool, String> {
+) -> Result<McpTool, CommandError> {
     if !payload.apply_pending {
-        return Err("apply_pending must be true".to_string());
+        return Err(command_error(McpError::validation("apply_pending must be true")));
     }
-    apply_pending_update(&state, &tool_id).await.map_err(to_string)
+    apply_pending_update(&state, &tool_id).await.map_err(command_error)
 }
 
 #[tauri::command]
 pub async fn resolve_mcp_conflict(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     tool_id: String,
     payload: ResolveConflictRequest,
-) -> Result<McpTool, String> {
+) -> Result<McpTool, CommandError> {
     match payload.action.as_str() {
-        "update" => apply_pending_update(&state, &tool_id).await.map_err(to_string),
+        "update" => apply_pending_update(&state, &tool_id).await.map_err(command_error),
+        "merge" => merge_pending_update(&state, &tool_id).await.map_err(command_error),
         "keep" => {
-            state.store.clear_pending_update(&tool_id).await.map_err(to_string)?;
+            state.store.clear_pending_update(&tool_id).await.map_err(command_error)?;
             state
                 .store
                 .get_tool(&tool_id)
                 .await
-                .map_err(to_string)?
-                .ok_or_else(|| to_string(McpError::NotFound(format!("tool {tool_id} not found"))))
+                .map_err(command_error)?
+                .ok_or_else(|| command_error(McpError::ToolNotFound(tool_id.to_string())))
         }
-        _ => Err("invalid action".to_string()),
+        _ => Err(command_error(McpError::validation("invalid action"))),
+    }
+}
+
+#[tauri::command]
+pub async fn publish_mcp_tool(
+    state: Ready<'_>,
+    tool_id: String,
+    metadata: PublishToolRequest,
+    source_id: Option<String>,
+) -> Result<McpTool, CommandError> {
+    let tool = state
+        .store
+        .get_tool(&tool_id)
+        .await
+        .map_err(command_error)?
+        .ok_or_else(|| command_error(McpError::ToolNotFound(tool_id.to_string())))?;
+    let identifier = metadata.identifier.trim().to_string();
+    if identifier.is_empty() {
+        return Err(command_error(McpError::validation("identifier is required")));
+    }
+    let body = build_publish_request(&tool, identifier, metadata).map_err(command_error)?;
+
+    let account = state
+        .cloud_account(source_id.as_deref())
+        .await
+        .map_err(command_error)?;
+    let url = account.url("/api/v1/mcp/market-tools");
+    let response = account
+        .send(&state.client, |token| {
+            state.client.post(&url).bearer_auth(token).json(&body)
+        })
+        .await
+        .map_err(command_error)?;
+    if !response.status().is_success() {
+        return Err(command_error(McpError::Network(format!(
+            "cloud publish failed: {}",
+            response.status()
+        ))));
     }
+    let published: CloudPublishResponse = response
+        .json()
+        .await
+        .map_err(|err| McpError::Network(err.to_string()))
+        .map_err(command_error)?;
+
+    state
+        .store
+        .set_tool_market_id(&tool_id, &published.id)
+        .await
+        .map_err(command_error)
 }
 
+/// A tool's newest stored log lines, across app restarts, narrowed by
+/// `filter` before `limit` applies. `limit` defaults to the `log_buffer_size`
+/// setting; pass the first returned line's `seq` as `before` to page back
+/// through older history.
 #[tauri::command]
 pub async fn get_mcp_logs(
-    state: State<'_, McpRuntimeState>,
+    state: Ready<'_>,
     tool_id: String,
-) -> Result<Vec<McpLogEntry>, String> {
-    Ok(state.process_manager.logs(&tool_id).await)
+    limit: Option<usize>,
+    before: Option<u64>,
+    filter: Option<LogFilterQuery>,
+) -> Result<Vec<McpLogEntry>, CommandError> {
+    let limit = limit.unwrap_or_else(|| state.process_manager.log_buffer_size());
+    let filter = LogFilter::new(&filter.unwrap_or_default()).map_err(command_error)?;
+    if filter.is_empty() {
+        return state
+            .process_manager
+            .log_history(&tool_id, Some(limit), before)
+            .await
+            .map_err(command_error);
+    }
+    let entries = state
+        .process_manager
+        .log_history(&tool_id, None, before)
+        .await
+        .map_err(command_error)?;
+    Ok(filter.apply(entries, Some(limit)))
 }
 
+/// Starts emitting every tool's lines, tagged with their tool, on the single
+/// `mcp-log-all://` event, so a global console needs one listener instead of
+/// one per tool. Pair each call with `unsubscribe_all_mcp_logs`.
 #[tauri::command]
-pub async fn clear_mcp_logs(
-    state: State<'_, McpRuntimeState>,
+pub async fn subscribe_all_mcp_logs(state: Ready<'_>) -> Result<(), CommandError> {
+    state.process_manager.subscribe_all_logs();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unsubscribe_all_mcp_logs(state: Ready<'_>) -> Result<(), CommandError> {
+    state.process_manager.unsubscribe_all_logs();
+    Ok(())
+}
+
+/// Searches the stored logs of every tool for lines containing `query`
+/// (case-insensitive; blank matches any line) at `level` or above, logged at
+/// or after `since`. Returns the newest `limit` matches (default 100), newest
+/// first, each with `context` lines (default 2) on either side.
+#[tauri::command]
+pub async fn search_mcp_logs(
+    state: Ready<'_>,
+    query: String,
+    level: Option<McpLogLevel>,
+    since: Option<String>,
+    limit: Option<usize>,
+    context: Option<usize>,
+) -> Result<Vec<LogSearchMatch>, CommandError> {
+    let since = since
+        .as_deref()
+        .map(str::trim)
+        .filter(|since| !since.is_empty())
+        .map(log_filter::utc_timestamp)
+        .transpose()
+        .map_err(command_error)?;
+    state
+        .process_manager
+        .search_logs(
+            query.trim(),
+            level.unwrap_or_default(),
+            since.as_deref(),
+            limit.unwrap_or(LOG_SEARCH_LIMIT),
+            context.unwrap_or(LOG_SEARCH_CONTEXT),
+        )
+        .await
+        .map_err(command_error)
+}
+
+/// Writes every stored log line of a tool to `path`, or to a file picked in a save
+/// dialog when no path is given, and optionally opens it. Returns the path
+/// written, or `None` when the dialog is cancelled.
+#[tauri::command]
+pub async fn export_tool_logs(
+    app: AppHandle,
+    state: Ready<'_>,
     tool_id: String,
-) -> Result<(), String> {
+    path: Option<String>,
+    format: Option<LogExportFormat>,
+    open: Option<bool>,
+) -> Result<Option<String>, CommandError> {
+    let tool = state
+        .store
+        .get_tool(&tool_id)
+        .await
+        .map_err(command_error)?
+        .ok_or_else(|| command_error(McpError::ToolNotFound(tool_id.clone())))?;
+    let format = format.unwrap_or_default();
+    let path = match path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => expand_path(&path),
+        None => {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            app.dialog()
+                .file()
+                .set_title("Export tool logs")
+                .set_file_name(format!("{}-logs.{}", tool.name, format.extension()))
+                .save_file(move |path| {
+                    let _ = tx.send(path);
+                });
+            let Some(path) = rx.await.ok().flatten() else {
+                return Ok(None);
+            };
+            path.into_path()
+                .map_err(|err| command_error(McpError::validation(err.to_string())))?
+        }
+    };
+
+    let entries = state
+        .process_manager
+        .log_history(&tool.id, None, None)
+        .await
+        .map_err(command_error)?;
+    let content = log_export::render(&entries, format).map_err(command_error)?;
+    let storage = |err: std::io::Error| {
+        command_error(McpError::Storage(format!("{}: {err}", path.display())))
+    };
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        tokio::fs::create_dir_all(parent).await.map_err(storage)?;
+    }
+    tokio::fs::write(&path, content).await.map_err(storage)?;
+
+    let written = path.to_string_lossy().into_owned();
+    if open.unwrap_or(false) {
+        app.opener()
+            .open_path(written.as_str(), None::<&str>)
+            .map_err(|err| {
+                command_error(McpError::Process(format!("failed to open {written}: {err}")))
+            })?;
+    }
+    Ok(Some(written))
+}
+
+#[tauri::command]
+pub async fn clear_mcp_logs(state: Ready<'_>, tool_id: String) -> Result<(), CommandError> {
     state.process_manager.clear_logs(&tool_id).await;
     Ok(())
 }
 
+/// Syncs every source, a few at a time, and reports how each one went.
+#[tauri::command]
+pub async fn sync_all_sources(
+    app: AppHandle,
+    state: Ready<'_>,
+) -> Result<Vec<SourceSyncResult>, CommandError> {
+    let sources = state.store.list_sources().await.map_err(command_error)?;
+    Ok(scheduler::sync_sources(&app, &state, sources).await)
+}
+
 #[tauri::command]
 pub async fn sync_cloud_subscriptions(
     app: AppHandle,
-    state: State<'_, McpRuntimeState>,
-    access_token: String,
-) -> Result<Vec<McpTool>, String> {
-    let base_url = state.cloud_base_url.read().await.clone();
-    let url = format!("{}/api/v1/mcp/subscriptions", base_url.trim_end_matches('/'));
-    let response = state
-        .client
-        .get(&url)
-        .bearer_auth(access_token)
-        .send()
+    state: Ready<'_>,
+    access_token: Option<String>,
+    source_id: Option<String>,
+) -> Result<SyncChanges, CommandError> {
+    sync_cloud_subscriptions_inner(&app, &state, source_id.as_deref(), access_token)
         .await
-        .map_err(|err| McpError::Network(err.to_string()))
-        .map_err(to_string)?;
+        .map_err(command_error)
+}
 
-    if !response.status().is_success() {
-        return Err(format!("cloud sync failed: {}", response.status()));
+#[tauri::command]
+pub async fn list_cloud_accounts(
+    state: Ready<'_>,
+) -> Result<Vec<CloudAccountStatus>, CommandError> {
+    let sources = state
+        .store
+        .list_sources_by_type(McpSourceType::Cloud)
+        .await
+        .map_err(command_error)?;
+    let mut accounts = Vec::with_capacity(sources.len());
+    for source in sources {
+        let account = state
+            .cloud_account(Some(&source.id))
+            .await
+            .map_err(command_error)?;
+        accounts.push(CloudAccountStatus {
+            is_primary: source.id == state.cloud_accounts.primary_source_id(),
+            has_session: account.auth.has_session().await,
+            base_url: account.base_url,
+            source,
+        });
     }
+    Ok(accounts)
+}
 
-    let subs: Vec<CloudSubscriptionItem> = response
-        .json()
+/// Adds a cloud account for another server. Its tools are `Community`
+/// trust: only the built-in account's server is `Official`.
+#[tauri::command]
+pub async fn add_cloud_account(
+    app: AppHandle,
+    state: Ready<'_>,
+    name: String,
+    base_url: String,
+) -> Result<McpSource, CommandError> {
+    let name = name.trim().to_string();
+    let base_url = base_url.trim().trim_end_matches('/').to_string();
+    if name.is_empty() || base_url.is_empty() {
+        return Err(command_error(McpError::validation(
+            "name and base_url are required",
+        )));
+    }
+    let source = state
+        .store
+        .insert_source(NewSource {
+            name,
+            source_type: McpSourceType::Cloud,
+            path_or_url: base_url,
+            trust_level: McpTrustLevel::Community,
+            status: McpSourceStatus::Active,
+            last_synced_at: None,
+            is_read_only: true,
+        })
         .await
-        .map_err(|err| McpError::Network(err.to_string()))
-        .map_err(to_string)?;
+        .map_err(command_error)?;
+    cloud_events::spawn_subscription_listener(app, (*state).clone(), source.id.clone());
+    Ok(source)
+}
 
-    let cloud_source = state.store.ensure_cloud_source(&base_url).await.map_err(to_string)?;
-    let mut seen_identifiers = HashSet::new();
+#[tauri::command]
+pub async fn remove_cloud_account(state: Ready<'_>, source_id: String) -> Result<(), CommandError> {
+    if source_id == state.cloud_accounts.primary_source_id() {
+        return Err(command_error(McpError::validation(
+            "the primary cloud account cannot be removed",
+        )));
+    }
+    let account = state
+        .cloud_account(Some(&source_id))
+        .await
+        .map_err(command_error)?;
+    let tools = state.store.list_tools().await.map_err(command_error)?;
+    for tool in tools
+        .iter()
+        .filter(|tool| tool.source_id.as_deref() == Some(source_id.as_str()))
+    {
+        let _ = state.process_manager.stop_tool(&tool.id).await;
+    }
+    account
+        .auth
+        .logout(&state.client, &account.base_url)
+        .await
+        .map_err(command_error)?;
+    state
+        .cloud_accounts
+        .forget(&source_id)
+        .await
+        .map_err(command_error)?;
+    state
+        .store
+        .delete_source(&source_id)
+        .await
+        .map_err(command_error)?;
+    state
+        .process_manager
+        .reload_attention()
+        .await
+        .map_err(command_error)
+}
 
-    for sub in subs.iter() {
-        let tool = &sub.tool;
-        seen_identifiers.insert(tool.identifier.clone());
-        let config_json = build_cloud_config_json(tool)?;
-        let config_hash = state
-            .store
-            .compute_config_hash(&config_json)
-            .map_err(to_string)?;
-        let config_json_text = serde_json::to_string(&config_json)
-            .map_err(|err| McpError::Storage(err.to_string()))
-            .map_err(to_string)?;
-
-        let extracted = ExtractedToolFields {
-            name: tool.name.clone(),
-            description: tool.description.clone(),
-            command: Some(tool.install_manifest.command.clone()),
-            args: Some(tool.install_manifest.args.clone()),
-            env: None,
-            capabilities: vec![],
-        };
+#[tauri::command]
+pub async fn cloud_login(
+    state: Ready<'_>,
+    email: String,
+    code: String,
+    source_id: Option<String>,
+) -> Result<(), CommandError> {
+    let account = state
+        .cloud_account(source_id.as_deref())
+        .await
+        .map_err(command_error)?;
+    account
+        .auth
+        .login(&state.client, &account.base_url, email.trim(), code.trim())
+        .await
+        .map_err(command_error)
+}
 
-        let name_conflict = state
-            .store
-            .has_name_conflict(&extracted.name, &cloud_source.id)
-            .await
-            .map_err(to_string)?;
+#[tauri::command]
+pub async fn cloud_set_session(
+    state: Ready<'_>,
+    access_token: String,
+    refresh_token: String,
+    source_id: Option<String>,
+) -> Result<(), CommandError> {
+    let account = state
+        .cloud_account(source_id.as_deref())
+        .await
+        .map_err(command_error)?;
+    account
+        .auth
+        .set_tokens(CloudTokens {
+            access_token,
+            refresh_token,
+        })
+        .await
+        .map_err(command_error)
+}
 
-        let existing = state
-            .store
-            .get_tool_by_source_identifier(&cloud_source.id, &tool.identifier)
-            .await
-            .map_err(to_string)?;
+#[tauri::command]
+pub async fn cloud_logout(state: Ready<'_>, source_id: Option<String>) -> Result<(), CommandError> {
+    let account = state
+        .cloud_account(source_id.as_deref())
+        .await
+        .map_err(command_error)?;
+    account
+        .auth
+        .logout(&state.client, &account.base_url)
+        .await
+        .map_err(command_error)
+}
 
-        match existing {
-            Some(existing_tool) => {
-                if existing_tool.config_hash == config_hash {
-                    continue;
-                }
-                let conflict_status = if name_conflict {
-                    McpConflictStatus::Conflict
-                } else {
-                    McpConflictStatus::UpdateAvailable
-                };
-                state
-                    .store
-                    .mark_tool_pending_update(
-                        &existing_tool.id,
-                        config_json_text.clone(),
-                        config_hash.clone(),
-                        conflict_status,
-                    )
-                    .await
-                    .map_err(to_string)?;
-            }
-            None => {
-                let tool_upsert = ToolUpsert {
-                    id: None,
-                    source_id: cloud_source.id.clone(),
-                    identifier: Some(tool.identifier.clone()),
-                    name: extracted.name,
-                    source_type: McpSourceType::Cloud,
-                    status: McpToolStatus::Stopped,
-                    ping_ms: None,
-                    capabilities: extracted.capabilities,
-                    description: extracted.description,
-                    error: None,
-                    command: extracted.command,
-                    args: extracted.args,
-                    env: extracted.env,
-                    config_json: config_json_text.clone(),
-                    config_hash: config_hash.clone(),
-                    pending_config_json: None,
-                    pending_config_hash: None,
-                    conflict_status: if name_conflict {
-                        McpConflictStatus::Conflict
-                    } else {
-                        McpConflictStatus::None
-                    },
-                    is_read_only: true,
-                    is_new: true,
-                };
-                state.store.upsert_tool(tool_upsert).await.map_err(to_string)?;
-            }
+#[tauri::command]
+pub async fn get_cloud_session_status(
+    state: Ready<'_>,
+    source_id: Option<String>,
+) -> Result<bool, CommandError> {
+    let account = state
+        .cloud_account(source_id.as_deref())
+        .await
+        .map_err(command_error)?;
+    Ok(account.auth.has_session().await)
+}
+
+#[tauri::command]
+pub async fn sync_cloud_assistants(
+    state: Ready<'_>,
+    strategy: Option<AssistantConflictStrategy>,
+) -> Result<AssistantSyncResult, CommandError> {
+    assistant_sync::sync_assistants(&state, strategy.unwrap_or_default())
+        .await
+        .map_err(command_error)
+}
+
+#[tauri::command]
+pub async fn resolve_assistant_conflict(
+    state: Ready<'_>,
+    assistant_id: String,
+    keep: String,
+) -> Result<LocalAssistant, CommandError> {
+    let resolution = ConflictResolution::parse(keep.trim()).map_err(command_error)?;
+    assistant_sync::resolve_assistant_conflict(&state, &assistant_id, resolution)
+        .await
+        .map_err(command_error)
+}
+
+#[tauri::command]
+pub async fn list_cloud_market_tools(
+    state: Ready<'_>,
+    query: Option<String>,
+    category: Option<String>,
+    page: Option<usize>,
+    source_id: Option<String>,
+) -> Result<CloudMarketToolPage, CommandError> {
+    let account = state
+        .cloud_account(source_id.as_deref())
+        .await
+        .map_err(command_error)?;
+    let url = account.url("/api/v1/mcp/market-tools");
+    let mut params = Vec::new();
+    if let Some(query) = query.map(|q| q.trim().to_string()).filter(|q| !q.is_empty()) {
+        params.push(("q", query));
+    }
+    if let Some(category) = category.filter(|c| !c.is_empty()) {
+        params.push(("category", category));
+    }
+    let response = account
+        .send(&state.client, |token| {
+            state.client.get(&url).query(&params).bearer_auth(token)
+        })
+        .await
+        .map_err(command_error)?;
+    if !response.status().is_success() {
+        return Err(command_error(McpError::Network(format!(
+            "cloud market listing failed: {}",
+            response.status()
+        ))));
+    }
+    let tools: Vec<CloudMarketTool> = response
+        .json()
+        .await
+        .map_err(|err| McpError::Network(err.to_string()))
+        .map_err(command_error)?;
+
+    // The catalog endpoint returns every match, so paging happens here.
+    let page = page.unwrap_or(1).max(1);
+    let total = tools.len();
+    let items = tools
+        .into_iter()
+        .skip((page - 1) * MARKET_PAGE_SIZE)
+        .take(MARKET_PAGE_SIZE)
+        .collect();
+    Ok(CloudMarketToolPage {
+        items,
+        page,
+        page_size: MARKET_PAGE_SIZE,
+        total,
+    })
+}
+
+#[tauri::command]
+pub async fn subscribe_cloud_tool(
+    state: Ready<'_>,
+    market_tool_id: String,
+    source_id: Option<String>,
+) -> Result<McpTool, CommandError> {
+    subscribe_cloud_tool_inner(&state, source_id.as_deref(), &market_tool_id)
+        .await
+        .map_err(command_error)
+}
+
+/// Subscribes to a marketplace tool with the cloud account of `source_id`
+/// (the primary one by default) and installs it into that account's source;
+/// shared by `subscribe_cloud_tool` and deep link installs.
+async fn subscribe_cloud_tool_inner(
+    state: &McpRuntimeState,
+    source_id: Option<&str>,
+    market_tool_id: &str,
+) -> Result<McpTool, McpError> {
+    let account = state.cloud_account(source_id).await?;
+    let url = account.url("/api/v1/mcp/subscriptions");
+    let body = serde_json::json!({ "tool_id": market_tool_id });
+    let response = account
+        .send(&state.client, |token| {
+            state.client.post(&url).bearer_auth(token).json(&body)
+        })
+        .await?;
+    if !response.status().is_success() {
+        return Err(McpError::Network(format!(
+            "cloud subscribe failed: {}",
+            response.status()
+        )));
+    }
+    let sub: CloudSubscriptionItem = response
+        .json()
+        .await
+        .map_err(|err| McpError::Network(err.to_string()))?;
+
+    apply_cloud_subscription(state, &account.source, &sub).await?;
+    state
+        .store
+        .get_tool_by_source_identifier(&account.source.id, &sub.tool.identifier)
+        .await?
+        .ok_or_else(|| McpError::NotFound("subscribed tool missing after install".to_string()))
+}
+
+#[tauri::command]
+pub async fn list_install_requests(state: Ready<'_>) -> Result<Vec<InstallRequest>, CommandError> {
+    Ok(state.pending_installs.list().await)
+}
+
+/// Installs a deep link request the user confirmed. Installed tools still go
+/// through the usual approval policy before their first start.
+#[tauri::command]
+pub async fn confirm_install_request(
+    state: Ready<'_>,
+    request_id: String,
+) -> Result<Vec<McpTool>, CommandError> {
+    let request = state
+        .pending_installs
+        .take(&request_id)
+        .await
+        .ok_or_else(|| {
+            command_error(McpError::NotFound(format!(
+                "install request {request_id} not found"
+            )))
+        })?;
+    match request.payload {
+        InstallPayload::Market { market_tool_id } => {
+            subscribe_cloud_tool_inner(&state, None, &market_tool_id)
+                .await
+                .map(|tool| vec![tool])
+                .map_err(command_error)
+        }
+        InstallPayload::Config { config } => {
+            let source = state.store.ensure_local_source().await.map_err(command_error)?;
+            apply_config_payload(&state, &source, config)
+                .await
+                .map_err(command_error)
         }
     }
+}
+
+#[tauri::command]
+pub async fn dismiss_install_request(
+    state: Ready<'_>,
+    request_id: String,
+) -> Result<(), CommandError> {
+    state.pending_installs.take(&request_id).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_cloud_sync_settings(state: Ready<'_>) -> Result<CloudSyncSettings, CommandError> {
+    state
+        .store
+        .get_setting(CLOUD_SYNC_SETTINGS_KEY)
+        .await
+        .map_err(command_error)
+}
+
+#[tauri::command]
+pub async fn update_cloud_sync_settings(
+    state: Ready<'_>,
+    settings: CloudSyncSettings,
+) -> Result<CloudSyncSettings, CommandError> {
+    if settings.interval_minutes == 0 {
+        return Err(command_error(McpError::validation(
+            "interval_minutes must be greater than zero",
+        )));
+    }
+    state
+        .store
+        .set_setting(CLOUD_SYNC_SETTINGS_KEY, &settings)
+        .await
+        .map_err(command_error)?;
+    state.sync_scheduler.wake();
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn get_sync_scheduler_status(
+    state: Ready<'_>,
+) -> Result<SyncSchedulerStatus, CommandError> {
+    scheduler::status(&state).await.map_err(command_error)
+}
+
+/// Pauses or resumes the background sync without touching its interval.
+#[tauri::command]
+pub async fn set_sync_scheduler_enabled(
+    state: Ready<'_>,
+    enabled: bool,
+) -> Result<SyncSchedulerStatus, CommandError> {
+    let mut settings: CloudSyncSettings = state
+        .store
+        .get_setting(CLOUD_SYNC_SETTINGS_KEY)
+        .await
+        .map_err(command_error)?;
+    settings.enabled = enabled;
+    state
+        .store
+        .set_setting(CLOUD_SYNC_SETTINGS_KEY, &settings)
+        .await
+        .map_err(command_error)?;
+    state.sync_scheduler.wake();
+    scheduler::status(&state).await.map_err(command_error)
+}
+
+/// Starts a background sync now, even while the scheduler is paused.
+/// Progress and results arrive as `cloud-sync-status` events.
+#[tauri::command]
+pub async fn trigger_sync_scheduler(state: Ready<'_>) -> Result<(), CommandError> {
+    state.sync_scheduler.trigger();
+    Ok(())
+}
+
+/// Writes a diagnostics zip to `path` and returns the path written.
+#[tauri::command]
+pub async fn export_diagnostics(
+    app: AppHandle,
+    state: Ready<'_>,
+    path: String,
+) -> Result<String, CommandError> {
+    if path.trim().is_empty() {
+        return Err(command_error(McpError::validation("path is required")));
+    }
+    let written = crate::mcp::diagnostics::export(&app, &state, &expand_path(&path))
+        .await
+        .map_err(command_error)?;
+    Ok(written.to_string_lossy().into_owned())
+}
+
+/// Writes an app state archive to `path` and returns the path written.
+/// Secret env values are blanked unless `include_secrets`.
+#[tauri::command]
+pub async fn export_app_state(
+    app: AppHandle,
+    state: Ready<'_>,
+    path: String,
+    include_secrets: Option<bool>,
+) -> Result<String, CommandError> {
+    if path.trim().is_empty() {
+        return Err(command_error(McpError::validation("path is required")));
+    }
+    let written = crate::mcp::app_state::export(
+        &app,
+        &state,
+        &expand_path(&path),
+        include_secrets.unwrap_or(false),
+    )
+    .await
+    .map_err(command_error)?;
+    Ok(written.to_string_lossy().into_owned())
+}
+
+/// Restores an archive written by `export_app_state`; all tools must be
+/// stopped.
+#[tauri::command]
+pub async fn import_app_state(
+    app: AppHandle,
+    state: Ready<'_>,
+    path: String,
+) -> Result<AppStateImportSummary, CommandError> {
+    if path.trim().is_empty() {
+        return Err(command_error(McpError::validation("path is required")));
+    }
+    crate::mcp::app_state::import(&app, &state, &expand_path(&path))
+        .await
+        .map_err(command_error)
+}
+
+/// Lets a window that opened after `mcp-store-ready` (or `mcp-store-failed`)
+/// was emitted find out where startup stands.
+#[tauri::command]
+pub fn get_startup_status(startup: State<'_, Startup>) -> StartupStatus {
+    startup.status()
+}
+
+#[tauri::command]
+pub async fn get_app_settings(state: Ready<'_>) -> Result<AppSettings, CommandError> {
+    settings::load(&state).await.map_err(command_error)
+}
+
+#[tauri::command]
+pub async fn update_app_settings(
+    state: Ready<'_>,
+    settings: AppSettings,
+) -> Result<AppSettings, CommandError> {
+    state
+        .store
+        .set_setting(APP_SETTINGS_KEY, &settings)
+        .await
+        .map_err(command_error)?;
+    settings::apply(&state, &settings).await;
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn get_launch_settings(state: Ready<'_>) -> Result<LaunchSettings, CommandError> {
+    state
+        .store
+        .get_setting(LAUNCH_SETTINGS_KEY)
+        .await
+        .map_err(command_error)
+}
+
+/// Updates the OS login entry first so the stored setting never claims a
+/// registration that failed.
+#[tauri::command]
+pub async fn update_launch_settings(
+    app: AppHandle,
+    state: Ready<'_>,
+    settings: LaunchSettings,
+) -> Result<LaunchSettings, CommandError> {
+    crate::autostart::apply(&app, &settings).map_err(command_error)?;
+    state
+        .store
+        .set_setting(LAUNCH_SETTINGS_KEY, &settings)
+        .await
+        .map_err(command_error)?;
+    Ok(settings)
+}
+
+#[tauri::command]
+pub async fn set_mcp_tool_auto_start(
+    state: Ready<'_>,
+    tool_id: String,
+    auto_start: bool,
+) -> Result<McpTool, CommandError> {
+    state
+        .store
+        .set_tool_auto_start(&tool_id, auto_start)
+        .await
+        .map_err(command_error)
+}
+
+/// Turns on or off writing the tool's output to
+/// `logs/<name>-<id>/current.log` in the config directory, rotated by the
+/// `log_file_*` app settings.
+#[tauri::command]
+pub async fn set_mcp_tool_log_to_file(
+    state: Ready<'_>,
+    tool_id: String,
+    log_to_file: bool,
+) -> Result<McpTool, CommandError> {
+    let tool = state
+        .store
+        .set_tool_log_to_file(&tool_id, log_to_file)
+        .await
+        .map_err(command_error)?;
+    state.process_manager.apply_log_to_file(&tool);
+    Ok(tool)
+}
+
+/// Keeps `log_buffer_size` in-memory log lines for a chatty tool instead of
+/// the app setting; `None` goes back to the app setting.
+#[tauri::command]
+pub async fn set_mcp_tool_log_buffer_size(
+    state: Ready<'_>,
+    tool_id: String,
+    log_buffer_size: Option<usize>,
+) -> Result<McpTool, CommandError> {
+    if log_buffer_size == Some(0) {
+        return Err(command_error(McpError::validation(
+            "log_buffer_size must be at least 1",
+        )));
+    }
+    let tool = state
+        .store
+        .set_tool_log_buffer_size(&tool_id, log_buffer_size)
+        .await
+        .map_err(command_error)?;
+    state
+        .process_manager
+        .set_tool_log_buffer_size(&tool.id, tool.log_buffer_size)
+        .await;
+    Ok(tool)
+}
+
+#[tauri::command]
+pub async fn get_notification_settings(
+    state: Ready<'_>,
+) -> Result<NotificationSettings, CommandError> {
+    state
+        .store
+        .get_setting(NOTIFICATION_SETTINGS_KEY)
+        .await
+        .map_err(command_error)
+}
+
+#[tauri::command]
+pub async fn update_notification_settings(
+    state: Ready<'_>,
+    settings: NotificationSettings,
+) -> Result<NotificationSettings, CommandError> {
+    state
+        .store
+        .set_setting(NOTIFICATION_SETTINGS_KEY, &settings)
+        .await
+        .map_err(command_error)?;
+    Ok(settings)
+}
 
-    let all_tools = state.store.list_tools().await.map_err(to_string)?;
+#[tauri::command]
+pub async fn get_telemetry_settings(state: Ready<'_>) -> Result<TelemetrySettings, CommandError> {
+    state
+        .store
+        .get_setting(TELEMETRY_SETTINGS_KEY)
+        .await
+        .map_err(command_error)
+}
+
+#[tauri::command]
+pub async fn update_telemetry_settings(
+    state: Ready<'_>,
+    settings: TelemetrySettings,
+) -> Result<TelemetrySettings, CommandError> {
+    state
+        .store
+        .set_setting(TELEMETRY_SETTINGS_KEY, &settings)
+        .await
+        .map_err(command_error)?;
+    Ok(settings)
+}
+
+pub(crate) async fn sync_cloud_subscriptions_inner(
+    app: &AppHandle,
+    state: &McpRuntimeState,
+    source_id: Option<&str>,
+    access_token: Option<String>,
+) -> Result<SyncChanges, McpError> {
+    let account = state.cloud_account(source_id).await?;
+    let source = account.source.clone();
+    let result = sync_account_subscriptions(state, account, access_token).await;
+    scheduler::record_result(app, state, &source, &result).await;
+    result
+}
+
+/// Skips reconciling when the response matches the last one applied, by
+/// `ETag` (a `304` included) or by body hash when the server sends none.
+async fn sync_account_subscriptions(
+    state: &McpRuntimeState,
+    account: CloudAccount,
+    access_token: Option<String>,
+) -> Result<SyncChanges, McpError> {
+    let url = format!(
+        "{}/api/v1/mcp/subscriptions",
+        account.base_url.trim_end_matches('/')
+    );
+    let etag = state
+        .store
+        .applied_config(&account.source.id)
+        .filter(|applied| is_entity_tag(applied));
+    let request = |token: &str| {
+        let request = state.client.get(&url).bearer_auth(token);
+        match &etag {
+            Some(etag) => request.header(IF_NONE_MATCH, etag),
+            None => request,
+        }
+    };
+    let response = match access_token {
+        Some(access_token) => request(&access_token)
+            .send()
+            .await
+            .map_err(|err| McpError::Network(err.to_string()))?,
+        None => {
+            account
+                .auth
+                .send(&state.client, &account.base_url, &request)
+                .await?
+        }
+    };
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return unchanged_subscriptions(state, &account.source).await;
+    }
+    if !response.status().is_success() {
+        return Err(McpError::Network(format!("cloud sync failed: {}", response.status())));
+    }
+
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_string);
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|err| McpError::Network(err.to_string()))?;
+    let fingerprint = match etag {
+        Some(etag) => etag,
+        None => state.store.compute_config_hash(&body)?,
+    };
+    if state
+        .store
+        .is_config_applied(&account.source.id, &fingerprint)
+    {
+        return unchanged_subscriptions(state, &account.source).await;
+    }
+    let subs: Vec<CloudSubscriptionItem> =
+        serde_json::from_value(body).map_err(|err| McpError::Network(err.to_string()))?;
+
+    let cloud_source = account.source;
+    state.store.forget_applied_config(&cloud_source.id);
+    let mut seen_identifiers = HashMap::new();
+
+    for sub in subs.iter() {
+        let change = apply_cloud_subscription(state, &cloud_source, sub).await?;
+        seen_identifiers.insert(sub.tool.identifier.clone(), change);
+    }
+
+    let all_tools = state.store.list_tools().await?;
+    let mut orphaned = HashSet::new();
     for tool in all_tools.iter().filter(|t| t.source_id.as_deref() == Some(&cloud_source.id)) {
         let Some(identifier) = tool.identifier.clone() else { continue };
-        if !seen_identifiers.contains(&identifier) {
+        if !seen_identifiers.contains_key(&identifier) {
+            orphaned.insert(tool.id.clone());
             let _ = state
                 .store
                 .set_tool_status(&tool.id, McpToolStatus::Orphaned, None, Some("cloud subscription removed".to_string()))
                 .await;
-            app.emit_all(&format!("mcp-log://{}", tool.id), McpLogEntry {
+            state.process_manager.publish_status(&tool.id).await;
+            state.process_manager.broadcast_log(&tool.id, McpLogEntry {
+                seq: 0,
                 timestamp: now_rfc3339(),
                 stream: crate::mcp::types::McpLogStream::Event,
                 message: "cloud subscription removed".to_string(),
-            }).ok();
+                level: crate::mcp::types::McpLogLevel::Warn,
+                structured: None,
+            });
+        }
+    }
+
+    let mut changes = SyncChanges::default();
+    for tool in state.store.list_tools().await? {
+        if tool.source_id.as_deref() != Some(cloud_source.id.as_str()) {
+            continue;
+        }
+        let change = if orphaned.contains(&tool.id) {
+            Some(ToolChange::Orphaned)
+        } else {
+            tool.identifier
+                .as_ref()
+                .and_then(|identifier| seen_identifiers.get(identifier).copied())
+        };
+        if let Some(change) = change {
+            changes.record(change, tool);
+        }
+    }
+    state
+        .store
+        .record_applied_config(&cloud_source.id, fingerprint);
+    Ok(changes)
+}
+
+async fn unchanged_subscriptions(
+    state: &McpRuntimeState,
+    cloud_source: &McpSource,
+) -> Result<SyncChanges, McpError> {
+    let mut changes = SyncChanges::default();
+    for tool in state.store.list_tools().await? {
+        if tool.source_id.as_deref() == Some(cloud_source.id.as_str()) {
+            changes.record(ToolChange::Unchanged, tool);
+        }
+    }
+    Ok(changes)
+}
+
+/// Body hashes are bare hex; only real entity tags (`"…"`, `W/"…"`) are
+/// sent back as `If-None-Match`.
+fn is_entity_tag(value: &str) -> bool {
+    value.starts_with('"') || value.starts_with("W/\"")
+}
+
+/// Reconciles a single cloud subscription with its local tool row: installs
+/// it when missing, or stages the new manifest as a pending update.
+pub(crate) async fn apply_cloud_subscription(
+    state: &McpRuntimeState,
+    cloud_source: &McpSource,
+    sub: &CloudSubscriptionItem,
+) -> Result<ToolChange, McpError> {
+    let tool = &sub.tool;
+    let config_json = build_cloud_config_json(tool).map_err(McpError::Validation)?;
+    let config_hash = state.store.compute_config_hash(&config_json)?;
+    let config_json_text = serde_json::to_string(&config_json)
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+
+    let extracted = ExtractedToolFields {
+        name: tool.name.clone(),
+        description: tool.description.clone(),
+        command: Some(tool.install_manifest.command.clone()),
+        args: Some(tool.install_manifest.args.clone()),
+        env: None,
+        capabilities: vec![],
+    };
+
+    let name_conflict = state
+        .store
+        .has_name_conflict(&extracted.name, &cloud_source.id)
+        .await?;
+
+    let existing = state
+        .store
+        .get_tool_by_source_identifier(&cloud_source.id, &tool.identifier)
+        .await?;
+
+    match existing {
+        Some(existing_tool) => {
+            if existing_tool.market_tool_id.is_none() {
+                state
+                    .store
+                    .set_tool_market_id(&existing_tool.id, &sub.market_tool_id)
+                    .await?;
+            }
+            if existing_tool.config_hash == config_hash
+                || is_absorbed_by_merge(state, &existing_tool, &config_json)?
+            {
+                return Ok(ToolChange::Unchanged);
+            }
+            let conflict_status = if name_conflict {
+                McpConflictStatus::Conflict
+            } else {
+                McpConflictStatus::UpdateAvailable
+            };
+            let newly_staged = is_newly_staged(&existing_tool, &config_hash, &conflict_status);
+            state
+                .store
+                .mark_tool_pending_update(
+                    &existing_tool.id,
+                    config_json_text,
+                    config_hash,
+                    conflict_status,
+                )
+                .await?;
+            if newly_staged {
+                state
+                    .process_manager
+                    .publish_pending_update(&existing_tool.id)
+                    .await;
+            }
+            Ok(ToolChange::Updated)
+        }
+        None => {
+            let tool_upsert = ToolUpsert {
+                id: None,
+                source_id: cloud_source.id.clone(),
+                identifier: Some(tool.identifier.clone()),
+                name: extracted.name,
+                source_type: McpSourceType::Cloud,
+                status: McpToolStatus::Stopped,
+                ping_ms: None,
+                capabilities: extracted.capabilities,
+                description: extracted.description,
+                error: None,
+                command: extracted.command,
+                args: extracted.args,
+                env: extracted.env,
+                config_json: config_json_text,
+                config_hash,
+                pending_config_json: None,
+                pending_config_hash: None,
+                conflict_status: if name_conflict {
+                    McpConflictStatus::Conflict
+                } else {
+                    McpConflictStatus::None
+                },
+                is_read_only: true,
+                is_new: true,
+            };
+            let installed = state.store.upsert_tool(tool_upsert).await?;
+            state
+                .store
+                .set_tool_market_id(&installed.id, &sub.market_tool_id)
+                .await?;
+            Ok(ToolChange::Added)
         }
     }
+}
 
-    state.store.list_tools().await.map_err(to_string)
+/// Syncs the local config source. Runs once at startup and from the tray's
+/// "Sync now".
+pub(crate) async fn sync_local_source(state: &McpRuntimeState) -> Result<(), McpError> {
+    let source = state.store.ensure_local_source().await?;
+    run_source_sync(state, source, None).await.map(|_| ())
+}
+
+/// Syncs `source`, recording the outcome on its status and reporting each
+/// phase on `mcp-sync-status://{source_id}`.
+pub(crate) async fn run_source_sync(
+    state: &McpRuntimeState,
+    source: McpSource,
+    auth_token: Option<String>,
+) -> Result<SyncChanges, McpError> {
+    let source_id = source.id.clone();
+    let reporter = SyncReporter::new(state.process_manager.events(), &source_id);
+    reporter.started();
+    let result = match state
+        .store
+        .update_source_status(&source_id, McpSourceStatus::Syncing, None)
+        .await
+    {
+        Ok(()) => sync_source_inner(state, source, auth_token, &reporter).await,
+        Err(err) => Err(err),
+    };
+    let result = match result {
+        Ok(synced) => state
+            .store
+            .update_source_status(&source_id, McpSourceStatus::Active, Some(now_rfc3339()))
+            .await
+            .map(|_| synced),
+        Err(err) => {
+            let _ = state
+                .store
+                .update_source_status(&source_id, McpSourceStatus::Error, None)
+                .await;
+            Err(err)
+        }
+    };
+    match result {
+        Ok((changes, counts)) => {
+            reporter.finished(counts);
+            Ok(changes)
+        }
+        Err(err) => {
+            reporter.failed(&err);
+            Err(err)
+        }
+    }
+}
+
+/// Whether staging `config_hash` changes anything the user has already been
+/// told about; repeated syncs of the same upstream change stay quiet.
+fn is_newly_staged(
+    existing: &McpTool,
+    config_hash: &str,
+    conflict_status: &McpConflictStatus,
+) -> bool {
+    existing.pending_config_hash.as_deref() != Some(config_hash)
+        || existing.conflict_status != *conflict_status
 }
 
 pub(crate) async fn sync_source_inner(
     state: &McpRuntimeState,
     source: McpSource,
     auth_token: Option<String>,
-) -> Result<Vec<McpTool>, McpError> {
-    let payload = match source.source_type {
-        McpSourceType::Local => {
-            let path = expand_path(&source.path_or_url);
-            let content = tokio::fs::read_to_string(&path)
-                .await
-                .map_err(|err| McpError::Storage(err.to_string()))?;
-            serde_json::from_str::<McpConfigPayload>(&content)
-                .map_err(|err| McpError::Storage(err.to_string()))?
+    reporter: &SyncReporter,
+) -> Result<(SyncChanges, SyncCounts), McpError> {
+    if source.source_type == McpSourceType::Local {
+        // Hashed after parsing, so saves that only reformat the file are
+        // skipped as well.
+        let value = read_local_config(&source.path_or_url).await?;
+        let content_hash = state.store.compute_config_hash(&value)?;
+        if state.store.is_config_applied(&source.id, &content_hash) {
+            let mut changes = SyncChanges::default();
+            for tool in state.store.list_tools().await? {
+                if tool.source_id.as_deref() == Some(source.id.as_str()) {
+                    changes.record(ToolChange::Unchanged, tool);
+                }
+            }
+            let counts = SyncCounts {
+                total: changes.unchanged.len(),
+                unchanged: changes.unchanged.len(),
+                ..SyncCounts::default()
+            };
+            return Ok((changes, counts));
         }
+        let payload = serde_json::from_value::<McpConfigPayload>(value)
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        let applied = apply_config_payload_counted(state, &source, payload, Some(reporter)).await?;
+        state.store.record_applied_config(&source.id, content_hash);
+        return Ok((applied.changes, applied.counts));
+    }
+
+    let payload = fetch_source_payload(
+        &state.client,
+        &source.source_type,
+        &source.path_or_url,
+        auth_token,
+    )
+    .await?;
+
+    let applied = apply_config_payload_counted(state, &source, payload, Some(reporter)).await?;
+    Ok((applied.changes, applied.counts))
+}
+
+async fn fetch_source_payload(
+    client: &reqwest::Client,
+    source_type: &McpSourceType,
+    path_or_url: &str,
+    auth_token: Option<String>,
+) -> Result<McpConfigPayload, McpError> {
+    match source_type {
+        McpSourceType::Local => serde_json::from_value::<McpConfigPayload>(
+            read_local_config(path_or_url).await?,
+        )
+        .map_err(|err| McpError::Storage(err.to_string())),
         _ => {
-            let mut request = state.client.get(&source.path_or_url);
+            let mut request = client.get(path_or_url);
             if let Some(token) = auth_token {
                 request = request.bearer_auth(token);
             }
@@ -604,11 +1881,16 @@ pub(crate) async fn sync_source_inner(
             response
                 .json::<McpConfigPayload>()
                 .await
-                .map_err(|err| McpError::Network(err.to_string()))?
+                .map_err(|err| McpError::Network(err.to_string()))
         }
-    };
+    }
+}
 
-    apply_config_payload(state, &source, payload).await
+async fn read_local_config(path: &str) -> Result<serde_json::Value, McpError> {
+    let content = tokio::fs::read_to_string(expand_path(path))
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+    serde_json::from_str(&content).map_err(|err| McpError::Storage(err.to_string()))
 }
 
 async fn apply_config_payload(
@@ -616,8 +1898,37 @@ async fn apply_config_payload(
     source: &McpSource,
     payload: McpConfigPayload,
 ) -> Result<Vec<McpTool>, McpError> {
-    let mut tools = Vec::with_capacity(payload.mcp_servers.len());
+    apply_config_payload_counted(state, source, payload, None)
+        .await
+        .map(|applied| applied.tools)
+}
+
+/// Every tool of the payload, with what the sync did to each.
+struct AppliedPayload {
+    tools: Vec<McpTool>,
+    changes: SyncChanges,
+    counts: SyncCounts,
+}
+
+async fn apply_config_payload_counted(
+    state: &McpRuntimeState,
+    source: &McpSource,
+    payload: McpConfigPayload,
+    reporter: Option<&SyncReporter>,
+) -> Result<AppliedPayload, McpError> {
+    state.store.forget_applied_config(&source.id);
+    let total = payload.mcp_servers.len();
+    let mut writes = Vec::with_capacity(total);
+    let mut tool_changes = Vec::with_capacity(total);
+    // Tools whose pending update or conflict changed, announced once the
+    // writes are committed.
+    let mut staged = Vec::new();
+    let mut counts = SyncCounts {
+        total,
+        ..SyncCounts::default()
+    };
     let is_read_only = source.source_type != McpSourceType::Local || source.is_read_only;
+    let mut snapshot = state.store.source_snapshot(&source.id).await?;
 
     for (name, config_payload) in payload.mcp_servers {
         let config_value = state.store.build_config_json(&name, &config_payload)?;
@@ -625,75 +1936,68 @@ async fn apply_config_payload(
         let config_json = serde_json::to_string(&config_value)
             .map_err(|err| McpError::Storage(err.to_string()))?;
         let extracted: ExtractedToolFields = state.store.extract_tool_fields(&name, &config_payload);
-        let name_conflict = state
-            .store
-            .has_name_conflict(&name, &source.id)
-            .await?;
-
-        let existing = state
-            .store
-            .get_tool_by_source_name(&source.id, &name)
-            .await?;
+        let name_conflict = snapshot.local_names.contains(&name);
 
-        let tool = match existing {
+        let write = match snapshot.tools.remove(&name) {
             Some(existing_tool) => {
-                if existing_tool.config_hash == config_hash {
-                    existing_tool
+                if existing_tool.config_hash == config_hash
+                    || is_absorbed_by_merge(state, &existing_tool, &config_value)?
+                {
+                    counts.unchanged += 1;
+                    SyncWrite::Keep(existing_tool.id)
                 } else if is_read_only {
+                    counts.pending += 1;
                     let conflict_status = if name_conflict {
                         McpConflictStatus::Conflict
                     } else {
                         McpConflictStatus::UpdateAvailable
                     };
-                    state
-                        .store
-                        .mark_tool_pending_update(
-                            &existing_tool.id,
-                            config_json,
-                            config_hash,
-                            conflict_status,
-                        )
-                        .await?;
-                    state
-                        .store
-                        .get_tool(&existing_tool.id)
-                        .await?
-                        .ok_or_else(|| McpError::NotFound("tool missing after update".to_string()))?
+                    if is_newly_staged(&existing_tool, &config_hash, &conflict_status) {
+                        staged.push(existing_tool.id.clone());
+                    }
+                    SyncWrite::Pending {
+                        id: existing_tool.id,
+                        config_json,
+                        config_hash,
+                        conflict_status,
+                    }
                 } else {
-                    state
-                        .store
-                        .upsert_tool(ToolUpsert {
-                            id: Some(existing_tool.id.clone()),
-                            source_id: source.id.clone(),
-                            identifier: existing_tool.identifier.clone(),
-                            name: extracted.name,
-                            source_type: source.source_type.clone(),
-                            status: existing_tool.status.clone(),
-                            ping_ms: existing_tool.ping_ms,
-                            capabilities: extracted.capabilities,
-                            description: extracted.description,
-                            error: existing_tool.error.clone(),
-                            command: extracted.command,
-                            args: extracted.args,
-                            env: extracted.env,
-                            config_json,
-                            config_hash,
-                            pending_config_json: None,
-                            pending_config_hash: None,
-                            conflict_status: if name_conflict {
-                                McpConflictStatus::Conflict
-                            } else {
-                                McpConflictStatus::None
-                            },
-                            is_read_only,
-                            is_new: existing_tool.is_new,
-                        })
-                        .await?
+                    counts.updated += 1;
+                    let conflict_status = if name_conflict {
+                        McpConflictStatus::Conflict
+                    } else {
+                        McpConflictStatus::None
+                    };
+                    if conflict_status != existing_tool.conflict_status {
+                        staged.push(existing_tool.id.clone());
+                    }
+                    SyncWrite::Upsert(Box::new(ToolUpsert {
+                        id: Some(existing_tool.id.clone()),
+                        source_id: source.id.clone(),
+                        identifier: existing_tool.identifier.clone(),
+                        name: extracted.name,
+                        source_type: source.source_type.clone(),
+                        status: existing_tool.status.clone(),
+                        ping_ms: existing_tool.ping_ms,
+                        capabilities: extracted.capabilities,
+                        description: extracted.description,
+                        error: existing_tool.error.clone(),
+                        command: extracted.command,
+                        args: extracted.args,
+                        env: extracted.env,
+                        config_json,
+                        config_hash,
+                        pending_config_json: None,
+                        pending_config_hash: None,
+                        conflict_status,
+                        is_read_only,
+                        is_new: existing_tool.is_new,
+                    }))
                 }
             }
-            None => state
-                .store
-                .upsert_tool(ToolUpsert {
+            None => {
+                counts.added += 1;
+                SyncWrite::Upsert(Box::new(ToolUpsert {
                     id: None,
                     source_id: source.id.clone(),
                     identifier: None,
@@ -718,43 +2022,91 @@ async fn apply_config_payload(
                     },
                     is_read_only,
                     is_new: true,
-                })
-                .await?,
+                }))
+            }
         };
 
-        tools.push(tool);
+        tool_changes.push(match &write {
+            SyncWrite::Keep(_) => ToolChange::Unchanged,
+            SyncWrite::Upsert(upsert) if upsert.id.is_none() => ToolChange::Added,
+            _ => ToolChange::Updated,
+        });
+        writes.push(write);
+        if let Some(reporter) = reporter {
+            reporter.progress(writes.len(), total);
+        }
     }
 
-    Ok(tools)
+    let tools = state.store.apply_sync_writes(&source.id, writes).await?;
+    for tool_id in staged {
+        state.process_manager.publish_pending_update(&tool_id).await;
+    }
+    let mut changes = SyncChanges::default();
+    for (change, tool) in tool_changes.into_iter().zip(tools.iter().cloned()) {
+        changes.record(change, tool);
+    }
+    Ok(AppliedPayload {
+        tools,
+        changes,
+        counts,
+    })
 }
 
 async fn apply_pending_update(
     state: &McpRuntimeState,
     tool_id: &str,
 ) -> Result<McpTool, McpError> {
+    let (tool, pending_value) = load_pending_update(state, tool_id).await?;
+    apply_tool_config(state, tool, pending_value).await
+}
+
+async fn merge_pending_update(
+    state: &McpRuntimeState,
+    tool_id: &str,
+) -> Result<McpTool, McpError> {
+    let (tool, pending_value) = load_pending_update(state, tool_id).await?;
+    let current_value: serde_json::Value =
+        serde_json::from_str(&tool.config_json).map_err(|err| McpError::Storage(err.to_string()))?;
+    let merged_value = merge_config(&current_value, tool.env.as_ref(), &pending_value);
+    apply_tool_config(state, tool, merged_value).await
+}
+
+async fn load_pending_update(
+    state: &McpRuntimeState,
+    tool_id: &str,
+) -> Result<(McpTool, serde_json::Value), McpError> {
     let tool = state
         .store
         .get_tool(tool_id)
         .await?
-        .ok_or_else(|| McpError::NotFound(format!("tool {tool_id} not found")))?;
-    let source_id = tool
-        .source_id
-        .clone()
-        .ok_or_else(|| McpError::Validation("tool missing source_id".to_string()))?;
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
     let pending_json = state
         .store
         .get_pending_config_json(tool_id)
         .await?
         .ok_or_else(|| McpError::Validation("no pending config".to_string()))?;
-
     let pending_value: serde_json::Value =
         serde_json::from_str(&pending_json).map_err(|err| McpError::Storage(err.to_string()))?;
-    let pending_payload: McpToolConfigPayload =
-        serde_json::from_value(pending_value.clone()).map_err(|err| McpError::Storage(err.to_string()))?;
+    Ok((tool, pending_value))
+}
+
+async fn apply_tool_config(
+    state: &McpRuntimeState,
+    tool: McpTool,
+    config_value: serde_json::Value,
+) -> Result<McpTool, McpError> {
+    let source_id = tool
+        .source_id
+        .clone()
+        .ok_or_else(|| McpError::Validation("tool missing source_id".to_string()))?;
+    let payload: McpToolConfigPayload =
+        serde_json::from_value(config_value.clone()).map_err(|err| McpError::Storage(err.to_string()))?;
     let extracted = state
         .store
-        .extract_tool_fields(&tool.name, &pending_payload);
-    let config_hash = state.store.compute_config_hash(&pending_value)?;
+        .extract_tool_fields(&tool.name, &payload);
+    let config_hash = state.store.compute_config_hash(&config_value)?;
+    let config_json =
+        serde_json::to_string(&config_value).map_err(|err| McpError::Storage(err.to_string()))?;
 
     let updated = state
         .store
@@ -772,7 +2124,7 @@ async fn apply_pending_update(
             command: extracted.command,
             args: extracted.args,
             env: extracted.env,
-            config_json: pending_json,
+            config_json,
             config_hash,
             pending_config_json: None,
             pending_config_hash: None,
@@ -785,6 +2137,21 @@ async fn apply_pending_update(
     Ok(updated)
 }
 
+/// Returns true when `incoming` is already reflected in the tool's config once
+/// the user's local env overrides are merged in, i.e. a previous "merge"
+/// resolution already absorbed this upstream change.
+fn is_absorbed_by_merge(
+    state: &McpRuntimeState,
+    tool: &McpTool,
+    incoming: &serde_json::Value,
+) -> Result<bool, McpError> {
+    let Ok(current_value) = serde_json::from_str::<serde_json::Value>(&tool.config_json) else {
+        return Ok(false);
+    };
+    let merged = merge_config(&current_value, tool.env.as_ref(), incoming);
+    Ok(state.store.compute_config_hash(&merged)? == tool.config_hash)
+}
+
 fn build_cloud_config_json(tool: &CloudToolSummary) -> Result<serde_json::Value, String> {
     let mut map = serde_json::Map::new();
     map.insert("identifier".to_string(), serde_json::Value::String(tool.identifier.clone()));
@@ -823,23 +2190,74 @@ fn build_cloud_config_json(tool: &CloudToolSummary) -> Result<serde_json::Value,
     Ok(serde_json::Value::Object(map))
 }
 
-fn missing_required_env(tool: &McpTool) -> Option<Vec<String>> {
-    let config: serde_json::Value = serde_json::from_str(&tool.config_json).ok()?;
-    let env_config = config.get("env_config")?.as_array()?;
-    let env = tool.env.as_ref();
-    let mut missing = Vec::new();
-    for item in env_config {
-        let key = item.get("key").and_then(|v| v.as_str()).unwrap_or("");
-        let required = item.get("required").and_then(|v| v.as_bool()).unwrap_or(false);
-        if !required || key.is_empty() {
-            continue;
-        }
-        let present = env.and_then(|env| env.get(key)).map(|v| !v.is_empty()).unwrap_or(false);
-        if !present {
-            missing.push(key.to_string());
-        }
+/// Builds the marketplace payload for a local tool. Env is published as keys
+/// only; a non-secret value becomes the default just when its key is listed in
+/// `default_env_keys`. Args go out with secret flags and URL credentials
+/// redacted, as in diagnostics exports.
+fn build_publish_request(
+    tool: &McpTool,
+    identifier: String,
+    metadata: PublishToolRequest,
+) -> Result<CloudPublishRequest, McpError> {
+    let command = tool
+        .command
+        .clone()
+        .ok_or_else(|| McpError::validation("tool has no command to publish"))?;
+    let runtime = metadata
+        .runtime
+        .unwrap_or_else(|| infer_runtime(&command).to_string());
+
+    let mut env_keys: Vec<(&String, &String)> = tool
+        .env
+        .as_ref()
+        .map(|env| env.iter().collect())
+        .unwrap_or_default();
+    env_keys.sort();
+    let env_config = env_keys
+        .into_iter()
+        .map(|(key, value)| {
+            let secret = is_secret_env_key(key);
+            let mut item = Map::new();
+            item.insert("key".to_string(), Value::String(key.clone()));
+            item.insert("label".to_string(), Value::String(key.clone()));
+            item.insert("required".to_string(), Value::Bool(true));
+            item.insert("secret".to_string(), Value::Bool(secret));
+            if !secret && !value.is_empty() && metadata.default_env_keys.contains(key) {
+                item.insert("default".to_string(), Value::String(value.clone()));
+            }
+            Value::Object(item)
+        })
+        .collect();
+
+    Ok(CloudPublishRequest {
+        identifier,
+        name: metadata.name.unwrap_or_else(|| tool.name.clone()),
+        description: metadata
+            .description
+            .unwrap_or_else(|| tool.description.clone()),
+        category: metadata.category.unwrap_or_else(|| "other".to_string()),
+        tags: metadata.tags.unwrap_or_default(),
+        avatar_url: metadata.avatar_url,
+        install_manifest: CloudPublishManifest {
+            runtime,
+            command,
+            args: redact_args(tool.args.as_deref().unwrap_or_default()),
+            env_config,
+        },
+    })
+}
+
+fn infer_runtime(command: &str) -> &'static str {
+    let program = std::path::Path::new(command)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(command);
+    match program {
+        "npx" | "node" | "npm" | "pnpm" | "bunx" | "bun" => "node",
+        "uvx" | "uv" | "python" | "python3" | "pipx" => "python",
+        "docker" | "podman" => "docker",
+        _ => "binary",
     }
-    Some(missing)
 }
 
 fn now_rfc3339() -> String {
@@ -848,8 +2266,8 @@ fn now_rfc3339() -> String {
         .unwrap_or_else(|_| "".to_string())
 }
 
-fn to_string(err: McpError) -> String {
-    err.to_string()
+fn command_error(err: McpError) -> CommandError {
+    err.into()
 }
 
 pub fn default_cloud_source_name() -> &'static str {
@@ -857,5 +2275,116 @@ pub fn default_cloud_source_name() -> &'static str {
 }
 
 pub fn default_local_source_path() -> PathBuf {
-    expand_path("~/.config/deeting/mcp.json")
+    crate::paths::default_local_config_path()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_tool(args: &[&str], env: &[(&str, &str)]) -> McpTool {
+        McpTool {
+            id: "tool".to_string(),
+            identifier: None,
+            name: "db".to_string(),
+            source_type: McpSourceType::Local,
+            source_id: None,
+            status: McpToolStatus::Stopped,
+            ping_ms: None,
+            capabilities: Vec::new(),
+            description: String::new(),
+            error: None,
+            command: Some("npx".to_string()),
+            args: Some(args.iter().map(|arg| arg.to_string()).collect()),
+            env: Some(
+                env.iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            ),
+            config_json: "{}".to_string(),
+            pending_config_json: None,
+            config_hash: String::new(),
+            pending_config_hash: None,
+            conflict_status: McpConflictStatus::None,
+            is_read_only: false,
+            is_new: false,
+            approved_at: None,
+            market_tool_id: None,
+            auto_start: false,
+            log_to_file: false,
+            log_buffer_size: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    fn metadata(default_env_keys: &[&str]) -> PublishToolRequest {
+        PublishToolRequest {
+            identifier: "me/db".to_string(),
+            name: None,
+            description: None,
+            category: None,
+            tags: None,
+            avatar_url: None,
+            runtime: None,
+            default_env_keys: default_env_keys.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn tests_local_sources_without_syncing() {
+        let dir = std::env::temp_dir().join(format!("deeting-source-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("mcp.json");
+        std::fs::write(
+            &path,
+            r#"{"mcpServers":{"github":{"command":"npx"},"fs":{"command":"npx"}}}"#,
+        )
+        .unwrap();
+        let client = reqwest::Client::new();
+
+        let fetched =
+            fetch_source_payload(&client, &McpSourceType::Local, path.to_str().unwrap(), None).await;
+        let result = source_test_result(fetched);
+        assert!(result.ok);
+        assert_eq!(result.server_count, 2);
+        assert_eq!(result.server_names, ["fs", "github"]);
+        assert_eq!(result.error, None);
+
+        let missing = dir.join("missing.json");
+        let fetched =
+            fetch_source_payload(&client, &McpSourceType::Local, missing.to_str().unwrap(), None)
+                .await;
+        let result = source_test_result(fetched);
+        assert!(!result.ok);
+        assert_eq!(result.server_count, 0);
+        assert!(result.error.is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn publishes_no_credentials() {
+        let tool = local_tool(
+            &["server-postgres", "postgres://app:pw@db/app", "--api-key", "sk-live"],
+            &[("DATABASE_URL", "postgres://app:pw@db/app"), ("REGION", "eu")],
+        );
+        let request =
+            build_publish_request(&tool, "me/db".to_string(), metadata(&["REGION"])).unwrap();
+        let body = serde_json::to_string(&request).unwrap();
+        assert!(!body.contains("pw@"), "{body}");
+        assert!(!body.contains("sk-live"), "{body}");
+        assert_eq!(
+            request.install_manifest.args,
+            ["server-postgres", "postgres://[redacted]@db/app", "--api-key", "[redacted]"]
+        );
+
+        let defaults: Vec<_> = request
+            .install_manifest
+            .env_config
+            .iter()
+            .filter_map(|item| Some((item["key"].as_str()?, item.get("default")?.as_str()?)))
+            .collect();
+        assert_eq!(defaults, [("REGION", "eu")]);
+    }
 }
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use log::warn;
+use mcp_core::env::is_secret_env_key;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::mcp::error::McpError;
+use crate::mcp::import::parse_config;
+use crate::mcp::policy::requires_approval;
+use crate::mcp::types::{McpConfigPayload, McpTrustLevel, ToolHooks};
+use crate::mcp::McpRuntimeState;
+
+pub const SCHEME: &str = "deeting";
+pub const INSTALL_REQUEST_EVENT: &str = "mcp-install-request";
+/// Shown in place of secret env values in an install preview.
+const MASKED_VALUE: &str = "********";
+
+/// What a `deeting://install` link asks to install:
+/// `?market_id=<id>` subscribes to a marketplace tool, `?config=<base64>`
+/// imports a config (any layout `import::parse_config` accepts) into the
+/// local source.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InstallPayload {
+    Market { market_tool_id: String },
+    Config { config: McpConfigPayload },
+}
+
+/// What a server the install would add runs, shown before the user
+/// confirms: its command line, env, hooks and health check command.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallServerPreview {
+    pub name: String,
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    /// Secret values (see `is_secret_env_key`) are masked.
+    pub env: BTreeMap<String, String>,
+    pub hooks: ToolHooks,
+    pub healthcheck_command: Option<String>,
+}
+
+/// An install waiting for the user to confirm it in the app, from a link or
+/// the clipboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallRequest {
+    pub id: String,
+    pub payload: InstallPayload,
+    pub source_name: String,
+    pub trust_level: McpTrustLevel,
+    /// Whether the installed tools still need approval before their first start.
+    pub requires_approval: bool,
+    /// Empty for marketplace installs; the cloud resolves the config.
+    pub servers: Vec<InstallServerPreview>,
+}
+
+#[derive(Clone, Default)]
+pub struct PendingInstalls(Arc<Mutex<HashMap<String, InstallRequest>>>);
+
+impl PendingInstalls {
+    pub async fn list(&self) -> Vec<InstallRequest> {
+        self.0.lock().await.values().cloned().collect()
+    }
+
+    pub async fn take(&self, id: &str) -> Option<InstallRequest> {
+        self.0.lock().await.remove(id)
+    }
+
+    async fn insert(&self, request: InstallRequest) {
+        self.0.lock().await.insert(request.id.clone(), request);
+    }
+}
+
+pub fn parse_install_url(url: &Url) -> Result<InstallPayload, McpError> {
+    if url.scheme() != SCHEME || url.host_str() != Some("install") {
+        return Err(McpError::validation(format!("unsupported link: {url}")));
+    }
+    let params: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    if let Some(market_tool_id) = params.get("market_id").filter(|id| !id.is_empty()) {
+        return Ok(InstallPayload::Market {
+            market_tool_id: market_tool_id.clone(),
+        });
+    }
+    let encoded = params
+        .get("config")
+        .ok_or_else(|| McpError::validation("install link needs market_id or config"))?;
+    let trimmed = encoded.trim().trim_end_matches('=');
+    let bytes = URL_SAFE_NO_PAD
+        .decode(trimmed)
+        // An unescaped `+` from the standard alphabet arrives as a space.
+        .or_else(|_| STANDARD.decode(encoded.trim().replace(' ', "+")))
+        .map_err(|err| McpError::validation(format!("invalid config encoding: {err}")))?;
+    let config = parse_config(&bytes)?;
+    Ok(InstallPayload::Config { config })
+}
+
+/// Turns an opened link into a pending install and brings the window up so
+/// the user can confirm it. Nothing is installed until they do.
+pub async fn handle_url(app: &AppHandle, url: &Url) {
+    let payload = match parse_install_url(url) {
+        Ok(payload) => payload,
+        Err(err) => {
+            warn!("ignoring deep link: {}", err);
+            return;
+        }
+    };
+    let state = app.state::<McpRuntimeState>();
+    let request = match stage_install(&state, payload).await {
+        Ok(request) => request,
+        Err(err) => {
+            warn!("failed to prepare deep link install: {}", err);
+            return;
+        }
+    };
+    crate::tray::show_main_window(app);
+    let _ = app.emit(INSTALL_REQUEST_EVENT, request);
+}
+
+/// Records an install for `confirm_install_request`; also used by clipboard
+/// imports so both share the same preview and confirmation.
+pub(crate) async fn stage_install(
+    state: &McpRuntimeState,
+    payload: InstallPayload,
+) -> Result<InstallRequest, McpError> {
+    let request = build_request(state, payload).await?;
+    state.pending_installs.insert(request.clone()).await;
+    Ok(request)
+}
+
+async fn build_request(
+    state: &McpRuntimeState,
+    payload: InstallPayload,
+) -> Result<InstallRequest, McpError> {
+    let (source, servers) = match &payload {
+        InstallPayload::Market { .. } => (state.cloud_account(None).await?.source, Vec::new()),
+        InstallPayload::Config { config } => {
+            let mut servers: Vec<InstallServerPreview> = config
+                .mcp_servers
+                .iter()
+                .map(|(name, server)| InstallServerPreview {
+                    name: name.clone(),
+                    command: server.command.clone(),
+                    args: server.args.clone().unwrap_or_default(),
+                    env: preview_env(server.env.as_ref()),
+                    hooks: server.hooks.clone().unwrap_or_default(),
+                    healthcheck_command: server
+                        .healthcheck
+                        .as_ref()
+                        .and_then(|check| check.command.clone()),
+                })
+                .collect();
+            servers.sort_by(|a, b| a.name.cmp(&b.name));
+            (state.store.ensure_local_source().await?, servers)
+        }
+    };
+    Ok(InstallRequest {
+        id: Uuid::new_v4().to_string(),
+        payload,
+        requires_approval: requires_approval(&source.trust_level),
+        source_name: source.name,
+        trust_level: source.trust_level,
+        servers,
+    })
+}
+
+fn preview_env(env: Option<&HashMap<String, String>>) -> BTreeMap<String, String> {
+    env.into_iter()
+        .flatten()
+        .map(|(key, value)| {
+            let shown = if is_secret_env_key(key) && !value.is_empty() {
+                MASKED_VALUE.to_string()
+            } else {
+                value.clone()
+            };
+            (key.clone(), shown)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> Result<InstallPayload, McpError> {
+        parse_install_url(&Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn parses_marketplace_install() {
+        match parse("deeting://install?market_id=tool-123").unwrap() {
+            InstallPayload::Market { market_tool_id } => assert_eq!(market_tool_id, "tool-123"),
+            other => panic!("unexpected payload: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parses_base64_config_with_either_alphabet() {
+        let json = r#"{"mcpServers":{"fs":{"command":"npx","args":["-y","fs?"]}}}"#;
+        for encoded in [URL_SAFE_NO_PAD.encode(json), STANDARD.encode(json)] {
+            let url = Url::parse_with_params("deeting://install", &[("config", encoded)]).unwrap();
+            match parse_install_url(&url).unwrap() {
+                InstallPayload::Config { config } => {
+                    assert_eq!(config.mcp_servers["fs"].command.as_deref(), Some("npx"));
+                }
+                other => panic!("unexpected payload: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn masks_secret_env_values_in_previews() {
+        let env = HashMap::from([
+            ("API_KEY".to_string(), "sk-live".to_string()),
+            ("GITHUB_TOKEN".to_string(), String::new()),
+            ("LOG_LEVEL".to_string(), "debug".to_string()),
+        ]);
+        let preview = preview_env(Some(&env));
+        assert_eq!(preview["API_KEY"], MASKED_VALUE);
+        assert_eq!(preview["GITHUB_TOKEN"], "");
+        assert_eq!(preview["LOG_LEVEL"], "debug");
+        assert!(preview_env(None).is_empty());
+    }
+
+    #[test]
+    fn rejects_other_hosts_and_empty_configs() {
+        assert!(parse("deeting://open?market_id=x").is_err());
+        assert!(parse("deeting://install").is_err());
+        let empty = URL_SAFE_NO_PAD.encode(r#"{"mcpServers":{}}"#);
+        assert!(parse(&format!("deeting://install?config={empty}")).is_err());
+    }
+}
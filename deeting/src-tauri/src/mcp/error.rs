@@ -1,3 +1,6 @@
+use mcp_core::env::EnvError;
+use serde::Serialize;
+use serde_json::{json, Value};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -6,16 +9,87 @@ pub enum McpError {
     Validation(String),
     #[error("not found: {0}")]
     NotFound(String),
+    #[error("not found: tool {0} not found")]
+    ToolNotFound(String),
+    #[error("not found: source {0} not found")]
+    SourceNotFound(String),
+    #[error("validation error: missing required env: {}", .0.join(", "))]
+    EnvMissing(Vec<String>),
     #[error("process error: {0}")]
     Process(String),
+    #[error("process error: tool {0} already running")]
+    ToolAlreadyRunning(String),
     #[error("storage error: {0}")]
     Storage(String),
     #[error("network error: {0}")]
     Network(String),
+    #[error("approval required: {0}")]
+    ApprovalRequired(String),
+    #[error("the app is still starting up")]
+    Initializing,
+    #[error("startup failed: {0}")]
+    StartupFailed(String),
 }
 
 impl McpError {
     pub fn validation(message: impl Into<String>) -> Self {
         McpError::Validation(message.into())
     }
+
+    /// Stable identifier the frontend localizes; never reworded once shipped.
+    pub fn code(&self) -> &'static str {
+        match self {
+            McpError::Validation(_) => "VALIDATION_FAILED",
+            McpError::NotFound(_) => "NOT_FOUND",
+            McpError::ToolNotFound(_) => "TOOL_NOT_FOUND",
+            McpError::SourceNotFound(_) => "SOURCE_NOT_FOUND",
+            McpError::EnvMissing(_) => "ENV_MISSING",
+            McpError::Process(_) => "PROCESS_ERROR",
+            McpError::ToolAlreadyRunning(_) => "TOOL_ALREADY_RUNNING",
+            McpError::Storage(_) => "STORAGE_ERROR",
+            McpError::Network(_) => "NETWORK_ERROR",
+            McpError::ApprovalRequired(_) => "APPROVAL_REQUIRED",
+            McpError::Initializing => "INITIALIZING",
+            McpError::StartupFailed(_) => "STARTUP_FAILED",
+        }
+    }
+
+    fn details(&self) -> Option<Value> {
+        match self {
+            McpError::ToolNotFound(id) | McpError::ToolAlreadyRunning(id) => {
+                Some(json!({ "tool_id": id }))
+            }
+            McpError::SourceNotFound(id) => Some(json!({ "source_id": id })),
+            McpError::EnvMissing(keys) => Some(json!({ "missing": keys })),
+            _ => None,
+        }
+    }
+}
+
+impl From<EnvError> for McpError {
+    fn from(err: EnvError) -> Self {
+        match err {
+            EnvError::Invalid(message) => McpError::Validation(message),
+            EnvError::Missing(keys) => McpError::EnvMissing(keys),
+        }
+    }
+}
+
+/// What a failed command rejects with on the frontend.
+#[derive(Debug, Serialize)]
+pub struct CommandError {
+    pub code: &'static str,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Value>,
+}
+
+impl From<McpError> for CommandError {
+    fn from(err: McpError) -> Self {
+        Self {
+            code: err.code(),
+            details: err.details(),
+            message: err.to_string(),
+        }
+    }
 }
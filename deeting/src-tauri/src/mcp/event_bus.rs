@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use log::warn;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::mcp::notifications::{self, NotificationCategory};
+use crate::mcp::store::McpStore;
+use crate::mcp::types::{McpConflictStatus, McpToolStatusEvent, SyncStatusEvent};
+
+const EVENT_BUS_CAPACITY: usize = 256;
+
+/// Something that happened to a tool or source. The process manager and sync
+/// publish these on the [`EventBus`]; [`spawn_event_emitter`] turns them into
+/// window events, tray refreshes and desktop notifications.
+#[derive(Debug, Clone)]
+pub enum DomainEvent {
+    /// A tool's status was written; `status` is `None` once it is gone.
+    ToolStatus {
+        tool_id: String,
+        status: Option<McpToolStatusEvent>,
+    },
+    ToolCrashed {
+        tool_id: String,
+        tool_name: String,
+        message: String,
+    },
+    ConflictDetected {
+        tool_id: String,
+        tool_name: String,
+        conflict_status: McpConflictStatus,
+    },
+    SourceSync(SyncStatusEvent),
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl EventBus {
+    pub fn publish(&self, event: DomainEvent) {
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[derive(Serialize)]
+struct SupervisorPayload {
+    tool_id: String,
+    tool_name: String,
+    message: String,
+}
+
+/// Delivers bus events to the frontend for the life of the app:
+/// `mcp-status://{tool_id}`, `mcp-supervisor` and
+/// `mcp-sync-status://{source_id}`, plus the tray and notifications.
+pub fn spawn_event_emitter(
+    app: AppHandle,
+    store: Arc<McpStore>,
+    mut events: broadcast::Receiver<DomainEvent>,
+) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => emit(&app, &store, event).await,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("event emitter lagged, skipped {} events", skipped);
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+async fn emit(app: &AppHandle, store: &McpStore, event: DomainEvent) {
+    match event {
+        DomainEvent::ToolStatus { tool_id, status } => {
+            if let Some(status) = status {
+                let _ = app.emit(&format!("mcp-status://{}", tool_id), status);
+            }
+            crate::tray::refresh(app);
+        }
+        DomainEvent::ToolCrashed {
+            tool_id,
+            tool_name,
+            message,
+        } => {
+            notifications::notify(
+                app,
+                store,
+                NotificationCategory::Crashes,
+                &format!("{tool_name} crashed"),
+                &message,
+            )
+            .await;
+            let _ = app.emit(
+                "mcp-supervisor",
+                SupervisorPayload {
+                    tool_id,
+                    tool_name,
+                    message,
+                },
+            );
+        }
+        DomainEvent::ConflictDetected {
+            tool_name,
+            conflict_status,
+            ..
+        } => {
+            let body = match conflict_status {
+                McpConflictStatus::None => return,
+                McpConflictStatus::UpdateAvailable => "An update is available.",
+                McpConflictStatus::Conflict => "An update clashes with another tool name.",
+            };
+            notifications::notify(app, store, NotificationCategory::Updates, &tool_name, body)
+                .await;
+        }
+        DomainEvent::SourceSync(event) => {
+            let source_id = match &event {
+                SyncStatusEvent::Started { source_id }
+                | SyncStatusEvent::Progress { source_id, .. }
+                | SyncStatusEvent::Finished { source_id, .. }
+                | SyncStatusEvent::Failed { source_id, .. } => source_id.clone(),
+            };
+            let _ = app.emit(&format!("mcp-sync-status://{}", source_id), event);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::mcp::error::McpError;
+use crate::mcp::types::{McpConfigPayload, McpToolConfigPayload};
+
+/// Parses an MCP config file in any of the layouts other clients write:
+/// `mcpServers` (Claude Desktop, Cursor), `servers` (VS Code `mcp.json`) or
+/// `mcp.servers` (VS Code `settings.json`). Every server must name a command
+/// or a url.
+pub fn parse_config(bytes: &[u8]) -> Result<McpConfigPayload, McpError> {
+    let root: Value = serde_json::from_slice(bytes)
+        .map_err(|err| McpError::validation(format!("config is not valid JSON: {err}")))?;
+    let servers = detect_servers(&root).ok_or_else(|| {
+        McpError::validation("unrecognized config format: expected mcpServers, servers or mcp.servers")
+    })?;
+    let mcp_servers: HashMap<String, McpToolConfigPayload> =
+        serde_json::from_value(servers.clone())
+            .map_err(|err| McpError::validation(format!("invalid server entry: {err}")))?;
+    if mcp_servers.is_empty() {
+        return Err(McpError::validation("config has no servers"));
+    }
+    for (name, server) in &mcp_servers {
+        let has_command = server
+            .command
+            .as_deref()
+            .is_some_and(|command| !command.trim().is_empty());
+        let has_url = server.extra.get("url").is_some_and(Value::is_string);
+        if !has_command && !has_url {
+            return Err(McpError::validation(format!("server {name} needs a command or url")));
+        }
+    }
+    Ok(McpConfigPayload { mcp_servers })
+}
+
+/// Like `parse_config`, for text pasted from a README: strips Markdown code
+/// fences, `//` and `/* */` comments and trailing commas, and wraps a bare
+/// `"mcpServers": {...}` fragment in braces.
+pub fn parse_config_lenient(text: &str) -> Result<McpConfigPayload, McpError> {
+    let cleaned = strip_trailing_commas(&strip_comments(strip_code_fence(text.trim())));
+    let cleaned = cleaned.trim();
+    if cleaned.starts_with('{') {
+        parse_config(cleaned.as_bytes())
+    } else {
+        parse_config(format!("{{{cleaned}}}").as_bytes())
+    }
+}
+
+fn strip_code_fence(text: &str) -> &str {
+    let Some(rest) = text.strip_prefix("```") else {
+        return text;
+    };
+    // Drop the info string (`json`, `jsonc`) on the opening line.
+    let body = rest.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end().strip_suffix("```").unwrap_or(body)
+}
+
+/// Removes comments outside string literals.
+fn strip_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(ch) = chars.next() {
+        if in_string {
+            out.push(ch);
+            match ch {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (ch, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(ch);
+            }
+            ('/', Some('/')) => {
+                while chars.next_if(|next| *next != '\n').is_some() {}
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = None;
+                for next in chars.by_ref() {
+                    if previous == Some('*') && next == '/' {
+                        break;
+                    }
+                    previous = Some(next);
+                }
+            }
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// Removes commas directly before a closing bracket, outside string literals.
+fn strip_trailing_commas(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for ch in text.chars() {
+        if in_string {
+            match ch {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if ch == '"' {
+            in_string = true;
+        } else if ch == '}' || ch == ']' {
+            let trimmed = out.trim_end().len();
+            if out[..trimmed].ends_with(',') {
+                out.truncate(trimmed - 1);
+            }
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn detect_servers(root: &Value) -> Option<&Value> {
+    root.get("mcpServers")
+        .or_else(|| root.get("servers"))
+        .or_else(|| root.get("mcp").and_then(|mcp| mcp.get("servers")))
+        .filter(|servers| servers.is_object())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_known_layouts() {
+        for config in [
+            r#"{"mcpServers":{"fs":{"command":"npx"}}}"#,
+            r#"{"servers":{"fs":{"command":"npx"}}}"#,
+            r#"{"mcp":{"servers":{"fs":{"command":"npx"}}},"editor.fontSize":14}"#,
+        ] {
+            let parsed = parse_config(config.as_bytes()).unwrap();
+            assert_eq!(parsed.mcp_servers["fs"].command.as_deref(), Some("npx"));
+        }
+    }
+
+    #[test]
+    fn accepts_url_only_servers() {
+        let parsed = parse_config(br#"{"servers":{"remote":{"url":"https://x/mcp"}}}"#).unwrap();
+        assert!(parsed.mcp_servers.contains_key("remote"));
+    }
+
+    #[test]
+    fn lenient_parse_accepts_readme_snippets() {
+        let snippet = r#"```jsonc
+"mcpServers": {
+  // Local files
+  "fs": {
+    "command": "npx", /* pinned */
+    "args": ["-y", "https://example.com/a,]", "x//y"],
+  },
+}
+```"#;
+        let parsed = parse_config_lenient(snippet).unwrap();
+        let args = parsed.mcp_servers["fs"].args.clone().unwrap();
+        assert_eq!(args, ["-y", "https://example.com/a,]", "x//y"]);
+    }
+
+    #[test]
+    fn rejects_unknown_or_incomplete_configs() {
+        assert!(parse_config(b"not json").is_err());
+        assert!(parse_config(br#"{"tools":{}}"#).is_err());
+        assert!(parse_config(br#"{"mcpServers":{}}"#).is_err());
+        assert!(parse_config(br#"{"mcpServers":{"fs":{"args":["-y"]}}}"#).is_err());
+    }
+}
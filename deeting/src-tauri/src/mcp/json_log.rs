@@ -0,0 +1,12 @@
+use mcp_core::json_log;
+
+use crate::mcp::types::StructuredLog;
+
+/// [`json_log::detect`] as the [`StructuredLog`] stored with a line.
+pub fn detect(message: &str) -> Option<StructuredLog> {
+    json_log::detect(message).map(|log| StructuredLog {
+        level: log.level,
+        msg: log.msg,
+        time: log.time,
+    })
+}
@@ -0,0 +1,67 @@
+use crate::mcp::error::McpError;
+use crate::mcp::types::{LogExportFormat, McpLogEntry};
+
+pub fn format_line(entry: &McpLogEntry) -> String {
+    format!("{} [{}] {}", entry.timestamp, entry.stream.as_str(), entry.message)
+}
+
+/// Renders log entries as file content, one entry per line with a trailing
+/// newline.
+pub fn render(entries: &[McpLogEntry], format: LogExportFormat) -> Result<String, McpError> {
+    let mut out = String::new();
+    for entry in entries {
+        match format {
+            LogExportFormat::Text => out.push_str(&format_line(entry)),
+            LogExportFormat::Jsonl => out.push_str(
+                &serde_json::to_string(entry).map_err(|err| McpError::Storage(err.to_string()))?,
+            ),
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::types::{McpLogLevel, McpLogStream};
+
+    fn entries() -> Vec<McpLogEntry> {
+        vec![
+            McpLogEntry {
+                seq: 1,
+                timestamp: "2024-01-01T00:00:00Z".to_string(),
+                stream: McpLogStream::Stdout,
+                message: "ready".to_string(),
+                level: McpLogLevel::Info,
+                structured: None,
+            },
+            McpLogEntry {
+                seq: 2,
+                timestamp: "2024-01-01T00:00:01Z".to_string(),
+                stream: McpLogStream::Stderr,
+                message: "say \"hi\"".to_string(),
+                level: McpLogLevel::Info,
+                structured: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn renders_text_lines() {
+        assert_eq!(
+            render(&entries(), LogExportFormat::Text).unwrap(),
+            "2024-01-01T00:00:00Z [stdout] ready\n2024-01-01T00:00:01Z [stderr] say \"hi\"\n"
+        );
+    }
+
+    #[test]
+    fn renders_one_json_object_per_line() {
+        let out = render(&entries(), LogExportFormat::Jsonl).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let second: McpLogEntry = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(second.stream, McpLogStream::Stderr);
+        assert_eq!(second.message, "say \"hi\"");
+    }
+}
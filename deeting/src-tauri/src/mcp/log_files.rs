@@ -0,0 +1,112 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::mcp::types::McpTool;
+
+pub const DEFAULT_LOG_FILE_MAX_BYTES: u64 = 5 * 1024 * 1024;
+pub const DEFAULT_LOG_FILE_KEEP: usize = 5;
+const CURRENT_FILE: &str = "current.log";
+
+/// `logs/<name>-<id>` under the config directory, where a tool's
+/// `log_to_file` output goes.
+pub fn tool_log_dir(tool: &McpTool) -> PathBuf {
+    crate::paths::config_dir().join("logs").join(format!(
+        "{}-{}",
+        crate::mcp::diagnostics::file_name(&tool.name),
+        tool.id
+    ))
+}
+
+/// `current.log` in a tool's log directory, rotated to `current.log.1`,
+/// `.2`, ... once it would grow past `max_bytes`; rotations beyond `keep`
+/// are deleted.
+pub struct RotatingLog {
+    dir: PathBuf,
+    file: Option<File>,
+    size: u64,
+}
+
+impl RotatingLog {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            file: None,
+            size: 0,
+        }
+    }
+
+    /// Appends `text`, rotating first when it would not fit. A single write
+    /// larger than `max_bytes` still goes to a fresh file whole.
+    pub fn write(&mut self, text: &str, max_bytes: u64, keep: usize) -> io::Result<()> {
+        if self.file.is_none() {
+            self.open()?;
+        }
+        let len = text.len() as u64;
+        if self.size > 0 && self.size + len > max_bytes {
+            self.rotate(keep)?;
+        }
+        let file = self.file.as_mut().expect("log file opened above");
+        file.write_all(text.as_bytes())?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(0))?;
+        self.size = file.metadata()?.len();
+        self.file = Some(file);
+        Ok(())
+    }
+
+    fn rotate(&mut self, keep: usize) -> io::Result<()> {
+        self.file = None;
+        let _ = std::fs::remove_file(self.path(keep));
+        for index in (0..keep).rev() {
+            let from = self.path(index);
+            if from.is_file() {
+                std::fs::rename(&from, self.path(index + 1))?;
+            }
+        }
+        if keep == 0 {
+            let _ = std::fs::remove_file(self.path(0));
+        }
+        self.open()
+    }
+
+    fn path(&self, index: usize) -> PathBuf {
+        match index {
+            0 => self.dir.join(CURRENT_FILE),
+            _ => self.dir.join(format!("{CURRENT_FILE}.{index}")),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotates_by_size_and_keeps_n_files() {
+        let dir = std::env::temp_dir().join(format!("deeting-logs-{}", uuid::Uuid::new_v4()));
+        let mut log = RotatingLog::new(dir.clone());
+        for line in ["aaaa\n", "bbbb\n", "cccc\n", "dddd\n"] {
+            log.write(line, 8, 2).unwrap();
+        }
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("current.log"), "dddd\n");
+        assert_eq!(read("current.log.1"), "cccc\n");
+        assert_eq!(read("current.log.2"), "bbbb\n");
+        assert!(!dir.join("current.log.3").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use mcp_core::log_filter::{self as text_filter, TextFilter};
+
+use crate::mcp::error::McpError;
+use crate::mcp::types::{LogFilterQuery, McpLogEntry, McpLogLevel, McpLogStream};
+
+/// A validated `LogFilterQuery`, ready to test entries against.
+pub struct LogFilter {
+    stream: Option<McpLogStream>,
+    level: Option<McpLogLevel>,
+    text: TextFilter,
+}
+
+impl LogFilter {
+    pub fn new(query: &LogFilterQuery) -> Result<Self, McpError> {
+        let text = TextFilter::new(
+            query.contains.as_deref(),
+            query.regex.as_deref(),
+            query.since.as_deref(),
+        )
+        .map_err(McpError::Validation)?;
+        Ok(Self {
+            stream: query.stream.clone(),
+            level: query.level,
+            text,
+        })
+    }
+
+    /// Whether any filter is set; an empty filter lets the store apply `limit`.
+    pub fn is_empty(&self) -> bool {
+        self.stream.is_none() && self.level.is_none() && self.text.is_empty()
+    }
+
+    pub fn matches(&self, entry: &McpLogEntry) -> bool {
+        if self.stream.as_ref().is_some_and(|stream| *stream != entry.stream) {
+            return false;
+        }
+        if self.level.is_some_and(|level| entry.level < level) {
+            return false;
+        }
+        self.text.matches(&entry.message, &entry.timestamp)
+    }
+
+    /// Keeps the matching entries, then the newest `limit` of them.
+    pub fn apply(&self, entries: Vec<McpLogEntry>, limit: Option<usize>) -> Vec<McpLogEntry> {
+        let mut matched: Vec<McpLogEntry> =
+            entries.into_iter().filter(|entry| self.matches(entry)).collect();
+        if let Some(limit) = limit {
+            let excess = matched.len().saturating_sub(limit);
+            matched.drain(..excess);
+        }
+        matched
+    }
+}
+
+/// [`text_filter::utc_timestamp`], for `since` bounds the store compares as
+/// strings.
+pub fn utc_timestamp(value: &str) -> Result<String, McpError> {
+    text_filter::utc_timestamp(value).map_err(McpError::Validation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, stream: McpLogStream, message: &str) -> McpLogEntry {
+        McpLogEntry {
+            seq: 0,
+            timestamp: timestamp.to_string(),
+            stream: stream.clone(),
+            message: message.to_string(),
+            level: crate::mcp::log_level::infer(&stream, message, None),
+            structured: None,
+        }
+    }
+
+    #[test]
+    fn filters_before_limiting() {
+        let entries = vec![
+            entry("2026-01-01T00:00:00Z", McpLogStream::Stderr, "ERROR: boot"),
+            entry("2026-01-01T00:00:01Z", McpLogStream::Stdout, "ready"),
+            entry("2026-01-01T00:00:02Z", McpLogStream::Stderr, "error: retry 2"),
+            entry("2026-01-01T00:00:03Z", McpLogStream::Stderr, "warn: slow"),
+        ];
+        let filter = LogFilter::new(&LogFilterQuery {
+            stream: Some(McpLogStream::Stderr),
+            contains: Some("error".to_string()),
+            since: Some("2026-01-01T00:00:01Z".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+        let matched = filter.apply(entries.clone(), Some(5));
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].message, "error: retry 2");
+        let problems = LogFilter::new(&LogFilterQuery {
+            level: Some(McpLogLevel::Warn),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(problems.apply(entries, None).len(), 3);
+        assert!(LogFilter::new(&LogFilterQuery::default()).unwrap().is_empty());
+        assert!(LogFilter::new(&LogFilterQuery {
+            regex: Some("[".to_string()),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,38 @@
+pub use mcp_core::log_level::{ErrorRate, DEGRADED_ERROR_COUNT, DEGRADED_WINDOW};
+use mcp_core::log_level::{self, Level};
+
+use crate::mcp::types::{McpLogLevel, McpLogStream, StructuredLog};
+
+/// [`log_level::infer`] for a captured line.
+pub fn infer(
+    stream: &McpLogStream,
+    message: &str,
+    structured: Option<&StructuredLog>,
+) -> McpLogLevel {
+    let json_level = structured.and_then(|structured| structured.level.as_deref());
+    match log_level::infer(*stream == McpLogStream::Event, message, json_level) {
+        Level::Info => McpLogLevel::Info,
+        Level::Warn => McpLogLevel::Warn,
+        Level::Error => McpLogLevel::Error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::json_log;
+
+    #[test]
+    fn reads_the_json_level_of_structured_lines() {
+        let message = r#"{"level":40,"msg":"an error is coming"}"#;
+        let structured = json_log::detect(message);
+        assert_eq!(
+            infer(&McpLogStream::Stdout, message, structured.as_ref()),
+            McpLogLevel::Warn
+        );
+        assert_eq!(
+            infer(&McpLogStream::Event, "process exited with code 1", None),
+            McpLogLevel::Error
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::warn;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::mcp::log_export::format_line;
+use crate::mcp::log_files::{RotatingLog, DEFAULT_LOG_FILE_KEEP, DEFAULT_LOG_FILE_MAX_BYTES};
+use crate::mcp::store::McpStore;
+use crate::mcp::types::McpLogEntry;
+
+pub const DEFAULT_LOG_RETENTION_DAYS: u32 = 7;
+pub const DEFAULT_LOG_RETENTION_ENTRIES: usize = 5000;
+/// Lines written per transaction at most.
+const BATCH_SIZE: usize = 256;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+enum LogWrite {
+    Append { tool_id: String, entry: McpLogEntry },
+    Clear { tool_id: String },
+    /// Starts (`Some` directory) or stops teeing a tool's lines to files.
+    File { tool_id: String, dir: Option<PathBuf> },
+    Flush(oneshot::Sender<()>),
+}
+
+/// Persists tool log lines to `mcp_logs` in the background so a chatty tool
+/// never waits on SQLite, and prunes them by the retention settings. Tools
+/// with `log_to_file` also get their lines appended to rotating files. Writes
+/// are applied in order, so a clear or flush covers every line queued before it.
+#[derive(Clone)]
+pub struct LogWriter {
+    tx: mpsc::UnboundedSender<LogWrite>,
+    retention_days: Arc<AtomicU32>,
+    retention_entries: Arc<AtomicUsize>,
+    file_max_bytes: Arc<AtomicU64>,
+    file_keep: Arc<AtomicUsize>,
+}
+
+impl LogWriter {
+    pub fn spawn(store: Arc<McpStore>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let writer = Self {
+            tx,
+            retention_days: Arc::new(AtomicU32::new(DEFAULT_LOG_RETENTION_DAYS)),
+            retention_entries: Arc::new(AtomicUsize::new(DEFAULT_LOG_RETENTION_ENTRIES)),
+            file_max_bytes: Arc::new(AtomicU64::new(DEFAULT_LOG_FILE_MAX_BYTES)),
+            file_keep: Arc::new(AtomicUsize::new(DEFAULT_LOG_FILE_KEEP)),
+        };
+        tauri::async_runtime::spawn(writer.clone().run(store, rx));
+        writer
+    }
+
+    pub fn append(&self, tool_id: &str, entry: McpLogEntry) {
+        let _ = self.tx.send(LogWrite::Append {
+            tool_id: tool_id.to_string(),
+            entry,
+        });
+    }
+
+    pub fn clear(&self, tool_id: &str) {
+        let _ = self.tx.send(LogWrite::Clear {
+            tool_id: tool_id.to_string(),
+        });
+    }
+
+    /// Tees the tool's lines to `current.log` in `dir`, or stops when `None`.
+    pub fn set_file(&self, tool_id: &str, dir: Option<PathBuf>) {
+        let _ = self.tx.send(LogWrite::File {
+            tool_id: tool_id.to_string(),
+            dir,
+        });
+    }
+
+    /// Waits until every line queued so far is stored.
+    pub async fn flush(&self) {
+        let (done, wait) = oneshot::channel();
+        if self.tx.send(LogWrite::Flush(done)).is_ok() {
+            let _ = wait.await;
+        }
+    }
+
+    /// Applies the `log_retention_*` app settings from the next prune on.
+    pub fn set_retention(&self, days: u32, entries: usize) {
+        self.retention_days.store(days, Ordering::SeqCst);
+        self.retention_entries.store(entries.max(1), Ordering::SeqCst);
+    }
+
+    /// Applies the `log_file_*` app settings from the next write on.
+    pub fn set_file_rotation(&self, max_bytes: u64, keep: usize) {
+        self.file_max_bytes.store(max_bytes.max(1), Ordering::SeqCst);
+        self.file_keep.store(keep, Ordering::SeqCst);
+    }
+
+    async fn run(self, store: Arc<McpStore>, mut rx: mpsc::UnboundedReceiver<LogWrite>) {
+        // The first tick fires at once, pruning what earlier runs left behind.
+        let mut prune = tokio::time::interval(PRUNE_INTERVAL);
+        let mut files = HashMap::new();
+        loop {
+            tokio::select! {
+                write = rx.recv() => {
+                    let Some(write) = write else { break };
+                    let mut batch = vec![write];
+                    while batch.len() < BATCH_SIZE {
+                        match rx.try_recv() {
+                            Ok(write) => batch.push(write),
+                            Err(_) => break,
+                        }
+                    }
+                    self.write_files(&mut files, &batch);
+                    apply(&store, batch).await;
+                }
+                _ = prune.tick() => self.prune(&store).await,
+            }
+        }
+    }
+
+    fn write_files(&self, files: &mut HashMap<String, RotatingLog>, batch: &[LogWrite]) {
+        let mut text: HashMap<&str, String> = HashMap::new();
+        for write in batch {
+            match write {
+                LogWrite::Append { tool_id, entry } if files.contains_key(tool_id) => {
+                    let lines = text.entry(tool_id.as_str()).or_default();
+                    lines.push_str(&format_line(entry));
+                    lines.push('\n');
+                }
+                LogWrite::File { tool_id, dir } => {
+                    // Lines batched before the switch still go to the old file.
+                    if let Some(lines) = text.remove(tool_id.as_str()) {
+                        self.write_file(files, tool_id, &lines);
+                    }
+                    match dir {
+                        Some(dir) => {
+                            files.insert(tool_id.clone(), RotatingLog::new(dir.clone()));
+                        }
+                        None => {
+                            files.remove(tool_id);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        for (tool_id, lines) in text {
+            self.write_file(files, tool_id, &lines);
+        }
+    }
+
+    fn write_file(&self, files: &mut HashMap<String, RotatingLog>, tool_id: &str, text: &str) {
+        let Some(log) = files.get_mut(tool_id) else {
+            return;
+        };
+        let max_bytes = self.file_max_bytes.load(Ordering::SeqCst);
+        let keep = self.file_keep.load(Ordering::SeqCst);
+        if let Err(err) = log.write(text, max_bytes, keep) {
+            warn!("failed to write log file in {}: {}", log.dir().display(), err);
+        }
+    }
+
+    async fn prune(&self, store: &McpStore) {
+        let days = self.retention_days.load(Ordering::SeqCst);
+        let cutoff = (days > 0)
+            .then(|| time::OffsetDateTime::now_utc() - time::Duration::days(days.into()))
+            .and_then(|cutoff| {
+                cutoff
+                    .format(&time::format_description::well_known::Rfc3339)
+                    .ok()
+            });
+        let entries = self.retention_entries.load(Ordering::SeqCst);
+        if let Err(err) = store.prune_logs(cutoff.as_deref(), entries).await {
+            warn!("failed to prune stored logs: {}", err);
+        }
+    }
+}
+
+async fn apply(store: &McpStore, batch: Vec<LogWrite>) {
+    let mut pending = Vec::new();
+    for write in batch {
+        match write {
+            LogWrite::Append { tool_id, entry } => pending.push((tool_id, entry)),
+            LogWrite::Clear { tool_id } => {
+                pending.retain(|(queued, _)| queued != &tool_id);
+                store_lines(store, &mut pending).await;
+                if let Err(err) = store.clear_logs(&tool_id).await {
+                    warn!("failed to clear stored logs of {}: {}", tool_id, err);
+                }
+            }
+            LogWrite::File { .. } => {}
+            LogWrite::Flush(done) => {
+                store_lines(store, &mut pending).await;
+                let _ = done.send(());
+            }
+        }
+    }
+    store_lines(store, &mut pending).await;
+}
+
+async fn store_lines(store: &McpStore, pending: &mut Vec<(String, McpLogEntry)>) {
+    if pending.is_empty() {
+        return;
+    }
+    if let Err(err) = store.append_logs(pending).await {
+        warn!("failed to store {} log lines: {}", pending.len(), err);
+    }
+    pending.clear();
+}
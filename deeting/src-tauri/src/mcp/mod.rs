@@ -1,16 +1,46 @@
+pub mod app_state;
+pub mod approval;
+pub mod assistant_sync;
+pub mod attention;
+pub mod cloud_auth;
+pub mod cloud_events;
 pub mod commands;
+pub mod deep_link;
+pub mod diagnostics;
 pub mod error;
+pub mod event_bus;
+pub mod import;
+pub mod json_log;
+pub mod log_dedup;
+pub mod log_export;
+pub mod log_files;
+pub mod log_filter;
+pub mod log_level;
+pub mod log_store;
+pub mod notifications;
+pub mod policy;
 pub mod process;
+pub mod scheduler;
+pub mod settings;
+pub mod startup;
 pub mod store;
+pub mod sync_events;
+pub mod telemetry;
 pub mod types;
 
 use std::sync::Arc;
 
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder, Response};
 use tokio::sync::RwLock;
 
+use crate::mcp::approval::PendingApprovals;
+use crate::mcp::cloud_auth::{CloudAccounts, CloudAuth, DEFAULT_CLOUD_ACCOUNT};
+use crate::mcp::deep_link::PendingInstalls;
+use crate::mcp::error::McpError;
 use crate::mcp::process::ProcessManager;
+use crate::mcp::scheduler::SyncScheduler;
 use crate::mcp::store::McpStore;
+use crate::mcp::types::{McpSource, McpSourceType};
 
 #[derive(Clone)]
 pub struct McpRuntimeState {
@@ -18,15 +48,80 @@ pub struct McpRuntimeState {
     pub process_manager: ProcessManager,
     pub cloud_base_url: Arc<RwLock<String>>,
     pub client: Client,
+    pub cloud_accounts: CloudAccounts,
+    pub sync_scheduler: SyncScheduler,
+    pub pending_installs: PendingInstalls,
+    pub pending_approvals: PendingApprovals,
+}
+
+/// A Cloud-type source together with the endpoint and session used to talk
+/// to it.
+pub struct CloudAccount {
+    pub source: McpSource,
+    pub base_url: String,
+    pub auth: CloudAuth,
+}
+
+impl CloudAccount {
+    /// `path` (e.g. `/api/v1/mcp/subscriptions`) on this account's server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    /// [`CloudAuth::send`] with this account's session.
+    pub async fn send<F>(&self, client: &Client, build: F) -> Result<Response, McpError>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        self.auth.send(client, &self.base_url, build).await
+    }
 }
 
 impl McpRuntimeState {
-    pub fn new(store: Arc<McpStore>, process_manager: ProcessManager, cloud_base_url: String) -> Self {
+    pub fn new(
+        store: Arc<McpStore>,
+        process_manager: ProcessManager,
+        cloud_base_url: String,
+        primary_cloud_source_id: String,
+    ) -> Self {
+        let primary_auth = CloudAuth::load(DEFAULT_CLOUD_ACCOUNT);
         Self {
             store,
             process_manager,
             cloud_base_url: Arc::new(RwLock::new(cloud_base_url)),
             client: Client::new(),
+            cloud_accounts: CloudAccounts::new(primary_cloud_source_id, primary_auth),
+            sync_scheduler: SyncScheduler::default(),
+            pending_installs: PendingInstalls::default(),
+            pending_approvals: PendingApprovals::default(),
+        }
+    }
+
+    /// Resolves the cloud account for `source_id`, or the primary account when
+    /// none is given. The primary account follows `cloud_base_url`; others use
+    /// the URL stored on their source.
+    pub async fn cloud_account(&self, source_id: Option<&str>) -> Result<CloudAccount, McpError> {
+        let source_id = source_id.unwrap_or(self.cloud_accounts.primary_source_id());
+        let source = self
+            .store
+            .get_source(source_id)
+            .await?
+            .ok_or_else(|| McpError::SourceNotFound(source_id.to_string()))?;
+        if source.source_type != McpSourceType::Cloud {
+            return Err(McpError::validation(format!(
+                "source {source_id} is not a cloud account"
+            )));
         }
+        let base_url = if source.id == self.cloud_accounts.primary_source_id() {
+            self.cloud_base_url.read().await.clone()
+        } else {
+            source.path_or_url.clone()
+        };
+        let auth = self.cloud_accounts.auth_for(&source.id).await;
+        Ok(CloudAccount {
+            source,
+            base_url,
+            auth,
+        })
     }
 }
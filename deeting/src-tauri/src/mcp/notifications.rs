@@ -0,0 +1,42 @@
+use log::warn;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::mcp::store::McpStore;
+use crate::mcp::types::NotificationSettings;
+
+pub const NOTIFICATION_SETTINGS_KEY: &str = "notifications";
+
+/// Categories the user can mute independently in the `notifications` setting.
+#[derive(Debug, Clone, Copy)]
+pub enum NotificationCategory {
+    /// A tool crashed or was given up on after a crash loop.
+    Crashes,
+    /// Sync staged an update or flagged a name conflict.
+    Updates,
+}
+
+/// Shows a desktop notification unless its category is muted. Failures are
+/// only logged; the in-app event is the source of truth.
+pub async fn notify(
+    app: &AppHandle,
+    store: &McpStore,
+    category: NotificationCategory,
+    title: &str,
+    body: &str,
+) {
+    let settings = store
+        .get_setting::<NotificationSettings>(NOTIFICATION_SETTINGS_KEY)
+        .await
+        .unwrap_or_default();
+    let enabled = match category {
+        NotificationCategory::Crashes => settings.crashes,
+        NotificationCategory::Updates => settings.updates,
+    };
+    if !enabled {
+        return;
+    }
+    if let Err(err) = app.notification().builder().title(title).body(body).show() {
+        warn!("failed to show notification: {}", err);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::mcp::error::McpError;
+use crate::mcp::store::McpStore;
+use crate::mcp::types::{McpSource, McpTool, McpTrustLevel};
+
+/// Tools synced from sources below `Official` trust run arbitrary commands on
+/// the user's machine, so they need a one-time explicit approval before the
+/// first start.
+pub fn requires_approval(trust_level: &McpTrustLevel) -> bool {
+    !matches!(trust_level, McpTrustLevel::Official)
+}
+
+/// A tool without a source (e.g. added by hand before sources existed)
+/// counts as local, so it still goes through approval like any private tool.
+pub async fn source_for_tool(store: &McpStore, tool: &McpTool) -> Result<McpSource, McpError> {
+    let Some(source_id) = tool.source_id.as_deref() else {
+        return store.ensure_local_source().await;
+    };
+    store
+        .get_source(source_id)
+        .await?
+        .ok_or_else(|| McpError::SourceNotFound(source_id.to_string()))
+}
+
+/// The tool's source when the tool still needs approval before it may start.
+pub async fn pending_approval(
+    store: &McpStore,
+    tool: &McpTool,
+) -> Result<Option<McpSource>, McpError> {
+    if tool.approved_at.is_some() {
+        return Ok(None);
+    }
+    let source = source_for_tool(store, tool).await?;
+    Ok(requires_approval(&source.trust_level).then_some(source))
+}
+
+pub async fn ensure_start_allowed(store: &McpStore, tool: &McpTool) -> Result<(), McpError> {
+    if let Some(source) = pending_approval(store, tool).await? {
+        return Err(McpError::ApprovalRequired(format!(
+            "tool {} from {} source requires approval",
+            tool.name,
+            source.trust_level.as_str()
+        )));
+    }
+    Ok(())
+}
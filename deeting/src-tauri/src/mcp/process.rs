@@ -1,19 +1,43 @@
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use log::warn;
+use mcp_core::ansi::AnsiMode;
+use mcp_core::health::{ProbeTracker, ProbeTransition};
+use mcp_core::interpolate::{builtin_vars, interpolate_command, interpolate_shell, Interpolated};
+use mcp_core::log_redact::Redactor;
 use tauri::{AppHandle, Emitter};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Child;
 use tokio::sync::{Mutex, RwLock};
 
+use crate::mcp::attention::AttentionAggregate;
 use crate::mcp::error::McpError;
+use crate::mcp::event_bus::{DomainEvent, EventBus};
+use crate::mcp::log_dedup::{self, LineDedup, Verdict};
+use crate::mcp::log_level::{self, ErrorRate};
+use crate::mcp::log_store::LogWriter;
+use crate::mcp::policy;
 use crate::mcp::store::McpStore;
-use crate::mcp::types::{McpLogEntry, McpLogStream, McpTool, McpToolStatus};
+use crate::mcp::types::{
+    HealthCheck, LogSearchMatch, McpConflictStatus, McpLogEntry, McpLogLevel, McpLogStream,
+    McpSourceType, McpTool, McpToolLogEntry, McpToolStatus, McpToolStatusEvent, ToolHooks,
+};
 
-const DEFAULT_LOG_BUFFER_SIZE: usize = 1000;
+pub const DEFAULT_LOG_BUFFER_SIZE: usize = 1000;
+pub const DEFAULT_LOG_MEMORY_MB: usize = 64;
+/// Carries every tool's lines, tagged with their tool, while
+/// `subscribe_all_mcp_logs` is in effect.
+pub const ALL_LOGS_EVENT: &str = "mcp-log-all://";
 const CRASH_WINDOW: Duration = Duration::from_secs(5);
+/// Longest slice of an error line quoted in a crash message.
+const LAST_ERROR_CHARS: usize = 200;
+/// How long one pre-start or post-stop hook may run before it is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(600);
 const BACKOFF_DELAYS: [Duration; 3] = [
     Duration::from_secs(0),
     Duration::from_secs(10),
@@ -34,11 +58,29 @@ pub struct ProcessManager {
     logs: Arc<RwLock<HashMap<String, LogBuffer>>>,
     backoff: Arc<RwLock<HashMap<String, CrashBackoff>>>,
     stop_requests: Arc<RwLock<HashSet<String>>>,
-    log_buffer_size: usize,
+    crash_counts: Arc<RwLock<HashMap<String, u32>>>,
+    log_buffer_size: Arc<AtomicUsize>,
+    /// Per-tool `log_buffer_size` overrides.
+    tool_log_buffer_sizes: Arc<RwLock<HashMap<String, usize>>>,
+    /// Bytes all log buffers may hold together.
+    log_memory_budget: Arc<AtomicUsize>,
+    attention: AttentionAggregate,
+    log_writer: LogWriter,
+    log_seq: Arc<AtomicU64>,
+    log_ansi: Arc<std::sync::RwLock<AnsiMode>>,
+    error_rates: Arc<std::sync::Mutex<HashMap<String, ErrorRate>>>,
+    log_dedup: Arc<std::sync::Mutex<LineDedup>>,
+    redactors: Arc<std::sync::RwLock<HashMap<String, Redactor>>>,
+    env_strict: Arc<AtomicBool>,
+    /// Open `subscribe_all_mcp_logs` subscriptions; lines go to
+    /// `ALL_LOGS_EVENT` only while there is one.
+    all_log_subscribers: Arc<AtomicUsize>,
+    events: EventBus,
 }
 
 impl ProcessManager {
     pub fn new(store: Arc<McpStore>, app_handle: AppHandle) -> Self {
+        let log_writer = LogWriter::spawn(store.clone());
         Self {
             store,
             app_handle,
@@ -46,17 +88,130 @@ impl ProcessManager {
             logs: Arc::new(RwLock::new(HashMap::new())),
             backoff: Arc::new(RwLock::new(HashMap::new())),
             stop_requests: Arc::new(RwLock::new(HashSet::new())),
-            log_buffer_size: DEFAULT_LOG_BUFFER_SIZE,
+            crash_counts: Arc::new(RwLock::new(HashMap::new())),
+            log_buffer_size: Arc::new(AtomicUsize::new(DEFAULT_LOG_BUFFER_SIZE)),
+            tool_log_buffer_sizes: Arc::new(RwLock::new(HashMap::new())),
+            log_memory_budget: Arc::new(AtomicUsize::new(DEFAULT_LOG_MEMORY_MB << 20)),
+            attention: AttentionAggregate::default(),
+            log_writer,
+            log_seq: Arc::new(AtomicU64::new(0)),
+            log_ansi: Arc::new(std::sync::RwLock::new(AnsiMode::default())),
+            error_rates: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            log_dedup: Arc::new(std::sync::Mutex::new(LineDedup::new(
+                log_dedup::DEFAULT_LOG_DEDUP_WINDOW,
+            ))),
+            redactors: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            env_strict: Arc::new(AtomicBool::new(false)),
+            all_log_subscribers: Arc::new(AtomicUsize::new(0)),
+            events: EventBus::default(),
         }
     }
 
+    /// Applies the `log_buffer_size` app setting; existing buffers keep their
+    /// newest lines when shrunk. Tools with their own size keep it.
+    pub async fn set_log_buffer_size(&self, size: usize) {
+        let size = size.max(1);
+        self.log_buffer_size.store(size, Ordering::SeqCst);
+        let overrides = self.tool_log_buffer_sizes.read().await;
+        for (tool_id, buffer) in self.logs.write().await.iter_mut() {
+            if !overrides.contains_key(tool_id) {
+                buffer.resize(size);
+            }
+        }
+    }
+
+    /// Applies a tool's `log_buffer_size`, or the app setting again when it
+    /// has none, resizing its current buffer.
+    pub async fn set_tool_log_buffer_size(&self, tool_id: &str, size: Option<usize>) {
+        {
+            let mut overrides = self.tool_log_buffer_sizes.write().await;
+            match size {
+                Some(size) => overrides.insert(tool_id.to_string(), size.max(1)),
+                None => overrides.remove(tool_id),
+            };
+        }
+        let capacity = self.log_capacity(tool_id).await;
+        if let Some(buffer) = self.logs.write().await.get_mut(tool_id) {
+            buffer.resize(capacity);
+        }
+    }
+
+    /// Applies the `log_memory_mb` app setting. When all buffers together
+    /// are over it, the oldest lines of the largest buffer go first, so one
+    /// chatty tool cannot push out everyone else's history. Storage freed by
+    /// the trim is returned to the allocator.
+    pub async fn set_log_memory_budget(&self, bytes: usize) {
+        let bytes = bytes.max(1);
+        self.log_memory_budget.store(bytes, Ordering::SeqCst);
+        let mut logs = self.logs.write().await;
+        enforce_memory_budget(&mut logs, bytes);
+        for buffer in logs.values_mut() {
+            buffer.entries.shrink_to_fit();
+        }
+    }
+
+    /// Continues log numbering after the newest stored line; called once at
+    /// startup, before any tool runs.
+    pub async fn resume_log_seq(&self) -> Result<(), McpError> {
+        let max = self.store.max_log_seq().await?;
+        self.log_seq.fetch_max(max, Ordering::SeqCst);
+        Ok(())
+    }
+
+    pub fn log_buffer_size(&self) -> usize {
+        self.log_buffer_size.load(Ordering::SeqCst)
+    }
+
+    /// Applies the `log_retention_*` app settings to stored logs.
+    pub fn set_log_retention(&self, days: u32, entries: usize) {
+        self.log_writer.set_retention(days, entries);
+    }
+
+    /// Applies the `log_ansi` app setting to lines captured from now on.
+    pub fn set_log_ansi(&self, mode: AnsiMode) {
+        *self.log_ansi.write().expect("ANSI mode lock poisoned") = mode;
+    }
+
+    /// Applies the `log_dedup_window_ms` app setting.
+    pub fn set_log_dedup_window(&self, window: Duration) {
+        self.log_dedup
+            .lock()
+            .expect("log dedup lock poisoned")
+            .set_window(window);
+    }
+
+    /// Applies the `env_strict` app setting: unresolved `${VAR}` references
+    /// fail the start instead of being passed through with a warning.
+    pub fn set_env_strict(&self, strict: bool) {
+        self.env_strict.store(strict, Ordering::SeqCst);
+    }
+
+    /// Applies the `log_file_*` app settings to per-tool log files.
+    pub fn set_log_file_rotation(&self, max_bytes: u64, keep: usize) {
+        self.log_writer.set_file_rotation(max_bytes, keep);
+    }
+
+    /// Starts or stops teeing the tool's output to files, following its
+    /// `log_to_file` flag.
+    pub fn apply_log_to_file(&self, tool: &McpTool) {
+        let dir = tool
+            .log_to_file
+            .then(|| crate::mcp::log_files::tool_log_dir(tool));
+        self.log_writer.set_file(&tool.id, dir);
+    }
+
+    async fn log_capacity(&self, tool_id: &str) -> usize {
+        self.tool_log_buffer_sizes
+            .read()
+            .await
+            .get(tool_id)
+            .copied()
+            .unwrap_or_else(|| self.log_buffer_size())
+    }
+
     pub async fn start_tool(&self, tool: McpTool, reset_backoff: bool) -> Result<(), McpError> {
-        let mut processes = self.processes.write().await;
-        if processes.contains_key(&tool.id) {
-            return Err(McpError::Process(format!(
-                "tool {} already running",
-                tool.id
-            )));
+        if self.processes.read().await.contains_key(&tool.id) {
+            return Err(McpError::ToolAlreadyRunning(tool.id.clone()));
         }
 
         let command = tool
@@ -64,20 +219,110 @@ impl ProcessManager {
             .clone()
             .ok_or_else(|| McpError::Validation("missing command".to_string()))?;
 
-        let args = tool.args.clone().unwrap_or_default();
-        let mut cmd = tokio::process::Command::new(command);
-        cmd.args(args);
-        if let Some(env) = &tool.env {
-            cmd.envs(env);
+        let builtins = builtin_vars(
+            dirs::home_dir().as_deref(),
+            &crate::paths::config_dir(),
+            &tool.id,
+            self.workspace_dir(&tool).await.as_deref(),
+        );
+        let cwd = config_cwd(&tool.config_json);
+        let mut resolved = interpolate_command(
+            &command,
+            tool.args.as_deref().unwrap_or_default(),
+            tool.env.as_ref(),
+            cwd.as_deref(),
+            &builtins,
+            |name| std::env::var(name).ok(),
+        );
+        let hooks = config_hooks(&tool.config_json);
+        let pre_start: Vec<String> = hooks
+            .pre_start
+            .iter()
+            .map(|hook| interpolate_hook(hook, &builtins, &mut resolved))
+            .collect();
+        let post_stop: Vec<String> = hooks
+            .post_stop
+            .iter()
+            .map(|hook| interpolate_hook(hook, &builtins, &mut resolved))
+            .collect();
+        let healthcheck = config_healthcheck(&tool.config_json).map(|mut check| {
+            if let Some(command) = &mut check.command {
+                *command = interpolate_hook(command, &builtins, &mut resolved);
+            }
+            for target in [&mut check.tcp, &mut check.http].into_iter().flatten() {
+                *target = interpolate_target(target, &builtins, &mut resolved);
+            }
+            check
+        });
+        if !resolved.unresolved.is_empty() {
+            if self.env_strict.load(Ordering::SeqCst) {
+                return Err(McpError::EnvMissing(resolved.unresolved));
+            }
+            warn!(
+                "{}: unresolved variables passed through verbatim: {}",
+                tool.id,
+                resolved.unresolved.join(", ")
+            );
+        }
+        let tool_dir = resolved
+            .builtins
+            .iter()
+            .find(|(name, _)| *name == "TOOL_DIR");
+        if let Some((_, tool_dir)) = tool_dir {
+            tokio::fs::create_dir_all(tool_dir)
+                .await
+                .map_err(|err| McpError::Process(format!("cannot create {tool_dir}: {err}")))?;
+        }
+        let hook_env = Arc::new(HookEnv {
+            env: resolved.env.clone(),
+            cwd: resolved.cwd.as_deref().map(crate::paths::expand_path),
+        });
+
+        self.apply_log_to_file(&tool);
+        self.set_tool_log_buffer_size(&tool.id, tool.log_buffer_size).await;
+        self.redactors
+            .write()
+            .expect("redactor lock poisoned")
+            .insert(tool.id.clone(), Redactor::for_env(Some(&resolved.env)));
+        self.ensure_log_buffer(&tool.id).await;
+        if !resolved.builtins.is_empty() {
+            let vars: Vec<String> = resolved
+                .builtins
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect();
+            let message = format!("template variables: {}", vars.join(", "));
+            self.emit_log(&tool.id, McpLogStream::Event, message).await;
+        }
+        if !pre_start.is_empty() {
+            self.set_status(&tool.id, McpToolStatus::Starting, None, None).await?;
+            for hook in &pre_start {
+                if let Err(message) = self.run_hook(&tool.id, "pre-start", hook, &hook_env).await {
+                    self.set_status(&tool.id, McpToolStatus::Error, None, Some(message.clone()))
+                        .await?;
+                    return Err(McpError::Process(message));
+                }
+            }
+        }
+
+        // Taken only now so a slow pre-start hook does not hold up other
+        // tools; hence the second check.
+        let mut processes = self.processes.write().await;
+        if processes.contains_key(&tool.id) {
+            return Err(McpError::ToolAlreadyRunning(tool.id.clone()));
+        }
+
+        let mut cmd = tokio::process::Command::new(&resolved.command);
+        cmd.args(&resolved.args);
+        cmd.envs(&resolved.env);
+        if let Some(cwd) = &hook_env.cwd {
+            cmd.current_dir(cwd);
         }
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
 
-        self.store
-            .set_tool_status(&tool.id, McpToolStatus::Starting, None, None)
-            .await?;
+        self.set_status(&tool.id, McpToolStatus::Starting, None, None).await?;
         self.record_start(&tool.id, reset_backoff).await;
-
         let mut child = cmd
             .spawn()
             .map_err(|err| McpError::Process(err.to_string()))?;
@@ -89,12 +334,12 @@ impl ProcessManager {
             tool.id.clone(),
             ProcessHandle {
                 child: child.clone(),
+                post_stop: Arc::new(post_stop),
+                hook_env: hook_env.clone(),
             },
         );
         drop(processes);
 
-        self.ensure_log_buffer(&tool.id).await;
-
         if let Some(stdout) = stdout {
             let tool_id = tool.id.clone();
             let manager = self.clone();
@@ -123,20 +368,220 @@ impl ProcessManager {
             });
         }
 
-        self.store
-            .set_tool_status(&tool.id, McpToolStatus::Healthy, None, None)
-            .await?;
+        self.set_status(&tool.id, McpToolStatus::Healthy, None, None).await?;
         if reset_backoff {
             let _ = self.store.set_tool_new_flag(&tool.id, false).await;
         }
         self.emit_log(&tool.id, McpLogStream::Event, "process started".to_string())
             .await;
 
+        if let Some(check) = healthcheck {
+            self.spawn_health_monitor(tool.id.clone(), child.clone(), check, hook_env);
+        }
         self.spawn_monitor(tool.id.clone(), child).await;
 
         Ok(())
     }
 
+    /// Runs one hook through the shell, its output going to the tool's log.
+    /// Fails with a message for the log and the tool's error when the hook
+    /// cannot start, exits non-zero or outlasts [`HOOK_TIMEOUT`].
+    async fn run_hook(
+        &self,
+        tool_id: &str,
+        phase: &str,
+        hook: &str,
+        hook_env: &HookEnv,
+    ) -> Result<(), String> {
+        self.emit_log(tool_id, McpLogStream::Event, format!("{phase} hook: {hook}"))
+            .await;
+        let mut cmd = shell_command(hook);
+        cmd.envs(&hook_env.env);
+        if let Some(cwd) = &hook_env.cwd {
+            cmd.current_dir(cwd);
+        }
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+        cmd.kill_on_drop(true);
+
+        let result = match cmd.spawn() {
+            Ok(mut child) => {
+                let stdout = child.stdout.take();
+                let stderr = child.stderr.take();
+                let run = async {
+                    tokio::join!(
+                        self.forward_output(tool_id, McpLogStream::Stdout, stdout),
+                        self.forward_output(tool_id, McpLogStream::Stderr, stderr),
+                    );
+                    child.wait().await
+                };
+                let outcome = tokio::time::timeout(HOOK_TIMEOUT, run).await;
+                match outcome {
+                    Ok(Ok(status)) if status.success() => Ok(()),
+                    Ok(Ok(status)) => Err(format!(
+                        "{phase} hook failed with code {}",
+                        status.code().unwrap_or(-1)
+                    )),
+                    Ok(Err(err)) => Err(format!("{phase} hook failed: {err}")),
+                    Err(_) => {
+                        let _ = child.kill().await;
+                        Err(format!(
+                            "{phase} hook timed out after {}s",
+                            HOOK_TIMEOUT.as_secs()
+                        ))
+                    }
+                }
+            }
+            Err(err) => Err(format!("{phase} hook failed to start: {err}")),
+        };
+        if let Err(message) = &result {
+            warn!("{}: {}", tool_id, message);
+            self.flush_repeats(tool_id).await;
+            self.emit_log(tool_id, McpLogStream::Event, message.clone())
+                .await;
+        }
+        result
+    }
+
+    /// Runs an exited tool's post-stop hooks; a failing one does not keep
+    /// the rest from running.
+    async fn run_post_stop(&self, tool_id: &str, handle: &ProcessHandle) {
+        for hook in handle.post_stop.iter() {
+            let _ = self
+                .run_hook(tool_id, "post-stop", hook, &handle.hook_env)
+                .await;
+        }
+    }
+
+    /// Probes the tool's health check every interval while `child` runs. A
+    /// passing probe keeps the tool healthy with the probe time as its ping
+    /// and a failing one marks it degraded; once failures reach the
+    /// threshold the process is killed, and the exit monitor handles it as a
+    /// crash.
+    fn spawn_health_monitor(
+        &self,
+        tool_id: String,
+        child: Arc<Mutex<Child>>,
+        check: HealthCheck,
+        hook_env: Arc<HookEnv>,
+    ) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let mut tracker = ProbeTracker::new(check.failure_threshold());
+            loop {
+                tokio::time::sleep(check.interval()).await;
+                if !manager.is_current(&tool_id, &child).await {
+                    break;
+                }
+                let result = probe(&check, &hook_env, &client).await;
+                if !manager.is_current(&tool_id, &child).await {
+                    break;
+                }
+                let transition = tracker.record(result.is_ok());
+                let reason = match result {
+                    Ok(elapsed) => {
+                        manager.record_probe_pass(&tool_id, elapsed).await;
+                        continue;
+                    }
+                    Err(reason) => reason,
+                };
+                let message = format!(
+                    "health check failed ({}/{}): {reason}",
+                    tracker.failures(),
+                    check.failure_threshold().max(1)
+                );
+                manager
+                    .emit_log(&tool_id, McpLogStream::Event, message.clone())
+                    .await;
+                match transition {
+                    Some(ProbeTransition::Degraded) => {
+                        manager.record_probe_failure(&tool_id, message).await;
+                    }
+                    Some(ProbeTransition::Crashed) => {
+                        warn!("{}: {}; stopping the process", tool_id, message);
+                        if let Err(err) = child.lock().await.kill().await {
+                            warn!("{}: failed to kill unhealthy tool: {}", tool_id, err);
+                        }
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        });
+    }
+
+    /// Whether `child` is still the tracked process of the tool.
+    async fn is_current(&self, tool_id: &str, child: &Arc<Mutex<Child>>) -> bool {
+        self.processes
+            .read()
+            .await
+            .get(tool_id)
+            .is_some_and(|handle| Arc::ptr_eq(&handle.child, child))
+    }
+
+    /// Marks a running tool healthy after a passing probe, with the probe
+    /// time as its ping.
+    async fn record_probe_pass(&self, tool_id: &str, elapsed: Duration) {
+        let Ok(Some(tool)) = self.store.get_tool(tool_id).await else {
+            return;
+        };
+        if !matches!(tool.status, McpToolStatus::Healthy | McpToolStatus::Degraded) {
+            return;
+        }
+        let ping_ms = i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX);
+        let _ = self
+            .set_status(tool_id, McpToolStatus::Healthy, Some(ping_ms), None)
+            .await;
+    }
+
+    async fn record_probe_failure(&self, tool_id: &str, message: String) {
+        let Ok(Some(tool)) = self.store.get_tool(tool_id).await else {
+            return;
+        };
+        if tool.status != McpToolStatus::Healthy {
+            return;
+        }
+        let _ = self
+            .set_status(tool_id, McpToolStatus::Degraded, None, Some(message))
+            .await;
+    }
+
+    async fn forward_output<R: AsyncRead + Unpin>(
+        &self,
+        tool_id: &str,
+        stream: McpLogStream,
+        reader: Option<R>,
+    ) {
+        let Some(reader) = reader else {
+            return;
+        };
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            self.emit_log(tool_id, stream.clone(), line).await;
+        }
+    }
+
+    /// `WORKSPACE` for a tool: the directory of the local config file it was
+    /// imported from.
+    async fn workspace_dir(&self, tool: &McpTool) -> Option<PathBuf> {
+        let source_id = tool.source_id.as_deref()?;
+        let source = self.store.get_source(source_id).await.ok()??;
+        if source.source_type != McpSourceType::Local {
+            return None;
+        }
+        crate::paths::expand_path(&source.path_or_url)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+    }
+
+    /// Tools with a process right now.
+    pub async fn running_count(&self) -> usize {
+        self.processes.read().await.len()
+    }
+
     pub async fn stop_tool(&self, tool_id: &str) -> Result<(), McpError> {
         self.request_stop(tool_id).await;
         let handle = {
@@ -145,9 +590,7 @@ impl ProcessManager {
         };
 
         let Some(handle) = handle else {
-            self.store
-                .set_tool_status(tool_id, McpToolStatus::Stopped, None, None)
-                .await?;
+            self.set_status(tool_id, McpToolStatus::Stopped, None, None).await?;
             self.clear_backoff(tool_id).await;
             return Ok(());
         };
@@ -157,9 +600,8 @@ impl ProcessManager {
             return Err(McpError::Process(format!("failed to stop tool: {err}")));
         }
 
-        self.store
-            .set_tool_status(tool_id, McpToolStatus::Stopped, None, None)
-            .await?;
+        self.set_status(tool_id, McpToolStatus::Stopped, None, None).await?;
+        self.flush_repeats(tool_id).await;
         self.emit_log(tool_id, McpLogStream::Event, "process stopped".to_string())
             .await;
         self.clear_backoff(tool_id).await;
@@ -167,6 +609,63 @@ impl ProcessManager {
         Ok(())
     }
 
+    /// Updates the attention aggregate and publishes the tool's current
+    /// status on the event bus. Called after every status write here and by
+    /// sync when `conflict_status` changes.
+    pub async fn publish_status(&self, tool_id: &str) {
+        let status = match self.store.get_tool(tool_id).await {
+            Ok(Some(tool)) => {
+                self.attention.update(tool_id, Some(&tool));
+                Some(McpToolStatusEvent::from(&tool))
+            }
+            Ok(None) => {
+                self.attention.update(tool_id, None);
+                None
+            }
+            Err(_) => None,
+        };
+        self.events.publish(DomainEvent::ToolStatus {
+            tool_id: tool_id.to_string(),
+            status,
+        });
+    }
+
+    /// Bus for tool and sync events; see [`spawn_event_emitter`](crate::mcp::event_bus::spawn_event_emitter).
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Rebuilds the attention aggregate from the store, for startup and for
+    /// changes that remove tools without publishing each one.
+    pub async fn reload_attention(&self) -> Result<(), McpError> {
+        let tools = self.store.list_tools().await?;
+        self.attention.reset(&tools);
+        crate::tray::refresh(&self.app_handle);
+        Ok(())
+    }
+
+    /// Number of tools that are crashed or have a pending update or conflict.
+    pub fn attention_count(&self) -> usize {
+        self.attention.count()
+    }
+
+    /// Publishes a tool whose pending update or name conflict was just staged
+    /// by sync, flagging the conflict for an `Updates` desktop notification.
+    pub async fn publish_pending_update(&self, tool_id: &str) {
+        self.publish_status(tool_id).await;
+        let Ok(Some(tool)) = self.store.get_tool(tool_id).await else {
+            return;
+        };
+        if tool.conflict_status == McpConflictStatus::None {
+            return;
+        }
+        self.events.publish(DomainEvent::ConflictDetected {
+            tool_id: tool.id,
+            tool_name: tool.name,
+            conflict_status: tool.conflict_status,
+        });
+    }
+
     pub async fn logs(&self, tool_id: &str) -> Vec<McpLogEntry> {
         let logs = self.logs.read().await;
         logs.get(tool_id)
@@ -174,9 +673,69 @@ impl ProcessManager {
             .unwrap_or_default()
     }
 
+    /// Stored log lines, including those of earlier app runs: the newest
+    /// `limit` (all retained lines when `None`) older than the `before` seq,
+    /// oldest first.
+    pub async fn log_history(
+        &self,
+        tool_id: &str,
+        limit: Option<usize>,
+        before: Option<u64>,
+    ) -> Result<Vec<McpLogEntry>, McpError> {
+        self.log_writer.flush().await;
+        self.store.list_logs(tool_id, limit, before).await
+    }
+
+    /// Stored lines of every tool matching a `search_mcp_logs` query, newest
+    /// first, each with up to `context` lines of the same tool around it.
+    pub async fn search_logs(
+        &self,
+        contains: &str,
+        level: McpLogLevel,
+        since: Option<&str>,
+        limit: usize,
+        context: usize,
+    ) -> Result<Vec<LogSearchMatch>, McpError> {
+        self.log_writer.flush().await;
+        let found = self.store.search_logs(contains, level, since, limit).await?;
+        let mut matches = Vec::with_capacity(found.len());
+        for (tool_id, entry) in found {
+            let (before, after) = if context == 0 {
+                (Vec::new(), Vec::new())
+            } else {
+                (
+                    self.store.list_logs(&tool_id, Some(context), Some(entry.seq)).await?,
+                    self.store.list_logs_after(&tool_id, entry.seq, context).await?,
+                )
+            };
+            matches.push(LogSearchMatch {
+                tool_id,
+                entry,
+                before,
+                after,
+            });
+        }
+        Ok(matches)
+    }
+
     pub async fn clear_logs(&self, tool_id: &str) {
+        let capacity = self.log_capacity(tool_id).await;
         let mut logs = self.logs.write().await;
-        logs.insert(tool_id.to_string(), LogBuffer::new(self.log_buffer_size));
+        logs.insert(tool_id.to_string(), LogBuffer::new(capacity));
+        self.log_writer.clear(tool_id);
+    }
+
+    /// Drains the per-tool count of unexpected exits recorded since the last call.
+    pub async fn take_crash_counts(&self) -> HashMap<String, u32> {
+        std::mem::take(&mut *self.crash_counts.write().await)
+    }
+
+    /// Puts counts taken with `take_crash_counts` back, e.g. after a failed report.
+    pub async fn restore_crash_counts(&self, counts: HashMap<String, u32>) {
+        let mut crash_counts = self.crash_counts.write().await;
+        for (tool_id, count) in counts {
+            *crash_counts.entry(tool_id).or_insert(0) += count;
+        }
     }
 
     async fn record_start(&self, tool_id: &str, reset_backoff: bool) {
@@ -189,6 +748,10 @@ impl ProcessManager {
             entry.attempts = 0;
         }
         entry.last_start = Instant::now();
+        self.error_rates
+            .lock()
+            .expect("error rate lock poisoned")
+            .remove(tool_id);
         let mut stop_requests = self.stop_requests.write().await;
         stop_requests.remove(tool_id);
     }
@@ -210,23 +773,27 @@ impl ProcessManager {
         stop_requests.remove(tool_id);
     }
 
+    /// Crash restarts pass the same approval policy as a manual start: a tool
+    /// whose commands changed since it was approved is left pending instead.
     async fn restart_tool(&self, tool_id: &str) -> Result<(), McpError> {
         let tool = self
             .store
             .get_tool(tool_id)
             .await?
-            .ok_or_else(|| McpError::NotFound(format!("tool {tool_id} not found")))?;
-        self.start_tool(tool, false).await
+            .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+        match policy::ensure_start_allowed(&self.store, &tool).await {
+            Ok(()) => self.start_tool(tool, false).await,
+            Err(err @ McpError::ApprovalRequired(_)) => {
+                let message = format!("not restarted: {err}");
+                self.emit_log(tool_id, McpLogStream::Event, message.clone()).await;
+                self.clear_backoff(tool_id).await;
+                self.set_status(tool_id, McpToolStatus::Pending, None, Some(message)).await
+            }
+            Err(err) => Err(err),
+        }
     }
 
     async fn notify_crash(&self, tool_id: &str, message: String) {
-        #[derive(serde::Serialize)]
-        struct SupervisorPayload {
-            tool_id: String,
-            tool_name: String,
-            message: String,
-        }
-
         let tool_name = self
             .store
             .get_tool(tool_id)
@@ -235,38 +802,208 @@ impl ProcessManager {
             .flatten()
             .map(|tool| tool.name)
             .unwrap_or_else(|| tool_id.to_string());
-        let payload = SupervisorPayload {
+        self.events.publish(DomainEvent::ToolCrashed {
             tool_id: tool_id.to_string(),
             tool_name,
             message,
-        };
-        let _ = self.app_handle.emit_all("mcp-supervisor", payload);
+        });
+    }
+
+    async fn set_status(
+        &self,
+        tool_id: &str,
+        status: McpToolStatus,
+        ping_ms: Option<i64>,
+        error: Option<String>,
+    ) -> Result<(), McpError> {
+        self.store
+            .set_tool_status(tool_id, status, ping_ms, error)
+            .await?;
+        self.publish_status(tool_id).await;
+        Ok(())
     }
 
     async fn ensure_log_buffer(&self, tool_id: &str) {
+        let capacity = self.log_capacity(tool_id).await;
         let mut logs = self.logs.write().await;
         logs.entry(tool_id.to_string())
-            .or_insert_with(|| LogBuffer::new(self.log_buffer_size));
+            .or_insert_with(|| LogBuffer::new(capacity));
     }
 
     async fn emit_log(&self, tool_id: &str, stream: McpLogStream, message: String) {
+        if stream != McpLogStream::Event {
+            let verdict = self
+                .log_dedup
+                .lock()
+                .expect("log dedup lock poisoned")
+                .check(tool_id, &stream, &message, Instant::now());
+            match verdict {
+                Verdict::Suppress => return,
+                Verdict::Emit {
+                    repeated: Some(count),
+                } => {
+                    let note = log_dedup::repeated_message(count);
+                    self.push_log(tool_id, stream.clone(), note).await;
+                }
+                Verdict::Emit { repeated: None } => {}
+            }
+        }
+        self.push_log(tool_id, stream, message).await;
+    }
+
+    /// Masks secrets before a line is buffered, stored or emitted; see
+    /// [`Redactor`]. Tools never started here only get the pattern checks.
+    fn redact(&self, tool_id: &str, message: String) -> String {
+        let redactors = self.redactors.read().expect("redactor lock poisoned");
+        match redactors.get(tool_id) {
+            Some(redactor) => redactor.apply(message),
+            None => Redactor::default().apply(message),
+        }
+    }
+
+    /// Reports repeats of the tool's last lines that were coalesced and not
+    /// reported yet; called when its process goes away.
+    async fn flush_repeats(&self, tool_id: &str) {
+        let repeats = self
+            .log_dedup
+            .lock()
+            .expect("log dedup lock poisoned")
+            .take(tool_id);
+        for (stream, count) in repeats {
+            self.push_log(tool_id, stream, log_dedup::repeated_message(count))
+                .await;
+        }
+    }
+
+    async fn push_log(&self, tool_id: &str, stream: McpLogStream, message: String) {
+        let (message, structured) = match stream {
+            McpLogStream::Event => (self.redact(tool_id, message), None),
+            _ => {
+                let message = self.log_ansi.read().expect("ANSI mode lock poisoned").apply(message);
+                let message = self.redact(tool_id, message);
+                let structured = crate::mcp::json_log::detect(&message);
+                (message, structured)
+            }
+        };
+        let level = log_level::infer(&stream, &message, structured.as_ref());
+        let degraded = match stream {
+            McpLogStream::Event => None,
+            _ => self
+                .error_rates
+                .lock()
+                .expect("error rate lock poisoned")
+                .entry(tool_id.to_string())
+                .or_default()
+                .record(level == McpLogLevel::Error, Instant::now()),
+        };
         let entry = McpLogEntry {
+            seq: self.log_seq.fetch_add(1, Ordering::SeqCst) + 1,
             timestamp: now_rfc3339(),
             stream,
             message,
+            level,
+            structured,
         };
 
         {
+            let capacity = self.log_capacity(tool_id).await;
             let mut logs = self.logs.write().await;
             logs.entry(tool_id.to_string())
-                .or_insert_with(|| LogBuffer::new(self.log_buffer_size))
+                .or_insert_with(|| LogBuffer::new(capacity))
                 .push(entry.clone());
+            enforce_memory_budget(&mut logs, self.log_memory_budget.load(Ordering::SeqCst));
         }
+        self.log_writer.append(tool_id, entry.clone());
+        self.broadcast_log(tool_id, entry);
 
+        if let Some(degraded) = degraded {
+            self.set_degraded(tool_id, degraded).await;
+        }
+    }
+
+    /// Emits a line on the tool's `mcp-log://{tool_id}` stream and, while
+    /// someone is subscribed, on `ALL_LOGS_EVENT`. Notes that are not kept
+    /// in the buffer (e.g. a start refused for missing env) go through here
+    /// directly.
+    pub fn broadcast_log(&self, tool_id: &str, entry: McpLogEntry) {
+        if self.all_log_subscribers.load(Ordering::SeqCst) > 0 {
+            let _ = self.app_handle.emit_all(
+                ALL_LOGS_EVENT,
+                McpToolLogEntry {
+                    tool_id: tool_id.to_string(),
+                    entry: entry.clone(),
+                },
+            );
+        }
         let event_name = format!("mcp-log://{}", tool_id);
         let _ = self.app_handle.emit_all(&event_name, entry);
     }
 
+    pub fn subscribe_all_logs(&self) {
+        self.all_log_subscribers.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub fn unsubscribe_all_logs(&self) {
+        let _ = self
+            .all_log_subscribers
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                count.checked_sub(1)
+            });
+    }
+
+    /// Moves a running tool between `Healthy` and `Degraded` as its error
+    /// rate crosses the threshold; other statuses are left alone.
+    async fn set_degraded(&self, tool_id: &str, degraded: bool) {
+        let Ok(Some(tool)) = self.store.get_tool(tool_id).await else {
+            return;
+        };
+        let (status, error) = match (degraded, tool.status) {
+            (true, McpToolStatus::Healthy) => (
+                McpToolStatus::Degraded,
+                Some(format!(
+                    "{} or more errors logged within {}s",
+                    log_level::DEGRADED_ERROR_COUNT,
+                    log_level::DEGRADED_WINDOW.as_secs()
+                )),
+            ),
+            (false, McpToolStatus::Degraded) => (McpToolStatus::Healthy, None),
+            _ => return,
+        };
+        let _ = self.set_status(tool_id, status, None, error).await;
+    }
+
+    /// Appends the newest error line of the tool's current run to a crash
+    /// message, so notifications say why it went down.
+    async fn with_last_error(&self, tool_id: &str, message: String) -> String {
+        let logs = self.logs.read().await;
+        let last_error = logs.get(tool_id).and_then(|buffer| {
+            buffer
+                .entries
+                .iter()
+                .rev()
+                .take_while(|entry| {
+                    !(entry.stream == McpLogStream::Event && entry.message == "process started")
+                })
+                .find(|entry| {
+                    entry.stream != McpLogStream::Event && entry.level == McpLogLevel::Error
+                })
+                .map(|entry| {
+                    entry
+                        .structured
+                        .as_ref()
+                        .and_then(|structured| structured.msg.clone())
+                        .unwrap_or_else(|| entry.message.clone())
+                })
+        });
+        match last_error {
+            Some(line) => {
+                let line: String = line.chars().take(LAST_ERROR_CHARS).collect();
+                format!("{message}; last error: {line}")
+            }
+            None => message,
+        }
+    }
+
     async fn spawn_monitor(&self, tool_id: String, child: Arc<Mutex<Child>>) {
         let manager = self.clone();
         tokio::spawn(async move {
@@ -276,7 +1013,12 @@ impl ProcessManager {
                 match child_guard.try_wait() {
                     Ok(Some(status)) => {
                         let exit_code = status.code().unwrap_or(-1);
-                        manager.processes.write().await.remove(&tool_id);
+                        drop(child_guard);
+                        let handle = manager.processes.write().await.remove(&tool_id);
+                        manager.flush_repeats(&tool_id).await;
+                        if let Some(handle) = handle {
+                            manager.run_post_stop(&tool_id, &handle).await;
+                        }
                         if manager.consume_stop_request(&tool_id).await {
                             manager.clear_backoff(&tool_id).await;
                             break;
@@ -296,13 +1038,19 @@ impl ProcessManager {
                                 .emit_log(&tool_id, McpLogStream::Event, message.clone())
                                 .await;
                             let _ = manager
-                                .store
-                                .set_tool_status(&tool_id, McpToolStatus::Stopped, None, Some(message))
+                                .set_status(&tool_id, McpToolStatus::Stopped, None, Some(message))
                                 .await;
                             manager.clear_backoff(&tool_id).await;
                             break;
                         }
 
+                        *manager
+                            .crash_counts
+                            .write()
+                            .await
+                            .entry(tool_id.clone())
+                            .or_insert(0) += 1;
+
                         if uptime <= CRASH_WINDOW {
                             let attempt = {
                                 let mut backoff = manager.backoff.write().await;
@@ -316,12 +1064,12 @@ impl ProcessManager {
 
                             if attempt as usize > BACKOFF_DELAYS.len() {
                                 let message = format!("process exited with code {exit_code}; crash loop detected");
+                                let message = manager.with_last_error(&tool_id, message).await;
                                 manager
                                     .emit_log(&tool_id, McpLogStream::Event, message.clone())
                                     .await;
                                 let _ = manager
-                                    .store
-                                    .set_tool_status(&tool_id, McpToolStatus::Crashed, None, Some(message.clone()))
+                                    .set_status(&tool_id, McpToolStatus::Crashed, None, Some(message.clone()))
                                     .await;
                                 manager.notify_crash(&tool_id, message).await;
                                 manager.clear_backoff(&tool_id).await;
@@ -339,8 +1087,7 @@ impl ProcessManager {
                                 .emit_log(&tool_id, McpLogStream::Event, message.clone())
                                 .await;
                             let _ = manager
-                                .store
-                                .set_tool_status(&tool_id, McpToolStatus::Starting, None, Some(message))
+                                .set_status(&tool_id, McpToolStatus::Starting, None, Some(message))
                                 .await;
 
                             let manager_clone = manager.clone();
@@ -355,8 +1102,7 @@ impl ProcessManager {
                                         .emit_log(&tool_id_clone, McpLogStream::Event, message.clone())
                                         .await;
                                     let _ = manager_clone
-                                        .store
-                                        .set_tool_status(&tool_id_clone, McpToolStatus::Crashed, None, Some(message.clone()))
+                                        .set_status(&tool_id_clone, McpToolStatus::Crashed, None, Some(message.clone()))
                                         .await;
                                     manager_clone.notify_crash(&tool_id_clone, message).await;
                                     manager_clone.clear_backoff(&tool_id_clone).await;
@@ -366,12 +1112,12 @@ impl ProcessManager {
                         }
 
                         let message = format!("process exited with code {exit_code}");
+                        let message = manager.with_last_error(&tool_id, message).await;
                         manager
                             .emit_log(&tool_id, McpLogStream::Event, message.clone())
                             .await;
                         let _ = manager
-                            .store
-                            .set_tool_status(&tool_id, McpToolStatus::Crashed, None, Some(message.clone()))
+                            .set_status(&tool_id, McpToolStatus::Crashed, None, Some(message.clone()))
                             .await;
                         manager.notify_crash(&tool_id, message).await;
                         manager.clear_backoff(&tool_id).await;
@@ -388,27 +1134,204 @@ impl ProcessManager {
 #[derive(Clone)]
 struct ProcessHandle {
     child: Arc<Mutex<Child>>,
+    /// Resolved when the tool started, to run once it exits.
+    post_stop: Arc<Vec<String>>,
+    hook_env: Arc<HookEnv>,
+}
+
+/// Where a tool's hooks and health check commands run: its resolved env and
+/// working directory.
+struct HookEnv {
+    env: HashMap<String, String>,
+    cwd: Option<PathBuf>,
 }
 
 struct LogBuffer {
     entries: VecDeque<McpLogEntry>,
     capacity: usize,
+    /// Approximate heap and inline size of `entries`.
+    bytes: usize,
 }
 
 impl LogBuffer {
     fn new(capacity: usize) -> Self {
         Self {
-            entries: VecDeque::with_capacity(capacity),
+            entries: VecDeque::new(),
             capacity,
+            bytes: 0,
         }
     }
 
     fn push(&mut self, entry: McpLogEntry) {
         if self.entries.len() >= self.capacity {
-            self.entries.pop_front();
+            self.pop_front();
         }
+        self.bytes += entry_bytes(&entry);
         self.entries.push_back(entry);
     }
+
+    /// Drops the oldest line, returning the bytes freed.
+    fn pop_front(&mut self) -> Option<usize> {
+        let freed = entry_bytes(&self.entries.pop_front()?);
+        self.bytes -= freed;
+        Some(freed)
+    }
+
+    /// Also releases storage beyond the new capacity; a deque keeps its
+    /// allocation when lines are popped.
+    fn resize(&mut self, capacity: usize) {
+        while self.entries.len() > capacity {
+            self.pop_front();
+        }
+        self.entries.shrink_to(capacity);
+        self.capacity = capacity;
+    }
+}
+
+/// The `cwd` of a tool's config, which has no column of its own.
+fn config_cwd(config_json: &str) -> Option<String> {
+    let config: serde_json::Value = serde_json::from_str(config_json).ok()?;
+    config.get("cwd")?.as_str().map(str::to_string)
+}
+
+/// The `hooks` of a tool's config; unreadable hooks count as none.
+pub(crate) fn config_hooks(config_json: &str) -> ToolHooks {
+    serde_json::from_str::<serde_json::Value>(config_json)
+        .ok()
+        .and_then(|mut config| serde_json::from_value(config.get_mut("hooks")?.take()).ok())
+        .unwrap_or_default()
+}
+
+/// The `healthcheck` of a tool's config; an unreadable one counts as none.
+pub(crate) fn config_healthcheck(config_json: &str) -> Option<HealthCheck> {
+    let mut config: serde_json::Value = serde_json::from_str(config_json).ok()?;
+    serde_json::from_value(config.get_mut("healthcheck")?.take()).ok()
+}
+
+/// Expands a hook's `${VAR}`s like the tool's command, quoting each value
+/// for the shell, and adds the built-ins it used and the names it left
+/// unresolved to `resolved`.
+fn interpolate_hook(
+    hook: &str,
+    builtins: &HashMap<&'static str, String>,
+    resolved: &mut Interpolated,
+) -> String {
+    let hook = interpolate_shell(hook, builtins, |name| std::env::var(name).ok());
+    merge_references(hook, resolved)
+}
+
+/// [`interpolate_hook`] for a health check's `tcp` or `http` target, which
+/// is not run through the shell and so is not quoted.
+fn interpolate_target(
+    target: &str,
+    builtins: &HashMap<&'static str, String>,
+    resolved: &mut Interpolated,
+) -> String {
+    let target = interpolate_command(target, &[], None, None, builtins, |name| {
+        std::env::var(name).ok()
+    });
+    merge_references(target, resolved)
+}
+
+fn merge_references(hook: Interpolated, resolved: &mut Interpolated) -> String {
+    for builtin in hook.builtins {
+        if !resolved.builtins.contains(&builtin) {
+            resolved.builtins.push(builtin);
+        }
+    }
+    for name in hook.unresolved {
+        if !resolved.unresolved.contains(&name) {
+            resolved.unresolved.push(name);
+        }
+    }
+    hook.command
+}
+
+/// Runs one probe of `check`, returning how long it took to pass.
+async fn probe(
+    check: &HealthCheck,
+    hook_env: &HookEnv,
+    client: &reqwest::Client,
+) -> Result<Duration, String> {
+    let started = Instant::now();
+    let timeout = check.timeout();
+    let outcome = if let Some(command) = &check.command {
+        let mut cmd = shell_command(command);
+        cmd.envs(&hook_env.env);
+        if let Some(cwd) = &hook_env.cwd {
+            cmd.current_dir(cwd);
+        }
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::null());
+        cmd.stderr(Stdio::null());
+        cmd.kill_on_drop(true);
+        tokio::time::timeout(timeout, cmd.status())
+            .await
+            .map(|status| match status {
+                Ok(status) if status.success() => Ok(()),
+                Ok(status) => Err(format!(
+                    "`{command}` exited with code {}",
+                    status.code().unwrap_or(-1)
+                )),
+                Err(err) => Err(format!("`{command}` failed to start: {err}")),
+            })
+    } else if let Some(address) = &check.tcp {
+        tokio::time::timeout(timeout, tokio::net::TcpStream::connect(address.as_str()))
+            .await
+            .map(|connected| {
+                connected
+                    .map(drop)
+                    .map_err(|err| format!("cannot connect to {address}: {err}"))
+            })
+    } else if let Some(url) = &check.http {
+        tokio::time::timeout(timeout, client.get(url).send())
+            .await
+            .map(|response| match response {
+                Ok(response) if response.status().is_success() => Ok(()),
+                Ok(response) => Err(format!("{url} answered {}", response.status())),
+                Err(err) => Err(format!("{url}: {err}")),
+            })
+    } else {
+        return Err("no command, tcp or http target".to_string());
+    };
+    match outcome {
+        Ok(Ok(())) => Ok(started.elapsed()),
+        Ok(Err(reason)) => Err(reason),
+        Err(_) => Err(format!("timed out after {}s", timeout.as_secs())),
+    }
+}
+
+fn shell_command(line: &str) -> tokio::process::Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", line]);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", line]);
+        cmd
+    }
+}
+
+fn entry_bytes(entry: &McpLogEntry) -> usize {
+    std::mem::size_of::<McpLogEntry>() + entry.timestamp.len() + entry.message.len()
+}
+
+/// Trims the largest buffers, oldest lines first, until all fit in `budget`.
+fn enforce_memory_budget(logs: &mut HashMap<String, LogBuffer>, budget: usize) {
+    let mut total: usize = logs.values().map(|buffer| buffer.bytes).sum();
+    while total > budget {
+        let Some(largest) = logs.values_mut().max_by_key(|buffer| buffer.bytes) else {
+            break;
+        };
+        let Some(freed) = largest.pop_front() else {
+            break;
+        };
+        total -= freed;
+    }
 }
 
 fn now_rfc3339() -> String {
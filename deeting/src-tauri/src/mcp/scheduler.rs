@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::{stream, StreamExt};
+use log::warn;
+use tauri::{AppHandle, Emitter};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::sync::{Mutex, Notify};
+
+use crate::mcp::assistant_sync;
+use crate::mcp::commands::{run_source_sync, sync_cloud_subscriptions_inner};
+use crate::mcp::error::McpError;
+use crate::mcp::types::{
+    AssistantConflictStrategy, CloudSyncSettings, McpSource, McpSourceType, SourceSyncResult,
+    SyncChanges, SyncSchedulerStatus,
+};
+use crate::mcp::McpRuntimeState;
+
+pub const CLOUD_SYNC_SETTINGS_KEY: &str = "cloud_sync";
+pub const SYNC_STATUS_EVENT: &str = "cloud-sync-status";
+const MIN_INTERVAL_MINUTES: u64 = 1;
+/// Sources `sync_sources` syncs at once.
+const SYNC_CONCURRENCY: usize = 4;
+
+/// Handle to the background sync loop. Besides waking it, it remembers when
+/// the loop last ran and runs next, and the latest result per cloud source,
+/// so the UI can show "last synced 5 min ago, next in 25 min".
+#[derive(Clone, Default)]
+pub struct SyncScheduler {
+    wake: Arc<Notify>,
+    forced: Arc<AtomicBool>,
+    runs: Arc<Mutex<RunState>>,
+}
+
+#[derive(Default)]
+struct RunState {
+    running: bool,
+    last_run_at: Option<String>,
+    next_run_at: Option<String>,
+    sources: HashMap<String, SourceSyncResult>,
+}
+
+impl SyncScheduler {
+    /// Makes the loop re-read its settings; when enabled it syncs right away.
+    pub fn wake(&self) {
+        self.wake.notify_one();
+    }
+
+    /// Runs one sync now, even while the scheduler is paused.
+    pub fn trigger(&self) {
+        self.forced.store(true, Ordering::SeqCst);
+        self.wake.notify_one();
+    }
+
+    async fn status(&self, settings: &CloudSyncSettings) -> SyncSchedulerStatus {
+        let runs = self.runs.lock().await;
+        let mut sources: Vec<SourceSyncResult> = runs.sources.values().cloned().collect();
+        sources.sort_by(|a, b| a.source_name.cmp(&b.source_name));
+        SyncSchedulerStatus {
+            enabled: settings.enabled,
+            interval_minutes: settings.interval_minutes,
+            running: runs.running,
+            last_run_at: runs.last_run_at.clone(),
+            next_run_at: runs.next_run_at.clone().filter(|_| settings.enabled),
+            sources,
+        }
+    }
+}
+
+/// Runs the cloud subscription sync for every signed-in cloud account (and,
+/// when enabled, the assistant sync for the primary account) on the interval
+/// stored in the `cloud_sync` setting.
+///
+/// Ticks are skipped while the job is disabled, unless a run was requested
+/// through `SyncScheduler::trigger`; accounts without a stored session are
+/// left alone. Saving the setting wakes the loop, so a new interval applies
+/// immediately.
+pub fn spawn_cloud_sync_scheduler(app: AppHandle, state: McpRuntimeState) {
+    tauri::async_runtime::spawn(async move {
+        let scheduler = state.sync_scheduler.clone();
+        loop {
+            let settings = load_settings(&state).await;
+            let forced = scheduler.forced.swap(false, Ordering::SeqCst);
+
+            if settings.enabled || forced {
+                scheduler.runs.lock().await.running = true;
+                publish_status(&app, &state).await;
+                run_cloud_sync(&app, &state, &settings).await;
+                let mut runs = scheduler.runs.lock().await;
+                runs.running = false;
+                runs.last_run_at = Some(rfc3339(OffsetDateTime::now_utc()));
+            }
+
+            let interval =
+                Duration::from_secs(settings.interval_minutes.max(MIN_INTERVAL_MINUTES) * 60);
+            scheduler.runs.lock().await.next_run_at =
+                settings.enabled.then(|| rfc3339(OffsetDateTime::now_utc() + interval));
+            publish_status(&app, &state).await;
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = scheduler.wake.notified() => {}
+            }
+        }
+    });
+}
+
+pub async fn status(state: &McpRuntimeState) -> Result<SyncSchedulerStatus, McpError> {
+    let settings = state
+        .store
+        .get_setting::<CloudSyncSettings>(CLOUD_SYNC_SETTINGS_KEY)
+        .await?;
+    Ok(state.sync_scheduler.status(&settings).await)
+}
+
+/// Remembers the outcome of a subscription sync for `source` and tells the
+/// UI; called for scheduled and manual syncs alike.
+pub(crate) async fn record_result(
+    app: &AppHandle,
+    state: &McpRuntimeState,
+    source: &McpSource,
+    result: &Result<SyncChanges, McpError>,
+) {
+    let entry = sync_result(source, result);
+    state
+        .sync_scheduler
+        .runs
+        .lock()
+        .await
+        .sources
+        .insert(source.id.clone(), entry);
+    publish_status(app, state).await;
+}
+
+fn sync_result(source: &McpSource, result: &Result<SyncChanges, McpError>) -> SourceSyncResult {
+    SourceSyncResult {
+        source_id: source.id.clone(),
+        source_name: source.name.clone(),
+        finished_at: rfc3339(OffsetDateTime::now_utc()),
+        success: result.is_ok(),
+        tool_count: result.as_ref().ok().map(SyncChanges::tool_count),
+        error: result.as_ref().err().map(ToString::to_string),
+    }
+}
+
+/// Syncs `sources`, at most `SYNC_CONCURRENCY` at a time, and returns each
+/// one's outcome in name order. Cloud sources without a stored session are
+/// skipped; other remote sources are fetched without a token.
+pub(crate) async fn sync_sources(
+    app: &AppHandle,
+    state: &McpRuntimeState,
+    sources: Vec<McpSource>,
+) -> Vec<SourceSyncResult> {
+    let mut results: Vec<SourceSyncResult> = stream::iter(sources)
+        .map(|source| sync_one(app, state, source))
+        .buffer_unordered(SYNC_CONCURRENCY)
+        .filter_map(|result| async move { result })
+        .collect()
+        .await;
+    results.sort_by(|a, b| a.source_name.cmp(&b.source_name));
+    results
+}
+
+async fn sync_one(
+    app: &AppHandle,
+    state: &McpRuntimeState,
+    source: McpSource,
+) -> Option<SourceSyncResult> {
+    let result = if source.source_type == McpSourceType::Cloud {
+        if !state
+            .cloud_accounts
+            .auth_for(&source.id)
+            .await
+            .has_session()
+            .await
+        {
+            return None;
+        }
+        sync_cloud_subscriptions_inner(app, state, Some(&source.id), None).await
+    } else {
+        run_source_sync(state, source.clone(), None).await
+    };
+    if let Err(err) = &result {
+        warn!("sync failed for {}: {}", source.name, err);
+    }
+    Some(sync_result(&source, &result))
+}
+
+async fn publish_status(app: &AppHandle, state: &McpRuntimeState) {
+    match status(state).await {
+        Ok(status) => {
+            let _ = app.emit(SYNC_STATUS_EVENT, status);
+        }
+        Err(err) => warn!("cloud sync status unavailable: {}", err),
+    }
+}
+
+async fn load_settings(state: &McpRuntimeState) -> CloudSyncSettings {
+    match state
+        .store
+        .get_setting::<CloudSyncSettings>(CLOUD_SYNC_SETTINGS_KEY)
+        .await
+    {
+        Ok(settings) => settings,
+        Err(err) => {
+            warn!("cloud sync settings unavailable: {}", err);
+            CloudSyncSettings::default()
+        }
+    }
+}
+
+async fn run_cloud_sync(app: &AppHandle, state: &McpRuntimeState, settings: &CloudSyncSettings) {
+    let sources = match state.store.list_sources_by_type(McpSourceType::Cloud).await {
+        Ok(sources) => sources,
+        Err(err) => {
+            warn!("scheduled cloud sync skipped: {}", err);
+            return;
+        }
+    };
+    sync_sources(app, state, sources).await;
+    let primary = state.cloud_accounts.primary_source_id();
+    if settings.include_assistants
+        && state
+            .cloud_accounts
+            .auth_for(primary)
+            .await
+            .has_session()
+            .await
+    {
+        if let Err(err) =
+            assistant_sync::sync_assistants(state, AssistantConflictStrategy::LastWriteWins).await
+        {
+            warn!("scheduled assistant sync failed: {}", err);
+        }
+    }
+}
+
+fn rfc3339(at: OffsetDateTime) -> String {
+    at.format(&Rfc3339).unwrap_or_default()
+}
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use crate::mcp::error::McpError;
+use crate::mcp::types::AppSettings;
+use crate::mcp::McpRuntimeState;
+
+pub const APP_SETTINGS_KEY: &str = "app";
+
+/// Pushes the settings that live in memory into the running state. Called at
+/// startup and whenever `update_app_settings` saves.
+pub async fn apply(state: &McpRuntimeState, settings: &AppSettings) {
+    state
+        .process_manager
+        .set_log_buffer_size(settings.log_buffer_size)
+        .await;
+    state
+        .process_manager
+        .set_log_memory_budget(settings.log_memory_mb << 20)
+        .await;
+    state
+        .process_manager
+        .set_log_retention(settings.log_retention_days, settings.log_retention_entries);
+    state
+        .process_manager
+        .set_log_file_rotation(settings.log_file_max_bytes, settings.log_file_keep);
+    state.process_manager.set_log_ansi(settings.log_ansi);
+    state
+        .process_manager
+        .set_log_dedup_window(Duration::from_millis(settings.log_dedup_window_ms));
+    state.process_manager.set_env_strict(settings.env_strict);
+}
+
+pub async fn load(state: &McpRuntimeState) -> Result<AppSettings, McpError> {
+    state.store.get_setting(APP_SETTINGS_KEY).await
+}
@@ -0,0 +1,90 @@
+use std::ops::Deref;
+use std::sync::RwLock;
+
+use serde::Serialize;
+use tauri::ipc::{CommandArg, CommandItem, InvokeError};
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+use crate::mcp::error::{CommandError, McpError};
+use crate::mcp::McpRuntimeState;
+
+/// Emitted once [`McpRuntimeState`] is managed and commands can run.
+pub const STORE_READY_EVENT: &str = "mcp-store-ready";
+/// Emitted with the error message when initialization fails.
+pub const STORE_FAILED_EVENT: &str = "mcp-store-failed";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum StartupStatus {
+    Initializing,
+    Ready,
+    Failed { message: String },
+}
+
+/// Where initialization stands. Managed before the setup hook returns, so
+/// the window opens while the database and sources are still being set up
+/// in the background.
+pub struct Startup {
+    status: RwLock<StartupStatus>,
+}
+
+impl Default for Startup {
+    fn default() -> Self {
+        Self {
+            status: RwLock::new(StartupStatus::Initializing),
+        }
+    }
+}
+
+impl Startup {
+    pub fn status(&self) -> StartupStatus {
+        self.status.read().expect("startup status poisoned").clone()
+    }
+
+    fn set(&self, status: StartupStatus) {
+        *self.status.write().expect("startup status poisoned") = status;
+    }
+}
+
+/// Manages the runtime state and tells the frontend it can start calling
+/// commands.
+pub fn mark_ready(app: &AppHandle, state: McpRuntimeState) {
+    app.manage(state);
+    app.state::<Startup>().set(StartupStatus::Ready);
+    let _ = app.emit(STORE_READY_EVENT, ());
+}
+
+pub fn mark_failed(app: &AppHandle, err: &McpError) {
+    let message = err.to_string();
+    app.state::<Startup>().set(StartupStatus::Failed {
+        message: message.clone(),
+    });
+    let _ = app.emit(STORE_FAILED_EVENT, message);
+}
+
+/// Command argument for the runtime state. Rejects with `INITIALIZING`
+/// while startup is still running, and with the startup error if it failed,
+/// instead of Tauri's generic unmanaged-state error.
+pub struct Ready<'r>(State<'r, McpRuntimeState>);
+
+impl Deref for Ready<'_> {
+    type Target = McpRuntimeState;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<'de, R: Runtime> CommandArg<'de, R> for Ready<'de> {
+    fn from_command(command: CommandItem<'de, R>) -> Result<Self, InvokeError> {
+        let states = command.message.state_ref();
+        if let Some(state) = states.try_get::<McpRuntimeState>() {
+            return Ok(Ready(state));
+        }
+        let err = match states.try_get::<Startup>().map(|startup| startup.status()) {
+            Some(StartupStatus::Failed { message }) => McpError::StartupFailed(message),
+            _ => McpError::Initializing,
+        };
+        Err(CommandError::from(err).into())
+    }
+}
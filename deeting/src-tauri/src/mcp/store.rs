@@ -1,33 +1,95 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
-
-use sha2::{Digest, Sha256};
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions, SqliteRow};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::warn;
+use mcp_core::hash::hash_json;
+use mcp_core::pool::{self, PoolSettings};
+use mcp_core::tool_cache::ToolCache;
+use sqlx::sqlite::{SqliteConnection, SqlitePool};
 use sqlx::Row;
 use uuid::Uuid;
 
 use crate::mcp::error::McpError;
 use crate::mcp::types::{
     CreateAssistantMessageRequest, CreateLocalAssistantRequest, LocalAssistant, LocalAssistantMessage,
-    McpConflictStatus, McpSource, McpSourceStatus, McpSourceType, McpTool, McpToolConfigPayload,
-    McpToolStatus, McpTrustLevel, UpdateLocalAssistantRequest,
+    McpConflictStatus, McpLogEntry, McpLogLevel, McpLogStream, McpSource, McpSourceStatus, McpSourceType,
+    McpTool, McpToolConfigPayload, McpToolStatus, McpTrustLevel, SchemaInfo,
+    UpdateLocalAssistantRequest,
 };
+use crate::paths::default_local_config_path;
 
-const DEFAULT_LOCAL_SOURCE_PATH: &str = "~/.config/deeting/mcp.json";
 const DEFAULT_CLOUD_SOURCE_NAME: &str = "Deeting Cloud";
 
+/// [`PoolSettings`] from the environment rather than app settings, which
+/// live in the database. Defaults to 5 connections, a 30s acquire timeout and
+/// 100 cached statements.
+fn pool_settings() -> PoolSettings {
+    let defaults = PoolSettings {
+        max_connections: 5,
+        acquire_timeout: Duration::from_secs(30),
+        statement_cache: 100,
+    };
+    PoolSettings::from_env(defaults, |key, value| {
+        warn!("ignoring invalid {key}={value:?}")
+    })
+}
+
 pub struct McpStore {
     pool: SqlitePool,
+    /// Long reads (log history and search, assistant messages); see
+    /// [`pool::connect`].
+    read_pool: SqlitePool,
+    tool_cache: ToolCache<McpTool>,
+    /// Content hash of the config file last applied per local source.
+    applied_configs: std::sync::Mutex<HashMap<String, String>>,
 }
 
 impl McpStore {
     pub async fn new(database_url: &str) -> Result<Self, McpError> {
-        let pool = SqlitePoolOptions::new()
-            .max_connections(5)
-            .connect(database_url)
+        let (pool, read_pool) = pool::connect(&pool_settings(), database_url)
             .await
             .map_err(|err| McpError::Storage(err.to_string()))?;
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            read_pool,
+            tool_cache: ToolCache::default(),
+            applied_configs: std::sync::Mutex::default(),
+        })
+    }
+
+    /// Whether `content_hash` is the config last applied to the source, so
+    /// syncing it again would change nothing.
+    pub fn is_config_applied(&self, source_id: &str, content_hash: &str) -> bool {
+        self.applied_configs
+            .lock()
+            .expect("applied configs poisoned")
+            .get(source_id)
+            .is_some_and(|applied| applied == content_hash)
+    }
+
+    pub fn applied_config(&self, source_id: &str) -> Option<String> {
+        self.applied_configs
+            .lock()
+            .expect("applied configs poisoned")
+            .get(source_id)
+            .cloned()
+    }
+
+    pub fn record_applied_config(&self, source_id: &str, content_hash: String) {
+        self.applied_configs
+            .lock()
+            .expect("applied configs poisoned")
+            .insert(source_id.to_string(), content_hash);
+    }
+
+    /// Makes the next sync of the source apply its config again, after its
+    /// tools were changed some other way.
+    pub fn forget_applied_config(&self, source_id: &str) {
+        self.applied_configs
+            .lock()
+            .expect("applied configs poisoned")
+            .remove(source_id);
     }
 
     pub async fn init(&self) -> Result<(), McpError> {
@@ -74,6 +136,11 @@ impl McpStore {
               conflict_status TEXT NOT NULL,
               is_read_only INTEGER NOT NULL,
               is_new INTEGER NOT NULL,
+              approved_at TEXT,
+              market_tool_id TEXT,
+              auto_start INTEGER NOT NULL DEFAULT 0,
+              log_to_file INTEGER NOT NULL DEFAULT 0,
+              log_buffer_size INTEGER,
               created_at TEXT NOT NULL,
               updated_at TEXT NOT NULL,
               FOREIGN KEY (source_id) REFERENCES mcp_sources(id)
@@ -97,6 +164,8 @@ impl McpStore {
               visibility TEXT NOT NULL,
               source TEXT NOT NULL,
               cloud_id TEXT,
+              cloud_updated_at TEXT,
+              cloud_synced_at TEXT,
               is_deleted INTEGER NOT NULL,
               created_at TEXT NOT NULL,
               updated_at TEXT NOT NULL
@@ -125,6 +194,45 @@ impl McpStore {
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS app_settings (
+              key TEXT PRIMARY KEY,
+              value TEXT NOT NULL,
+              updated_at TEXT NOT NULL
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS mcp_logs (
+              id INTEGER PRIMARY KEY AUTOINCREMENT,
+              tool_id TEXT NOT NULL,
+              timestamp TEXT NOT NULL,
+              stream TEXT NOT NULL,
+              message TEXT NOT NULL,
+              level TEXT NOT NULL DEFAULT 'info'
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_mcp_logs_tool_id_id
+            ON mcp_logs(tool_id, id);
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+
         sqlx::query(
             r#"
             CREATE INDEX IF NOT EXISTS idx_assistant_messages_assistant_id_created_at
@@ -135,6 +243,20 @@ impl McpStore {
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
+        self.ensure_column(
+            "assistants",
+            "cloud_updated_at",
+            "ALTER TABLE assistants ADD COLUMN cloud_updated_at TEXT;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "assistants",
+            "cloud_synced_at",
+            "ALTER TABLE assistants ADD COLUMN cloud_synced_at TEXT;",
+        )
+        .await?;
+
         self.ensure_column(
             "mcp_tools",
             "identifier",
@@ -163,6 +285,48 @@ impl McpStore {
         )
         .await?;
 
+        self.ensure_column(
+            "mcp_tools",
+            "approved_at",
+            "ALTER TABLE mcp_tools ADD COLUMN approved_at TEXT;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "market_tool_id",
+            "ALTER TABLE mcp_tools ADD COLUMN market_tool_id TEXT;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "auto_start",
+            "ALTER TABLE mcp_tools ADD COLUMN auto_start INTEGER NOT NULL DEFAULT 0;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "log_to_file",
+            "ALTER TABLE mcp_tools ADD COLUMN log_to_file INTEGER NOT NULL DEFAULT 0;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "log_buffer_size",
+            "ALTER TABLE mcp_tools ADD COLUMN log_buffer_size INTEGER;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_logs",
+            "level",
+            "ALTER TABLE mcp_logs ADD COLUMN level TEXT NOT NULL DEFAULT 'info';",
+        )
+        .await?;
+
         sqlx::query(
             r#"
             CREATE UNIQUE INDEX IF NOT EXISTS idx_mcp_tools_source_name
@@ -193,23 +357,23 @@ impl McpStore {
 
         let now = now_rfc3339()?;
         let id = Uuid::new_v4().to_string();
-        sqlx::query(
+        sqlx::query!(
             r#"
             INSERT INTO mcp_sources
               (id, name, source_type, path_or_url, trust_level, status, last_synced_at, is_read_only, created_at, updated_at)
             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
             "#,
+            &id,
+            "Local Config",
+            McpSourceType::Local.as_str(),
+            default_local_config_path().to_string_lossy().into_owned(),
+            McpTrustLevel::Private.as_str(),
+            McpSourceStatus::Active.as_str(),
+            None::<String>,
+            0,
+            &now,
+            &now,
         )
-        .bind(&id)
-        .bind("Local Config")
-        .bind(McpSourceType::Local.as_str())
-        .bind(DEFAULT_LOCAL_SOURCE_PATH)
-        .bind(McpTrustLevel::Private.as_str())
-        .bind(McpSourceStatus::Active.as_str())
-        .bind::<Option<String>>(None)
-        .bind(0)
-        .bind(&now)
-        .bind(&now)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
@@ -226,23 +390,23 @@ impl McpStore {
 
         let now = now_rfc3339()?;
         let id = Uuid::new_v4().to_string();
-        sqlx::query(
+        sqlx::query!(
             r#"
             INSERT INTO mcp_sources
               (id, name, source_type, path_or_url, trust_level, status, last_synced_at, is_read_only, created_at, updated_at)
             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
             "#,
+            &id,
+            DEFAULT_CLOUD_SOURCE_NAME,
+            McpSourceType::Cloud.as_str(),
+            base_url,
+            McpTrustLevel::Official.as_str(),
+            McpSourceStatus::Active.as_str(),
+            None::<String>,
+            1,
+            &now,
+            &now,
         )
-        .bind(&id)
-        .bind(DEFAULT_CLOUD_SOURCE_NAME)
-        .bind(McpSourceType::Cloud.as_str())
-        .bind(base_url)
-        .bind(McpTrustLevel::Official.as_str())
-        .bind(McpSourceStatus::Active.as_str())
-        .bind::<Option<String>>(None)
-        .bind(1)
-        .bind(&now)
-        .bind(&now)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
@@ -253,9 +417,10 @@ impl McpStore {
     }
 
     pub async fn list_sources(&self) -> Result<Vec<McpSource>, McpError> {
-        let rows = sqlx::query(
+        let rows = sqlx::query_as!(
+            SourceRow,
             r#"
-            SELECT id, name, source_type, path_or_url, trust_level, status,
+            SELECT id AS "id!", name, source_type, path_or_url, trust_level, status,
                    last_synced_at, is_read_only, created_at, updated_at
             FROM mcp_sources
             ORDER BY created_at ASC;
@@ -265,70 +430,125 @@ impl McpStore {
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        let mut sources = Vec::with_capacity(rows.len());
-        for row in rows {
-            sources.push(row_to_source(&row)?);
-        }
-        Ok(sources)
+        rows.into_iter().map(McpSource::try_from).collect()
     }
 
     pub async fn get_source(&self, id: &str) -> Result<Option<McpSource>, McpError> {
-        let row = sqlx::query(
+        let row = sqlx::query_as!(
+            SourceRow,
             r#"
-            SELECT id, name, source_type, path_or_url, trust_level, status,
+            SELECT id AS "id!", name, source_type, path_or_url, trust_level, status,
                    last_synced_at, is_read_only, created_at, updated_at
             FROM mcp_sources
             WHERE id = ?;
             "#,
+            id,
         )
-        .bind(id)
         .fetch_optional(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        row.map(|row| row_to_source(&row)).transpose()
+        row.map(McpSource::try_from).transpose()
     }
 
     pub async fn find_source_by_type(
         &self,
         source_type: McpSourceType,
     ) -> Result<Option<McpSource>, McpError> {
-        let row = sqlx::query(
+        let row = sqlx::query_as!(
+            SourceRow,
             r#"
-            SELECT id, name, source_type, path_or_url, trust_level, status,
+            SELECT id AS "id!", name, source_type, path_or_url, trust_level, status,
                    last_synced_at, is_read_only, created_at, updated_at
             FROM mcp_sources
-            WHERE source_type = ?;
+            WHERE source_type = ?
+            ORDER BY created_at ASC
+            LIMIT 1;
             "#,
+            source_type.as_str(),
         )
-        .bind(source_type.as_str())
         .fetch_optional(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        row.map(|row| row_to_source(&row)).transpose()
+        row.map(McpSource::try_from).transpose()
+    }
+
+    pub async fn list_sources_by_type(
+        &self,
+        source_type: McpSourceType,
+    ) -> Result<Vec<McpSource>, McpError> {
+        let rows = sqlx::query_as!(
+            SourceRow,
+            r#"
+            SELECT id AS "id!", name, source_type, path_or_url, trust_level, status,
+                   last_synced_at, is_read_only, created_at, updated_at
+            FROM mcp_sources
+            WHERE source_type = ?
+            ORDER BY created_at ASC;
+            "#,
+            source_type.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+
+        rows.into_iter().map(McpSource::try_from).collect()
+    }
+
+    /// Deletes a source together with every tool it provided.
+    pub async fn delete_source(&self, id: &str) -> Result<(), McpError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        sqlx::query!(
+            "DELETE FROM mcp_logs WHERE tool_id IN (SELECT id FROM mcp_tools WHERE source_id = ?);",
+            id,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+        sqlx::query!("DELETE FROM mcp_tools WHERE source_id = ?;", id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        let result = sqlx::query!("DELETE FROM mcp_sources WHERE id = ?;", id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        if result.rows_affected() == 0 {
+            return Err(McpError::SourceNotFound(id.to_string()));
+        }
+        tx.commit()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
+        self.forget_applied_config(id);
+        Ok(())
     }
 
     pub async fn insert_source(&self, source: NewSource) -> Result<McpSource, McpError> {
         let now = now_rfc3339()?;
         let id = Uuid::new_v4().to_string();
-        sqlx::query(
+        sqlx::query!(
             r#"
             INSERT INTO mcp_sources
               (id, name, source_type, path_or_url, trust_level, status, last_synced_at, is_read_only, created_at, updated_at)
             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
             "#,
+            &id,
+            &source.name,
+            source.source_type.as_str(),
+            &source.path_or_url,
+            source.trust_level.as_str(),
+            source.status.as_str(),
+            source.last_synced_at,
+            if source.is_read_only { 1 } else { 0 },
+            &now,
+            &now,
         )
-        .bind(&id)
-        .bind(&source.name)
-        .bind(source.source_type.as_str())
-        .bind(&source.path_or_url)
-        .bind(source.trust_level.as_str())
-        .bind(source.status.as_str())
-        .bind(source.last_synced_at)
-        .bind(if source.is_read_only { 1 } else { 0 })
-        .bind(&now)
-        .bind(&now)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
@@ -345,17 +565,17 @@ impl McpStore {
         last_synced_at: Option<String>,
     ) -> Result<(), McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        sqlx::query!(
             r#"
             UPDATE mcp_sources
             SET status = ?, last_synced_at = ?, updated_at = ?
             WHERE id = ?;
             "#,
+            status.as_str(),
+            last_synced_at,
+            now,
+            id,
         )
-        .bind(status.as_str())
-        .bind(last_synced_at)
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
@@ -363,11 +583,23 @@ impl McpStore {
     }
 
     pub async fn list_tools(&self) -> Result<Vec<McpTool>, McpError> {
-        let rows = sqlx::query(
+        Ok(self.cached_tools().await?.as_ref().clone())
+    }
+
+    /// Every tool in `created_at` order, from SQLite only when a write has
+    /// invalidated the cache since the last read.
+    async fn cached_tools(&self) -> Result<Arc<Vec<McpTool>>, McpError> {
+        if let Some(tools) = self.tool_cache.get() {
+            return Ok(tools);
+        }
+        let generation = self.tool_cache.generation();
+        let rows = sqlx::query_as!(
+            ToolRow,
             r#"
-            SELECT id, source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
                    error, command, args, env, config_json, config_hash, pending_config_json,
-                   pending_config_hash, conflict_status, is_read_only, is_new, created_at, updated_at
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, auto_start, log_to_file, log_buffer_size, created_at, updated_at
             FROM mcp_tools
             ORDER BY created_at ASC;
             "#,
@@ -376,69 +608,47 @@ impl McpStore {
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        let mut tools = Vec::with_capacity(rows.len());
-        for row in rows {
-            tools.push(row_to_tool(&row)?);
-        }
-        Ok(tools)
+        let tools = rows
+            .into_iter()
+            .map(McpTool::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(self.tool_cache.fill(generation, tools))
     }
 
     pub async fn get_tool(&self, id: &str) -> Result<Option<McpTool>, McpError> {
-        let row = sqlx::query(
+        let row = sqlx::query_as!(
+            ToolRow,
             r#"
-            SELECT id, source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
                    error, command, args, env, config_json, config_hash, pending_config_json,
-                   pending_config_hash, conflict_status, is_read_only, is_new, created_at, updated_at
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, auto_start, log_to_file, log_buffer_size, created_at, updated_at
             FROM mcp_tools
             WHERE id = ?;
             "#,
+            id,
         )
-        .bind(id)
         .fetch_optional(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        row.map(|row| row_to_tool(&row)).transpose()
+        row.map(McpTool::try_from).transpose()
     }
 
     pub async fn get_pending_config_json(&self, id: &str) -> Result<Option<String>, McpError> {
-        let row = sqlx::query(
+        let pending = sqlx::query_scalar!(
             r#"
             SELECT pending_config_json
             FROM mcp_tools
             WHERE id = ?;
             "#,
+            id,
         )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await
-        .map_err(|err| McpError::Storage(err.to_string()))?;
-
-        Ok(row.and_then(|row| row.try_get::<String, _>("pending_config_json").ok()))
-    }
-
-    pub async fn get_tool_by_source_name(
-        &self,
-        source_id: &str,
-        name: &str,
-    ) -> Result<Option<McpTool>, McpError> {
-        let row = sqlx::query(
-            r#"
-            SELECT id, source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
-                   error, command, args, env, config_json, config_hash, pending_config_json,
-                   pending_config_hash, conflict_status, is_read_only, is_new, created_at, updated_at
-            FROM mcp_tools
-            WHERE source_id = ? AND name = ?
-            LIMIT 1;
-            "#,
-        )
-        .bind(source_id)
-        .bind(name)
         .fetch_optional(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        row.map(|row| row_to_tool(&row)).transpose()
+        Ok(pending.flatten())
     }
 
     pub async fn get_tool_by_source_identifier(
@@ -446,23 +656,25 @@ impl McpStore {
         source_id: &str,
         identifier: &str,
     ) -> Result<Option<McpTool>, McpError> {
-        let row = sqlx::query(
+        let row = sqlx::query_as!(
+            ToolRow,
             r#"
-            SELECT id, source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
                    error, command, args, env, config_json, config_hash, pending_config_json,
-                   pending_config_hash, conflict_status, is_read_only, is_new, created_at, updated_at
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, auto_start, log_to_file, log_buffer_size, created_at, updated_at
             FROM mcp_tools
             WHERE source_id = ? AND identifier = ?
             LIMIT 1;
             "#,
+            source_id,
+            identifier,
         )
-        .bind(source_id)
-        .bind(identifier)
         .fetch_optional(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        row.map(|row| row_to_tool(&row)).transpose()
+        row.map(McpTool::try_from).transpose()
     }
 
     pub async fn has_name_conflict(
@@ -470,30 +682,151 @@ impl McpStore {
         name: &str,
         source_id: &str,
     ) -> Result<bool, McpError> {
-        let row = sqlx::query(
+        let count = sqlx::query_scalar!(
             r#"
             SELECT COUNT(*) as count
             FROM mcp_tools
             WHERE name = ? AND source_id != ? AND source_type = ?;
             "#,
+            name,
+            source_id,
+            McpSourceType::Local.as_str(),
         )
-        .bind(name)
-        .bind(source_id)
-        .bind(McpSourceType::Local.as_str())
         .fetch_one(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        let count: i64 = row.try_get("count")?;
         Ok(count > 0)
     }
 
+    /// Reads what a sync compares a source's servers against, in two queries
+    /// instead of several per server.
+    pub async fn source_snapshot(&self, source_id: &str) -> Result<SourceSnapshot, McpError> {
+        let mut tools = HashMap::new();
+        for tool in self.list_source_tools(source_id).await? {
+            tools.entry(tool.name.clone()).or_insert(tool);
+        }
+
+        let local_names = sqlx::query_scalar!(
+            r#"
+            SELECT name
+            FROM mcp_tools
+            WHERE source_id != ? AND source_type = ?;
+            "#,
+            source_id,
+            McpSourceType::Local.as_str(),
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?
+        .into_iter()
+        .collect();
+
+        Ok(SourceSnapshot { tools, local_names })
+    }
+
+    /// Applies a sync's writes to `source_id` in one transaction and returns
+    /// the tools in the order of `writes`.
+    pub async fn apply_sync_writes(
+        &self,
+        source_id: &str,
+        writes: Vec<SyncWrite>,
+    ) -> Result<Vec<McpTool>, McpError> {
+        let now = now_rfc3339()?;
+        let mut ids = Vec::with_capacity(writes.len());
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        for write in writes {
+            match write {
+                SyncWrite::Keep(id) => ids.push(id),
+                SyncWrite::Pending {
+                    id,
+                    config_json,
+                    config_hash,
+                    conflict_status,
+                } => {
+                    stage_pending_update(
+                        &mut tx,
+                        &id,
+                        config_json,
+                        config_hash,
+                        conflict_status,
+                        &now,
+                    )
+                    .await?;
+                    ids.push(id);
+                }
+                SyncWrite::Upsert(tool) => {
+                    let id = match tool.id.clone() {
+                        Some(id) => {
+                            update_tool(&mut tx, &id, *tool, &now).await?;
+                            id
+                        }
+                        None => {
+                            let id = Uuid::new_v4().to_string();
+                            insert_tool(&mut tx, &id, *tool, &now).await?;
+                            id
+                        }
+                    };
+                    ids.push(id);
+                }
+            }
+        }
+        tx.commit()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
+
+        let mut stored: HashMap<String, McpTool> = self
+            .list_source_tools(source_id)
+            .await?
+            .into_iter()
+            .map(|tool| (tool.id.clone(), tool))
+            .collect();
+        ids.into_iter()
+            .map(|id| {
+                stored
+                    .remove(&id)
+                    .ok_or_else(|| McpError::NotFound("tool missing after sync".to_string()))
+            })
+            .collect()
+    }
+
+    async fn list_source_tools(&self, source_id: &str) -> Result<Vec<McpTool>, McpError> {
+        let rows = sqlx::query_as!(
+            ToolRow,
+            r#"
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+                   error, command, args, env, config_json, config_hash, pending_config_json,
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, auto_start, log_to_file, log_buffer_size, created_at, updated_at
+            FROM mcp_tools
+            WHERE source_id = ?
+            ORDER BY created_at ASC;
+            "#,
+            source_id,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+        rows.into_iter().map(McpTool::try_from).collect()
+    }
+
     pub async fn upsert_tool(&self, tool: ToolUpsert) -> Result<McpTool, McpError> {
         if let Some(existing_id) = self
             .find_tool_id_by_source_identifier(tool.source_id.as_str(), tool.identifier.as_deref())
             .await?
         {
-            self.update_tool(&existing_id, tool.clone()).await?;
+            let mut conn = self
+                .pool
+                .acquire()
+                .await
+                .map_err(|err| McpError::Storage(err.to_string()))?;
+            update_tool(&mut conn, &existing_id, tool.clone(), &now_rfc3339()?).await?;
+            self.tool_cache.invalidate();
             let updated = self
                 .get_tool(&existing_id)
                 .await?
@@ -501,7 +834,15 @@ impl McpStore {
             return Ok(updated);
         }
 
-        self.insert_tool(tool.clone()).await?;
+        let id = tool.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        insert_tool(&mut conn, &id, tool.clone(), &now_rfc3339()?).await?;
+        drop(conn);
+        self.tool_cache.invalidate();
         let created = self
             .find_tool_id_by_source_identifier(tool.source_id.as_str(), tool.identifier.as_deref())
             .await?
@@ -519,21 +860,22 @@ impl McpStore {
         error: Option<String>,
     ) -> Result<(), McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        sqlx::query!(
             r#"
             UPDATE mcp_tools
             SET status = ?, ping_ms = ?, error = ?, updated_at = ?
             WHERE id = ?;
             "#,
+            status.as_str(),
+            ping_ms,
+            error,
+            now,
+            id,
         )
-        .bind(status.as_str())
-        .bind(ping_ms)
-        .bind(error)
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
         Ok(())
     }
 
@@ -543,19 +885,20 @@ impl McpStore {
         env: Option<HashMap<String, String>>,
     ) -> Result<McpTool, McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        sqlx::query!(
             r#"
             UPDATE mcp_tools
             SET env = ?, is_new = 0, updated_at = ?
             WHERE id = ?;
             "#,
+            serialize_json(&env)?,
+            now,
+            id,
         )
-        .bind(serialize_json(&env)?)
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
 
         self.get_tool(id)
             .await?
@@ -564,107 +907,240 @@ impl McpStore {
 
     pub async fn set_tool_new_flag(&self, id: &str, is_new: bool) -> Result<(), McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        sqlx::query!(
             r#"
             UPDATE mcp_tools
             SET is_new = ?, updated_at = ?
             WHERE id = ?;
             "#,
+            if is_new { 1 } else { 0 },
+            now,
+            id,
         )
-        .bind(if is_new { 1 } else { 0 })
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
         Ok(())
     }
 
-    pub async fn mark_tool_pending_update(
+    pub async fn set_tool_approval(
         &self,
         id: &str,
-        pending_config_json: String,
-        pending_config_hash: String,
-        conflict_status: McpConflictStatus,
-    ) -> Result<(), McpError> {
+        approved_at: Option<String>,
+    ) -> Result<McpTool, McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        let result = sqlx::query!(
             r#"
             UPDATE mcp_tools
-            SET pending_config_json = ?,
-                pending_config_hash = ?,
-                conflict_status = ?,
-                updated_at = ?
+            SET approved_at = ?, updated_at = ?
             WHERE id = ?;
             "#,
+            approved_at,
+            now,
+            id,
         )
-        .bind(pending_config_json)
-        .bind(pending_config_hash)
-        .bind(conflict_status.as_str())
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
-        Ok(())
+        self.tool_cache.invalidate();
+
+        if result.rows_affected() == 0 {
+            return Err(McpError::ToolNotFound(id.to_string()));
+        }
+        self.get_tool(id)
+            .await?
+            .ok_or_else(|| McpError::NotFound("tool missing after approval".to_string()))
     }
 
-    pub async fn clear_pending_update(&self, id: &str) -> Result<(), McpError> {
+    pub async fn set_tool_market_id(
+        &self,
+        id: &str,
+        market_tool_id: &str,
+    ) -> Result<McpTool, McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        sqlx::query!(
             r#"
             UPDATE mcp_tools
-            SET pending_config_json = NULL,
-                pending_config_hash = NULL,
-                conflict_status = ?,
-                updated_at = ?
+            SET market_tool_id = ?, updated_at = ?
             WHERE id = ?;
             "#,
+            market_tool_id,
+            now,
+            id,
         )
-        .bind(McpConflictStatus::None.as_str())
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
-        Ok(())
+        self.tool_cache.invalidate();
+
+        self.get_tool(id)
+            .await?
+            .ok_or_else(|| McpError::NotFound("tool missing after publish".to_string()))
     }
 
-    pub fn extract_tool_fields(
-        &self,
-        name: &str,
-        payload: &McpToolConfigPayload,
-    ) -> ExtractedToolFields {
-        ExtractedToolFields {
-            name: name.to_string(),
-            description: payload
-                .description
-                .clone()
-                .unwrap_or_else(|| "MCP tool".to_string()),
-            command: payload.command.clone(),
-            args: payload.args.clone(),
-            env: payload.env.clone(),
-            capabilities: payload.capabilities.clone().unwrap_or_default(),
+    pub async fn set_tool_auto_start(&self, id: &str, auto_start: bool) -> Result<McpTool, McpError> {
+        let now = now_rfc3339()?;
+        let result = sqlx::query!(
+            r#"
+            UPDATE mcp_tools
+            SET auto_start = ?, updated_at = ?
+            WHERE id = ?;
+            "#,
+            if auto_start { 1 } else { 0 },
+            now,
+            id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
+        if result.rows_affected() == 0 {
+            return Err(McpError::ToolNotFound(id.to_string()));
         }
+        self.get_tool(id)
+            .await?
+            .ok_or_else(|| McpError::NotFound("tool missing after update".to_string()))
     }
 
-    pub fn build_config_json(
+    pub async fn set_tool_log_to_file(
         &self,
-        name: &str,
-        payload: &McpToolConfigPayload,
-    ) -> Result<serde_json::Value, McpError> {
-        let mut map = serde_json::Map::new();
-        map.insert("name".to_string(), serde_json::Value::String(name.to_string()));
-        if let Some(command) = &payload.command {
-            map.insert("command".to_string(), serde_json::Value::String(command.clone()));
-        }
-        if let Some(args) = &payload.args {
-            map.insert(
-                "args".to_string(),
-                serde_json::Value::Array(
-                    args.iter().cloned().map(serde_json::Value::String).collect(),
-                ),
-            );
+        id: &str,
+        log_to_file: bool,
+    ) -> Result<McpTool, McpError> {
+        let now = now_rfc3339()?;
+        let result = sqlx::query!(
+            r#"
+            UPDATE mcp_tools
+            SET log_to_file = ?, updated_at = ?
+            WHERE id = ?;
+            "#,
+            if log_to_file { 1 } else { 0 },
+            now,
+            id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
+        if result.rows_affected() == 0 {
+            return Err(McpError::ToolNotFound(id.to_string()));
+        }
+        self.get_tool(id)
+            .await?
+            .ok_or_else(|| McpError::NotFound("tool missing after update".to_string()))
+    }
+
+    pub async fn set_tool_log_buffer_size(
+        &self,
+        id: &str,
+        log_buffer_size: Option<usize>,
+    ) -> Result<McpTool, McpError> {
+        let now = now_rfc3339()?;
+        let result = sqlx::query!(
+            r#"
+            UPDATE mcp_tools
+            SET log_buffer_size = ?, updated_at = ?
+            WHERE id = ?;
+            "#,
+            log_buffer_size.map(|size| size as i64),
+            now,
+            id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
+        if result.rows_affected() == 0 {
+            return Err(McpError::ToolNotFound(id.to_string()));
+        }
+        self.get_tool(id)
+            .await?
+            .ok_or_else(|| McpError::NotFound("tool missing after update".to_string()))
+    }
+
+    pub async fn mark_tool_pending_update(
+        &self,
+        id: &str,
+        pending_config_json: String,
+        pending_config_hash: String,
+        conflict_status: McpConflictStatus,
+    ) -> Result<(), McpError> {
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        stage_pending_update(
+            &mut conn,
+            id,
+            pending_config_json,
+            pending_config_hash,
+            conflict_status,
+            &now_rfc3339()?,
+        )
+        .await?;
+        self.tool_cache.invalidate();
+        Ok(())
+    }
+
+    pub async fn clear_pending_update(&self, id: &str) -> Result<(), McpError> {
+        let now = now_rfc3339()?;
+        sqlx::query!(
+            r#"
+            UPDATE mcp_tools
+            SET pending_config_json = NULL,
+                pending_config_hash = NULL,
+                conflict_status = ?,
+                updated_at = ?
+            WHERE id = ?;
+            "#,
+            McpConflictStatus::None.as_str(),
+            now,
+            id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
+        Ok(())
+    }
+
+    pub fn extract_tool_fields(
+        &self,
+        name: &str,
+        payload: &McpToolConfigPayload,
+    ) -> ExtractedToolFields {
+        ExtractedToolFields {
+            name: name.to_string(),
+            description: payload
+                .description
+                .clone()
+                .unwrap_or_else(|| "MCP tool".to_string()),
+            command: payload.command.clone(),
+            args: payload.args.clone(),
+            env: payload.env.clone(),
+            capabilities: payload.capabilities.clone().unwrap_or_default(),
+        }
+    }
+
+    pub fn build_config_json(
+        &self,
+        name: &str,
+        payload: &McpToolConfigPayload,
+    ) -> Result<serde_json::Value, McpError> {
+        let mut map = serde_json::Map::new();
+        map.insert("name".to_string(), serde_json::Value::String(name.to_string()));
+        if let Some(command) = &payload.command {
+            map.insert("command".to_string(), serde_json::Value::String(command.clone()));
+        }
+        if let Some(args) = &payload.args {
+            map.insert(
+                "args".to_string(),
+                serde_json::Value::Array(
+                    args.iter().cloned().map(serde_json::Value::String).collect(),
+                ),
+            );
         }
         if let Some(env) = &payload.env {
             let env_map = env
@@ -673,6 +1149,19 @@ impl McpStore {
                 .collect();
             map.insert("env".to_string(), serde_json::Value::Object(env_map));
         }
+        if let Some(cwd) = &payload.cwd {
+            map.insert("cwd".to_string(), serde_json::Value::String(cwd.clone()));
+        }
+        if let Some(hooks) = payload.hooks.as_ref().filter(|hooks| !hooks.is_empty()) {
+            map.insert("hooks".to_string(), serde_json::to_value(hooks)?);
+        }
+        if let Some(healthcheck) = &payload.healthcheck {
+            healthcheck.validate().map_err(McpError::validation)?;
+            map.insert(
+                "healthcheck".to_string(),
+                serde_json::to_value(healthcheck)?,
+            );
+        }
         if let Some(description) = &payload.description {
             map.insert(
                 "description".to_string(),
@@ -698,7 +1187,7 @@ impl McpStore {
     }
 
     pub fn compute_config_hash(&self, value: &serde_json::Value) -> Result<String, McpError> {
-        Ok(hash_json(value))
+        hash_json(value).map_err(|err| McpError::Storage(err.to_string()))
     }
 
     async fn find_tool_id_by_source_identifier(
@@ -706,122 +1195,45 @@ impl McpStore {
         source_id: &str,
         identifier: Option<&str>,
     ) -> Result<Option<String>, McpError> {
-        let row = if let Some(identifier) = identifier {
-            sqlx::query(
+        let id = if let Some(identifier) = identifier {
+            sqlx::query_scalar!(
                 r#"
-                SELECT id
+                SELECT id AS "id!"
                 FROM mcp_tools
                 WHERE source_id = ? AND identifier = ?
                 LIMIT 1;
                 "#,
+                source_id,
+                identifier,
             )
-            .bind(source_id)
-            .bind(identifier)
             .fetch_optional(&self.pool)
             .await
             .map_err(|err| McpError::Storage(err.to_string()))?
         } else {
-            sqlx::query(
+            sqlx::query_scalar!(
                 r#"
-                SELECT id
+                SELECT id AS "id!"
                 FROM mcp_tools
                 WHERE source_id = ? AND identifier IS NULL
                 LIMIT 1;
                 "#,
+                source_id,
             )
-            .bind(source_id)
             .fetch_optional(&self.pool)
             .await
             .map_err(|err| McpError::Storage(err.to_string()))?
         };
 
-        Ok(row.and_then(|row| row.try_get::<String, _>("id").ok()))
-    }
-
-    async fn insert_tool(&self, tool: ToolUpsert) -> Result<(), McpError> {
-        let now = now_rfc3339()?;
-        let id = tool.id.unwrap_or_else(|| Uuid::new_v4().to_string());
-        sqlx::query(
-            r#"
-            INSERT INTO mcp_tools
-              (id, source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
-               error, command, args, env, config_json, config_hash, pending_config_json,
-               pending_config_hash, conflict_status, is_read_only, is_new, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
-            "#,
-        )
-        .bind(&id)
-        .bind(&tool.source_id)
-        .bind(&tool.identifier)
-        .bind(&tool.name)
-        .bind(tool.source_type.as_str())
-        .bind(tool.status.as_str())
-        .bind(tool.ping_ms)
-        .bind(serde_json::to_string(&tool.capabilities)?)
-        .bind(&tool.description)
-        .bind(tool.error)
-        .bind(tool.command)
-        .bind(serialize_json(&tool.args)?)
-        .bind(serialize_json(&tool.env)?)
-        .bind(tool.config_json)
-        .bind(tool.config_hash)
-        .bind(tool.pending_config_json)
-        .bind(tool.pending_config_hash)
-        .bind(tool.conflict_status.as_str())
-        .bind(if tool.is_read_only { 1 } else { 0 })
-        .bind(if tool.is_new { 1 } else { 0 })
-        .bind(&now)
-        .bind(&now)
-        .execute(&self.pool)
-        .await
-        .map_err(|err| McpError::Storage(err.to_string()))?;
-        Ok(())
-    }
-
-    async fn update_tool(&self, id: &str, tool: ToolUpsert) -> Result<(), McpError> {
-        let now = now_rfc3339()?;
-        sqlx::query(
-            r#"
-            UPDATE mcp_tools
-            SET source_id = ?, identifier = ?, name = ?, source_type = ?, status = ?, ping_ms = ?,
-                capabilities = ?, description = ?, error = ?, command = ?, args = ?, env = ?,
-                config_json = ?, config_hash = ?, pending_config_json = ?, pending_config_hash = ?,
-                conflict_status = ?, is_read_only = ?, is_new = ?, updated_at = ?
-            WHERE id = ?;
-            "#,
-        )
-        .bind(&tool.source_id)
-        .bind(&tool.identifier)
-        .bind(&tool.name)
-        .bind(tool.source_type.as_str())
-        .bind(tool.status.as_str())
-        .bind(tool.ping_ms)
-        .bind(serde_json::to_string(&tool.capabilities)?)
-        .bind(&tool.description)
-        .bind(tool.error)
-        .bind(tool.command)
-        .bind(serialize_json(&tool.args)?)
-        .bind(serialize_json(&tool.env)?)
-        .bind(tool.config_json)
-        .bind(tool.config_hash)
-        .bind(tool.pending_config_json)
-        .bind(tool.pending_config_hash)
-        .bind(tool.conflict_status.as_str())
-        .bind(if tool.is_read_only { 1 } else { 0 })
-        .bind(if tool.is_new { 1 } else { 0 })
-        .bind(&now)
-        .bind(id)
-        .execute(&self.pool)
-        .await
-        .map_err(|err| McpError::Storage(err.to_string()))?;
-        Ok(())
+        Ok(id)
     }
 
     pub async fn list_local_assistants(&self) -> Result<Vec<LocalAssistant>, McpError> {
-        let rows = sqlx::query(
+        let rows = sqlx::query_as!(
+            AssistantRow,
             r#"
-            SELECT id, name, description, avatar, system_prompt, model_config, tags,
-                   visibility, source, cloud_id, is_deleted, created_at, updated_at
+            SELECT id AS "id!", name, description, avatar, system_prompt, model_config, tags,
+                   visibility, source, cloud_id, cloud_updated_at, cloud_synced_at, is_deleted,
+                   created_at, updated_at
             FROM assistants
             WHERE is_deleted = 0
             ORDER BY updated_at DESC;
@@ -831,35 +1243,30 @@ impl McpStore {
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        let mut assistants = Vec::with_capacity(rows.len());
-        for row in rows {
-            assistants.push(row_to_assistant(&row)?);
-        }
-        Ok(assistants)
+        rows.into_iter().map(LocalAssistant::try_from).collect()
     }
 
     pub async fn get_local_assistant(
         &self,
         id: &str,
     ) -> Result<Option<LocalAssistant>, McpError> {
-        let row = sqlx::query(
+        let row = sqlx::query_as!(
+            AssistantRow,
             r#"
-            SELECT id, name, description, avatar, system_prompt, model_config, tags,
-                   visibility, source, cloud_id, is_deleted, created_at, updated_at
+            SELECT id AS "id!", name, description, avatar, system_prompt, model_config, tags,
+                   visibility, source, cloud_id, cloud_updated_at, cloud_synced_at, is_deleted,
+                   created_at, updated_at
             FROM assistants
             WHERE id = ?
             LIMIT 1;
             "#,
+            id,
         )
-        .bind(id)
         .fetch_optional(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        match row {
-            Some(row) => Ok(Some(row_to_assistant(&row)?)),
-            None => Ok(None),
-        }
+        row.map(LocalAssistant::try_from).transpose()
     }
 
     pub async fn create_local_assistant(
@@ -885,27 +1292,27 @@ impl McpStore {
         let tags_json = serialize_json(&Some(tags))?;
         let model_config_json = serialize_json(&payload.model_config)?;
 
-        sqlx::query(
+        sqlx::query!(
             r#"
             INSERT INTO assistants
               (id, name, description, avatar, system_prompt, model_config, tags, visibility, source,
                cloud_id, is_deleted, created_at, updated_at)
             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
             "#,
+            &id,
+            &name,
+            payload.description,
+            payload.avatar,
+            &system_prompt,
+            model_config_json,
+            tags_json,
+            visibility,
+            source,
+            payload.cloud_id,
+            0,
+            &now,
+            &now,
         )
-        .bind(&id)
-        .bind(&name)
-        .bind(payload.description)
-        .bind(payload.avatar)
-        .bind(&system_prompt)
-        .bind(model_config_json)
-        .bind(tags_json)
-        .bind(visibility)
-        .bind(source)
-        .bind(payload.cloud_id)
-        .bind(0)
-        .bind(&now)
-        .bind(&now)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
@@ -961,25 +1368,25 @@ impl McpStore {
         let tags_json = serialize_json(&Some(tags))?;
         let model_config_json = serialize_json(&model_config)?;
 
-        sqlx::query(
+        sqlx::query!(
             r#"
             UPDATE assistants
             SET name = ?, description = ?, avatar = ?, system_prompt = ?, model_config = ?,
                 tags = ?, visibility = ?, source = ?, cloud_id = ?, updated_at = ?
             WHERE id = ?;
             "#,
+            name,
+            description,
+            avatar,
+            system_prompt,
+            model_config_json,
+            tags_json,
+            visibility,
+            source,
+            cloud_id,
+            &now,
+            id,
         )
-        .bind(name)
-        .bind(description)
-        .bind(avatar)
-        .bind(system_prompt)
-        .bind(model_config_json)
-        .bind(tags_json)
-        .bind(visibility)
-        .bind(source)
-        .bind(cloud_id)
-        .bind(&now)
-        .bind(id)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
@@ -991,15 +1398,15 @@ impl McpStore {
 
     pub async fn delete_local_assistant(&self, id: &str) -> Result<(), McpError> {
         let now = now_rfc3339()?;
-        let result = sqlx::query(
+        let result = sqlx::query!(
             r#"
             UPDATE assistants
             SET is_deleted = 1, updated_at = ?
             WHERE id = ?;
             "#,
+            &now,
+            id,
         )
-        .bind(&now)
-        .bind(id)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
@@ -1011,28 +1418,146 @@ impl McpStore {
         Ok(())
     }
 
+    /// Lists every assistant linked to a cloud record, including soft-deleted
+    /// rows whose deletion still has to be pushed.
+    pub async fn list_cloud_linked_assistants(&self) -> Result<Vec<LocalAssistant>, McpError> {
+        let rows = sqlx::query_as!(
+            AssistantRow,
+            r#"
+            SELECT id AS "id!", name, description, avatar, system_prompt, model_config, tags,
+                   visibility, source, cloud_id, cloud_updated_at, cloud_synced_at, is_deleted,
+                   created_at, updated_at
+            FROM assistants
+            WHERE cloud_id IS NOT NULL
+            ORDER BY updated_at DESC;
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+
+        rows.into_iter().map(LocalAssistant::try_from).collect()
+    }
+
+    /// Writes a cloud assistant into the local table, inserting when `id` is
+    /// `None`, and stamps it as in sync with `cloud_updated_at`.
+    pub async fn save_cloud_assistant(
+        &self,
+        id: Option<&str>,
+        assistant: CloudAssistantFields,
+    ) -> Result<String, McpError> {
+        let now = now_rfc3339()?;
+        let tags_json = serialize_json(&Some(assistant.tags))?;
+        let model_config_json = serialize_json(&assistant.model_config)?;
+        let id = match id {
+            Some(id) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE assistants
+                    SET name = ?, description = ?, avatar = ?, system_prompt = ?, model_config = ?,
+                        tags = ?, visibility = ?, cloud_id = ?, cloud_updated_at = ?,
+                        cloud_synced_at = ?, is_deleted = 0, updated_at = ?
+                    WHERE id = ?;
+                    "#,
+                    &assistant.name,
+                    &assistant.description,
+                    &assistant.avatar,
+                    &assistant.system_prompt,
+                    model_config_json,
+                    tags_json,
+                    &assistant.visibility,
+                    &assistant.cloud_id,
+                    &assistant.cloud_updated_at,
+                    &now,
+                    &now,
+                    id,
+                )
+                .execute(&self.pool)
+                .await
+                .map_err(|err| McpError::Storage(err.to_string()))?;
+                id.to_string()
+            }
+            None => {
+                let id = Uuid::new_v4().to_string();
+                sqlx::query!(
+                    r#"
+                    INSERT INTO assistants
+                      (id, name, description, avatar, system_prompt, model_config, tags, visibility,
+                       source, cloud_id, cloud_updated_at, cloud_synced_at, is_deleted, created_at,
+                       updated_at)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+                    "#,
+                    &id,
+                    &assistant.name,
+                    &assistant.description,
+                    &assistant.avatar,
+                    &assistant.system_prompt,
+                    model_config_json,
+                    tags_json,
+                    &assistant.visibility,
+                    "cloud",
+                    &assistant.cloud_id,
+                    &assistant.cloud_updated_at,
+                    &now,
+                    0,
+                    &now,
+                    &now,
+                )
+                .execute(&self.pool)
+                .await
+                .map_err(|err| McpError::Storage(err.to_string()))?;
+                id
+            }
+        };
+        Ok(id)
+    }
+
+    /// Records that a local assistant was pushed: links it to `cloud_id` and
+    /// marks the current local state as synced.
+    pub async fn mark_assistant_synced(
+        &self,
+        id: &str,
+        cloud_id: Option<&str>,
+        cloud_updated_at: Option<&str>,
+    ) -> Result<(), McpError> {
+        let now = now_rfc3339()?;
+        sqlx::query!(
+            r#"
+            UPDATE assistants
+            SET cloud_id = ?, cloud_updated_at = ?, cloud_synced_at = ?, updated_at = ?
+            WHERE id = ?;
+            "#,
+            cloud_id,
+            cloud_updated_at,
+            &now,
+            &now,
+            id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+        Ok(())
+    }
+
     pub async fn list_assistant_messages(
         &self,
         assistant_id: &str,
     ) -> Result<Vec<LocalAssistantMessage>, McpError> {
-        let rows = sqlx::query(
+        let rows = sqlx::query_as!(
+            AssistantMessageRow,
             r#"
-            SELECT id, assistant_id, role, content, is_deleted, created_at, updated_at
+            SELECT id AS "id!", assistant_id, role, content, is_deleted, created_at, updated_at
             FROM assistant_messages
             WHERE assistant_id = ? AND is_deleted = 0
             ORDER BY created_at ASC;
             "#,
+            assistant_id,
         )
-        .bind(assistant_id)
-        .fetch_all(&self.pool)
+        .fetch_all(&self.read_pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
 
-        let mut messages = Vec::with_capacity(rows.len());
-        for row in rows {
-            messages.push(row_to_assistant_message(&row)?);
-        }
-        Ok(messages)
+        Ok(rows.into_iter().map(LocalAssistantMessage::from).collect())
     }
 
     pub async fn append_assistant_message(
@@ -1054,20 +1579,20 @@ impl McpStore {
         let id = Uuid::new_v4().to_string();
         let now = now_rfc3339()?;
 
-        sqlx::query(
+        sqlx::query!(
             r#"
             INSERT INTO assistant_messages
               (id, assistant_id, role, content, is_deleted, created_at, updated_at)
             VALUES (?, ?, ?, ?, ?, ?, ?);
             "#,
+            &id,
+            &payload.assistant_id,
+            role,
+            &content,
+            0,
+            &now,
+            &now,
         )
-        .bind(&id)
-        .bind(&payload.assistant_id)
-        .bind(role)
-        .bind(&content)
-        .bind(0)
-        .bind(&now)
-        .bind(&now)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
@@ -1085,21 +1610,397 @@ impl McpStore {
 
     pub async fn delete_assistant_messages(&self, assistant_id: &str) -> Result<(), McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        sqlx::query!(
             r#"
             UPDATE assistant_messages
             SET is_deleted = 1, updated_at = ?
             WHERE assistant_id = ?;
             "#,
+            &now,
+            assistant_id,
         )
-        .bind(&now)
-        .bind(assistant_id)
         .execute(&self.pool)
         .await
         .map_err(|err| McpError::Storage(err.to_string()))?;
         Ok(())
     }
 
+    /// Appends tool log lines in one transaction.
+    pub async fn append_logs(&self, entries: &[(String, McpLogEntry)]) -> Result<(), McpError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        for (tool_id, entry) in entries {
+            sqlx::query!(
+                r#"
+                INSERT INTO mcp_logs (id, tool_id, timestamp, stream, message, level)
+                VALUES (?, ?, ?, ?, ?, ?);
+                "#,
+                entry.seq as i64,
+                tool_id,
+                &entry.timestamp,
+                entry.stream.as_str(),
+                &entry.message,
+                entry.level.as_str(),
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))
+    }
+
+    /// The newest `limit` stored log lines of a tool (all when `None`) with a
+    /// `seq` below `before`, oldest first.
+    pub async fn list_logs(
+        &self,
+        tool_id: &str,
+        limit: Option<usize>,
+        before: Option<u64>,
+    ) -> Result<Vec<McpLogEntry>, McpError> {
+        // SQLite treats a negative LIMIT as no limit.
+        let limit = limit.map_or(-1, |limit| limit as i64);
+        let before = before.map_or(i64::MAX, |before| before as i64);
+        let rows = sqlx::query_as!(
+            LogRow,
+            r#"
+            SELECT id AS "id!", timestamp, stream, message, level FROM (
+              SELECT id, timestamp, stream, message, level
+              FROM mcp_logs
+              WHERE tool_id = ? AND id < ?
+              ORDER BY id DESC
+              LIMIT ?
+            )
+            ORDER BY id ASC;
+            "#,
+            tool_id,
+            before,
+            limit,
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+
+        rows.into_iter().map(McpLogEntry::try_from).collect()
+    }
+
+    /// Stored lines of every tool containing `contains` (case-insensitive)
+    /// at `level` or above, logged at or after `since` (RFC 3339); the
+    /// newest `limit`, newest first.
+    pub async fn search_logs(
+        &self,
+        contains: &str,
+        level: McpLogLevel,
+        since: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<(String, McpLogEntry)>, McpError> {
+        let pattern = format!(
+            "%{}%",
+            contains
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let rows = sqlx::query_as!(
+            ToolLogRow,
+            r#"
+            SELECT id AS "id!", tool_id, timestamp, stream, message, level
+            FROM mcp_logs
+            WHERE message LIKE ? ESCAPE '\'
+              AND CASE level WHEN 'error' THEN 2 WHEN 'warn' THEN 1 ELSE 0 END >= ?
+              AND (? IS NULL OR timestamp >= ?)
+            ORDER BY id DESC
+            LIMIT ?;
+            "#,
+            pattern,
+            level as i64,
+            since,
+            since,
+            limit as i64,
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+
+        rows.into_iter()
+            .map(|row| {
+                let entry = LogRow {
+                    id: row.id,
+                    timestamp: row.timestamp,
+                    stream: row.stream,
+                    message: row.message,
+                    level: row.level,
+                };
+                Ok((row.tool_id, McpLogEntry::try_from(entry)?))
+            })
+            .collect()
+    }
+
+    /// Up to `lines` stored lines of the tool right after `seq`, oldest first.
+    pub async fn list_logs_after(
+        &self,
+        tool_id: &str,
+        seq: u64,
+        lines: usize,
+    ) -> Result<Vec<McpLogEntry>, McpError> {
+        let rows = sqlx::query_as!(
+            LogRow,
+            r#"
+            SELECT id AS "id!", timestamp, stream, message, level
+            FROM mcp_logs
+            WHERE tool_id = ? AND id > ?
+            ORDER BY id ASC
+            LIMIT ?;
+            "#,
+            tool_id,
+            seq as i64,
+            lines as i64,
+        )
+        .fetch_all(&self.read_pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+
+        rows.into_iter().map(McpLogEntry::try_from).collect()
+    }
+
+    /// The highest `seq` stored, so numbering continues across app runs.
+    pub async fn max_log_seq(&self) -> Result<u64, McpError> {
+        let max: Option<i64> = sqlx::query_scalar!("SELECT MAX(id) FROM mcp_logs;")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        Ok(max.unwrap_or(0) as u64)
+    }
+
+    pub async fn clear_logs(&self, tool_id: &str) -> Result<(), McpError> {
+        sqlx::query!("DELETE FROM mcp_logs WHERE tool_id = ?;", tool_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Deletes log lines written before `older_than` (an RFC 3339 timestamp),
+    /// then all but the newest `max_per_tool` lines of each tool. Returns the
+    /// number of lines deleted.
+    pub async fn prune_logs(
+        &self,
+        older_than: Option<&str>,
+        max_per_tool: usize,
+    ) -> Result<u64, McpError> {
+        let mut deleted = 0;
+        if let Some(older_than) = older_than {
+            deleted += sqlx::query!("DELETE FROM mcp_logs WHERE timestamp < ?;", older_than)
+                .execute(&self.pool)
+                .await
+                .map_err(|err| McpError::Storage(err.to_string()))?
+                .rows_affected();
+        }
+        deleted += sqlx::query!(
+            r#"
+            DELETE FROM mcp_logs WHERE id IN (
+              SELECT id FROM (
+                SELECT id, ROW_NUMBER() OVER (PARTITION BY tool_id ORDER BY id DESC) AS row_num
+                FROM mcp_logs
+              )
+              WHERE row_num > ?
+            );
+            "#,
+            max_per_tool as i64,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?
+        .rows_affected();
+        Ok(deleted)
+    }
+
+    /// Reads a JSON setting, returning `T::default()` when it was never written.
+    pub async fn get_setting<T>(&self, key: &str) -> Result<T, McpError>
+    where
+        T: serde::de::DeserializeOwned + Default,
+    {
+        let raw = sqlx::query_scalar!("SELECT value FROM app_settings WHERE key = ? LIMIT 1;", key)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        match raw {
+            Some(raw) => {
+                serde_json::from_str(&raw).map_err(|err| McpError::Storage(err.to_string()))
+            }
+            None => Ok(T::default()),
+        }
+    }
+
+    pub async fn set_setting<T>(&self, key: &str, value: &T) -> Result<(), McpError>
+    where
+        T: serde::Serialize,
+    {
+        let now = now_rfc3339()?;
+        let raw = serde_json::to_string(value).map_err(|err| McpError::Storage(err.to_string()))?;
+        sqlx::query!(
+            r#"
+            INSERT INTO app_settings (key, value, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at;
+            "#,
+            key,
+            raw,
+            &now,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+        Ok(())
+    }
+
+    /// SQLite version plus the DDL of every table and index, for diagnostics.
+    pub async fn schema_info(&self) -> Result<SchemaInfo, McpError> {
+        let sqlite_version: String = sqlx::query_scalar("SELECT sqlite_version();")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        let user_version: i64 = sqlx::query_scalar("PRAGMA user_version;")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        let statements: Vec<String> = sqlx::query_scalar(
+            "SELECT sql FROM sqlite_master WHERE sql IS NOT NULL ORDER BY type DESC, name;",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| McpError::Storage(err.to_string()))?;
+        Ok(SchemaInfo {
+            sqlite_version,
+            user_version,
+            statements,
+        })
+    }
+
+    /// Every row of `table` as a JSON object keyed by column.
+    pub async fn dump_table(&self, table: &str) -> Result<Vec<serde_json::Value>, McpError> {
+        let pairs: Vec<String> = self
+            .table_columns(table)
+            .await?
+            .iter()
+            .map(|column| format!("'{column}', \"{column}\""))
+            .collect();
+        let sql = format!("SELECT json_object({}) FROM {};", pairs.join(", "), table);
+        let rows: Vec<String> = sqlx::query_scalar(&sql)
+            .fetch_all(&self.read_pool)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        rows.iter()
+            .map(|row| serde_json::from_str(row).map_err(|err| McpError::Storage(err.to_string())))
+            .collect()
+    }
+
+    /// Writes rows read by `dump_table` back in one transaction, updating
+    /// rows with the same primary key. A row that clashes with a different
+    /// row on another unique index fails the restore instead of replacing
+    /// that row, so callers leave such rows out. Only columns both the rows
+    /// and the current schema have are written, so dumps from other versions
+    /// restore as far as they overlap.
+    pub async fn restore_tables(
+        &self,
+        tables: &[(&str, Vec<serde_json::Value>)],
+    ) -> Result<(), McpError> {
+        let mut statements = Vec::new();
+        for (table, rows) in tables {
+            let Some(first) = rows.first().and_then(serde_json::Value::as_object) else {
+                continue;
+            };
+            let columns: Vec<String> = self
+                .table_columns(table)
+                .await?
+                .into_iter()
+                .filter(|column| first.contains_key(column))
+                .collect();
+            let key = self.primary_key(table).await?;
+            let names: Vec<String> = columns
+                .iter()
+                .map(|column| format!("\"{column}\""))
+                .collect();
+            let values: Vec<String> = columns
+                .iter()
+                .map(|column| format!("json_extract(row.value, '$.\"{column}\"')"))
+                .collect();
+            let updates: Vec<String> = columns
+                .iter()
+                .filter(|column| !key.contains(column))
+                .map(|column| format!("\"{column}\" = excluded.\"{column}\""))
+                .collect();
+            let on_conflict = if updates.is_empty() {
+                "DO NOTHING".to_string()
+            } else {
+                format!("DO UPDATE SET {}", updates.join(", "))
+            };
+            // `WHERE true` keeps SQLite from reading `ON` as a join clause.
+            let sql = format!(
+                "INSERT INTO {} ({}) SELECT {} FROM json_each(?) AS row WHERE true \
+                 ON CONFLICT({}) {};",
+                table,
+                names.join(", "),
+                values.join(", "),
+                key.join(", "),
+                on_conflict
+            );
+            let rows = serde_json::Value::Array(rows.clone()).to_string();
+            statements.push((sql, rows));
+        }
+
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        for (sql, rows) in statements {
+            sqlx::query(&sql)
+                .bind(rows)
+                .execute(&mut *tx)
+                .await
+                .map_err(|err| McpError::Storage(err.to_string()))?;
+        }
+        tx.commit()
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        self.tool_cache.invalidate();
+        self.applied_configs
+            .lock()
+            .expect("applied configs poisoned")
+            .clear();
+        Ok(())
+    }
+
+    async fn table_columns(&self, table: &str) -> Result<Vec<String>, McpError> {
+        let sql = format!("PRAGMA table_info({})", table);
+        let rows = sqlx::query(&sql)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        Ok(rows
+            .iter()
+            .filter_map(|row| row.try_get::<String, _>("name").ok())
+            .collect())
+    }
+
+    async fn primary_key(&self, table: &str) -> Result<Vec<String>, McpError> {
+        let sql = format!("PRAGMA table_info({})", table);
+        let rows = sqlx::query(&sql)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| McpError::Storage(err.to_string()))?;
+        Ok(rows
+            .iter()
+            .filter(|row| row.try_get::<i64, _>("pk").is_ok_and(|pk| pk > 0))
+            .filter_map(|row| row.try_get::<String, _>("name").ok())
+            .collect())
+    }
+
     async fn ensure_column(&self, table: &str, column: &str, ddl: &str) -> Result<(), McpError> {
         let sql = format!("PRAGMA table_info({})", table);
         let rows = sqlx::query(&sql)
@@ -1156,6 +2057,42 @@ pub struct ToolUpsert {
     pub is_new: bool,
 }
 
+pub struct CloudAssistantFields {
+    pub name: String,
+    pub description: Option<String>,
+    pub avatar: Option<String>,
+    pub system_prompt: String,
+    pub model_config: Option<serde_json::Value>,
+    pub tags: Vec<String>,
+    pub visibility: String,
+    pub cloud_id: String,
+    pub cloud_updated_at: String,
+}
+
+/// A source's stored tools as a sync sees them; see `source_snapshot`.
+pub struct SourceSnapshot {
+    /// The source's tools by name.
+    pub tools: HashMap<String, McpTool>,
+    /// Names held by local tools of other sources; a synced tool with one of
+    /// them is in conflict.
+    pub local_names: HashSet<String>,
+}
+
+/// One server's outcome in `apply_sync_writes`.
+pub enum SyncWrite {
+    /// The stored tool already matches; nothing is written.
+    Keep(String),
+    /// Stages a config as the tool's pending update.
+    Pending {
+        id: String,
+        config_json: String,
+        config_hash: String,
+        conflict_status: McpConflictStatus,
+    },
+    /// Updates the tool with `id`, or inserts a new one.
+    Upsert(Box<ToolUpsert>),
+}
+
 pub struct ExtractedToolFields {
     pub name: String,
     pub description: String,
@@ -1165,87 +2102,341 @@ pub struct ExtractedToolFields {
     pub capabilities: Vec<String>,
 }
 
-fn row_to_source(row: &SqliteRow) -> Result<McpSource, McpError> {
-    let source_type: String = row.try_get("source_type")?;
-    let trust_level: String = row.try_get("trust_level")?;
-    let status: String = row.try_get("status")?;
-    Ok(McpSource {
-        id: row.try_get("id")?,
-        name: row.try_get("name")?,
-        source_type: source_type.parse().map_err(McpError::validation)?,
-        path_or_url: row.try_get("path_or_url")?,
-        trust_level: trust_level.parse().map_err(McpError::validation)?,
-        status: status.parse().map_err(McpError::validation)?,
-        last_synced_at: row.try_get("last_synced_at")?,
-        is_read_only: row.try_get::<i64, _>("is_read_only")? != 0,
-        created_at: row.try_get("created_at")?,
-        updated_at: row.try_get("updated_at")?,
-    })
+/// An `mcp_sources` row as `query_as!` reads it.
+struct SourceRow {
+    id: String,
+    name: String,
+    source_type: String,
+    path_or_url: String,
+    trust_level: String,
+    status: String,
+    last_synced_at: Option<String>,
+    is_read_only: i64,
+    created_at: String,
+    updated_at: String,
 }
 
-fn row_to_tool(row: &SqliteRow) -> Result<McpTool, McpError> {
-    let source_type: String = row.try_get("source_type")?;
-    let status: String = row.try_get("status")?;
-    let conflict_status: String = row.try_get("conflict_status")?;
-    let capabilities: String = row.try_get("capabilities")?;
-    let args: Option<String> = row.try_get("args")?;
-    let env: Option<String> = row.try_get("env")?;
-    Ok(McpTool {
-        id: row.try_get("id")?,
-        identifier: row.try_get("identifier")?,
-        name: row.try_get("name")?,
-        source_type: source_type.parse().map_err(McpError::validation)?,
-        source_id: row.try_get("source_id")?,
-        status: status.parse().map_err(McpError::validation)?,
-        ping_ms: row.try_get("ping_ms")?,
-        capabilities: serde_json::from_str(&capabilities)?,
-        description: row.try_get("description")?,
-        error: row.try_get("error")?,
-        command: row.try_get("command")?,
-        args: deserialize_json(args)?,
-        env: deserialize_json(env)?,
-        config_json: row.try_get("config_json")?,
-        pending_config_json: row.try_get("pending_config_json")?,
-        config_hash: row.try_get("config_hash")?,
-        pending_config_hash: row.try_get("pending_config_hash")?,
-        conflict_status: conflict_status.parse().map_err(McpError::validation)?,
-        is_read_only: row.try_get::<i64, _>("is_read_only")? != 0,
-        is_new: row.try_get::<i64, _>("is_new")? != 0,
-        created_at: row.try_get("created_at")?,
-        updated_at: row.try_get("updated_at")?,
-    })
+impl TryFrom<SourceRow> for McpSource {
+    type Error = McpError;
+
+    fn try_from(row: SourceRow) -> Result<Self, McpError> {
+        Ok(McpSource {
+            id: row.id,
+            name: row.name,
+            source_type: row.source_type.parse().map_err(McpError::validation)?,
+            path_or_url: row.path_or_url,
+            trust_level: row.trust_level.parse().map_err(McpError::validation)?,
+            status: row.status.parse().map_err(McpError::validation)?,
+            last_synced_at: row.last_synced_at,
+            is_read_only: row.is_read_only != 0,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
 }
 
-fn row_to_assistant(row: &SqliteRow) -> Result<LocalAssistant, McpError> {
-    let tags: Option<Vec<String>> = deserialize_json(row.try_get("tags")?)?;
-    let model_config: Option<serde_json::Value> = deserialize_json(row.try_get("model_config")?)?;
-    Ok(LocalAssistant {
-        id: row.try_get("id")?,
-        name: row.try_get("name")?,
-        description: row.try_get("description")?,
-        avatar: row.try_get("avatar")?,
-        system_prompt: row.try_get("system_prompt")?,
-        model_config,
-        tags: tags.unwrap_or_default(),
-        visibility: row.try_get("visibility")?,
-        source: row.try_get("source")?,
-        cloud_id: row.try_get("cloud_id")?,
-        is_deleted: row.try_get::<i64, _>("is_deleted")? != 0,
-        created_at: row.try_get("created_at")?,
-        updated_at: row.try_get("updated_at")?,
-    })
+struct LogRow {
+    id: i64,
+    timestamp: String,
+    stream: String,
+    message: String,
+    level: String,
 }
 
-fn row_to_assistant_message(row: &SqliteRow) -> Result<LocalAssistantMessage, McpError> {
-    Ok(LocalAssistantMessage {
-        id: row.try_get("id")?,
-        assistant_id: row.try_get("assistant_id")?,
-        role: row.try_get("role")?,
-        content: row.try_get("content")?,
-        is_deleted: row.try_get::<i64, _>("is_deleted")? != 0,
-        created_at: row.try_get("created_at")?,
-        updated_at: row.try_get("updated_at")?,
-    })
+/// A `search_logs` hit, which spans tools.
+struct ToolLogRow {
+    id: i64,
+    tool_id: String,
+    timestamp: String,
+    stream: String,
+    message: String,
+    level: String,
+}
+
+impl TryFrom<LogRow> for McpLogEntry {
+    type Error = McpError;
+
+    fn try_from(row: LogRow) -> Result<Self, McpError> {
+        let stream: McpLogStream = row.stream.parse().map_err(McpError::validation)?;
+        // Not stored; derived from the message like when it was captured.
+        let structured = match stream {
+            McpLogStream::Event => None,
+            _ => crate::mcp::json_log::detect(&row.message),
+        };
+        Ok(McpLogEntry {
+            seq: row.id as u64,
+            timestamp: row.timestamp,
+            stream,
+            message: row.message,
+            level: row.level.parse().map_err(McpError::validation)?,
+            structured,
+        })
+    }
+}
+
+async fn insert_tool(
+    conn: &mut SqliteConnection,
+    id: &str,
+    tool: ToolUpsert,
+    now: &str,
+) -> Result<(), McpError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO mcp_tools
+          (id, source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+           error, command, args, env, config_json, config_hash, pending_config_json,
+           pending_config_hash, conflict_status, is_read_only, is_new, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+        "#,
+        id,
+        &tool.source_id,
+        &tool.identifier,
+        &tool.name,
+        tool.source_type.as_str(),
+        tool.status.as_str(),
+        tool.ping_ms,
+        serde_json::to_string(&tool.capabilities)?,
+        &tool.description,
+        tool.error,
+        tool.command,
+        serialize_json(&tool.args)?,
+        serialize_json(&tool.env)?,
+        tool.config_json,
+        tool.config_hash,
+        tool.pending_config_json,
+        tool.pending_config_hash,
+        tool.conflict_status.as_str(),
+        if tool.is_read_only { 1 } else { 0 },
+        if tool.is_new { 1 } else { 0 },
+        now,
+        now,
+    )
+    .execute(conn)
+    .await
+    .map_err(|err| McpError::Storage(err.to_string()))?;
+    Ok(())
+}
+
+async fn update_tool(
+    conn: &mut SqliteConnection,
+    id: &str,
+    tool: ToolUpsert,
+    now: &str,
+) -> Result<(), McpError> {
+    let command = tool.command;
+    let args = serialize_json(&tool.args)?;
+    // SET expressions see the row before the update, so the CASE compares
+    // the approved command lines with the incoming ones.
+    sqlx::query!(
+        r#"
+        UPDATE mcp_tools
+        SET source_id = ?, identifier = ?, name = ?, source_type = ?, status = ?, ping_ms = ?,
+            capabilities = ?, description = ?, error = ?, command = ?, args = ?, env = ?,
+            config_json = ?, config_hash = ?, pending_config_json = ?, pending_config_hash = ?,
+            conflict_status = ?, is_read_only = ?, is_new = ?, updated_at = ?,
+            approved_at = CASE
+              WHEN command IS ? AND args IS ?
+                AND json_extract(config_json, '$.hooks') IS json_extract(?, '$.hooks')
+                AND json_extract(config_json, '$.healthcheck.command')
+                  IS json_extract(?, '$.healthcheck.command')
+              THEN approved_at
+              ELSE NULL
+            END
+        WHERE id = ?;
+        "#,
+        &tool.source_id,
+        &tool.identifier,
+        &tool.name,
+        tool.source_type.as_str(),
+        tool.status.as_str(),
+        tool.ping_ms,
+        serde_json::to_string(&tool.capabilities)?,
+        &tool.description,
+        tool.error,
+        &command,
+        &args,
+        serialize_json(&tool.env)?,
+        &tool.config_json,
+        tool.config_hash,
+        tool.pending_config_json,
+        tool.pending_config_hash,
+        tool.conflict_status.as_str(),
+        if tool.is_read_only { 1 } else { 0 },
+        if tool.is_new { 1 } else { 0 },
+        now,
+        &command,
+        &args,
+        &tool.config_json,
+        &tool.config_json,
+        id,
+    )
+    .execute(conn)
+    .await
+    .map_err(|err| McpError::Storage(err.to_string()))?;
+    Ok(())
+}
+
+async fn stage_pending_update(
+    conn: &mut SqliteConnection,
+    id: &str,
+    pending_config_json: String,
+    pending_config_hash: String,
+    conflict_status: McpConflictStatus,
+    now: &str,
+) -> Result<(), McpError> {
+    sqlx::query!(
+        r#"
+        UPDATE mcp_tools
+        SET pending_config_json = ?,
+            pending_config_hash = ?,
+            conflict_status = ?,
+            updated_at = ?
+        WHERE id = ?;
+        "#,
+        pending_config_json,
+        pending_config_hash,
+        conflict_status.as_str(),
+        now,
+        id,
+    )
+    .execute(conn)
+    .await
+    .map_err(|err| McpError::Storage(err.to_string()))?;
+    Ok(())
+}
+
+/// An `mcp_tools` row as `query_as!` reads it.
+struct ToolRow {
+    id: String,
+    source_id: String,
+    identifier: Option<String>,
+    name: String,
+    source_type: String,
+    status: String,
+    ping_ms: Option<i64>,
+    capabilities: String,
+    description: String,
+    error: Option<String>,
+    command: Option<String>,
+    args: Option<String>,
+    env: Option<String>,
+    config_json: String,
+    config_hash: String,
+    pending_config_json: Option<String>,
+    pending_config_hash: Option<String>,
+    conflict_status: String,
+    is_read_only: i64,
+    is_new: i64,
+    approved_at: Option<String>,
+    market_tool_id: Option<String>,
+    auto_start: i64,
+    log_to_file: i64,
+    log_buffer_size: Option<i64>,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<ToolRow> for McpTool {
+    type Error = McpError;
+
+    fn try_from(row: ToolRow) -> Result<Self, McpError> {
+        Ok(McpTool {
+            id: row.id,
+            identifier: row.identifier,
+            name: row.name,
+            source_type: row.source_type.parse().map_err(McpError::validation)?,
+            source_id: Some(row.source_id),
+            status: row.status.parse().map_err(McpError::validation)?,
+            ping_ms: row.ping_ms,
+            capabilities: serde_json::from_str(&row.capabilities)?,
+            description: row.description,
+            error: row.error,
+            command: row.command,
+            args: deserialize_json(row.args)?,
+            env: deserialize_json(row.env)?,
+            config_json: row.config_json,
+            pending_config_json: row.pending_config_json,
+            config_hash: row.config_hash,
+            pending_config_hash: row.pending_config_hash,
+            conflict_status: row.conflict_status.parse().map_err(McpError::validation)?,
+            is_read_only: row.is_read_only != 0,
+            is_new: row.is_new != 0,
+            approved_at: row.approved_at,
+            market_tool_id: row.market_tool_id,
+            auto_start: row.auto_start != 0,
+            log_to_file: row.log_to_file != 0,
+            log_buffer_size: row.log_buffer_size.map(|size| size.max(1) as usize),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+struct AssistantRow {
+    id: String,
+    name: String,
+    description: Option<String>,
+    avatar: Option<String>,
+    system_prompt: String,
+    model_config: Option<String>,
+    tags: Option<String>,
+    visibility: String,
+    source: String,
+    cloud_id: Option<String>,
+    cloud_updated_at: Option<String>,
+    cloud_synced_at: Option<String>,
+    is_deleted: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<AssistantRow> for LocalAssistant {
+    type Error = McpError;
+
+    fn try_from(row: AssistantRow) -> Result<Self, McpError> {
+        let tags: Option<Vec<String>> = deserialize_json(row.tags)?;
+        Ok(LocalAssistant {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            avatar: row.avatar,
+            system_prompt: row.system_prompt,
+            model_config: deserialize_json(row.model_config)?,
+            tags: tags.unwrap_or_default(),
+            visibility: row.visibility,
+            source: row.source,
+            cloud_id: row.cloud_id,
+            cloud_updated_at: row.cloud_updated_at,
+            cloud_synced_at: row.cloud_synced_at,
+            is_deleted: row.is_deleted != 0,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+struct AssistantMessageRow {
+    id: String,
+    assistant_id: String,
+    role: String,
+    content: String,
+    is_deleted: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+impl From<AssistantMessageRow> for LocalAssistantMessage {
+    fn from(row: AssistantMessageRow) -> Self {
+        LocalAssistantMessage {
+            id: row.id,
+            assistant_id: row.assistant_id,
+            role: row.role,
+            content: row.content,
+            is_deleted: row.is_deleted != 0,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
 }
 
 fn deserialize_json<T>(value: Option<String>) -> Result<Option<T>, McpError>
@@ -1273,19 +2464,3 @@ fn now_rfc3339() -> Result<String, McpError> {
         .format(&time::format_description::well_known::Rfc3339)
         .map_err(|err| McpError::Storage(err.to_string()))?)
 }
-
-fn hash_json(value: &serde_json::Value) -> String {
-    let raw = serde_json::to_string(value).unwrap_or_default();
-    let mut hasher = Sha256::new();
-    hasher.update(raw.as_bytes());
-    hex::encode(hasher.finalize())
-}
-
-pub fn expand_path(path: &str) -> PathBuf {
-    if let Some(stripped) = path.strip_prefix("~/") {
-        if let Ok(home) = std::env::var("HOME") {
-            return PathBuf::from(home).join(stripped);
-        }
-    }
-    PathBuf::from(path)
-}
@@ -0,0 +1,52 @@
+use crate::mcp::error::McpError;
+use crate::mcp::event_bus::{DomainEvent, EventBus};
+use crate::mcp::types::{SyncCounts, SyncStatusEvent};
+
+/// Publishes the phases of one source sync on the event bus, which emits
+/// them as `mcp-sync-status://{source_id}`.
+pub struct SyncReporter {
+    events: EventBus,
+    source_id: String,
+}
+
+impl SyncReporter {
+    pub fn new(events: &EventBus, source_id: &str) -> Self {
+        Self {
+            events: events.clone(),
+            source_id: source_id.to_string(),
+        }
+    }
+
+    pub fn started(&self) {
+        self.emit(SyncStatusEvent::Started {
+            source_id: self.source_id.clone(),
+        });
+    }
+
+    pub fn progress(&self, processed: usize, total: usize) {
+        self.emit(SyncStatusEvent::Progress {
+            source_id: self.source_id.clone(),
+            processed,
+            total,
+        });
+    }
+
+    pub fn finished(&self, counts: SyncCounts) {
+        self.emit(SyncStatusEvent::Finished {
+            source_id: self.source_id.clone(),
+            counts,
+        });
+    }
+
+    pub fn failed(&self, err: &McpError) {
+        self.emit(SyncStatusEvent::Failed {
+            source_id: self.source_id.clone(),
+            code: err.code(),
+            message: err.to_string(),
+        });
+    }
+
+    fn emit(&self, event: SyncStatusEvent) {
+        self.events.publish(DomainEvent::SourceSync(event));
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use log::warn;
+use serde::Serialize;
+
+use crate::mcp::error::McpError;
+use crate::mcp::types::{McpToolStatus, TelemetrySettings};
+use crate::mcp::{CloudAccount, McpRuntimeState};
+
+pub const TELEMETRY_SETTINGS_KEY: &str = "telemetry";
+const REPORT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// The cloud rejects larger batches.
+const MAX_REPORTS_PER_BATCH: usize = 100;
+
+/// One subscription's health since the previous report. Only the public
+/// marketplace id is sent; local names, commands and env never leave the
+/// machine.
+#[derive(Debug, Serialize)]
+struct ToolHealthReport {
+    market_tool_id: String,
+    status: McpToolStatus,
+    crash_count: u32,
+    ping_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+struct HealthReportBatch<'a> {
+    client_version: &'static str,
+    reports: &'a [ToolHealthReport],
+}
+
+/// Periodically posts health data for marketplace subscriptions when the user
+/// has opted in through the `telemetry` setting. Nothing is collected or sent
+/// while the opt-in is off.
+pub fn spawn_health_reporter(state: McpRuntimeState) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(REPORT_INTERVAL).await;
+            let settings = state
+                .store
+                .get_setting::<TelemetrySettings>(TELEMETRY_SETTINGS_KEY)
+                .await
+                .unwrap_or_default();
+            if !settings.health_reporting {
+                // Do not let counts pile up for a later opt-in.
+                state.process_manager.take_crash_counts().await;
+                continue;
+            }
+            if let Err(err) = report_health(&state).await {
+                warn!("tool health report failed: {}", err);
+            }
+        }
+    });
+}
+
+/// Sends each tool's report to the cloud account it was subscribed through;
+/// tools from other sources (e.g. published local tools) report to the
+/// primary account.
+async fn report_health(state: &McpRuntimeState) -> Result<(), McpError> {
+    let mut crash_counts = state.process_manager.take_crash_counts().await;
+    let tools = state.store.list_tools().await?;
+    let mut batches: HashMap<String, (CloudAccount, Vec<ToolHealthReport>, HashMap<String, u32>)> =
+        HashMap::new();
+    for tool in tools {
+        let Some(market_tool_id) = tool.market_tool_id else {
+            continue;
+        };
+        let account = match state.cloud_account(tool.source_id.as_deref()).await {
+            Ok(account) => account,
+            Err(_) => state.cloud_account(None).await?,
+        };
+        let crash_count = crash_counts.remove(&tool.id).unwrap_or(0);
+        let (_, reports, counts) = batches
+            .entry(account.source.id.clone())
+            .or_insert_with(|| (account, Vec::new(), HashMap::new()));
+        if crash_count > 0 {
+            counts.insert(tool.id, crash_count);
+        }
+        reports.push(ToolHealthReport {
+            market_tool_id,
+            status: tool.status,
+            crash_count,
+            ping_ms: tool.ping_ms,
+        });
+    }
+
+    let mut result = Ok(());
+    for (account, reports, counts) in batches.into_values() {
+        if let Err(err) = send_reports(state, &account, reports).await {
+            state.process_manager.restore_crash_counts(counts).await;
+            result = Err(err);
+        }
+    }
+    result
+}
+
+async fn send_reports(
+    state: &McpRuntimeState,
+    account: &CloudAccount,
+    reports: Vec<ToolHealthReport>,
+) -> Result<(), McpError> {
+    let url = account.url("/api/v1/mcp/telemetry/health");
+    for chunk in reports.chunks(MAX_REPORTS_PER_BATCH) {
+        let body = HealthReportBatch {
+            client_version: env!("CARGO_PKG_VERSION"),
+            reports: chunk,
+        };
+        // Sent without credentials so reports cannot be tied to an account.
+        let response = state
+            .client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|err| McpError::Network(err.to_string()))?;
+        if !response.status().is_success() {
+            return Err(McpError::Network(format!(
+                "health report failed: {}",
+                response.status()
+            )));
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,15 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
+pub use mcp_core::env::EnvRequirement;
+use mcp_core::health;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use ts_rs::TS;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
 #[serde(rename_all = "lowercase")]
+#[ts(export)]
 pub enum McpSourceType {
     Local,
     Cloud,
@@ -40,8 +45,9 @@ impl std::str::FromStr for McpSourceType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
 #[serde(rename_all = "lowercase")]
+#[ts(export)]
 pub enum McpSourceStatus {
     Active,
     Inactive,
@@ -74,8 +80,9 @@ impl std::str::FromStr for McpSourceStatus {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
 #[serde(rename_all = "lowercase")]
+#[ts(export)]
 pub enum McpTrustLevel {
     Official,
     Community,
@@ -105,8 +112,9 @@ impl std::str::FromStr for McpTrustLevel {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
 #[serde(rename_all = "lowercase")]
+#[ts(export)]
 pub enum McpToolStatus {
     Pending,
     Stopped,
@@ -154,8 +162,9 @@ impl std::str::FromStr for McpToolStatus {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export)]
 pub enum McpConflictStatus {
     None,
     UpdateAvailable,
@@ -185,7 +194,8 @@ impl std::str::FromStr for McpConflictStatus {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct McpSource {
     pub id: String,
     pub name: String,
@@ -199,7 +209,8 @@ pub struct McpSource {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct McpTool {
     pub id: String,
     pub identifier: Option<String>,
@@ -207,12 +218,14 @@ pub struct McpTool {
     pub source_type: McpSourceType,
     pub source_id: Option<String>,
     pub status: McpToolStatus,
+    #[ts(type = "number | null")]
     pub ping_ms: Option<i64>,
     pub capabilities: Vec<String>,
     pub description: String,
     pub error: Option<String>,
     pub command: Option<String>,
     pub args: Option<Vec<String>>,
+    #[ts(type = "Record<string, string> | null")]
     pub env: Option<HashMap<String, String>>,
     pub config_json: String,
     pub pending_config_json: Option<String>,
@@ -221,6 +234,14 @@ pub struct McpTool {
     pub conflict_status: McpConflictStatus,
     pub is_read_only: bool,
     pub is_new: bool,
+    pub approved_at: Option<String>,
+    pub market_tool_id: Option<String>,
+    /// Started whenever the app launches, e.g. at login.
+    pub auto_start: bool,
+    /// Also writes the tool's output to rotating files under `logs/`.
+    pub log_to_file: bool,
+    /// Log lines kept in memory for this tool instead of the app setting.
+    pub log_buffer_size: Option<usize>,
     pub created_at: String,
     pub updated_at: String,
 }
@@ -230,19 +251,109 @@ pub struct McpToolConfigPayload {
     pub command: Option<String>,
     pub args: Option<Vec<String>>,
     pub env: Option<HashMap<String, String>>,
+    /// Working directory; like `command`, `args` and `env` it may reference
+    /// `${VAR}`s, resolved at start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<ToolHooks>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<HealthCheck>,
     pub description: Option<String>,
     pub capabilities: Option<Vec<String>>,
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
+/// Shell commands run around a tool's process, in the tool's working
+/// directory with its env. Their output goes to the tool's log, and they may
+/// reference `${VAR}`s like the command.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolHooks {
+    /// Run in order before every start, e.g. `npm install`. The tool does
+    /// not start if one fails.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_start: Vec<String>,
+    /// Run in order once the process has exited, whether it was stopped or
+    /// crashed. Failures are logged and the remaining hooks still run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_stop: Vec<String>,
+}
+
+impl ToolHooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_start.is_empty() && self.post_stop.is_empty()
+    }
+}
+
+/// How the health monitor checks a tool that the MCP ping cannot reach.
+/// Exactly one of `command`, `tcp` or `http` is set; like the command they
+/// may reference `${VAR}`s. A failed probe marks the tool degraded, and
+/// `failureThreshold` failures in a row stop it as crashed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    /// Shell command run in the tool's working directory with its env;
+    /// passes when it exits 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// `host:port` that must accept a TCP connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<String>,
+    /// URL whose GET must answer with a 2xx status.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<String>,
+    /// Seconds between probes; 30 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    /// Seconds one probe may take; 5 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Failed probes in a row before the tool is stopped; 3 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_threshold: Option<u32>,
+}
+
+impl HealthCheck {
+    pub fn validate(&self) -> Result<(), String> {
+        let targets = [&self.command, &self.tcp, &self.http]
+            .iter()
+            .filter(|target| target.is_some())
+            .count();
+        if targets != 1 {
+            return Err("healthcheck needs exactly one of command, tcp or http".to_string());
+        }
+        if self.interval_secs == Some(0) || self.timeout_secs == Some(0) {
+            return Err("healthcheck intervalSecs and timeoutSecs must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval_secs
+            .map_or(health::DEFAULT_INTERVAL, Duration::from_secs)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout_secs
+            .map_or(health::DEFAULT_TIMEOUT, Duration::from_secs)
+    }
+
+    pub fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+            .unwrap_or(health::DEFAULT_FAILURE_THRESHOLD)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpConfigPayload {
     #[serde(rename = "mcpServers")]
     pub mcp_servers: HashMap<String, McpToolConfigPayload>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct CreateSourceRequest {
     pub name: String,
     pub source_type: McpSourceType,
@@ -262,6 +373,21 @@ pub struct SyncSourceRequest {
     pub auth_token: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestSourceRequest {
+    pub source_id: Option<String>,
+    pub source: Option<CreateSourceRequest>,
+    pub auth_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceTestResult {
+    pub ok: bool,
+    pub server_count: usize,
+    pub server_names: Vec<String>,
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateToolConfigRequest {
     pub apply_pending: bool,
@@ -273,20 +399,294 @@ pub struct ResolveConflictRequest {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishToolRequest {
+    pub identifier: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub avatar_url: Option<String>,
+    pub runtime: Option<String>,
+    /// Env keys whose current value is published as the default; all other
+    /// values stay local. Ignored for secret keys.
+    #[serde(default)]
+    pub default_env_keys: Vec<String>,
+}
+
+/// Payload of the `mcp-status://{tool_id}` event, emitted whenever a tool's
+/// status or conflict status changes so the UI does not have to poll.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct McpToolStatusEvent {
+    pub tool_id: String,
+    pub status: McpToolStatus,
+    pub conflict_status: McpConflictStatus,
+    pub error: Option<String>,
+}
+
+/// What a sync did to one tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolChange {
+    Added,
+    /// Rewritten, or given a pending update to review.
+    Updated,
+    Orphaned,
+    Unchanged,
+}
+
+/// Result of `sync_mcp_source` and `sync_cloud_subscriptions`: the ids a
+/// sync touched, by outcome, and the current rows of every changed tool, so
+/// the UI can patch its list instead of reloading every tool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SyncChanges {
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    /// Added or updated tools left with a pending update or a name conflict.
+    pub conflicted: Vec<String>,
+    /// Cloud tools whose subscription is gone.
+    pub orphaned: Vec<String>,
+    pub unchanged: Vec<String>,
+    pub tools: Vec<McpTool>,
+}
+
+impl SyncChanges {
+    pub fn record(&mut self, change: ToolChange, tool: McpTool) {
+        let ids = match change {
+            ToolChange::Unchanged => {
+                self.unchanged.push(tool.id);
+                return;
+            }
+            ToolChange::Orphaned => &mut self.orphaned,
+            _ if tool.conflict_status != McpConflictStatus::None => &mut self.conflicted,
+            ToolChange::Added => &mut self.added,
+            ToolChange::Updated => &mut self.updated,
+        };
+        ids.push(tool.id.clone());
+        self.tools.push(tool);
+    }
+
+    /// Tools of the source the sync saw, changed or not.
+    pub fn tool_count(&self) -> usize {
+        self.added.len()
+            + self.updated.len()
+            + self.conflicted.len()
+            + self.orphaned.len()
+            + self.unchanged.len()
+    }
+}
+
+/// How a source sync changed the source's tools.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export)]
+pub struct SyncCounts {
+    pub total: usize,
+    pub added: usize,
+    pub updated: usize,
+    /// Upstream changes staged for review on read-only sources.
+    pub pending: usize,
+    pub unchanged: usize,
+}
+
+/// Payload of the `mcp-sync-status://{source_id}` event, emitted as a source
+/// sync runs so the UI can report it without awaiting the command.
+#[derive(Debug, Clone, Serialize, TS)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+#[ts(export)]
+pub enum SyncStatusEvent {
+    Started {
+        source_id: String,
+    },
+    Progress {
+        source_id: String,
+        processed: usize,
+        total: usize,
+    },
+    Finished {
+        source_id: String,
+        counts: SyncCounts,
+    },
+    Failed {
+        source_id: String,
+        code: &'static str,
+        message: String,
+    },
+}
+
+impl From<&McpTool> for McpToolStatusEvent {
+    fn from(tool: &McpTool) -> Self {
+        Self {
+            tool_id: tool.id.clone(),
+            status: tool.status.clone(),
+            conflict_status: tool.conflict_status.clone(),
+            error: tool.error.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct McpLogEntry {
+    /// Increases with every line logged, across tools and app runs; the
+    /// `before` cursor of `get_mcp_logs`. `0` for lines that are only emitted.
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub seq: u64,
     pub timestamp: String,
     pub stream: McpLogStream,
     pub message: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+    /// Guessed from the line; see `log_level::infer`.
+    #[serde(default)]
+    pub level: McpLogLevel,
+    /// Fields read from a line that is a JSON log record.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub structured: Option<StructuredLog>,
+}
+
+/// `level`, `msg` and `time` of a JSON log line (pino, winston, ...).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct StructuredLog {
+    /// Lowercase name; numeric pino levels are translated (`50` is `error`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub msg: Option<String>,
+    /// RFC 3339, as logged by the tool rather than when it was captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub time: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, TS)]
 #[serde(rename_all = "lowercase")]
+#[ts(export)]
 pub enum McpLogStream {
     Stdout,
     Stderr,
     Event,
 }
 
+impl McpLogStream {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            McpLogStream::Stdout => "stdout",
+            McpLogStream::Stderr => "stderr",
+            McpLogStream::Event => "event",
+        }
+    }
+}
+
+impl std::str::FromStr for McpLogStream {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "stdout" => Ok(McpLogStream::Stdout),
+            "stderr" => Ok(McpLogStream::Stderr),
+            "event" => Ok(McpLogStream::Event),
+            _ => Err(format!("unknown log stream: {value}")),
+        }
+    }
+}
+
+/// Severity of a log line, ordered from `info` up to `error`.
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, TS,
+)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum McpLogLevel {
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+impl McpLogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            McpLogLevel::Info => "info",
+            McpLogLevel::Warn => "warn",
+            McpLogLevel::Error => "error",
+        }
+    }
+}
+
+impl std::str::FromStr for McpLogLevel {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "info" => Ok(McpLogLevel::Info),
+            "warn" => Ok(McpLogLevel::Warn),
+            "error" => Ok(McpLogLevel::Error),
+            _ => Err(format!("unknown log level: {value}")),
+        }
+    }
+}
+
+/// A log entry tagged with the tool that produced it, as emitted on the
+/// merged `mcp-log-all://` stream.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct McpToolLogEntry {
+    pub tool_id: String,
+    pub entry: McpLogEntry,
+}
+
+/// A stored line found by `search_mcp_logs`, with lines of the same tool
+/// around it.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LogSearchMatch {
+    pub tool_id: String,
+    pub entry: McpLogEntry,
+    /// Up to `context` lines logged just before `entry`, oldest first.
+    pub before: Vec<McpLogEntry>,
+    /// Up to `context` lines logged just after `entry`.
+    pub after: Vec<McpLogEntry>,
+}
+
+/// Filters `get_mcp_logs` applies before its `limit`; they combine.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFilterQuery {
+    pub stream: Option<McpLogStream>,
+    /// Lowest severity to include, e.g. `warn` for warnings and errors.
+    pub level: Option<McpLogLevel>,
+    /// Case-insensitive substring the message must contain.
+    pub contains: Option<String>,
+    /// Regular expression the message must match.
+    pub regex: Option<String>,
+    /// RFC 3339 timestamp; older lines are left out.
+    pub since: Option<String>,
+}
+
+/// File layout for `export_tool_logs`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum LogExportFormat {
+    /// `<timestamp> [<stream>] <message>` per line.
+    #[default]
+    Text,
+    /// One `McpLogEntry` JSON object per line.
+    Jsonl,
+}
+
+impl LogExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            LogExportFormat::Text => "log",
+            LogExportFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalAssistant {
     pub id: String,
@@ -299,6 +699,8 @@ pub struct LocalAssistant {
     pub visibility: String,
     pub source: String,
     pub cloud_id: Option<String>,
+    pub cloud_updated_at: Option<String>,
+    pub cloud_synced_at: Option<String>,
     pub is_deleted: bool,
     pub created_at: String,
     pub updated_at: String,
@@ -330,6 +732,227 @@ pub struct UpdateLocalAssistantRequest {
     pub cloud_id: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloudSyncSettings {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+    /// Uploads local assistants on each scheduled sync; off until the user
+    /// opts in, since signing in alone should not share them.
+    pub include_assistants: bool,
+}
+
+impl Default for CloudSyncSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: 15,
+            include_assistants: false,
+        }
+    }
+}
+
+/// Outcome of one source sync: the latest subscription sync of a cloud
+/// source, scheduled or manual, or one entry of `sync_all_sources`.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SourceSyncResult {
+    pub source_id: String,
+    pub source_name: String,
+    pub finished_at: String,
+    pub success: bool,
+    pub tool_count: Option<usize>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SyncSchedulerStatus {
+    pub enabled: bool,
+    #[ts(type = "number")]
+    pub interval_minutes: u64,
+    pub running: bool,
+    pub last_run_at: Option<String>,
+    /// `None` while the scheduler is paused.
+    pub next_run_at: Option<String>,
+    pub sources: Vec<SourceSyncResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudAccountStatus {
+    pub source: McpSource,
+    pub base_url: String,
+    pub is_primary: bool,
+    pub has_session: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudMarketTool {
+    pub id: String,
+    pub identifier: String,
+    pub name: String,
+    pub description: String,
+    pub avatar_url: Option<String>,
+    pub category: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub author: String,
+    pub is_official: bool,
+    pub download_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudMarketToolPage {
+    pub items: Vec<CloudMarketTool>,
+    pub page: usize,
+    pub page_size: usize,
+    pub total: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetrySettings {
+    pub health_reporting: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaInfo {
+    pub sqlite_version: String,
+    /// `PRAGMA user_version`; columns are added in place, so the DDL below is
+    /// the authoritative schema.
+    pub user_version: i64,
+    pub statements: Vec<String>,
+}
+
+/// Rows `import_app_state` restored, per kind.
+#[derive(Debug, Clone, Serialize)]
+pub struct AppStateImportSummary {
+    pub sources: usize,
+    pub tools: usize,
+    pub assistants: usize,
+    pub messages: usize,
+    pub settings: usize,
+    /// False when secret env values were blanked on export.
+    pub secrets_included: bool,
+    /// Names of archived tools that were not restored: those of the exported
+    /// cloud source, which the next cloud sync brings back, and those whose
+    /// name or identifier another tool of their source already has.
+    pub skipped_tools: Vec<String>,
+}
+
+/// General preferences stored under the `app` setting.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Log lines kept in memory per tool.
+    pub log_buffer_size: usize,
+    /// Memory all tools' in-memory log lines share, in MiB.
+    pub log_memory_mb: usize,
+    /// Days stored log lines are kept; `0` keeps them regardless of age.
+    pub log_retention_days: u32,
+    /// Stored log lines kept per tool, newest first.
+    pub log_retention_entries: usize,
+    /// Size at which a tool's `current.log` is rotated.
+    pub log_file_max_bytes: u64,
+    /// Rotated log files kept per tool besides `current.log`.
+    pub log_file_keep: usize,
+    /// How escape sequences in tool output are handled before buffering.
+    pub log_ansi: mcp_core::ansi::AnsiMode,
+    /// Window in which identical consecutive lines of a tool are coalesced
+    /// into one plus a repeat count; `0` logs every line.
+    pub log_dedup_window_ms: u64,
+    /// Refuse to start a tool whose command, args or env reference an unset
+    /// `${VAR}`; otherwise the reference is passed through as written.
+    pub env_strict: bool,
+    /// Used by local chat when a request leaves `base_url` or `model` empty.
+    pub default_provider: Option<ChatProviderSettings>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            log_buffer_size: crate::mcp::process::DEFAULT_LOG_BUFFER_SIZE,
+            log_memory_mb: crate::mcp::process::DEFAULT_LOG_MEMORY_MB,
+            log_retention_days: crate::mcp::log_store::DEFAULT_LOG_RETENTION_DAYS,
+            log_retention_entries: crate::mcp::log_store::DEFAULT_LOG_RETENTION_ENTRIES,
+            log_file_max_bytes: crate::mcp::log_files::DEFAULT_LOG_FILE_MAX_BYTES,
+            log_file_keep: crate::mcp::log_files::DEFAULT_LOG_FILE_KEEP,
+            log_ansi: mcp_core::ansi::AnsiMode::default(),
+            log_dedup_window_ms: crate::mcp::log_dedup::DEFAULT_LOG_DEDUP_WINDOW.as_millis() as u64,
+            env_strict: false,
+            default_provider: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatProviderSettings {
+    pub base_url: String,
+    pub model: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchSettings {
+    /// Registers Deeting to start on OS login.
+    pub launch_at_login: bool,
+    /// Keeps the window hidden in the tray after a login launch.
+    pub start_hidden: bool,
+}
+
+impl Default for LaunchSettings {
+    fn default() -> Self {
+        Self {
+            launch_at_login: false,
+            start_hidden: true,
+        }
+    }
+}
+
+/// Desktop notification categories; turning one off mutes it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    /// Crashes and crash loops.
+    pub crashes: bool,
+    /// Updates available and name conflicts found by sync.
+    pub updates: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            crashes: true,
+            updates: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AssistantConflictStrategy {
+    #[default]
+    LastWriteWins,
+    Prompt,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistantSyncConflict {
+    pub assistant_id: String,
+    pub cloud_id: String,
+    pub name: String,
+    pub local_updated_at: String,
+    pub cloud_updated_at: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AssistantSyncResult {
+    pub pulled: usize,
+    pub pushed: usize,
+    pub deleted: usize,
+    pub conflicts: Vec<AssistantSyncConflict>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalAssistantMessage {
     pub id: String,
@@ -357,6 +980,7 @@ pub struct LocalChatInputMessage {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalChatRequest {
     pub assistant_id: Option<String>,
+    #[serde(default)]
     pub model: String,
     pub messages: Vec<LocalChatInputMessage>,
     pub temperature: Option<f32>,
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use futures_util::stream::BoxStream;
 use futures_util::StreamExt;
+use hyper::body::Bytes;
 use log::warn;
 use serde::Serialize;
 use tokio::sync::{Mutex, RwLock};
@@ -10,6 +12,9 @@ use tauri::Emitter;
 #[derive(Default)]
 pub struct McpBridgeState {
     base_url: Arc<RwLock<String>>,
+    /// Bearer token for desktop-backend when it requires one
+    /// (`DESKTOP_API_TOKENS` or `api_tokens`).
+    token: Arc<RwLock<Option<String>>>,
     streams: Arc<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>>,
     client: reqwest::Client,
 }
@@ -18,6 +23,7 @@ impl McpBridgeState {
     pub fn new(default_base_url: String) -> Self {
         Self {
             base_url: Arc::new(RwLock::new(default_base_url)),
+            token: Arc::new(RwLock::new(None)),
             streams: Arc::new(Mutex::new(HashMap::new())),
             client: reqwest::Client::new(),
         }
@@ -31,6 +37,17 @@ impl McpBridgeState {
         let mut base_url = self.base_url.write().await;
         *base_url = url;
     }
+
+    async fn get_token(&self) -> Option<String> {
+        self.token.read().await.clone()
+    }
+
+    async fn set_token(&self, token: Option<String>) {
+        let mut current = self.token.write().await;
+        *current = token
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty());
+    }
 }
 
 #[derive(Serialize)]
@@ -43,8 +60,10 @@ struct LogFallbackPayload {
 pub async fn set_mcp_backend_url(
     state: tauri::State<'_, McpBridgeState>,
     url: String,
+    token: Option<String>,
 ) -> Result<(), String> {
     state.set_base_url(url).await;
+    state.set_token(token).await;
     Ok(())
 }
 
@@ -60,11 +79,12 @@ pub async fn start_mcp_log_stream(
     }
 
     let base_url = state.get_base_url().await;
+    let token = state.get_token().await;
     let client = state.client.clone();
     let tool_id_clone = tool_id.clone();
     let handle = tauri::async_runtime::spawn(async move {
         if let Err(err) =
-            stream_logs(&client, &base_url, &tool_id_clone, &app).await
+            stream_logs(&client, &base_url, token.as_deref(), &tool_id_clone, &app).await
         {
             warn!("mcp log stream failed for {}: {}", tool_id_clone, err);
         }
@@ -89,26 +109,21 @@ pub async fn stop_mcp_log_stream(
 async fn stream_logs(
     client: &reqwest::Client,
     base_url: &str,
+    token: Option<&str>,
     tool_id: &str,
     app: &tauri::AppHandle,
 ) -> Result<(), String> {
-    let url = format!("{}/mcp/tools/{}/logs/stream", base_url.trim_end_matches('/'), tool_id);
-    let response = client
-        .get(&url)
-        .header("Accept", "text/event-stream")
-        .send()
-        .await
-        .map_err(|err| err.to_string())?;
-    if !response.status().is_success() {
-        return Err(format!("log stream http status {}", response.status()));
-    }
+    let path = format!("/api/v1/mcp/tools/{}/logs/stream", tool_id);
+    let mut stream = match unix_socket_path(base_url) {
+        Some(socket_path) => open_unix_event_stream(socket_path, &path, token).await?,
+        None => open_http_event_stream(client, base_url, &path, token).await?,
+    };
 
     let mut buffer = String::new();
-    let mut stream = response.bytes_stream();
     while let Some(chunk) = stream.next().await {
         let chunk = match chunk {
             Ok(bytes) => bytes,
-            Err(err) => return Err(err.to_string()),
+            Err(err) => return Err(err),
         };
         let text = String::from_utf8_lossy(&chunk);
         buffer.push_str(&text);
@@ -127,6 +142,100 @@ async fn stream_logs(
     Ok(())
 }
 
+type EventByteStream = BoxStream<'static, Result<Bytes, String>>;
+
+/// Backend URLs of the form `unix:/path/to/backend.sock` (as printed by
+/// desktop-backend when started with `LISTEN_SOCKET`) address a Unix domain
+/// socket instead of a TCP host.
+fn unix_socket_path(base_url: &str) -> Option<&str> {
+    let path = base_url.strip_prefix("unix:")?;
+    let path = path.strip_prefix("//").unwrap_or(path);
+    Some(path.trim_end_matches('/')).filter(|path| !path.is_empty())
+}
+
+async fn open_http_event_stream(
+    client: &reqwest::Client,
+    base_url: &str,
+    path: &str,
+    token: Option<&str>,
+) -> Result<EventByteStream, String> {
+    let url = format!("{}{}", base_url.trim_end_matches('/'), path);
+    let mut request = client.get(&url).header("Accept", "text/event-stream");
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|err| err.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("log stream http status {}", response.status()));
+    }
+    Ok(response
+        .bytes_stream()
+        .map(|chunk| chunk.map_err(|err| err.to_string()))
+        .boxed())
+}
+
+#[cfg(unix)]
+async fn open_unix_event_stream(
+    socket_path: &str,
+    path: &str,
+    token: Option<&str>,
+) -> Result<EventByteStream, String> {
+    use http_body_util::{BodyStream, Empty};
+    use hyper_util::rt::TokioIo;
+
+    let socket = tokio::net::UnixStream::connect(socket_path)
+        .await
+        .map_err(|err| format!("connect {}: {}", socket_path, err))?;
+    let (mut sender, connection) = hyper::client::conn::http1::handshake(TokioIo::new(socket))
+        .await
+        .map_err(|err| err.to_string())?;
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = connection.await {
+            warn!("mcp log stream connection closed: {}", err);
+        }
+    });
+
+    let mut request = hyper::Request::get(path)
+        .header(hyper::header::HOST, "localhost")
+        .header(hyper::header::ACCEPT, "text/event-stream");
+    if let Some(token) = token {
+        request = request.header(hyper::header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+    let request = request
+        .body(Empty::<Bytes>::new())
+        .map_err(|err| err.to_string())?;
+    let response = sender
+        .send_request(request)
+        .await
+        .map_err(|err| err.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("log stream http status {}", response.status()));
+    }
+    Ok(BodyStream::new(response.into_body())
+        .filter_map(|frame| async move {
+            match frame {
+                Ok(frame) => frame.into_data().ok().map(Ok),
+                Err(err) => Some(Err(err.to_string())),
+            }
+        })
+        .boxed())
+}
+
+#[cfg(not(unix))]
+async fn open_unix_event_stream(
+    socket_path: &str,
+    _path: &str,
+    _token: Option<&str>,
+) -> Result<EventByteStream, String> {
+    Err(format!(
+        "unix socket backend {} is not supported on this platform",
+        socket_path
+    ))
+}
+
 fn parse_sse_data(raw_event: &str, tool_id: &str) -> Option<serde_json::Value> {
     let mut data_lines = Vec::new();
     for line in raw_event.lines() {
@@ -166,4 +275,12 @@ mod tests {
         let payload = parse_sse_data(raw, "tool-1").unwrap();
         assert!(payload.get("raw").is_some());
     }
+
+    #[test]
+    fn unix_socket_base_urls() {
+        assert_eq!(unix_socket_path("unix:/tmp/backend.sock"), Some("/tmp/backend.sock"));
+        assert_eq!(unix_socket_path("unix:///tmp/backend.sock"), Some("/tmp/backend.sock"));
+        assert_eq!(unix_socket_path("unix:"), None);
+        assert_eq!(unix_socket_path("http://127.0.0.1:3000"), None);
+    }
 }
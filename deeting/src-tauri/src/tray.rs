@@ -0,0 +1,199 @@
+use log::warn;
+use tauri::menu::{MenuBuilder, MenuItemBuilder, SubmenuBuilder};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager};
+
+use crate::mcp::commands::{start_tool_with_approval, sync_local_source};
+use crate::mcp::types::{McpTool, McpToolStatus};
+use crate::mcp::McpRuntimeState;
+
+pub const MAIN_WINDOW: &str = "main";
+
+const TRAY_ID: &str = "deeting-tray";
+const OPEN_ID: &str = "tray:open";
+const SYNC_ID: &str = "tray:sync";
+const QUIT_ID: &str = "tray:quit";
+const START_PREFIX: &str = "tray:start:";
+const STOP_PREFIX: &str = "tray:stop:";
+
+/// Installs the tray icon. Its menu lists every tool with start/stop actions
+/// and is rebuilt by `refresh` whenever a tool status is published, together
+/// with the tooltip and, on macOS, the dock badge counting tools that need
+/// attention.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let menu = MenuBuilder::new(app)
+        .text(OPEN_ID, "Open Deeting")
+        .text(QUIT_ID, "Quit")
+        .build()?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Deeting")
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(|app, event| handle_menu_event(app, event.id().as_ref()));
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    refresh(app);
+    Ok(())
+}
+
+/// Rebuilds the tray menu, tooltip and dock badge in the background.
+pub fn refresh(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = rebuild_menu(&app).await {
+            warn!("failed to refresh tray menu: {}", err);
+        }
+    });
+}
+
+async fn rebuild_menu(app: &AppHandle) -> Result<(), String> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    let Some(state) = app.try_state::<McpRuntimeState>() else {
+        return Ok(());
+    };
+    let tools = state
+        .store
+        .list_tools()
+        .await
+        .map_err(|err| err.to_string())?;
+    let running = tools.iter().filter(|tool| is_running(tool)).count();
+    let crashed = tools
+        .iter()
+        .filter(|tool| tool.status == McpToolStatus::Crashed)
+        .count();
+    let attention = state.process_manager.attention_count();
+    let mut summary = format!("{running} running / {crashed} crashed");
+    if attention > 0 {
+        summary.push_str(&format!(" / {attention} need attention"));
+    }
+
+    let summary_item = MenuItemBuilder::new(&summary)
+        .enabled(false)
+        .build(app)
+        .map_err(to_string)?;
+    let mut tools_menu = SubmenuBuilder::new(app, "Tools");
+    if tools.is_empty() {
+        let empty = MenuItemBuilder::new("No tools installed")
+            .enabled(false)
+            .build(app)
+            .map_err(to_string)?;
+        tools_menu = tools_menu.item(&empty);
+    }
+    for tool in &tools {
+        let running = is_running(tool);
+        let start = MenuItemBuilder::with_id(format!("{START_PREFIX}{}", tool.id), "Start")
+            .enabled(!running)
+            .build(app)
+            .map_err(to_string)?;
+        let stop = MenuItemBuilder::with_id(format!("{STOP_PREFIX}{}", tool.id), "Stop")
+            .enabled(running)
+            .build(app)
+            .map_err(to_string)?;
+        let submenu = SubmenuBuilder::new(app, format!("{} ({})", tool.name, tool.status.as_str()))
+            .item(&start)
+            .item(&stop)
+            .build()
+            .map_err(to_string)?;
+        tools_menu = tools_menu.item(&submenu);
+    }
+    let tools_menu = tools_menu.build().map_err(to_string)?;
+
+    let menu = MenuBuilder::new(app)
+        .item(&summary_item)
+        .separator()
+        .item(&tools_menu)
+        .text(SYNC_ID, "Sync now")
+        .separator()
+        .text(OPEN_ID, "Open Deeting")
+        .text(QUIT_ID, "Quit")
+        .build()
+        .map_err(to_string)?;
+    tray.set_menu(Some(menu)).map_err(to_string)?;
+    tray.set_tooltip(Some(format!("Deeting: {summary}")))
+        .map_err(to_string)?;
+    set_dock_badge(app, attention);
+    Ok(())
+}
+
+/// Shows the attention count on the macOS dock icon; other platforms rely on
+/// the tray tooltip.
+#[cfg(target_os = "macos")]
+fn set_dock_badge(app: &AppHandle, count: usize) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let badge = (count > 0).then_some(count as i64);
+        if let Err(err) = window.set_badge_count(badge) {
+            warn!("failed to set dock badge: {}", err);
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_dock_badge(_app: &AppHandle, _count: usize) {}
+
+fn handle_menu_event(app: &AppHandle, id: &str) {
+    match id {
+        OPEN_ID => show_main_window(app),
+        QUIT_ID => app.exit(0),
+        SYNC_ID => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let Some(state) = app.try_state::<McpRuntimeState>() else {
+                    return;
+                };
+                if let Err(err) = sync_local_source(&state).await {
+                    warn!("tray sync failed: {}", err);
+                }
+                // Cloud subscriptions follow the scheduler's enabled setting.
+                state.sync_scheduler.wake();
+                refresh(&app);
+            });
+        }
+        _ => {
+            if let Some(tool_id) = id.strip_prefix(START_PREFIX) {
+                let (app, tool_id) = (app.clone(), tool_id.to_string());
+                tauri::async_runtime::spawn(async move {
+                    let Some(state) = app.try_state::<McpRuntimeState>() else {
+                        return;
+                    };
+                    if let Err(err) = start_tool_with_approval(&app, &state, &tool_id).await {
+                        warn!("tray start of {} failed: {}", tool_id, err);
+                        refresh(&app);
+                    }
+                });
+            } else if let Some(tool_id) = id.strip_prefix(STOP_PREFIX) {
+                let (app, tool_id) = (app.clone(), tool_id.to_string());
+                tauri::async_runtime::spawn(async move {
+                    let Some(state) = app.try_state::<McpRuntimeState>() else {
+                        return;
+                    };
+                    if let Err(err) = state.process_manager.stop_tool(&tool_id).await {
+                        warn!("tray stop of {} failed: {}", tool_id, err);
+                    }
+                });
+            }
+        }
+    }
+}
+
+pub(crate) fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn is_running(tool: &McpTool) -> bool {
+    matches!(
+        tool.status,
+        McpToolStatus::Starting | McpToolStatus::Healthy | McpToolStatus::Degraded
+    )
+}
+
+fn to_string(err: tauri::Error) -> String {
+    err.to_string()
+}
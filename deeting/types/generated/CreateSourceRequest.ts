@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpSourceType } from "./McpSourceType";
+import type { McpTrustLevel } from "./McpTrustLevel";
+
+export type CreateSourceRequest = { name: string, source_type: McpSourceType, path_or_url: string, trust_level: McpTrustLevel, is_read_only: boolean | null, };
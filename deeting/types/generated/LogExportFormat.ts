@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * File layout for `export_tool_logs`.
+ */
+export type LogExportFormat = "text" | "jsonl";
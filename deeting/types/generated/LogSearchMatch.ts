@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpLogEntry } from "./McpLogEntry";
+
+/**
+ * A stored line found by `search_mcp_logs`, with lines of the same tool
+ * around it.
+ */
+export type LogSearchMatch = { tool_id: string, entry: McpLogEntry, /**
+ * Up to `context` lines logged just before `entry`, oldest first.
+ */
+before: Array<McpLogEntry>, /**
+ * Up to `context` lines logged just after `entry`.
+ */
+after: Array<McpLogEntry>, };
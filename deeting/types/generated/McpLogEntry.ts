@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpLogLevel } from "./McpLogLevel";
+import type { McpLogStream } from "./McpLogStream";
+import type { StructuredLog } from "./StructuredLog";
+
+export type McpLogEntry = { /**
+ * Increases with every line logged, across tools and app runs; the
+ * `before` cursor of `get_mcp_logs`. `0` for lines that are only emitted.
+ */
+seq: number, timestamp: string, stream: McpLogStream, message: string, /**
+ * Guessed from the line; see `log_level::infer`.
+ */
+level: McpLogLevel, /**
+ * Fields read from a line that is a JSON log record.
+ */
+structured?: StructuredLog, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Severity of a log line, ordered from `info` up to `error`.
+ */
+export type McpLogLevel = "info" | "warn" | "error";
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpSourceStatus } from "./McpSourceStatus";
+import type { McpSourceType } from "./McpSourceType";
+import type { McpTrustLevel } from "./McpTrustLevel";
+
+export type McpSource = { id: string, name: string, source_type: McpSourceType, path_or_url: string, trust_level: McpTrustLevel, status: McpSourceStatus, last_synced_at: string | null, is_read_only: boolean, created_at: string, updated_at: string, };
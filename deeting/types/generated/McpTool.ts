@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpConflictStatus } from "./McpConflictStatus";
+import type { McpSourceType } from "./McpSourceType";
+import type { McpToolStatus } from "./McpToolStatus";
+
+export type McpTool = { id: string, identifier: string | null, name: string, source_type: McpSourceType, source_id: string | null, status: McpToolStatus, ping_ms: number | null, capabilities: Array<string>, description: string, error: string | null, command: string | null, args: Array<string> | null, env: Record<string, string> | null, config_json: string, pending_config_json: string | null, config_hash: string, pending_config_hash: string | null, conflict_status: McpConflictStatus, is_read_only: boolean, is_new: boolean, approved_at: string | null, market_tool_id: string | null, /**
+ * Started whenever the app launches, e.g. at login.
+ */
+auto_start: boolean, /**
+ * Also writes the tool's output to rotating files under `logs/`.
+ */
+log_to_file: boolean, /**
+ * Log lines kept in memory for this tool instead of the app setting.
+ */
+log_buffer_size: number | null, created_at: string, updated_at: string, };
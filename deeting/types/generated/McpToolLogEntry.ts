@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpLogEntry } from "./McpLogEntry";
+
+/**
+ * A log entry tagged with the tool that produced it, as emitted on the
+ * merged `mcp-log-all://` stream.
+ */
+export type McpToolLogEntry = { tool_id: string, entry: McpLogEntry, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpConflictStatus } from "./McpConflictStatus";
+import type { McpToolStatus } from "./McpToolStatus";
+
+/**
+ * Payload of the `mcp-status://{tool_id}` event, emitted whenever a tool's
+ * status or conflict status changes so the UI does not have to poll.
+ */
+export type McpToolStatusEvent = { tool_id: string, status: McpToolStatus, conflict_status: McpConflictStatus, error: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of one source sync: the latest subscription sync of a cloud
+ * source, scheduled or manual, or one entry of `sync_all_sources`.
+ */
+export type SourceSyncResult = { source_id: string, source_name: string, finished_at: string, success: boolean, tool_count: number | null, error: string | null, };
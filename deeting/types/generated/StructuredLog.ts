@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * `level`, `msg` and `time` of a JSON log line (pino, winston, ...).
+ */
+export type StructuredLog = { /**
+ * Lowercase name; numeric pino levels are translated (`50` is `error`).
+ */
+level?: string, msg?: string, /**
+ * RFC 3339, as logged by the tool rather than when it was captured.
+ */
+time?: string, };
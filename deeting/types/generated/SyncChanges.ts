@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpTool } from "./McpTool";
+
+/**
+ * Result of `sync_mcp_source` and `sync_cloud_subscriptions`: the ids a
+ * sync touched, by outcome, and the current rows of every changed tool, so
+ * the UI can patch its list instead of reloading every tool.
+ */
+export type SyncChanges = { added: Array<string>, updated: Array<string>, /**
+ * Added or updated tools left with a pending update or a name conflict.
+ */
+conflicted: Array<string>, /**
+ * Cloud tools whose subscription is gone.
+ */
+orphaned: Array<string>, unchanged: Array<string>, tools: Array<McpTool>, };
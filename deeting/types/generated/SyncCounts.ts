@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How a source sync changed the source's tools.
+ */
+export type SyncCounts = { total: number, added: number, updated: number, /**
+ * Upstream changes staged for review on read-only sources.
+ */
+pending: number, unchanged: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourceSyncResult } from "./SourceSyncResult";
+
+export type SyncSchedulerStatus = { enabled: boolean, interval_minutes: number, running: boolean, last_run_at: string | null, /**
+ * `None` while the scheduler is paused.
+ */
+next_run_at: string | null, sources: Array<SourceSyncResult>, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SyncCounts } from "./SyncCounts";
+
+/**
+ * Payload of the `mcp-sync-status://{source_id}` event, emitted as a source
+ * sync runs so the UI can report it without awaiting the command.
+ */
+export type SyncStatusEvent = { "phase": "started", source_id: string, } | { "phase": "progress", source_id: string, processed: number, total: number, } | { "phase": "finished", source_id: string, counts: SyncCounts, } | { "phase": "failed", source_id: string, code: string, message: string, };
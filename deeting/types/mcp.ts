@@ -1,21 +1,36 @@
-export type MCPSourceType = "local" | "cloud" | "modelscope" | "github" | "url"
+// 与 Rust 结构一致的类型由 ts-rs 生成在 `./generated`（`npm run desktop:bindings`），此处只做别名与前端视图类型。
+import type { CreateSourceRequest } from "./generated/CreateSourceRequest"
+import type { LogExportFormat } from "./generated/LogExportFormat"
+import type { LogSearchMatch } from "./generated/LogSearchMatch"
+import type { McpConflictStatus } from "./generated/McpConflictStatus"
+import type { McpLogEntry } from "./generated/McpLogEntry"
+import type { McpLogLevel } from "./generated/McpLogLevel"
+import type { McpLogStream } from "./generated/McpLogStream"
+import type { McpSource } from "./generated/McpSource"
+import type { McpSourceStatus } from "./generated/McpSourceStatus"
+import type { McpSourceType } from "./generated/McpSourceType"
+import type { McpTool } from "./generated/McpTool"
+import type { McpToolLogEntry as GeneratedMcpToolLogEntry } from "./generated/McpToolLogEntry"
+import type { McpToolStatus } from "./generated/McpToolStatus"
+import type { McpToolStatusEvent as GeneratedMcpToolStatusEvent } from "./generated/McpToolStatusEvent"
+import type { McpTrustLevel } from "./generated/McpTrustLevel"
+import type { SourceSyncResult } from "./generated/SourceSyncResult"
+import type { StructuredLog } from "./generated/StructuredLog"
+import type { SyncChanges } from "./generated/SyncChanges"
+import type { SyncCounts } from "./generated/SyncCounts"
+import type { SyncSchedulerStatus } from "./generated/SyncSchedulerStatus"
+import type { SyncStatusEvent } from "./generated/SyncStatusEvent"
 
-export type MCPSourceTrustLevel = "official" | "community" | "private"
+export type MCPSourceType = McpSourceType
 
-export type MCPSourceStatus = "active" | "inactive" | "syncing" | "error" | "draft"
+export type MCPSourceTrustLevel = McpTrustLevel
 
-export interface McpSourceRecord {
-  id: string
-  name: string
-  source_type: MCPSourceType
-  path_or_url: string
-  trust_level: MCPSourceTrustLevel
-  status: MCPSourceStatus
-  last_synced_at?: string | null
-  is_read_only: boolean
-  created_at: string
-  updated_at: string
-}
+/** `draft` 仅存在于前端（尚未保存的源） */
+export type MCPSourceStatus = McpSourceStatus | "draft"
+
+export type McpSourceRecord = McpSource
+
+export type McpCreateSourceRequest = CreateSourceRequest
 
 export interface MCPSource {
   id: string
@@ -31,43 +46,11 @@ export interface MCPSource {
   updatedAt?: string
 }
 
-export type MCPToolStatus =
-  | "pending"
-  | "stopped"
-  | "starting"
-  | "healthy"
-  | "degraded"
-  | "crashed"
-  | "updating"
-  | "error"
-  | "orphaned"
+export type MCPToolStatus = McpToolStatus
 
-export type MCPConflictStatus = "none" | "update_available" | "conflict"
+export type MCPConflictStatus = McpConflictStatus
 
-export interface McpToolRecord {
-  id: string
-  identifier?: string | null
-  name: string
-  source_type: MCPSourceType
-  source_id?: string | null
-  status: MCPToolStatus
-  ping_ms?: number | null
-  capabilities: string[]
-  description: string
-  error?: string | null
-  command?: string | null
-  args?: string[] | null
-  env?: Record<string, string> | null
-  config_json: string
-  pending_config_json?: string | null
-  config_hash: string
-  pending_config_hash?: string | null
-  conflict_status: MCPConflictStatus
-  is_read_only: boolean
-  is_new: boolean
-  created_at: string
-  updated_at: string
-}
+export type McpToolRecord = McpTool
 
 export interface MCPEnvConfigItem {
   key: string
@@ -112,10 +95,77 @@ export interface MCPTool {
   conflict?: MCPToolConflict
 }
 
-export type MCPLogStream = "stdout" | "stderr" | "event"
+export type MCPLogStream = McpLogStream
+
+/** 按内容推断的日志级别 */
+export type MCPLogLevel = McpLogLevel
+
+/** Payload of the Tauri `mcp-status://{tool_id}` event. */
+export type McpToolStatusEvent = GeneratedMcpToolStatusEvent
+
+export type MCPLogEntry = McpLogEntry
+
+/** 输出行为 JSON 日志（pino/winston 等）时解析出的字段 */
+export type MCPStructuredLog = StructuredLog
+
+export type McpLogExportFormat = LogExportFormat
+
+/** `mcp-log-all://` 事件的载荷（`subscribe_all_mcp_logs` 开启后推送所有工具的日志） */
+export type McpToolLogEntry = GeneratedMcpToolLogEntry
+
+/** `search_mcp_logs` 的结果：命中行及同一工具前后的上下文 */
+export type McpLogSearchMatch = LogSearchMatch
+
+export type McpInstallPayload =
+  | { kind: "market"; market_tool_id: string }
+  | { kind: "config"; config: { mcpServers: Record<string, unknown> } }
+
+/** Shell commands a tool config runs around its process (`hooks` in the config). */
+export interface McpToolHooks {
+  preStart?: string[]
+  postStop?: string[]
+}
+
+/** Everything a tool would run: its command line, hooks and health check command. */
+export interface McpToolRunPreview {
+  command?: string | null
+  args: string[]
+  env?: Record<string, string>
+  hooks: McpToolHooks
+  healthcheck_command?: string | null
+}
 
-export interface MCPLogEntry {
-  timestamp: string
-  stream: MCPLogStream
-  message: string
+export interface McpInstallServerPreview extends McpToolRunPreview {
+  name: string
+  /** Secret values arrive masked. */
+  env: Record<string, string>
 }
+
+export interface McpInstallRequest {
+  id: string
+  payload: McpInstallPayload
+  source_name: string
+  trust_level: MCPSourceTrustLevel
+  requires_approval: boolean
+  servers: McpInstallServerPreview[]
+}
+
+/** Payload of the Tauri `mcp-tool-approval` event: a first start waiting for approval. */
+export interface McpToolApprovalRequest extends McpToolRunPreview {
+  id: string
+  tool_id: string
+  tool_name: string
+  source_name: string
+  trust_level: MCPSourceTrustLevel
+}
+
+export type McpSourceSyncResult = SourceSyncResult
+
+export type McpSyncSchedulerStatus = SyncSchedulerStatus
+
+export type McpSyncCounts = SyncCounts
+
+/** What `sync_mcp_source` / `sync_cloud_subscriptions` changed; `tools` holds every tool outside `unchanged`. */
+export type McpSyncChanges = SyncChanges
+
+export type McpSyncStatusEvent = SyncStatusEvent
@@ -0,0 +1,70 @@
+use axum::extract::State;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::health;
+use crate::mcp::{DatabaseStats, ErrorResponse, McpError};
+use crate::settings::{self, ReloadResponse};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/reload", post(reload))
+        .route("/stats", get(stats))
+}
+
+/// Snapshot for triaging a slow backend.
+#[derive(Serialize, ToSchema)]
+pub struct StatsResponse {
+    pub uptime_ms: u64,
+    pub running_processes: usize,
+    pub database: DatabaseStats,
+    pub subscribers: SubscriberStats,
+}
+
+/// Open broadcast receivers: SSE/WebSocket clients plus internal listeners.
+#[derive(Serialize, ToSchema)]
+pub struct SubscriberStats {
+    /// Tool/source status events (`/mcp/tools/events`, `/mcp/ws`).
+    pub tool_events: usize,
+    /// Combined log stream (`/mcp/logs/stream`).
+    pub all_logs: usize,
+    /// Per-tool log streams, summed.
+    pub tool_logs: usize,
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/reload",
+    tag = "admin",
+    responses((status = 200, body = ReloadResponse))
+)]
+pub(crate) async fn reload(State(state): State<AppState>) -> Json<ReloadResponse> {
+    Json(settings::reload(&state).await)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/stats",
+    tag = "admin",
+    responses(
+        (status = 200, body = StatsResponse),
+        (status = 500, description = "Database error", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn stats(State(state): State<AppState>) -> Result<Json<StatsResponse>, McpError> {
+    let database = state.store.database_stats().await?;
+    let manager = &state.process_manager;
+    Ok(Json(StatsResponse {
+        uptime_ms: health::uptime().as_millis() as u64,
+        running_processes: manager.running_count().await,
+        database,
+        subscribers: SubscriberStats {
+            tool_events: state.store.event_subscriber_count(),
+            all_logs: manager.all_logs_subscriber_count(),
+            tool_logs: manager.tool_logs_subscriber_count().await,
+        },
+    }))
+}
@@ -0,0 +1,63 @@
+use axum::extract::Request;
+use axum::http::{header, HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::mcp::McpError;
+
+/// Current API version; routes are served under `/api/v{API_VERSION}`.
+pub const API_VERSION: &str = "1";
+pub const API_PREFIX: &str = "/api/v1";
+
+/// Sent by clients to pin the version they were built against, and echoed on
+/// every API response with the version that produced it.
+pub static API_VERSION_HEADER: HeaderName = HeaderName::from_static("x-api-version");
+
+static DEPRECATION_HEADER: HeaderName = HeaderName::from_static("deprecation");
+
+/// Rejects requests pinned to a version this server does not speak, so a
+/// frontend expecting a different `McpTool` shape fails loudly instead of
+/// misreading the payload. Accepts `1` or `v1`; no header means "current".
+pub async fn negotiate(request: Request, next: Next) -> Response {
+    let requested = request
+        .headers()
+        .get(&API_VERSION_HEADER)
+        .map(|value| value.to_str().unwrap_or_default().trim().to_string());
+    if let Some(requested) = requested {
+        let normalized = requested.strip_prefix(['v', 'V']).unwrap_or(&requested);
+        if normalized != API_VERSION {
+            let mut response = McpError::Validation(format!(
+                "unsupported API version {requested:?}; this server speaks v{API_VERSION}"
+            ))
+            .into_response();
+            response.headers_mut().insert(
+                API_VERSION_HEADER.clone(),
+                HeaderValue::from_static(API_VERSION),
+            );
+            return response;
+        }
+    }
+
+    let mut response = next.run(request).await;
+    response.headers_mut().insert(
+        API_VERSION_HEADER.clone(),
+        HeaderValue::from_static(API_VERSION),
+    );
+    response
+}
+
+/// Marks responses served from the unversioned legacy paths as deprecated and
+/// points at the `/api/v1` successor.
+pub async fn deprecated_alias(request: Request, next: Next) -> Response {
+    let successor = format!(
+        "<{API_PREFIX}{}>; rel=\"successor-version\"",
+        request.uri().path()
+    );
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION_HEADER.clone(), HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.append(header::LINK, link);
+    }
+    response
+}
@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use crate::mcp::McpError;
+use crate::state::AppState;
+
+/// Static bearer tokens read from `DESKTOP_API_TOKENS` (comma separated).
+/// Tokens created through `/mcp/auth/tokens` live in the `api_tokens` table.
+#[derive(Clone, Default)]
+pub struct AuthConfig {
+    env_tokens: Arc<HashSet<String>>,
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Self {
+        let env_tokens = std::env::var("DESKTOP_API_TOKENS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(str::to_string)
+            .collect();
+        Self {
+            env_tokens: Arc::new(env_tokens),
+        }
+    }
+
+    pub fn has_env_tokens(&self) -> bool {
+        !self.env_tokens.is_empty()
+    }
+}
+
+/// A bearer token [`require_token`] has verified, attached to the request
+/// for later layers such as rate limiting.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedToken(pub String);
+
+/// Rejects requests without a valid bearer token once any token is
+/// configured. With no env tokens and an empty `api_tokens` table the API
+/// stays open to loopback and Unix socket clients only, so a fresh local
+/// install never serves process-spawning routes to the network.
+///
+/// Browsers cannot set headers on `EventSource`/`WebSocket`, so the token is
+/// also accepted as an `access_token` query parameter.
+pub async fn require_token(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let presented = bearer_token(&request).or_else(|| query_token(&request));
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.ip());
+    match authorize(&state, presented, peer).await {
+        Ok(verified) => {
+            if let Some(token) = verified {
+                request.extensions_mut().insert(VerifiedToken(token));
+            }
+            next.run(request).await
+        }
+        Err(err) => err.into_response(),
+    }
+}
+
+/// The presented token once it has been checked; `None` while the API is
+/// open, since nothing is verified then.
+async fn authorize(
+    state: &AppState,
+    presented: Option<String>,
+    peer: Option<IpAddr>,
+) -> Result<Option<String>, McpError> {
+    let has_db_tokens = state.store.has_api_tokens().await?;
+    if !state.auth.has_env_tokens() && !has_db_tokens {
+        if is_local_peer(peer) {
+            return Ok(None);
+        }
+        return Err(McpError::Unauthorized(
+            "no API tokens configured; only loopback clients may connect".to_string(),
+        ));
+    }
+
+    let presented =
+        presented.ok_or_else(|| McpError::Unauthorized("missing bearer token".to_string()))?;
+    if state.auth.env_tokens.contains(&presented) {
+        return Ok(Some(presented));
+    }
+    if has_db_tokens && state.store.verify_api_token(&presented).await? {
+        return Ok(Some(presented));
+    }
+    Err(McpError::Unauthorized("invalid token".to_string()))
+}
+
+/// Unix socket connections carry no peer address and count as local.
+fn is_local_peer(peer: Option<IpAddr>) -> bool {
+    peer.is_none_or(|ip| ip.to_canonical().is_loopback())
+}
+
+pub(crate) fn bearer_token<B>(request: &axum::http::Request<B>) -> Option<String> {
+    let value = request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?;
+    let token = value.strip_prefix("Bearer ")?.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+fn query_token(request: &Request) -> Option<String> {
+    request.uri().query()?.split('&').find_map(|pair| {
+        let token = pair.strip_prefix("access_token=")?;
+        (!token.is_empty()).then(|| token.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_loopback_and_socket_peers_are_local() {
+        assert!(is_local_peer(None));
+        assert!(is_local_peer(Some("127.0.0.1".parse().unwrap())));
+        assert!(is_local_peer(Some("::1".parse().unwrap())));
+        assert!(is_local_peer(Some("::ffff:127.0.0.1".parse().unwrap())));
+        assert!(!is_local_peer(Some("192.168.1.20".parse().unwrap())));
+        assert!(!is_local_peer(Some("0.0.0.0".parse().unwrap())));
+    }
+}
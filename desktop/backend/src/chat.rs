@@ -0,0 +1,504 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::routing::post;
+use axum::{Json, Router};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, warn};
+
+use crate::mcp::{
+    CreateAssistantMessageRequest, ErrorResponse, LocalAssistant, LocalChatInputMessage,
+    LocalChatRequest, LocalChatResponse, McpError,
+};
+use crate::state::AppState;
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/", post(chat_complete))
+        .route("/stream", post(chat_stream))
+}
+
+#[utoipa::path(
+    post,
+    path = "/chat",
+    tag = "chat",
+    request_body = LocalChatRequest,
+    responses(
+        (status = 200, body = LocalChatResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 502, description = "Upstream error", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn chat_complete(
+    State(state): State<AppState>,
+    Json(payload): Json<LocalChatRequest>,
+) -> Result<Json<LocalChatResponse>, McpError> {
+    let prepared = prepare_chat(&state, payload).await?;
+    let response = send_chat_request(&state, &prepared, false).await?;
+    let response_json: Value = response
+        .json()
+        .await
+        .map_err(|err| McpError::Upstream(err.to_string()))?;
+
+    let content = extract_chat_content(&response_json)
+        .ok_or_else(|| McpError::Upstream("empty response content".to_string()))?;
+    persist_exchange(&state, &prepared, &content).await?;
+
+    Ok(Json(LocalChatResponse { content }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/chat/stream",
+    tag = "chat",
+    request_body = LocalChatRequest,
+    responses(
+        (status = 200, description = "Server-sent `delta` events with `{content}`, then one `done` \
+                                      event with `{content, finish_reason, usage}` or an `error` event",
+         content_type = "text/event-stream"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 502, description = "Upstream error", body = ErrorResponse),
+    )
+)]
+pub(crate) async fn chat_stream(
+    State(state): State<AppState>,
+    Json(payload): Json<LocalChatRequest>,
+) -> Result<Sse<ReceiverStream<Result<Event, Infallible>>>, McpError> {
+    let prepared = prepare_chat(&state, payload).await?;
+    let response = send_chat_request(&state, &prepared, true).await?;
+
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    tokio::spawn(async move {
+        // The receiver is dropped when the client disconnects; stop reading
+        // the upstream body so the provider connection is closed as well.
+        tokio::select! {
+            _ = relay_stream(&state, &prepared, response, &tx) => {}
+            _ = tx.closed() => {
+                debug!("chat stream client disconnected; upstream request cancelled");
+            }
+        }
+    });
+    Ok(Sse::new(ReceiverStream::new(rx))
+        .keep_alive(KeepAlive::new().interval(Duration::from_secs(15))))
+}
+
+/// Forwards upstream `data:` chunks as `delta` events and finishes with a
+/// `done` event. The exchange is persisted only once the upstream completes.
+async fn relay_stream(
+    state: &AppState,
+    prepared: &PreparedChat,
+    response: reqwest::Response,
+    tx: &mpsc::Sender<Result<Event, Infallible>>,
+) {
+    let mut body = response.bytes_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut parser = StreamState::default();
+
+    while let Some(chunk) = body.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => {
+                send_error(tx, &McpError::Upstream(err.to_string())).await;
+                return;
+            }
+        };
+        buffer.extend_from_slice(&chunk);
+        while let Some(pos) = buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            for delta in parser.push_line(line.trim()) {
+                let event = Event::default()
+                    .event("delta")
+                    .json_data(json!({ "content": delta }));
+                if let Ok(event) = event {
+                    if tx.send(Ok(event)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            if parser.done {
+                break;
+            }
+        }
+        if parser.done {
+            break;
+        }
+    }
+
+    if let Err(err) = persist_exchange(state, prepared, &parser.content).await {
+        warn!("failed to persist streamed chat: {err}");
+    }
+    let done = Event::default().event("done").json_data(json!({
+        "content": parser.content,
+        "finish_reason": parser.finish_reason,
+        "usage": parser.usage,
+    }));
+    if let Ok(done) = done {
+        let _ = tx.send(Ok(done)).await;
+    }
+}
+
+async fn send_error(tx: &mpsc::Sender<Result<Event, Infallible>>, err: &McpError) {
+    if let Ok(event) = Event::default()
+        .event("error")
+        .json_data(json!({ "code": err.code(), "message": err.to_string() }))
+    {
+        let _ = tx.send(Ok(event)).await;
+    }
+}
+
+/// Accumulates an OpenAI-style `data:` stream.
+#[derive(Default)]
+struct StreamState {
+    content: String,
+    finish_reason: Option<String>,
+    usage: Option<Value>,
+    done: bool,
+}
+
+impl StreamState {
+    /// Consumes one SSE line and returns the content deltas it carried.
+    fn push_line(&mut self, line: &str) -> Vec<String> {
+        let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+            return Vec::new();
+        };
+        if data == "[DONE]" {
+            self.done = true;
+            return Vec::new();
+        }
+        let Ok(chunk) = serde_json::from_str::<Value>(data) else {
+            return Vec::new();
+        };
+        if let Some(usage) = chunk.get("usage").filter(|usage| !usage.is_null()) {
+            self.usage = Some(usage.clone());
+        }
+        let mut deltas = Vec::new();
+        for choice in chunk
+            .get("choices")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(delta) = choice
+                .pointer("/delta/content")
+                .and_then(Value::as_str)
+                .filter(|delta| !delta.is_empty())
+            {
+                self.content.push_str(delta);
+                deltas.push(delta.to_string());
+            }
+            if let Some(reason) = choice.get("finish_reason").and_then(Value::as_str) {
+                self.finish_reason = Some(reason.to_string());
+            }
+        }
+        deltas
+    }
+}
+
+/// Posts the chat payload and maps non-2xx upstream replies to `Upstream`.
+async fn send_chat_request(
+    state: &AppState,
+    prepared: &PreparedChat,
+    stream: bool,
+) -> Result<reqwest::Response, McpError> {
+    let request_body = build_chat_payload(prepared, stream);
+    let mut request = state.http.post(&prepared.endpoint).json(&request_body);
+    if let Some(authorization) = &prepared.authorization {
+        request = request.header("Authorization", authorization);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|err| McpError::Upstream(err.to_string()))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body: Value = response.json().await.unwrap_or(Value::Null);
+    let message =
+        extract_error_message(&body).unwrap_or_else(|| format!("upstream returned {status}"));
+    Err(McpError::Upstream(message))
+}
+
+/// A validated chat request with the assistant and provider resolved.
+struct PreparedChat {
+    assistant: Option<LocalAssistant>,
+    model: String,
+    messages: Vec<LocalChatInputMessage>,
+    temperature: Option<f32>,
+    top_p: Option<f32>,
+    max_tokens: Option<u32>,
+    endpoint: String,
+    authorization: Option<String>,
+}
+
+/// Resolves the assistant (prepending its system prompt when the client did
+/// not send one) and the provider. `model` comes from the request first, then
+/// the assistant's `model_config`, then `DESKTOP_CHAT_MODEL`; see
+/// [`resolve_provider`] for `base_url` and `api_key`.
+async fn prepare_chat(
+    state: &AppState,
+    payload: LocalChatRequest,
+) -> Result<PreparedChat, McpError> {
+    if payload.messages.is_empty() {
+        return Err(McpError::Validation("messages is required".to_string()));
+    }
+
+    let mut assistant = None;
+    if let Some(assistant_id) = payload.assistant_id.as_deref() {
+        let found = state
+            .store
+            .get_assistant(assistant_id)
+            .await?
+            .ok_or_else(|| McpError::NotFound(format!("assistant {assistant_id} not found")))?;
+        assistant = Some(found);
+    }
+    let model_config = assistant.as_ref().and_then(|a| a.model_config.as_ref());
+
+    let model = non_empty(Some(payload.model))
+        .or_else(|| config_str(model_config, "model"))
+        .or_else(|| env_str("DESKTOP_CHAT_MODEL"))
+        .ok_or_else(|| McpError::Validation("model is required".to_string()))?;
+    let (base_url, api_key) = resolve_provider(payload.base_url, payload.api_key, model_config)?;
+
+    let mut messages = payload.messages;
+    if let Some(assistant) = &assistant {
+        let system_prompt = assistant.system_prompt.trim();
+        if !system_prompt.is_empty() && !messages.iter().any(|msg| msg.role == "system") {
+            messages.insert(
+                0,
+                LocalChatInputMessage {
+                    role: "system".to_string(),
+                    content: system_prompt.to_string(),
+                },
+            );
+        }
+    }
+
+    Ok(PreparedChat {
+        assistant,
+        model,
+        messages,
+        temperature: payload.temperature,
+        top_p: payload.top_p,
+        max_tokens: payload.max_tokens,
+        endpoint: build_chat_endpoint(&base_url),
+        authorization: api_key.as_deref().map(normalize_bearer_token),
+    })
+}
+
+/// Stores the latest user turn and the reply in the assistant's history.
+/// Earlier turns are assumed to be persisted already.
+async fn persist_exchange(
+    state: &AppState,
+    prepared: &PreparedChat,
+    reply: &str,
+) -> Result<(), McpError> {
+    let Some(assistant) = &prepared.assistant else {
+        return Ok(());
+    };
+    if let Some(last) = prepared.messages.last().filter(|msg| msg.role == "user") {
+        state
+            .store
+            .append_assistant_message(CreateAssistantMessageRequest {
+                assistant_id: assistant.id.clone(),
+                role: last.role.clone(),
+                content: last.content.clone(),
+            })
+            .await?;
+    }
+    if !reply.trim().is_empty() {
+        state
+            .store
+            .append_assistant_message(CreateAssistantMessageRequest {
+                assistant_id: assistant.id.clone(),
+                role: "assistant".to_string(),
+                content: reply.to_string(),
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+fn build_chat_payload(prepared: &PreparedChat, stream: bool) -> Value {
+    let mut body = json!({
+        "model": prepared.model,
+        "messages": prepared.messages,
+        "stream": stream,
+    });
+    if let Some(temperature) = prepared.temperature {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = prepared.top_p {
+        body["top_p"] = json!(top_p);
+    }
+    if let Some(max_tokens) = prepared.max_tokens {
+        body["max_tokens"] = json!(max_tokens);
+    }
+    if stream {
+        body["stream_options"] = json!({ "include_usage": true });
+    }
+    body
+}
+
+/// Accepts either a provider root (`https://api.openai.com/v1`) or the full
+/// `/chat/completions` URL.
+fn build_chat_endpoint(base_url: &str) -> String {
+    let base = base_url.trim().trim_end_matches('/');
+    if base.ends_with("/chat/completions") {
+        base.to_string()
+    } else {
+        format!("{base}/chat/completions")
+    }
+}
+
+fn normalize_bearer_token(api_key: &str) -> String {
+    let key = api_key.trim();
+    if key.to_ascii_lowercase().starts_with("bearer ") {
+        key.to_string()
+    } else {
+        format!("Bearer {key}")
+    }
+}
+
+fn extract_error_message(body: &Value) -> Option<String> {
+    let error = body.get("error").unwrap_or(body);
+    error
+        .get("message")
+        .and_then(Value::as_str)
+        .or_else(|| error.as_str())
+        .map(str::to_string)
+}
+
+/// `choices[0].message.content`, either a string or an array of text parts.
+fn extract_chat_content(body: &Value) -> Option<String> {
+    let content = body.pointer("/choices/0/message/content")?;
+    let text = match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// The upstream `base_url` and `api_key`. A key stored on the server (the
+/// assistant's `model_config` or `DESKTOP_CHAT_API_KEY`) is only sent to a
+/// base URL that also comes from the server; a request that sets its own
+/// `base_url` has to bring its own `api_key`.
+fn resolve_provider(
+    request_base_url: Option<String>,
+    request_api_key: Option<String>,
+    model_config: Option<&Value>,
+) -> Result<(String, Option<String>), McpError> {
+    let request_api_key = non_empty(request_api_key);
+    if let Some(base_url) = non_empty(request_base_url) {
+        if request_api_key.is_none() {
+            return Err(McpError::Validation(
+                "api_key is required when the request sets base_url".to_string(),
+            ));
+        }
+        return Ok((base_url, request_api_key));
+    }
+    let base_url = config_str(model_config, "base_url")
+        .or_else(|| env_str("DESKTOP_CHAT_BASE_URL"))
+        .ok_or_else(|| McpError::Validation("base_url is required".to_string()))?;
+    let api_key = request_api_key
+        .or_else(|| config_str(model_config, "api_key"))
+        .or_else(|| env_str("DESKTOP_CHAT_API_KEY"));
+    Ok((base_url, api_key))
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn config_str(config: Option<&Value>, key: &str) -> Option<String> {
+    non_empty(config?.get(key)?.as_str().map(str::to_string))
+}
+
+fn env_str(key: &str) -> Option<String> {
+    non_empty(std::env::var(key).ok())
+}
+
+const STREAM_BUFFER: usize = 64;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_endpoint_from_base_or_full_url() {
+        assert_eq!(
+            build_chat_endpoint("https://api.example.com/v1/"),
+            "https://api.example.com/v1/chat/completions"
+        );
+        assert_eq!(
+            build_chat_endpoint("https://api.example.com/v1/chat/completions"),
+            "https://api.example.com/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn server_key_is_only_sent_to_the_server_base_url() {
+        let config = json!({"base_url": "https://api.example.com/v1", "api_key": "sk-stored"});
+        let (base_url, api_key) = resolve_provider(None, None, Some(&config)).unwrap();
+        assert_eq!(base_url, "https://api.example.com/v1");
+        assert_eq!(api_key.as_deref(), Some("sk-stored"));
+
+        let attacker = Some("https://attacker.example".to_string());
+        assert!(matches!(
+            resolve_provider(attacker.clone(), None, Some(&config)),
+            Err(McpError::Validation(_))
+        ));
+        let (base_url, api_key) =
+            resolve_provider(attacker, Some("sk-own".to_string()), Some(&config)).unwrap();
+        assert_eq!(base_url, "https://attacker.example");
+        assert_eq!(api_key.as_deref(), Some("sk-own"));
+    }
+
+    #[test]
+    fn extracts_string_and_part_content() {
+        let body = json!({"choices": [{"message": {"content": "hi"}}]});
+        assert_eq!(extract_chat_content(&body).as_deref(), Some("hi"));
+
+        let body = json!({"choices": [{"message": {"content": [
+            {"type": "text", "text": "a"},
+            {"type": "text", "text": "b"}
+        ]}}]});
+        assert_eq!(extract_chat_content(&body).as_deref(), Some("ab"));
+
+        let body = json!({"error": {"message": "bad key"}});
+        assert_eq!(extract_chat_content(&body), None);
+        assert_eq!(extract_error_message(&body).as_deref(), Some("bad key"));
+    }
+
+    #[test]
+    fn accumulates_stream_deltas_and_usage() {
+        let mut state = StreamState::default();
+        assert_eq!(
+            state.push_line(r#"data: {"choices":[{"delta":{"content":"he"}}]}"#),
+            vec!["he"]
+        );
+        assert!(state.push_line(": keep-alive").is_empty());
+        state.push_line(r#"data: {"choices":[{"delta":{"content":"y"},"finish_reason":"stop"}]}"#);
+        state.push_line(r#"data: {"choices":[],"usage":{"total_tokens":4}}"#);
+        state.push_line("data: [DONE]");
+
+        assert_eq!(state.content, "hey");
+        assert_eq!(state.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(state.usage, Some(json!({"total_tokens": 4})));
+        assert!(state.done);
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use tracing::warn;
+
+use crate::api_version::API_VERSION_HEADER;
+use crate::request_log::REQUEST_ID_HEADER;
+
+const DEFAULT_HEADERS: &str = "authorization,content-type,x-request-id,x-api-version";
+
+/// Builds the CORS layer from the environment:
+///
+/// - `DESKTOP_CORS_ORIGINS`: comma separated origins, or `*` for any origin.
+///   Unset leaves CORS disabled, so only same-origin callers get through.
+/// - `DESKTOP_CORS_HEADERS`: allowed request headers
+///   (default `authorization,content-type,x-request-id,x-api-version`).
+/// - `DESKTOP_CORS_CREDENTIALS`: `true` to allow cookies/credentials. Browsers
+///   refuse credentials with a wildcard origin, so it is ignored with `*`.
+pub fn cors_layer_from_env() -> CorsLayer {
+    let Some(origins) = env_list("DESKTOP_CORS_ORIGINS") else {
+        return CorsLayer::new();
+    };
+    let wildcard = origins.iter().any(|origin| origin == "*");
+
+    let mut layer = CorsLayer::new()
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .expose_headers([
+            REQUEST_ID_HEADER.clone(),
+            API_VERSION_HEADER.clone(),
+            HeaderName::from_static("deprecation"),
+            axum::http::header::LINK,
+        ])
+        .max_age(Duration::from_secs(600));
+
+    layer = if wildcard {
+        layer.allow_origin(Any)
+    } else {
+        let origins: Vec<HeaderValue> = origins
+            .iter()
+            .filter_map(|origin| match HeaderValue::from_str(origin) {
+                Ok(value) => Some(value),
+                Err(_) => {
+                    warn!("ignoring invalid CORS origin {origin:?}");
+                    None
+                }
+            })
+            .collect();
+        layer.allow_origin(AllowOrigin::list(origins))
+    };
+
+    let headers: Vec<HeaderName> = env_list("DESKTOP_CORS_HEADERS")
+        .unwrap_or_else(|| DEFAULT_HEADERS.split(',').map(str::to_string).collect())
+        .iter()
+        .filter_map(|name| HeaderName::from_bytes(name.as_bytes()).ok())
+        .collect();
+    layer = layer.allow_headers(headers);
+
+    let credentials = std::env::var("DESKTOP_CORS_CREDENTIALS")
+        .map(|value| matches!(value.trim(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    if credentials && wildcard {
+        warn!("DESKTOP_CORS_CREDENTIALS ignored because DESKTOP_CORS_ORIGINS is `*`");
+    } else if credentials {
+        layer = layer.allow_credentials(true);
+    }
+    layer
+}
+
+fn env_list(key: &str) -> Option<Vec<String>> {
+    let values: Vec<String> = std::env::var(key)
+        .ok()?
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .collect();
+    (!values.is_empty()).then_some(values)
+}
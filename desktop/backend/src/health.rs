@@ -0,0 +1,139 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use axum::extract::{Request, State};
+use axum::http::StatusCode;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+use crate::mcp::McpError;
+use crate::state::AppState;
+
+static APP_START: Lazy<std::time::Instant> = Lazy::new(std::time::Instant::now);
+
+/// Startup milestones reached by the background initialization in `main`.
+/// The listener binds before they are done so liveness can answer early.
+#[derive(Clone, Default)]
+pub struct Readiness {
+    migrated: Arc<AtomicBool>,
+    local_source: Arc<AtomicBool>,
+}
+
+impl Readiness {
+    pub fn mark_migrated(&self) {
+        self.migrated.store(true, Ordering::SeqCst);
+    }
+
+    pub fn mark_local_source(&self) {
+        self.local_source.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.migrated.load(Ordering::SeqCst) && self.local_source.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Serialize)]
+struct LivenessPayload {
+    status: &'static str,
+    uptime_ms: u128,
+}
+
+#[derive(Serialize)]
+struct ReadinessPayload {
+    status: &'static str,
+    checks: ReadinessChecks,
+}
+
+#[derive(Serialize)]
+struct ReadinessChecks {
+    database: bool,
+    local_source: bool,
+    process_manager: bool,
+}
+
+/// Force the start instant so `uptime_ms` counts from process start rather
+/// than from the first probe.
+pub fn init_start_time() {
+    Lazy::force(&APP_START);
+}
+
+pub fn uptime() -> std::time::Duration {
+    APP_START.elapsed()
+}
+
+/// The process is up and serving HTTP; says nothing about its dependencies.
+pub async fn livez() -> impl IntoResponse {
+    let payload = LivenessPayload {
+        status: "ok",
+        uptime_ms: uptime().as_millis(),
+    };
+    (StatusCode::OK, Json(payload))
+}
+
+/// `200` once the schema is migrated, the local source exists and the process
+/// manager accepts starts; `503` while starting up or shutting down.
+pub async fn readyz(State(state): State<AppState>) -> impl IntoResponse {
+    let migrated = state.readiness.migrated.load(Ordering::SeqCst);
+    let checks = ReadinessChecks {
+        database: migrated && state.store.ping().await.is_ok(),
+        local_source: state.readiness.local_source.load(Ordering::SeqCst),
+        process_manager: !state.process_manager.is_shutting_down(),
+    };
+    let (code, status) = if !checks.process_manager {
+        (StatusCode::SERVICE_UNAVAILABLE, "stopping")
+    } else if !migrated || !checks.local_source {
+        (StatusCode::SERVICE_UNAVAILABLE, "starting")
+    } else if !checks.database {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    } else {
+        (StatusCode::OK, "ready")
+    };
+    (code, Json(ReadinessPayload { status, checks }))
+}
+
+/// Answers `503 SERVICE_UNAVAILABLE` until startup has finished, so clients
+/// hitting the API while migrations run can retry instead of seeing database
+/// errors.
+pub async fn require_ready(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    if !state.readiness.is_ready() {
+        return McpError::Unavailable("starting up; retry shortly".to_string()).into_response();
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthConfig;
+    use crate::mcp::{McpStore, ProcessManager};
+    use crate::settings::{RuntimeSettings, SharedSettings};
+
+    #[tokio::test]
+    async fn readyz_waits_for_startup_steps() {
+        let store = Arc::new(McpStore::new("sqlite::memory:").await.unwrap());
+        let state = AppState {
+            version: "test",
+            store: store.clone(),
+            process_manager: ProcessManager::new(store),
+            providers: Default::default(),
+            auth: AuthConfig::from_env(),
+            http: reqwest::Client::new(),
+            readiness: Readiness::default(),
+            settings: SharedSettings::new(RuntimeSettings::from_env()),
+        };
+        let status =
+            |state: AppState| async move { readyz(State(state)).await.into_response().status() };
+
+        assert_eq!(status(state.clone()).await, StatusCode::SERVICE_UNAVAILABLE);
+        state.readiness.mark_migrated();
+        assert_eq!(status(state.clone()).await, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!state.readiness.is_ready());
+        state.readiness.mark_local_source();
+        assert_eq!(status(state.clone()).await, StatusCode::OK);
+        assert!(state.readiness.is_ready());
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::SystemTime;
+
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use crate::mcp::McpError;
+
+/// Serializes `payload` as a JSON response carrying a weak `ETag` (a hash of
+/// the body) and, when known, a `Last-Modified` of the newest row it contains.
+/// Matching `If-None-Match` / `If-Modified-Since` validators short-circuit to
+/// `304 Not Modified` without a body.
+///
+/// The ETag is weak because compression may re-encode the body. Deleting a row
+/// changes the ETag but not necessarily `Last-Modified`, so clients should
+/// prefer `If-None-Match`, which takes precedence when both are sent.
+pub fn conditional_json<T: Serialize>(
+    headers: &HeaderMap,
+    payload: &T,
+    last_modified: Option<OffsetDateTime>,
+) -> Result<Response, McpError> {
+    let body = serde_json::to_vec(payload)?;
+    let etag = format!("W/\"{}\"", &hex::encode(Sha256::digest(&body))[..32]);
+    // HTTP dates have whole-second precision.
+    let last_modified = last_modified
+        .and_then(|at| at.replace_nanosecond(0).ok())
+        .map(SystemTime::from);
+
+    let not_modified = match headers.get(header::IF_NONE_MATCH) {
+        Some(value) => etag_matches(value, &etag),
+        None => match (last_modified, if_modified_since(headers)) {
+            (Some(modified), Some(since)) => modified <= since,
+            _ => false,
+        },
+    };
+
+    let mut response = if not_modified {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+    };
+    let headers = response.headers_mut();
+    headers.insert(
+        header::ETAG,
+        HeaderValue::from_str(&etag).expect("hex etag"),
+    );
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("private, no-cache"),
+    );
+    if let Some(modified) = last_modified {
+        let value = httpdate::fmt_http_date(modified);
+        headers.insert(
+            header::LAST_MODIFIED,
+            HeaderValue::from_str(&value).expect("http date"),
+        );
+    }
+    Ok(response)
+}
+
+/// Latest of a set of RFC 3339 timestamps as stored in `updated_at` columns;
+/// unparsable values are skipped.
+pub fn latest_timestamp<'a>(values: impl IntoIterator<Item = &'a str>) -> Option<OffsetDateTime> {
+    values
+        .into_iter()
+        .filter_map(|value| OffsetDateTime::parse(value, &Rfc3339).ok())
+        .max()
+}
+
+fn etag_matches(header: &HeaderValue, etag: &str) -> bool {
+    let Ok(header) = header.to_str() else {
+        return false;
+    };
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    header
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(etag))
+}
+
+fn if_modified_since(headers: &HeaderMap) -> Option<SystemTime> {
+    let value = headers.get(header::IF_MODIFIED_SINCE)?.to_str().ok()?;
+    httpdate::parse_http_date(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response_for(headers: &HeaderMap) -> Response {
+        let last_modified = latest_timestamp(["2024-01-01T00:00:00Z", "2024-01-02T03:04:05.678Z"]);
+        conditional_json(headers, &json!({"tools": []}), last_modified).unwrap()
+    }
+
+    #[test]
+    fn revalidates_with_etag_and_last_modified() {
+        let fresh = response_for(&HeaderMap::new());
+        assert_eq!(fresh.status(), StatusCode::OK);
+        let etag = fresh.headers()[header::ETAG].clone();
+        assert_eq!(
+            fresh.headers()[header::LAST_MODIFIED],
+            "Tue, 02 Jan 2024 03:04:05 GMT"
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, etag);
+        assert_eq!(response_for(&headers).status(), StatusCode::NOT_MODIFIED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"other\""));
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Tue, 02 Jan 2024 03:04:05 GMT"),
+        );
+        assert_eq!(response_for(&headers).status(), StatusCode::OK);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Tue, 02 Jan 2024 03:04:05 GMT"),
+        );
+        assert_eq!(response_for(&headers).status(), StatusCode::NOT_MODIFIED);
+    }
+}
@@ -0,0 +1,55 @@
+//! Deeting's MCP management: the SQLite-backed tool registry, the process
+//! manager that runs tools, and the source sync engine.
+//!
+//! With the default `server` feature this crate is also the desktop-backend
+//! HTTP service. Other applications can depend on it with
+//! `default-features = false` and embed the registry through [`Registry`]
+//! instead of talking to the service over HTTP:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), desktop_backend::McpError> {
+//! let registry = desktop_backend::Registry::open("sqlite::memory:").await?;
+//! let local = registry.store().ensure_local_source().await?;
+//! for tool in registry.sync_source(&local.id, None).await? {
+//!     registry.start_tool(&tool.id).await?;
+//! }
+//! registry.shutdown(std::time::Duration::from_secs(5)).await;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod mcp;
+pub mod paths;
+mod registry;
+
+#[cfg(feature = "server")]
+pub mod admin;
+#[cfg(feature = "server")]
+pub mod api_version;
+#[cfg(feature = "server")]
+pub mod auth;
+#[cfg(feature = "server")]
+pub mod chat;
+#[cfg(feature = "server")]
+pub mod cors;
+#[cfg(feature = "server")]
+pub mod health;
+#[cfg(feature = "server")]
+mod http_cache;
+#[cfg(feature = "server")]
+pub mod listener;
+#[cfg(feature = "server")]
+pub mod metrics;
+#[cfg(feature = "server")]
+mod rate_limit;
+#[cfg(feature = "server")]
+pub mod request_log;
+#[cfg(feature = "server")]
+pub mod settings;
+#[cfg(feature = "server")]
+pub mod state;
+#[cfg(feature = "server")]
+pub mod telemetry;
+
+pub use mcp::{McpError, McpEvent, McpStore, ProcessManager};
+pub use registry::Registry;
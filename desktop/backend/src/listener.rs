@@ -0,0 +1,208 @@
+use std::future::Future;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use axum::Router;
+use tracing::info;
+
+/// Where the HTTP server accepts connections.
+pub enum ListenTarget {
+    Tcp(SocketAddr),
+    /// HTTPS on `addr` with a PEM certificate chain and private key, so remote
+    /// deployments do not send tool configs and tokens in cleartext.
+    Tls {
+        addr: SocketAddr,
+        cert_path: PathBuf,
+        key_path: PathBuf,
+    },
+    /// Unix domain socket path, for local clients (the Tauri bridge) that
+    /// should not need a network port at all.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl ListenTarget {
+    /// `LISTEN_SOCKET` selects a Unix domain socket; otherwise the server
+    /// binds `{DESKTOP_BIND_ADDR}:{port}` (loopback unless set), serving HTTPS
+    /// when both `DESKTOP_TLS_CERT` and `DESKTOP_TLS_KEY` are set.
+    pub fn from_env(port: u16) -> anyhow::Result<Self> {
+        let tls = match (env_path("DESKTOP_TLS_CERT"), env_path("DESKTOP_TLS_KEY")) {
+            (Some(cert_path), Some(key_path)) => Some((cert_path, key_path)),
+            (None, None) => None,
+            _ => anyhow::bail!("DESKTOP_TLS_CERT and DESKTOP_TLS_KEY must be set together"),
+        };
+        match std::env::var("LISTEN_SOCKET") {
+            Ok(path) if !path.trim().is_empty() && tls.is_some() => {
+                anyhow::bail!(
+                    "DESKTOP_TLS_CERT/DESKTOP_TLS_KEY cannot be combined with LISTEN_SOCKET"
+                )
+            }
+            Ok(path) if !path.trim().is_empty() => {
+                #[cfg(unix)]
+                {
+                    Ok(Self::Unix(PathBuf::from(path.trim())))
+                }
+                #[cfg(not(unix))]
+                {
+                    anyhow::bail!("LISTEN_SOCKET={path} is only supported on unix platforms")
+                }
+            }
+            _ => {
+                let addr = SocketAddr::new(bind_ip()?, port);
+                Ok(match tls {
+                    Some((cert_path, key_path)) => Self::Tls {
+                        addr,
+                        cert_path,
+                        key_path,
+                    },
+                    None => Self::Tcp(addr),
+                })
+            }
+        }
+    }
+}
+
+/// Wider binds still need a token: `auth::require_token` only serves
+/// loopback clients while none is configured.
+fn bind_ip() -> anyhow::Result<IpAddr> {
+    match std::env::var("DESKTOP_BIND_ADDR") {
+        Ok(value) if !value.trim().is_empty() => value
+            .trim()
+            .parse()
+            .map_err(|_| anyhow::anyhow!("DESKTOP_BIND_ADDR={value} is not an IP address")),
+        _ => Ok(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+    }
+}
+
+fn env_path(key: &str) -> Option<PathBuf> {
+    std::env::var(key)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+pub async fn serve(
+    router: Router,
+    target: ListenTarget,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> anyhow::Result<()> {
+    match target {
+        ListenTarget::Tcp(addr) => {
+            info!("desktop-backend listening on http://{}", addr);
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(shutdown)
+            .await?;
+        }
+        ListenTarget::Tls {
+            addr,
+            cert_path,
+            key_path,
+        } => serve_tls(router, addr, cert_path, key_path, shutdown).await?,
+        #[cfg(unix)]
+        ListenTarget::Unix(path) => serve_unix(router, path, shutdown).await?,
+    }
+    Ok(())
+}
+
+/// How long in-flight HTTPS requests may take to finish after a shutdown
+/// signal; open SSE and WebSocket streams would otherwise hold it forever.
+const TLS_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+async fn serve_tls(
+    router: Router,
+    addr: SocketAddr,
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use axum_server::tls_rustls::RustlsConfig;
+
+    // `ServerConfig::builder()` needs a process-wide crypto provider and only
+    // the ring backend is compiled in.
+    let _ = rustls::crypto::ring::default_provider().install_default();
+    let config = RustlsConfig::from_pem_file(&cert_path, &key_path)
+        .await
+        .with_context(|| {
+            format!(
+                "failed to load TLS certificate {} / key {}",
+                cert_path.display(),
+                key_path.display()
+            )
+        })?;
+
+    let handle = axum_server::Handle::new();
+    let shutdown_handle = handle.clone();
+    tokio::spawn(async move {
+        shutdown.await;
+        shutdown_handle.graceful_shutdown(Some(TLS_SHUTDOWN_GRACE));
+    });
+
+    info!("desktop-backend listening on https://{}", addr);
+    axum_server::bind_rustls(addr, config)
+        .handle(handle)
+        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
+        .await?;
+    Ok(())
+}
+
+/// Serves `router` on a Unix domain socket. A stale socket file left by a
+/// previous run is replaced, and the socket is restricted to the current user
+/// since it carries the same API as the TCP listener.
+#[cfg(unix)]
+async fn serve_unix(
+    router: Router,
+    path: PathBuf,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> anyhow::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    use hyper_util::rt::{TokioExecutor, TokioIo};
+    use hyper_util::server::conn::auto;
+    use hyper_util::service::TowerToHyperService;
+    use tracing::debug;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match std::fs::remove_file(&path) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err.into()),
+    }
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    info!("desktop-backend listening on unix:{}", path.display());
+
+    tokio::pin!(shutdown);
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    debug!("unix socket accept failed: {}", err);
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        let service = TowerToHyperService::new(router.clone());
+        tokio::spawn(async move {
+            if let Err(err) = auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("unix socket connection closed: {}", err);
+            }
+        });
+    }
+
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
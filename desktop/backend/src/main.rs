@@ -1,5 +1,3 @@
-use std::{net::SocketAddr, path::PathBuf};
-
 use axum::{
     extract::State,
     http::StatusCode,
@@ -7,25 +5,29 @@ use axum::{
     routing::{get, Router},
     Json,
 };
-use serde::Serialize;
-use tracing::{info, Level};
-use tracing_subscriber::EnvFilter;
-
-mod mcp;
-mod state;
-
-use crate::state::AppState;
+use tracing::{info, warn};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use desktop_backend::state::AppState;
+use desktop_backend::{
+    admin, api_version, auth, chat, cors, health, listener, mcp, metrics, paths, request_log,
+    settings, telemetry,
+};
 
-#[derive(Serialize)]
-struct HealthPayload {
-    status: &'static str,
-    uptime_ms: u128,
-}
+/// How long managed tools get to exit after SIGTERM when the backend stops.
+const TOOL_STOP_GRACE: std::time::Duration = std::time::Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    settings::capture_inherited_env();
     dotenvy::dotenv().ok();
-    init_tracing();
+    // Held to the end of `main` so queued spans are flushed on shutdown.
+    let _telemetry = init_tracing();
+    health::init_start_time();
 
     let port = std::env::var("PORT")
         .ok()
@@ -34,28 +36,116 @@ async fn main() -> anyhow::Result<()> {
 
     let database_url = resolve_database_url()?;
     let store = std::sync::Arc::new(mcp::McpStore::new(&database_url).await?);
-    store.init().await?;
-    let _ = store.ensure_local_source().await?;
 
     let state = AppState {
         version: env!("CARGO_PKG_VERSION"),
         store: store.clone(),
-        process_manager: mcp::ProcessManager::new(store),
+        process_manager: mcp::ProcessManager::new(store.clone()),
+        providers: Default::default(),
+        auth: auth::AuthConfig::from_env(),
+        http: reqwest::Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .build()?,
+        readiness: health::Readiness::default(),
+        settings: settings::SharedSettings::new(settings::RuntimeSettings::from_env()),
     };
+    state
+        .process_manager
+        .set_log_buffer_size(state.settings.current().log_buffer_size)
+        .await;
+    state
+        .process_manager
+        .set_log_ansi(state.settings.current().log_ansi);
+    state
+        .process_manager
+        .set_log_memory_budget(state.settings.current().log_memory_mb << 20)
+        .await;
+    state
+        .process_manager
+        .set_log_dedup_window(std::time::Duration::from_millis(
+            state.settings.current().log_dedup_ms,
+        ));
+    state
+        .process_manager
+        .set_env_strict(state.settings.current().env_strict);
+    tokio::spawn(metrics::record_events(state.store.subscribe_events()));
+    tokio::spawn(mcp::webhooks::deliver_events(
+        state.store.clone(),
+        state.http.clone(),
+        state.store.subscribe_events(),
+    ));
+    #[cfg(unix)]
+    tokio::spawn(settings::reload_on_sighup(state.clone()));
+    tokio::spawn(mcp::schedule::run(state.clone()));
+    let process_manager = state.process_manager.clone();
+    let api = Router::new()
+        .nest("/mcp", mcp::routes::router())
+        .nest("/assistants", mcp::routes::assistants_router())
+        .nest("/chat", chat::router())
+        .nest("/admin", admin::router())
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_token,
+        ))
+        .route_layer(axum::middleware::from_fn(api_version::negotiate))
+        // Outermost, so nothing touches the database before it is migrated.
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            health::require_ready,
+        ));
+    // The unversioned paths predate `/api/v1` and stay as deprecated aliases.
+    let protected = Router::new()
+        .nest(api_version::API_PREFIX, api.clone())
+        .merge(api.route_layer(axum::middleware::from_fn(
+            api_version::deprecated_alias,
+        )));
     let router = Router::new()
         .route("/", get(root))
-        .route("/healthz", get(healthz))
+        .route("/livez", get(health::livez))
+        .route("/readyz", get(health::readyz))
+        // Kept for existing probes; same as `/livez`.
+        .route("/healthz", get(health::livez))
         .route("/version", get(version))
-        .nest("/mcp", mcp::routes::router())
-        .with_state(state);
-
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    info!("desktop-backend listening on http://{}", addr);
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>())
-    .with_graceful_shutdown(shutdown_signal())
-    .await?;
+        .merge(protected)
+        .route_layer(axum::middleware::from_fn(metrics::track_latency))
+        .route("/metrics", get(metrics::metrics_handler))
+        .merge(SwaggerUi::new("/docs").url("/openapi.json", mcp::openapi::ApiDoc::openapi()))
+        .layer(tower_http::compression::CompressionLayer::new())
+        .layer(cors::cors_layer_from_env())
+        .layer(axum::middleware::from_fn(request_log::track_request))
+        .with_state(state.clone());
+
+    let target = listener::ListenTarget::from_env(port)?;
+    let shutdown = async move {
+        shutdown_signal().await;
+        // Stop children before draining HTTP: open log streams can keep the
+        // drain waiting indefinitely.
+        info!("shutting down; stopping managed tools");
+        process_manager.shutdown_all(TOOL_STOP_GRACE).await;
+    };
+    // Bind first so `/livez` answers while migrations run; `/readyz` turns
+    // green and the API stops answering 503 once `prepare` has finished. A
+    // failed startup stops the server.
+    let serve = listener::serve(router, target, shutdown);
+    tokio::pin!(serve);
+    tokio::select! {
+        result = &mut serve => return result,
+        result = prepare(&state) => result?,
+    }
+    serve.await
+}
 
+/// Migrates the schema and seeds the local source, recording each step in
+/// `state.readiness`.
+async fn prepare(state: &AppState) -> anyhow::Result<()> {
+    state.store.init().await?;
+    state.readiness.mark_migrated();
+    let _ = state.store.ensure_local_source().await?;
+    state.readiness.mark_local_source();
+    if !state.auth.has_env_tokens() && !state.store.has_api_tokens().await? {
+        warn!("no API tokens configured; /mcp, /assistants, /chat and /admin only answer loopback clients");
+    }
+    info!("desktop-backend ready");
     Ok(())
 }
 
@@ -67,34 +157,48 @@ async fn version(State(state): State<AppState>) -> impl IntoResponse {
     Json(serde_json::json!({
         "service": "desktop-backend",
         "version": state.version,
+        "api_version": api_version::API_VERSION,
     }))
 }
 
-async fn healthz() -> impl IntoResponse {
-    // TODO: plug in real checks (local index, disk space, background workers)
-    let payload = HealthPayload {
-        status: "ok",
-        uptime_ms: app_start_time().elapsed().as_millis(),
-    };
-    (StatusCode::OK, Json(payload))
-}
-
-fn init_tracing() {
+/// `LOG_FORMAT=json` switches to one JSON object per line for Loki/ELK; event
+/// fields (`request_id`, `tool_id`, ...) become top-level keys and the
+/// enclosing `request` span is included. Anything else keeps the compact
+/// human format. Spans are also exported over OTLP when an
+/// `OTEL_EXPORTER_OTLP_*` endpoint is set; see [`telemetry::layer`].
+fn init_tracing() -> Option<telemetry::Telemetry> {
     let filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new("desktop_backend=info,axum=info"));
-    tracing_subscriber::fmt()
-        .with_max_level(Level::INFO)
-        .with_env_filter(filter)
-        .with_target(false)
-        .compact()
+    let format = std::env::var("LOG_FORMAT").unwrap_or_default();
+    let format = format.trim().to_ascii_lowercase();
+    let output = tracing_subscriber::fmt::layer();
+    let output = match format.as_str() {
+        "json" => output
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+        _ => output.with_target(false).compact().boxed(),
+    };
+    let (export, telemetry, export_error) = match telemetry::layer() {
+        Ok(Some((layer, telemetry))) => (Some(layer), Some(telemetry), None),
+        Ok(None) => (None, None, None),
+        Err(err) => (None, None, Some(err)),
+    };
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(output)
+        .with(export)
         .init();
-}
 
-static APP_START: once_cell::sync::Lazy<std::time::Instant> =
-    once_cell::sync::Lazy::new(std::time::Instant::now);
-
-fn app_start_time() -> &'static std::time::Instant {
-    &APP_START
+    if !format.is_empty() && format != "json" && format != "compact" {
+        warn!("unknown LOG_FORMAT {format:?}; using compact");
+    }
+    if let Some(err) = export_error {
+        warn!("trace export disabled: {err}");
+    }
+    telemetry
 }
 
 async fn shutdown_signal() {
@@ -121,32 +225,26 @@ async fn shutdown_signal() {
 }
 
 fn resolve_database_url() -> anyhow::Result<String> {
-    let db_path = std::env::var("DESKTOP_DB_PATH").unwrap_or_else(|_| default_db_path());
+    let db_path = match std::env::var("DESKTOP_DB_PATH") {
+        Ok(db_path) => db_path,
+        Err(_) => {
+            let default = paths::default_db_path();
+            if let Err(err) = paths::migrate_legacy_db(&default) {
+                warn!(error = %err, "failed to move the database from its legacy location");
+            }
+            default.to_string_lossy().into_owned()
+        }
+    };
     if db_path == ":memory:" {
         return Ok("sqlite::memory:".to_string());
     }
     if db_path.starts_with("sqlite:") {
         return Ok(db_path);
     }
-    let expanded = expand_path(&db_path);
+    let expanded = paths::expand_path(&db_path);
     if let Some(parent) = expanded.parent() {
         std::fs::create_dir_all(parent)?;
     }
-    Ok(format!("sqlite://{}", expanded.to_string_lossy()))
-}
-
-fn default_db_path() -> String {
-    if let Ok(home) = std::env::var("HOME") {
-        return format!("{home}/.config/deeting/mcp.db");
-    }
-    "mcp.db".to_string()
-}
-
-fn expand_path(path: &str) -> PathBuf {
-    if let Some(stripped) = path.strip_prefix("~/") {
-        if let Ok(home) = std::env::var("HOME") {
-            return PathBuf::from(home).join(stripped);
-        }
-    }
-    PathBuf::from(path)
+    // `mode=rwc` creates the file, e.g. in a fresh portable data directory.
+    Ok(format!("sqlite://{}?mode=rwc", expanded.to_string_lossy()))
 }
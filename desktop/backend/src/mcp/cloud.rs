@@ -0,0 +1,284 @@
+use std::collections::HashSet;
+
+use mcp_core::hash::hash_json;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::mcp::sync::is_absorbed_by_merge;
+use crate::mcp::{
+    McpConflictStatus, McpError, McpSource, McpSourceType, McpTool, McpToolStatus, ToolUpsert,
+};
+use crate::state::AppState;
+
+/// Status message stamped on tools whose subscription disappeared upstream.
+const SUBSCRIPTION_REMOVED: &str = "cloud subscription removed";
+
+#[derive(Debug, Deserialize)]
+pub struct CloudSubscriptionItem {
+    pub tool: CloudToolSummary,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloudToolSummary {
+    pub identifier: String,
+    pub name: String,
+    pub description: String,
+    pub avatar_url: Option<String>,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub author: Option<String>,
+    pub is_official: Option<bool>,
+    pub install_manifest: CloudInstallManifest,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CloudInstallManifest {
+    pub runtime: Option<String>,
+    pub command: String,
+    pub args: Vec<String>,
+    pub env_config: Option<Vec<Map<String, Value>>>,
+}
+
+/// Result of reconciling a cloud source with its subscriptions.
+pub struct CloudSyncOutcome {
+    pub tools: Vec<McpTool>,
+    /// Ids of tools whose subscription is gone; they are kept as `orphaned`.
+    pub orphaned: Vec<String>,
+}
+
+/// Pulls `GET {base_url}/api/v1/mcp/subscriptions` and reconciles every item
+/// with the tools of `source`: new subscriptions are installed, changed
+/// manifests are staged as pending updates and tools without a subscription
+/// are marked orphaned rather than deleted.
+///
+/// A response matching the last one applied (by `ETag`, or by body hash when
+/// the server sends none) is not reconciled again.
+#[tracing::instrument(name = "source.sync_cloud", skip_all, fields(source_id = %source.id))]
+pub async fn sync_cloud_subscriptions(
+    state: &AppState,
+    source: &McpSource,
+    access_token: &str,
+) -> Result<CloudSyncOutcome, McpError> {
+    let url = format!(
+        "{}/api/v1/mcp/subscriptions",
+        source.path_or_url.trim_end_matches('/')
+    );
+    let mut request = state.http.get(&url).bearer_auth(access_token);
+    if let Some(etag) = state
+        .store
+        .applied_config(&source.id)
+        .filter(|applied| is_entity_tag(applied))
+    {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|err| McpError::Upstream(err.to_string()))?;
+    match response.status() {
+        StatusCode::NOT_MODIFIED => return unchanged(state, source).await,
+        status if status.is_success() => {}
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            return Err(McpError::Unauthorized(
+                "cloud session rejected; sign in again".to_string(),
+            ));
+        }
+        status => {
+            return Err(McpError::Upstream(format!("cloud sync failed: {status}")));
+        }
+    }
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_string);
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|err| McpError::Upstream(err.to_string()))?;
+    let fingerprint = match etag {
+        Some(etag) => etag,
+        None => hash_json(&body)?,
+    };
+    if state.store.is_config_applied(&source.id, &fingerprint) {
+        return unchanged(state, source).await;
+    }
+    let subscriptions: Vec<CloudSubscriptionItem> =
+        serde_json::from_value(body).map_err(|err| McpError::Upstream(err.to_string()))?;
+    state.store.forget_applied_config(&source.id);
+
+    let mut seen = HashSet::new();
+    for subscription in &subscriptions {
+        seen.insert(subscription.tool.identifier.clone());
+        apply_cloud_subscription(state, source, subscription).await?;
+    }
+
+    let mut orphaned = Vec::new();
+    for tool in state.store.list_tools_by_source(&source.id).await? {
+        let Some(identifier) = tool.identifier.as_deref() else {
+            continue;
+        };
+        if seen.contains(identifier) || tool.status == McpToolStatus::Orphaned {
+            continue;
+        }
+        let _ = state.process_manager.stop_tool(&tool.id).await;
+        state
+            .store
+            .set_tool_status(
+                &tool.id,
+                McpToolStatus::Orphaned,
+                None,
+                Some(SUBSCRIPTION_REMOVED.to_string()),
+            )
+            .await?;
+        state
+            .process_manager
+            .log_event(&tool.id, SUBSCRIPTION_REMOVED.to_string())
+            .await;
+        orphaned.push(tool.id);
+    }
+
+    state.store.record_applied_config(&source.id, fingerprint);
+    Ok(CloudSyncOutcome {
+        tools: state.store.list_tools_by_source(&source.id).await?,
+        orphaned,
+    })
+}
+
+async fn unchanged(state: &AppState, source: &McpSource) -> Result<CloudSyncOutcome, McpError> {
+    Ok(CloudSyncOutcome {
+        tools: state.store.list_tools_by_source(&source.id).await?,
+        orphaned: Vec::new(),
+    })
+}
+
+/// Body hashes are bare hex; only real entity tags (`"…"`, `W/"…"`) are
+/// sent back as `If-None-Match`.
+fn is_entity_tag(value: &str) -> bool {
+    value.starts_with('"') || value.starts_with("W/\"")
+}
+
+/// Installs a subscription that has no local row yet, or stages its manifest
+/// as a pending update when it differs from the installed config.
+async fn apply_cloud_subscription(
+    state: &AppState,
+    source: &McpSource,
+    subscription: &CloudSubscriptionItem,
+) -> Result<(), McpError> {
+    let tool = &subscription.tool;
+    let config_value = build_cloud_config_json(tool);
+    let config_hash = state.store.compute_config_hash(&config_value)?;
+    let config_json = serde_json::to_string(&config_value)?;
+    let name_conflict = state
+        .store
+        .has_name_conflict(&tool.name, &source.id)
+        .await?;
+
+    let existing = state
+        .store
+        .get_tool_by_source_identifier(&source.id, &tool.identifier)
+        .await?;
+    match existing {
+        Some(existing) => {
+            let unchanged = existing.config_hash == config_hash
+                || is_absorbed_by_merge(&state.store, &existing, &config_value).await?;
+            if !unchanged {
+                let conflict_status = if name_conflict {
+                    McpConflictStatus::Conflict
+                } else {
+                    McpConflictStatus::UpdateAvailable
+                };
+                state
+                    .store
+                    .mark_tool_pending_update(
+                        &existing.id,
+                        config_json,
+                        config_hash,
+                        conflict_status,
+                    )
+                    .await?;
+            }
+            // Re-subscribed after being orphaned: bring it back as stopped.
+            if existing.status == McpToolStatus::Orphaned {
+                state
+                    .store
+                    .set_tool_status(&existing.id, McpToolStatus::Stopped, None, None)
+                    .await?;
+            }
+        }
+        None => {
+            state
+                .store
+                .upsert_tool(ToolUpsert {
+                    id: None,
+                    source_id: source.id.clone(),
+                    identifier: Some(tool.identifier.clone()),
+                    name: tool.name.clone(),
+                    source_type: McpSourceType::Cloud,
+                    status: McpToolStatus::Stopped,
+                    ping_ms: None,
+                    capabilities: vec![],
+                    description: tool.description.clone(),
+                    error: None,
+                    command: Some(tool.install_manifest.command.clone()),
+                    args: Some(tool.install_manifest.args.clone()),
+                    env: None,
+                    config_json,
+                    config_hash,
+                    pending_config_json: None,
+                    pending_config_hash: None,
+                    conflict_status: if name_conflict {
+                        McpConflictStatus::Conflict
+                    } else {
+                        McpConflictStatus::None
+                    },
+                    is_read_only: true,
+                    is_new: true,
+                })
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Canonical config stored for a cloud tool; its hash drives update detection.
+fn build_cloud_config_json(tool: &CloudToolSummary) -> Value {
+    let manifest = &tool.install_manifest;
+    let mut config = json!({
+        "identifier": tool.identifier,
+        "name": tool.name,
+        "description": tool.description,
+        "command": manifest.command,
+        "args": manifest.args,
+    });
+    let optional = [
+        (
+            "runtime",
+            manifest.runtime.as_ref().map(|runtime| json!(runtime)),
+        ),
+        (
+            "env_config",
+            manifest.env_config.as_ref().map(|env| json!(env)),
+        ),
+        ("tags", tool.tags.as_ref().map(|tags| json!(tags))),
+        (
+            "category",
+            tool.category.as_ref().map(|category| json!(category)),
+        ),
+        ("author", tool.author.as_ref().map(|author| json!(author))),
+        (
+            "is_official",
+            tool.is_official.map(|official| json!(official)),
+        ),
+        ("avatar_url", tool.avatar_url.as_ref().map(|url| json!(url))),
+    ];
+    for (key, value) in optional {
+        if let Some(value) = value {
+            config[key] = value;
+        }
+    }
+    config
+}
@@ -0,0 +1,66 @@
+use serde::Serialize;
+
+use super::types::{McpConflictStatus, McpSourceStatus, McpTool, McpToolStatus};
+
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Snapshot of the fields dashboards watch on a tool.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolStatusEvent {
+    pub tool_id: String,
+    pub status: McpToolStatus,
+    pub conflict_status: McpConflictStatus,
+    pub ping_ms: Option<i64>,
+    pub error: Option<String>,
+    pub updated_at: String,
+}
+
+impl From<&McpTool> for ToolStatusEvent {
+    fn from(tool: &McpTool) -> Self {
+        Self {
+            tool_id: tool.id.clone(),
+            status: tool.status.clone(),
+            conflict_status: tool.conflict_status.clone(),
+            ping_ms: tool.ping_ms,
+            error: tool.error.clone(),
+            updated_at: tool.updated_at.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceStatusEvent {
+    pub source_id: String,
+    pub status: McpSourceStatus,
+    pub last_synced_at: Option<String>,
+}
+
+/// Domain events on the store's broadcast channel. `McpStore` publishes the
+/// write-driven ones itself; the process manager and sync routes publish
+/// theirs through [`McpStore::publish`](super::store::McpStore::publish).
+/// The WebSocket and SSE routes and the metrics recorder all consume this
+/// one channel.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum McpEvent {
+    ToolStatus(ToolStatusEvent),
+    ToolRemoved {
+        tool_id: String,
+    },
+    /// A tool process exited with a non-zero code.
+    ToolCrashed {
+        tool_id: String,
+        exit_code: i32,
+    },
+    SourceStatus(SourceStatusEvent),
+    /// A source sync finished, whether or not it succeeded.
+    SourceSynced {
+        source_id: String,
+        success: bool,
+    },
+    /// A write left a tool with a conflict to resolve.
+    ConflictDetected {
+        tool_id: String,
+        conflict_status: McpConflictStatus,
+    },
+}
@@ -0,0 +1,6 @@
+pub use mcp_core::log_dedup::{repeated_message, Verdict, DEFAULT_LOG_DEDUP_WINDOW};
+
+use crate::mcp::McpLogStream;
+
+/// Coalesces repeated lines per tool and stream.
+pub type LineDedup = mcp_core::log_dedup::LineDedup<McpLogStream>;
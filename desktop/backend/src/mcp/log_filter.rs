@@ -0,0 +1,147 @@
+use mcp_core::log_filter::TextFilter;
+
+use crate::mcp::{LogFilterQuery, McpError, McpLogEntry, McpLogLevel, McpLogStream};
+
+/// A validated `LogFilterQuery`, ready to test entries against.
+pub struct LogFilter {
+    before: Option<u64>,
+    stream: Option<McpLogStream>,
+    level: Option<McpLogLevel>,
+    text: TextFilter,
+}
+
+impl LogFilter {
+    pub fn new(query: &LogFilterQuery) -> Result<Self, McpError> {
+        let text = TextFilter::new(
+            query.contains.as_deref(),
+            query.regex.as_deref(),
+            query.since.as_deref(),
+        )
+        .map_err(McpError::Validation)?;
+        Ok(Self {
+            before: query.before,
+            stream: query.stream.clone(),
+            level: query.level,
+            text,
+        })
+    }
+
+    pub fn matches(&self, entry: &McpLogEntry) -> bool {
+        if self.before.is_some_and(|before| entry.seq >= before) {
+            return false;
+        }
+        if self.stream.as_ref().is_some_and(|stream| *stream != entry.stream) {
+            return false;
+        }
+        if self.level.is_some_and(|level| entry.level < level) {
+            return false;
+        }
+        self.text.matches(&entry.message, &entry.timestamp)
+    }
+
+    /// Keeps the matching entries, then the newest `limit` of them. The flag
+    /// is set when older matching entries were cut off.
+    pub fn apply(
+        &self,
+        entries: Vec<McpLogEntry>,
+        limit: Option<usize>,
+    ) -> (Vec<McpLogEntry>, bool) {
+        let mut matched: Vec<McpLogEntry> =
+            entries.into_iter().filter(|entry| self.matches(entry)).collect();
+        let excess = limit.map_or(0, |limit| matched.len().saturating_sub(limit));
+        matched.drain(..excess);
+        (matched, excess > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::log_level;
+
+    fn entry(seq: u64, timestamp: &str, stream: McpLogStream, message: &str) -> McpLogEntry {
+        McpLogEntry {
+            seq,
+            timestamp: timestamp.to_string(),
+            stream: stream.clone(),
+            message: message.to_string(),
+            level: log_level::infer(&stream, message, None),
+            structured: None,
+        }
+    }
+
+    fn entries() -> Vec<McpLogEntry> {
+        vec![
+            entry(1, "2026-01-01T00:00:00Z", McpLogStream::Stdout, "listening on 3000"),
+            entry(2, "2026-01-01T00:00:01.5Z", McpLogStream::Stderr, "ERROR: token expired"),
+            entry(3, "2026-01-01T00:00:02Z", McpLogStream::Stderr, "error: retry 2"),
+            entry(4, "2026-01-01T00:00:03Z", McpLogStream::Event, "process exited"),
+        ]
+    }
+
+    fn messages(filter: LogFilterQuery) -> Vec<String> {
+        page(filter).0
+    }
+
+    fn page(filter: LogFilterQuery) -> (Vec<String>, bool) {
+        let limit = filter.limit;
+        let (entries, has_more) = LogFilter::new(&filter).unwrap().apply(entries(), limit);
+        (entries.into_iter().map(|entry| entry.message).collect(), has_more)
+    }
+
+    #[test]
+    fn combines_stream_text_and_time_filters() {
+        let errors = messages(LogFilterQuery {
+            stream: Some(McpLogStream::Stderr),
+            contains: Some("Error".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(errors, ["ERROR: token expired", "error: retry 2"]);
+
+        let recent = messages(LogFilterQuery {
+            since: Some("2026-01-01T00:00:01Z".to_string()),
+            regex: Some(r"retry \d|exited".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(recent, ["process exited"]);
+
+        let problems = messages(LogFilterQuery {
+            level: Some(McpLogLevel::Warn),
+            ..Default::default()
+        });
+        assert_eq!(problems, ["ERROR: token expired", "error: retry 2"]);
+    }
+
+    #[test]
+    fn pages_back_from_a_cursor() {
+        let (tail, has_more) = page(LogFilterQuery {
+            limit: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(tail, ["error: retry 2", "process exited"]);
+        assert!(has_more);
+
+        let (older, has_more) = page(LogFilterQuery {
+            before: Some(3),
+            limit: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(older, ["listening on 3000", "ERROR: token expired"]);
+        assert!(!has_more);
+    }
+
+    #[test]
+    fn rejects_invalid_regex_and_timestamp() {
+        let bad_regex = LogFilterQuery {
+            regex: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(LogFilter::new(&bad_regex), Err(McpError::Validation(_))));
+        let bad_since = LogFilterQuery {
+            since: Some("yesterday".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(LogFilter::new(&bad_since), Err(McpError::Validation(_))));
+    }
+}
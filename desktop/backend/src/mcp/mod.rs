@@ -1,17 +1,45 @@
-pub mod hash;
+#[cfg(feature = "server")]
+pub mod cloud;
+pub mod events;
+pub mod json_log;
+pub mod log_dedup;
+pub mod log_filter;
+pub mod log_level;
+#[cfg(feature = "server")]
+pub mod openapi;
 pub mod process;
+pub mod providers;
+#[cfg(feature = "server")]
 pub mod routes;
+#[cfg(feature = "server")]
+pub mod schema;
+#[cfg(feature = "server")]
+pub mod schedule;
+pub mod session_cipher;
 pub mod store;
+pub mod sync;
 pub mod types;
+pub mod webhooks;
+#[cfg(feature = "server")]
+pub mod ws;
 
+#[cfg(feature = "server")]
 use axum::http::StatusCode;
+#[cfg(feature = "server")]
 use axum::response::{IntoResponse, Response};
+#[cfg(feature = "server")]
 use serde::Serialize;
+use mcp_core::env::EnvError;
+use serde_json::{json, Value};
 use thiserror::Error;
+#[cfg(feature = "server")]
+use utoipa::ToSchema;
 
-pub use store::{ExtractedToolFields, McpStore, NewSource, ToolUpsert};
+pub use events::McpEvent;
+pub use store::{ExtractedToolFields, McpStore, NewSource, SyncWrite, ToolUpsert};
 pub use types::*;
 pub use process::ProcessManager;
+pub use providers::{FetchedConfig, SourceProvider, SourceProviders};
 
 #[derive(Debug, Error)]
 pub enum McpError {
@@ -27,31 +55,111 @@ pub enum McpError {
     Validation(String),
     #[error("not found: {0}")]
     NotFound(String),
+    #[error("not found: tool {0} not found")]
+    ToolNotFound(String),
+    #[error("not found: source {0} not found")]
+    SourceNotFound(String),
+    #[error("validation error: missing required env: {}", .0.join(", "))]
+    EnvMissing(Vec<String>),
     #[error("process error: {0}")]
     Process(String),
+    #[error("process error: tool {0} already running")]
+    ToolAlreadyRunning(String),
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+    #[error("upstream error: {0}")]
+    Upstream(String),
+    #[error("unavailable: {0}")]
+    Unavailable(String),
 }
 
 impl McpError {
     pub fn validation(message: String) -> Self {
         McpError::Validation(message)
     }
+
+    /// Stable identifier clients localize; never reworded once shipped.
+    pub fn code(&self) -> &'static str {
+        match self {
+            McpError::Database(_) => "DATABASE_ERROR",
+            McpError::Serialization(_) => "SERIALIZATION_ERROR",
+            McpError::Time(_) | McpError::Io(_) => "INTERNAL_ERROR",
+            McpError::Validation(_) => "VALIDATION_FAILED",
+            McpError::NotFound(_) => "NOT_FOUND",
+            McpError::ToolNotFound(_) => "TOOL_NOT_FOUND",
+            McpError::SourceNotFound(_) => "SOURCE_NOT_FOUND",
+            McpError::EnvMissing(_) => "ENV_MISSING",
+            McpError::Process(_) => "PROCESS_ERROR",
+            McpError::ToolAlreadyRunning(_) => "TOOL_ALREADY_RUNNING",
+            McpError::Unauthorized(_) => "UNAUTHORIZED",
+            McpError::RateLimited(_) => "RATE_LIMITED",
+            McpError::Upstream(_) => "UPSTREAM_ERROR",
+            McpError::Unavailable(_) => "SERVICE_UNAVAILABLE",
+        }
+    }
+
+    /// Structured context for [`Self::code`], e.g. the missing env keys.
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            McpError::ToolNotFound(id) | McpError::ToolAlreadyRunning(id) => {
+                Some(json!({ "tool_id": id }))
+            }
+            McpError::SourceNotFound(id) => Some(json!({ "source_id": id })),
+            McpError::EnvMissing(keys) => Some(json!({ "missing": keys })),
+            _ => None,
+        }
+    }
+}
+
+impl From<EnvError> for McpError {
+    fn from(err: EnvError) -> Self {
+        match err {
+            EnvError::Invalid(message) => McpError::Validation(message),
+            EnvError::Missing(keys) => McpError::EnvMissing(keys),
+        }
+    }
 }
 
-#[derive(Serialize)]
-struct ErrorResponse {
-    error: String,
+/// Error envelope for every non-2xx response.
+#[cfg(feature = "server")]
+#[derive(Serialize, ToSchema)]
+pub struct ErrorResponse {
+    /// Stable machine-readable code, e.g. `TOOL_NOT_FOUND` or `ENV_MISSING`.
+    code: String,
+    message: String,
+    /// Structured context for the code, e.g. `{"missing": ["API_KEY"]}`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    details: Option<Value>,
+    /// Matches the `x-request-id` response header and the server log line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
 }
 
+#[cfg(feature = "server")]
 impl IntoResponse for McpError {
     fn into_response(self) -> Response {
-        let (status, message) = match &self {
-            McpError::Validation(_) => (StatusCode::BAD_REQUEST, self.to_string()),
-            McpError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
-            McpError::Process(_) => (StatusCode::CONFLICT, self.to_string()),
-            _ => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+        let status = match &self {
+            McpError::Validation(_) | McpError::EnvMissing(_) => StatusCode::BAD_REQUEST,
+            McpError::NotFound(_) | McpError::ToolNotFound(_) | McpError::SourceNotFound(_) => {
+                StatusCode::NOT_FOUND
+            }
+            McpError::Process(_) | McpError::ToolAlreadyRunning(_) => StatusCode::CONFLICT,
+            McpError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            McpError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            McpError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            McpError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
 
-        let body = axum::Json(ErrorResponse { error: message });
+        let body = axum::Json(ErrorResponse {
+            code: self.code().to_string(),
+            message: self.to_string(),
+            details: self.details(),
+            request_id: crate::request_log::current_request_id(),
+        });
         (status, body).into_response()
     }
 }
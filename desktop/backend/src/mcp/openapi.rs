@@ -0,0 +1,82 @@
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use super::routes;
+
+/// Generated contract for the `/mcp`, `/assistants`, `/chat` and `/admin` APIs, served
+/// at `/openapi.json` with a Swagger UI at `/docs`. Paths are relative to the
+/// `/api/v1` server; the unversioned aliases are deprecated and not listed.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "desktop-backend", description = "Local MCP registry and assistant API."),
+    servers((url = "/api/v1", description = "API v1")),
+    paths(
+        routes::list_sources,
+        routes::create_source,
+        routes::delete_source,
+        routes::sync_source,
+        routes::cloud_sync,
+        routes::list_tools,
+        routes::tool_events_stream,
+        routes::get_tool,
+        routes::delete_tool,
+        routes::import_config,
+        routes::import_config_file,
+        routes::validate_import,
+        routes::get_schema,
+        routes::start_tool,
+        routes::stop_tool,
+        routes::batch_tools,
+        routes::apply_pending_update,
+        routes::resolve_conflict,
+        routes::update_tool_env,
+        routes::update_tool_log_buffer,
+        routes::tool_logs,
+        routes::clear_tool_logs,
+        routes::tool_logs_stream,
+        routes::all_logs_stream,
+        routes::list_api_tokens,
+        routes::create_api_token,
+        routes::delete_api_token,
+        routes::list_webhooks,
+        routes::create_webhook,
+        routes::delete_webhook,
+        routes::list_assistants,
+        routes::create_assistant,
+        routes::get_assistant,
+        routes::update_assistant,
+        routes::delete_assistant,
+        routes::list_assistant_messages,
+        routes::append_assistant_message,
+        routes::clear_assistant_messages,
+        crate::chat::chat_complete,
+        crate::chat::chat_stream,
+        crate::admin::reload,
+        crate::admin::stats,
+    ),
+    modifiers(&BearerAuth),
+    security(("bearer" = [])),
+    tags(
+        (name = "sources", description = "MCP config sources"),
+        (name = "tools", description = "Installed MCP tools"),
+        (name = "logs", description = "Tool process logs"),
+        (name = "auth", description = "API tokens"),
+        (name = "webhooks", description = "Outbound event webhooks"),
+        (name = "assistants", description = "Local assistants and chat history"),
+        (name = "chat", description = "OpenAI-compatible chat completion"),
+        (name = "admin", description = "Runtime administration"),
+    )
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
@@ -1,18 +1,38 @@
 use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use tokio::io::{AsyncBufReadExt, BufReader};
+use mcp_core::ansi::AnsiMode;
+use mcp_core::health::{ProbeTracker, ProbeTransition};
+use mcp_core::interpolate::{builtin_vars, interpolate_command, interpolate_shell, Interpolated};
+use mcp_core::log_redact::Redactor;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Child;
 use tokio::sync::{broadcast, Mutex, RwLock};
-use tracing::warn;
+use tracing::{info, warn};
 
-use super::types::{McpLogEntry, McpLogStream, McpTool, McpToolStatus};
+#[cfg(feature = "server")]
+use crate::metrics::METRICS;
+
+use super::log_dedup::{self, LineDedup, Verdict};
+use super::{json_log, log_level};
+use super::events::McpEvent;
+use super::types::{
+    HealthCheck, McpLogEntry, McpLogStream, McpSourceType, McpTool, McpToolLogEntry, McpToolStatus,
+};
 use super::{McpError, McpStore};
 
-const DEFAULT_LOG_BUFFER_SIZE: usize = 1000;
+pub const DEFAULT_LOG_BUFFER_SIZE: usize = 1000;
+pub const DEFAULT_LOG_MEMORY_MB: usize = 64;
 const DEFAULT_BROADCAST_CAPACITY: usize = 512;
+/// The combined channel carries every tool's output, so it gets more slack
+/// before slow subscribers start lagging.
+const ALL_LOGS_BROADCAST_CAPACITY: usize = 2048;
+/// How long one pre-start or post-stop hook may run before it is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(600);
 
 #[derive(Clone)]
 pub struct ProcessManager {
@@ -20,39 +40,237 @@ pub struct ProcessManager {
     processes: Arc<RwLock<HashMap<String, ProcessHandle>>>,
     logs: Arc<RwLock<HashMap<String, LogBuffer>>>,
     broadcasters: Arc<RwLock<HashMap<String, broadcast::Sender<McpLogEntry>>>>,
-    log_buffer_size: usize,
+    all_logs: broadcast::Sender<McpToolLogEntry>,
+    shutting_down: Arc<AtomicBool>,
+    log_buffer_size: Arc<AtomicUsize>,
+    /// Per-tool `log_buffer_size` overrides.
+    tool_log_buffer_sizes: Arc<RwLock<HashMap<String, usize>>>,
+    /// Bytes all log buffers may hold together.
+    log_memory_budget: Arc<AtomicUsize>,
+    log_seq: Arc<AtomicU64>,
+    log_ansi: Arc<std::sync::RwLock<AnsiMode>>,
+    log_dedup: Arc<std::sync::Mutex<LineDedup>>,
+    redactors: Arc<std::sync::RwLock<HashMap<String, Redactor>>>,
+    /// Refuse to start a tool whose `${VAR}` references are unresolved.
+    env_strict: Arc<AtomicBool>,
 }
 
 impl ProcessManager {
     pub fn new(store: Arc<McpStore>) -> Self {
+        let (all_logs, _) = broadcast::channel(ALL_LOGS_BROADCAST_CAPACITY);
         Self {
             store,
             processes: Arc::new(RwLock::new(HashMap::new())),
             logs: Arc::new(RwLock::new(HashMap::new())),
             broadcasters: Arc::new(RwLock::new(HashMap::new())),
-            log_buffer_size: DEFAULT_LOG_BUFFER_SIZE,
+            all_logs,
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            log_buffer_size: Arc::new(AtomicUsize::new(DEFAULT_LOG_BUFFER_SIZE)),
+            tool_log_buffer_sizes: Arc::new(RwLock::new(HashMap::new())),
+            log_memory_budget: Arc::new(AtomicUsize::new(DEFAULT_LOG_MEMORY_MB << 20)),
+            log_seq: Arc::new(AtomicU64::new(0)),
+            log_ansi: Arc::new(std::sync::RwLock::new(AnsiMode::default())),
+            log_dedup: Arc::new(std::sync::Mutex::new(LineDedup::new(
+                log_dedup::DEFAULT_LOG_DEDUP_WINDOW,
+            ))),
+            redactors: Arc::new(std::sync::RwLock::new(HashMap::new())),
+            env_strict: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    pub async fn start_tool(&self, tool: McpTool) -> Result<(), McpError> {
-        let mut processes = self.processes.write().await;
-        if processes.contains_key(&tool.id) {
-            return Err(McpError::Process(format!(
-                "tool {} already running",
-                tool.id
-            )));
+    /// Changes how many lines each tool's log buffer keeps. Existing buffers
+    /// are resized in place, dropping their oldest lines when shrinking;
+    /// tools with their own `log_buffer_size` keep it.
+    pub async fn set_log_buffer_size(&self, size: usize) {
+        let size = size.max(1);
+        self.log_buffer_size.store(size, Ordering::SeqCst);
+        let overrides = self.tool_log_buffer_sizes.read().await;
+        for (tool_id, buffer) in self.logs.write().await.iter_mut() {
+            if !overrides.contains_key(tool_id) {
+                buffer.resize(size);
+            }
+        }
+    }
+
+    /// Gives one tool its own buffer size, or the global one again with
+    /// `None`, resizing its current buffer.
+    pub async fn set_tool_log_buffer_size(&self, tool_id: &str, size: Option<usize>) {
+        {
+            let mut overrides = self.tool_log_buffer_sizes.write().await;
+            match size {
+                Some(size) => overrides.insert(tool_id.to_string(), size.max(1)),
+                None => overrides.remove(tool_id),
+            };
         }
+        let capacity = self.log_capacity(tool_id).await;
+        if let Some(buffer) = self.logs.write().await.get_mut(tool_id) {
+            buffer.resize(capacity);
+        }
+    }
+
+    /// Caps the memory of all log buffers together; when over, the oldest
+    /// lines of the largest buffer go first, so one chatty tool cannot push
+    /// out everyone else's history. Storage freed by the trim is returned
+    /// to the allocator.
+    pub async fn set_log_memory_budget(&self, bytes: usize) {
+        self.log_memory_budget.store(bytes.max(1), Ordering::SeqCst);
+        let mut logs = self.logs.write().await;
+        enforce_memory_budget(&mut logs, bytes.max(1));
+        for buffer in logs.values_mut() {
+            buffer.entries.shrink_to_fit();
+        }
+    }
+
+    async fn log_capacity(&self, tool_id: &str) -> usize {
+        self.tool_log_buffer_sizes
+            .read()
+            .await
+            .get(tool_id)
+            .copied()
+            .unwrap_or_else(|| self.log_buffer_size.load(Ordering::SeqCst))
+    }
+
+    /// Changes how escape sequences in tool output are handled from the next
+    /// line on; lines already buffered are left as they are.
+    pub fn set_log_ansi(&self, mode: AnsiMode) {
+        *self.log_ansi.write().expect("ANSI mode lock poisoned") = mode;
+    }
+
+    /// Changes how long identical consecutive lines are coalesced; zero logs
+    /// every line.
+    pub fn set_log_dedup_window(&self, window: Duration) {
+        self.log_dedup
+            .lock()
+            .expect("log dedup lock poisoned")
+            .set_window(window);
+    }
+
+    /// With `strict`, starting a tool fails while its command, args or env
+    /// reference an unset variable; otherwise the reference is passed through
+    /// verbatim and a warning logged.
+    pub fn set_env_strict(&self, strict: bool) {
+        self.env_strict.store(strict, Ordering::SeqCst);
+    }
+
+    /// True once `shutdown_all` has begun; new starts are refused from then on.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    #[tracing::instrument(name = "tool.start", skip_all, fields(tool_id = %tool.id, tool_name = %tool.name))]
+    pub async fn start_tool(&self, tool: McpTool) -> Result<(), McpError> {
+        self.ensure_startable(&tool.id, &*self.processes.read().await)?;
 
         let command = tool
             .command
             .clone()
             .ok_or_else(|| McpError::Validation("missing command".to_string()))?;
 
-        let args = tool.args.clone().unwrap_or_default();
-        let mut cmd = tokio::process::Command::new(command);
-        cmd.args(args);
-        if let Some(env) = &tool.env {
-            cmd.envs(env);
+        let builtins = builtin_vars(
+            dirs::home_dir().as_deref(),
+            &crate::paths::config_dir(),
+            &tool.id,
+            self.workspace_dir(&tool).await.as_deref(),
+        );
+        let mut resolved = interpolate_command(
+            &command,
+            tool.args.as_deref().unwrap_or_default(),
+            tool.env.as_ref(),
+            tool.cwd.as_deref(),
+            &builtins,
+            |name| std::env::var(name).ok(),
+        );
+        let pre_start: Vec<String> = tool
+            .hooks
+            .pre_start
+            .iter()
+            .map(|hook| interpolate_hook(hook, &builtins, &mut resolved))
+            .collect();
+        let post_stop: Vec<String> = tool
+            .hooks
+            .post_stop
+            .iter()
+            .map(|hook| interpolate_hook(hook, &builtins, &mut resolved))
+            .collect();
+        let healthcheck = tool.healthcheck.clone().map(|mut check| {
+            if let Some(command) = &mut check.command {
+                *command = interpolate_hook(command, &builtins, &mut resolved);
+            }
+            for target in [&mut check.tcp, &mut check.http].into_iter().flatten() {
+                *target = interpolate_target(target, &builtins, &mut resolved);
+            }
+            check
+        });
+        if !resolved.unresolved.is_empty() {
+            if self.env_strict.load(Ordering::SeqCst) {
+                return Err(McpError::EnvMissing(resolved.unresolved));
+            }
+            warn!(
+                "unresolved variables passed through verbatim: {}",
+                resolved.unresolved.join(", ")
+            );
+        }
+        let tool_dir = resolved
+            .builtins
+            .iter()
+            .find(|(name, _)| *name == "TOOL_DIR");
+        if let Some((_, tool_dir)) = tool_dir {
+            tokio::fs::create_dir_all(tool_dir)
+                .await
+                .map_err(|err| McpError::Process(format!("cannot create {tool_dir}: {err}")))?;
+        }
+        let hook_env = Arc::new(HookEnv {
+            env: resolved.env.clone(),
+            cwd: resolved.cwd.as_deref().map(crate::paths::expand_path),
+        });
+
+        self.redactors
+            .write()
+            .expect("redactor lock poisoned")
+            .insert(tool.id.clone(), Redactor::for_env(Some(&resolved.env)));
+        let log_sender = self.ensure_broadcaster(&tool.id).await;
+        self.set_tool_log_buffer_size(&tool.id, tool.log_buffer_size).await;
+        self.ensure_log_buffer(&tool.id).await;
+        if !resolved.builtins.is_empty() {
+            let vars: Vec<String> = resolved
+                .builtins
+                .iter()
+                .map(|(name, value)| format!("{name}={value}"))
+                .collect();
+            let message = format!("template variables: {}", vars.join(", "));
+            self.emit_log(&tool.id, McpLogStream::Event, message, Some(&log_sender))
+                .await;
+        }
+
+        if !pre_start.is_empty() {
+            self.store
+                .set_tool_status(&tool.id, McpToolStatus::Starting, None, None)
+                .await?;
+            for hook in &pre_start {
+                if let Err(message) = self.run_hook(&tool.id, "pre-start", hook, &hook_env).await {
+                    self.store
+                        .set_tool_status(
+                            &tool.id,
+                            McpToolStatus::Error,
+                            None,
+                            Some(message.clone()),
+                        )
+                        .await?;
+                    return Err(McpError::Process(message));
+                }
+            }
+        }
+
+        // Taken only now so a slow pre-start hook does not hold up other
+        // tools; hence the second check.
+        let mut processes = self.processes.write().await;
+        self.ensure_startable(&tool.id, &processes)?;
+
+        let mut cmd = tokio::process::Command::new(&resolved.command);
+        cmd.args(&resolved.args);
+        cmd.envs(&resolved.env);
+        if let Some(cwd) = &hook_env.cwd {
+            cmd.current_dir(cwd);
         }
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped());
@@ -72,13 +290,12 @@ impl ProcessManager {
             tool.id.clone(),
             ProcessHandle {
                 child: child.clone(),
+                post_stop: Arc::new(post_stop),
+                hook_env: hook_env.clone(),
             },
         );
         drop(processes);
 
-        let log_sender = self.ensure_broadcaster(&tool.id).await;
-        self.ensure_log_buffer(&tool.id).await;
-
         if let Some(stdout) = stdout {
             let tool_id = tool.id.clone();
             let sender = log_sender.clone();
@@ -114,12 +331,137 @@ impl ProcessManager {
             .await?;
         self.emit_log(&tool.id, McpLogStream::Event, "process started".to_string(), None)
             .await;
+        info!(tool_id = %tool.id, tool_name = %tool.name, "tool process started");
 
+        if let Some(check) = healthcheck {
+            self.spawn_health_monitor(tool.id.clone(), child.clone(), check, hook_env);
+        }
         self.spawn_monitor(tool.id.clone(), child).await;
 
         Ok(())
     }
 
+    fn ensure_startable(
+        &self,
+        tool_id: &str,
+        processes: &HashMap<String, ProcessHandle>,
+    ) -> Result<(), McpError> {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return Err(McpError::Process("backend is shutting down".to_string()));
+        }
+        if processes.contains_key(tool_id) {
+            return Err(McpError::ToolAlreadyRunning(tool_id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Runs one hook through the shell, its output going to the tool's log.
+    /// Fails with a message for the log and the tool's error when the hook
+    /// cannot start, exits non-zero or outlasts [`HOOK_TIMEOUT`].
+    #[tracing::instrument(name = "tool.hook", skip(self, hook_env))]
+    async fn run_hook(
+        &self,
+        tool_id: &str,
+        phase: &str,
+        hook: &str,
+        hook_env: &HookEnv,
+    ) -> Result<(), String> {
+        self.emit_log(
+            tool_id,
+            McpLogStream::Event,
+            format!("{phase} hook: {hook}"),
+            None,
+        )
+        .await;
+        let mut cmd = shell_command(hook);
+        cmd.envs(&hook_env.env);
+        if let Some(cwd) = &hook_env.cwd {
+            cmd.current_dir(cwd);
+        }
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::piped());
+        cmd.stderr(Stdio::piped());
+        cmd.kill_on_drop(true);
+
+        let result = match cmd.spawn() {
+            Ok(mut child) => {
+                let stdout = child.stdout.take();
+                let stderr = child.stderr.take();
+                let run = async {
+                    tokio::join!(
+                        self.forward_output(tool_id, McpLogStream::Stdout, stdout),
+                        self.forward_output(tool_id, McpLogStream::Stderr, stderr),
+                    );
+                    child.wait().await
+                };
+                let outcome = tokio::time::timeout(HOOK_TIMEOUT, run).await;
+                match outcome {
+                    Ok(Ok(status)) if status.success() => Ok(()),
+                    Ok(Ok(status)) => Err(format!(
+                        "{phase} hook failed with code {}",
+                        status.code().unwrap_or(-1)
+                    )),
+                    Ok(Err(err)) => Err(format!("{phase} hook failed: {err}")),
+                    Err(_) => {
+                        let _ = child.kill().await;
+                        Err(format!(
+                            "{phase} hook timed out after {}s",
+                            HOOK_TIMEOUT.as_secs()
+                        ))
+                    }
+                }
+            }
+            Err(err) => Err(format!("{phase} hook failed to start: {err}")),
+        };
+        if let Err(message) = &result {
+            warn!("{}", message);
+            self.flush_repeats(tool_id).await;
+            self.emit_log(tool_id, McpLogStream::Event, message.clone(), None)
+                .await;
+        }
+        result
+    }
+
+    /// Runs a stopped tool's post-stop hooks; a failing one does not keep
+    /// the rest from running.
+    async fn run_post_stop(&self, tool_id: &str, handle: &ProcessHandle) {
+        for hook in handle.post_stop.iter() {
+            let _ = self
+                .run_hook(tool_id, "post-stop", hook, &handle.hook_env)
+                .await;
+        }
+    }
+
+    async fn forward_output<R: AsyncRead + Unpin>(
+        &self,
+        tool_id: &str,
+        stream: McpLogStream,
+        reader: Option<R>,
+    ) {
+        let Some(reader) = reader else {
+            return;
+        };
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            self.emit_log(tool_id, stream.clone(), line, None).await;
+        }
+    }
+
+    /// `WORKSPACE` for a tool: the directory of the local config file it was
+    /// imported from.
+    async fn workspace_dir(&self, tool: &McpTool) -> Option<PathBuf> {
+        let source_id = tool.source_id.as_deref()?;
+        let source = self.store.get_source(source_id).await.ok()??;
+        if source.source_type != McpSourceType::Local {
+            return None;
+        }
+        crate::paths::expand_path(&source.path_or_url)
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(Path::to_path_buf)
+    }
+
+    #[tracing::instrument(name = "tool.stop", skip(self))]
     pub async fn stop_tool(&self, tool_id: &str) -> Result<(), McpError> {
         let handle = {
             let processes = self.processes.read().await;
@@ -141,12 +483,96 @@ impl ProcessManager {
         self.store
             .set_tool_status(tool_id, McpToolStatus::Stopped, None, None)
             .await?;
+        self.flush_repeats(tool_id).await;
         self.emit_log(tool_id, McpLogStream::Event, "process stopped".to_string(), None)
             .await;
 
         Ok(())
     }
 
+    /// Kills a running tool and drops its log buffer and broadcaster, closing
+    /// any open log streams. Used when the tool itself is being deleted.
+    pub async fn remove_tool(&self, tool_id: &str) -> Result<(), McpError> {
+        // Untracked before the kill, so the monitor does not report the exit
+        // and run the post-stop hooks a second time.
+        let handle = self.processes.write().await.remove(tool_id);
+        if let Some(handle) = handle {
+            let killed = handle.child.lock().await.kill().await;
+            if let Err(err) = killed {
+                self.processes
+                    .write()
+                    .await
+                    .insert(tool_id.to_string(), handle);
+                return Err(McpError::Process(format!("failed to stop tool: {err}")));
+            }
+            self.run_post_stop(tool_id, &handle).await;
+        }
+
+        self.logs.write().await.remove(tool_id);
+        self.tool_log_buffer_sizes.write().await.remove(tool_id);
+        self.log_dedup
+            .lock()
+            .expect("log dedup lock poisoned")
+            .take(tool_id);
+        self.redactors
+            .write()
+            .expect("redactor lock poisoned")
+            .remove(tool_id);
+        self.broadcasters.write().await.remove(tool_id);
+        Ok(())
+    }
+
+    /// Terminates every managed child and records it as stopped, so tools do
+    /// not keep running detached once the backend exits. Each child gets
+    /// SIGTERM and `grace` to exit before it is killed; later `start_tool`
+    /// calls are refused.
+    #[tracing::instrument(name = "tools.shutdown", skip(self))]
+    pub async fn shutdown_all(&self, grace: Duration) {
+        let handles: Vec<(String, ProcessHandle)> = {
+            let mut processes = self.processes.write().await;
+            self.shutting_down.store(true, Ordering::SeqCst);
+            processes.drain().collect()
+        };
+        let stops = handles.into_iter().map(|(tool_id, handle)| async move {
+            terminate_child(&handle.child, grace).await;
+            self.run_post_stop(&tool_id, &handle).await;
+            if let Err(err) = self
+                .store
+                .set_tool_status(&tool_id, McpToolStatus::Stopped, None, None)
+                .await
+            {
+                warn!(tool_id = %tool_id, "failed to record shutdown: {}", err);
+            }
+            self.emit_log(
+                &tool_id,
+                McpLogStream::Event,
+                "process stopped (backend shutdown)".to_string(),
+                None,
+            )
+            .await;
+        });
+        futures_util::future::join_all(stops).await;
+    }
+
+    pub async fn running_count(&self) -> usize {
+        self.processes.read().await.len()
+    }
+
+    /// Open subscriptions to the combined log stream.
+    pub fn all_logs_subscriber_count(&self) -> usize {
+        self.all_logs.receiver_count()
+    }
+
+    /// Open subscriptions to per-tool log streams, summed over tools.
+    pub async fn tool_logs_subscriber_count(&self) -> usize {
+        self.broadcasters
+            .read()
+            .await
+            .values()
+            .map(broadcast::Sender::receiver_count)
+            .sum()
+    }
+
     pub async fn logs(&self, tool_id: &str) -> Vec<McpLogEntry> {
         let logs = self.logs.read().await;
         logs.get(tool_id)
@@ -154,6 +580,17 @@ impl ProcessManager {
             .unwrap_or_default()
     }
 
+    pub async fn clear_logs(&self, tool_id: &str) {
+        let capacity = self.log_capacity(tool_id).await;
+        let mut logs = self.logs.write().await;
+        logs.insert(tool_id.to_string(), LogBuffer::new(capacity));
+    }
+
+    /// Records a lifecycle note (not process output) in the tool's log.
+    pub async fn log_event(&self, tool_id: &str, message: String) {
+        self.emit_log(tool_id, McpLogStream::Event, message, None).await;
+    }
+
     pub async fn subscribe_logs(
         &self,
         tool_id: &str,
@@ -161,6 +598,11 @@ impl ProcessManager {
         self.ensure_broadcaster(tool_id).await.subscribe()
     }
 
+    /// Log entries of every tool, including ones started after subscribing.
+    pub fn subscribe_all(&self) -> broadcast::Receiver<McpToolLogEntry> {
+        self.all_logs.subscribe()
+    }
+
     async fn ensure_broadcaster(&self, tool_id: &str) -> broadcast::Sender<McpLogEntry> {
         let mut broadcasters = self.broadcasters.write().await;
         broadcasters
@@ -173,9 +615,10 @@ impl ProcessManager {
     }
 
     async fn ensure_log_buffer(&self, tool_id: &str) {
+        let capacity = self.log_capacity(tool_id).await;
         let mut logs = self.logs.write().await;
         logs.entry(tool_id.to_string())
-            .or_insert_with(|| LogBuffer::new(self.log_buffer_size));
+            .or_insert_with(|| LogBuffer::new(capacity));
     }
 
     async fn emit_log(
@@ -185,19 +628,92 @@ impl ProcessManager {
         message: String,
         sender: Option<&broadcast::Sender<McpLogEntry>>,
     ) {
+        #[cfg(feature = "server")]
+        METRICS.record_log_line(&stream);
+        if stream != McpLogStream::Event {
+            let verdict = self
+                .log_dedup
+                .lock()
+                .expect("log dedup lock poisoned")
+                .check(tool_id, &stream, &message, Instant::now());
+            match verdict {
+                Verdict::Suppress => return,
+                Verdict::Emit {
+                    repeated: Some(count),
+                } => {
+                    let note = log_dedup::repeated_message(count);
+                    self.push_log(tool_id, stream.clone(), note, sender).await;
+                }
+                Verdict::Emit { repeated: None } => {}
+            }
+        }
+        self.push_log(tool_id, stream, message, sender).await;
+    }
+
+    /// Masks secrets before a line is buffered, stored or broadcast; see
+    /// [`Redactor`]. Tools never started here only get the pattern checks.
+    fn redact(&self, tool_id: &str, message: String) -> String {
+        let redactors = self.redactors.read().expect("redactor lock poisoned");
+        match redactors.get(tool_id) {
+            Some(redactor) => redactor.apply(message),
+            None => Redactor::default().apply(message),
+        }
+    }
+
+    /// Reports repeats of the tool's last lines that were coalesced and not
+    /// reported yet; called when its process goes away.
+    async fn flush_repeats(&self, tool_id: &str) {
+        let repeats = self
+            .log_dedup
+            .lock()
+            .expect("log dedup lock poisoned")
+            .take(tool_id);
+        for (stream, count) in repeats {
+            self.push_log(tool_id, stream, log_dedup::repeated_message(count), None)
+                .await;
+        }
+    }
+
+    async fn push_log(
+        &self,
+        tool_id: &str,
+        stream: McpLogStream,
+        message: String,
+        sender: Option<&broadcast::Sender<McpLogEntry>>,
+    ) {
+        let (message, structured) = match stream {
+            McpLogStream::Event => (self.redact(tool_id, message), None),
+            _ => {
+                let message = self.log_ansi.read().expect("ANSI mode lock poisoned").apply(message);
+                let message = self.redact(tool_id, message);
+                let structured = json_log::detect(&message);
+                (message, structured)
+            }
+        };
+        let level = log_level::infer(&stream, &message, structured.as_ref());
         let entry = McpLogEntry {
+            seq: self.log_seq.fetch_add(1, Ordering::SeqCst) + 1,
             timestamp: now_rfc3339(),
             stream,
             message,
+            level,
+            structured,
         };
 
         {
+            let capacity = self.log_capacity(tool_id).await;
             let mut logs = self.logs.write().await;
             logs.entry(tool_id.to_string())
-                .or_insert_with(|| LogBuffer::new(self.log_buffer_size))
+                .or_insert_with(|| LogBuffer::new(capacity))
                 .push(entry.clone());
+            enforce_memory_budget(&mut logs, self.log_memory_budget.load(Ordering::SeqCst));
         }
 
+        let _ = self.all_logs.send(McpToolLogEntry {
+            tool_id: tool_id.to_string(),
+            entry: entry.clone(),
+        });
+
         if let Some(sender) = sender {
             let _ = sender.send(entry);
             return;
@@ -214,64 +730,370 @@ impl ProcessManager {
         tokio::spawn(async move {
             loop {
                 tokio::time::sleep(Duration::from_millis(500)).await;
+                if !manager.is_current(&tool_id, &child).await {
+                    // Removed through `remove_tool`; nothing left to report.
+                    break;
+                }
                 let mut child_guard = child.lock().await;
                 match child_guard.try_wait() {
                     Ok(Some(status)) => {
-                        let exit_code = status.code().unwrap_or(-1);
-                        let message = format!("process exited with code {exit_code}");
+                        drop(child_guard);
                         manager
-                            .emit_log(&tool_id, McpLogStream::Event, message.clone(), None)
+                            .record_exit(&tool_id, status.code().unwrap_or(-1))
                             .await;
-                        let status = if exit_code == 0 {
-                            McpToolStatus::Stopped
-                        } else {
-                            McpToolStatus::Crashed
-                        };
-                        if let Err(err) = manager
-                            .store
-                            .set_tool_status(&tool_id, status, None, Some(message))
-                            .await
-                        {
-                            warn!("failed to update status for {}: {}", tool_id, err);
-                        }
-                        manager.processes.write().await.remove(&tool_id);
                         break;
                     }
                     Ok(None) => continue,
                     Err(err) => {
-                        warn!("failed to poll tool {}: {}", tool_id, err);
+                        warn!(tool_id = %tool_id, "failed to poll tool: {}", err);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Whether `child` is still the tracked process of the tool.
+    async fn is_current(&self, tool_id: &str, child: &Arc<Mutex<Child>>) -> bool {
+        self.processes
+            .read()
+            .await
+            .get(tool_id)
+            .is_some_and(|handle| Arc::ptr_eq(&handle.child, child))
+    }
+
+    /// Probes the tool's health check every interval while `child` runs. A
+    /// passing probe keeps the tool healthy with the probe time as its ping
+    /// and a failing one marks it degraded; once failures reach the
+    /// threshold the process is stopped, and the exit monitor records the
+    /// crash.
+    fn spawn_health_monitor(
+        &self,
+        tool_id: String,
+        child: Arc<Mutex<Child>>,
+        check: HealthCheck,
+        hook_env: Arc<HookEnv>,
+    ) {
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            let mut tracker = ProbeTracker::new(check.failure_threshold());
+            loop {
+                tokio::time::sleep(check.interval()).await;
+                if !manager.is_current(&tool_id, &child).await {
+                    break;
+                }
+                let result = probe(&check, &hook_env, &client).await;
+                if !manager.is_current(&tool_id, &child).await {
+                    break;
+                }
+                let transition = tracker.record(result.is_ok());
+                let reason = match result {
+                    Ok(elapsed) => {
+                        manager.record_probe_pass(&tool_id, elapsed).await;
+                        continue;
+                    }
+                    Err(reason) => reason,
+                };
+                let message = format!(
+                    "health check failed ({}/{}): {reason}",
+                    tracker.failures(),
+                    check.failure_threshold().max(1)
+                );
+                manager
+                    .emit_log(&tool_id, McpLogStream::Event, message.clone(), None)
+                    .await;
+                match transition {
+                    Some(ProbeTransition::Degraded) => {
+                        manager.record_probe_failure(&tool_id, message).await;
+                    }
+                    Some(ProbeTransition::Crashed) => {
+                        warn!(tool_id = %tool_id, "{}; stopping the process", message);
+                        terminate_child(&child, Duration::from_secs(5)).await;
                         break;
                     }
+                    _ => {}
                 }
             }
         });
     }
+
+    /// Marks a running tool healthy after a passing probe, with the probe
+    /// time as its ping.
+    async fn record_probe_pass(&self, tool_id: &str, elapsed: Duration) {
+        let status = self.tool_status(tool_id).await;
+        if !matches!(
+            status,
+            Some(McpToolStatus::Healthy | McpToolStatus::Degraded)
+        ) {
+            return;
+        }
+        let ping_ms = i64::try_from(elapsed.as_millis()).unwrap_or(i64::MAX);
+        if let Err(err) = self
+            .store
+            .set_tool_status(tool_id, McpToolStatus::Healthy, Some(ping_ms), None)
+            .await
+        {
+            warn!(tool_id = %tool_id, "failed to record health check: {}", err);
+        }
+    }
+
+    async fn record_probe_failure(&self, tool_id: &str, message: String) {
+        if self.tool_status(tool_id).await != Some(McpToolStatus::Healthy) {
+            return;
+        }
+        if let Err(err) = self
+            .store
+            .set_tool_status(tool_id, McpToolStatus::Degraded, None, Some(message))
+            .await
+        {
+            warn!(tool_id = %tool_id, "failed to record health check: {}", err);
+        }
+    }
+
+    async fn tool_status(&self, tool_id: &str) -> Option<McpToolStatus> {
+        let tool = self.store.get_tool(tool_id).await.ok()??;
+        Some(tool.status)
+    }
+
+    /// Logs an exit the monitor noticed and records it as stopped, or as
+    /// crashed (with a `ToolCrashed` event) for a non-zero code.
+    #[tracing::instrument(name = "tool.exit", skip(self))]
+    async fn record_exit(&self, tool_id: &str, exit_code: i32) {
+        let message = format!("process exited with code {exit_code}");
+        self.flush_repeats(tool_id).await;
+        self.emit_log(tool_id, McpLogStream::Event, message.clone(), None)
+            .await;
+        let handle = self.processes.read().await.get(tool_id).cloned();
+        if let Some(handle) = handle {
+            self.run_post_stop(tool_id, &handle).await;
+        }
+        let status = if exit_code == 0 {
+            info!(tool_id = %tool_id, exit_code, "tool process exited");
+            McpToolStatus::Stopped
+        } else {
+            warn!(tool_id = %tool_id, exit_code, "tool process crashed");
+            McpToolStatus::Crashed
+        };
+        if let Err(err) = self
+            .store
+            .set_tool_status(tool_id, status, None, Some(message))
+            .await
+        {
+            warn!(tool_id = %tool_id, "failed to update status: {}", err);
+        }
+        if exit_code != 0 {
+            self.store.publish(McpEvent::ToolCrashed {
+                tool_id: tool_id.to_string(),
+                exit_code,
+            });
+        }
+        self.processes.write().await.remove(tool_id);
+    }
+}
+
+/// Asks the child to exit with SIGTERM, falling back to a kill once `grace`
+/// runs out (or straight away where signals are unavailable).
+#[cfg_attr(not(unix), allow(unused_variables))]
+async fn terminate_child(child: &Mutex<Child>, grace: Duration) {
+    let mut child = child.lock().await;
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        // SAFETY: kill(2) has no memory-safety requirements; the pid belongs
+        // to a child we spawned and have not reaped yet.
+        unsafe {
+            libc::kill(pid as libc::pid_t, libc::SIGTERM);
+        }
+        if tokio::time::timeout(grace, child.wait()).await.is_ok() {
+            return;
+        }
+    }
+    if let Err(err) = child.kill().await {
+        warn!("failed to kill tool process: {}", err);
+    }
 }
 
 #[derive(Clone)]
 struct ProcessHandle {
     child: Arc<Mutex<Child>>,
+    /// Resolved when the tool started, to run once it exits.
+    post_stop: Arc<Vec<String>>,
+    hook_env: Arc<HookEnv>,
+}
+
+/// Where a tool's hooks and health check commands run: its resolved env and
+/// working directory.
+struct HookEnv {
+    env: HashMap<String, String>,
+    cwd: Option<PathBuf>,
+}
+
+/// Expands a hook's `${VAR}`s like the tool's command, quoting each value
+/// for the shell, and adds the built-ins it used and the names it left
+/// unresolved to `resolved`.
+fn interpolate_hook(
+    hook: &str,
+    builtins: &HashMap<&'static str, String>,
+    resolved: &mut Interpolated,
+) -> String {
+    let hook = interpolate_shell(hook, builtins, |name| std::env::var(name).ok());
+    merge_references(hook, resolved)
+}
+
+/// [`interpolate_hook`] for a health check's `tcp` or `http` target, which
+/// is not run through the shell and so is not quoted.
+fn interpolate_target(
+    target: &str,
+    builtins: &HashMap<&'static str, String>,
+    resolved: &mut Interpolated,
+) -> String {
+    let target = interpolate_command(target, &[], None, None, builtins, |name| {
+        std::env::var(name).ok()
+    });
+    merge_references(target, resolved)
+}
+
+fn merge_references(hook: Interpolated, resolved: &mut Interpolated) -> String {
+    for builtin in hook.builtins {
+        if !resolved.builtins.contains(&builtin) {
+            resolved.builtins.push(builtin);
+        }
+    }
+    for name in hook.unresolved {
+        if !resolved.unresolved.contains(&name) {
+            resolved.unresolved.push(name);
+        }
+    }
+    hook.command
+}
+
+/// Runs one probe of `check`, returning how long it took to pass.
+async fn probe(
+    check: &HealthCheck,
+    hook_env: &HookEnv,
+    client: &reqwest::Client,
+) -> Result<Duration, String> {
+    let started = Instant::now();
+    let timeout = check.timeout();
+    let outcome = if let Some(command) = &check.command {
+        let mut cmd = shell_command(command);
+        cmd.envs(&hook_env.env);
+        if let Some(cwd) = &hook_env.cwd {
+            cmd.current_dir(cwd);
+        }
+        cmd.stdin(Stdio::null());
+        cmd.stdout(Stdio::null());
+        cmd.stderr(Stdio::null());
+        cmd.kill_on_drop(true);
+        tokio::time::timeout(timeout, cmd.status())
+            .await
+            .map(|status| match status {
+                Ok(status) if status.success() => Ok(()),
+                Ok(status) => Err(format!(
+                    "`{command}` exited with code {}",
+                    status.code().unwrap_or(-1)
+                )),
+                Err(err) => Err(format!("`{command}` failed to start: {err}")),
+            })
+    } else if let Some(address) = &check.tcp {
+        tokio::time::timeout(timeout, tokio::net::TcpStream::connect(address.as_str()))
+            .await
+            .map(|connected| {
+                connected
+                    .map(drop)
+                    .map_err(|err| format!("cannot connect to {address}: {err}"))
+            })
+    } else if let Some(url) = &check.http {
+        tokio::time::timeout(timeout, client.get(url).send())
+            .await
+            .map(|response| match response {
+                Ok(response) if response.status().is_success() => Ok(()),
+                Ok(response) => Err(format!("{url} answered {}", response.status())),
+                Err(err) => Err(format!("{url}: {err}")),
+            })
+    } else {
+        return Err("no command, tcp or http target".to_string());
+    };
+    match outcome {
+        Ok(Ok(())) => Ok(started.elapsed()),
+        Ok(Err(reason)) => Err(reason),
+        Err(_) => Err(format!("timed out after {}s", timeout.as_secs())),
+    }
+}
+
+fn shell_command(line: &str) -> tokio::process::Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = tokio::process::Command::new("cmd");
+        cmd.args(["/C", line]);
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", line]);
+        cmd
+    }
 }
 
 struct LogBuffer {
     entries: VecDeque<McpLogEntry>,
     capacity: usize,
+    /// Approximate heap and inline size of `entries`.
+    bytes: usize,
 }
 
 impl LogBuffer {
     fn new(capacity: usize) -> Self {
         Self {
-            entries: VecDeque::with_capacity(capacity),
+            entries: VecDeque::new(),
             capacity,
+            bytes: 0,
         }
     }
 
     fn push(&mut self, entry: McpLogEntry) {
         if self.entries.len() >= self.capacity {
-            self.entries.pop_front();
+            self.pop_front();
         }
+        self.bytes += entry_bytes(&entry);
         self.entries.push_back(entry);
     }
+
+    /// Drops the oldest line, returning the bytes freed.
+    fn pop_front(&mut self) -> Option<usize> {
+        let freed = entry_bytes(&self.entries.pop_front()?);
+        self.bytes -= freed;
+        Some(freed)
+    }
+
+    /// Also releases storage beyond the new capacity; a deque keeps its
+    /// allocation when lines are popped.
+    fn resize(&mut self, capacity: usize) {
+        while self.entries.len() > capacity {
+            self.pop_front();
+        }
+        self.entries.shrink_to(capacity);
+        self.capacity = capacity;
+    }
+}
+
+fn entry_bytes(entry: &McpLogEntry) -> usize {
+    std::mem::size_of::<McpLogEntry>() + entry.timestamp.len() + entry.message.len()
+}
+
+/// Trims the largest buffers, oldest lines first, until all fit in `budget`.
+fn enforce_memory_budget(logs: &mut HashMap<String, LogBuffer>, budget: usize) {
+    let mut total: usize = logs.values().map(|buffer| buffer.bytes).sum();
+    while total > budget {
+        let Some(largest) = logs.values_mut().max_by_key(|buffer| buffer.bytes) else {
+            break;
+        };
+        let Some(freed) = largest.pop_front() else {
+            break;
+        };
+        total -= freed;
+    }
 }
 
 fn now_rfc3339() -> String {
@@ -283,29 +1105,42 @@ fn now_rfc3339() -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::McpLogLevel;
 
     #[test]
     fn log_buffer_eviction_keeps_latest() {
         let mut buffer = LogBuffer::new(3);
         buffer.push(McpLogEntry {
+            seq: 0,
             timestamp: "t1".to_string(),
             stream: McpLogStream::Event,
             message: "one".to_string(),
+            level: McpLogLevel::Info,
+            structured: None,
         });
         buffer.push(McpLogEntry {
+            seq: 0,
             timestamp: "t2".to_string(),
             stream: McpLogStream::Event,
             message: "two".to_string(),
+            level: McpLogLevel::Info,
+            structured: None,
         });
         buffer.push(McpLogEntry {
+            seq: 0,
             timestamp: "t3".to_string(),
             stream: McpLogStream::Event,
             message: "three".to_string(),
+            level: McpLogLevel::Info,
+            structured: None,
         });
         buffer.push(McpLogEntry {
+            seq: 0,
             timestamp: "t4".to_string(),
             stream: McpLogStream::Event,
             message: "four".to_string(),
+            level: McpLogLevel::Info,
+            structured: None,
         });
 
         let messages: Vec<_> = buffer
@@ -315,4 +1150,129 @@ mod tests {
             .collect();
         assert_eq!(messages, vec!["two", "three", "four"]);
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hook_output_is_logged_and_failures_reported() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        let manager = ProcessManager::new(Arc::new(store));
+        let hook_env = HookEnv {
+            env: HashMap::from([("GREETING".to_string(), "hi".to_string())]),
+            cwd: None,
+        };
+
+        let result = manager
+            .run_hook(
+                "alpha",
+                "pre-start",
+                "echo $GREETING >&2; exit 3",
+                &hook_env,
+            )
+            .await;
+
+        assert_eq!(result, Err("pre-start hook failed with code 3".to_string()));
+        let logged: Vec<_> = manager
+            .logs("alpha")
+            .await
+            .into_iter()
+            .map(|entry| (entry.stream, entry.message))
+            .collect();
+        assert_eq!(
+            logged,
+            vec![
+                (
+                    McpLogStream::Event,
+                    "pre-start hook: echo $GREETING >&2; exit 3".to_string()
+                ),
+                (McpLogStream::Stderr, "hi".to_string()),
+                (
+                    McpLogStream::Event,
+                    "pre-start hook failed with code 3".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn probes_commands_and_tcp_targets() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let hook_env = HookEnv {
+            env: HashMap::new(),
+            cwd: None,
+        };
+        let client = reqwest::Client::new();
+        let tcp = HealthCheck {
+            tcp: Some(listener.local_addr().unwrap().to_string()),
+            ..HealthCheck::default()
+        };
+        assert!(probe(&tcp, &hook_env, &client).await.is_ok());
+
+        let command = HealthCheck {
+            command: Some("exit 7".to_string()),
+            ..HealthCheck::default()
+        };
+        assert_eq!(
+            probe(&command, &hook_env, &client).await,
+            Err("`exit 7` exited with code 7".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn shrinking_log_buffer_keeps_latest_lines() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        let manager = ProcessManager::new(Arc::new(store));
+        for message in ["one", "two", "three"] {
+            manager.log_event("alpha", message.to_string()).await;
+        }
+
+        manager.set_log_buffer_size(2).await;
+        manager.log_event("alpha", "four".to_string()).await;
+
+        let messages: Vec<_> = manager
+            .logs("alpha")
+            .await
+            .into_iter()
+            .map(|entry| entry.message)
+            .collect();
+        assert_eq!(messages, vec!["three", "four"]);
+    }
+
+    #[tokio::test]
+    async fn memory_budget_trims_the_largest_buffer_first() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        let manager = ProcessManager::new(Arc::new(store));
+        manager.log_event("quiet", "hello".to_string()).await;
+        for index in 0..50 {
+            manager.log_event("noisy", format!("line {index}")).await;
+        }
+        let kept: usize = manager.logs("noisy").await[40..].iter().map(entry_bytes).sum();
+        let quiet = entry_bytes(&manager.logs("quiet").await[0]);
+        manager.set_log_memory_budget(quiet + kept).await;
+
+        assert_eq!(manager.logs("quiet").await.len(), 1);
+        let noisy = manager.logs("noisy").await;
+        assert_eq!(noisy.len(), 10);
+        assert_eq!(noisy.last().unwrap().message, "line 49");
+        assert!(manager.logs.read().await["noisy"].entries.capacity() < 50);
+
+        manager.set_tool_log_buffer_size("noisy", Some(3)).await;
+        manager.set_log_buffer_size(1).await;
+        assert_eq!(manager.logs("noisy").await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn subscribe_all_tags_entries_with_tool_id() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        let manager = ProcessManager::new(Arc::new(store));
+        let mut all = manager.subscribe_all();
+
+        manager.log_event("alpha", "one".to_string()).await;
+        manager.log_event("beta", "two".to_string()).await;
+
+        let first = all.recv().await.unwrap();
+        let second = all.recv().await.unwrap();
+        assert_eq!((first.tool_id.as_str(), first.entry.message.as_str()), ("alpha", "one"));
+        assert_eq!((second.tool_id.as_str(), second.entry.message.as_str()), ("beta", "two"));
+    }
 }
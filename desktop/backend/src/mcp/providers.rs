@@ -0,0 +1,232 @@
+//! Where source configs come from. Each [`SourceProvider`] fetches the config
+//! of the sources it handles; the sync engine only applies what comes back,
+//! so another registry (an npm or PyPI-based index, a company catalog) is a
+//! new provider registered on [`SourceProviders`] rather than a change to
+//! [`super::sync`].
+
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+use mcp_core::hash::hash_json;
+
+use crate::paths::expand_path;
+
+use super::{McpConfigPayload, McpError, McpSource, McpSourceType};
+
+/// A source's config as a provider fetched it.
+#[derive(Debug, Clone)]
+pub struct FetchedConfig {
+    pub payload: McpConfigPayload,
+    /// Hash of the fetched content. When it matches the hash recorded the
+    /// last time the source was applied, the sync keeps the current tools.
+    pub content_hash: Option<String>,
+}
+
+impl From<McpConfigPayload> for FetchedConfig {
+    fn from(payload: McpConfigPayload) -> Self {
+        Self {
+            payload,
+            content_hash: None,
+        }
+    }
+}
+
+pub trait SourceProvider: Send + Sync {
+    /// Short name for logs, e.g. `local` or `http`.
+    fn name(&self) -> &'static str;
+
+    /// Whether this provider fetches `source`.
+    fn handles(&self, source: &McpSource) -> bool;
+
+    /// Reads the source's current config. `auth_token` is the caller's
+    /// bearer token, when the sync request carried one.
+    fn fetch<'a>(
+        &'a self,
+        source: &'a McpSource,
+        auth_token: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<FetchedConfig, McpError>>;
+}
+
+/// The providers a sync picks from, most recently registered first, so a
+/// provider can take over sources a built-in one would handle.
+#[derive(Clone)]
+pub struct SourceProviders {
+    providers: Vec<Arc<dyn SourceProvider>>,
+}
+
+impl SourceProviders {
+    /// No providers; every sync fails until one is registered.
+    pub fn empty() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+
+    pub fn register(&mut self, provider: Arc<dyn SourceProvider>) {
+        self.providers.insert(0, provider);
+    }
+
+    /// The provider that fetches `source`, if any.
+    pub fn for_source(&self, source: &McpSource) -> Option<&dyn SourceProvider> {
+        self.providers
+            .iter()
+            .find(|provider| provider.handles(source))
+            .map(|provider| provider.as_ref())
+    }
+}
+
+/// The built-in providers: [`HttpProvider`] and [`LocalFileProvider`].
+impl Default for SourceProviders {
+    fn default() -> Self {
+        let mut providers = Self::empty();
+        providers.register(Arc::new(HttpProvider::default()));
+        providers.register(Arc::new(LocalFileProvider));
+        providers
+    }
+}
+
+/// Reads local sources from their config file.
+pub struct LocalFileProvider;
+
+impl SourceProvider for LocalFileProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn handles(&self, source: &McpSource) -> bool {
+        source.source_type == McpSourceType::Local
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        source: &'a McpSource,
+        _auth_token: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<FetchedConfig, McpError>> {
+        Box::pin(async move {
+            let path = expand_path(&source.path_or_url);
+            let content = tokio::fs::read_to_string(&path).await?;
+            // Hashed after parsing, so saves that only reformat the file
+            // are skipped as well.
+            let value: serde_json::Value = serde_json::from_str(&content)?;
+            let content_hash = hash_json(&value)?;
+            Ok(FetchedConfig {
+                payload: serde_json::from_value(value)?,
+                content_hash: Some(content_hash),
+            })
+        })
+    }
+}
+
+/// GETs ModelScope, GitHub and URL sources, passing the caller's token as
+/// bearer auth.
+#[derive(Default)]
+pub struct HttpProvider {
+    client: reqwest::Client,
+}
+
+impl SourceProvider for HttpProvider {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    fn handles(&self, source: &McpSource) -> bool {
+        matches!(
+            source.source_type,
+            McpSourceType::Modelscope | McpSourceType::Github | McpSourceType::Url
+        )
+    }
+
+    fn fetch<'a>(
+        &'a self,
+        source: &'a McpSource,
+        auth_token: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<FetchedConfig, McpError>> {
+        Box::pin(async move {
+            let mut request = self.client.get(&source.path_or_url);
+            if let Some(token) = auth_token {
+                request = request.bearer_auth(token);
+            }
+            let response = request
+                .send()
+                .await
+                .map_err(|err| McpError::Process(err.to_string()))?;
+            if !response.status().is_success() {
+                return Err(McpError::Process(format!(
+                    "sync failed with status {}",
+                    response.status()
+                )));
+            }
+            let payload = response
+                .json::<McpConfigPayload>()
+                .await
+                .map_err(|err| McpError::Process(err.to_string()))?;
+            Ok(payload.into())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::{McpSourceStatus, McpTrustLevel};
+
+    struct Catalog;
+
+    impl SourceProvider for Catalog {
+        fn name(&self) -> &'static str {
+            "catalog"
+        }
+
+        fn handles(&self, source: &McpSource) -> bool {
+            source.path_or_url.starts_with("catalog://")
+        }
+
+        fn fetch<'a>(
+            &'a self,
+            _source: &'a McpSource,
+            _auth_token: Option<&'a str>,
+        ) -> BoxFuture<'a, Result<FetchedConfig, McpError>> {
+            Box::pin(async {
+                Ok(McpConfigPayload {
+                    mcp_servers: Default::default(),
+                }
+                .into())
+            })
+        }
+    }
+
+    fn source(source_type: McpSourceType, path_or_url: &str) -> McpSource {
+        McpSource {
+            id: "s1".to_string(),
+            name: "Source".to_string(),
+            source_type,
+            path_or_url: path_or_url.to_string(),
+            trust_level: McpTrustLevel::Private,
+            status: McpSourceStatus::Active,
+            last_synced_at: None,
+            is_read_only: false,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn registered_providers_take_precedence_over_built_ins() {
+        let mut providers = SourceProviders::default();
+        let name = |providers: &SourceProviders, source: &McpSource| {
+            providers.for_source(source).map(|provider| provider.name())
+        };
+        let catalog = source(McpSourceType::Url, "catalog://tools");
+        let url = source(McpSourceType::Url, "https://example.com/mcp.json");
+        assert_eq!(name(&providers, &catalog), Some("http"));
+        assert_eq!(
+            name(&providers, &source(McpSourceType::Local, "~/mcp.json")),
+            Some("local")
+        );
+        assert_eq!(name(&providers, &source(McpSourceType::Cloud, "")), None);
+
+        providers.register(Arc::new(Catalog));
+        assert_eq!(name(&providers, &catalog), Some("catalog"));
+        assert_eq!(name(&providers, &url), Some("http"));
+    }
+}
@@ -1,42 +1,133 @@
+use std::collections::HashMap;
 use std::convert::Infallible;
-use std::path::PathBuf;
 use std::time::Duration;
 
-use axum::extract::{Path, State};
+use axum::extract::{Multipart, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
 use axum::response::sse::{Event, KeepAlive, Sse};
-use axum::routing::{get, patch, post};
+use axum::response::Response;
+use axum::routing::{delete, get, patch, post};
 use axum::{Json, Router};
 use futures_util::StreamExt;
+use mcp_core::env::{env_requirements, validate_env};
+use mcp_core::merge::merge_config;
+use schemars::schema::RootSchema;
 use tokio_stream::wrappers::BroadcastStream;
 
+use crate::http_cache::{conditional_json, latest_timestamp};
 use crate::state::AppState;
+use crate::mcp::log_filter::LogFilter;
+use crate::mcp::schema::{cloud_manifest_schema, config_schema, validate_config, ConfigValidation};
+use crate::mcp::sync::{self, apply_config_payload, now_rfc3339};
+use crate::mcp::ErrorResponse;
 use crate::mcp::{
-    CreateSourceRequest, CreateSourceResponse, ExtractedToolFields, ImportConfigRequest,
-    ImportConfigResponse, ListSourcesResponse, ListToolsResponse, McpConfigPayload, McpConflictStatus,
-    McpError, McpSource, McpSourceStatus, McpSourceType, McpTool, McpToolStatus, NewSource,
-    SyncSourceRequest, SyncSourceResponse, ToolLogsResponse, ToolUpsert, UpdateToolConfigRequest,
+    AppendAssistantMessageRequest, BatchToolAction, BatchToolResult, BatchToolsRequest,
+    BatchToolsResponse, CloudSyncRequest, CloudSyncResponse, CreateApiTokenRequest,
+    CreateApiTokenResponse, CreateAssistantMessageRequest, CreateLocalAssistantRequest,
+    CreateSourceRequest, CreateSourceResponse, CreateWebhookRequest, CreateWebhookResponse, DeleteSourceQuery, DeleteToolQuery,
+    ImportConfigRequest, ImportConfigResponse, ListApiTokensResponse,
+    ListAssistantMessagesResponse, ListAssistantsResponse, ListSourcesQuery, ListSourcesResponse,
+    ListToolsQuery, LogFilterQuery, ListToolsResponse, ListWebhooksResponse, LocalAssistant, LocalAssistantMessage, McpConfigPayload,
+    McpConflictStatus, McpError, McpEvent, McpSource, McpSourceStatus, McpSourceType,
+    McpTool, McpToolDetail, McpToolStatus, McpTrustLevel, NewSource, ResolveConflictRequest,
+    SourceDeleteMode, SyncSourceRequest, SyncSourceResponse, ToolLogsResponse,
+    ToolUpsert,
+    UpdateLocalAssistantRequest, UpdateLogBufferRequest, UpdateToolConfigRequest,
+    UpdateToolEnvRequest,
 };
 
+/// Upper bound on ids per `/tools/batch` call and how many run at once.
+const BATCH_MAX_TOOLS: usize = 100;
+const BATCH_CONCURRENCY: usize = 4;
+
+const DEFAULT_CLOUD_SOURCE_NAME: &str = "Deeting Cloud";
+
 pub fn router() -> Router<AppState> {
     Router::new()
         .route("/sources", get(list_sources).post(create_source))
-        .route("/sources/:id/sync", post(sync_source))
+        .route("/sources/:id", delete(delete_source))
         .route("/tools", get(list_tools))
+        .route("/tools/events", get(tool_events_stream))
+        .route("/tools/import/validate", post(validate_import))
+        .route("/tools/:id", get(get_tool).delete(delete_tool))
+        .route("/tools/:id/config", patch(apply_pending_update))
+        .route("/tools/:id/conflict", post(resolve_conflict))
+        .route("/tools/:id/env", patch(update_tool_env))
+        .route("/tools/:id/logs/buffer", patch(update_tool_log_buffer))
+        .route("/tools/:id/logs", get(tool_logs).delete(clear_tool_logs))
+        .route("/tools/:id/logs/stream", get(tool_logs_stream))
+        .route("/logs/stream", get(all_logs_stream))
+        .route("/ws", get(super::ws::ws_handler))
+        .route("/auth/tokens", get(list_api_tokens).post(create_api_token))
+        .route("/auth/tokens/:id", delete(delete_api_token))
+        .route("/webhooks", get(list_webhooks).post(create_webhook))
+        .route("/webhooks/:id", delete(delete_webhook))
+        .route("/schemas/:name", get(get_schema))
+        .merge(rate_limited_router())
+}
+
+/// Routes that spawn processes or reach remote sources share one per-client
+/// token bucket.
+fn rate_limited_router() -> Router<AppState> {
+    Router::new()
+        .route("/sources/:id/sync", post(sync_source))
         .route("/tools/import", post(import_config))
+        .route("/tools/import-file", post(import_config_file))
+        .route("/tools/batch", post(batch_tools))
         .route("/tools/:id/start", post(start_tool))
         .route("/tools/:id/stop", post(stop_tool))
-        .route("/tools/:id/config", patch(apply_pending_update))
-        .route("/tools/:id/logs", get(tool_logs))
-        .route("/tools/:id/logs/stream", get(tool_logs_stream))
+        .route("/cloud/sync", post(cloud_sync))
+        .route_layer(crate::rate_limit::layer_from_env())
 }
 
+pub fn assistants_router() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_assistants).post(create_assistant))
+        .route(
+            "/:id",
+            get(get_assistant)
+                .patch(update_assistant)
+                .delete(delete_assistant),
+        )
+        .route(
+            "/:id/messages",
+            get(list_assistant_messages)
+                .post(append_assistant_message)
+                .delete(clear_assistant_messages),
+        )
+}
+
+#[utoipa::path(
+    get,
+    path = "/mcp/sources",
+    tag = "sources",
+    params(ListSourcesQuery),
+    responses(
+        (status = 200, body = ListSourcesResponse),
+        (status = 304, description = "Not modified (`If-None-Match` / `If-Modified-Since`)"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+    )
+)]
 async fn list_sources(
     State(state): State<AppState>,
-) -> Result<Json<ListSourcesResponse>, McpError> {
-    let sources = state.store.list_sources().await?;
-    Ok(Json(ListSourcesResponse { sources }))
+    Query(query): Query<ListSourcesQuery>,
+    headers: HeaderMap,
+) -> Result<Response, McpError> {
+    let (sources, total) = state.store.query_sources(&query).await?;
+    let last_modified = latest_timestamp(sources.iter().map(|source| source.updated_at.as_str()));
+    conditional_json(&headers, &ListSourcesResponse { sources, total }, last_modified)
 }
 
+#[utoipa::path(
+    post,
+    path = "/mcp/sources",
+    tag = "sources",
+    request_body = CreateSourceRequest,
+    responses(
+        (status = 200, body = CreateSourceResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+    )
+)]
 async fn create_source(
     State(state): State<AppState>,
     Json(payload): Json<CreateSourceRequest>,
@@ -56,6 +147,61 @@ async fn create_source(
     Ok(Json(CreateSourceResponse { source }))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/mcp/sources/{id}",
+    tag = "sources",
+    params(("id" = String, Path, description = "Source id"), DeleteSourceQuery),
+    responses(
+        (status = 204, description = "Source deleted"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn delete_source(
+    State(state): State<AppState>,
+    Path(source_id): Path<String>,
+    Query(query): Query<DeleteSourceQuery>,
+) -> Result<StatusCode, McpError> {
+    let source = state
+        .store
+        .get_source(&source_id)
+        .await?
+        .ok_or_else(|| McpError::SourceNotFound(source_id.to_string()))?;
+    let local = state.store.ensure_local_source().await?;
+    if source.id == local.id {
+        return Err(McpError::Validation(
+            "the local source cannot be deleted".to_string(),
+        ));
+    }
+
+    let mode = query.mode.unwrap_or_default();
+    if mode == SourceDeleteMode::Orphan {
+        state.store.check_orphanable(&source_id, &local.id).await?;
+    }
+    for tool in state.store.list_tools_by_source(&source_id).await? {
+        match mode {
+            SourceDeleteMode::Delete => state.process_manager.remove_tool(&tool.id).await?,
+            SourceDeleteMode::Orphan => state.process_manager.stop_tool(&tool.id).await?,
+        }
+    }
+    state.store.delete_source(&source_id, mode, &local.id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/mcp/sources/{id}/sync",
+    tag = "sources",
+    params(("id" = String, Path, description = "Source id")),
+    request_body = SyncSourceRequest,
+    responses(
+        (status = 200, body = SyncSourceResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 409, description = "Process error", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse),
+    )
+)]
 async fn sync_source(
     State(state): State<AppState>,
     Path(source_id): Path<String>,
@@ -65,37 +211,237 @@ async fn sync_source(
         .store
         .get_source(&source_id)
         .await?
-        .ok_or_else(|| McpError::NotFound(format!("source {source_id} not found")))?;
+        .ok_or_else(|| McpError::SourceNotFound(source_id.to_string()))?;
+
+    let sync = sync_source_inner(&state, source, payload.auth_token);
+    let tools = sync::track_sync(&state.store, &source_id, sync).await?;
+    Ok(Json(SyncSourceResponse { tools }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mcp/cloud/sync",
+    tag = "sources",
+    request_body = CloudSyncRequest,
+    responses(
+        (status = 200, body = CloudSyncResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "No or rejected cloud session", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse),
+        (status = 502, description = "Upstream error", body = ErrorResponse),
+    )
+)]
+async fn cloud_sync(
+    State(state): State<AppState>,
+    Json(payload): Json<CloudSyncRequest>,
+) -> Result<Json<CloudSyncResponse>, McpError> {
+    let source = resolve_cloud_source(&state, &payload).await?;
+    let access_token = match payload.access_token.as_deref().map(str::trim) {
+        Some(token) if !token.is_empty() => token.to_string(),
+        _ => cloud_session(&state, &source.id).await?,
+    };
 
     state
         .store
-        .update_source_status(&source_id, McpSourceStatus::Syncing, None)
+        .update_source_status(&source.id, McpSourceStatus::Syncing, None)
         .await?;
-
-    let result = sync_source_inner(&state, source, payload.auth_token).await;
-    match result {
-        Ok(tools) => {
-            state
-                .store
-                .update_source_status(&source_id, McpSourceStatus::Active, Some(now_rfc3339()?))
-                .await?;
-            Ok(Json(SyncSourceResponse { tools }))
-        }
+    let result = super::cloud::sync_cloud_subscriptions(&state, &source, &access_token).await;
+    state.store.publish(McpEvent::SourceSynced {
+        source_id: source.id.clone(),
+        success: result.is_ok(),
+    });
+    let outcome = match result {
+        Ok(outcome) => outcome,
         Err(err) => {
             state
                 .store
-                .update_source_status(&source_id, McpSourceStatus::Error, None)
+                .update_source_status(&source.id, McpSourceStatus::Error, None)
                 .await?;
-            Err(err)
+            return Err(err);
+        }
+    };
+
+    state
+        .store
+        .set_cloud_session(&source.id, &access_token)
+        .await?;
+    state
+        .store
+        .update_source_status(&source.id, McpSourceStatus::Active, Some(now_rfc3339()?))
+        .await?;
+    let source = state
+        .store
+        .get_source(&source.id)
+        .await?
+        .ok_or_else(|| McpError::SourceNotFound(source.id.clone()))?;
+    Ok(Json(CloudSyncResponse {
+        source,
+        tools: outcome.tools,
+        orphaned: outcome.orphaned,
+    }))
+}
+
+/// The requested cloud source, else the first one, else a new source
+/// pointing at `base_url` / `DESKTOP_CLOUD_BASE_URL`. Only the configured
+/// `DESKTOP_CLOUD_BASE_URL` is trusted as official.
+async fn resolve_cloud_source(
+    state: &AppState,
+    payload: &CloudSyncRequest,
+) -> Result<McpSource, McpError> {
+    if let Some(source_id) = payload.source_id.as_deref() {
+        let source = state
+            .store
+            .get_source(source_id)
+            .await?
+            .ok_or_else(|| McpError::SourceNotFound(source_id.to_string()))?;
+        if source.source_type != McpSourceType::Cloud {
+            return Err(McpError::Validation(format!(
+                "source {source_id} is not a cloud source"
+            )));
         }
+        return Ok(source);
     }
+    if let Some(source) = state.store.find_source_by_type(McpSourceType::Cloud).await? {
+        return Ok(source);
+    }
+
+    let normalize = |url: String| {
+        Some(url.trim().trim_end_matches('/').to_string()).filter(|url| !url.is_empty())
+    };
+    let configured = state.settings.current().cloud_base_url.and_then(normalize);
+    let base_url = payload
+        .base_url
+        .clone()
+        .and_then(normalize)
+        .or_else(|| configured.clone())
+        .ok_or_else(|| {
+            McpError::Validation(
+                "no cloud source configured; pass base_url or set DESKTOP_CLOUD_BASE_URL"
+                    .to_string(),
+            )
+        })?;
+    let trust_level = if configured.as_deref() == Some(base_url.as_str()) {
+        McpTrustLevel::Official
+    } else {
+        McpTrustLevel::Community
+    };
+    state
+        .store
+        .insert_source(NewSource {
+            name: DEFAULT_CLOUD_SOURCE_NAME.to_string(),
+            source_type: McpSourceType::Cloud,
+            path_or_url: base_url,
+            trust_level,
+            status: McpSourceStatus::Active,
+            last_synced_at: None,
+            is_read_only: true,
+        })
+        .await
+}
+
+async fn cloud_session(state: &AppState, source_id: &str) -> Result<String, McpError> {
+    state
+        .store
+        .get_cloud_session(source_id)
+        .await?
+        .ok_or_else(|| {
+            McpError::Unauthorized("no stored cloud session; pass access_token".to_string())
+        })
 }
 
-async fn list_tools(State(state): State<AppState>) -> Result<Json<ListToolsResponse>, McpError> {
-    let tools = state.store.list_tools().await?;
-    Ok(Json(ListToolsResponse { tools }))
+#[utoipa::path(
+    get,
+    path = "/mcp/tools",
+    tag = "tools",
+    params(ListToolsQuery),
+    responses(
+        (status = 200, body = ListToolsResponse),
+        (status = 304, description = "Not modified (`If-None-Match` / `If-Modified-Since`)"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+    )
+)]
+async fn list_tools(
+    State(state): State<AppState>,
+    Query(query): Query<ListToolsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, McpError> {
+    let (tools, total) = state.store.query_tools(&query).await?;
+    let last_modified = latest_timestamp(tools.iter().map(|tool| tool.updated_at.as_str()));
+    conditional_json(&headers, &ListToolsResponse { tools, total }, last_modified)
+}
+
+#[utoipa::path(
+    get,
+    path = "/mcp/tools/{id}",
+    tag = "tools",
+    params(("id" = String, Path, description = "Tool id")),
+    responses(
+        (status = 200, body = McpToolDetail),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn get_tool(
+    State(state): State<AppState>,
+    Path(tool_id): Path<String>,
+) -> Result<Json<McpToolDetail>, McpError> {
+    let detail = state
+        .store
+        .get_tool_detail(&tool_id)
+        .await?
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+    Ok(Json(detail))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/mcp/tools/{id}",
+    tag = "tools",
+    params(("id" = String, Path, description = "Tool id"), DeleteToolQuery),
+    responses(
+        (status = 204, description = "Tool deleted"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 409, description = "Process error", body = ErrorResponse),
+    )
+)]
+async fn delete_tool(
+    State(state): State<AppState>,
+    Path(tool_id): Path<String>,
+    Query(query): Query<DeleteToolQuery>,
+) -> Result<StatusCode, McpError> {
+    let tool = state
+        .store
+        .get_tool(&tool_id)
+        .await?
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+    if tool.is_read_only && !query.force.unwrap_or(false) {
+        return Err(McpError::Validation(format!(
+            "tool {tool_id} is read-only; pass force=true to delete it"
+        )));
+    }
+
+    state.process_manager.remove_tool(&tool_id).await?;
+    state.store.delete_tool(&tool_id).await?;
+    // A later sync should reinstall it even if the source has not changed.
+    if let Some(source_id) = &tool.source_id {
+        state.store.forget_applied_config(source_id);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/mcp/tools/import",
+    tag = "tools",
+    request_body = ImportConfigRequest,
+    responses(
+        (status = 200, body = ImportConfigResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse),
+    )
+)]
 async fn import_config(
     State(state): State<AppState>,
     Json(payload): Json<ImportConfigRequest>,
@@ -105,46 +451,237 @@ async fn import_config(
             .store
             .get_source(&source_id)
             .await?
-            .ok_or_else(|| McpError::NotFound(format!("source {source_id} not found")))?
+            .ok_or_else(|| McpError::SourceNotFound(source_id.to_string()))?
     } else {
         state.store.ensure_local_source().await?
     };
 
-    let tools = apply_config_payload(&state, &source, payload.config).await?;
+    let tools = apply_config_payload(&state.store, &source, payload.config).await?;
     Ok(Json(ImportConfigResponse { tools }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/mcp/tools/import/validate",
+    tag = "tools",
+    request_body(content = String, content_type = "text/plain", description = "Pasted `mcpServers` JSON"),
+    responses(
+        (status = 200, body = ConfigValidation),
+    )
+)]
+async fn validate_import(body: String) -> Json<ConfigValidation> {
+    Json(validate_config(&body))
+}
+
+#[utoipa::path(
+    get,
+    path = "/mcp/schemas/{name}",
+    tag = "tools",
+    params(("name" = String, Path, description = "`config` (an import payload) or `cloud-manifest`")),
+    responses(
+        (status = 200, description = "JSON Schema (draft 7)", content_type = "application/json"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn get_schema(Path(name): Path<String>) -> Result<Json<RootSchema>, McpError> {
+    let schema = match name.as_str() {
+        "config" => config_schema(),
+        "cloud-manifest" => cloud_manifest_schema(),
+        _ => return Err(McpError::NotFound(format!("schema {name}"))),
+    };
+    Ok(Json(schema))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mcp/tools/import-file",
+    tag = "tools",
+    request_body(
+        content_type = "multipart/form-data",
+        description = "`file`: an mcp.json (or Claude/Cursor config) with `mcpServers`; \
+                       optional `source_id` text field, defaulting to the local source",
+    ),
+    responses(
+        (status = 200, body = ImportConfigResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse),
+    )
+)]
+async fn import_config_file(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<Json<ImportConfigResponse>, McpError> {
+    let mut source_id = None;
+    let mut content = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|err| McpError::Validation(err.body_text()))?
+    {
+        match field.name() {
+            Some("file") => {
+                let bytes = field
+                    .bytes()
+                    .await
+                    .map_err(|err| McpError::Validation(err.body_text()))?;
+                content = Some(bytes);
+            }
+            Some("source_id") => {
+                let text = field
+                    .text()
+                    .await
+                    .map_err(|err| McpError::Validation(err.body_text()))?;
+                source_id = Some(text.trim().to_string()).filter(|id| !id.is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    let content =
+        content.ok_or_else(|| McpError::Validation("missing multipart field `file`".to_string()))?;
+    let config = serde_json::from_slice::<McpConfigPayload>(&content)
+        .map_err(|err| McpError::Validation(format!("invalid config file: {err}")))?;
+
+    let source = if let Some(source_id) = source_id {
+        state
+            .store
+            .get_source(&source_id)
+            .await?
+            .ok_or_else(|| McpError::SourceNotFound(source_id.to_string()))?
+    } else {
+        state.store.ensure_local_source().await?
+    };
+
+    let tools = apply_config_payload(&state.store, &source, config).await?;
+    Ok(Json(ImportConfigResponse { tools }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mcp/tools/{id}/start",
+    tag = "tools",
+    params(("id" = String, Path, description = "Tool id")),
+    responses(
+        (status = 200, body = McpTool),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 409, description = "Process error", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse),
+    )
+)]
 async fn start_tool(
     State(state): State<AppState>,
     Path(tool_id): Path<String>,
 ) -> Result<Json<McpTool>, McpError> {
+    Ok(Json(start_tool_inner(&state, &tool_id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mcp/tools/{id}/stop",
+    tag = "tools",
+    params(("id" = String, Path, description = "Tool id")),
+    responses(
+        (status = 200, body = McpTool),
+        (status = 404, description = "Not found", body = ErrorResponse),
+        (status = 409, description = "Process error", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse),
+    )
+)]
+async fn stop_tool(
+    State(state): State<AppState>,
+    Path(tool_id): Path<String>,
+) -> Result<Json<McpTool>, McpError> {
+    Ok(Json(stop_tool_inner(&state, &tool_id).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mcp/tools/batch",
+    tag = "tools",
+    request_body = BatchToolsRequest,
+    responses(
+        (status = 200, body = BatchToolsResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 429, description = "Rate limited", body = ErrorResponse),
+    )
+)]
+async fn batch_tools(
+    State(state): State<AppState>,
+    Json(payload): Json<BatchToolsRequest>,
+) -> Result<Json<BatchToolsResponse>, McpError> {
+    if payload.tool_ids.len() > BATCH_MAX_TOOLS {
+        return Err(McpError::Validation(format!(
+            "at most {BATCH_MAX_TOOLS} tool ids per batch"
+        )));
+    }
+
+    let results = futures_util::stream::iter(payload.tool_ids)
+        .map(|tool_id| {
+            let state = &state;
+            async move {
+                let result = match payload.action {
+                    BatchToolAction::Start => start_tool_inner(state, &tool_id).await,
+                    BatchToolAction::Stop => stop_tool_inner(state, &tool_id).await,
+                };
+                match result {
+                    Ok(tool) => BatchToolResult {
+                        tool_id,
+                        tool: Some(tool),
+                        code: None,
+                        error: None,
+                    },
+                    Err(err) => BatchToolResult {
+                        tool_id,
+                        tool: None,
+                        code: Some(err.code().to_string()),
+                        error: Some(err.to_string()),
+                    },
+                }
+            }
+        })
+        .buffered(BATCH_CONCURRENCY)
+        .collect()
+        .await;
+    Ok(Json(BatchToolsResponse { results }))
+}
+
+async fn start_tool_inner(state: &AppState, tool_id: &str) -> Result<McpTool, McpError> {
     let tool = state
         .store
-        .get_tool(&tool_id)
+        .get_tool(tool_id)
         .await?
-        .ok_or_else(|| McpError::NotFound(format!("tool {tool_id} not found")))?;
-    state.process_manager.start_tool(tool.clone()).await?;
-    let updated = state
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+    state.process_manager.start_tool(tool).await?;
+    state
         .store
-        .get_tool(&tool_id)
+        .get_tool(tool_id)
         .await?
-        .ok_or_else(|| McpError::NotFound(format!("tool {tool_id} not found")))?;
-    Ok(Json(updated))
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))
 }
 
-async fn stop_tool(
-    State(state): State<AppState>,
-    Path(tool_id): Path<String>,
-) -> Result<Json<McpTool>, McpError> {
-    state.process_manager.stop_tool(&tool_id).await?;
-    let updated = state
+async fn stop_tool_inner(state: &AppState, tool_id: &str) -> Result<McpTool, McpError> {
+    state.process_manager.stop_tool(tool_id).await?;
+    state
         .store
-        .get_tool(&tool_id)
+        .get_tool(tool_id)
         .await?
-        .ok_or_else(|| McpError::NotFound(format!("tool {tool_id} not found")))?;
-    Ok(Json(updated))
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/mcp/tools/{id}/config",
+    tag = "tools",
+    params(("id" = String, Path, description = "Tool id")),
+    request_body = UpdateToolConfigRequest,
+    responses(
+        (status = 200, body = McpTool),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
 async fn apply_pending_update(
     State(state): State<AppState>,
     Path(tool_id): Path<String>,
@@ -156,34 +693,102 @@ async fn apply_pending_update(
         ));
     }
 
+    let (tool, pending_value) = load_pending_update(&state, &tool_id).await?;
+    let updated = apply_tool_config(&state, tool, pending_value).await?;
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mcp/tools/{id}/conflict",
+    tag = "tools",
+    params(("id" = String, Path, description = "Tool id")),
+    request_body = ResolveConflictRequest,
+    responses(
+        (status = 200, body = McpTool),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn resolve_conflict(
+    State(state): State<AppState>,
+    Path(tool_id): Path<String>,
+    Json(payload): Json<ResolveConflictRequest>,
+) -> Result<Json<McpTool>, McpError> {
+    let updated = match payload.action.as_str() {
+        "update" => {
+            let (tool, pending_value) = load_pending_update(&state, &tool_id).await?;
+            apply_tool_config(&state, tool, pending_value).await?
+        }
+        "merge" => {
+            let (tool, pending_value) = load_pending_update(&state, &tool_id).await?;
+            let config_json = state
+                .store
+                .get_config_json(&tool_id)
+                .await?
+                .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+            let current_value: serde_json::Value = serde_json::from_str(&config_json)?;
+            let merged_value = merge_config(&current_value, tool.env.as_ref(), &pending_value);
+            apply_tool_config(&state, tool, merged_value).await?
+        }
+        "keep" => {
+            state.store.clear_pending_update(&tool_id).await?;
+            state
+                .store
+                .get_tool(&tool_id)
+                .await?
+                .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?
+        }
+        action => {
+            return Err(McpError::Validation(format!(
+                "invalid action: {action} (expected keep, update or merge)"
+            )))
+        }
+    };
+    Ok(Json(updated))
+}
+
+async fn load_pending_update(
+    state: &AppState,
+    tool_id: &str,
+) -> Result<(McpTool, serde_json::Value), McpError> {
     let tool = state
         .store
-        .get_tool(&tool_id)
+        .get_tool(tool_id)
         .await?
-        .ok_or_else(|| McpError::NotFound(format!("tool {tool_id} not found")))?;
-    let source_id = tool
-        .source_id
-        .clone()
-        .ok_or_else(|| McpError::Validation("tool missing source_id".to_string()))?;
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
     let pending_json = state
         .store
-        .get_pending_config_json(&tool_id)
+        .get_pending_config_json(tool_id)
         .await?
         .ok_or_else(|| McpError::Validation("no pending config".to_string()))?;
-
     let pending_value: serde_json::Value = serde_json::from_str(&pending_json)?;
-    let pending_payload: crate::mcp::McpToolConfigPayload =
-        serde_json::from_value(pending_value.clone())?;
+    Ok((tool, pending_value))
+}
+
+async fn apply_tool_config(
+    state: &AppState,
+    tool: McpTool,
+    config_value: serde_json::Value,
+) -> Result<McpTool, McpError> {
+    let source_id = tool
+        .source_id
+        .clone()
+        .ok_or_else(|| McpError::Validation("tool missing source_id".to_string()))?;
+    let payload: crate::mcp::McpToolConfigPayload =
+        serde_json::from_value(config_value.clone())?;
     let extracted = state
         .store
-        .extract_tool_fields(&tool.name, &pending_payload);
-    let config_hash = state.store.compute_config_hash(&pending_value)?;
+        .extract_tool_fields(&tool.name, &payload);
+    let config_hash = state.store.compute_config_hash(&config_value)?;
+    let config_json = serde_json::to_string(&config_value)?;
 
-    let updated = state
+    state
         .store
         .upsert_tool(ToolUpsert {
             id: Some(tool.id.clone()),
             source_id,
+            identifier: tool.identifier.clone(),
             name: extracted.name,
             source_type: tool.source_type.clone(),
             status: tool.status.clone(),
@@ -194,26 +799,154 @@ async fn apply_pending_update(
             command: extracted.command,
             args: extracted.args,
             env: extracted.env,
-            config_json: pending_json,
+            config_json,
             config_hash,
             pending_config_json: None,
             pending_config_hash: None,
             conflict_status: McpConflictStatus::None,
             is_read_only: tool.is_read_only,
+            is_new: tool.is_new,
         })
-        .await?;
+        .await
+}
 
+#[utoipa::path(
+    patch,
+    path = "/mcp/tools/{id}/env",
+    tag = "tools",
+    params(("id" = String, Path, description = "Tool id")),
+    request_body = UpdateToolEnvRequest,
+    responses(
+        (status = 200, body = McpTool),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn update_tool_env(
+    State(state): State<AppState>,
+    Path(tool_id): Path<String>,
+    Json(payload): Json<UpdateToolEnvRequest>,
+) -> Result<Json<McpTool>, McpError> {
+    let config_json = state
+        .store
+        .get_config_json(&tool_id)
+        .await?
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+    if let Some(env) = payload.env.as_ref() {
+        validate_env(&env_requirements(&config_json, Some(env)), env)?;
+    }
+    let updated = state.store.update_tool_env(&tool_id, payload.env).await?;
     Ok(Json(updated))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/mcp/tools/{id}/logs/buffer",
+    tag = "logs",
+    params(("id" = String, Path, description = "Tool id")),
+    request_body = UpdateLogBufferRequest,
+    responses(
+        (status = 200, body = McpTool),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn update_tool_log_buffer(
+    State(state): State<AppState>,
+    Path(tool_id): Path<String>,
+    Json(payload): Json<UpdateLogBufferRequest>,
+) -> Result<Json<McpTool>, McpError> {
+    if payload.log_buffer_size == Some(0) {
+        return Err(McpError::Validation("log_buffer_size must be at least 1".to_string()));
+    }
+    let updated = state
+        .store
+        .set_tool_log_buffer_size(&tool_id, payload.log_buffer_size)
+        .await?;
+    state
+        .process_manager
+        .set_tool_log_buffer_size(&tool_id, updated.log_buffer_size)
+        .await;
+    Ok(Json(updated))
+}
+
+#[utoipa::path(
+    get,
+    path = "/mcp/tools/{id}/logs",
+    tag = "logs",
+    params(("id" = String, Path, description = "Tool id"), LogFilterQuery),
+    responses(
+        (status = 200, body = ToolLogsResponse),
+        (status = 304, description = "Not modified (`If-None-Match` / `If-Modified-Since`)"),
+        (status = 400, description = "Invalid regex or timestamp", body = ErrorResponse),
+    )
+)]
 async fn tool_logs(
     State(state): State<AppState>,
     Path(tool_id): Path<String>,
-) -> Result<Json<ToolLogsResponse>, McpError> {
-    let entries = state.process_manager.logs(&tool_id).await;
-    Ok(Json(ToolLogsResponse { entries }))
+    Query(query): Query<LogFilterQuery>,
+    headers: HeaderMap,
+) -> Result<Response, McpError> {
+    let filter = LogFilter::new(&query)?;
+    let (entries, has_more) =
+        filter.apply(state.process_manager.logs(&tool_id).await, query.limit);
+    let last_modified = latest_timestamp(entries.iter().map(|entry| entry.timestamp.as_str()));
+    conditional_json(&headers, &ToolLogsResponse { entries, has_more }, last_modified)
 }
 
+#[utoipa::path(
+    delete,
+    path = "/mcp/tools/{id}/logs",
+    tag = "logs",
+    params(("id" = String, Path, description = "Tool id")),
+    responses(
+        (status = 204, description = "Logs cleared"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn clear_tool_logs(
+    State(state): State<AppState>,
+    Path(tool_id): Path<String>,
+) -> Result<StatusCode, McpError> {
+    state
+        .store
+        .get_tool(&tool_id)
+        .await?
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+    state.process_manager.clear_logs(&tool_id).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/mcp/logs/stream",
+    tag = "logs",
+    responses(
+        (status = 200, description = "Server-sent `McpToolLogEntry` events for every tool", content_type = "text/event-stream"),
+    )
+)]
+async fn all_logs_stream(
+    State(state): State<AppState>,
+) -> Sse<impl futures_util::Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.process_manager.subscribe_all();
+    let stream = BroadcastStream::new(receiver).filter_map(|result| async {
+        match result {
+            Ok(entry) => Event::default().json_data(entry).ok().map(Ok),
+            Err(_) => None,
+        }
+    });
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/mcp/tools/{id}/logs/stream",
+    tag = "logs",
+    params(("id" = String, Path, description = "Tool id")),
+    responses(
+        (status = 200, description = "Server-sent `McpLogEntry` events", content_type = "text/event-stream"),
+    )
+)]
 async fn tool_logs_stream(
     State(state): State<AppState>,
     Path(tool_id): Path<String>,
@@ -231,164 +964,353 @@ async fn tool_logs_stream(
     Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
 }
 
-async fn sync_source_inner(
-    state: &AppState,
-    source: McpSource,
-    auth_token: Option<String>,
-) -> Result<Vec<McpTool>, McpError> {
-    let payload = match source.source_type {
-        McpSourceType::Local => {
-            let path = expand_path(&source.path_or_url);
-            let content = tokio::fs::read_to_string(&path).await?;
-            serde_json::from_str::<McpConfigPayload>(&content)?
-        }
-        _ => {
-            let client = reqwest::Client::new();
-            let mut request = client.get(&source.path_or_url);
-            if let Some(token) = auth_token {
-                request = request.bearer_auth(token);
-            }
-            let response = request
-                .send()
-                .await
-                .map_err(|err| McpError::Process(err.to_string()))?;
-            if !response.status().is_success() {
-                return Err(McpError::Process(format!(
-                    "sync failed with status {}",
-                    response.status()
-                )));
-            }
-            response
-                .json::<McpConfigPayload>()
-                .await
-                .map_err(|err| McpError::Process(err.to_string()))?
-        }
-    };
+#[utoipa::path(
+    get,
+    path = "/mcp/auth/tokens",
+    tag = "auth",
+    responses(
+        (status = 200, body = ListApiTokensResponse),
+    )
+)]
+async fn list_api_tokens(
+    State(state): State<AppState>,
+) -> Result<Json<ListApiTokensResponse>, McpError> {
+    let tokens = state.store.list_api_tokens().await?;
+    Ok(Json(ListApiTokensResponse { tokens }))
+}
 
-    apply_config_payload(state, &source, payload).await
+#[utoipa::path(
+    post,
+    path = "/mcp/auth/tokens",
+    tag = "auth",
+    request_body = CreateApiTokenRequest,
+    responses(
+        (status = 200, body = CreateApiTokenResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+    )
+)]
+async fn create_api_token(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateApiTokenRequest>,
+) -> Result<Json<CreateApiTokenResponse>, McpError> {
+    let (token, secret) = state.store.create_api_token(&payload.name).await?;
+    Ok(Json(CreateApiTokenResponse { token, secret }))
 }
 
-async fn apply_config_payload(
-    state: &AppState,
-    source: &McpSource,
-    payload: McpConfigPayload,
-) -> Result<Vec<McpTool>, McpError> {
-    let mut tools = Vec::with_capacity(payload.mcp_servers.len());
-    let is_read_only = source.source_type != McpSourceType::Local || source.is_read_only;
-
-    for (name, config_payload) in payload.mcp_servers {
-        let config_value = state.store.build_config_json(&name, &config_payload)?;
-        let config_hash = state.store.compute_config_hash(&config_value)?;
-        let config_json = serde_json::to_string(&config_value)?;
-        let extracted: ExtractedToolFields = state.store.extract_tool_fields(&name, &config_payload);
-        let name_conflict = state
-            .store
-            .has_name_conflict(&name, &source.id)
-            .await?;
+#[utoipa::path(
+    delete,
+    path = "/mcp/auth/tokens/{id}",
+    tag = "auth",
+    params(("id" = String, Path, description = "Token id")),
+    responses(
+        (status = 204, description = "Token revoked"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn delete_api_token(
+    State(state): State<AppState>,
+    Path(token_id): Path<String>,
+) -> Result<StatusCode, McpError> {
+    state.store.delete_api_token(&token_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-        let existing = state
-            .store
-            .get_tool_by_source_name(&source.id, &name)
-            .await?;
-
-        let tool = match existing {
-            Some(existing_tool) => {
-                if existing_tool.config_hash == config_hash {
-                    existing_tool
-                } else if is_read_only {
-                    let conflict_status = if name_conflict {
-                        McpConflictStatus::Conflict
-                    } else {
-                        McpConflictStatus::UpdateAvailable
-                    };
-                    state
-                        .store
-                        .mark_tool_pending_update(
-                            &existing_tool.id,
-                            config_json,
-                            config_hash,
-                            conflict_status,
-                        )
-                        .await?;
-                    state
-                        .store
-                        .get_tool(&existing_tool.id)
-                        .await?
-                        .ok_or_else(|| McpError::NotFound("tool missing after update".to_string()))?
+#[utoipa::path(
+    get,
+    path = "/mcp/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, body = ListWebhooksResponse),
+    )
+)]
+async fn list_webhooks(
+    State(state): State<AppState>,
+) -> Result<Json<ListWebhooksResponse>, McpError> {
+    let webhooks = state.store.list_webhooks().await?;
+    Ok(Json(ListWebhooksResponse { webhooks }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mcp/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, body = CreateWebhookResponse),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+    )
+)]
+async fn create_webhook(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<Json<CreateWebhookResponse>, McpError> {
+    let (webhook, secret) = state
+        .store
+        .create_webhook(&payload.url, payload.secret, payload.events)
+        .await?;
+    Ok(Json(CreateWebhookResponse { webhook, secret }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/mcp/webhooks/{id}",
+    tag = "webhooks",
+    params(("id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn delete_webhook(
+    State(state): State<AppState>,
+    Path(webhook_id): Path<String>,
+) -> Result<StatusCode, McpError> {
+    state.store.delete_webhook(&webhook_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/assistants",
+    tag = "assistants",
+    responses(
+        (status = 200, body = ListAssistantsResponse),
+    )
+)]
+async fn list_assistants(
+    State(state): State<AppState>,
+) -> Result<Json<ListAssistantsResponse>, McpError> {
+    let assistants = state.store.list_assistants().await?;
+    Ok(Json(ListAssistantsResponse { assistants }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/assistants",
+    tag = "assistants",
+    request_body = CreateLocalAssistantRequest,
+    responses(
+        (status = 200, body = LocalAssistant),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+    )
+)]
+async fn create_assistant(
+    State(state): State<AppState>,
+    Json(payload): Json<CreateLocalAssistantRequest>,
+) -> Result<Json<LocalAssistant>, McpError> {
+    let assistant = state.store.create_assistant(payload).await?;
+    Ok(Json(assistant))
+}
+
+#[utoipa::path(
+    get,
+    path = "/assistants/{id}",
+    tag = "assistants",
+    params(("id" = String, Path, description = "Assistant id")),
+    responses(
+        (status = 200, body = LocalAssistant),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn get_assistant(
+    State(state): State<AppState>,
+    Path(assistant_id): Path<String>,
+) -> Result<Json<LocalAssistant>, McpError> {
+    let assistant = state
+        .store
+        .get_assistant(&assistant_id)
+        .await?
+        .ok_or_else(|| McpError::NotFound(format!("assistant {assistant_id} not found")))?;
+    Ok(Json(assistant))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/assistants/{id}",
+    tag = "assistants",
+    params(("id" = String, Path, description = "Assistant id")),
+    request_body = UpdateLocalAssistantRequest,
+    responses(
+        (status = 200, body = LocalAssistant),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn update_assistant(
+    State(state): State<AppState>,
+    Path(assistant_id): Path<String>,
+    Json(payload): Json<UpdateLocalAssistantRequest>,
+) -> Result<Json<LocalAssistant>, McpError> {
+    let assistant = state.store.update_assistant(&assistant_id, payload).await?;
+    Ok(Json(assistant))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/assistants/{id}",
+    tag = "assistants",
+    params(("id" = String, Path, description = "Assistant id")),
+    responses(
+        (status = 204, description = "Assistant deleted"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn delete_assistant(
+    State(state): State<AppState>,
+    Path(assistant_id): Path<String>,
+) -> Result<StatusCode, McpError> {
+    state.store.delete_assistant(&assistant_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/assistants/{id}/messages",
+    tag = "assistants",
+    params(("id" = String, Path, description = "Assistant id")),
+    responses(
+        (status = 200, body = ListAssistantMessagesResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn list_assistant_messages(
+    State(state): State<AppState>,
+    Path(assistant_id): Path<String>,
+) -> Result<Json<ListAssistantMessagesResponse>, McpError> {
+    ensure_assistant(&state, &assistant_id).await?;
+    let messages = state.store.list_assistant_messages(&assistant_id).await?;
+    Ok(Json(ListAssistantMessagesResponse { messages }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/assistants/{id}/messages",
+    tag = "assistants",
+    params(("id" = String, Path, description = "Assistant id")),
+    request_body = AppendAssistantMessageRequest,
+    responses(
+        (status = 200, body = LocalAssistantMessage),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn append_assistant_message(
+    State(state): State<AppState>,
+    Path(assistant_id): Path<String>,
+    Json(payload): Json<AppendAssistantMessageRequest>,
+) -> Result<Json<LocalAssistantMessage>, McpError> {
+    ensure_assistant(&state, &assistant_id).await?;
+    let message = state
+        .store
+        .append_assistant_message(CreateAssistantMessageRequest {
+            assistant_id,
+            role: payload.role,
+            content: payload.content,
+        })
+        .await?;
+    Ok(Json(message))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/assistants/{id}/messages",
+    tag = "assistants",
+    params(("id" = String, Path, description = "Assistant id")),
+    responses(
+        (status = 204, description = "History cleared"),
+        (status = 404, description = "Not found", body = ErrorResponse),
+    )
+)]
+async fn clear_assistant_messages(
+    State(state): State<AppState>,
+    Path(assistant_id): Path<String>,
+) -> Result<StatusCode, McpError> {
+    ensure_assistant(&state, &assistant_id).await?;
+    state.store.delete_assistant_messages(&assistant_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn ensure_assistant(state: &AppState, assistant_id: &str) -> Result<(), McpError> {
+    state
+        .store
+        .get_assistant(assistant_id)
+        .await?
+        .ok_or_else(|| McpError::NotFound(format!("assistant {assistant_id} not found")))?;
+    Ok(())
+}
+
+/// Streams tool status changes, crashes and detected conflicts. Store writes
+/// that leave status, conflict_status and ping untouched are filtered out per
+/// connection.
+#[utoipa::path(
+    get,
+    path = "/mcp/tools/events",
+    tag = "tools",
+    responses(
+        (status = 200, description = "Server-sent `tool_status`, `tool_removed`, `tool_crashed` and `conflict_detected` events", content_type = "text/event-stream"),
+    )
+)]
+async fn tool_events_stream(
+    State(state): State<AppState>,
+) -> Sse<impl futures_util::Stream<Item = Result<Event, Infallible>>> {
+    let receiver = state.store.subscribe_events();
+    let mut last_seen: HashMap<String, (McpToolStatus, McpConflictStatus, Option<i64>)> =
+        HashMap::new();
+    let stream = BroadcastStream::new(receiver).filter_map(move |result| {
+        let event = match result {
+            Ok(McpEvent::ToolStatus(event)) => {
+                let snapshot = (
+                    event.status.clone(),
+                    event.conflict_status.clone(),
+                    event.ping_ms,
+                );
+                if last_seen.get(&event.tool_id) == Some(&snapshot) {
+                    None
                 } else {
-                    state
-                        .store
-                        .upsert_tool(ToolUpsert {
-                            id: Some(existing_tool.id.clone()),
-                            source_id: source.id.clone(),
-                            name: extracted.name,
-                            source_type: source.source_type.clone(),
-                            status: existing_tool.status.clone(),
-                            ping_ms: existing_tool.ping_ms,
-                            capabilities: extracted.capabilities,
-                            description: extracted.description,
-                            error: existing_tool.error.clone(),
-                            command: extracted.command,
-                            args: extracted.args,
-                            env: extracted.env,
-                            config_json,
-                            config_hash,
-                            pending_config_json: None,
-                            pending_config_hash: None,
-                            conflict_status: if name_conflict {
-                                McpConflictStatus::Conflict
-                            } else {
-                                McpConflictStatus::None
-                            },
-                            is_read_only,
-                        })
-                        .await?
+                    last_seen.insert(event.tool_id.clone(), snapshot);
+                    Event::default().event("tool_status").json_data(event).ok()
                 }
             }
-            None => state
-                .store
-                .upsert_tool(ToolUpsert {
-                    id: None,
-                    source_id: source.id.clone(),
-                    name: extracted.name,
-                    source_type: source.source_type.clone(),
-                    status: McpToolStatus::Stopped,
-                    ping_ms: None,
-                    capabilities: extracted.capabilities,
-                    description: extracted.description,
-                    error: None,
-                    command: extracted.command,
-                    args: extracted.args,
-                    env: extracted.env,
-                    config_json,
-                    config_hash,
-                    pending_config_json: None,
-                    pending_config_hash: None,
-                    conflict_status: if name_conflict {
-                        McpConflictStatus::Conflict
-                    } else {
-                        McpConflictStatus::None
-                    },
-                    is_read_only,
-                })
-                .await?,
+            Ok(McpEvent::ToolRemoved { tool_id }) => {
+                last_seen.remove(&tool_id);
+                Event::default()
+                    .event("tool_removed")
+                    .json_data(serde_json::json!({ "tool_id": tool_id }))
+                    .ok()
+            }
+            Ok(McpEvent::ToolCrashed { tool_id, exit_code }) => Event::default()
+                .event("tool_crashed")
+                .json_data(serde_json::json!({ "tool_id": tool_id, "exit_code": exit_code }))
+                .ok(),
+            Ok(McpEvent::ConflictDetected {
+                tool_id,
+                conflict_status,
+            }) => Event::default()
+                .event("conflict_detected")
+                .json_data(serde_json::json!({
+                    "tool_id": tool_id,
+                    "conflict_status": conflict_status,
+                }))
+                .ok(),
+            _ => None,
         };
-
-        tools.push(tool);
-    }
-
-    Ok(tools)
+        futures_util::future::ready(event.map(Ok))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(15)))
 }
 
-fn expand_path(path: &str) -> PathBuf {
-    if let Some(stripped) = path.strip_prefix("~/") {
-        if let Ok(home) = std::env::var("HOME") {
-            return PathBuf::from(home).join(stripped);
-        }
+pub(crate) async fn sync_source_inner(
+    state: &AppState,
+    source: McpSource,
+    auth_token: Option<String>,
+) -> Result<Vec<McpTool>, McpError> {
+    if source.source_type != McpSourceType::Cloud {
+        return sync::sync_source(&state.store, &state.providers, source, auth_token).await;
     }
-    PathBuf::from(path)
-}
-
-fn now_rfc3339() -> Result<String, McpError> {
-    Ok(time::OffsetDateTime::now_utc().format(&time::format_description::well_known::Rfc3339)?)
+    let token = match auth_token {
+        Some(token) => token,
+        None => cloud_session(state, &source.id).await?,
+    };
+    let outcome = super::cloud::sync_cloud_subscriptions(state, &source, &token).await?;
+    Ok(outcome.tools)
 }
@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use crate::mcp::routes::sync_source_inner;
+use crate::mcp::sync;
+use crate::mcp::{ListSourcesQuery, McpSourceType};
+use crate::state::AppState;
+
+/// Syncs every source each `DESKTOP_SYNC_INTERVAL_SECS` (off at 0). A reload
+/// that changes the interval takes effect at once and restarts the wait.
+pub async fn run(state: AppState) {
+    let mut settings = state.settings.subscribe();
+    loop {
+        let interval = settings.borrow_and_update().sync_interval_secs;
+        if interval == 0 {
+            if settings.changed().await.is_err() {
+                return;
+            }
+            continue;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(interval)) => sync_all(&state).await,
+            changed = settings.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn sync_all(state: &AppState) {
+    if !state.readiness.is_ready() {
+        return;
+    }
+    let sources = match state.store.query_sources(&ListSourcesQuery::default()).await {
+        Ok((sources, _)) => sources,
+        Err(err) => {
+            warn!("scheduled sync could not list sources: {}", err);
+            return;
+        }
+    };
+    info!(count = sources.len(), "scheduled sync");
+    for source in sources {
+        let source_id = source.id.clone();
+        // Cloud sources wait for a session from `POST /mcp/cloud/sync`.
+        if source.source_type == McpSourceType::Cloud {
+            match state.store.get_cloud_session(&source_id).await {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    debug!(source_id, "skipping cloud source without a session");
+                    continue;
+                }
+                Err(err) => {
+                    warn!(source_id, "scheduled sync failed: {}", err);
+                    continue;
+                }
+            }
+        }
+        let sync = sync_source_inner(state, source, None);
+        if let Err(err) = sync::track_sync(&state.store, &source_id, sync).await {
+            warn!(source_id, "scheduled sync failed: {}", err);
+        }
+    }
+}
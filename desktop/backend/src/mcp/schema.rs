@@ -0,0 +1,86 @@
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::mcp::cloud::CloudInstallManifest;
+use crate::mcp::McpConfigPayload;
+
+/// Schema of an `mcpServers` config as accepted by `/mcp/tools/import`.
+pub fn config_schema() -> RootSchema {
+    schema_for!(McpConfigPayload)
+}
+
+/// Schema of the `install_manifest` a cloud subscription carries.
+pub fn cloud_manifest_schema() -> RootSchema {
+    schema_for!(CloudInstallManifest)
+}
+
+/// Result of checking pasted config text before importing it.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigValidation {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ConfigValidationError>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConfigValidationError {
+    pub message: String,
+    /// 1-based, like an editor gutter.
+    pub line: usize,
+    /// 1-based; `0` when the text ended early.
+    pub column: usize,
+}
+
+/// Parses `text` exactly as an import would and points at the first problem.
+pub fn validate_config(text: &str) -> ConfigValidation {
+    let error = serde_json::from_str::<McpConfigPayload>(text)
+        .err()
+        .map(|err| {
+            let message = err.to_string();
+            // The position is reported separately.
+            let message = match message.rsplit_once(" at line ") {
+                Some((message, _)) => message.to_string(),
+                None => message,
+            };
+            ConfigValidationError {
+                message,
+                line: err.line(),
+                column: err.column(),
+            }
+        });
+    ConfigValidation {
+        valid: error.is_none(),
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_at_the_first_invalid_field() {
+        let valid = validate_config(r#"{"mcpServers": {"fs": {"command": "npx"}}}"#);
+        assert!(valid.valid);
+
+        let invalid =
+            validate_config("{\n  \"mcpServers\": {\n    \"fs\": {\"args\": \"-y\"}\n  }\n}");
+        assert!(!invalid.valid);
+        let error = invalid.error.unwrap();
+        assert_eq!(error.line, 3);
+        assert!(
+            error.message.starts_with("invalid type: string"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[test]
+    fn config_schema_describes_servers() {
+        let schema = serde_json::to_value(config_schema()).unwrap();
+        assert!(schema["properties"]["mcpServers"].is_object());
+        assert_eq!(schema["required"], serde_json::json!(["mcpServers"]));
+    }
+}
@@ -0,0 +1,150 @@
+//! Encryption of stored cloud access tokens. The key is kept in
+//! `session.key` beside the database (owner-only on Unix); in-memory
+//! databases get a key that lives as long as the process.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use super::McpError;
+
+const KEY_FILE: &str = "session.key";
+/// Marks values written by [`SessionCipher::encrypt`]; anything else in
+/// `cloud_sessions` is a plaintext token from an earlier version.
+const PREFIX: &str = "v1:";
+const NONCE_LEN: usize = 12;
+
+pub struct SessionCipher {
+    cipher: ChaCha20Poly1305,
+}
+
+impl SessionCipher {
+    /// Loads the key beside the database at `database_url`, creating it on
+    /// first use.
+    pub fn open(database_url: &str) -> Result<Self, McpError> {
+        let Some(path) = key_path(database_url) else {
+            return Ok(Self::ephemeral());
+        };
+        let key = match fs::read_to_string(&path) {
+            Ok(contents) => parse_key(&contents).ok_or_else(|| {
+                McpError::Validation(format!("{} is not a valid session key", path.display()))
+            })?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => create_key(&path)?,
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self {
+            cipher: ChaCha20Poly1305::new(&key),
+        })
+    }
+
+    pub fn ephemeral() -> Self {
+        Self {
+            cipher: ChaCha20Poly1305::new(&ChaCha20Poly1305::generate_key(&mut OsRng)),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .expect("encrypting into a Vec cannot fail");
+        let mut bytes = nonce.to_vec();
+        bytes.extend_from_slice(&ciphertext);
+        format!("{PREFIX}{}", hex::encode(bytes))
+    }
+
+    /// `None` when `stored` was written under another key or was altered.
+    pub fn decrypt(&self, stored: &str) -> Option<String> {
+        let bytes = hex::decode(stored.strip_prefix(PREFIX)?).ok()?;
+        if bytes.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+
+    pub fn is_encrypted(stored: &str) -> bool {
+        stored.starts_with(PREFIX)
+    }
+}
+
+/// The key file for a `sqlite://path?mode=rwc` style URL; `None` for
+/// in-memory databases.
+fn key_path(database_url: &str) -> Option<PathBuf> {
+    let rest = database_url
+        .strip_prefix("sqlite://")
+        .or_else(|| database_url.strip_prefix("sqlite:"))
+        .unwrap_or(database_url);
+    let path = rest.split('?').next().unwrap_or_default();
+    if path.is_empty() || path == ":memory:" {
+        return None;
+    }
+    let dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+    Some(dir.join(KEY_FILE))
+}
+
+fn parse_key(contents: &str) -> Option<Key> {
+    let bytes = hex::decode(contents.trim()).ok()?;
+    (bytes.len() == 32).then(|| *Key::from_slice(&bytes))
+}
+
+fn create_key(path: &Path) -> Result<Key, McpError> {
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    match options.open(path) {
+        Ok(mut file) => {
+            file.write_all(hex::encode(key).as_bytes())?;
+            Ok(key)
+        }
+        // Another store opened the same database first.
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+            parse_key(&fs::read_to_string(path)?).ok_or_else(|| {
+                McpError::Validation(format!("{} is not a valid session key", path.display()))
+            })
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_rejects_tampering() {
+        let cipher = SessionCipher::ephemeral();
+        let stored = cipher.encrypt("token");
+        assert!(SessionCipher::is_encrypted(&stored));
+        assert!(!stored.contains("token"));
+        assert_eq!(cipher.decrypt(&stored).as_deref(), Some("token"));
+
+        let mut tampered = stored.clone();
+        let last = if tampered.ends_with('0') { "1" } else { "0" };
+        tampered.replace_range(tampered.len() - 1.., last);
+        assert_eq!(cipher.decrypt(&tampered), None);
+        assert_eq!(SessionCipher::ephemeral().decrypt(&stored), None);
+    }
+
+    #[test]
+    fn keeps_the_key_beside_the_database() {
+        assert_eq!(key_path("sqlite::memory:"), None);
+        assert_eq!(
+            key_path("sqlite:///data/deeting/mcp.db?mode=rwc"),
+            Some(PathBuf::from("/data/deeting/session.key"))
+        );
+    }
+}
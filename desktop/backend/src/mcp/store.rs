@@ -1,26 +1,131 @@
-use std::collections::HashMap;
-
-use sqlx::{Row, SqlitePool};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use mcp_core::hash::{hash_json, hash_token};
+use mcp_core::pool::{self, PoolSettings};
+use mcp_core::tool_cache::ToolCache;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
-use super::hash::hash_json;
+use super::events::{McpEvent, SourceStatusEvent, ToolStatusEvent, EVENT_CHANNEL_CAPACITY};
+use super::session_cipher::SessionCipher;
 use super::types::{
-    McpConflictStatus, McpSource, McpSourceStatus, McpSourceType, McpTool, McpToolStatus,
-    McpTrustLevel, McpToolConfigPayload,
+    ApiToken, CreateAssistantMessageRequest, CreateLocalAssistantRequest, DatabaseStats, HealthCheck,
+    ListSourcesQuery,
+    ListToolsQuery, LocalAssistant, LocalAssistantMessage, McpConflictStatus, McpSource,
+    McpSourceStatus, McpSourceType, McpTool, McpToolDetail, McpToolStatus, McpTrustLevel, McpToolConfigPayload,
+    PoolStats, SourceDeleteMode, ToolHooks, UpdateLocalAssistantRequest, Webhook, WebhookEvent,
 };
 use super::McpError;
+use crate::paths::default_local_config_path;
 
 const DEFAULT_LOCAL_SOURCE_NAME: &str = "Local Config";
-const DEFAULT_LOCAL_SOURCE_PATH: &str = "~/.config/deeting/mcp.json";
+
+/// [`PoolSettings`] from the environment, defaulting to 10 connections, a
+/// 30s acquire timeout and 100 cached statements.
+fn pool_settings() -> PoolSettings {
+    let defaults = PoolSettings {
+        max_connections: 10,
+        acquire_timeout: Duration::from_secs(30),
+        statement_cache: 100,
+    };
+    PoolSettings::from_env(defaults, |key, value| {
+        tracing::warn!("ignoring invalid {key}={value:?}")
+    })
+}
 
 pub struct McpStore {
     pool: SqlitePool,
+    /// Long reads (log and history listings, stats); see [`pool::connect`].
+    read_pool: SqlitePool,
+    events: broadcast::Sender<McpEvent>,
+    tool_cache: ToolCache<McpTool>,
+    /// What was last applied per source: the content hash of a local config
+    /// file, or the `ETag` (else body hash) of a cloud subscriptions response.
+    applied_configs: std::sync::Mutex<HashMap<String, String>>,
+    session_cipher: SessionCipher,
 }
 
 impl McpStore {
     pub async fn new(database_url: &str) -> Result<Self, McpError> {
-        let pool = SqlitePool::connect(database_url).await?;
-        Ok(Self { pool })
+        let (pool, read_pool) = pool::connect(&pool_settings(), database_url).await?;
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Ok(Self {
+            pool,
+            read_pool,
+            events,
+            tool_cache: ToolCache::default(),
+            applied_configs: std::sync::Mutex::default(),
+            session_cipher: SessionCipher::open(database_url)?,
+        })
+    }
+
+    pub fn subscribe_events(&self) -> broadcast::Receiver<McpEvent> {
+        self.events.subscribe()
+    }
+
+    pub fn publish(&self, event: McpEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// Publishes a freshly written tool, flagging it when the write left it
+    /// in conflict.
+    fn publish_written_tool(&self, tool: &McpTool) {
+        self.publish(McpEvent::ToolStatus(ToolStatusEvent::from(tool)));
+        if tool.conflict_status != McpConflictStatus::None {
+            self.publish(McpEvent::ConflictDetected {
+                tool_id: tool.id.clone(),
+                conflict_status: tool.conflict_status.clone(),
+            });
+        }
+    }
+
+    /// Whether `content_hash` is the config last applied to the source, so
+    /// syncing it again would change nothing.
+    pub fn is_config_applied(&self, source_id: &str, content_hash: &str) -> bool {
+        self.applied_configs
+            .lock()
+            .expect("applied configs poisoned")
+            .get(source_id)
+            .is_some_and(|applied| applied == content_hash)
+    }
+
+    pub fn applied_config(&self, source_id: &str) -> Option<String> {
+        self.applied_configs
+            .lock()
+            .expect("applied configs poisoned")
+            .get(source_id)
+            .cloned()
+    }
+
+    pub fn record_applied_config(&self, source_id: &str, content_hash: String) {
+        self.applied_configs
+            .lock()
+            .expect("applied configs poisoned")
+            .insert(source_id.to_string(), content_hash);
+    }
+
+    /// Makes the next sync of the source apply its config again, after its
+    /// tools were changed some other way.
+    pub fn forget_applied_config(&self, source_id: &str) {
+        self.applied_configs
+            .lock()
+            .expect("applied configs poisoned")
+            .remove(source_id);
+    }
+
+    /// Re-reads a tool after a status-affecting write and publishes it. The
+    /// read is skipped while nobody is listening.
+    async fn publish_tool_status(&self, id: &str) -> Result<(), McpError> {
+        if self.events.receiver_count() == 0 {
+            return Ok(());
+        }
+        if let Some(tool) = self.get_tool(id).await? {
+            self.publish(McpEvent::ToolStatus(ToolStatusEvent::from(&tool)));
+        }
+        Ok(())
     }
 
 
@@ -49,6 +154,7 @@ impl McpStore {
             CREATE TABLE IF NOT EXISTS mcp_tools (
               id TEXT PRIMARY KEY,
               source_id TEXT NOT NULL,
+              identifier TEXT,
               name TEXT NOT NULL,
               source_type TEXT NOT NULL,
               status TEXT NOT NULL,
@@ -65,6 +171,10 @@ impl McpStore {
               pending_config_hash TEXT,
               conflict_status TEXT NOT NULL,
               is_read_only INTEGER NOT NULL,
+              is_new INTEGER NOT NULL,
+              approved_at TEXT,
+              market_tool_id TEXT,
+              log_buffer_size INTEGER,
               created_at TEXT NOT NULL,
               updated_at TEXT NOT NULL,
               FOREIGN KEY (source_id) REFERENCES mcp_sources(id)
@@ -74,6 +184,55 @@ impl McpStore {
         .execute(&self.pool)
         .await?;
 
+        self.ensure_column(
+            "mcp_tools",
+            "identifier",
+            "ALTER TABLE mcp_tools ADD COLUMN identifier TEXT;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "pending_config_json",
+            "ALTER TABLE mcp_tools ADD COLUMN pending_config_json TEXT;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "pending_config_hash",
+            "ALTER TABLE mcp_tools ADD COLUMN pending_config_hash TEXT;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "is_new",
+            "ALTER TABLE mcp_tools ADD COLUMN is_new INTEGER NOT NULL DEFAULT 0;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "approved_at",
+            "ALTER TABLE mcp_tools ADD COLUMN approved_at TEXT;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "market_tool_id",
+            "ALTER TABLE mcp_tools ADD COLUMN market_tool_id TEXT;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "mcp_tools",
+            "log_buffer_size",
+            "ALTER TABLE mcp_tools ADD COLUMN log_buffer_size INTEGER;",
+        )
+        .await?;
+
         sqlx::query(
             r#"
             CREATE UNIQUE INDEX IF NOT EXISTS idx_mcp_tools_source_name
@@ -83,6 +242,152 @@ impl McpStore {
         .execute(&self.pool)
         .await?;
 
+        sqlx::query(
+            r#"
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_mcp_tools_source_identifier
+            ON mcp_tools(source_id, identifier);
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS assistants (
+              id TEXT PRIMARY KEY,
+              name TEXT NOT NULL,
+              description TEXT,
+              avatar TEXT,
+              system_prompt TEXT NOT NULL,
+              model_config TEXT,
+              tags TEXT,
+              visibility TEXT NOT NULL,
+              source TEXT NOT NULL,
+              cloud_id TEXT,
+              cloud_updated_at TEXT,
+              cloud_synced_at TEXT,
+              is_deleted INTEGER NOT NULL,
+              created_at TEXT NOT NULL,
+              updated_at TEXT NOT NULL
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS assistant_messages (
+              id TEXT PRIMARY KEY,
+              assistant_id TEXT NOT NULL,
+              role TEXT NOT NULL,
+              content TEXT NOT NULL,
+              is_deleted INTEGER NOT NULL,
+              created_at TEXT NOT NULL,
+              updated_at TEXT NOT NULL,
+              FOREIGN KEY (assistant_id) REFERENCES assistants(id)
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_assistant_messages_assistant_id_created_at
+            ON assistant_messages(assistant_id, created_at);
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.ensure_column(
+            "assistants",
+            "cloud_updated_at",
+            "ALTER TABLE assistants ADD COLUMN cloud_updated_at TEXT;",
+        )
+        .await?;
+
+        self.ensure_column(
+            "assistants",
+            "cloud_synced_at",
+            "ALTER TABLE assistants ADD COLUMN cloud_synced_at TEXT;",
+        )
+        .await?;
+
+        // Owned by the desktop app; created here so both sides can open the
+        // same database file.
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS app_settings (
+              key TEXT PRIMARY KEY,
+              value TEXT NOT NULL,
+              updated_at TEXT NOT NULL
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS api_tokens (
+              id TEXT PRIMARY KEY,
+              name TEXT NOT NULL,
+              token_hash TEXT NOT NULL UNIQUE,
+              created_at TEXT NOT NULL,
+              last_used_at TEXT
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS webhooks (
+              id TEXT PRIMARY KEY,
+              url TEXT NOT NULL,
+              secret TEXT NOT NULL,
+              events TEXT NOT NULL,
+              created_at TEXT NOT NULL,
+              last_delivery_at TEXT,
+              last_error TEXT
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS cloud_sessions (
+              source_id TEXT PRIMARY KEY,
+              access_token TEXT NOT NULL,
+              updated_at TEXT NOT NULL,
+              FOREIGN KEY (source_id) REFERENCES mcp_sources(id) ON DELETE CASCADE
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Adds a column to databases created before it existed.
+    async fn ensure_column(&self, table: &str, column: &str, ddl: &str) -> Result<(), McpError> {
+        let rows = sqlx::query(&format!("PRAGMA table_info({table})"))
+            .fetch_all(&self.pool)
+            .await?;
+        let exists = rows.iter().any(|row| {
+            row.try_get::<String, _>("name")
+                .map(|name| name == column)
+                .unwrap_or(false)
+        });
+        if !exists {
+            sqlx::query(ddl).execute(&self.pool).await?;
+        }
         Ok(())
     }
 
@@ -93,23 +398,27 @@ impl McpStore {
 
         let now = now_rfc3339()?;
         let id = Uuid::new_v4().to_string();
-        sqlx::query(
+        let source_type = McpSourceType::Local.as_str();
+        let path_or_url = default_local_config_path().to_string_lossy().into_owned();
+        let trust_level = McpTrustLevel::Private.as_str();
+        let status = McpSourceStatus::Active.as_str();
+        sqlx::query!(
             r#"
             INSERT INTO mcp_sources
               (id, name, source_type, path_or_url, trust_level, status, last_synced_at, is_read_only, created_at, updated_at)
             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
             "#,
+            id,
+            DEFAULT_LOCAL_SOURCE_NAME,
+            source_type,
+            path_or_url,
+            trust_level,
+            status,
+            None::<String>,
+            0,
+            now,
+            now,
         )
-        .bind(&id)
-        .bind(DEFAULT_LOCAL_SOURCE_NAME)
-        .bind(McpSourceType::Local.as_str())
-        .bind(DEFAULT_LOCAL_SOURCE_PATH)
-        .bind(McpTrustLevel::Private.as_str())
-        .bind(McpSourceStatus::Active.as_str())
-        .bind::<Option<String>>(None)
-        .bind(0)
-        .bind(&now)
-        .bind(&now)
         .execute(&self.pool)
         .await?;
 
@@ -118,82 +427,183 @@ impl McpStore {
             .ok_or_else(|| McpError::NotFound("local source missing after insert".to_string()))
     }
 
-    pub async fn list_sources(&self) -> Result<Vec<McpSource>, McpError> {
-        let rows = sqlx::query(
+    /// Filtered page of sources plus the total number of matches.
+    pub async fn query_sources(
+        &self,
+        query: &ListSourcesQuery,
+    ) -> Result<(Vec<McpSource>, i64), McpError> {
+        let (limit, offset) = page_bounds(query.limit, query.offset)?;
+        let status = query.status.as_ref().map(|status| status.as_str());
+        let pattern = like_pattern(query.q.as_deref());
+
+        let pattern = pattern.as_deref();
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "total: i64"
+            FROM mcp_sources
+            WHERE (?1 IS NULL OR status = ?1)
+              AND (?2 IS NULL OR name LIKE ?2 ESCAPE '\' OR path_or_url LIKE ?2 ESCAPE '\');
+            "#,
+            status,
+            pattern,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        let rows = sqlx::query_as!(
+            SourceRow,
             r#"
-            SELECT id, name, source_type, path_or_url, trust_level, status, last_synced_at,
+            SELECT id AS "id!", name, source_type, path_or_url, trust_level, status, last_synced_at,
                    is_read_only, created_at, updated_at
             FROM mcp_sources
-            ORDER BY created_at ASC;
+            WHERE (?1 IS NULL OR status = ?1)
+              AND (?2 IS NULL OR name LIKE ?2 ESCAPE '\' OR path_or_url LIKE ?2 ESCAPE '\')
+            ORDER BY created_at ASC
+            LIMIT ?3 OFFSET ?4;
             "#,
+            status,
+            pattern,
+            limit,
+            offset,
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let mut sources = Vec::with_capacity(rows.len());
-        for row in rows {
-            sources.push(row_to_source(&row)?);
-        }
-        Ok(sources)
+        let sources = rows
+            .into_iter()
+            .map(McpSource::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok((sources, total))
     }
 
     pub async fn get_source(&self, id: &str) -> Result<Option<McpSource>, McpError> {
-        let row = sqlx::query(
+        let row = sqlx::query_as!(
+            SourceRow,
             r#"
-            SELECT id, name, source_type, path_or_url, trust_level, status, last_synced_at,
+            SELECT id AS "id!", name, source_type, path_or_url, trust_level, status, last_synced_at,
                    is_read_only, created_at, updated_at
             FROM mcp_sources
             WHERE id = ?;
             "#,
+            id,
         )
-        .bind(id)
         .fetch_optional(&self.pool)
         .await?;
 
-        row.map(|row| row_to_source(&row)).transpose()
+        row.map(McpSource::try_from).transpose()
     }
 
     pub async fn find_source_by_type(
         &self,
         source_type: McpSourceType,
     ) -> Result<Option<McpSource>, McpError> {
-        let row = sqlx::query(
+        let source_type = source_type.as_str();
+        let row = sqlx::query_as!(
+            SourceRow,
             r#"
-            SELECT id, name, source_type, path_or_url, trust_level, status, last_synced_at,
+            SELECT id AS "id!", name, source_type, path_or_url, trust_level, status, last_synced_at,
                    is_read_only, created_at, updated_at
             FROM mcp_sources
             WHERE source_type = ?
             ORDER BY created_at ASC
             LIMIT 1;
             "#,
+            source_type,
         )
-        .bind(source_type.as_str())
         .fetch_optional(&self.pool)
         .await?;
 
-        row.map(|row| row_to_source(&row)).transpose()
+        row.map(McpSource::try_from).transpose()
+    }
+
+    /// Removes a source and, depending on `mode`, either deletes its tools or
+    /// moves them to `local_source_id` as orphaned, editable entries. Orphaning
+    /// is refused when a moved tool would clash with a local tool's name.
+    pub async fn delete_source(
+        &self,
+        id: &str,
+        mode: SourceDeleteMode,
+        local_source_id: &str,
+    ) -> Result<(), McpError> {
+        let mut tx = self.pool.begin().await?;
+        match mode {
+            SourceDeleteMode::Delete => {
+                sqlx::query!("DELETE FROM mcp_tools WHERE source_id = ?;", id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            SourceDeleteMode::Orphan => {
+                check_orphan_clashes(&mut tx, id, local_source_id).await?;
+
+                let now = now_rfc3339()?;
+                let source_type = McpSourceType::Local.as_str();
+                let status = McpToolStatus::Orphaned.as_str();
+                let conflict_status = McpConflictStatus::None.as_str();
+                sqlx::query!(
+                    r#"
+                    UPDATE mcp_tools
+                    SET source_id = ?, identifier = NULL, source_type = ?, status = ?, error = ?,
+                        pending_config_json = NULL, pending_config_hash = NULL,
+                        conflict_status = ?, is_read_only = 0, updated_at = ?
+                    WHERE source_id = ?;
+                    "#,
+                    local_source_id,
+                    source_type,
+                    status,
+                    "source removed",
+                    conflict_status,
+                    now,
+                    id,
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        let result = sqlx::query!("DELETE FROM mcp_sources WHERE id = ?;", id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(McpError::SourceNotFound(id.to_string()));
+        }
+        tx.commit().await?;
+        self.tool_cache.invalidate();
+        self.forget_applied_config(id);
+        self.forget_applied_config(local_source_id);
+        Ok(())
+    }
+
+    /// Refuses orphaning `id`'s tools when one would clash with a local
+    /// tool's name; callers check before stopping anything.
+    pub async fn check_orphanable(&self, id: &str, local_source_id: &str) -> Result<(), McpError> {
+        let mut conn = self.pool.acquire().await?;
+        check_orphan_clashes(&mut conn, id, local_source_id).await
     }
 
     pub async fn insert_source(&self, source: NewSource) -> Result<McpSource, McpError> {
         let now = now_rfc3339()?;
         let id = Uuid::new_v4().to_string();
-        sqlx::query(
+        let source_type = source.source_type.as_str();
+        let trust_level = source.trust_level.as_str();
+        let status = source.status.as_str();
+        let is_read_only = i64::from(source.is_read_only);
+        sqlx::query!(
             r#"
             INSERT INTO mcp_sources
               (id, name, source_type, path_or_url, trust_level, status, last_synced_at, is_read_only, created_at, updated_at)
             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
             "#,
+            id,
+            source.name,
+            source_type,
+            source.path_or_url,
+            trust_level,
+            status,
+            source.last_synced_at,
+            is_read_only,
+            now,
+            now,
         )
-        .bind(&id)
-        .bind(&source.name)
-        .bind(source.source_type.as_str())
-        .bind(&source.path_or_url)
-        .bind(source.trust_level.as_str())
-        .bind(source.status.as_str())
-        .bind(source.last_synced_at.clone())
-        .bind(if source.is_read_only { 1 } else { 0 })
-        .bind(&now)
-        .bind(&now)
         .execute(&self.pool)
         .await?;
 
@@ -209,29 +619,88 @@ impl McpStore {
         last_synced_at: Option<String>,
     ) -> Result<(), McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        let status_str = status.as_str();
+        sqlx::query!(
             r#"
             UPDATE mcp_sources
             SET status = ?, last_synced_at = ?, updated_at = ?
             WHERE id = ?;
             "#,
+            status_str,
+            last_synced_at,
+            now,
+            id,
         )
-        .bind(status.as_str())
-        .bind(last_synced_at)
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await?;
 
+        self.publish(McpEvent::SourceStatus(SourceStatusEvent {
+            source_id: id.to_string(),
+            status,
+            last_synced_at,
+        }));
         Ok(())
     }
 
     pub async fn list_tools(&self) -> Result<Vec<McpTool>, McpError> {
-        let rows = sqlx::query(
+        Ok(self.cached_tools().await?.as_ref().clone())
+    }
+
+    /// Filtered page of tools plus the total number of matches, served from
+    /// the tool cache. `q` matches name or description, ignoring case.
+    pub async fn query_tools(
+        &self,
+        query: &ListToolsQuery,
+    ) -> Result<(Vec<McpTool>, i64), McpError> {
+        let (limit, offset) = page_bounds(query.limit, query.offset)?;
+        let needle = query
+            .q
+            .as_deref()
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .map(str::to_lowercase);
+        let tools = self.cached_tools().await?;
+        let matches: Vec<&McpTool> = tools
+            .iter()
+            .filter(|tool| query.status.as_ref().is_none_or(|status| tool.status == *status))
+            .filter(|tool| {
+                query
+                    .source_id
+                    .as_deref()
+                    .is_none_or(|source_id| tool.source_id.as_deref() == Some(source_id))
+            })
+            .filter(|tool| {
+                needle.as_deref().is_none_or(|needle| {
+                    tool.name.to_lowercase().contains(needle)
+                        || tool.description.to_lowercase().contains(needle)
+                })
+            })
+            .collect();
+
+        let total = matches.len() as i64;
+        let page = matches
+            .into_iter()
+            .skip(usize::try_from(offset).unwrap_or(usize::MAX))
+            .take(usize::try_from(limit).unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        Ok((page, total))
+    }
+
+    /// Every tool in `created_at` order, from SQLite only when a write has
+    /// invalidated the cache since the last read.
+    async fn cached_tools(&self) -> Result<Arc<Vec<McpTool>>, McpError> {
+        if let Some(tools) = self.tool_cache.get() {
+            return Ok(tools);
+        }
+        let generation = self.tool_cache.generation();
+        let rows = sqlx::query_as!(
+            ToolRow,
             r#"
-            SELECT id, source_id, name, source_type, status, ping_ms, capabilities, description,
-                   error, command, args, env, config_hash, pending_config_hash, conflict_status,
-                   is_read_only, created_at, updated_at
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+                   error, command, args, env, config_json, config_hash, pending_config_json,
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, log_buffer_size, created_at, updated_at
             FROM mcp_tools
             ORDER BY created_at ASC;
             "#,
@@ -239,177 +708,440 @@ impl McpStore {
         .fetch_all(&self.pool)
         .await?;
 
-        let mut tools = Vec::with_capacity(rows.len());
-        for row in rows {
-            tools.push(row_to_tool(&row)?);
-        }
-        Ok(tools)
+        let tools = rows
+            .into_iter()
+            .map(McpTool::try_from)
+            .collect::<Result<_, _>>()?;
+        Ok(self.tool_cache.fill(generation, tools))
+    }
+
+    pub async fn list_tools_by_source(&self, source_id: &str) -> Result<Vec<McpTool>, McpError> {
+        let rows = sqlx::query_as!(
+            ToolRow,
+            r#"
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+                   error, command, args, env, config_json, config_hash, pending_config_json,
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, log_buffer_size, created_at, updated_at
+            FROM mcp_tools
+            WHERE source_id = ?
+            ORDER BY created_at ASC;
+            "#,
+            source_id,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(McpTool::try_from).collect()
     }
 
     pub async fn get_tool(&self, id: &str) -> Result<Option<McpTool>, McpError> {
-        let row = sqlx::query(
+        let row = sqlx::query_as!(
+            ToolRow,
             r#"
-            SELECT id, source_id, name, source_type, status, ping_ms, capabilities, description,
-                   error, command, args, env, config_hash, pending_config_hash, conflict_status,
-                   is_read_only, created_at, updated_at
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+                   error, command, args, env, config_json, config_hash, pending_config_json,
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, log_buffer_size, created_at, updated_at
             FROM mcp_tools
             WHERE id = ?;
             "#,
+            id,
         )
-        .bind(id)
         .fetch_optional(&self.pool)
         .await?;
 
-        row.map(|row| row_to_tool(&row)).transpose()
+        row.map(McpTool::try_from).transpose()
     }
 
 
     pub async fn get_pending_config_json(&self, id: &str) -> Result<Option<String>, McpError> {
-        let row = sqlx::query(
+        let pending = sqlx::query_scalar!(
             r#"
             SELECT pending_config_json
             FROM mcp_tools
             WHERE id = ?;
             "#,
+            id,
         )
-        .bind(id)
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(row.and_then(|row| row.try_get::<String, _>("pending_config_json").ok()))
+        Ok(pending.flatten())
     }
 
-    pub async fn get_tool_by_source_name(
-        &self,
-        source_id: &str,
-        name: &str,
-    ) -> Result<Option<McpTool>, McpError> {
-        let row = sqlx::query(
+    pub async fn get_tool_detail(&self, id: &str) -> Result<Option<McpToolDetail>, McpError> {
+        let row = sqlx::query_as!(
+            ToolRow,
             r#"
-            SELECT id, source_id, name, source_type, status, ping_ms, capabilities, description,
-                   error, command, args, env, config_hash, pending_config_hash, conflict_status,
-                   is_read_only, created_at, updated_at
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+                   error, command, args, env, config_json, config_hash, pending_config_json,
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, log_buffer_size, created_at, updated_at
             FROM mcp_tools
-            WHERE source_id = ? AND name = ?
-            LIMIT 1;
+            WHERE id = ?;
             "#,
+            id,
         )
-        .bind(source_id)
-        .bind(name)
         .fetch_optional(&self.pool)
         .await?;
 
-        row.map(|row| row_to_tool(&row)).transpose()
+        row.map(|mut row| {
+            let config_json = std::mem::take(&mut row.config_json);
+            let pending_config_json = row.pending_config_json.take();
+            Ok(McpToolDetail {
+                tool: row.try_into()?,
+                config_json,
+                pending_config_json,
+            })
+        })
+        .transpose()
     }
 
-    pub async fn has_name_conflict(
-        &self,
-        name: &str,
-        source_id: &str,
-    ) -> Result<bool, McpError> {
-        let row = sqlx::query(
+    pub async fn delete_tool(&self, id: &str) -> Result<(), McpError> {
+        let result = sqlx::query!(
             r#"
-            SELECT COUNT(*) as count
-            FROM mcp_tools
-            WHERE name = ? AND source_id != ? AND source_type = ?;
+            DELETE FROM mcp_tools
+            WHERE id = ?;
             "#,
+            id,
         )
-        .bind(name)
-        .bind(source_id)
-        .bind(McpSourceType::Local.as_str())
-        .fetch_one(&self.pool)
+        .execute(&self.pool)
         .await?;
 
-        let count: i64 = row.try_get("count")?;
-        Ok(count > 0)
-    }
-
-    pub async fn upsert_tool(&self, tool: ToolUpsert) -> Result<McpTool, McpError> {
-        if let Some(existing_id) = self
-            .find_tool_id_by_source_name(tool.source_id.as_str(), &tool.name)
-            .await?
-        {
-            self.update_tool(&existing_id, tool.clone()).await?;
-            let updated = self
-                .get_tool(&existing_id)
-                .await?
-                .ok_or_else(|| McpError::NotFound("tool missing after update".to_string()))?;
-            return Ok(updated);
+        if result.rows_affected() == 0 {
+            return Err(McpError::ToolNotFound(id.to_string()));
         }
+        self.tool_cache.invalidate();
+        self.publish(McpEvent::ToolRemoved {
+            tool_id: id.to_string(),
+        });
+        Ok(())
+    }
 
-        self.insert_tool(tool.clone()).await?;
-        let created = self
-            .find_tool_id_by_source_name(tool.source_id.as_str(), &tool.name)
-            .await?
-            .ok_or_else(|| McpError::NotFound("tool missing after insert".to_string()))?;
-        self.get_tool(&created)
-            .await?
-            .ok_or_else(|| McpError::NotFound("tool missing after insert".to_string()))
+    pub async fn get_config_json(&self, id: &str) -> Result<Option<String>, McpError> {
+        Ok(sqlx::query_scalar!(
+            r#"
+            SELECT config_json
+            FROM mcp_tools
+            WHERE id = ?;
+            "#,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await?)
     }
 
-    pub async fn set_tool_status(
+    pub async fn update_tool_env(
         &self,
         id: &str,
-        status: McpToolStatus,
-        ping_ms: Option<i64>,
-        error: Option<String>,
-    ) -> Result<(), McpError> {
+        env: Option<HashMap<String, String>>,
+    ) -> Result<McpTool, McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        let env = serialize_json(&env)?;
+        sqlx::query!(
             r#"
             UPDATE mcp_tools
-            SET status = ?, ping_ms = ?, error = ?, updated_at = ?
+            SET env = ?, is_new = 0, updated_at = ?
             WHERE id = ?;
             "#,
+            env,
+            now,
+            id,
         )
-        .bind(status.as_str())
-        .bind(ping_ms)
-        .bind(error)
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await?;
+        self.tool_cache.invalidate();
 
-        Ok(())
+        self.get_tool(id)
+            .await?
+            .ok_or_else(|| McpError::NotFound("tool missing after env update".to_string()))
     }
 
-
-    pub async fn mark_tool_pending_update(
+    pub async fn set_tool_log_buffer_size(
         &self,
         id: &str,
-        pending_config_json: String,
-        pending_config_hash: String,
-        conflict_status: McpConflictStatus,
-    ) -> Result<(), McpError> {
+        log_buffer_size: Option<usize>,
+    ) -> Result<McpTool, McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        let log_buffer_size = log_buffer_size.map(|size| size as i64);
+        let result = sqlx::query!(
+            r#"
+            UPDATE mcp_tools
+            SET log_buffer_size = ?, updated_at = ?
+            WHERE id = ?;
+            "#,
+            log_buffer_size,
+            now,
+            id,
+        )
+        .execute(&self.pool)
+        .await?;
+        self.tool_cache.invalidate();
+        if result.rows_affected() == 0 {
+            return Err(McpError::ToolNotFound(id.to_string()));
+        }
+
+        self.get_tool(id)
+            .await?
+            .ok_or_else(|| McpError::NotFound("tool missing after update".to_string()))
+    }
+
+    pub async fn clear_pending_update(&self, id: &str) -> Result<(), McpError> {
+        let now = now_rfc3339()?;
+        let conflict_status = McpConflictStatus::None.as_str();
+        sqlx::query!(
             r#"
             UPDATE mcp_tools
-            SET pending_config_json = ?,
-                pending_config_hash = ?,
+            SET pending_config_json = NULL,
+                pending_config_hash = NULL,
                 conflict_status = ?,
                 updated_at = ?
             WHERE id = ?;
             "#,
+            conflict_status,
+            now,
+            id,
         )
-        .bind(pending_config_json)
-        .bind(pending_config_hash)
-        .bind(conflict_status.as_str())
-        .bind(now)
-        .bind(id)
         .execute(&self.pool)
         .await?;
-
-        Ok(())
+        self.tool_cache.invalidate();
+        self.publish_tool_status(id).await
     }
 
-    pub fn extract_tool_fields(
+    pub async fn has_name_conflict(
         &self,
         name: &str,
-        payload: &McpToolConfigPayload,
-    ) -> ExtractedToolFields {
-        ExtractedToolFields {
-            name: name.to_string(),
+        source_id: &str,
+    ) -> Result<bool, McpError> {
+        let local = McpSourceType::Local.as_str();
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as count
+            FROM mcp_tools
+            WHERE name = ? AND source_id != ? AND source_type = ?;
+            "#,
+            name,
+            source_id,
+            local,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    /// Reads what a sync compares a source's servers against, in two queries
+    /// instead of several per server.
+    pub async fn source_snapshot(&self, source_id: &str) -> Result<SourceSnapshot, McpError> {
+        let rows = sqlx::query_as!(
+            ToolRow,
+            r#"
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+                   error, command, args, env, config_json, config_hash, pending_config_json,
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, log_buffer_size, created_at, updated_at
+            FROM mcp_tools
+            WHERE source_id = ?
+            ORDER BY created_at ASC;
+            "#,
+            source_id,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut tools = HashMap::with_capacity(rows.len());
+        for mut row in rows {
+            let config_json = std::mem::take(&mut row.config_json);
+            let tool = McpTool::try_from(row)?;
+            tools.entry(tool.name.clone()).or_insert((tool, config_json));
+        }
+
+        let local = McpSourceType::Local.as_str();
+        let local_names = sqlx::query_scalar!(
+            r#"
+            SELECT name
+            FROM mcp_tools
+            WHERE source_id != ? AND source_type = ?;
+            "#,
+            source_id,
+            local,
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        Ok(SourceSnapshot { tools, local_names })
+    }
+
+    /// Applies a sync's writes to `source_id` in one transaction and returns
+    /// the tools in the order of `writes`. Status events go out after the
+    /// commit, for written tools only.
+    pub async fn apply_sync_writes(
+        &self,
+        source_id: &str,
+        writes: Vec<SyncWrite>,
+    ) -> Result<Vec<McpTool>, McpError> {
+        let now = now_rfc3339()?;
+        let mut ids = Vec::with_capacity(writes.len());
+        let mut tx = self.pool.begin().await?;
+        for write in writes {
+            match write {
+                SyncWrite::Keep(id) => ids.push((id, false)),
+                SyncWrite::Pending {
+                    id,
+                    config_json,
+                    config_hash,
+                    conflict_status,
+                } => {
+                    stage_pending_update(
+                        &mut tx,
+                        &id,
+                        config_json,
+                        config_hash,
+                        conflict_status,
+                        &now,
+                    )
+                    .await?;
+                    ids.push((id, true));
+                }
+                SyncWrite::Upsert(tool) => {
+                    let id = match tool.id.clone() {
+                        Some(id) => {
+                            update_tool(&mut tx, &id, *tool, &now).await?;
+                            id
+                        }
+                        None => {
+                            let id = Uuid::new_v4().to_string();
+                            insert_tool(&mut tx, &id, *tool, &now).await?;
+                            id
+                        }
+                    };
+                    ids.push((id, true));
+                }
+            }
+        }
+        tx.commit().await?;
+        self.tool_cache.invalidate();
+
+        let mut stored: HashMap<String, McpTool> = self
+            .list_tools_by_source(source_id)
+            .await?
+            .into_iter()
+            .map(|tool| (tool.id.clone(), tool))
+            .collect();
+        ids.into_iter()
+            .map(|(id, written)| {
+                let tool = stored
+                    .remove(&id)
+                    .ok_or_else(|| McpError::NotFound("tool missing after sync".to_string()))?;
+                if written {
+                    self.publish_written_tool(&tool);
+                }
+                Ok(tool)
+            })
+            .collect()
+    }
+
+    /// Matches an existing row by `(source_id, identifier)` when the tool has
+    /// an identifier (cloud tools may be renamed upstream), else by name.
+    pub async fn upsert_tool(&self, tool: ToolUpsert) -> Result<McpTool, McpError> {
+        let now = now_rfc3339()?;
+        if let Some(existing_id) = self.find_existing_tool_id(&tool).await? {
+            let mut conn = self.pool.acquire().await?;
+            update_tool(&mut conn, &existing_id, tool.clone(), &now).await?;
+            self.tool_cache.invalidate();
+            let updated = self
+                .get_tool(&existing_id)
+                .await?
+                .ok_or_else(|| McpError::NotFound("tool missing after update".to_string()))?;
+            self.publish_written_tool(&updated);
+            return Ok(updated);
+        }
+
+        let id = tool.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
+        let mut conn = self.pool.acquire().await?;
+        insert_tool(&mut conn, &id, tool.clone(), &now).await?;
+        self.tool_cache.invalidate();
+        let created = self
+            .find_existing_tool_id(&tool)
+            .await?
+            .ok_or_else(|| McpError::NotFound("tool missing after insert".to_string()))?;
+        let created = self
+            .get_tool(&created)
+            .await?
+            .ok_or_else(|| McpError::NotFound("tool missing after insert".to_string()))?;
+        self.publish_written_tool(&created);
+        Ok(created)
+    }
+
+    pub async fn set_tool_status(
+        &self,
+        id: &str,
+        status: McpToolStatus,
+        ping_ms: Option<i64>,
+        error: Option<String>,
+    ) -> Result<(), McpError> {
+        let now = now_rfc3339()?;
+        let status = status.as_str();
+        sqlx::query!(
+            r#"
+            UPDATE mcp_tools
+            SET status = ?, ping_ms = ?, error = ?, updated_at = ?
+            WHERE id = ?;
+            "#,
+            status,
+            ping_ms,
+            error,
+            now,
+            id,
+        )
+        .execute(&self.pool)
+        .await?;
+        self.tool_cache.invalidate();
+
+        self.publish_tool_status(id).await
+    }
+
+
+    pub async fn mark_tool_pending_update(
+        &self,
+        id: &str,
+        pending_config_json: String,
+        pending_config_hash: String,
+        conflict_status: McpConflictStatus,
+    ) -> Result<(), McpError> {
+        let now = now_rfc3339()?;
+        let mut conn = self.pool.acquire().await?;
+        stage_pending_update(
+            &mut conn,
+            id,
+            pending_config_json,
+            pending_config_hash,
+            conflict_status.clone(),
+            &now,
+        )
+        .await?;
+        drop(conn);
+        self.tool_cache.invalidate();
+
+        self.publish_tool_status(id).await?;
+        if conflict_status != McpConflictStatus::None {
+            self.publish(McpEvent::ConflictDetected {
+                tool_id: id.to_string(),
+                conflict_status,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn extract_tool_fields(
+        &self,
+        name: &str,
+        payload: &McpToolConfigPayload,
+    ) -> ExtractedToolFields {
+        ExtractedToolFields {
+            name: name.to_string(),
             description: payload
                 .description
                 .clone()
@@ -443,6 +1175,19 @@ impl McpStore {
                 .collect();
             map.insert("env".to_string(), serde_json::Value::Object(env_map));
         }
+        if let Some(cwd) = &payload.cwd {
+            map.insert("cwd".to_string(), serde_json::Value::String(cwd.clone()));
+        }
+        if let Some(hooks) = payload.hooks.as_ref().filter(|hooks| !hooks.is_empty()) {
+            map.insert("hooks".to_string(), serde_json::to_value(hooks)?);
+        }
+        if let Some(healthcheck) = &payload.healthcheck {
+            healthcheck.validate().map_err(McpError::Validation)?;
+            map.insert(
+                "healthcheck".to_string(),
+                serde_json::to_value(healthcheck)?,
+            );
+        }
         if let Some(description) = &payload.description {
             map.insert("description".to_string(), serde_json::Value::String(description.clone()));
         }
@@ -469,99 +1214,611 @@ impl McpStore {
         Ok(hash_json(value)?)
     }
 
+    async fn find_existing_tool_id(&self, tool: &ToolUpsert) -> Result<Option<String>, McpError> {
+        match tool.identifier.as_deref() {
+            Some(identifier) => Ok(self
+                .get_tool_by_source_identifier(&tool.source_id, identifier)
+                .await?
+                .map(|existing| existing.id)),
+            None => {
+                self.find_tool_id_by_source_name(&tool.source_id, &tool.name)
+                    .await
+            }
+        }
+    }
+
+    pub async fn get_tool_by_source_identifier(
+        &self,
+        source_id: &str,
+        identifier: &str,
+    ) -> Result<Option<McpTool>, McpError> {
+        let row = sqlx::query_as!(
+            ToolRow,
+            r#"
+            SELECT id AS "id!", source_id, identifier, name, source_type, status, ping_ms, capabilities, description,
+                   error, command, args, env, config_json, config_hash, pending_config_json,
+                   pending_config_hash, conflict_status, is_read_only, is_new, approved_at,
+                   market_tool_id, log_buffer_size, created_at, updated_at
+            FROM mcp_tools
+            WHERE source_id = ? AND identifier = ?
+            LIMIT 1;
+            "#,
+            source_id,
+            identifier,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(McpTool::try_from).transpose()
+    }
+
+    /// The stored token, or `None` when there is none or it can no longer be
+    /// decrypted (e.g. `session.key` was deleted). Plaintext tokens left by
+    /// earlier versions are encrypted in place.
+    pub async fn get_cloud_session(&self, source_id: &str) -> Result<Option<String>, McpError> {
+        let stored = sqlx::query_scalar!(
+            r#"
+            SELECT access_token FROM cloud_sessions WHERE source_id = ?;
+            "#,
+            source_id,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(stored) = stored else {
+            return Ok(None);
+        };
+        if !SessionCipher::is_encrypted(&stored) {
+            self.set_cloud_session(source_id, &stored).await?;
+            return Ok(Some(stored));
+        }
+        let token = self.session_cipher.decrypt(&stored);
+        if token.is_none() {
+            tracing::warn!(source_id, "stored cloud session could not be decrypted");
+        }
+        Ok(token)
+    }
+
+    pub async fn set_cloud_session(
+        &self,
+        source_id: &str,
+        access_token: &str,
+    ) -> Result<(), McpError> {
+        let now = now_rfc3339()?;
+        let access_token = self.session_cipher.encrypt(access_token);
+        sqlx::query!(
+            r#"
+            INSERT INTO cloud_sessions (source_id, access_token, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(source_id) DO UPDATE
+            SET access_token = excluded.access_token, updated_at = excluded.updated_at;
+            "#,
+            source_id,
+            access_token,
+            now,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     async fn find_tool_id_by_source_name(
         &self,
         source_id: &str,
         name: &str,
     ) -> Result<Option<String>, McpError> {
-        let row = sqlx::query(
+        Ok(sqlx::query_scalar!(
             r#"
-            SELECT id
+            SELECT id AS "id!"
             FROM mcp_tools
             WHERE source_id = ? AND name = ?
             LIMIT 1;
             "#,
+            source_id,
+            name,
         )
-        .bind(source_id)
-        .bind(name)
         .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    pub async fn list_api_tokens(&self) -> Result<Vec<ApiToken>, McpError> {
+        Ok(sqlx::query_as!(
+            ApiToken,
+            r#"
+            SELECT id AS "id!", name, created_at, last_used_at
+            FROM api_tokens
+            ORDER BY created_at ASC;
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub fn event_subscriber_count(&self) -> usize {
+        self.events.receiver_count()
+    }
+
+    /// Size, per-table row counts and pool usage for `GET /admin/stats`.
+    pub async fn database_stats(&self) -> Result<DatabaseStats, McpError> {
+        let page_count: i64 = sqlx::query_scalar("PRAGMA page_count;")
+            .fetch_one(&self.read_pool)
+            .await?;
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size;")
+            .fetch_one(&self.read_pool)
+            .await?;
+        let names: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name;",
+        )
+        .fetch_all(&self.read_pool)
         .await?;
+        let mut tables = BTreeMap::new();
+        for name in names {
+            let sql = format!("SELECT COUNT(*) FROM \"{}\";", name.replace('"', "\"\""));
+            let count: i64 = sqlx::query_scalar(&sql).fetch_one(&self.read_pool).await?;
+            tables.insert(name, count);
+        }
+        Ok(DatabaseStats {
+            size_bytes: page_count * page_size,
+            tables,
+            pool: PoolStats {
+                size: self.pool.size(),
+                idle: self.pool.num_idle() as u32,
+                max_connections: self.pool.options().get_max_connections(),
+            },
+        })
+    }
 
-        Ok(row.and_then(|row| row.try_get::<String, _>("id").ok()))
+    /// Cheap round trip used by the readiness probe.
+    pub async fn ping(&self) -> Result<(), McpError> {
+        sqlx::query("SELECT 1;").execute(&self.pool).await?;
+        Ok(())
     }
 
-    async fn insert_tool(&self, tool: ToolUpsert) -> Result<(), McpError> {
+    pub async fn has_api_tokens(&self) -> Result<bool, McpError> {
+        let count = sqlx::query_scalar!("SELECT COUNT(*) as count FROM api_tokens;")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count > 0)
+    }
+
+    /// Creates a token and returns it with its secret. Only a SHA-256 hash of
+    /// the secret is persisted, so it cannot be shown again.
+    pub async fn create_api_token(&self, name: &str) -> Result<(ApiToken, String), McpError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(McpError::Validation("token name is required".to_string()));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let secret = format!(
+            "dk_{}{}",
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        );
         let now = now_rfc3339()?;
-        let id = tool.id.unwrap_or_else(|| Uuid::new_v4().to_string());
-        sqlx::query(
+        let token_hash = hash_token(&secret);
+        sqlx::query!(
+            r#"
+            INSERT INTO api_tokens (id, name, token_hash, created_at, last_used_at)
+            VALUES (?, ?, ?, ?, ?);
+            "#,
+            id,
+            name,
+            token_hash,
+            now,
+            None::<String>,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let token = ApiToken {
+            id,
+            name: name.to_string(),
+            created_at: now,
+            last_used_at: None,
+        };
+        Ok((token, secret))
+    }
+
+    /// Looks a presented secret up by hash and stamps `last_used_at` on a match.
+    pub async fn verify_api_token(&self, secret: &str) -> Result<bool, McpError> {
+        let now = now_rfc3339()?;
+        let token_hash = hash_token(secret);
+        let result = sqlx::query!(
+            r#"
+            UPDATE api_tokens
+            SET last_used_at = ?
+            WHERE token_hash = ?;
+            "#,
+            now,
+            token_hash,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_api_token(&self, id: &str) -> Result<(), McpError> {
+        let result = sqlx::query!("DELETE FROM api_tokens WHERE id = ?;", id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(McpError::NotFound(format!("token {id} not found")));
+        }
+        Ok(())
+    }
+
+    pub async fn list_webhooks(&self) -> Result<Vec<Webhook>, McpError> {
+        Ok(self
+            .webhooks_with_secrets()
+            .await?
+            .into_iter()
+            .map(|(webhook, _)| webhook)
+            .collect())
+    }
+
+    /// Webhooks subscribed to `event`, with the secrets to sign deliveries.
+    pub async fn webhook_targets(
+        &self,
+        event: WebhookEvent,
+    ) -> Result<Vec<(Webhook, String)>, McpError> {
+        let mut webhooks = self.webhooks_with_secrets().await?;
+        webhooks
+            .retain(|(webhook, _)| webhook.events.is_empty() || webhook.events.contains(&event));
+        Ok(webhooks)
+    }
+
+    async fn webhooks_with_secrets(&self) -> Result<Vec<(Webhook, String)>, McpError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id AS "id!", url, secret, events, created_at, last_delivery_at, last_error
+            FROM webhooks
+            ORDER BY created_at ASC;
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter()
+            .map(|row| {
+                let webhook = Webhook {
+                    id: row.id,
+                    url: row.url,
+                    events: serde_json::from_str(&row.events)?,
+                    created_at: row.created_at,
+                    last_delivery_at: row.last_delivery_at,
+                    last_error: row.last_error,
+                };
+                Ok((webhook, row.secret))
+            })
+            .collect()
+    }
+
+    /// Creates a webhook and returns it with its signing secret, which is
+    /// generated unless the request brings one.
+    pub async fn create_webhook(
+        &self,
+        url: &str,
+        secret: Option<String>,
+        events: Vec<WebhookEvent>,
+    ) -> Result<(Webhook, String), McpError> {
+        let url = url.trim();
+        match reqwest::Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            _ => {
+                return Err(McpError::Validation(format!(
+                    "webhook url must be an http(s) URL: {url}"
+                )))
+            }
+        }
+        let secret = match secret.map(|secret| secret.trim().to_string()) {
+            Some(secret) if !secret.is_empty() => secret,
+            _ => format!("whsec_{}", Uuid::new_v4().simple()),
+        };
+
+        let id = Uuid::new_v4().to_string();
+        let now = now_rfc3339()?;
+        let events_json = serde_json::to_string(&events)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO webhooks (id, url, secret, events, created_at)
+            VALUES (?, ?, ?, ?, ?);
+            "#,
+            id,
+            url,
+            secret,
+            events_json,
+            now,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let webhook = Webhook {
+            id,
+            url: url.to_string(),
+            events,
+            created_at: now,
+            last_delivery_at: None,
+            last_error: None,
+        };
+        Ok((webhook, secret))
+    }
+
+    pub async fn delete_webhook(&self, id: &str) -> Result<(), McpError> {
+        let result = sqlx::query!("DELETE FROM webhooks WHERE id = ?;", id)
+            .execute(&self.pool)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(McpError::NotFound(format!("webhook {id} not found")));
+        }
+        Ok(())
+    }
+
+    /// Stamps the outcome of a delivery; `error` is `None` on success.
+    pub async fn record_webhook_delivery(
+        &self,
+        id: &str,
+        error: Option<&str>,
+    ) -> Result<(), McpError> {
+        let now = now_rfc3339()?;
+        sqlx::query!(
+            r#"
+            UPDATE webhooks
+            SET last_delivery_at = ?, last_error = ?
+            WHERE id = ?;
+            "#,
+            now,
+            error,
+            id,
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn list_assistants(&self) -> Result<Vec<LocalAssistant>, McpError> {
+        let rows = sqlx::query_as!(
+            AssistantRow,
+            r#"
+            SELECT id AS "id!", name, description, avatar, system_prompt, model_config, tags,
+                   visibility, source, cloud_id, cloud_updated_at, cloud_synced_at, is_deleted,
+                   created_at, updated_at
+            FROM assistants
+            WHERE is_deleted = 0
+            ORDER BY updated_at DESC;
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(LocalAssistant::try_from).collect()
+    }
+
+    pub async fn get_assistant(&self, id: &str) -> Result<Option<LocalAssistant>, McpError> {
+        let row = sqlx::query_as!(
+            AssistantRow,
+            r#"
+            SELECT id AS "id!", name, description, avatar, system_prompt, model_config, tags,
+                   visibility, source, cloud_id, cloud_updated_at, cloud_synced_at, is_deleted,
+                   created_at, updated_at
+            FROM assistants
+            WHERE id = ? AND is_deleted = 0;
+            "#,
+            id,
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(LocalAssistant::try_from).transpose()
+    }
+
+    pub async fn create_assistant(
+        &self,
+        payload: CreateLocalAssistantRequest,
+    ) -> Result<LocalAssistant, McpError> {
+        let name = payload.name.trim().to_string();
+        if name.is_empty() {
+            return Err(McpError::Validation("assistant name is required".to_string()));
+        }
+        let system_prompt = payload.system_prompt.trim().to_string();
+        if system_prompt.is_empty() {
+            return Err(McpError::Validation("system_prompt is required".to_string()));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = now_rfc3339()?;
+        let visibility = payload.visibility.unwrap_or_else(|| "private".to_string());
+        let source = payload.source.unwrap_or_else(|| "local".to_string());
+        let tags = payload.tags.unwrap_or_default();
+
+        let model_config = serialize_json(&payload.model_config)?;
+        let tags = serde_json::to_string(&tags)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO assistants
+              (id, name, description, avatar, system_prompt, model_config, tags, visibility, source,
+               cloud_id, is_deleted, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+            "#,
+            id,
+            name,
+            payload.description,
+            payload.avatar,
+            system_prompt,
+            model_config,
+            tags,
+            visibility,
+            source,
+            payload.cloud_id,
+            0,
+            now,
+            now,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.get_assistant(&id)
+            .await?
+            .ok_or_else(|| McpError::NotFound("assistant missing after insert".to_string()))
+    }
+
+    pub async fn update_assistant(
+        &self,
+        id: &str,
+        payload: UpdateLocalAssistantRequest,
+    ) -> Result<LocalAssistant, McpError> {
+        let existing = self
+            .get_assistant(id)
+            .await?
+            .ok_or_else(|| McpError::NotFound(format!("assistant {id} not found")))?;
+
+        let name = payload.name.unwrap_or(existing.name);
+        if name.trim().is_empty() {
+            return Err(McpError::Validation("assistant name is required".to_string()));
+        }
+        let system_prompt = payload.system_prompt.unwrap_or(existing.system_prompt);
+        if system_prompt.trim().is_empty() {
+            return Err(McpError::Validation("system_prompt is required".to_string()));
+        }
+        let now = now_rfc3339()?;
+
+        let name = name.trim();
+        let description = payload.description.or(existing.description);
+        let avatar = payload.avatar.or(existing.avatar);
+        let system_prompt = system_prompt.trim();
+        let model_config = serialize_json(&payload.model_config.or(existing.model_config))?;
+        let tags = serde_json::to_string(&payload.tags.unwrap_or(existing.tags))?;
+        let visibility = payload.visibility.unwrap_or(existing.visibility);
+        let source = payload.source.unwrap_or(existing.source);
+        let cloud_id = payload.cloud_id.or(existing.cloud_id);
+        sqlx::query!(
+            r#"
+            UPDATE assistants
+            SET name = ?, description = ?, avatar = ?, system_prompt = ?, model_config = ?,
+                tags = ?, visibility = ?, source = ?, cloud_id = ?, updated_at = ?
+            WHERE id = ?;
+            "#,
+            name,
+            description,
+            avatar,
+            system_prompt,
+            model_config,
+            tags,
+            visibility,
+            source,
+            cloud_id,
+            now,
+            id,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.get_assistant(id)
+            .await?
+            .ok_or_else(|| McpError::NotFound("assistant missing after update".to_string()))
+    }
+
+    /// Soft-deletes an assistant so a cloud-linked row can still be reconciled
+    /// by a Tauri client sharing this database.
+    pub async fn delete_assistant(&self, id: &str) -> Result<(), McpError> {
+        let now = now_rfc3339()?;
+        let result = sqlx::query!(
+            r#"
+            UPDATE assistants
+            SET is_deleted = 1, updated_at = ?
+            WHERE id = ? AND is_deleted = 0;
+            "#,
+            now,
+            id,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(McpError::NotFound(format!("assistant {id} not found")));
+        }
+        self.delete_assistant_messages(id).await?;
+        Ok(())
+    }
+
+    pub async fn list_assistant_messages(
+        &self,
+        assistant_id: &str,
+    ) -> Result<Vec<LocalAssistantMessage>, McpError> {
+        let rows = sqlx::query_as!(
+            AssistantMessageRow,
+            r#"
+            SELECT id AS "id!", assistant_id, role, content, is_deleted, created_at, updated_at
+            FROM assistant_messages
+            WHERE assistant_id = ? AND is_deleted = 0
+            ORDER BY created_at ASC;
+            "#,
+            assistant_id,
+        )
+        .fetch_all(&self.read_pool)
+        .await?;
+
+        Ok(rows.into_iter().map(LocalAssistantMessage::from).collect())
+    }
+
+    pub async fn append_assistant_message(
+        &self,
+        payload: CreateAssistantMessageRequest,
+    ) -> Result<LocalAssistantMessage, McpError> {
+        let role = payload.role.trim();
+        if role.is_empty() {
+            return Err(McpError::Validation("role is required".to_string()));
+        }
+        let content = payload.content.trim().to_string();
+        if content.is_empty() {
+            return Err(McpError::Validation("content is required".to_string()));
+        }
+        if payload.assistant_id.trim().is_empty() {
+            return Err(McpError::Validation("assistant_id is required".to_string()));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = now_rfc3339()?;
+
+        sqlx::query!(
             r#"
-            INSERT INTO mcp_tools
-              (id, source_id, name, source_type, status, ping_ms, capabilities, description,
-               error, command, args, env, config_json, config_hash, pending_config_json,
-               pending_config_hash, conflict_status, is_read_only, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
-            "#,
-        )
-        .bind(&id)
-        .bind(&tool.source_id)
-        .bind(&tool.name)
-        .bind(tool.source_type.as_str())
-        .bind(tool.status.as_str())
-        .bind(tool.ping_ms)
-        .bind(serde_json::to_string(&tool.capabilities)?)
-        .bind(&tool.description)
-        .bind(tool.error)
-        .bind(tool.command)
-        .bind(serialize_json(&tool.args)?)
-        .bind(serialize_json(&tool.env)?)
-        .bind(tool.config_json)
-        .bind(tool.config_hash)
-        .bind(tool.pending_config_json)
-        .bind(tool.pending_config_hash)
-        .bind(tool.conflict_status.as_str())
-        .bind(if tool.is_read_only { 1 } else { 0 })
-        .bind(&now)
-        .bind(&now)
+            INSERT INTO assistant_messages
+              (id, assistant_id, role, content, is_deleted, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?);
+            "#,
+            id,
+            payload.assistant_id,
+            role,
+            content,
+            0,
+            now,
+            now,
+        )
         .execute(&self.pool)
         .await?;
 
-        Ok(())
+        Ok(LocalAssistantMessage {
+            id,
+            assistant_id: payload.assistant_id,
+            role: role.to_string(),
+            content,
+            is_deleted: false,
+            created_at: now.clone(),
+            updated_at: now,
+        })
     }
 
-    async fn update_tool(&self, id: &str, tool: ToolUpsert) -> Result<(), McpError> {
+    pub async fn delete_assistant_messages(&self, assistant_id: &str) -> Result<(), McpError> {
         let now = now_rfc3339()?;
-        sqlx::query(
+        sqlx::query!(
             r#"
-            UPDATE mcp_tools
-            SET source_id = ?, name = ?, source_type = ?, status = ?, ping_ms = ?,
-                capabilities = ?, description = ?, error = ?, command = ?, args = ?, env = ?,
-                config_json = ?, config_hash = ?, pending_config_json = ?, pending_config_hash = ?,
-                conflict_status = ?, is_read_only = ?, updated_at = ?
-            WHERE id = ?;
+            UPDATE assistant_messages
+            SET is_deleted = 1, updated_at = ?
+            WHERE assistant_id = ? AND is_deleted = 0;
             "#,
+            now,
+            assistant_id,
         )
-        .bind(&tool.source_id)
-        .bind(&tool.name)
-        .bind(tool.source_type.as_str())
-        .bind(tool.status.as_str())
-        .bind(tool.ping_ms)
-        .bind(serde_json::to_string(&tool.capabilities)?)
-        .bind(&tool.description)
-        .bind(tool.error)
-        .bind(tool.command)
-        .bind(serialize_json(&tool.args)?)
-        .bind(serialize_json(&tool.env)?)
-        .bind(tool.config_json)
-        .bind(tool.config_hash)
-        .bind(tool.pending_config_json)
-        .bind(tool.pending_config_hash)
-        .bind(tool.conflict_status.as_str())
-        .bind(if tool.is_read_only { 1 } else { 0 })
-        .bind(&now)
-        .bind(id)
         .execute(&self.pool)
         .await?;
-
         Ok(())
     }
 }
@@ -580,6 +1837,7 @@ pub struct NewSource {
 pub struct ToolUpsert {
     pub id: Option<String>,
     pub source_id: String,
+    pub identifier: Option<String>,
     pub name: String,
     pub source_type: McpSourceType,
     pub status: McpToolStatus,
@@ -596,6 +1854,31 @@ pub struct ToolUpsert {
     pub pending_config_hash: Option<String>,
     pub conflict_status: McpConflictStatus,
     pub is_read_only: bool,
+    pub is_new: bool,
+}
+
+/// A source's stored tools as a sync sees them; see `source_snapshot`.
+pub struct SourceSnapshot {
+    /// The source's tools by name, with their stored `config_json`.
+    pub tools: HashMap<String, (McpTool, String)>,
+    /// Names held by local tools of other sources; a synced tool with one of
+    /// them is in conflict.
+    pub local_names: HashSet<String>,
+}
+
+/// One server's outcome in `apply_sync_writes`.
+pub enum SyncWrite {
+    /// The stored tool already matches; nothing is written.
+    Keep(String),
+    /// Stages a config as the tool's pending update.
+    Pending {
+        id: String,
+        config_json: String,
+        config_hash: String,
+        conflict_status: McpConflictStatus,
+    },
+    /// Updates the tool with `id`, or inserts a new one.
+    Upsert(Box<ToolUpsert>),
 }
 
 pub struct ExtractedToolFields {
@@ -607,51 +1890,348 @@ pub struct ExtractedToolFields {
     pub capabilities: Vec<String>,
 }
 
-fn row_to_source(row: &sqlx::sqlite::SqliteRow) -> Result<McpSource, McpError> {
-    let source_type: String = row.try_get("source_type")?;
-    let trust_level: String = row.try_get("trust_level")?;
-    let status: String = row.try_get("status")?;
-    Ok(McpSource {
-        id: row.try_get("id")?,
-        name: row.try_get("name")?,
-        source_type: source_type.parse().map_err(McpError::validation)?,
-        path_or_url: row.try_get("path_or_url")?,
-        trust_level: trust_level.parse().map_err(McpError::validation)?,
-        status: status.parse().map_err(McpError::validation)?,
-        last_synced_at: row.try_get("last_synced_at")?,
-        is_read_only: row.try_get::<i64, _>("is_read_only")? != 0,
-        created_at: row.try_get("created_at")?,
-        updated_at: row.try_get("updated_at")?,
-    })
+/// An `mcp_sources` row as `query_as!` reads it.
+struct SourceRow {
+    id: String,
+    name: String,
+    source_type: String,
+    path_or_url: String,
+    trust_level: String,
+    status: String,
+    last_synced_at: Option<String>,
+    is_read_only: i64,
+    created_at: String,
+    updated_at: String,
 }
 
-fn row_to_tool(row: &sqlx::sqlite::SqliteRow) -> Result<McpTool, McpError> {
-    let source_type: String = row.try_get("source_type")?;
-    let status: String = row.try_get("status")?;
-    let conflict_status: String = row.try_get("conflict_status")?;
-    let capabilities: String = row.try_get("capabilities")?;
-    let args: Option<String> = row.try_get("args")?;
-    let env: Option<String> = row.try_get("env")?;
-    Ok(McpTool {
-        id: row.try_get("id")?,
-        name: row.try_get("name")?,
-        source_type: source_type.parse().map_err(McpError::validation)?,
-        source_id: row.try_get("source_id")?,
-        status: status.parse().map_err(McpError::validation)?,
-        ping_ms: row.try_get("ping_ms")?,
-        capabilities: serde_json::from_str(&capabilities)?,
-        description: row.try_get("description")?,
-        error: row.try_get("error")?,
-        command: row.try_get("command")?,
-        args: deserialize_json(args)?,
-        env: deserialize_json(env)?,
-        config_hash: row.try_get("config_hash")?,
-        pending_config_hash: row.try_get("pending_config_hash")?,
-        conflict_status: conflict_status.parse().map_err(McpError::validation)?,
-        is_read_only: row.try_get::<i64, _>("is_read_only")? != 0,
-        created_at: row.try_get("created_at")?,
-        updated_at: row.try_get("updated_at")?,
-    })
+impl TryFrom<SourceRow> for McpSource {
+    type Error = McpError;
+
+    fn try_from(row: SourceRow) -> Result<Self, McpError> {
+        Ok(McpSource {
+            id: row.id,
+            name: row.name,
+            source_type: row.source_type.parse().map_err(McpError::validation)?,
+            path_or_url: row.path_or_url,
+            trust_level: row.trust_level.parse().map_err(McpError::validation)?,
+            status: row.status.parse().map_err(McpError::validation)?,
+            last_synced_at: row.last_synced_at,
+            is_read_only: row.is_read_only != 0,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+async fn check_orphan_clashes(
+    conn: &mut SqliteConnection,
+    id: &str,
+    local_source_id: &str,
+) -> Result<(), McpError> {
+    let clashes = sqlx::query_scalar!(
+        r#"
+        SELECT t.name
+        FROM mcp_tools t
+        JOIN mcp_tools l ON l.name = t.name AND l.source_id = ?
+        WHERE t.source_id = ?;
+        "#,
+        local_source_id,
+        id,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    if clashes.is_empty() {
+        return Ok(());
+    }
+    Err(McpError::Validation(format!(
+        "cannot orphan tools that clash with local tools: {}",
+        clashes.join(", ")
+    )))
+}
+
+async fn insert_tool(
+    conn: &mut SqliteConnection,
+    id: &str,
+    tool: ToolUpsert,
+    now: &str,
+) -> Result<(), McpError> {
+    let source_type = tool.source_type.as_str();
+    let status = tool.status.as_str();
+    let capabilities = serde_json::to_string(&tool.capabilities)?;
+    let args = serialize_json(&tool.args)?;
+    let env = serialize_json(&tool.env)?;
+    let conflict_status = tool.conflict_status.as_str();
+    let is_read_only = i64::from(tool.is_read_only);
+    let is_new = i64::from(tool.is_new);
+    sqlx::query!(
+        r#"
+        INSERT INTO mcp_tools
+          (id, source_id, identifier, name, source_type, status, ping_ms, capabilities,
+           description, error, command, args, env, config_json, config_hash,
+           pending_config_json, pending_config_hash, conflict_status, is_read_only, is_new,
+           created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);
+        "#,
+        id,
+        tool.source_id,
+        tool.identifier,
+        tool.name,
+        source_type,
+        status,
+        tool.ping_ms,
+        capabilities,
+        tool.description,
+        tool.error,
+        tool.command,
+        args,
+        env,
+        tool.config_json,
+        tool.config_hash,
+        tool.pending_config_json,
+        tool.pending_config_hash,
+        conflict_status,
+        is_read_only,
+        is_new,
+        now,
+        now,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn update_tool(
+    conn: &mut SqliteConnection,
+    id: &str,
+    tool: ToolUpsert,
+    now: &str,
+) -> Result<(), McpError> {
+    let source_type = tool.source_type.as_str();
+    let status = tool.status.as_str();
+    let capabilities = serde_json::to_string(&tool.capabilities)?;
+    let args = serialize_json(&tool.args)?;
+    let env = serialize_json(&tool.env)?;
+    let conflict_status = tool.conflict_status.as_str();
+    let is_read_only = i64::from(tool.is_read_only);
+    let is_new = i64::from(tool.is_new);
+    sqlx::query!(
+        r#"
+        UPDATE mcp_tools
+        SET source_id = ?, identifier = ?, name = ?, source_type = ?, status = ?, ping_ms = ?,
+            capabilities = ?, description = ?, error = ?, command = ?, args = ?, env = ?,
+            config_json = ?, config_hash = ?, pending_config_json = ?, pending_config_hash = ?,
+            conflict_status = ?, is_read_only = ?, is_new = ?, updated_at = ?
+        WHERE id = ?;
+        "#,
+        tool.source_id,
+        tool.identifier,
+        tool.name,
+        source_type,
+        status,
+        tool.ping_ms,
+        capabilities,
+        tool.description,
+        tool.error,
+        tool.command,
+        args,
+        env,
+        tool.config_json,
+        tool.config_hash,
+        tool.pending_config_json,
+        tool.pending_config_hash,
+        conflict_status,
+        is_read_only,
+        is_new,
+        now,
+        id,
+    )
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn stage_pending_update(
+    conn: &mut SqliteConnection,
+    id: &str,
+    pending_config_json: String,
+    pending_config_hash: String,
+    conflict_status: McpConflictStatus,
+    now: &str,
+) -> Result<(), McpError> {
+    let conflict_status = conflict_status.as_str();
+    sqlx::query!(
+        r#"
+        UPDATE mcp_tools
+        SET pending_config_json = ?,
+            pending_config_hash = ?,
+            conflict_status = ?,
+            updated_at = ?
+        WHERE id = ?;
+        "#,
+        pending_config_json,
+        pending_config_hash,
+        conflict_status,
+        now,
+        id,
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// An `mcp_tools` row as `query_as!` reads it. The queries are checked
+/// against the schema at build time, so a column that is renamed or changes
+/// type here and in the desktop app's store fails the build.
+struct ToolRow {
+    id: String,
+    source_id: String,
+    identifier: Option<String>,
+    name: String,
+    source_type: String,
+    status: String,
+    ping_ms: Option<i64>,
+    capabilities: String,
+    description: String,
+    error: Option<String>,
+    command: Option<String>,
+    args: Option<String>,
+    env: Option<String>,
+    config_json: String,
+    config_hash: String,
+    pending_config_json: Option<String>,
+    pending_config_hash: Option<String>,
+    conflict_status: String,
+    is_read_only: i64,
+    is_new: i64,
+    approved_at: Option<String>,
+    market_tool_id: Option<String>,
+    log_buffer_size: Option<i64>,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<ToolRow> for McpTool {
+    type Error = McpError;
+
+    fn try_from(row: ToolRow) -> Result<Self, McpError> {
+        Ok(McpTool {
+            id: row.id,
+            name: row.name,
+            source_type: row.source_type.parse().map_err(McpError::validation)?,
+            source_id: Some(row.source_id),
+            identifier: row.identifier,
+            status: row.status.parse().map_err(McpError::validation)?,
+            ping_ms: row.ping_ms,
+            capabilities: serde_json::from_str(&row.capabilities)?,
+            description: row.description,
+            error: row.error,
+            command: row.command,
+            args: deserialize_json(row.args)?,
+            env: deserialize_json(row.env)?,
+            cwd: config_cwd(&row.config_json),
+            hooks: config_hooks(&row.config_json),
+            healthcheck: config_healthcheck(&row.config_json),
+            config_hash: row.config_hash,
+            pending_config_hash: row.pending_config_hash,
+            conflict_status: row.conflict_status.parse().map_err(McpError::validation)?,
+            is_read_only: row.is_read_only != 0,
+            is_new: row.is_new != 0,
+            approved_at: row.approved_at,
+            market_tool_id: row.market_tool_id,
+            log_buffer_size: row.log_buffer_size.map(|size| size.max(1) as usize),
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+struct AssistantRow {
+    id: String,
+    name: String,
+    description: Option<String>,
+    avatar: Option<String>,
+    system_prompt: String,
+    model_config: Option<String>,
+    tags: Option<String>,
+    visibility: String,
+    source: String,
+    cloud_id: Option<String>,
+    cloud_updated_at: Option<String>,
+    cloud_synced_at: Option<String>,
+    is_deleted: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+impl TryFrom<AssistantRow> for LocalAssistant {
+    type Error = McpError;
+
+    fn try_from(row: AssistantRow) -> Result<Self, McpError> {
+        let tags: Option<Vec<String>> = deserialize_json(row.tags)?;
+        Ok(LocalAssistant {
+            id: row.id,
+            name: row.name,
+            description: row.description,
+            avatar: row.avatar,
+            system_prompt: row.system_prompt,
+            model_config: deserialize_json(row.model_config)?,
+            tags: tags.unwrap_or_default(),
+            visibility: row.visibility,
+            source: row.source,
+            cloud_id: row.cloud_id,
+            cloud_updated_at: row.cloud_updated_at,
+            cloud_synced_at: row.cloud_synced_at,
+            is_deleted: row.is_deleted != 0,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+struct AssistantMessageRow {
+    id: String,
+    assistant_id: String,
+    role: String,
+    content: String,
+    is_deleted: i64,
+    created_at: String,
+    updated_at: String,
+}
+
+impl From<AssistantMessageRow> for LocalAssistantMessage {
+    fn from(row: AssistantMessageRow) -> Self {
+        LocalAssistantMessage {
+            id: row.id,
+            assistant_id: row.assistant_id,
+            role: row.role,
+            content: row.content,
+            is_deleted: row.is_deleted != 0,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// The `cwd` of a stored config, which has no column of its own.
+fn config_cwd(config_json: &str) -> Option<String> {
+    let config: serde_json::Value = serde_json::from_str(config_json).ok()?;
+    config.get("cwd")?.as_str().map(str::to_string)
+}
+
+/// The `hooks` of a stored config; unreadable hooks count as none.
+fn config_hooks(config_json: &str) -> ToolHooks {
+    serde_json::from_str::<serde_json::Value>(config_json)
+        .ok()
+        .and_then(|mut config| serde_json::from_value(config.get_mut("hooks")?.take()).ok())
+        .unwrap_or_default()
+}
+
+/// The `healthcheck` of a stored config; an unreadable one counts as none.
+fn config_healthcheck(config_json: &str) -> Option<HealthCheck> {
+    let mut config: serde_json::Value = serde_json::from_str(config_json).ok()?;
+    serde_json::from_value(config.get_mut("healthcheck")?.take()).ok()
 }
 
 fn deserialize_json<T>(value: Option<String>) -> Result<Option<T>, McpError>
@@ -674,6 +2254,26 @@ where
     }
 }
 
+/// `LIMIT`/`OFFSET` bindings; SQLite treats a negative limit as unbounded.
+fn page_bounds(limit: Option<i64>, offset: Option<i64>) -> Result<(i64, i64), McpError> {
+    if limit.is_some_and(|limit| limit < 0) || offset.is_some_and(|offset| offset < 0) {
+        return Err(McpError::Validation(
+            "limit and offset must be non-negative".to_string(),
+        ));
+    }
+    Ok((limit.unwrap_or(-1), offset.unwrap_or(0)))
+}
+
+/// Substring `LIKE` pattern with `%`, `_` and `\` escaped.
+fn like_pattern(q: Option<&str>) -> Option<String> {
+    let q = q.map(str::trim).filter(|q| !q.is_empty())?;
+    let escaped = q
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    Some(format!("%{escaped}%"))
+}
+
 fn now_rfc3339() -> Result<String, McpError> {
     Ok(time::OffsetDateTime::now_utc().format(&time::format_description::well_known::Rfc3339)?)
 }
@@ -706,6 +2306,7 @@ mod tests {
         let tool = ToolUpsert {
             id: None,
             source_id: local.id.clone(),
+            identifier: None,
             name: "alpha".to_string(),
             source_type: McpSourceType::Local,
             status: McpToolStatus::Stopped,
@@ -722,6 +2323,7 @@ mod tests {
             pending_config_hash: None,
             conflict_status: McpConflictStatus::None,
             is_read_only: false,
+            is_new: false,
         };
         store.upsert_tool(tool).await.unwrap();
 
@@ -754,6 +2356,7 @@ mod tests {
         let tool = ToolUpsert {
             id: None,
             source_id: source.id.clone(),
+            identifier: None,
             name: "beta".to_string(),
             source_type: McpSourceType::Modelscope,
             status: McpToolStatus::Stopped,
@@ -770,6 +2373,7 @@ mod tests {
             pending_config_hash: None,
             conflict_status: McpConflictStatus::None,
             is_read_only: true,
+            is_new: false,
         };
         let created = store.upsert_tool(tool).await.unwrap();
 
@@ -788,5 +2392,550 @@ mod tests {
         let updated = store.get_tool(&created.id).await.unwrap().unwrap();
         assert_eq!(updated.pending_config_hash, Some(updated_hash));
         assert_eq!(updated.conflict_status, McpConflictStatus::UpdateAvailable);
+
+        let detail = store.get_tool_detail(&created.id).await.unwrap().unwrap();
+        assert_eq!(
+            detail.pending_config_json,
+            Some(serde_json::to_string(&updated_config).unwrap())
+        );
+        assert_eq!(detail.config_json, serde_json::to_string(&config).unwrap());
+    }
+
+    #[tokio::test]
+    async fn applies_sync_writes_in_one_pass() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        store.init().await.unwrap();
+        let local = store.ensure_local_source().await.unwrap();
+        let source = store
+            .insert_source(NewSource {
+                name: "ModelScope".to_string(),
+                source_type: McpSourceType::Modelscope,
+                path_or_url: "https://example.com/mcp.json".to_string(),
+                trust_level: McpTrustLevel::Community,
+                status: McpSourceStatus::Active,
+                last_synced_at: None,
+                is_read_only: true,
+            })
+            .await
+            .unwrap();
+        let upsert = |source_id: &str, source_type: McpSourceType, name: &str| ToolUpsert {
+            id: None,
+            source_id: source_id.to_string(),
+            identifier: None,
+            name: name.to_string(),
+            source_type,
+            status: McpToolStatus::Stopped,
+            ping_ms: None,
+            capabilities: vec![],
+            description: "tool".to_string(),
+            error: None,
+            command: Some("echo".to_string()),
+            args: None,
+            env: None,
+            config_json: format!(r#"{{"name":"{name}"}}"#),
+            config_hash: format!("hash-{name}"),
+            pending_config_json: None,
+            pending_config_hash: None,
+            conflict_status: McpConflictStatus::None,
+            is_read_only: true,
+            is_new: false,
+        };
+        store
+            .upsert_tool(upsert(&local.id, McpSourceType::Local, "gamma"))
+            .await
+            .unwrap();
+        let alpha = store
+            .upsert_tool(upsert(&source.id, McpSourceType::Modelscope, "alpha"))
+            .await
+            .unwrap();
+        let beta = store
+            .upsert_tool(upsert(&source.id, McpSourceType::Modelscope, "beta"))
+            .await
+            .unwrap();
+
+        let snapshot = store.source_snapshot(&source.id).await.unwrap();
+        assert_eq!(snapshot.tools["alpha"].1, r#"{"name":"alpha"}"#);
+        assert!(snapshot.local_names.contains("gamma"));
+        assert!(!snapshot.local_names.contains("alpha"));
+
+        let tools = store
+            .apply_sync_writes(
+                &source.id,
+                vec![
+                    SyncWrite::Upsert(Box::new(upsert(
+                        &source.id,
+                        McpSourceType::Modelscope,
+                        "gamma",
+                    ))),
+                    SyncWrite::Keep(alpha.id.clone()),
+                    SyncWrite::Pending {
+                        id: beta.id.clone(),
+                        config_json: r#"{"name":"beta","args":["x"]}"#.to_string(),
+                        config_hash: "hash-beta-2".to_string(),
+                        conflict_status: McpConflictStatus::UpdateAvailable,
+                    },
+                ],
+            )
+            .await
+            .unwrap();
+
+        let names: Vec<_> = tools.iter().map(|tool| tool.name.as_str()).collect();
+        assert_eq!(names, ["gamma", "alpha", "beta"]);
+        assert_eq!(tools[0].source_id.as_deref(), Some(source.id.as_str()));
+        assert_eq!(tools[2].pending_config_hash.as_deref(), Some("hash-beta-2"));
+        assert_eq!(tools[2].conflict_status, McpConflictStatus::UpdateAvailable);
+    }
+
+    #[tokio::test]
+    async fn filters_and_pages_tools() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        store.init().await.unwrap();
+        let local = store.ensure_local_source().await.unwrap();
+        for (name, status) in [
+            ("alpha_fs", McpToolStatus::Stopped),
+            ("beta", McpToolStatus::Healthy),
+            ("alphabet", McpToolStatus::Stopped),
+        ] {
+            let config = json!({"name": name, "command": "echo"});
+            store
+                .upsert_tool(ToolUpsert {
+                    id: None,
+                    source_id: local.id.clone(),
+                    identifier: None,
+                    name: name.to_string(),
+                    source_type: McpSourceType::Local,
+                    status,
+                    ping_ms: None,
+                    capabilities: vec![],
+                    description: String::new(),
+                    error: None,
+                    command: Some("echo".to_string()),
+                    args: None,
+                    env: None,
+                    config_json: serde_json::to_string(&config).unwrap(),
+                    config_hash: store.compute_config_hash(&config).unwrap(),
+                    pending_config_json: None,
+                    pending_config_hash: None,
+                    conflict_status: McpConflictStatus::None,
+                    is_read_only: false,
+                    is_new: false,
+                })
+                .await
+                .unwrap();
+        }
+
+        let (tools, total) = store
+            .query_tools(&ListToolsQuery {
+                status: Some(McpToolStatus::Stopped),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name, "alpha_fs");
+
+        // `_` is matched literally, ignoring case.
+        let (tools, total) = store
+            .query_tools(&ListToolsQuery {
+                q: Some("A_".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(tools[0].name, "alpha_fs");
+
+        let (tools, total) = store
+            .query_tools(&ListToolsQuery {
+                source_id: Some(local.id.clone()),
+                offset: Some(2),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(tools[0].name, "alphabet");
+
+        // Writes invalidate the cached list the queries above filled.
+        store
+            .set_tool_status(&tools[0].id, McpToolStatus::Healthy, Some(3), None)
+            .await
+            .unwrap();
+        let (tools, total) = store
+            .query_tools(&ListToolsQuery {
+                status: Some(McpToolStatus::Healthy),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(tools[1].name, "alphabet");
+        assert_eq!(tools[1].ping_ms, Some(3));
+
+        let err = store
+            .query_tools(&ListToolsQuery {
+                limit: Some(-1),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(err, McpError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn upserts_cloud_tools_by_identifier() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        store.init().await.unwrap();
+        let cloud = store
+            .insert_source(NewSource {
+                name: "Cloud".to_string(),
+                source_type: McpSourceType::Cloud,
+                path_or_url: "https://cloud.example.com".to_string(),
+                trust_level: McpTrustLevel::Official,
+                status: McpSourceStatus::Active,
+                last_synced_at: None,
+                is_read_only: true,
+            })
+            .await
+            .unwrap();
+
+        let upsert = |name: &str| {
+            let config = json!({"identifier": "acme/fs", "name": name, "command": "npx"});
+            ToolUpsert {
+                id: None,
+                source_id: cloud.id.clone(),
+                identifier: Some("acme/fs".to_string()),
+                name: name.to_string(),
+                source_type: McpSourceType::Cloud,
+                status: McpToolStatus::Stopped,
+                ping_ms: None,
+                capabilities: vec![],
+                description: String::new(),
+                error: None,
+                command: Some("npx".to_string()),
+                args: None,
+                env: None,
+                config_json: serde_json::to_string(&config).unwrap(),
+                config_hash: store.compute_config_hash(&config).unwrap(),
+                pending_config_json: None,
+                pending_config_hash: None,
+                conflict_status: McpConflictStatus::None,
+                is_read_only: true,
+                is_new: false,
+            }
+        };
+        let created = store.upsert_tool(upsert("fs")).await.unwrap();
+        let renamed = store.upsert_tool(upsert("filesystem")).await.unwrap();
+        assert_eq!(created.id, renamed.id);
+        assert_eq!(renamed.name, "filesystem");
+        assert_eq!(renamed.identifier.as_deref(), Some("acme/fs"));
+
+        assert_eq!(store.get_cloud_session(&cloud.id).await.unwrap(), None);
+        store.set_cloud_session(&cloud.id, "one").await.unwrap();
+        store.set_cloud_session(&cloud.id, "two").await.unwrap();
+        assert_eq!(
+            store.get_cloud_session(&cloud.id).await.unwrap().as_deref(),
+            Some("two")
+        );
+        let stored: String =
+            sqlx::query_scalar("SELECT access_token FROM cloud_sessions WHERE source_id = ?")
+                .bind(&cloud.id)
+                .fetch_one(&store.pool)
+                .await
+                .unwrap();
+        assert!(!stored.contains("two"));
+
+        sqlx::query("UPDATE cloud_sessions SET access_token = 'legacy' WHERE source_id = ?")
+            .bind(&cloud.id)
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_cloud_session(&cloud.id).await.unwrap().as_deref(),
+            Some("legacy")
+        );
+        let stored: String =
+            sqlx::query_scalar("SELECT access_token FROM cloud_sessions WHERE source_id = ?")
+                .bind(&cloud.id)
+                .fetch_one(&store.pool)
+                .await
+                .unwrap();
+        assert!(SessionCipher::is_encrypted(&stored));
+    }
+
+    #[tokio::test]
+    async fn deleting_source_can_orphan_its_tools() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        store.init().await.unwrap();
+        let local = store.ensure_local_source().await.unwrap();
+        let remote = store
+            .insert_source(NewSource {
+                name: "Hub".to_string(),
+                source_type: McpSourceType::Url,
+                path_or_url: "https://example.com/mcp.json".to_string(),
+                trust_level: McpTrustLevel::Community,
+                status: McpSourceStatus::Active,
+                last_synced_at: None,
+                is_read_only: true,
+            })
+            .await
+            .unwrap();
+
+        let config = json!({"name": "gamma", "command": "echo"});
+        let tool = store
+            .upsert_tool(ToolUpsert {
+                id: None,
+                source_id: remote.id.clone(),
+                identifier: None,
+                name: "gamma".to_string(),
+                source_type: McpSourceType::Url,
+                status: McpToolStatus::Stopped,
+                ping_ms: None,
+                capabilities: vec![],
+                description: "remote tool".to_string(),
+                error: None,
+                command: Some("echo".to_string()),
+                args: None,
+                env: None,
+                config_json: serde_json::to_string(&config).unwrap(),
+                config_hash: store.compute_config_hash(&config).unwrap(),
+                pending_config_json: None,
+                pending_config_hash: None,
+                conflict_status: McpConflictStatus::None,
+                is_read_only: true,
+                is_new: false,
+            })
+            .await
+            .unwrap();
+
+        store.check_orphanable(&remote.id, &local.id).await.unwrap();
+        store
+            .delete_source(&remote.id, SourceDeleteMode::Orphan, &local.id)
+            .await
+            .unwrap();
+
+        assert!(store.get_source(&remote.id).await.unwrap().is_none());
+        let orphaned = store.get_tool(&tool.id).await.unwrap().unwrap();
+        assert_eq!(orphaned.source_id, Some(local.id.clone()));
+        assert_eq!(orphaned.status, McpToolStatus::Orphaned);
+        assert!(!orphaned.is_read_only);
+    }
+
+    #[tokio::test]
+    async fn verifies_api_tokens_by_hash() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        store.init().await.unwrap();
+        assert!(!store.has_api_tokens().await.unwrap());
+
+        let (token, secret) = store.create_api_token("ci").await.unwrap();
+        assert!(store.has_api_tokens().await.unwrap());
+        assert!(store.verify_api_token(&secret).await.unwrap());
+        assert!(!store.verify_api_token("dk_wrong").await.unwrap());
+
+        let listed = store.list_api_tokens().await.unwrap();
+        assert!(listed[0].last_used_at.is_some());
+
+        store.delete_api_token(&token.id).await.unwrap();
+        assert!(!store.verify_api_token(&secret).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn filters_webhooks_by_event() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        store.init().await.unwrap();
+        assert!(store
+            .create_webhook("ftp://example.com", None, Vec::new())
+            .await
+            .is_err());
+
+        let (all, generated) = store
+            .create_webhook("https://example.com/all", None, Vec::new())
+            .await
+            .unwrap();
+        assert!(generated.starts_with("whsec_"));
+        let (crashes, secret) = store
+            .create_webhook(
+                "https://example.com/crashes",
+                Some("s3cret".to_string()),
+                vec![WebhookEvent::ToolCrashed],
+            )
+            .await
+            .unwrap();
+        assert_eq!(secret, "s3cret");
+
+        let targets = store
+            .webhook_targets(WebhookEvent::ToolCrashed)
+            .await
+            .unwrap();
+        assert_eq!(targets.len(), 2);
+        let targets = store
+            .webhook_targets(WebhookEvent::SyncFailed)
+            .await
+            .unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].0.id, all.id);
+
+        store
+            .record_webhook_delivery(&crashes.id, Some("endpoint answered 500"))
+            .await
+            .unwrap();
+        let listed = store.list_webhooks().await.unwrap();
+        assert_eq!(
+            listed[1].last_error.as_deref(),
+            Some("endpoint answered 500")
+        );
+
+        store.delete_webhook(&crashes.id).await.unwrap();
+        assert!(store.delete_webhook(&crashes.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn assistant_crud_round_trip() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        store.init().await.unwrap();
+
+        let created = store
+            .create_assistant(CreateLocalAssistantRequest {
+                name: "Reviewer".to_string(),
+                description: None,
+                avatar: None,
+                system_prompt: "Review code".to_string(),
+                model_config: Some(json!({"model": "gpt-4o"})),
+                tags: Some(vec!["code".to_string()]),
+                visibility: None,
+                source: None,
+                cloud_id: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(created.visibility, "private");
+        assert_eq!(created.source, "local");
+
+        let updated = store
+            .update_assistant(
+                &created.id,
+                UpdateLocalAssistantRequest {
+                    name: Some("Strict Reviewer".to_string()),
+                    description: None,
+                    avatar: None,
+                    system_prompt: None,
+                    model_config: None,
+                    tags: None,
+                    visibility: None,
+                    source: None,
+                    cloud_id: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.name, "Strict Reviewer");
+        assert_eq!(updated.system_prompt, "Review code");
+        assert_eq!(updated.model_config, Some(json!({"model": "gpt-4o"})));
+
+        store.delete_assistant(&created.id).await.unwrap();
+        assert!(store.get_assistant(&created.id).await.unwrap().is_none());
+        assert!(store.list_assistants().await.unwrap().is_empty());
+        assert!(store.delete_assistant(&created.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn clears_assistant_message_history() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        store.init().await.unwrap();
+        let assistant = store
+            .create_assistant(CreateLocalAssistantRequest {
+                name: "Helper".to_string(),
+                description: None,
+                avatar: None,
+                system_prompt: "Be helpful".to_string(),
+                model_config: None,
+                tags: None,
+                visibility: None,
+                source: None,
+                cloud_id: None,
+            })
+            .await
+            .unwrap();
+
+        for (role, content) in [("user", "hi"), ("assistant", "hello")] {
+            store
+                .append_assistant_message(CreateAssistantMessageRequest {
+                    assistant_id: assistant.id.clone(),
+                    role: role.to_string(),
+                    content: content.to_string(),
+                })
+                .await
+                .unwrap();
+        }
+        let messages = store.list_assistant_messages(&assistant.id).await.unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].role, "user");
+
+        store.delete_assistant_messages(&assistant.id).await.unwrap();
+        assert!(store
+            .list_assistant_messages(&assistant.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn migrates_tools_written_by_the_desktop_app() {
+        let store = McpStore::new("sqlite::memory:").await.unwrap();
+        // Schema of a database created before the parity columns existed.
+        sqlx::query(
+            r#"
+            CREATE TABLE mcp_tools (
+              id TEXT PRIMARY KEY,
+              source_id TEXT NOT NULL,
+              name TEXT NOT NULL,
+              source_type TEXT NOT NULL,
+              status TEXT NOT NULL,
+              ping_ms INTEGER,
+              capabilities TEXT NOT NULL,
+              description TEXT NOT NULL,
+              error TEXT,
+              command TEXT,
+              args TEXT,
+              env TEXT,
+              config_json TEXT NOT NULL,
+              config_hash TEXT NOT NULL,
+              conflict_status TEXT NOT NULL,
+              is_read_only INTEGER NOT NULL,
+              created_at TEXT NOT NULL,
+              updated_at TEXT NOT NULL
+            );
+            "#,
+        )
+        .execute(&store.pool)
+        .await
+        .unwrap();
+        store.init().await.unwrap();
+        let local = store.ensure_local_source().await.unwrap();
+
+        sqlx::query(
+            r#"
+            INSERT INTO mcp_tools
+              (id, source_id, name, source_type, status, capabilities, description,
+               config_json, config_hash, conflict_status, is_read_only, approved_at,
+               market_tool_id, created_at, updated_at)
+            VALUES ('t1', ?, 'alpha', 'cloud', 'pending', '[]', '', '{}', 'h', 'none', 1,
+                    '2024-01-01T00:00:00Z', 'm1', '2024-01-01T00:00:00Z',
+                    '2024-01-01T00:00:00Z');
+            "#,
+        )
+        .bind(&local.id)
+        .execute(&store.pool)
+        .await
+        .unwrap();
+
+        let tool = store.get_tool("t1").await.unwrap().unwrap();
+        assert_eq!(tool.status, McpToolStatus::Pending);
+        assert_eq!(tool.source_type, McpSourceType::Cloud);
+        assert!(!tool.is_new);
+        assert_eq!(tool.approved_at.as_deref(), Some("2024-01-01T00:00:00Z"));
+        assert_eq!(tool.market_tool_id.as_deref(), Some("m1"));
     }
 }
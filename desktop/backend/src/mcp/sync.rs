@@ -0,0 +1,233 @@
+//! The sync engine: reads a source's config and reconciles its tools. Used by
+//! the HTTP routes and by [`crate::Registry`] when embedded.
+
+use std::future::Future;
+
+use mcp_core::merge::merge_config;
+
+use super::{
+    ExtractedToolFields, McpConfigPayload, McpConflictStatus, McpError, McpEvent, McpSource,
+    McpSourceStatus, McpSourceType, McpStore, McpTool, McpToolStatus, SourceProviders, SyncWrite,
+    ToolUpsert,
+};
+
+/// Syncs a source with the provider that handles it, skipping the apply
+/// when the fetched content is what was applied last. Cloud sources need a
+/// cloud session and the subscription API, so only the HTTP service syncs
+/// them.
+#[tracing::instrument(
+    name = "source.sync",
+    skip_all,
+    fields(source_id = %source.id, source_type = ?source.source_type, provider)
+)]
+pub async fn sync_source(
+    store: &McpStore,
+    providers: &SourceProviders,
+    source: McpSource,
+    auth_token: Option<String>,
+) -> Result<Vec<McpTool>, McpError> {
+    let provider = match providers.for_source(&source) {
+        Some(provider) => provider,
+        None if source.source_type == McpSourceType::Cloud => {
+            return Err(McpError::Validation(format!(
+                "cloud source {} syncs through POST /mcp/cloud/sync",
+                source.id
+            )));
+        }
+        None => {
+            return Err(McpError::Validation(format!(
+                "no provider syncs source {} ({:?})",
+                source.id, source.source_type
+            )));
+        }
+    };
+    tracing::Span::current().record("provider", provider.name());
+    let fetched = provider.fetch(&source, auth_token.as_deref()).await?;
+    if let Some(content_hash) = &fetched.content_hash {
+        if store.is_config_applied(&source.id, content_hash) {
+            return store.list_tools_by_source(&source.id).await;
+        }
+    }
+    let tools = apply_config_payload(store, &source, fetched.payload).await?;
+    if let Some(content_hash) = fetched.content_hash {
+        store.record_applied_config(&source.id, content_hash);
+    }
+    Ok(tools)
+}
+
+/// Runs `sync` with the source marked `syncing`, then records the outcome:
+/// `active` with a fresh `last_sync_at`, or `error`, plus a `SourceSynced`
+/// event either way.
+pub async fn track_sync<F>(
+    store: &McpStore,
+    source_id: &str,
+    sync: F,
+) -> Result<Vec<McpTool>, McpError>
+where
+    F: Future<Output = Result<Vec<McpTool>, McpError>>,
+{
+    store
+        .update_source_status(source_id, McpSourceStatus::Syncing, None)
+        .await?;
+    let result = sync.await;
+    store.publish(McpEvent::SourceSynced {
+        source_id: source_id.to_string(),
+        success: result.is_ok(),
+    });
+    match result {
+        Ok(tools) => {
+            store
+                .update_source_status(source_id, McpSourceStatus::Active, Some(now_rfc3339()?))
+                .await?;
+            Ok(tools)
+        }
+        Err(err) => {
+            store
+                .update_source_status(source_id, McpSourceStatus::Error, None)
+                .await?;
+            Err(err)
+        }
+    }
+}
+
+/// Writes `payload` as the source's tools. Tools whose config is unchanged,
+/// or whose update was already merged, are kept; read-only sources get
+/// changes as pending updates instead of overwriting them.
+#[tracing::instrument(
+    name = "source.apply_config",
+    skip_all,
+    fields(source_id = %source.id, tools = payload.mcp_servers.len())
+)]
+pub async fn apply_config_payload(
+    store: &McpStore,
+    source: &McpSource,
+    payload: McpConfigPayload,
+) -> Result<Vec<McpTool>, McpError> {
+    store.forget_applied_config(&source.id);
+    let mut writes = Vec::with_capacity(payload.mcp_servers.len());
+    let is_read_only = source.source_type != McpSourceType::Local || source.is_read_only;
+    let mut snapshot = store.source_snapshot(&source.id).await?;
+
+    for (name, config_payload) in payload.mcp_servers {
+        let config_value = store.build_config_json(&name, &config_payload)?;
+        let config_hash = store.compute_config_hash(&config_value)?;
+        let config_json = serde_json::to_string(&config_value)?;
+        let extracted: ExtractedToolFields = store.extract_tool_fields(&name, &config_payload);
+        let name_conflict = snapshot.local_names.contains(&name);
+
+        let write = match snapshot.tools.remove(&name) {
+            Some((existing_tool, stored_config_json)) => {
+                if existing_tool.config_hash == config_hash
+                    || merges_to_stored_hash(
+                        store,
+                        &existing_tool,
+                        &stored_config_json,
+                        &config_value,
+                    )?
+                {
+                    SyncWrite::Keep(existing_tool.id)
+                } else if is_read_only {
+                    SyncWrite::Pending {
+                        id: existing_tool.id,
+                        config_json,
+                        config_hash,
+                        conflict_status: if name_conflict {
+                            McpConflictStatus::Conflict
+                        } else {
+                            McpConflictStatus::UpdateAvailable
+                        },
+                    }
+                } else {
+                    SyncWrite::Upsert(Box::new(ToolUpsert {
+                        id: Some(existing_tool.id.clone()),
+                        source_id: source.id.clone(),
+                        identifier: existing_tool.identifier.clone(),
+                        name: extracted.name,
+                        source_type: source.source_type.clone(),
+                        status: existing_tool.status.clone(),
+                        ping_ms: existing_tool.ping_ms,
+                        capabilities: extracted.capabilities,
+                        description: extracted.description,
+                        error: existing_tool.error.clone(),
+                        command: extracted.command,
+                        args: extracted.args,
+                        env: extracted.env,
+                        config_json,
+                        config_hash,
+                        pending_config_json: None,
+                        pending_config_hash: None,
+                        conflict_status: if name_conflict {
+                            McpConflictStatus::Conflict
+                        } else {
+                            McpConflictStatus::None
+                        },
+                        is_read_only,
+                        is_new: existing_tool.is_new,
+                    }))
+                }
+            }
+            None => SyncWrite::Upsert(Box::new(ToolUpsert {
+                id: None,
+                source_id: source.id.clone(),
+                identifier: None,
+                name: extracted.name,
+                source_type: source.source_type.clone(),
+                status: McpToolStatus::Stopped,
+                ping_ms: None,
+                capabilities: extracted.capabilities,
+                description: extracted.description,
+                error: None,
+                command: extracted.command,
+                args: extracted.args,
+                env: extracted.env,
+                config_json,
+                config_hash,
+                pending_config_json: None,
+                pending_config_hash: None,
+                conflict_status: if name_conflict {
+                    McpConflictStatus::Conflict
+                } else {
+                    McpConflictStatus::None
+                },
+                is_read_only,
+                is_new: true,
+            })),
+        };
+
+        writes.push(write);
+    }
+
+    store.apply_sync_writes(&source.id, writes).await
+}
+
+/// Returns true when `incoming` is already reflected in the tool's config once
+/// the user's local env overrides are merged in, i.e. a previous "merge"
+/// resolution already absorbed this upstream change.
+pub async fn is_absorbed_by_merge(
+    store: &McpStore,
+    tool: &McpTool,
+    incoming: &serde_json::Value,
+) -> Result<bool, McpError> {
+    let Some(config_json) = store.get_config_json(&tool.id).await? else {
+        return Ok(false);
+    };
+    merges_to_stored_hash(store, tool, &config_json, incoming)
+}
+
+/// `is_absorbed_by_merge` against the tool's already loaded `config_json`.
+fn merges_to_stored_hash(
+    store: &McpStore,
+    tool: &McpTool,
+    config_json: &str,
+    incoming: &serde_json::Value,
+) -> Result<bool, McpError> {
+    let Ok(current_value) = serde_json::from_str::<serde_json::Value>(config_json) else {
+        return Ok(false);
+    };
+    let merged = merge_config(&current_value, tool.env.as_ref(), incoming);
+    Ok(store.compute_config_hash(&merged)? == tool.config_hash)
+}
+
+pub(crate) fn now_rfc3339() -> Result<String, McpError> {
+    Ok(time::OffsetDateTime::now_utc().format(&time::format_description::well_known::Rfc3339)?)
+}
@@ -1,14 +1,22 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
 
+use mcp_core::health;
+#[cfg(feature = "server")]
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "server")]
+use utoipa::{IntoParams, ToSchema};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum McpSourceType {
     Local,
     Modelscope,
     Github,
     Url,
+    Cloud,
 }
 
 impl McpSourceType {
@@ -18,6 +26,7 @@ impl McpSourceType {
             McpSourceType::Modelscope => "modelscope",
             McpSourceType::Github => "github",
             McpSourceType::Url => "url",
+            McpSourceType::Cloud => "cloud",
         }
     }
 }
@@ -31,12 +40,14 @@ impl std::str::FromStr for McpSourceType {
             "modelscope" => Ok(McpSourceType::Modelscope),
             "github" => Ok(McpSourceType::Github),
             "url" => Ok(McpSourceType::Url),
+            "cloud" => Ok(McpSourceType::Cloud),
             _ => Err(format!("unknown source type: {value}")),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum McpSourceStatus {
     Active,
@@ -71,6 +82,7 @@ impl std::str::FromStr for McpSourceStatus {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum McpTrustLevel {
     Official,
@@ -102,8 +114,11 @@ impl std::str::FromStr for McpTrustLevel {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum McpToolStatus {
+    /// Waiting on the user (e.g. required env is missing) before it can start.
+    Pending,
     Stopped,
     Starting,
     Healthy,
@@ -111,11 +126,13 @@ pub enum McpToolStatus {
     Crashed,
     Updating,
     Error,
+    Orphaned,
 }
 
 impl McpToolStatus {
     pub fn as_str(&self) -> &'static str {
         match self {
+            McpToolStatus::Pending => "pending",
             McpToolStatus::Stopped => "stopped",
             McpToolStatus::Starting => "starting",
             McpToolStatus::Healthy => "healthy",
@@ -123,6 +140,7 @@ impl McpToolStatus {
             McpToolStatus::Crashed => "crashed",
             McpToolStatus::Updating => "updating",
             McpToolStatus::Error => "error",
+            McpToolStatus::Orphaned => "orphaned",
         }
     }
 }
@@ -132,6 +150,7 @@ impl std::str::FromStr for McpToolStatus {
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
         match value {
+            "pending" => Ok(McpToolStatus::Pending),
             "stopped" => Ok(McpToolStatus::Stopped),
             "starting" => Ok(McpToolStatus::Starting),
             "healthy" => Ok(McpToolStatus::Healthy),
@@ -139,12 +158,14 @@ impl std::str::FromStr for McpToolStatus {
             "crashed" => Ok(McpToolStatus::Crashed),
             "updating" => Ok(McpToolStatus::Updating),
             "error" => Ok(McpToolStatus::Error),
+            "orphaned" => Ok(McpToolStatus::Orphaned),
             _ => Err(format!("unknown tool status: {value}")),
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum McpConflictStatus {
     None,
@@ -176,6 +197,7 @@ impl std::str::FromStr for McpConflictStatus {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct McpSource {
     pub id: String,
     pub name: String,
@@ -190,11 +212,14 @@ pub struct McpSource {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct McpTool {
     pub id: String,
     pub name: String,
     pub source_type: McpSourceType,
     pub source_id: Option<String>,
+    /// Stable marketplace identifier for cloud tools; `None` for config imports.
+    pub identifier: Option<String>,
     pub status: McpToolStatus,
     pub ping_ms: Option<i64>,
     pub capabilities: Vec<String>,
@@ -203,32 +228,151 @@ pub struct McpTool {
     pub command: Option<String>,
     pub args: Option<Vec<String>>,
     pub env: Option<HashMap<String, String>>,
+    /// Working directory the tool is started in, from its config's `cwd`.
+    pub cwd: Option<String>,
+    /// Setup and teardown commands, from its config's `hooks`.
+    pub hooks: ToolHooks,
+    /// From its config's `healthcheck`.
+    pub healthcheck: Option<HealthCheck>,
     pub config_hash: String,
     pub pending_config_hash: Option<String>,
     pub conflict_status: McpConflictStatus,
     pub is_read_only: bool,
+    /// Installed by a sync and not configured yet; cleared by an env update.
+    pub is_new: bool,
+    /// When the user approved a tool from a non-official source to run.
+    pub approved_at: Option<String>,
+    pub market_tool_id: Option<String>,
+    /// Log lines kept in memory for this tool instead of
+    /// `DESKTOP_LOG_BUFFER_SIZE`.
+    pub log_buffer_size: Option<usize>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Full tool record, including the config documents `McpTool` leaves out of
+/// list responses.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct McpToolDetail {
+    #[serde(flatten)]
+    pub tool: McpTool,
+    pub config_json: String,
+    pub pending_config_json: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema, JsonSchema))]
 pub struct McpToolConfigPayload {
     pub command: Option<String>,
     pub args: Option<Vec<String>>,
     pub env: Option<HashMap<String, String>>,
+    /// Working directory; like `command`, `args` and `env` it may reference
+    /// `${VAR}`s, resolved at start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<ToolHooks>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<HealthCheck>,
     pub description: Option<String>,
     pub capabilities: Option<Vec<String>>,
     #[serde(flatten)]
     pub extra: HashMap<String, serde_json::Value>,
 }
 
+/// Shell commands run around a tool's process, in the tool's working
+/// directory with its env. Their output goes to the tool's log, and they may
+/// reference `${VAR}`s like the command.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema, JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ToolHooks {
+    /// Run in order before every start, e.g. `npm install`. The tool does
+    /// not start if one fails.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_start: Vec<String>,
+    /// Run in order once the process has exited, whether it was stopped or
+    /// crashed. Failures are logged and the remaining hooks still run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_stop: Vec<String>,
+}
+
+impl ToolHooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_start.is_empty() && self.post_stop.is_empty()
+    }
+}
+
+/// How the health monitor checks a tool that the MCP ping cannot reach.
+/// Exactly one of `command`, `tcp` or `http` is set; like the command they
+/// may reference `${VAR}`s. A failed probe marks the tool degraded, and
+/// `failureThreshold` failures in a row stop it as crashed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema, JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    /// Shell command run in the tool's working directory with its env;
+    /// passes when it exits 0.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// `host:port` that must accept a TCP connection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp: Option<String>,
+    /// URL whose GET must answer with a 2xx status.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<String>,
+    /// Seconds between probes; 30 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    /// Seconds one probe may take; 5 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Failed probes in a row before the tool is stopped; 3 when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_threshold: Option<u32>,
+}
+
+impl HealthCheck {
+    pub fn validate(&self) -> Result<(), String> {
+        let targets = [&self.command, &self.tcp, &self.http]
+            .iter()
+            .filter(|target| target.is_some())
+            .count();
+        if targets != 1 {
+            return Err("healthcheck needs exactly one of command, tcp or http".to_string());
+        }
+        if self.interval_secs == Some(0) || self.timeout_secs == Some(0) {
+            return Err("healthcheck intervalSecs and timeoutSecs must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval_secs
+            .map_or(health::DEFAULT_INTERVAL, Duration::from_secs)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout_secs
+            .map_or(health::DEFAULT_TIMEOUT, Duration::from_secs)
+    }
+
+    pub fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+            .unwrap_or(health::DEFAULT_FAILURE_THRESHOLD)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema, JsonSchema))]
 pub struct McpConfigPayload {
     #[serde(rename = "mcpServers")]
     pub mcp_servers: HashMap<String, McpToolConfigPayload>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct CreateSourceRequest {
     pub name: String,
     pub source_type: McpSourceType,
@@ -238,62 +382,489 @@ pub struct CreateSourceRequest {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct CreateSourceResponse {
     pub source: McpSource,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct ListSourcesResponse {
     pub sources: Vec<McpSource>,
+    /// Number of sources matching the filters, ignoring `limit`/`offset`.
+    pub total: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct ListToolsResponse {
     pub tools: Vec<McpTool>,
+    /// Number of tools matching the filters, ignoring `limit`/`offset`.
+    pub total: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct ImportConfigRequest {
     pub source_id: Option<String>,
     pub config: McpConfigPayload,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct ImportConfigResponse {
     pub tools: Vec<McpTool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct SyncSourceRequest {
     pub auth_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct SyncSourceResponse {
     pub tools: Vec<McpTool>,
 }
 
+/// `source_id` picks a cloud source (default: the first one, created from
+/// `base_url` or `DESKTOP_CLOUD_BASE_URL` when none exists). Without
+/// `access_token` the session stored by the last successful sync is used.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct CloudSyncRequest {
+    pub source_id: Option<String>,
+    pub base_url: Option<String>,
+    pub access_token: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct CloudSyncResponse {
+    pub source: McpSource,
+    pub tools: Vec<McpTool>,
+    /// Tools whose subscription was removed upstream, now `orphaned`.
+    pub orphaned: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct UpdateToolConfigRequest {
     pub apply_pending: bool,
 }
 
+/// What happens to a source's tools when the source is deleted.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SourceDeleteMode {
+    /// Delete the tools together with the source.
+    #[default]
+    Delete,
+    /// Keep the tools as orphaned entries of the local source.
+    Orphan,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(IntoParams))]
+#[cfg_attr(feature = "server", into_params(parameter_in = Query))]
+pub struct ListSourcesQuery {
+    pub status: Option<McpSourceStatus>,
+    /// Case-insensitive substring match on name or path/url.
+    pub q: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(IntoParams))]
+#[cfg_attr(feature = "server", into_params(parameter_in = Query))]
+pub struct ListToolsQuery {
+    pub status: Option<McpToolStatus>,
+    pub source_id: Option<String>,
+    /// Case-insensitive substring match on name or description.
+    pub q: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Server-side filters for `GET /mcp/tools/{id}/logs`; they combine.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(IntoParams))]
+#[cfg_attr(feature = "server", into_params(parameter_in = Query))]
+pub struct LogFilterQuery {
+    pub stream: Option<McpLogStream>,
+    /// Lowest severity to include, e.g. `warn` for warnings and errors.
+    pub level: Option<McpLogLevel>,
+    /// Case-insensitive substring the message must contain.
+    pub contains: Option<String>,
+    /// Regular expression the message must match.
+    pub regex: Option<String>,
+    /// RFC 3339 timestamp; older lines are left out.
+    pub since: Option<String>,
+    /// Only lines with a smaller `seq`, to page back from the oldest line
+    /// already shown.
+    pub before: Option<u64>,
+    /// Newest matching lines to return at most.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(IntoParams))]
+#[cfg_attr(feature = "server", into_params(parameter_in = Query))]
+pub struct DeleteSourceQuery {
+    pub mode: Option<SourceDeleteMode>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(IntoParams))]
+#[cfg_attr(feature = "server", into_params(parameter_in = Query))]
+pub struct DeleteToolQuery {
+    pub force: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum BatchToolAction {
+    Start,
+    Stop,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct BatchToolsRequest {
+    pub action: BatchToolAction,
+    pub tool_ids: Vec<String>,
+}
+
+/// Outcome for one id of a batch request; either `tool` or `code`/`error`
+/// is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct BatchToolResult {
+    pub tool_id: String,
+    pub tool: Option<McpTool>,
+    /// Same codes as the error envelope, e.g. `TOOL_NOT_FOUND`.
+    pub code: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct BatchToolsResponse {
+    pub results: Vec<BatchToolResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ResolveConflictRequest {
+    pub action: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct UpdateToolEnvRequest {
+    pub env: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct UpdateLogBufferRequest {
+    /// Lines to keep for this tool; `null` goes back to the global size.
+    pub log_buffer_size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct ToolLogsResponse {
     pub entries: Vec<McpLogEntry>,
+    /// Older matching lines exist; request them with `before` set to the
+    /// first entry's `seq`.
+    pub has_more: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 pub struct McpLogEntry {
+    /// Increases with every line the backend logs, across tools.
+    #[serde(default)]
+    pub seq: u64,
     pub timestamp: String,
     pub stream: McpLogStream,
     pub message: String,
+    /// Guessed from the line; see `log_level::infer`.
+    #[serde(default)]
+    pub level: McpLogLevel,
+    /// Fields read from a line that is a JSON log record.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured: Option<StructuredLog>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// `level`, `msg` and `time` of a JSON log line (pino, winston, ...).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct StructuredLog {
+    /// Lowercase name; numeric pino levels are translated (`50` is `error`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msg: Option<String>,
+    /// RFC 3339, as logged by the tool rather than when it was captured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+}
+
+/// A log entry tagged with the tool that produced it, as sent on the combined
+/// `/mcp/logs/stream`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct McpToolLogEntry {
+    pub tool_id: String,
+    pub entry: McpLogEntry,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum McpLogStream {
     Stdout,
     Stderr,
     Event,
 }
+
+/// Severity of a log line, ordered from `info` up to `error`.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum McpLogLevel {
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct LocalAssistant {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub avatar: Option<String>,
+    pub system_prompt: String,
+    pub model_config: Option<serde_json::Value>,
+    pub tags: Vec<String>,
+    pub visibility: String,
+    pub source: String,
+    pub cloud_id: Option<String>,
+    pub cloud_updated_at: Option<String>,
+    pub cloud_synced_at: Option<String>,
+    pub is_deleted: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct CreateLocalAssistantRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub avatar: Option<String>,
+    pub system_prompt: String,
+    pub model_config: Option<serde_json::Value>,
+    pub tags: Option<Vec<String>>,
+    pub visibility: Option<String>,
+    pub source: Option<String>,
+    pub cloud_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct UpdateLocalAssistantRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub avatar: Option<String>,
+    pub system_prompt: Option<String>,
+    pub model_config: Option<serde_json::Value>,
+    pub tags: Option<Vec<String>>,
+    pub visibility: Option<String>,
+    pub source: Option<String>,
+    pub cloud_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ListAssistantsResponse {
+    pub assistants: Vec<LocalAssistant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct LocalAssistantMessage {
+    pub id: String,
+    pub assistant_id: String,
+    pub role: String,
+    pub content: String,
+    pub is_deleted: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct CreateAssistantMessageRequest {
+    pub assistant_id: String,
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct AppendAssistantMessageRequest {
+    pub role: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ListAssistantMessagesResponse {
+    pub messages: Vec<LocalAssistantMessage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct LocalChatInputMessage {
+    pub role: String,
+    pub content: String,
+}
+
+/// OpenAI-compatible chat request. `base_url`/`api_key` fall back to the
+/// assistant's `model_config` and then to `DESKTOP_CHAT_BASE_URL` /
+/// `DESKTOP_CHAT_API_KEY`; a request that sets `base_url` must also set
+/// `api_key`, since stored keys are only sent to stored base URLs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct LocalChatRequest {
+    pub assistant_id: Option<String>,
+    pub model: String,
+    pub messages: Vec<LocalChatInputMessage>,
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub base_url: Option<String>,
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct LocalChatResponse {
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct CreateApiTokenRequest {
+    pub name: String,
+}
+
+/// Returned once on creation; only the hash of `secret` is stored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct CreateApiTokenResponse {
+    pub token: ApiToken,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ListApiTokensResponse {
+    pub tokens: Vec<ApiToken>,
+}
+
+/// Tool and source events a webhook can subscribe to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    ToolCrashed,
+    ConflictDetected,
+    SyncFailed,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::ToolCrashed => "tool_crashed",
+            WebhookEvent::ConflictDetected => "conflict_detected",
+            WebhookEvent::SyncFailed => "sync_failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct Webhook {
+    pub id: String,
+    pub url: String,
+    /// Events delivered to `url`; empty means all of them.
+    pub events: Vec<WebhookEvent>,
+    pub created_at: String,
+    pub last_delivery_at: Option<String>,
+    /// Why the last delivery failed after its retries; cleared on success.
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Signing key for `X-Deeting-Signature`; generated when omitted.
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+/// Returned once on creation; later listings leave `secret` out.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct CreateWebhookResponse {
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct ListWebhooksResponse {
+    pub webhooks: Vec<Webhook>,
+}
+
+/// SQLite size and contents, reported by `GET /admin/stats`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct DatabaseStats {
+    /// `page_count * page_size`; excludes the WAL file.
+    pub size_bytes: i64,
+    /// Row count per table.
+    pub tables: BTreeMap<String, i64>,
+    pub pool: PoolStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(ToSchema))]
+pub struct PoolStats {
+    /// Open connections, idle or in use.
+    pub size: u32,
+    pub idle: u32,
+    pub max_connections: u32,
+}
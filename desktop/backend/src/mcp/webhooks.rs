@@ -0,0 +1,215 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::sync::now_rfc3339;
+use super::{McpConflictStatus, McpEvent, McpStore, Webhook, WebhookEvent};
+
+/// `sha256=<hex>`: HMAC-SHA256 of the raw body, keyed with the secret.
+pub const SIGNATURE_HEADER: &str = "x-deeting-signature";
+const EVENT_HEADER: &str = "x-deeting-event";
+const DELIVERY_HEADER: &str = "x-deeting-delivery";
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Waits before the second and third attempt of a failed delivery.
+const RETRY_DELAYS: [Duration; 2] = [Duration::from_secs(2), Duration::from_secs(10)];
+
+/// POSTs crash, conflict and failed-sync events to the subscribed webhooks
+/// for the life of the process. Each delivery runs on its own task so a slow
+/// endpoint does not hold up the others.
+pub async fn deliver_events(
+    store: Arc<McpStore>,
+    http: reqwest::Client,
+    mut events: broadcast::Receiver<McpEvent>,
+) {
+    loop {
+        match events.recv().await {
+            Ok(event) => dispatch(&store, &http, event).await,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("webhook delivery lagged, skipped {} events", skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+async fn dispatch(store: &Arc<McpStore>, http: &reqwest::Client, event: McpEvent) {
+    let Some(kind) = webhook_event(&event) else {
+        return;
+    };
+    let targets = match store.webhook_targets(kind).await {
+        Ok(targets) if !targets.is_empty() => targets,
+        Ok(_) => return,
+        Err(err) => {
+            warn!(error = %err, "failed to load webhooks");
+            return;
+        }
+    };
+    let body = payload(store, kind, event).await.to_string();
+    for (webhook, secret) in targets {
+        tokio::spawn(deliver(
+            store.clone(),
+            http.clone(),
+            webhook,
+            secret,
+            kind,
+            body.clone(),
+        ));
+    }
+}
+
+fn webhook_event(event: &McpEvent) -> Option<WebhookEvent> {
+    match event {
+        McpEvent::ToolCrashed { .. } => Some(WebhookEvent::ToolCrashed),
+        McpEvent::ConflictDetected { .. } => Some(WebhookEvent::ConflictDetected),
+        McpEvent::SourceSynced { success: false, .. } => Some(WebhookEvent::SyncFailed),
+        _ => None,
+    }
+}
+
+/// The delivered JSON. `text` is a one-line summary, which is all a Slack
+/// incoming webhook needs to post it.
+async fn payload(store: &McpStore, kind: WebhookEvent, event: McpEvent) -> Value {
+    let (text, data) = match event {
+        McpEvent::ToolCrashed { tool_id, exit_code } => {
+            let name = tool_name(store, &tool_id).await;
+            (
+                format!("{name} crashed (exit code {exit_code})"),
+                json!({ "tool_id": tool_id, "tool_name": name, "exit_code": exit_code }),
+            )
+        }
+        McpEvent::ConflictDetected {
+            tool_id,
+            conflict_status,
+        } => {
+            let name = tool_name(store, &tool_id).await;
+            let text = match conflict_status {
+                McpConflictStatus::Conflict => {
+                    format!("{name}: an update clashes with a local tool")
+                }
+                _ => format!("{name}: an update is waiting for review"),
+            };
+            (
+                text,
+                json!({ "tool_id": tool_id, "tool_name": name, "conflict_status": conflict_status }),
+            )
+        }
+        McpEvent::SourceSynced { source_id, .. } => {
+            let name = match store.get_source(&source_id).await {
+                Ok(Some(source)) => source.name,
+                _ => source_id.clone(),
+            };
+            (
+                format!("Sync of {name} failed"),
+                json!({ "source_id": source_id, "source_name": name }),
+            )
+        }
+        _ => (String::new(), Value::Null),
+    };
+    json!({
+        "id": Uuid::new_v4().to_string(),
+        "event": kind,
+        "created_at": now_rfc3339().unwrap_or_default(),
+        "text": text,
+        "data": data,
+    })
+}
+
+async fn tool_name(store: &McpStore, tool_id: &str) -> String {
+    match store.get_tool(tool_id).await {
+        Ok(Some(tool)) => tool.name,
+        _ => tool_id.to_string(),
+    }
+}
+
+#[tracing::instrument(
+    name = "webhook.deliver",
+    skip_all,
+    fields(webhook_id = %webhook.id, event = kind.as_str())
+)]
+async fn deliver(
+    store: Arc<McpStore>,
+    http: reqwest::Client,
+    webhook: Webhook,
+    secret: String,
+    kind: WebhookEvent,
+    body: String,
+) {
+    let signature = sign(&secret, body.as_bytes());
+    let delivery_id = Uuid::new_v4().to_string();
+    let mut error = None;
+    for attempt in 0..=RETRY_DELAYS.len() {
+        if attempt > 0 {
+            tokio::time::sleep(RETRY_DELAYS[attempt - 1]).await;
+        }
+        let result = http
+            .post(&webhook.url)
+            .timeout(DELIVERY_TIMEOUT)
+            .header(CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, kind.as_str())
+            .header(DELIVERY_HEADER, &delivery_id)
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.clone())
+            .send()
+            .await;
+        error = match result {
+            Ok(response) if response.status().is_success() => None,
+            Ok(response) => Some(format!("endpoint answered {}", response.status())),
+            Err(err) => Some(err.to_string()),
+        };
+        if error.is_none() {
+            break;
+        }
+    }
+    if let Some(error) = &error {
+        warn!(webhook_id = %webhook.id, event = kind.as_str(), "webhook delivery failed: {error}");
+    }
+    if let Err(err) = store
+        .record_webhook_delivery(&webhook.id, error.as_deref())
+        .await
+    {
+        warn!(webhook_id = %webhook.id, "failed to record webhook delivery: {}", err);
+    }
+}
+
+/// Value of [`SIGNATURE_HEADER`] for `body`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_with_hmac_sha256() {
+        // RFC 4231, test case 2.
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn only_failed_syncs_are_delivered() {
+        let failed = McpEvent::SourceSynced {
+            source_id: "s".to_string(),
+            success: false,
+        };
+        let succeeded = McpEvent::SourceSynced {
+            source_id: "s".to_string(),
+            success: true,
+        };
+        assert_eq!(webhook_event(&failed), Some(WebhookEvent::SyncFailed));
+        assert_eq!(webhook_event(&succeeded), None);
+    }
+}
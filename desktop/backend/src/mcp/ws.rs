@@ -0,0 +1,142 @@
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::State;
+use axum::response::Response;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::RecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamMap;
+use tracing::warn;
+
+use crate::mcp::{McpError, McpLogEntry};
+use crate::state::AppState;
+
+/// Messages a client sends over `/mcp/ws`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe { tool_id: String },
+    Unsubscribe { tool_id: String },
+    Start { tool_id: String },
+    Stop { tool_id: String },
+}
+
+/// Replies and log frames sent to the client. Store events (`tool_status`,
+/// `tool_removed`, `source_status`) are forwarded as-is and share the same
+/// `type` discriminator.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    Log {
+        tool_id: String,
+        entry: McpLogEntry,
+    },
+    Ack {
+        action: &'static str,
+        tool_id: String,
+    },
+    Error {
+        code: &'static str,
+        message: String,
+    },
+}
+
+pub async fn ws_handler(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| handle_socket(state, socket))
+}
+
+/// Every connection receives all store events; log entries are only sent for
+/// tools the client subscribed to.
+async fn handle_socket(state: AppState, mut socket: WebSocket) {
+    let mut events = state.store.subscribe_events();
+    let mut logs: StreamMap<String, BroadcastStream<McpLogEntry>> = StreamMap::new();
+
+    loop {
+        let outgoing = tokio::select! {
+            incoming = socket.recv() => {
+                let Some(Ok(message)) = incoming else {
+                    break;
+                };
+                match message {
+                    Message::Text(text) => handle_client_message(&state, &mut logs, &text).await,
+                    Message::Close(_) => break,
+                    _ => continue,
+                }
+            }
+            event = events.recv() => match event {
+                Ok(event) => serde_json::to_string(&event).ok(),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("websocket client lagged, skipped {} events", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => break,
+            },
+            Some((tool_id, Ok(entry))) = logs.next() => {
+                to_text(&ServerMessage::Log { tool_id, entry })
+            }
+        };
+
+        if let Some(text) = outgoing {
+            if socket.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+    }
+}
+
+async fn handle_client_message(
+    state: &AppState,
+    logs: &mut StreamMap<String, BroadcastStream<McpLogEntry>>,
+    text: &str,
+) -> Option<String> {
+    let message = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(message) => message,
+        Err(err) => {
+            return to_text(&ServerMessage::Error {
+                code: "INVALID_MESSAGE",
+                message: format!("invalid message: {err}"),
+            })
+        }
+    };
+
+    let (action, tool_id, result) = match message {
+        ClientMessage::Subscribe { tool_id } => {
+            let receiver = state.process_manager.subscribe_logs(&tool_id).await;
+            logs.insert(tool_id.clone(), BroadcastStream::new(receiver));
+            ("subscribe", tool_id, Ok(()))
+        }
+        ClientMessage::Unsubscribe { tool_id } => {
+            logs.remove(&tool_id);
+            ("unsubscribe", tool_id, Ok(()))
+        }
+        ClientMessage::Start { tool_id } => {
+            let result = start_tool(state, &tool_id).await;
+            ("start", tool_id, result)
+        }
+        ClientMessage::Stop { tool_id } => {
+            let result = state.process_manager.stop_tool(&tool_id).await;
+            ("stop", tool_id, result)
+        }
+    };
+
+    match result {
+        Ok(()) => to_text(&ServerMessage::Ack { action, tool_id }),
+        Err(err) => to_text(&ServerMessage::Error {
+            code: err.code(),
+            message: err.to_string(),
+        }),
+    }
+}
+
+async fn start_tool(state: &AppState, tool_id: &str) -> Result<(), McpError> {
+    let tool = state
+        .store
+        .get_tool(tool_id)
+        .await?
+        .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))?;
+    state.process_manager.start_tool(tool).await
+}
+
+fn to_text(message: &ServerMessage) -> Option<String> {
+    serde_json::to_string(message).ok()
+}
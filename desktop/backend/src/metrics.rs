@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use crate::mcp::{McpError, McpEvent, McpLogStream, McpToolStatus};
+use crate::state::AppState;
+
+/// Process-wide counters rendered by `GET /metrics` in the Prometheus text
+/// format. Gauges that can be derived from the store are computed per scrape.
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::default);
+
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+const TOOL_STATUSES: [McpToolStatus; 9] = [
+    McpToolStatus::Pending,
+    McpToolStatus::Stopped,
+    McpToolStatus::Starting,
+    McpToolStatus::Healthy,
+    McpToolStatus::Degraded,
+    McpToolStatus::Crashed,
+    McpToolStatus::Updating,
+    McpToolStatus::Error,
+    McpToolStatus::Orphaned,
+];
+
+#[derive(Default)]
+pub struct Metrics {
+    tool_crashes: AtomicU64,
+    conflicts: AtomicU64,
+    sync_success: AtomicU64,
+    sync_failure: AtomicU64,
+    log_lines_stdout: AtomicU64,
+    log_lines_stderr: AtomicU64,
+    log_lines_event: AtomicU64,
+    requests: Mutex<BTreeMap<(String, String), Histogram>>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(LATENCY_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+}
+
+impl Metrics {
+    fn record_event(&self, event: &McpEvent) {
+        match event {
+            McpEvent::ToolCrashed { .. } => {
+                self.tool_crashes.fetch_add(1, Ordering::Relaxed);
+            }
+            McpEvent::SourceSynced { success, .. } => self.record_sync(*success),
+            McpEvent::ConflictDetected { .. } => {
+                self.conflicts.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+    }
+
+    fn record_sync(&self, success: bool) {
+        let counter = if success {
+            &self.sync_success
+        } else {
+            &self.sync_failure
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_log_line(&self, stream: &McpLogStream) {
+        let counter = match stream {
+            McpLogStream::Stdout => &self.log_lines_stdout,
+            McpLogStream::Stderr => &self.log_lines_stderr,
+            McpLogStream::Event => &self.log_lines_event,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn record_request(&self, method: &str, path: &str, elapsed: Duration) {
+        let mut requests = self.requests.lock().unwrap_or_else(|err| err.into_inner());
+        requests
+            .entry((method.to_string(), path.to_string()))
+            .or_default()
+            .observe(elapsed.as_secs_f64());
+    }
+
+    fn render(&self, tools_by_status: &BTreeMap<&'static str, u64>, running: usize) -> String {
+        let mut out = String::new();
+
+        write_header(
+            &mut out,
+            "desktop_backend_tools",
+            "gauge",
+            "Tools by status.",
+        );
+        for (status, count) in tools_by_status {
+            let _ = writeln!(out, "desktop_backend_tools{{status=\"{status}\"}} {count}");
+        }
+
+        write_header(
+            &mut out,
+            "desktop_backend_running_processes",
+            "gauge",
+            "Child processes currently managed.",
+        );
+        let _ = writeln!(out, "desktop_backend_running_processes {running}");
+
+        write_header(
+            &mut out,
+            "desktop_backend_tool_crashes_total",
+            "counter",
+            "Tool processes that exited with a non-zero code.",
+        );
+        let _ = writeln!(
+            out,
+            "desktop_backend_tool_crashes_total {}",
+            self.tool_crashes.load(Ordering::Relaxed)
+        );
+
+        write_header(
+            &mut out,
+            "desktop_backend_conflicts_detected_total",
+            "counter",
+            "Tool writes that left a conflict to resolve.",
+        );
+        let _ = writeln!(
+            out,
+            "desktop_backend_conflicts_detected_total {}",
+            self.conflicts.load(Ordering::Relaxed)
+        );
+
+        write_header(
+            &mut out,
+            "desktop_backend_source_syncs_total",
+            "counter",
+            "Source syncs by result.",
+        );
+        for (result, counter) in [
+            ("success", &self.sync_success),
+            ("failure", &self.sync_failure),
+        ] {
+            let _ = writeln!(
+                out,
+                "desktop_backend_source_syncs_total{{result=\"{result}\"}} {}",
+                counter.load(Ordering::Relaxed)
+            );
+        }
+
+        write_header(
+            &mut out,
+            "desktop_backend_log_lines_total",
+            "counter",
+            "Log lines captured from tool processes.",
+        );
+        for (stream, counter) in [
+            ("stdout", &self.log_lines_stdout),
+            ("stderr", &self.log_lines_stderr),
+            ("event", &self.log_lines_event),
+        ] {
+            let _ = writeln!(
+                out,
+                "desktop_backend_log_lines_total{{stream=\"{stream}\"}} {}",
+                counter.load(Ordering::Relaxed)
+            );
+        }
+
+        write_header(
+            &mut out,
+            "desktop_backend_http_request_duration_seconds",
+            "histogram",
+            "HTTP request latency by method and route.",
+        );
+        let requests = self.requests.lock().unwrap_or_else(|err| err.into_inner());
+        for ((method, path), histogram) in requests.iter() {
+            let labels = format!("method=\"{method}\",path=\"{path}\"");
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+                let _ = writeln!(
+                    out,
+                    "desktop_backend_http_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {count}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "desktop_backend_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "desktop_backend_http_request_duration_seconds_sum{{{labels}}} {}",
+                histogram.sum
+            );
+            let _ = writeln!(
+                out,
+                "desktop_backend_http_request_duration_seconds_count{{{labels}}} {}",
+                histogram.count
+            );
+        }
+
+        out
+    }
+}
+
+/// Counts crashes, syncs and conflicts off the store's event channel for as
+/// long as the store lives.
+pub async fn record_events(mut events: broadcast::Receiver<McpEvent>) {
+    loop {
+        match events.recv().await {
+            Ok(event) => METRICS.record_event(&event),
+            Err(RecvError::Lagged(skipped)) => {
+                warn!("metrics recorder lagged, skipped {} events", skipped);
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+fn write_header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Records request latency, labelled by the matched route template so path
+/// parameters don't explode label cardinality.
+pub async fn track_latency(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let path = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let started = Instant::now();
+    let response = next.run(request).await;
+    METRICS.record_request(&method, &path, started.elapsed());
+    response
+}
+
+pub async fn metrics_handler(State(state): State<AppState>) -> Result<Response, McpError> {
+    let mut tools_by_status: BTreeMap<&'static str, u64> = TOOL_STATUSES
+        .iter()
+        .map(|status| (status.as_str(), 0))
+        .collect();
+    for tool in state.store.list_tools().await? {
+        *tools_by_status.entry(tool.status.as_str()).or_default() += 1;
+    }
+    let running = state.process_manager.running_count().await;
+    let body = METRICS.render(&tools_by_status, running);
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_cumulative_latency_buckets() {
+        let metrics = Metrics::default();
+        metrics.record_request("GET", "/mcp/tools", Duration::from_millis(20));
+        metrics.record_request("GET", "/mcp/tools", Duration::from_secs(10));
+        metrics.record_sync(true);
+        metrics.record_event(&McpEvent::ToolCrashed {
+            tool_id: "a".to_string(),
+            exit_code: 1,
+        });
+
+        let text = metrics.render(&BTreeMap::new(), 0);
+        let labels = "method=\"GET\",path=\"/mcp/tools\"";
+        assert!(text.contains(&format!(
+            "desktop_backend_http_request_duration_seconds_bucket{{{labels},le=\"0.025\"}} 1"
+        )));
+        assert!(text.contains(&format!(
+            "desktop_backend_http_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} 2"
+        )));
+        assert!(text.contains("desktop_backend_source_syncs_total{result=\"success\"} 1"));
+        assert!(text.contains("desktop_backend_tool_crashes_total 1"));
+    }
+}
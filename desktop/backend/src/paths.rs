@@ -0,0 +1,169 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use tracing::info;
+
+const APP_DIR: &str = "deeting";
+const PORTABLE_ENV: &str = "DEETING_PORTABLE";
+const PORTABLE_MARKER: &str = "portable";
+const PORTABLE_DATA_DIR: &str = "data";
+const DB_FILE: &str = "mcp.db";
+/// SQLite sidecar files that must move together with the database.
+const DB_SIDECARS: [&str; 2] = ["-wal", "-shm"];
+
+static PORTABLE_DIR: Lazy<Option<PathBuf>> = Lazy::new(|| {
+    let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let enabled = match std::env::var(PORTABLE_ENV) {
+        Ok(value) => matches!(value.trim(), "1" | "true" | "yes"),
+        Err(_) => exe_dir.join(PORTABLE_MARKER).is_file(),
+    };
+    enabled.then(|| exe_dir.join(PORTABLE_DATA_DIR))
+});
+
+/// The `data` directory beside the executable when running in portable mode:
+/// `DEETING_PORTABLE=1`, or a file named `portable` next to the executable
+/// (`DEETING_PORTABLE=0` overrides the file).
+pub fn portable_dir() -> Option<&'static Path> {
+    PORTABLE_DIR.as_deref()
+}
+
+/// Where Deeting keeps its files: the portable directory, otherwise the
+/// platform config directory (`~/.config/deeting` on Linux,
+/// `~/Library/Application Support/deeting` on macOS, `%APPDATA%\deeting` on
+/// Windows).
+pub fn config_dir() -> PathBuf {
+    if let Some(dir) = portable_dir() {
+        return dir.to_path_buf();
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_DIR))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn default_db_path() -> PathBuf {
+    config_dir().join(DB_FILE)
+}
+
+/// Default location of the local `mcp.json` source.
+pub fn default_local_config_path() -> PathBuf {
+    config_dir().join("mcp.json")
+}
+
+/// Expands a leading `~` to the user's home directory, with either
+/// separator so Windows paths like `~\data` work too.
+pub fn expand_path(path: &str) -> PathBuf {
+    let rest = if path == "~" {
+        Some("")
+    } else {
+        path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\"))
+    };
+    match (rest, dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// Where earlier versions put the database by default: under
+/// `$HOME/.config/deeting`, or the working directory when `HOME` was unset
+/// (the usual case on Windows).
+fn legacy_db_path() -> PathBuf {
+    match std::env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".config").join(APP_DIR).join(DB_FILE),
+        None => PathBuf::from(DB_FILE),
+    }
+}
+
+/// Moves a database left at the legacy default location to `target`, unless
+/// `target` already exists. Returns the path it was moved from. Portable
+/// installs never take over the user's database.
+pub fn migrate_legacy_db(target: &Path) -> io::Result<Option<PathBuf>> {
+    if portable_dir().is_some() {
+        return Ok(None);
+    }
+    migrate_db(&legacy_db_path(), target)
+}
+
+fn migrate_db(legacy: &Path, target: &Path) -> io::Result<Option<PathBuf>> {
+    if !legacy.is_file() || target.exists() || same_file(legacy, target) {
+        return Ok(None);
+    }
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    move_file(legacy, target)?;
+    for suffix in DB_SIDECARS {
+        let from = with_suffix(legacy, suffix);
+        if from.is_file() {
+            move_file(&from, &with_suffix(target, suffix))?;
+        }
+    }
+    info!(
+        from = %legacy.display(),
+        to = %target.display(),
+        "moved database to the platform config directory"
+    );
+    Ok(Some(legacy.to_path_buf()))
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// `rename`, falling back to copy and delete across filesystems.
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    std::fs::copy(from, to)?;
+    std::fs::remove_file(from)
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("deeting-paths-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn expands_home_with_either_separator() {
+        let Some(home) = dirs::home_dir() else { return };
+        assert_eq!(expand_path("~"), home);
+        assert_eq!(expand_path("~/a/b.db"), home.join("a/b.db"));
+        assert_eq!(expand_path("~\\a"), home.join("a"));
+        assert_eq!(expand_path("/tmp/~/x"), PathBuf::from("/tmp/~/x"));
+    }
+
+    #[test]
+    fn moves_legacy_db_with_sidecars() {
+        let dir = scratch_dir();
+        let legacy = dir.join("old").join(DB_FILE);
+        std::fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        std::fs::write(&legacy, b"db").unwrap();
+        std::fs::write(with_suffix(&legacy, "-wal"), b"wal").unwrap();
+        let target = dir.join("new").join(DB_FILE);
+
+        assert_eq!(migrate_db(&legacy, &target).unwrap(), Some(legacy.clone()));
+        assert_eq!(std::fs::read(&target).unwrap(), b"db");
+        assert_eq!(std::fs::read(with_suffix(&target, "-wal")).unwrap(), b"wal");
+        assert!(!legacy.exists());
+
+        std::fs::write(&legacy, b"stale").unwrap();
+        assert_eq!(migrate_db(&legacy, &target).unwrap(), None);
+        assert_eq!(std::fs::read(&target).unwrap(), b"db");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,126 @@
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderValue, Request};
+use axum::response::IntoResponse;
+use governor::middleware::NoOpMiddleware;
+use tower_governor::governor::GovernorConfigBuilder;
+use tower_governor::key_extractor::KeyExtractor;
+use tower_governor::{GovernorError, GovernorLayer};
+
+use crate::auth::VerifiedToken;
+use crate::mcp::McpError;
+
+const DEFAULT_REPLENISH_MS: u64 = 500;
+const DEFAULT_BURST: u32 = 10;
+
+/// Identifies a client for rate limiting: the token `require_token` verified
+/// (every local client shares 127.0.0.1), otherwise the peer IP. Unverified
+/// tokens are ignored so made-up tokens cannot each get a fresh bucket.
+#[derive(Debug, Clone, Copy)]
+pub struct ClientKeyExtractor;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    Token(String),
+    Peer(Option<IpAddr>),
+}
+
+impl KeyExtractor for ClientKeyExtractor {
+    type Key = ClientKey;
+
+    fn extract<T>(&self, req: &Request<T>) -> Result<Self::Key, GovernorError> {
+        if let Some(VerifiedToken(token)) = req.extensions().get::<VerifiedToken>() {
+            return Ok(ClientKey::Token(token.clone()));
+        }
+        let peer = req
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.ip());
+        Ok(ClientKey::Peer(peer))
+    }
+}
+
+pub type RateLimitLayer = GovernorLayer<ClientKeyExtractor, NoOpMiddleware>;
+
+/// Token bucket shared by the routes that spawn processes or hit remote
+/// sources (sync, import, start/stop). Tuned through
+/// `DESKTOP_RATE_LIMIT_REPLENISH_MS` (one request regained per interval,
+/// default 500) and `DESKTOP_RATE_LIMIT_BURST` (default 10).
+pub fn layer_from_env() -> RateLimitLayer {
+    let replenish_ms =
+        env_number("DESKTOP_RATE_LIMIT_REPLENISH_MS").unwrap_or(DEFAULT_REPLENISH_MS);
+    let burst = env_number("DESKTOP_RATE_LIMIT_BURST")
+        .and_then(|burst| u32::try_from(burst).ok())
+        .unwrap_or(DEFAULT_BURST);
+
+    let config = GovernorConfigBuilder::default()
+        .per_millisecond(replenish_ms.max(1))
+        .burst_size(burst.max(1))
+        .key_extractor(ClientKeyExtractor)
+        .error_handler(rate_limited_response)
+        .finish()
+        .expect("rate limit period and burst are non-zero");
+    let config = Arc::new(config);
+
+    // Keys are never evicted on their own; drop idle clients periodically.
+    let limiter = config.limiter().clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            limiter.retain_recent();
+        }
+    });
+
+    GovernorLayer { config }
+}
+
+fn rate_limited_response(error: GovernorError) -> axum::response::Response {
+    match error {
+        GovernorError::TooManyRequests { wait_time, .. } => {
+            let mut response =
+                McpError::RateLimited(format!("retry in {wait_time}s")).into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(wait_time));
+            response
+        }
+        other => McpError::RateLimited(other.to_string()).into_response(),
+    }
+}
+
+fn env_number(key: &str) -> Option<u64> {
+    std::env::var(key).ok()?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_on_verified_tokens_only() {
+        let request = |verified: bool| {
+            let mut request = Request::builder()
+                .header(header::AUTHORIZATION, "Bearer made-up")
+                .body(())
+                .unwrap();
+            if verified {
+                request
+                    .extensions_mut()
+                    .insert(VerifiedToken("made-up".to_string()));
+            }
+            request
+        };
+        assert_eq!(
+            ClientKeyExtractor.extract(&request(false)).unwrap(),
+            ClientKey::Peer(None)
+        );
+        assert_eq!(
+            ClientKeyExtractor.extract(&request(true)).unwrap(),
+            ClientKey::Token("made-up".to_string())
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use crate::mcp::sync;
+use crate::mcp::{
+    McpError, McpEvent, McpStore, McpTool, ProcessManager, SourceProvider, SourceProviders,
+};
+
+/// The store and process manager wired together the way desktop-backend
+/// runs them, for applications that embed the registry.
+#[derive(Clone)]
+pub struct Registry {
+    store: Arc<McpStore>,
+    process_manager: ProcessManager,
+    providers: Arc<SourceProviders>,
+}
+
+impl Registry {
+    /// Opens the database at `database_url` (e.g. `sqlite:///path/mcp.db?mode=rwc`
+    /// or `sqlite::memory:`), migrates it and seeds the local source.
+    pub async fn open(database_url: &str) -> Result<Self, McpError> {
+        let store = Arc::new(McpStore::new(database_url).await?);
+        store.init().await?;
+        store.ensure_local_source().await?;
+        Ok(Self {
+            process_manager: ProcessManager::new(store.clone()),
+            providers: Default::default(),
+            store,
+        })
+    }
+
+    /// Sources, tools, logs and everything else the HTTP API reads.
+    pub fn store(&self) -> &Arc<McpStore> {
+        &self.store
+    }
+
+    pub fn process_manager(&self) -> &ProcessManager {
+        &self.process_manager
+    }
+
+    /// Adds a provider for [`Self::sync_source`], e.g. for an internal
+    /// catalog. It is tried before the built-in local and HTTP providers.
+    pub fn register_source_provider(&mut self, provider: Arc<dyn SourceProvider>) {
+        Arc::make_mut(&mut self.providers).register(provider);
+    }
+
+    /// Tool status changes, source syncs and conflicts as they happen.
+    pub fn subscribe(&self) -> broadcast::Receiver<McpEvent> {
+        self.store.subscribe_events()
+    }
+
+    /// Syncs a local or remote source, recording its status like
+    /// `POST /mcp/sources/{id}/sync` does.
+    pub async fn sync_source(
+        &self,
+        source_id: &str,
+        auth_token: Option<String>,
+    ) -> Result<Vec<McpTool>, McpError> {
+        let source = self
+            .store
+            .get_source(source_id)
+            .await?
+            .ok_or_else(|| McpError::SourceNotFound(source_id.to_string()))?;
+        let sync = sync::sync_source(&self.store, &self.providers, source, auth_token);
+        sync::track_sync(&self.store, source_id, sync).await
+    }
+
+    pub async fn start_tool(&self, tool_id: &str) -> Result<McpTool, McpError> {
+        let tool = self.tool(tool_id).await?;
+        self.process_manager.start_tool(tool).await?;
+        self.tool(tool_id).await
+    }
+
+    pub async fn stop_tool(&self, tool_id: &str) -> Result<McpTool, McpError> {
+        self.process_manager.stop_tool(tool_id).await?;
+        self.tool(tool_id).await
+    }
+
+    /// Stops every running tool, killing those still up after `grace`.
+    pub async fn shutdown(&self, grace: Duration) {
+        self.process_manager.shutdown_all(grace).await;
+    }
+
+    async fn tool(&self, tool_id: &str) -> Result<McpTool, McpError> {
+        self.store
+            .get_tool(tool_id)
+            .await?
+            .ok_or_else(|| McpError::ToolNotFound(tool_id.to_string()))
+    }
+}
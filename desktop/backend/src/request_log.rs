@@ -0,0 +1,83 @@
+use std::time::Instant;
+
+use axum::extract::Request;
+use axum::http::{HeaderName, HeaderValue};
+use axum::middleware::Next;
+use axum::response::Response;
+use tracing::field::Empty;
+use tracing::{info, info_span, warn, Instrument};
+
+use crate::telemetry;
+
+pub static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Id of the request currently being handled, if any. `McpError` responses
+/// include it so client-reported failures can be matched with server logs.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Assigns every request an id (reusing a client supplied `x-request-id`),
+/// echoes it back as a response header and logs one structured line per
+/// request once the response head is ready. Handler logs run inside a
+/// `request` span carrying the id, so JSON output can correlate them; with
+/// trace export on, that span is the root of the request's trace (or a child
+/// of the caller's, given a `traceparent` header).
+pub async fn track_request(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        request
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let started = Instant::now();
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        otel.name = %format!("{method} {path}"),
+        otel.kind = "server",
+        otel.status_code = Empty,
+        http.request.method = %method,
+        url.path = %path,
+        http.response.status_code = Empty,
+    );
+    telemetry::set_remote_parent(&span, request.headers());
+    let mut response = REQUEST_ID
+        .scope(
+            request_id.clone(),
+            next.run(request).instrument(span.clone()),
+        )
+        .await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let status = response.status().as_u16();
+    span.record("http.response.status_code", status);
+    if response.status().is_server_error() {
+        span.record("otel.status_code", "ERROR");
+    }
+
+    if response.status().is_server_error() {
+        warn!(request_id = %request_id, method = %method, path = %path, status, latency_ms, "request failed");
+    } else {
+        info!(request_id = %request_id, method = %method, path = %path, status, latency_ms, "request");
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
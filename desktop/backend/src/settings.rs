@@ -0,0 +1,273 @@
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use mcp_core::ansi::AnsiMode;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tokio::sync::watch;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use crate::mcp::log_dedup::DEFAULT_LOG_DEDUP_WINDOW;
+use crate::mcp::process::{DEFAULT_LOG_BUFFER_SIZE, DEFAULT_LOG_MEMORY_MB};
+use crate::state::AppState;
+
+const CLOUD_BASE_URL: &str = "DESKTOP_CLOUD_BASE_URL";
+const LOG_BUFFER_SIZE: &str = "DESKTOP_LOG_BUFFER_SIZE";
+const LOG_ANSI: &str = "DESKTOP_LOG_ANSI";
+const LOG_MEMORY_MB: &str = "DESKTOP_LOG_MEMORY_MB";
+const LOG_DEDUP_MS: &str = "DESKTOP_LOG_DEDUP_MS";
+const ENV_STRICT: &str = "DESKTOP_ENV_STRICT";
+const SYNC_INTERVAL_SECS: &str = "DESKTOP_SYNC_INTERVAL_SECS";
+
+/// Variables re-read by a reload. Everything else (port, database, TLS, CORS,
+/// rate limits, tokens) is fixed for the lifetime of the process.
+const RELOADABLE_KEYS: [&str; 7] = [
+    CLOUD_BASE_URL,
+    LOG_BUFFER_SIZE,
+    LOG_ANSI,
+    LOG_MEMORY_MB,
+    LOG_DEDUP_MS,
+    ENV_STRICT,
+    SYNC_INTERVAL_SECS,
+];
+
+/// Reloadable keys that were set by the parent process rather than `.env`.
+/// They win over `.env` at startup (`dotenv` does not override), so a reload
+/// leaves them alone to keep the same precedence.
+static INHERITED_KEYS: Lazy<HashSet<&'static str>> = Lazy::new(|| {
+    RELOADABLE_KEYS
+        .into_iter()
+        .filter(|key| std::env::var_os(key).is_some())
+        .collect()
+});
+
+/// Env-derived settings that can change while tools keep running.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct RuntimeSettings {
+    /// `DESKTOP_CLOUD_BASE_URL`: used when the first cloud source is created.
+    pub cloud_base_url: Option<String>,
+    /// `DESKTOP_LOG_BUFFER_SIZE`: lines kept per tool (default 1000).
+    pub log_buffer_size: usize,
+    /// `DESKTOP_LOG_ANSI`: `keep`, `strip` (default) or `hint` escape
+    /// sequences in tool output.
+    pub log_ansi: AnsiMode,
+    /// `DESKTOP_LOG_MEMORY_MB`: memory all tools' log buffers share
+    /// (default 64).
+    pub log_memory_mb: usize,
+    /// `DESKTOP_LOG_DEDUP_MS`: window in which identical consecutive lines
+    /// are coalesced (default 1000, 0 disables).
+    pub log_dedup_ms: u64,
+    /// `DESKTOP_ENV_STRICT`: refuse to start a tool whose command, args or
+    /// env reference an unset `${VAR}` (default off).
+    pub env_strict: bool,
+    /// `DESKTOP_SYNC_INTERVAL_SECS`: how often every source is synced in the
+    /// background (default 0, off).
+    pub sync_interval_secs: u64,
+}
+
+impl RuntimeSettings {
+    pub fn from_env() -> Self {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    /// Settings from `lookup`, which returns a variable's value if it is set.
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let cloud_base_url = lookup(CLOUD_BASE_URL)
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty());
+        let log_buffer_size = positive(&lookup, LOG_BUFFER_SIZE, DEFAULT_LOG_BUFFER_SIZE);
+        let log_memory_mb = positive(&lookup, LOG_MEMORY_MB, DEFAULT_LOG_MEMORY_MB);
+        let log_dedup_ms = number(
+            &lookup,
+            LOG_DEDUP_MS,
+            DEFAULT_LOG_DEDUP_WINDOW.as_millis() as u64,
+        );
+        let log_ansi = match lookup(LOG_ANSI) {
+            Some(value) => value.parse().unwrap_or_else(|_| {
+                warn!("ignoring invalid {LOG_ANSI}={value:?}");
+                AnsiMode::default()
+            }),
+            None => AnsiMode::default(),
+        };
+        let env_strict = lookup(ENV_STRICT)
+            .map(|value| matches!(value.trim(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        let sync_interval_secs = number(&lookup, SYNC_INTERVAL_SECS, 0);
+        Self {
+            cloud_base_url,
+            log_buffer_size,
+            log_ansi,
+            log_memory_mb,
+            log_dedup_ms,
+            env_strict,
+            sync_interval_secs,
+        }
+    }
+
+    fn changed_keys(&self, other: &Self) -> Vec<String> {
+        let mut changed = Vec::new();
+        if self.cloud_base_url != other.cloud_base_url {
+            changed.push(CLOUD_BASE_URL.to_string());
+        }
+        if self.log_buffer_size != other.log_buffer_size {
+            changed.push(LOG_BUFFER_SIZE.to_string());
+        }
+        if self.log_ansi != other.log_ansi {
+            changed.push(LOG_ANSI.to_string());
+        }
+        if self.log_memory_mb != other.log_memory_mb {
+            changed.push(LOG_MEMORY_MB.to_string());
+        }
+        if self.log_dedup_ms != other.log_dedup_ms {
+            changed.push(LOG_DEDUP_MS.to_string());
+        }
+        if self.env_strict != other.env_strict {
+            changed.push(ENV_STRICT.to_string());
+        }
+        if self.sync_interval_secs != other.sync_interval_secs {
+            changed.push(SYNC_INTERVAL_SECS.to_string());
+        }
+        changed
+    }
+}
+
+fn positive(lookup: impl Fn(&str) -> Option<String>, key: &str, default: usize) -> usize {
+    match lookup(key) {
+        Some(value) => match value.trim().parse::<usize>() {
+            Ok(size) if size > 0 => size,
+            _ => {
+                warn!("ignoring invalid {key}={value:?}");
+                default
+            }
+        },
+        None => default,
+    }
+}
+
+fn number(lookup: impl Fn(&str) -> Option<String>, key: &str, default: u64) -> u64 {
+    match lookup(key) {
+        Some(value) => value.trim().parse().unwrap_or_else(|_| {
+            warn!("ignoring invalid {key}={value:?}");
+            default
+        }),
+        None => default,
+    }
+}
+
+/// The current settings; tasks that must react to a reload, like the
+/// background sync, [`subscribe`](Self::subscribe) to changes.
+#[derive(Clone)]
+pub struct SharedSettings(watch::Sender<RuntimeSettings>);
+
+impl SharedSettings {
+    pub fn new(settings: RuntimeSettings) -> Self {
+        Self(watch::Sender::new(settings))
+    }
+
+    pub fn current(&self) -> RuntimeSettings {
+        self.0.borrow().clone()
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<RuntimeSettings> {
+        self.0.subscribe()
+    }
+
+    fn replace(&self, settings: RuntimeSettings) -> RuntimeSettings {
+        self.0.send_replace(settings)
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReloadResponse {
+    pub settings: RuntimeSettings,
+    /// Env keys whose effective value changed with this reload.
+    pub changed: Vec<String>,
+}
+
+/// Must run before `.env` is loaded so inherited values can be told apart.
+pub fn capture_inherited_env() {
+    Lazy::force(&INHERITED_KEYS);
+}
+
+/// Re-reads `.env`, recomputes the settings and applies them to the running
+/// state. Managed processes are not restarted.
+pub async fn reload(state: &AppState) -> ReloadResponse {
+    let settings = RuntimeSettings::from_lookup(reloaded_env(dotenv_values()));
+    state
+        .process_manager
+        .set_log_buffer_size(settings.log_buffer_size)
+        .await;
+    state.process_manager.set_log_ansi(settings.log_ansi);
+    state
+        .process_manager
+        .set_log_memory_budget(settings.log_memory_mb << 20)
+        .await;
+    state
+        .process_manager
+        .set_log_dedup_window(Duration::from_millis(settings.log_dedup_ms));
+    state.process_manager.set_env_strict(settings.env_strict);
+    let previous = state.settings.replace(settings.clone());
+    let changed = previous.changed_keys(&settings);
+    info!("settings reloaded; changed: {:?}", changed);
+    ReloadResponse { settings, changed }
+}
+
+/// Reloads on every SIGHUP for the life of the process.
+#[cfg(unix)]
+pub async fn reload_on_sighup(state: AppState) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            warn!("failed to install SIGHUP handler: {}", err);
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        info!("SIGHUP received; reloading settings");
+        reload(&state).await;
+    }
+}
+
+fn dotenv_values() -> HashMap<String, String> {
+    match dotenvy::dotenv_iter() {
+        Ok(iter) => iter.filter_map(Result::ok).collect(),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Looks keys up the way startup saw them: the parent process's value if it
+/// set one, else `.env` as it is now. The process environment is only read,
+/// never written, so no other thread can observe it mid-update.
+fn reloaded_env(from_file: HashMap<String, String>) -> impl Fn(&str) -> Option<String> {
+    move |key| {
+        if INHERITED_KEYS.contains(key) {
+            std::env::var(key).ok()
+        } else {
+            from_file.get(key).cloned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_settings_from_a_lookup() {
+        let values = HashMap::from([
+            (CLOUD_BASE_URL.to_string(), "https://cloud.example/".to_string()),
+            (LOG_BUFFER_SIZE.to_string(), "0".to_string()),
+            (SYNC_INTERVAL_SECS.to_string(), "300".to_string()),
+        ]);
+        let settings = RuntimeSettings::from_lookup(reloaded_env(values));
+        assert_eq!(settings.cloud_base_url.as_deref(), Some("https://cloud.example"));
+        assert_eq!(settings.log_buffer_size, DEFAULT_LOG_BUFFER_SIZE);
+        assert_eq!(settings.sync_interval_secs, 300);
+
+        let defaults = RuntimeSettings::from_lookup(|_| None);
+        assert_eq!(settings.changed_keys(&defaults), [CLOUD_BASE_URL, SYNC_INTERVAL_SECS]);
+    }
+}
@@ -1,10 +1,21 @@
 use std::sync::Arc;
 
-use crate::mcp::{McpStore, ProcessManager};
+use crate::auth::AuthConfig;
+use crate::health::Readiness;
+use crate::mcp::{McpStore, ProcessManager, SourceProviders};
+use crate::settings::SharedSettings;
 
 #[derive(Clone)]
 pub struct AppState {
     pub version: &'static str,
     pub store: Arc<McpStore>,
     pub process_manager: ProcessManager,
+    /// Fetch source configs for `POST /mcp/sources/{id}/sync`.
+    pub providers: Arc<SourceProviders>,
+    pub auth: AuthConfig,
+    /// Shared client for upstream calls (chat providers).
+    pub http: reqwest::Client,
+    pub readiness: Readiness,
+    /// Reloadable env settings; see `POST /admin/reload`.
+    pub settings: SharedSettings,
 }
@@ -0,0 +1,82 @@
+use axum::http::HeaderMap;
+use opentelemetry::global;
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{ExporterBuildError, SpanExporter};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+const ENDPOINT: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+const TRACES_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT";
+const SERVICE_NAME: &str = "OTEL_SERVICE_NAME";
+const DEFAULT_SERVICE_NAME: &str = "desktop-backend";
+
+/// Keeps the span exporter alive; dropping it at shutdown flushes the spans
+/// still queued for export.
+pub struct Telemetry {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Err(err) = self.provider.shutdown() {
+            tracing::warn!("failed to flush traces: {err}");
+        }
+    }
+}
+
+/// A layer exporting spans over OTLP/HTTP, when `OTEL_EXPORTER_OTLP_ENDPOINT`
+/// or `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` is set. The exporter takes its
+/// endpoint, headers and timeout from the standard `OTEL_EXPORTER_OTLP_*`
+/// variables; `OTEL_SERVICE_NAME` defaults to `desktop-backend`.
+#[allow(clippy::type_complexity)]
+pub fn layer<S>() -> Result<Option<(impl Layer<S>, Telemetry)>, ExporterBuildError>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let configured = [ENDPOINT, TRACES_ENDPOINT]
+        .iter()
+        .any(|key| std::env::var(key).is_ok_and(|value| !value.trim().is_empty()));
+    if !configured {
+        return Ok(None);
+    }
+
+    let exporter = SpanExporter::builder().with_http().build()?;
+    let mut resource = Resource::builder();
+    if std::env::var_os(SERVICE_NAME).is_none() {
+        resource = resource.with_service_name(DEFAULT_SERVICE_NAME);
+    }
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource.build())
+        .build();
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let tracer = provider.tracer(DEFAULT_SERVICE_NAME);
+    let layer = tracing_opentelemetry::layer().with_tracer(tracer);
+    Ok(Some((layer, Telemetry { provider })))
+}
+
+/// Continues the caller's trace when the request carries a W3C
+/// `traceparent` header. Does nothing while export is off.
+pub fn set_remote_parent(span: &tracing::Span, headers: &HeaderMap) {
+    let parent =
+        global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+    let _ = span.set_parent(parent);
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
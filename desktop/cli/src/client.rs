@@ -0,0 +1,68 @@
+use anyhow::{anyhow, Context};
+use reqwest::{Method, RequestBuilder, Response};
+use serde_json::Value;
+
+/// Calls the desktop-backend `/api/v1` API, with the bearer token when one
+/// is configured.
+pub struct ApiClient {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl ApiClient {
+    pub fn new(base_url: &str, token: Option<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: format!("{}/api/v1", base_url.trim_end_matches('/')),
+            token,
+        }
+    }
+
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self
+            .http
+            .request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    pub async fn get(&self, path: &str) -> anyhow::Result<Value> {
+        self.json(self.request(Method::GET, path)).await
+    }
+
+    pub async fn post(&self, path: &str) -> anyhow::Result<Value> {
+        self.json(
+            self.request(Method::POST, path)
+                .json(&serde_json::json!({})),
+        )
+        .await
+    }
+
+    pub async fn json(&self, request: RequestBuilder) -> anyhow::Result<Value> {
+        let response = self.send(request).await?;
+        if response.status() == reqwest::StatusCode::NO_CONTENT {
+            return Ok(Value::Null);
+        }
+        response.json().await.context("invalid response body")
+    }
+
+    /// Sends `request` and turns the backend's error envelope into an error.
+    pub async fn send(&self, request: RequestBuilder) -> anyhow::Result<Response> {
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("cannot reach {}", self.base_url))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        Err(match (body["code"].as_str(), body["message"].as_str()) {
+            (Some(code), Some(message)) => anyhow!("{code}: {message}"),
+            _ => anyhow!("request failed: {status}"),
+        })
+    }
+}
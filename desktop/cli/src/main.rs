@@ -0,0 +1,240 @@
+mod client;
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Parser, Subcommand};
+use futures_util::StreamExt;
+use reqwest::Method;
+use serde_json::Value;
+
+use crate::client::ApiClient;
+
+/// Manages a running desktop-backend from the command line.
+#[derive(Parser)]
+#[command(name = "deeting", version)]
+struct Cli {
+    /// Backend address.
+    #[arg(long, env = "DEETING_URL", default_value = "http://127.0.0.1:3000")]
+    url: String,
+    /// API token, when the backend has `DESKTOP_API_TOKENS` set.
+    #[arg(long, env = "DEETING_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// Print the raw JSON responses instead of tables.
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(subcommand)]
+    Tools(ToolsCommand),
+    #[command(subcommand)]
+    Sources(SourcesCommand),
+    #[command(subcommand)]
+    Assistants(AssistantsCommand),
+}
+
+#[derive(Subcommand)]
+enum ToolsCommand {
+    List {
+        /// Only tools with this status, e.g. `healthy` or `crashed`.
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long)]
+        source: Option<String>,
+    },
+    Start {
+        id: String,
+    },
+    Stop {
+        id: String,
+    },
+    Logs {
+        id: String,
+        /// Newest lines to print.
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+        /// Lowest level to print: `info`, `warn` or `error`.
+        #[arg(long)]
+        level: Option<String>,
+        /// Keep printing new lines until interrupted.
+        #[arg(short, long)]
+        follow: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SourcesCommand {
+    List,
+    Sync { id: String },
+}
+
+#[derive(Subcommand)]
+enum AssistantsCommand {
+    /// Writes every assistant with its message history as JSON.
+    Export {
+        /// File to write; stdout when omitted.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let client = ApiClient::new(&cli.url, cli.token);
+    match cli.command {
+        Command::Tools(command) => tools(&client, command, cli.json).await,
+        Command::Sources(command) => sources(&client, command, cli.json).await,
+        Command::Assistants(command) => assistants(&client, command).await,
+    }
+}
+
+async fn tools(client: &ApiClient, command: ToolsCommand, json: bool) -> anyhow::Result<()> {
+    match command {
+        ToolsCommand::List { status, source } => {
+            let query: Vec<(&str, String)> = [("status", status), ("source_id", source)]
+                .into_iter()
+                .filter_map(|(key, value)| value.map(|value| (key, value)))
+                .collect();
+            let request = client.request(Method::GET, "/mcp/tools").query(&query);
+            let response = client.json(request).await?;
+            print_tools(&response["tools"], json);
+        }
+        ToolsCommand::Start { id } => {
+            let tool = client.post(&format!("/mcp/tools/{id}/start")).await?;
+            print_tools(&Value::Array(vec![tool]), json);
+        }
+        ToolsCommand::Stop { id } => {
+            let tool = client.post(&format!("/mcp/tools/{id}/stop")).await?;
+            print_tools(&Value::Array(vec![tool]), json);
+        }
+        ToolsCommand::Logs {
+            id,
+            limit,
+            level,
+            follow,
+        } => {
+            let mut query = vec![("limit", limit.to_string())];
+            if let Some(level) = &level {
+                query.push(("level", level.clone()));
+            }
+            let request = client
+                .request(Method::GET, &format!("/mcp/tools/{id}/logs"))
+                .query(&query);
+            let response = client.json(request).await?;
+            for entry in response["entries"].as_array().into_iter().flatten() {
+                print_log(entry, json);
+            }
+            if follow {
+                follow_logs(client, &id, json).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Prints `data:` lines of the tool's log SSE stream as they arrive.
+async fn follow_logs(client: &ApiClient, id: &str, json: bool) -> anyhow::Result<()> {
+    let request = client.request(Method::GET, &format!("/mcp/tools/{id}/logs/stream"));
+    let mut stream = client.send(request).await?.bytes_stream();
+    let mut buffer = String::new();
+    while let Some(chunk) = stream.next().await {
+        buffer.push_str(&String::from_utf8_lossy(
+            &chunk.context("log stream failed")?,
+        ));
+        while let Some(end) = buffer.find('\n') {
+            let line: String = buffer.drain(..=end).collect();
+            if let Some(data) = line.trim_end().strip_prefix("data:") {
+                if let Ok(entry) = serde_json::from_str::<Value>(data.trim_start()) {
+                    print_log(&entry, json);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn sources(client: &ApiClient, command: SourcesCommand, json: bool) -> anyhow::Result<()> {
+    match command {
+        SourcesCommand::List => {
+            let response = client.get("/mcp/sources").await?;
+            if json {
+                println!("{}", response["sources"]);
+                return Ok(());
+            }
+            for source in response["sources"].as_array().into_iter().flatten() {
+                println!(
+                    "{:<38} {:<10} {:<9} {}",
+                    text(&source["id"]),
+                    text(&source["source_type"]),
+                    text(&source["status"]),
+                    text(&source["name"]),
+                );
+            }
+        }
+        SourcesCommand::Sync { id } => {
+            let response = client.post(&format!("/mcp/sources/{id}/sync")).await?;
+            print_tools(&response["tools"], json);
+        }
+    }
+    Ok(())
+}
+
+async fn assistants(client: &ApiClient, command: AssistantsCommand) -> anyhow::Result<()> {
+    match command {
+        AssistantsCommand::Export { output } => {
+            let response = client.get("/assistants").await?;
+            let mut exported = Vec::new();
+            for assistant in response["assistants"].as_array().into_iter().flatten() {
+                let id = text(&assistant["id"]);
+                let messages = client.get(&format!("/assistants/{id}/messages")).await?;
+                let mut assistant = assistant.clone();
+                assistant["messages"] = messages["messages"].clone();
+                exported.push(assistant);
+            }
+            let body = serde_json::to_string_pretty(&exported)?;
+            match output {
+                Some(path) => std::fs::write(&path, body)
+                    .with_context(|| format!("cannot write {}", path.display()))?,
+                None => println!("{body}"),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_tools(tools: &Value, json: bool) {
+    if json {
+        println!("{tools}");
+        return;
+    }
+    for tool in tools.as_array().into_iter().flatten() {
+        println!(
+            "{:<38} {:<9} {}",
+            text(&tool["id"]),
+            text(&tool["status"]),
+            text(&tool["name"]),
+        );
+    }
+}
+
+fn print_log(entry: &Value, json: bool) {
+    if json {
+        println!("{entry}");
+        return;
+    }
+    println!(
+        "{} [{}] {}",
+        text(&entry["timestamp"]),
+        text(&entry["stream"]),
+        text(&entry["message"]),
+    );
+}
+
+fn text(value: &Value) -> &str {
+    value.as_str().unwrap_or("-")
+}
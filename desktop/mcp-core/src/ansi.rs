@@ -0,0 +1,127 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+const ESC: char = '\u{1b}';
+const BEL: char = '\u{7}';
+
+/// What happens to ANSI escape sequences (colors, cursor moves) in tool
+/// output before it is buffered and broadcast.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum AnsiMode {
+    /// Lines are kept as the tool wrote them.
+    Keep,
+    /// Escape sequences are removed.
+    #[default]
+    Strip,
+    /// Escape sequences are removed; a line colored red or yellow gets an
+    /// `[error]` or `[warn]` prefix instead.
+    Hint,
+}
+
+impl FromStr for AnsiMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "keep" => Ok(Self::Keep),
+            "strip" => Ok(Self::Strip),
+            "hint" => Ok(Self::Hint),
+            other => Err(format!("unknown ANSI mode {other:?}")),
+        }
+    }
+}
+
+impl AnsiMode {
+    pub fn apply(self, line: String) -> String {
+        if self == Self::Keep || !line.contains(ESC) {
+            return line;
+        }
+        let (text, severity) = strip(&line);
+        match (self, severity) {
+            (Self::Hint, Some(severity)) => format!("[{severity}] {text}"),
+            _ => text,
+        }
+    }
+}
+
+/// Removes CSI (`ESC [ ... final`), OSC (`ESC ] ... BEL` or `ESC \`) and
+/// two-byte escapes from `line`. Also returns the most severe color an SGR
+/// sequence set along the way: red as `error`, yellow as `warn`.
+fn strip(line: &str) -> (String, Option<&'static str>) {
+    let mut text = String::with_capacity(line.len());
+    let mut severity = None;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                let mut params = String::new();
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        if c == 'm' {
+                            severity = severity.max(sgr_severity(&params));
+                        }
+                        break;
+                    }
+                    params.push(c);
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == BEL {
+                        break;
+                    }
+                    if c == ESC && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    (text, severity.map(|(_, name)| name))
+}
+
+fn sgr_severity(params: &str) -> Option<(u8, &'static str)> {
+    params
+        .split(';')
+        .filter_map(|code| match code {
+            "31" | "91" => Some((2, "error")),
+            "33" | "93" => Some((1, "warn")),
+            _ => None,
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_color_and_cursor_sequences() {
+        let line = "\u{1b}[32m✔\u{1b}[39m ready \u{1b}]0;title\u{7}on \u{1b}[1;36m3000\u{1b}[0m\u{1b}[K";
+        assert_eq!(AnsiMode::Strip.apply(line.to_string()), "✔ ready on 3000");
+        assert_eq!(AnsiMode::Keep.apply(line.to_string()), line);
+        assert_eq!(AnsiMode::Hint.apply(line.to_string()), "✔ ready on 3000");
+    }
+
+    #[test]
+    fn hints_severity_from_red_and_yellow() {
+        let error = "\u{1b}[33mslow\u{1b}[0m then \u{1b}[1;31mfailed\u{1b}[0m";
+        assert_eq!(AnsiMode::Hint.apply(error.to_string()), "[error] slow then failed");
+        let warn = "\u{1b}[93mdeprecated\u{1b}[0m";
+        assert_eq!(AnsiMode::Hint.apply(warn.to_string()), "[warn] deprecated");
+        assert_eq!(AnsiMode::Strip.apply(warn.to_string()), "deprecated");
+        assert_eq!("HINT".parse::<AnsiMode>(), Ok(AnsiMode::Hint));
+        assert!("colors".parse::<AnsiMode>().is_err());
+    }
+}
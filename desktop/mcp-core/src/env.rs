@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "utoipa")]
+use utoipa::ToSchema;
+
+const SECRET_KEY_MARKERS: [&str; 8] = [
+    "KEY",
+    "TOKEN",
+    "SECRET",
+    "PASSWORD",
+    "PASSWD",
+    "CREDENTIAL",
+    "AUTH",
+    "PRIVATE",
+];
+
+/// Heuristic for env keys whose values must never leave the machine.
+pub fn is_secret_env_key(key: &str) -> bool {
+    let upper = key.to_ascii_uppercase();
+    SECRET_KEY_MARKERS.iter().any(|marker| upper.contains(marker))
+}
+
+/// One `env_config` entry of a tool's config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvEntry {
+    pub key: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub required: bool,
+    /// Falls back to [`is_secret_env_key`] when the manifest omits it.
+    pub secret: bool,
+    pub default: Option<String>,
+}
+
+/// Reads the `env_config` entries stored in a tool's config. Entries without
+/// a key are skipped.
+pub fn env_entries(config_json: &str) -> Vec<EnvEntry> {
+    let Ok(config) = serde_json::from_str::<Value>(config_json) else {
+        return Vec::new();
+    };
+    let Some(items) = config.get("env_config").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+
+    items
+        .iter()
+        .filter_map(|item| {
+            let key = item.get("key").and_then(Value::as_str)?.trim();
+            if key.is_empty() {
+                return None;
+            }
+            let text = |field: &str| item.get(field).and_then(Value::as_str).map(str::to_string);
+            Some(EnvEntry {
+                key: key.to_string(),
+                label: text("label"),
+                description: text("description"),
+                required: item
+                    .get("required")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+                secret: item
+                    .get("secret")
+                    .and_then(Value::as_bool)
+                    .unwrap_or_else(|| is_secret_env_key(key)),
+                default: text("default"),
+            })
+        })
+        .collect()
+}
+
+/// An `env_config` entry together with whether it currently has a value;
+/// what both apps return for a tool's env requirements.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "utoipa", derive(ToSchema))]
+pub struct EnvRequirement {
+    pub key: String,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub required: bool,
+    pub secret: bool,
+    pub default: Option<String>,
+    pub is_set: bool,
+}
+
+/// Why [`validate_env`] rejected an env map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvError {
+    /// A key or value that cannot be passed to a process.
+    Invalid(String),
+    /// Required keys without a non-blank value.
+    Missing(Vec<String>),
+}
+
+/// [`env_entries`] of `config_json`, each marked with whether `env` has a
+/// value for it.
+pub fn env_requirements(
+    config_json: &str,
+    env: Option<&HashMap<String, String>>,
+) -> Vec<EnvRequirement> {
+    env_entries(config_json)
+        .into_iter()
+        .map(|entry| EnvRequirement {
+            is_set: is_set(env, &entry.key),
+            key: entry.key,
+            label: entry.label,
+            description: entry.description,
+            required: entry.required,
+            secret: entry.secret,
+            default: entry.default,
+        })
+        .collect()
+}
+
+/// Keys of required entries that have no value yet.
+pub fn missing_required(requirements: &[EnvRequirement]) -> Vec<String> {
+    requirements
+        .iter()
+        .filter(|requirement| requirement.required && !requirement.is_set)
+        .map(|requirement| requirement.key.clone())
+        .collect()
+}
+
+/// Checks an env map before it replaces a tool's env: names must be usable as
+/// process env keys and every required entry must be filled in.
+pub fn validate_env(
+    requirements: &[EnvRequirement],
+    env: &HashMap<String, String>,
+) -> Result<(), EnvError> {
+    check_env(env).map_err(EnvError::Invalid)?;
+    let missing: Vec<String> = requirements
+        .iter()
+        .filter(|requirement| requirement.required && !is_set(Some(env), &requirement.key))
+        .map(|requirement| requirement.key.clone())
+        .collect();
+    if !missing.is_empty() {
+        return Err(EnvError::Missing(missing));
+    }
+    Ok(())
+}
+
+/// Whether `key` has a non-blank value in `env`.
+pub fn is_set(env: Option<&HashMap<String, String>>, key: &str) -> bool {
+    env.and_then(|env| env.get(key))
+        .map(|value| !value.trim().is_empty())
+        .unwrap_or(false)
+}
+
+/// Checks that every name in `env` is usable as a process env key and no
+/// value contains a NUL byte.
+pub fn check_env(env: &HashMap<String, String>) -> Result<(), String> {
+    for (key, value) in env {
+        if key.is_empty() || key.contains('=') || key.contains('\0') {
+            return Err(format!("invalid env key: {key:?}"));
+        }
+        if value.contains('\0') {
+            return Err(format!("env value for {key} contains a NUL byte"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_entries_and_checks_env() {
+        let config = r#"{"env_config": [
+            {"key": "API_KEY", "required": true},
+            {"key": "REGION"},
+            {"key": " "}
+        ]}"#;
+        let entries = env_entries(config);
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].required && entries[0].secret);
+        assert!(!entries[1].required && !entries[1].secret);
+        assert!(env_entries("not json").is_empty());
+
+        let mut env = HashMap::new();
+        env.insert("REGION".to_string(), " ".to_string());
+        assert!(!is_set(Some(&env), "REGION"));
+        assert!(check_env(&env).is_ok());
+        env.insert("BAD=KEY".to_string(), "x".to_string());
+        assert!(check_env(&env).is_err());
+    }
+
+    #[test]
+    fn validates_env_against_config_requirements() {
+        let config = r#"{"env_config": [
+            {"key": "API_KEY", "required": true},
+            {"key": "REGION"}
+        ]}"#;
+        let mut env = HashMap::new();
+        env.insert("REGION".to_string(), "eu".to_string());
+        let requirements = env_requirements(config, Some(&env));
+        assert!(requirements[1].is_set);
+        assert_eq!(missing_required(&requirements), ["API_KEY"]);
+        assert_eq!(
+            validate_env(&requirements, &env),
+            Err(EnvError::Missing(vec!["API_KEY".to_string()]))
+        );
+
+        env.insert("API_KEY".to_string(), "secret".to_string());
+        assert!(validate_env(&requirements, &env).is_ok());
+
+        env.insert("BAD=KEY".to_string(), "x".to_string());
+        assert!(matches!(
+            validate_env(&requirements, &env),
+            Err(EnvError::Invalid(_))
+        ));
+    }
+}
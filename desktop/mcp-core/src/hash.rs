@@ -21,6 +21,10 @@ pub fn canonicalize_json(value: &Value) -> Value {
     }
 }
 
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
 pub fn hash_json(value: &Value) -> Result<String, serde_json::Error> {
     let canonical = canonicalize_json(value);
     let serialized = serde_json::to_string(&canonical)?;
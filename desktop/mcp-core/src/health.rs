@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+/// Between two probes of a tool's health check, unless it sets its own.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(30);
+/// How long one probe may take before it counts as failed.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Failed probes in a row after which the tool is considered crashed.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// A status change a probe result calls for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeTransition {
+    /// The first probe to pass after one or more failures.
+    Recovered,
+    /// The first failure after passing probes.
+    Degraded,
+    /// Failures reached the threshold.
+    Crashed,
+}
+
+/// Counts consecutive probe failures of one running tool.
+#[derive(Debug, Clone)]
+pub struct ProbeTracker {
+    threshold: u32,
+    failures: u32,
+}
+
+impl ProbeTracker {
+    /// A `threshold` of 0 is treated as 1.
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            failures: 0,
+        }
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Records a probe result, returning a transition only when the tool's
+    /// status should change.
+    pub fn record(&mut self, passed: bool) -> Option<ProbeTransition> {
+        if passed {
+            let recovered = self.failures > 0;
+            self.failures = 0;
+            return recovered.then_some(ProbeTransition::Recovered);
+        }
+        self.failures += 1;
+        if self.failures == self.threshold {
+            Some(ProbeTransition::Crashed)
+        } else if self.failures == 1 {
+            Some(ProbeTransition::Degraded)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degrades_on_first_failure_and_crashes_at_threshold() {
+        let mut tracker = ProbeTracker::new(3);
+        assert_eq!(tracker.record(true), None);
+        assert_eq!(tracker.record(false), Some(ProbeTransition::Degraded));
+        assert_eq!(tracker.record(true), Some(ProbeTransition::Recovered));
+        assert_eq!(tracker.record(false), Some(ProbeTransition::Degraded));
+        assert_eq!(tracker.record(false), None);
+        assert_eq!(tracker.record(false), Some(ProbeTransition::Crashed));
+        assert_eq!(tracker.failures(), 3);
+
+        let mut strict = ProbeTracker::new(0);
+        assert_eq!(strict.record(false), Some(ProbeTransition::Crashed));
+    }
+}
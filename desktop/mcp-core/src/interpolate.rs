@@ -0,0 +1,322 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A tool's command line after `${VAR}` references were expanded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpolated {
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub cwd: Option<String>,
+    /// Built-in variables that were referenced, in order of first use.
+    pub builtins: Vec<(&'static str, String)>,
+    /// Referenced variables that were unset and had no default, in order of
+    /// first use. Their references are left in place.
+    pub unresolved: Vec<String>,
+}
+
+/// Expands the references in a tool's command, args, env values and working
+/// directory. `builtins` (see [`builtin_vars`]) take precedence; other names
+/// are looked up with `lookup`, the host environment at spawn time.
+///
+/// `${NAME}` is replaced with the variable's value and `${NAME:-default}`
+/// falls back to `default` when it is unset or empty. `$${` writes a literal
+/// `${`. Anything else, including `$NAME` without braces, is kept as is.
+pub fn interpolate_command(
+    command: &str,
+    args: &[String],
+    env: Option<&HashMap<String, String>>,
+    cwd: Option<&str>,
+    builtins: &HashMap<&'static str, String>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Interpolated {
+    let mut used: Vec<(&'static str, String)> = Vec::new();
+    let mut resolve = resolver(builtins, &mut used, lookup);
+    let mut unresolved = Vec::new();
+    let command = interpolate(command, &mut resolve, &mut unresolved);
+    let args = args
+        .iter()
+        .map(|arg| interpolate(arg, &mut resolve, &mut unresolved))
+        .collect();
+    let env = env
+        .into_iter()
+        .flatten()
+        .map(|(key, value)| {
+            (
+                key.clone(),
+                interpolate(value, &mut resolve, &mut unresolved),
+            )
+        })
+        .collect();
+    let cwd = cwd.map(|cwd| interpolate(cwd, &mut resolve, &mut unresolved));
+    drop(resolve);
+    Interpolated {
+        command,
+        args,
+        env,
+        cwd,
+        builtins: used,
+        unresolved,
+    }
+}
+
+/// Expands the references in a hook or health check command, which runs
+/// through `sh -c` (`cmd /C` on Windows). Every substituted value, defaults
+/// included, is quoted as a single shell word, so a path with spaces stays
+/// one argument and metacharacters in a value are not run. Unresolved
+/// references are left in place, as in [`interpolate_command`].
+pub fn interpolate_shell(
+    line: &str,
+    builtins: &HashMap<&'static str, String>,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Interpolated {
+    let mut used: Vec<(&'static str, String)> = Vec::new();
+    let mut unresolved = Vec::new();
+    let command = expand(
+        line,
+        resolver(builtins, &mut used, lookup),
+        &mut unresolved,
+        shell_quote,
+    );
+    Interpolated {
+        command,
+        args: Vec::new(),
+        env: HashMap::new(),
+        cwd: None,
+        builtins: used,
+        unresolved,
+    }
+}
+
+/// Quotes `value` as one word for the platform shell.
+#[cfg(not(windows))]
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quotes `value` as one word for the platform shell. `cmd` has no escape
+/// for `"` inside quotes, so those are dropped; paths cannot contain them.
+#[cfg(windows)]
+pub fn shell_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', ""))
+}
+
+/// Looks names up in `builtins` first, recording the ones used, then with
+/// `lookup`.
+fn resolver<'a>(
+    builtins: &'a HashMap<&'static str, String>,
+    used: &'a mut Vec<(&'static str, String)>,
+    lookup: impl Fn(&str) -> Option<String> + 'a,
+) -> impl FnMut(&str) -> Option<String> + 'a {
+    move |name: &str| match builtins.get_key_value(name) {
+        Some((&builtin, value)) => {
+            if !used.iter().any(|(known, _)| *known == builtin) {
+                used.push((builtin, value.clone()));
+            }
+            Some(value.clone())
+        }
+        None => lookup(name),
+    }
+}
+
+/// Variables every tool can reference so a shared config needs no absolute
+/// paths:
+///
+/// - `HOME`: the user's home directory.
+/// - `DATA_DIR`: where Deeting keeps its files.
+/// - `TOOL_DIR`: a directory of the tool's own under `DATA_DIR`, see
+///   [`tool_dir`].
+/// - `WORKSPACE`: the directory of the config file the tool was imported
+///   from, when it came from one.
+///
+/// A variable whose value is unknown is left out, so the host environment
+/// is consulted for it instead.
+pub fn builtin_vars(
+    home: Option<&Path>,
+    data_dir: &Path,
+    tool_id: &str,
+    workspace: Option<&Path>,
+) -> HashMap<&'static str, String> {
+    let mut vars = HashMap::new();
+    if let Some(home) = home {
+        vars.insert("HOME", home.display().to_string());
+    }
+    vars.insert("DATA_DIR", data_dir.display().to_string());
+    vars.insert(
+        "TOOL_DIR",
+        tool_dir(data_dir, tool_id).display().to_string(),
+    );
+    if let Some(workspace) = workspace {
+        vars.insert("WORKSPACE", workspace.display().to_string());
+    }
+    vars
+}
+
+/// `TOOL_DIR` for a tool. It is not created until a config references it.
+pub fn tool_dir(data_dir: &Path, tool_id: &str) -> PathBuf {
+    data_dir.join("tools").join(tool_id)
+}
+
+/// Expands the references in one value; see [`interpolate_command`].
+pub fn interpolate(
+    value: &str,
+    lookup: impl FnMut(&str) -> Option<String>,
+    unresolved: &mut Vec<String>,
+) -> String {
+    expand(value, lookup, unresolved, str::to_string)
+}
+
+/// [`interpolate`], passing each substituted value through `quote`.
+fn expand(
+    value: &str,
+    mut lookup: impl FnMut(&str) -> Option<String>,
+    unresolved: &mut Vec<String>,
+    quote: fn(&str) -> String,
+) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(escaped) = tail.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let reference = tail
+            .strip_prefix("${")
+            .and_then(|body| Some((body, body.find('}')?)));
+        let Some((body, end)) = reference else {
+            out.push('$');
+            rest = &tail[1..];
+            continue;
+        };
+        let inner = &body[..end];
+        let (name, default) = match inner.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (inner, None),
+        };
+        if !is_variable_name(name) {
+            out.push('$');
+            rest = &tail[1..];
+            continue;
+        }
+        match (lookup(name), default) {
+            (Some(value), _) if !value.is_empty() => out.push_str(&quote(&value)),
+            (_, Some(default)) => out.push_str(&quote(default)),
+            (Some(_), None) => {}
+            (None, None) => {
+                if !unresolved.iter().any(|known| known == name) {
+                    unresolved.push(name.to_string());
+                }
+                out.push_str(&tail[..end + 3]);
+            }
+        }
+        rest = &body[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(first) if first == '_' || first.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/dev".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_references_and_defaults() {
+        let mut unresolved = Vec::new();
+        let expand =
+            |value: &str, unresolved: &mut Vec<String>| interpolate(value, lookup, unresolved);
+        assert_eq!(expand("${HOME}/data", &mut unresolved), "/home/dev/data");
+        assert_eq!(expand("${PORT:-8080}", &mut unresolved), "8080");
+        assert_eq!(expand("${EMPTY:-x}|${EMPTY}", &mut unresolved), "x|");
+        assert_eq!(
+            expand("$${HOME} $HOME ${1X} $", &mut unresolved),
+            "${HOME} $HOME ${1X} $"
+        );
+        assert!(unresolved.is_empty());
+
+        assert_eq!(
+            expand("${TOKEN}-${TOKEN}", &mut unresolved),
+            "${TOKEN}-${TOKEN}"
+        );
+        assert_eq!(unresolved, vec!["TOKEN".to_string()]);
+    }
+
+    #[test]
+    fn collects_unresolved_across_command_args_and_env() {
+        let env = HashMap::from([("KEY".to_string(), "${API_KEY}".to_string())]);
+        let result = interpolate_command(
+            "${HOME}/bin/server",
+            &["--root".to_string(), "${ROOT}".to_string()],
+            Some(&env),
+            None,
+            &HashMap::new(),
+            lookup,
+        );
+        assert_eq!(result.command, "/home/dev/bin/server");
+        assert_eq!(result.args, vec!["--root", "${ROOT}"]);
+        assert_eq!(result.unresolved, vec!["ROOT", "API_KEY"]);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn shell_lines_quote_each_substituted_value() {
+        let builtins = HashMap::from([("WORKSPACE", "/my repos/it's; rm -rf ~".to_string())]);
+        let result = interpolate_shell(
+            "cd ${WORKSPACE} && npm ci --prefix ${PREFIX:-a b} ${TOKEN}",
+            &builtins,
+            lookup,
+        );
+        assert_eq!(
+            result.command,
+            r"cd '/my repos/it'\''s; rm -rf ~' && npm ci --prefix 'a b' ${TOKEN}"
+        );
+        assert_eq!(result.builtins[0].0, "WORKSPACE");
+        assert_eq!(result.unresolved, vec!["TOKEN"]);
+    }
+
+    #[test]
+    fn builtins_take_precedence_and_are_reported() {
+        let builtins = builtin_vars(Some(Path::new("/users/me")), Path::new("/data"), "t1", None);
+        let result = interpolate_command(
+            "server",
+            &[
+                "--cache=${TOOL_DIR}/cache".to_string(),
+                "${HOME}".to_string(),
+            ],
+            None,
+            Some("${WORKSPACE:-.}"),
+            &builtins,
+            lookup,
+        );
+        assert_eq!(
+            result.args,
+            vec![
+                format!(
+                    "--cache={}/cache",
+                    tool_dir(Path::new("/data"), "t1").display()
+                ),
+                "/users/me".to_string(),
+            ]
+        );
+        assert_eq!(result.builtins[1], ("HOME", "/users/me".to_string()));
+        assert_eq!(result.builtins[0].0, "TOOL_DIR");
+        assert!(result.unresolved.is_empty());
+        assert_eq!(result.cwd.as_deref(), Some("."));
+    }
+}
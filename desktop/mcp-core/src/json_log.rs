@@ -0,0 +1,109 @@
+use serde_json::{Map, Value};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+/// `level`, `msg` and `time` of a JSON log line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonLog {
+    /// Lowercase name; numeric pino levels are translated (`50` is `error`).
+    pub level: Option<String>,
+    pub msg: Option<String>,
+    /// RFC 3339, as logged by the tool rather than when it was captured.
+    pub time: Option<String>,
+}
+
+/// Reads `level`, `msg` and `time` from a line that is a JSON object, as
+/// written by pino, winston, bunyan and similar loggers. Lines that are not
+/// objects, or carry none of those fields, give `None`.
+pub fn detect(message: &str) -> Option<JsonLog> {
+    let trimmed = message.trim();
+    if !trimmed.starts_with('{') {
+        return None;
+    }
+    let Ok(Value::Object(object)) = serde_json::from_str::<Value>(trimmed) else {
+        return None;
+    };
+    let structured = JsonLog {
+        level: field(&object, &["level", "severity"]).and_then(level),
+        msg: field(&object, &["msg", "message"]).and_then(|msg| match msg {
+            Value::String(msg) => Some(msg.clone()),
+            _ => None,
+        }),
+        time: field(&object, &["time", "timestamp", "ts"]).and_then(time),
+    };
+    (structured.level.is_some() || structured.msg.is_some() || structured.time.is_some())
+        .then_some(structured)
+}
+
+fn field<'a>(object: &'a Map<String, Value>, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter().find_map(|key| object.get(*key))
+}
+
+/// Lowercase level names; pino/bunyan numbers map to their names.
+fn level(value: &Value) -> Option<String> {
+    match value {
+        Value::String(name) => Some(name.to_lowercase()),
+        Value::Number(number) => {
+            let name = match number.as_u64()? {
+                0..=10 => "trace",
+                11..=20 => "debug",
+                21..=30 => "info",
+                31..=40 => "warn",
+                41..=50 => "error",
+                _ => "fatal",
+            };
+            Some(name.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// RFC 3339; epoch numbers are read as milliseconds when they are too large
+/// to be seconds.
+fn time(value: &Value) -> Option<String> {
+    match value {
+        Value::String(time) => Some(time.clone()),
+        Value::Number(number) => {
+            let epoch = number.as_i64()?;
+            let nanos = if epoch.abs() >= 100_000_000_000 {
+                i128::from(epoch) * 1_000_000
+            } else {
+                i128::from(epoch) * 1_000_000_000
+            };
+            OffsetDateTime::from_unix_timestamp_nanos(nanos)
+                .ok()?
+                .format(&Rfc3339)
+                .ok()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_pino_and_winston_lines() {
+        let pino = detect(r#"{"level":50,"time":1767225600000,"pid":7,"msg":"token expired"}"#)
+            .unwrap();
+        assert_eq!(pino.level.as_deref(), Some("error"));
+        assert_eq!(pino.msg.as_deref(), Some("token expired"));
+        assert_eq!(pino.time.as_deref(), Some("2026-01-01T00:00:00Z"));
+
+        let winston =
+            detect(r#"{"level":"WARN","message":"slow","timestamp":"2026-01-01T00:00:00.5Z"}"#)
+                .unwrap();
+        assert_eq!(winston.level.as_deref(), Some("warn"));
+        assert_eq!(winston.msg.as_deref(), Some("slow"));
+        assert_eq!(winston.time.as_deref(), Some("2026-01-01T00:00:00.5Z"));
+    }
+
+    #[test]
+    fn ignores_plain_and_unrelated_json_lines() {
+        assert!(detect("listening on 3000").is_none());
+        assert!(detect(r#"{"jsonrpc":"2.0","id":1}"#).is_none());
+        assert!(detect(r#"["level","info"]"#).is_none());
+        assert!(detect("{not json").is_none());
+    }
+}
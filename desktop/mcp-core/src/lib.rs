@@ -0,0 +1,35 @@
+//! Pieces shared by the desktop backend and the Tauri app: config hashing,
+//! the env merge applied on sync, `${VAR}` interpolation at spawn time
+//! (shell-quoted for hooks), health check failure counting, the tool list
+//! cache, the log line pipeline (ANSI handling, severity guessing, JSON log
+//! detection, repeat coalescing, secret redaction and filtering), `env_config`
+//! parsing and env requirement checks, and the SQLite pool.
+//!
+//! The store, process manager and wire types (`McpTool`, `McpLogEntry`, ...)
+//! stay in each app: the schemas and event transports differ, and the Tauri
+//! app derives its TypeScript bindings with ts-rs, which has to sit on the
+//! defining crate. The apps' `log_level` and `log_filter` modules only map
+//! those types onto the ones here.
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+pub mod ansi;
+pub mod env;
+pub mod hash;
+pub mod health;
+pub mod interpolate;
+pub mod json_log;
+pub mod log_dedup;
+pub mod log_filter;
+pub mod log_level;
+pub mod log_redact;
+pub mod merge;
+pub mod pool;
+pub mod tool_cache;
+
+/// Compiles `pattern` into `cell` on first use.
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("valid regex"))
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_LOG_DEDUP_WINDOW: Duration = Duration::from_secs(1);
+
+struct Repeat {
+    message: String,
+    since: Instant,
+    suppressed: u64,
+}
+
+/// What `emit_log` does with a line.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Log it, after a "repeated N times" note for the line it replaces when
+    /// `repeated` is set.
+    Emit { repeated: Option<u64> },
+    /// Same as the previous line of that tool and stream, within the window.
+    Suppress,
+}
+
+/// Coalesces a tool's identical consecutive lines: after one is logged,
+/// repeats within `window` are only counted, and the count is reported when
+/// a different line arrives or the window has passed. A tool spamming one
+/// line thus logs it about once per window. Lines are keyed by tool and
+/// stream `S`.
+pub struct LineDedup<S> {
+    window: Duration,
+    last: HashMap<(String, S), Repeat>,
+}
+
+impl<S: Hash + Eq + Clone> LineDedup<S> {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last: HashMap::new(),
+        }
+    }
+
+    /// A zero window turns coalescing off.
+    pub fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    pub fn check(
+        &mut self,
+        tool_id: &str,
+        stream: &S,
+        message: &str,
+        now: Instant,
+    ) -> Verdict {
+        if self.window.is_zero() {
+            return Verdict::Emit { repeated: None };
+        }
+        let key = (tool_id.to_string(), stream.clone());
+        if let Some(last) = self.last.get_mut(&key) {
+            if last.message == message && now.duration_since(last.since) < self.window {
+                last.suppressed += 1;
+                return Verdict::Suppress;
+            }
+        }
+        let previous = self.last.insert(
+            key,
+            Repeat {
+                message: message.to_string(),
+                since: now,
+                suppressed: 0,
+            },
+        );
+        Verdict::Emit {
+            repeated: previous
+                .map(|previous| previous.suppressed)
+                .filter(|suppressed| *suppressed > 0),
+        }
+    }
+
+    /// Forgets the tool's lines, returning the repeat counts not reported
+    /// yet, e.g. when its process exits.
+    pub fn take(&mut self, tool_id: &str) -> Vec<(S, u64)> {
+        let keys: Vec<_> = self
+            .last
+            .keys()
+            .filter(|(id, _)| id == tool_id)
+            .cloned()
+            .collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                let repeat = self.last.remove(&key)?;
+                (repeat.suppressed > 0).then_some((key.1, repeat.suppressed))
+            })
+            .collect()
+    }
+}
+
+pub fn repeated_message(count: u64) -> String {
+    format!("last message repeated {count} times")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coalesces_repeats_within_the_window() {
+        let start = Instant::now();
+        let mut dedup = LineDedup::new(Duration::from_secs(1));
+        let stderr = "stderr";
+        let at = |millis| start + Duration::from_millis(millis);
+
+        assert_eq!(
+            dedup.check("a", &stderr, "boom", at(0)),
+            Verdict::Emit { repeated: None }
+        );
+        assert_eq!(dedup.check("a", &stderr, "boom", at(10)), Verdict::Suppress);
+        assert_eq!(dedup.check("a", &stderr, "boom", at(20)), Verdict::Suppress);
+        assert_eq!(
+            dedup.check("b", &stderr, "boom", at(20)),
+            Verdict::Emit { repeated: None }
+        );
+        assert_eq!(
+            dedup.check("a", &stderr, "boom", at(1000)),
+            Verdict::Emit { repeated: Some(2) }
+        );
+        assert_eq!(
+            dedup.check("a", &stderr, "boom", at(1001)),
+            Verdict::Suppress
+        );
+        assert_eq!(
+            dedup.check("a", &stderr, "other", at(1002)),
+            Verdict::Emit { repeated: Some(1) }
+        );
+        assert_eq!(
+            dedup.check("a", &stderr, "other", at(1003)),
+            Verdict::Suppress
+        );
+        assert_eq!(dedup.take("a"), vec![("stderr", 1)]);
+        assert!(dedup.take("a").is_empty());
+
+        dedup.set_window(Duration::ZERO);
+        assert_eq!(
+            dedup.check("b", &stderr, "boom", at(30)),
+            Verdict::Emit { repeated: None }
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use regex::Regex;
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, UtcOffset};
+
+/// The text and time parts of a log filter: a case-insensitive substring, a
+/// regex, and a lower bound on the timestamp. Blank parts are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct TextFilter {
+    contains: Option<String>,
+    regex: Option<Regex>,
+    since: Option<OffsetDateTime>,
+}
+
+impl TextFilter {
+    pub fn new(
+        contains: Option<&str>,
+        regex: Option<&str>,
+        since: Option<&str>,
+    ) -> Result<Self, String> {
+        let regex = match regex.filter(|pattern| !pattern.is_empty()) {
+            Some(pattern) => {
+                Some(Regex::new(pattern).map_err(|err| format!("invalid regex: {err}"))?)
+            }
+            None => None,
+        };
+        let since = match since.filter(|since| !since.is_empty()) {
+            Some(since) => Some(parse_since(since)?),
+            None => None,
+        };
+        Ok(Self {
+            contains: contains
+                .filter(|needle| !needle.is_empty())
+                .map(str::to_lowercase),
+            regex,
+            since,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contains.is_none() && self.regex.is_none() && self.since.is_none()
+    }
+
+    /// Lines whose timestamp does not parse never match a `since` bound.
+    pub fn matches(&self, message: &str, timestamp: &str) -> bool {
+        if let Some(since) = self.since {
+            match OffsetDateTime::parse(timestamp, &Rfc3339) {
+                Ok(timestamp) if timestamp >= since => {}
+                _ => return false,
+            }
+        }
+        if let Some(needle) = &self.contains {
+            if !message.to_lowercase().contains(needle) {
+                return false;
+            }
+        }
+        self.regex
+            .as_ref()
+            .is_none_or(|regex| regex.is_match(message))
+    }
+}
+
+/// Normalizes an RFC 3339 timestamp to UTC, the form stored log lines use,
+/// so the two compare as strings.
+pub fn utc_timestamp(value: &str) -> Result<String, String> {
+    parse_since(value)?
+        .to_offset(UtcOffset::UTC)
+        .format(&Rfc3339)
+        .map_err(|err| err.to_string())
+}
+
+fn parse_since(value: &str) -> Result<OffsetDateTime, String> {
+    OffsetDateTime::parse(value, &Rfc3339)
+        .map_err(|err| format!("since must be an RFC 3339 timestamp: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combines_text_regex_and_time() {
+        let filter = TextFilter::new(Some("Error"), Some(r"retry \d"), Some("2026-01-01T00:00:01Z"))
+            .unwrap();
+        assert!(filter.matches("error: retry 2", "2026-01-01T00:00:02Z"));
+        assert!(!filter.matches("error: retry 2", "2026-01-01T00:00:00Z"));
+        assert!(!filter.matches("error: retry 2", "yesterday"));
+        assert!(!filter.matches("ERROR: token expired", "2026-01-01T00:00:02Z"));
+        assert!(!filter.matches("retry 3", "2026-01-01T00:00:02Z"));
+
+        let empty = TextFilter::new(Some(""), None, Some("")).unwrap();
+        assert!(empty.is_empty());
+        assert!(empty.matches("anything", "not a timestamp"));
+    }
+
+    #[test]
+    fn rejects_invalid_regex_and_timestamp() {
+        assert!(TextFilter::new(None, Some("("), None).is_err());
+        assert!(TextFilter::new(None, None, Some("yesterday")).is_err());
+        assert_eq!(
+            utc_timestamp("2026-01-01T08:00:00+08:00").unwrap(),
+            "2026-01-01T00:00:00Z"
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+
+use crate::regex;
+
+/// Error lines within `DEGRADED_WINDOW` that mark a running tool degraded.
+pub const DEGRADED_ERROR_COUNT: usize = 5;
+pub const DEGRADED_WINDOW: Duration = Duration::from_secs(60);
+
+/// A log line's severity, as guessed by [`infer`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    #[default]
+    Info,
+    Warn,
+    Error,
+}
+
+/// Guesses a line's severity. A JSON log's own `level` wins; JSON lines
+/// without one (e.g. JSON-RPC traffic on stdout) are `info`. Other lines are
+/// matched against error and warning words, and lifecycle events that report
+/// a non-zero exit are errors.
+pub fn infer(is_event: bool, message: &str, json_level: Option<&str>) -> Level {
+    if let Some(level) = json_level {
+        return match level {
+            "fatal" | "error" | "critical" | "crit" | "alert" | "emerg" => Level::Error,
+            "warn" | "warning" => Level::Warn,
+            _ => Level::Info,
+        };
+    }
+    static ERROR_WORDS: OnceLock<Regex> = OnceLock::new();
+    static WARN_WORDS: OnceLock<Regex> = OnceLock::new();
+    static EXIT_CODE: OnceLock<Regex> = OnceLock::new();
+
+    if is_event {
+        let failed_exit = regex(&EXIT_CODE, r"exited with code (-?\d+)")
+            .captures(message)
+            .is_some_and(|captures| &captures[1] != "0");
+        if failed_exit {
+            return Level::Error;
+        }
+    } else if message.trim_start().starts_with('{') {
+        return Level::Info;
+    }
+    let error_words = regex(
+        &ERROR_WORDS,
+        r"(?i)\b(error|err|fatal|panic|panicked|exception|traceback|critical|failed)\b",
+    );
+    if error_words.is_match(message) {
+        Level::Error
+    } else if regex(&WARN_WORDS, r"(?i)\b(warn|warning|deprecated)\b").is_match(message) {
+        Level::Warn
+    } else {
+        Level::Info
+    }
+}
+
+/// Error lines a running tool logged within `DEGRADED_WINDOW`.
+#[derive(Debug, Default)]
+pub struct ErrorRate {
+    errors: VecDeque<Instant>,
+    degraded: bool,
+}
+
+impl ErrorRate {
+    /// Counts a line, an error line when `is_error`; returns the new state
+    /// when the tool crosses into or out of degraded.
+    pub fn record(&mut self, is_error: bool, now: Instant) -> Option<bool> {
+        if is_error {
+            self.errors.push_back(now);
+        }
+        while self
+            .errors
+            .front()
+            .is_some_and(|first| now.duration_since(*first) > DEGRADED_WINDOW)
+        {
+            self.errors.pop_front();
+        }
+        let degraded = self.errors.len() >= DEGRADED_ERROR_COUNT;
+        (degraded != self.degraded).then(|| {
+            self.degraded = degraded;
+            degraded
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_words_json_levels_and_exit_codes() {
+        assert_eq!(infer(false, "Error: token expired", None), Level::Error);
+        assert_eq!(infer(false, "[warn] slow response", None), Level::Warn);
+        assert_eq!(infer(false, "listening on 3000", None), Level::Info);
+        assert_eq!(infer(false, "terrorist-free zone", None), Level::Info);
+        assert_eq!(
+            infer(false, r#"{"level":40,"msg":"an error is coming"}"#, Some("warn")),
+            Level::Warn
+        );
+        assert_eq!(
+            infer(false, r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32601}}"#, None),
+            Level::Info
+        );
+        assert_eq!(infer(true, "process exited with code 1", None), Level::Error);
+        assert_eq!(infer(true, "process exited with code 0", None), Level::Info);
+    }
+
+    #[test]
+    fn degrades_on_an_error_burst_and_recovers_after_the_window() {
+        let start = Instant::now();
+        let mut rate = ErrorRate::default();
+        for second in 0..DEGRADED_ERROR_COUNT as u64 - 1 {
+            let now = start + Duration::from_secs(second);
+            assert_eq!(rate.record(true, now), None);
+        }
+        assert_eq!(rate.record(false, start), None);
+        let burst = start + Duration::from_secs(10);
+        assert_eq!(rate.record(true, burst), Some(true));
+        assert_eq!(rate.record(true, burst), None);
+        let later = burst + DEGRADED_WINDOW;
+        assert_eq!(rate.record(false, later), Some(false));
+    }
+}
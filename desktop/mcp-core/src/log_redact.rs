@@ -0,0 +1,114 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+use crate::env::is_secret_env_key;
+use crate::regex;
+
+const REDACTED: &str = "[redacted]";
+
+/// Credentials with a recognisable shape, masked even when they did not come
+/// from the tool's env: bearer tokens, `sk-…` API keys, GitHub, Slack and AWS
+/// access keys.
+fn key_patterns() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    regex(
+        &CELL,
+        r"(?i)\bbearer\s+[a-z0-9._~+/=-]{8,}|\bsk-[a-z0-9_-]{16,}|\bgh[pousr]_[a-z0-9]{20,}|\bxox[abprs]-[a-z0-9-]{10,}|\bAKIA[0-9A-Z]{16}\b",
+    )
+}
+
+/// `API_KEY=…` / `"token": "…"` style assignments to secret-looking names.
+fn key_assignments() -> &'static Regex {
+    static CELL: OnceLock<Regex> = OnceLock::new();
+    regex(
+        &CELL,
+        r#"(?i)\b([a-z0-9_]*(?:key|token|secret|password|passwd)[a-z0-9_]*"?\s*[=:]\s*"?)[^\s",}]+"#,
+    )
+}
+
+/// Masks a tool's secret env values, and credentials matching
+/// [`key_patterns`] or [`key_assignments`], in its log lines.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    /// Longest first, so a secret containing another is masked whole.
+    values: Vec<String>,
+}
+
+impl Redactor {
+    /// Values shorter than 4 characters are left alone; masking them would
+    /// mangle unrelated text.
+    pub fn for_env(env: Option<&HashMap<String, String>>) -> Self {
+        let mut values: Vec<String> = env
+            .into_iter()
+            .flatten()
+            .filter(|(key, value)| is_secret_env_key(key) && value.len() >= 4)
+            .map(|(_, value)| value.clone())
+            .collect();
+        values.sort();
+        values.dedup();
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+        Self { values }
+    }
+
+    pub fn apply(&self, line: String) -> String {
+        let line = self.values.iter().fold(line, |line, value| {
+            if line.contains(value.as_str()) {
+                line.replace(value.as_str(), REDACTED)
+            } else {
+                line
+            }
+        });
+        let line = replace_all(key_patterns(), line, REDACTED);
+        replace_all(key_assignments(), line, "${1}[redacted]")
+    }
+}
+
+/// Only allocates when something matched.
+fn replace_all(regex: &Regex, line: String, with: &str) -> String {
+    match regex.replace_all(&line, with) {
+        Cow::Borrowed(_) => line,
+        Cow::Owned(masked) => masked,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_env_secrets_and_key_patterns() {
+        let mut env = HashMap::new();
+        env.insert("API_KEY".to_string(), "abcd-1234-efgh".to_string());
+        env.insert("REGION".to_string(), "us-east-1".to_string());
+        env.insert("PIN_TOKEN".to_string(), "42".to_string());
+        let redactor = Redactor::for_env(Some(&env));
+
+        assert_eq!(
+            redactor.apply("using abcd-1234-efgh in us-east-1".to_string()),
+            "using [redacted] in us-east-1"
+        );
+        assert_eq!(
+            redactor.apply("Authorization: Bearer eyJhbGciOi.J9x".to_string()),
+            "Authorization: [redacted]"
+        );
+        assert_eq!(
+            redactor.apply("key sk-proj-0123456789abcdefXYZ rejected".to_string()),
+            "key [redacted] rejected"
+        );
+        assert_eq!(
+            redactor.apply(r#"{"github_token": "hunter22", "page": 42}"#.to_string()),
+            r#"{"github_token": "[redacted]", "page": 42}"#
+        );
+        assert_eq!(
+            redactor.apply("OPENAI_API_KEY=foo started".to_string()),
+            "OPENAI_API_KEY=[redacted] started"
+        );
+        assert_eq!(
+            redactor.apply("listening on 42, keys loaded".to_string()),
+            "listening on 42, keys loaded"
+        );
+    }
+}
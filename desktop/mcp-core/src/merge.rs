@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+/// Three-way merge of env values: `base` is the env recorded in the currently
+/// applied config, `local` is what the user has set on the tool, and `remote`
+/// is the env carried by the incoming config.
+///
+/// A key the user changed (or that the incoming config no longer carries)
+/// keeps its local value; untouched keys follow the incoming config.
+pub fn merge_env(
+    base: &HashMap<String, String>,
+    local: &HashMap<String, String>,
+    remote: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let mut merged = remote.clone();
+    for (key, value) in local {
+        if value.is_empty() {
+            continue;
+        }
+        let changed_locally = base.get(key) != Some(value);
+        if changed_locally || !remote.contains_key(key) {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    merged
+}
+
+/// Applies `incoming` on top of the current tool config while keeping the
+/// user's env overrides, returning the merged config value.
+pub fn merge_config(
+    current_config: &Value,
+    local_env: Option<&HashMap<String, String>>,
+    incoming: &Value,
+) -> Value {
+    let base_env = env_from_config(current_config);
+    let remote_env = env_from_config(incoming);
+    let local_env = local_env.cloned().unwrap_or_default();
+    let merged_env = merge_env(&base_env, &local_env, &remote_env);
+
+    let mut merged = match incoming {
+        Value::Object(map) => map.clone(),
+        _ => Map::new(),
+    };
+    if merged_env.is_empty() {
+        merged.remove("env");
+    } else {
+        let env_map = merged_env
+            .into_iter()
+            .map(|(key, value)| (key, Value::String(value)))
+            .collect();
+        merged.insert("env".to_string(), Value::Object(env_map));
+    }
+    Value::Object(merged)
+}
+
+fn env_from_config(config: &Value) -> HashMap<String, String> {
+    config
+        .get("env")
+        .and_then(|env| env.as_object())
+        .map(|env| {
+            env.iter()
+                .filter_map(|(key, value)| value.as_str().map(|v| (key.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn env(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_local_overrides_and_takes_remote_changes() {
+        let base = env(&[("API_KEY", ""), ("REGION", "us")]);
+        let local = env(&[("API_KEY", "secret"), ("REGION", "us")]);
+        let remote = env(&[("API_KEY", ""), ("REGION", "eu")]);
+        let merged = merge_env(&base, &local, &remote);
+        assert_eq!(merged["API_KEY"], "secret");
+        assert_eq!(merged["REGION"], "eu");
+    }
+
+    #[test]
+    fn merge_config_is_stable_when_remote_has_no_env() {
+        let incoming = json!({"name": "alpha", "command": "npx"});
+        let local = env(&[("API_KEY", "secret")]);
+        let first = merge_config(&json!({"name": "alpha"}), Some(&local), &incoming);
+        let second = merge_config(&first, Some(&local), &incoming);
+        assert_eq!(first, second);
+        assert_eq!(first["env"]["API_KEY"], "secret");
+    }
+}
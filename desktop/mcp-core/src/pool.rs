@@ -0,0 +1,76 @@
+#[cfg(feature = "sqlite")]
+use std::str::FromStr;
+use std::time::Duration;
+
+#[cfg(feature = "sqlite")]
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+#[cfg(feature = "sqlite")]
+use sqlx::SqlitePool;
+
+const MAX_CONNECTIONS: &str = "DESKTOP_DB_MAX_CONNECTIONS";
+const ACQUIRE_TIMEOUT_MS: &str = "DESKTOP_DB_ACQUIRE_TIMEOUT_MS";
+const STATEMENT_CACHE: &str = "DESKTOP_DB_STATEMENT_CACHE";
+
+/// Connection pool tuning, read once at startup. Each app picks its own
+/// defaults; the environment overrides them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolSettings {
+    /// `DESKTOP_DB_MAX_CONNECTIONS`: connections for writes and short reads.
+    pub max_connections: u32,
+    /// `DESKTOP_DB_ACQUIRE_TIMEOUT_MS`: how long a query waits for a free
+    /// connection before failing.
+    pub acquire_timeout: Duration,
+    /// `DESKTOP_DB_STATEMENT_CACHE`: prepared statements kept per connection.
+    pub statement_cache: usize,
+}
+
+impl PoolSettings {
+    /// `defaults` with the environment applied. `invalid` is called with the
+    /// name and value of each variable that did not parse and was ignored.
+    pub fn from_env(defaults: Self, mut invalid: impl FnMut(&str, &str)) -> Self {
+        let mut number = |key: &str| -> Option<u64> {
+            let value = std::env::var(key).ok()?;
+            let parsed = value.trim().parse().ok();
+            if parsed.is_none() {
+                invalid(key, &value);
+            }
+            parsed
+        };
+        Self {
+            max_connections: number(MAX_CONNECTIONS)
+                .and_then(|max| u32::try_from(max).ok())
+                .filter(|max| *max > 0)
+                .unwrap_or(defaults.max_connections),
+            acquire_timeout: number(ACQUIRE_TIMEOUT_MS)
+                .filter(|millis| *millis > 0)
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.acquire_timeout),
+            statement_cache: number(STATEMENT_CACHE)
+                .and_then(|size| usize::try_from(size).ok())
+                .unwrap_or(defaults.statement_cache),
+        }
+    }
+}
+
+/// The main pool, and a single-connection pool for long reads so they queue
+/// behind each other instead of holding connections writes are waiting for.
+/// Both open the same database, `sqlite::memory:` included.
+#[cfg(feature = "sqlite")]
+pub async fn connect(
+    settings: &PoolSettings,
+    database_url: &str,
+) -> Result<(SqlitePool, SqlitePool), sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(database_url)?
+        .statement_cache_capacity(settings.statement_cache);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(settings.max_connections)
+        .acquire_timeout(settings.acquire_timeout)
+        .connect_with(options.clone())
+        .await?;
+    let read_pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(settings.acquire_timeout)
+        .connect_with(options)
+        .await?;
+    Ok((pool, read_pool))
+}
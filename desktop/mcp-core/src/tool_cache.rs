@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// All tools in `created_at` order, kept between reads of the tool list.
+/// Every store write to `mcp_tools` calls [`ToolCache::invalidate`]. `T` is
+/// the tool row type of the binary using it.
+///
+/// A read that misses loads the list from SQLite and offers it back with the
+/// generation it saw before loading; a write that lands in between bumps the
+/// generation, so the possibly stale list is dropped instead of cached.
+pub struct ToolCache<T> {
+    generation: AtomicU64,
+    tools: RwLock<Option<Arc<Vec<T>>>>,
+}
+
+impl<T> Default for ToolCache<T> {
+    fn default() -> Self {
+        Self {
+            generation: AtomicU64::new(0),
+            tools: RwLock::new(None),
+        }
+    }
+}
+
+impl<T> ToolCache<T> {
+    pub fn get(&self) -> Option<Arc<Vec<T>>> {
+        self.tools.read().expect("tool cache poisoned").clone()
+    }
+
+    /// Read before loading the list that is passed to [`ToolCache::fill`].
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    pub fn fill(&self, generation: u64, tools: Vec<T>) -> Arc<Vec<T>> {
+        let tools = Arc::new(tools);
+        let mut cached = self.tools.write().expect("tool cache poisoned");
+        if self.generation.load(Ordering::Acquire) == generation {
+            *cached = Some(tools.clone());
+        }
+        tools
+    }
+
+    pub fn invalidate(&self) {
+        let mut cached = self.tools.write().expect("tool cache poisoned");
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        *cached = None;
+    }
+}